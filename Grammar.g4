@@ -46,7 +46,7 @@ definition
     ;
 
 id: ID | '_' | INFIX_ID;
-operator_symbol_without_eq_dot: ('&' | '|' | '!' | '+' | '-' | '^' | '$' | '<' | '>' | '@' | ':' | '*' | '%' | '/');
+operator_symbol_without_eq_dot: ('&' | '|' | '!' | '+' | '-' | '^' | '$' | '<' | '>' | '@' | ':' | '*' | '%' | '/' | '~');
 operator_symbol: operator_symbol_without_eq_dot | '=';
 operator: (operator_symbol)+ operator_symbol | operator_symbol_without_eq_dot | '.' '.' +;
 operator_id: '__' operator '__';
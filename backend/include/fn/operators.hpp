@@ -2,13 +2,22 @@
 
 #include "fn/fn_gen.tpp"
 #include "fn/fn_inst.tpp"
+#include "lazy/fns.hpp"
 #include "lazy/types.hpp"
 #include "system/work_manager.tpp"
 #include "types/builtin.hpp"
 #include "types/utils.hpp"
 
+#include <algorithm>
 #include <compare>
+#include <cstdlib>
+#include <iostream>
+#include <limits>
 #include <memory>
+#include <stdexcept>
+#include <type_traits>
+#include <utility>
+#include <vector>
 
 // Macros to turn functions into function generators.
 #define Binary_Int_Int_Int_Op__BuiltIn(fn, size)                               \
@@ -115,6 +124,164 @@
     FnT<Bool, Int, Int> fn##_G =                                               \
         std::make_shared<TypedClosureG<Empty, Bool, Int, Int>>(fn##_I::init);
 
+#define Binary_Op__BuiltIn(fn, R, X, Y, size)                                  \
+    class fn##_I : public TypedFnI<R, X, Y> {                                  \
+      protected:                                                               \
+        LazyT<R> body(LazyT<X> &x, LazyT<Y> &y) override {                     \
+            WorkManager::enqueue(x);                                           \
+            WorkManager::enqueue(y);                                           \
+            WorkManager::await(x, y);                                          \
+            return make_lazy<R>(fn(x->value(), y->value()));                   \
+        }                                                                      \
+                                                                               \
+      public:                                                                  \
+        using TypedFnI<R, X, Y>::TypedFnI;                                     \
+        static std::unique_ptr<TypedFnI<R, X, Y>> init(const ArgsT &args) {    \
+            return std::make_unique<fn##_I>(args);                             \
+        }                                                                      \
+        constexpr std::size_t lower_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr std::size_t upper_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr bool is_recursive() const override { return false; };        \
+    };                                                                         \
+    FnT<R, X, Y> fn##_G =                                                      \
+        std::make_shared<TypedClosureG<Empty, R, X, Y>>(fn##_I::init);
+
+#define Ternary_Op__BuiltIn(fn, R, X, Y, Z, size)                              \
+    class fn##_I : public TypedFnI<R, X, Y, Z> {                               \
+      protected:                                                               \
+        LazyT<R> body(LazyT<X> &x, LazyT<Y> &y, LazyT<Z> &z) override {        \
+            WorkManager::enqueue(x);                                           \
+            WorkManager::enqueue(y);                                           \
+            WorkManager::enqueue(z);                                           \
+            WorkManager::await(x, y, z);                                       \
+            return make_lazy<R>(fn(x->value(), y->value(), z->value()));       \
+        }                                                                      \
+                                                                               \
+      public:                                                                  \
+        using TypedFnI<R, X, Y, Z>::TypedFnI;                                  \
+        static std::unique_ptr<TypedFnI<R, X, Y, Z>> init(const ArgsT &args) { \
+            return std::make_unique<fn##_I>(args);                             \
+        }                                                                      \
+        constexpr std::size_t lower_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr std::size_t upper_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr bool is_recursive() const override { return false; };        \
+    };                                                                         \
+    FnT<R, X, Y, Z> fn##_G =                                                   \
+        std::make_shared<TypedClosureG<Empty, R, X, Y, Z>>(fn##_I::init);
+
+#define Unary_Op__BuiltIn(fn, R, X, size)                                      \
+    class fn##_I : public TypedFnI<R, X> {                                     \
+      protected:                                                               \
+        LazyT<R> body(LazyT<X> &x) override {                                  \
+            WorkManager::enqueue(x);                                           \
+            WorkManager::await(x);                                             \
+            return make_lazy<R>(fn(x->value()));                               \
+        }                                                                      \
+                                                                               \
+      public:                                                                  \
+        using TypedFnI<R, X>::TypedFnI;                                        \
+        static std::unique_ptr<TypedFnI<R, X>> init(const ArgsT &args) {       \
+            return std::make_unique<fn##_I>(args);                             \
+        }                                                                      \
+        constexpr std::size_t lower_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr std::size_t upper_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr bool is_recursive() const override { return false; };        \
+    };                                                                         \
+    FnT<R, X> fn##_G =                                                         \
+        std::make_shared<TypedClosureG<Empty, R, X>>(fn##_I::init);
+
+// Macros to turn functions templated by an element type into function
+// generators.
+#define Generic_Nullary_Op__BuiltIn(fn, R, size)                               \
+    template <typename T> class fn##_I : public TypedFnI<R> {                  \
+      protected:                                                               \
+        LazyT<R> body() override { return make_lazy<R>(fn<T>()); }             \
+                                                                               \
+      public:                                                                  \
+        using typename TypedFnI<R>::ArgsT;                                     \
+        using TypedFnI<R>::TypedFnI;                                           \
+        static std::unique_ptr<TypedFnI<R>> init(const ArgsT &args) {          \
+            return std::make_unique<fn##_I>(args);                             \
+        }                                                                      \
+        constexpr std::size_t lower_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr std::size_t upper_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr bool is_recursive() const override { return false; };        \
+    };                                                                         \
+    template <typename T>                                                      \
+    FnT<R> fn##_G =                                                            \
+        std::make_shared<TypedClosureG<Empty, R>>(fn##_I<T>::init);
+
+#define Generic_Unary_Op__BuiltIn(fn, R, X, size)                              \
+    template <typename T> class fn##_I : public TypedFnI<R, X> {               \
+      protected:                                                               \
+        LazyT<R> body(LazyT<X> &x) override {                                  \
+            WorkManager::enqueue(x);                                           \
+            WorkManager::await(x);                                             \
+            return make_lazy<R>(fn<T>(x->value()));                            \
+        }                                                                      \
+                                                                               \
+      public:                                                                  \
+        using typename TypedFnI<R, X>::ArgsT;                                  \
+        using TypedFnI<R, X>::TypedFnI;                                        \
+        static std::unique_ptr<TypedFnI<R, X>> init(const ArgsT &args) {       \
+            return std::make_unique<fn##_I>(args);                             \
+        }                                                                      \
+        constexpr std::size_t lower_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr std::size_t upper_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr bool is_recursive() const override { return false; };        \
+    };                                                                         \
+    template <typename T>                                                      \
+    FnT<R, X> fn##_G =                                                         \
+        std::make_shared<TypedClosureG<Empty, R, X>>(fn##_I<T>::init);
+
+#define Generic_Binary_Op__BuiltIn(fn, R, X, Y, size)                          \
+    template <typename T> class fn##_I : public TypedFnI<R, X, Y> {            \
+      protected:                                                               \
+        LazyT<R> body(LazyT<X> &x, LazyT<Y> &y) override {                     \
+            WorkManager::enqueue(x);                                           \
+            WorkManager::enqueue(y);                                           \
+            WorkManager::await(x, y);                                          \
+            return make_lazy<R>(fn<T>(x->value(), y->value()));                \
+        }                                                                      \
+                                                                               \
+      public:                                                                  \
+        using typename TypedFnI<R, X, Y>::ArgsT;                               \
+        using TypedFnI<R, X, Y>::TypedFnI;                                     \
+        static std::unique_ptr<TypedFnI<R, X, Y>> init(const ArgsT &args) {    \
+            return std::make_unique<fn##_I>(args);                             \
+        }                                                                      \
+        constexpr std::size_t lower_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr std::size_t upper_size_bound() const override {              \
+            return size;                                                       \
+        };                                                                     \
+        constexpr bool is_recursive() const override { return false; };        \
+    };                                                                         \
+    template <typename T>                                                      \
+    FnT<R, X, Y> fn##_G =                                                      \
+        std::make_shared<TypedClosureG<Empty, R, X, Y>>(fn##_I<T>::init);
+
 // Operator definitions.
 Int Plus__BuiltIn(Int x, Int y) { return x + y; }
 
@@ -141,6 +308,10 @@ Int Modulo__BuiltIn(Int x, Int y) { return x % y; }
 
 Int Right_Shift__BuiltIn(Int x, Int y) { return x >> y; }
 
+Int Logical_Right_Shift__BuiltIn(Int x, Int y) {
+    return static_cast<Int>(static_cast<std::make_unsigned_t<Int>>(x) >> y);
+}
+
 Int Left_Shift__BuiltIn(Int x, Int y) { return x << y; }
 
 Int Spaceship__BuiltIn(Int x, Int y) {
@@ -158,12 +329,111 @@ Int Bitwise_Or__BuiltIn(Int x, Int y) { return x | y; }
 
 Int Bitwise_Xor__BuiltIn(Int x, Int y) { return x ^ y; }
 
+Int Bitwise_Not__BuiltIn(Int x) { return ~x; }
+
+Int Negative__BuiltIn(Int x) { return -x; }
+
 Int Increment__BuiltIn(Int x) { return x + 1; }
 
 Int Decrement__BuiltIn(Int x) { return x - 1; }
 
+Int Minimum__BuiltIn(Int x, Int y) { return std::min(x, y); }
+
+Int Maximum__BuiltIn(Int x, Int y) { return std::max(x, y); }
+
+Int Absolute__BuiltIn(Int x) { return x < 0 ? -x : x; }
+
+Int Fused_Multiply_Add__BuiltIn(Int x, Int y, Int z) { return x * y + z; }
+
+Int Saturating_Plus__BuiltIn(Int x, Int y) {
+    Int res;
+    if (__builtin_add_overflow(x, y, &res))
+        return y < 0 ? std::numeric_limits<Int>::min()
+                     : std::numeric_limits<Int>::max();
+    return res;
+}
+
+Int Saturating_Minus__BuiltIn(Int x, Int y) {
+    Int res;
+    if (__builtin_sub_overflow(x, y, &res))
+        return y < 0 ? std::numeric_limits<Int>::max()
+                     : std::numeric_limits<Int>::min();
+    return res;
+}
+
+Int Saturating_Multiply__BuiltIn(Int x, Int y) {
+    Int res;
+    if (__builtin_mul_overflow(x, y, &res))
+        return (x < 0) != (y < 0) ? std::numeric_limits<Int>::min()
+                                  : std::numeric_limits<Int>::max();
+    return res;
+}
+
+// The overflow builtins store the wrapped result.
+Int Wrapping_Plus__BuiltIn(Int x, Int y) {
+    Int res;
+    __builtin_add_overflow(x, y, &res);
+    return res;
+}
+
+Int Wrapping_Minus__BuiltIn(Int x, Int y) {
+    Int res;
+    __builtin_sub_overflow(x, y, &res);
+    return res;
+}
+
+Int Wrapping_Multiply__BuiltIn(Int x, Int y) {
+    Int res;
+    __builtin_mul_overflow(x, y, &res);
+    return res;
+}
+
+// Checked operators abort the whole program on overflow.
+[[noreturn]] void abort_on_overflow(const char *op) {
+    std::cerr << "Integer overflow in " << op << "." << std::endl;
+    std::abort();
+}
+
+Int Checked_Plus__BuiltIn(Int x, Int y) {
+    Int res;
+    if (__builtin_add_overflow(x, y, &res))
+        abort_on_overflow("+");
+    return res;
+}
+
+Int Checked_Minus__BuiltIn(Int x, Int y) {
+    Int res;
+    if (__builtin_sub_overflow(x, y, &res))
+        abort_on_overflow("-");
+    return res;
+}
+
+Int Checked_Multiply__BuiltIn(Int x, Int y) {
+    Int res;
+    if (__builtin_mul_overflow(x, y, &res))
+        abort_on_overflow("*");
+    return res;
+}
+
+Int Checked_Fused_Multiply_Add__BuiltIn(Int x, Int y, Int z) {
+    return Checked_Plus__BuiltIn(Checked_Multiply__BuiltIn(x, y), z);
+}
+
 Bool Negation__BuiltIn(Bool x) { return !x; }
 
+Bool Conjunction__BuiltIn(Bool x, Bool y) { return x && y; }
+
+Bool Disjunction__BuiltIn(Bool x, Bool y) { return x || y; }
+
+TupleT<> Assert__BuiltIn(Bool x) {
+    // Report the failure and abort the whole program.
+    if (!x) {
+        std::cerr << "Assertion failed." << std::endl;
+        std::abort();
+    }
+    return TupleT<>{};
+}
+
 Bool Comparison_LT__BuiltIn(Int x, Int y) { return x < y; }
 
 Bool Comparison_LE__BuiltIn(Int x, Int y) { return x <= y; }
@@ -176,6 +446,106 @@ Bool Comparison_GT__BuiltIn(Int x, Int y) { return x > y; }
 
 Bool Comparison_GE__BuiltIn(Int x, Int y) { return x >= y; }
 
+Str Concat__BuiltIn(Str x, Str y) { return x + y; }
+
+Int Length__BuiltIn(Str x) { return static_cast<Int>(x.size()); }
+
+Bool String_Comparison_EQ__BuiltIn(Str x, Str y) { return x == y; }
+
+Bool String_Comparison_NE__BuiltIn(Str x, Str y) { return x != y; }
+
+Int Ord__BuiltIn(Char x) { return static_cast<Int>(x); }
+
+Char Chr__BuiltIn(Int x) {
+    // Only Unicode scalar values are valid characters.
+    if (x < 0 || x > 0x10FFFF || (x >= 0xD800 && x <= 0xDFFF))
+        throw std::invalid_argument("Invalid character codepoint " +
+                                    std::to_string(x) + ".");
+    return static_cast<Char>(x);
+}
+
+Bool Char_Comparison_EQ__BuiltIn(Char x, Char y) { return x == y; }
+
+Bool Char_Comparison_NE__BuiltIn(Char x, Char y) { return x != y; }
+
+template <typename T> ArrayT<T> Array_Empty__BuiltIn() {
+    return std::make_shared<const std::vector<T>>();
+}
+
+template <typename T> ArrayT<T> Array_Push__BuiltIn(ArrayT<T> xs, T x) {
+    // Arrays are immutable so pushing copies the buffer.
+    auto ys = std::make_shared<std::vector<T>>();
+    ys->reserve(xs->size() + 1);
+    ys->insert(ys->end(), xs->begin(), xs->end());
+    ys->push_back(x);
+    return ys;
+}
+
+template <typename T> T Array_Get__BuiltIn(ArrayT<T> xs, Int i) {
+    // Out-of-bounds accesses trap at runtime.
+    if (i < 0 || static_cast<std::size_t>(i) >= xs->size())
+        throw std::out_of_range("Array index " + std::to_string(i) +
+                                " out of bounds for length " +
+                                std::to_string(xs->size()) + ".");
+    return (*xs)[i];
+}
+
+template <typename T> Int Array_Length__BuiltIn(ArrayT<T> xs) {
+    return static_cast<Int>(xs->size());
+}
+
+template <typename T> [[noreturn]] T Error__BuiltIn(Str message) {
+    // Report the message and abort the whole program.
+    std::cerr << message << std::endl;
+    std::abort();
+}
+
+template <typename T, typename U>
+ArrayT<U> Array_Parallel_Map__BuiltIn(FnT<U, T> f, ArrayT<T> xs) {
+    // Enqueue a call for every element before waiting for any of them.
+    std::vector<LazyT<U>> results;
+    results.reserve(xs->size());
+    for (const T &x : *xs) {
+        auto [work, result] = Work::fn_call(f, x);
+        WorkManager::enqueue(work);
+        results.push_back(result);
+    }
+    auto ys = std::make_shared<std::vector<U>>();
+    ys->reserve(results.size());
+    for (LazyT<U> &result : results) {
+        WorkManager::await_all(result);
+        ys->push_back(extract_lazy(result));
+    }
+    return ys;
+}
+
+template <typename T>
+T Array_Parallel_Reduce__BuiltIn(FnT<T, T, T> f, ArrayT<T> xs) {
+    if (xs->empty())
+        throw std::invalid_argument("Cannot reduce an empty array.");
+    std::vector<LazyT<T>> values;
+    values.reserve(xs->size());
+    for (const T &x : *xs) {
+        values.push_back(ensure_lazy(x));
+    }
+    // Combine adjacent pairs in a tree so that each level runs in parallel.
+    while (values.size() > 1) {
+        std::vector<LazyT<T>> combined;
+        combined.reserve((values.size() + 1) / 2);
+        for (std::size_t i = 0; i + 1 < values.size(); i += 2) {
+            auto [work, result] = Work::fn_call(f, values[i], values[i + 1]);
+            WorkManager::enqueue(work);
+            combined.push_back(result);
+        }
+        if (values.size() % 2 == 1) {
+            combined.push_back(values.back());
+        }
+        values = std::move(combined);
+    }
+    WorkManager::await_all(values.front());
+    return extract_lazy(values.front());
+}
+
 Binary_Int_Int_Int_Op__BuiltIn(Plus__BuiltIn, 9);
 Binary_Int_Int_Int_Op__BuiltIn(Minus__BuiltIn, 9);
 Binary_Int_Int_Int_Op__BuiltIn(Multiply__BuiltIn, 9);
@@ -183,16 +553,37 @@ Binary_Int_Int_Int_Op__BuiltIn(Divide__BuiltIn, 10);
 Binary_Int_Int_Int_Op__BuiltIn(Exponentiate__BuiltIn, 12);
 Binary_Int_Int_Int_Op__BuiltIn(Modulo__BuiltIn, 10);
 Binary_Int_Int_Int_Op__BuiltIn(Right_Shift__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Logical_Right_Shift__BuiltIn, 9);
 Binary_Int_Int_Int_Op__BuiltIn(Left_Shift__BuiltIn, 9);
 Binary_Int_Int_Int_Op__BuiltIn(Spaceship__BuiltIn, 9);
 Binary_Int_Int_Int_Op__BuiltIn(Bitwise_And__BuiltIn, 9);
 Binary_Int_Int_Int_Op__BuiltIn(Bitwise_Or__BuiltIn, 9);
 Binary_Int_Int_Int_Op__BuiltIn(Bitwise_Xor__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Minimum__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Maximum__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Saturating_Plus__BuiltIn, 10);
+Binary_Int_Int_Int_Op__BuiltIn(Saturating_Minus__BuiltIn, 10);
+Binary_Int_Int_Int_Op__BuiltIn(Saturating_Multiply__BuiltIn, 10);
+Binary_Int_Int_Int_Op__BuiltIn(Wrapping_Plus__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Wrapping_Minus__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Wrapping_Multiply__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Checked_Plus__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Checked_Minus__BuiltIn, 9);
+Binary_Int_Int_Int_Op__BuiltIn(Checked_Multiply__BuiltIn, 9);
 
 Unary_Int_Int_Op__BuiltIn(Increment__BuiltIn, 8);
 Unary_Int_Int_Op__BuiltIn(Decrement__BuiltIn, 8);
+Unary_Int_Int_Op__BuiltIn(Bitwise_Not__BuiltIn, 8);
+Unary_Int_Int_Op__BuiltIn(Negative__BuiltIn, 8);
+Unary_Int_Int_Op__BuiltIn(Absolute__BuiltIn, 8);
+Ternary_Op__BuiltIn(Fused_Multiply_Add__BuiltIn, Int, Int, Int, Int, 10);
+Ternary_Op__BuiltIn(Checked_Fused_Multiply_Add__BuiltIn, Int, Int, Int, Int,
+                    10);
 
 Unary_Bool_Bool_Op__BuiltIn(Negation__BuiltIn, 8);
+Unary_Op__BuiltIn(Assert__BuiltIn, TupleT<>, Bool, 8);
+Binary_Op__BuiltIn(Conjunction__BuiltIn, Bool, Bool, Bool, 9);
+Binary_Op__BuiltIn(Disjunction__BuiltIn, Bool, Bool, Bool, 9);
 
 Binary_Int_Int_Bool_Op__BuiltIn(Comparison_LT__BuiltIn, 9);
 Binary_Int_Int_Bool_Op__BuiltIn(Comparison_LE__BuiltIn, 9);
@@ -200,3 +591,78 @@ Binary_Int_Int_Bool_Op__BuiltIn(Comparison_EQ__BuiltIn, 9);
 Binary_Int_Int_Bool_Op__BuiltIn(Comparison_NE__BuiltIn, 9);
 Binary_Int_Int_Bool_Op__BuiltIn(Comparison_GT__BuiltIn, 9);
 Binary_Int_Int_Bool_Op__BuiltIn(Comparison_GE__BuiltIn, 9);
+
+Binary_Op__BuiltIn(Concat__BuiltIn, Str, Str, Str, 9);
+Unary_Op__BuiltIn(Length__BuiltIn, Int, Str, 8);
+Binary_Op__BuiltIn(String_Comparison_EQ__BuiltIn, Bool, Str, Str, 9);
+Binary_Op__BuiltIn(String_Comparison_NE__BuiltIn, Bool, Str, Str, 9);
+
+Unary_Op__BuiltIn(Ord__BuiltIn, Int, Char, 8);
+Unary_Op__BuiltIn(Chr__BuiltIn, Char, Int, 8);
+Binary_Op__BuiltIn(Char_Comparison_EQ__BuiltIn, Bool, Char, Char, 9);
+Binary_Op__BuiltIn(Char_Comparison_NE__BuiltIn, Bool, Char, Char, 9);
+
+Generic_Nullary_Op__BuiltIn(Array_Empty__BuiltIn, ArrayT<T>, 8);
+Generic_Binary_Op__BuiltIn(Array_Push__BuiltIn, ArrayT<T>, ArrayT<T>, T, 12);
+Generic_Binary_Op__BuiltIn(Array_Get__BuiltIn, T, ArrayT<T>, Int, 9);
+Generic_Unary_Op__BuiltIn(Array_Length__BuiltIn, Int, ArrayT<T>, 8);
+
+Generic_Unary_Op__BuiltIn(Error__BuiltIn, T, Str, 8);
+
+// Parallel built-ins have fn arguments so their generators are written out.
+template <typename T, typename U>
+class Array_Parallel_Map__BuiltIn_I
+    : public TypedFnI<ArrayT<U>, FnT<U, T>, ArrayT<T>> {
+  protected:
+    LazyT<ArrayT<U>> body(LazyT<FnT<U, T>> &f,
+                          LazyT<ArrayT<T>> &xs) override {
+        WorkManager::enqueue(f);
+        WorkManager::enqueue(xs);
+        WorkManager::await(f, xs);
+        return make_lazy<ArrayT<U>>(
+            Array_Parallel_Map__BuiltIn<T, U>(f->value(), xs->value()));
+    }
+
+  public:
+    using typename TypedFnI<ArrayT<U>, FnT<U, T>, ArrayT<T>>::ArgsT;
+    using TypedFnI<ArrayT<U>, FnT<U, T>, ArrayT<T>>::TypedFnI;
+    static std::unique_ptr<TypedFnI<ArrayT<U>, FnT<U, T>, ArrayT<T>>>
+    init(const ArgsT &args) {
+        return std::make_unique<Array_Parallel_Map__BuiltIn_I>(args);
+    }
+    constexpr std::size_t lower_size_bound() const override { return 12; };
+    constexpr std::size_t upper_size_bound() const override { return 12; };
+    constexpr bool is_recursive() const override { return false; };
+};
+template <typename T, typename U>
+FnT<ArrayT<U>, FnT<U, T>, ArrayT<T>> Array_Parallel_Map__BuiltIn_G =
+    std::make_shared<TypedClosureG<Empty, ArrayT<U>, FnT<U, T>, ArrayT<T>>>(
+        Array_Parallel_Map__BuiltIn_I<T, U>::init);
+
+template <typename T>
+class Array_Parallel_Reduce__BuiltIn_I
+    : public TypedFnI<T, FnT<T, T, T>, ArrayT<T>> {
+  protected:
+    LazyT<T> body(LazyT<FnT<T, T, T>> &f, LazyT<ArrayT<T>> &xs) override {
+        WorkManager::enqueue(f);
+        WorkManager::enqueue(xs);
+        WorkManager::await(f, xs);
+        return ensure_lazy(
+            Array_Parallel_Reduce__BuiltIn<T>(f->value(), xs->value()));
+    }
+
+  public:
+    using typename TypedFnI<T, FnT<T, T, T>, ArrayT<T>>::ArgsT;
+    using TypedFnI<T, FnT<T, T, T>, ArrayT<T>>::TypedFnI;
+    static std::unique_ptr<TypedFnI<T, FnT<T, T, T>, ArrayT<T>>>
+    init(const ArgsT &args) {
+        return std::make_unique<Array_Parallel_Reduce__BuiltIn_I>(args);
+    }
+    constexpr std::size_t lower_size_bound() const override { return 12; };
+    constexpr std::size_t upper_size_bound() const override { return 12; };
+    constexpr bool is_recursive() const override { return false; };
+};
+template <typename T>
+FnT<T, FnT<T, T, T>, ArrayT<T>> Array_Parallel_Reduce__BuiltIn_G =
+    std::make_shared<TypedClosureG<Empty, T, FnT<T, T, T>, ArrayT<T>>>(
+        Array_Parallel_Reduce__BuiltIn_I<T>::init);
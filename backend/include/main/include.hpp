@@ -11,4 +11,5 @@
 #include "types/display.hpp"
 #include "types/utils.hpp"
 
+#include <iostream>
 #include <utility>
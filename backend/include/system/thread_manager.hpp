@@ -8,6 +8,7 @@
 #include <pthread.h>
 #include <sched.h>
 #include <stdexcept>
+#include <string_view>
 #include <thread>
 #include <vector>
 
@@ -24,6 +25,8 @@ class ThreadManager {
     static void override_concurrency(unsigned int num_cpus);
     /// Reset to hardware concurrency.
     static void reset_concurrency_override();
+    /// Parse a number of CPUs, which must be a positive integer.
+    static std::optional<unsigned int> parse_concurrency(std::string_view value);
 
     /// Set the CPU id of a thread.
     static void register_self(ThreadId cpu_id);
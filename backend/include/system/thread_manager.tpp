@@ -6,6 +6,7 @@
 #include <range/v3/numeric/iota.hpp>
 
 #include <atomic>
+#include <charconv>
 #include <cstring>
 #include <iostream>
 #include <mutex>
@@ -13,6 +14,8 @@
 #include <pthread.h>
 #include <sched.h>
 #include <stdexcept>
+#include <string_view>
+#include <system_error>
 #include <thread>
 #include <cstdlib>
 
@@ -45,6 +48,17 @@ void ThreadManager::reset_concurrency_override() {
     set_shared_affinity();
 }
 
+std::optional<unsigned> ThreadManager::parse_concurrency(std::string_view value) {
+    unsigned num_cpus;
+    const char *end = value.data() + value.size();
+    auto [ptr, error] = std::from_chars(value.data(), end, num_cpus);
+    // Reject signs, trailing characters and zero.
+    if (error != std::errc() || ptr != end || num_cpus == 0) {
+        return std::nullopt;
+    }
+    return num_cpus;
+}
+
 thread_local ThreadManager::ThreadId thread_id;
 void ThreadManager::register_self(ThreadId cpu_id) {
     thread_id = cpu_id;
@@ -28,7 +28,13 @@ LazyT<Ret> WorkManager::run(FnT<Ret, Args...> fn, Args...args) {
           | ranges::to<std::vector>();
 
 
-    ThreadManager::run_multithreaded(main, &ref, config);
+    if (num_cpus == 1) {
+        // Run sequentially on the current thread.
+        ThreadManager::register_self(0);
+        main(&ref);
+    } else {
+        ThreadManager::run_multithreaded(main, &ref, config);
+    }
     return result;
 }
 
@@ -2,10 +2,13 @@
 
 #include <cstdint>
 #include <iostream>
+#include <string>
 #include <variant>
 
 typedef int64_t Int;
 typedef bool Bool;
+typedef std::string Str;
+typedef uint32_t Char;
 
 typedef std::monostate Empty;
 
@@ -7,9 +7,13 @@
 #include <new>
 #include <tuple>
 #include <type_traits>
+#include <vector>
 
 template <typename... Ts> using TupleT = std::tuple<Ts...>;
 
+/// Store an array as a shared, immutable contiguous buffer.
+template <typename T> using ArrayT = std::shared_ptr<const std::vector<T>>;
+
 /// Store a variant as a tag and then a  union.
 template <typename... Types> struct VariantT {
     static_assert(sizeof...(Types) > 0, "VariantT must have at least one type");
@@ -71,3 +75,10 @@ template <typename... Types> struct VariantT {
         return os;
     }
 };
+
+/// Void types have no values, so their variant never holds any data.
+template <> struct VariantT<> {
+    friend std::ostream &operator<<(std::ostream &os, const VariantT &) {
+        return os << "[]";
+    }
+};
@@ -3,8 +3,11 @@
 #include "types/compound.tpp"
 #include "types/utils.hpp"
 
+#include <algorithm>
+#include <bitset>
 #include <iostream>
 #include <memory>
+#include <string>
 #include <type_traits>
 
 template <typename... Args>
@@ -20,8 +23,24 @@ std::ostream &operator<<(std::ostream &os, TupleT<Args...> const &t) {
     return os;
 }
 
+template <typename T>
+std::ostream &operator<<(std::ostream &os, ArrayT<T> const &array) {
+    os << '[';
+    for (std::size_t i = 0; i < array->size(); i++) {
+        os << (i == 0 ? "" : ", ") << (*array)[i];
+    }
+    os << ']';
+    return os;
+}
+
 template <typename T, typename = std::enable_if_t<is_lazy_v<T>>>
 std::ostream &operator<<(std::ostream &os, T const &t) {
     os << t->value();
     return os;
 }
+
+/// Binary digits of an integer without leading zeros.
+std::string binary_digits(Int value) {
+    std::string digits = std::bitset<64>(value).to_string();
+    return digits.substr(std::min(digits.find('1'), digits.size() - 1));
+}
@@ -72,6 +72,11 @@ Int convert_arg(char *&arg) requires std::same_as<T, Int> {
     return std::stoll(arg);
 }
 
+template <typename T>
+Str convert_arg(char *&arg) requires std::same_as<T, Str> {
+    return Str(arg);
+}
+
 template <typename T>
 Bool convert_arg(char *&arg) requires std::same_as<T, Bool> {
     std::string_view str(arg);
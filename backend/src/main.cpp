@@ -1,9 +1,12 @@
 #include "main/main.hpp"
+#include "system/thread_manager.hpp"
 #include "system/work_manager.hpp"
 #include "time/utils.hpp"
 #include "types/utils.hpp"
 
 #include <iostream>
+#include <string>
+#include <string_view>
 
 int main(int argc, char *argv[]) {
     auto start = time_utils::now();
@@ -11,6 +14,21 @@ int main(int argc, char *argv[]) {
     // Ignore first argument.
     argc--;
     argv++;
+
+    // Parse the number of threads (defaults to the available concurrency).
+    const std::string threads_flag = "--threads=";
+    if (argc > 0 && std::string(argv[0]).starts_with(threads_flag)) {
+        auto value = std::string_view(argv[0]).substr(threads_flag.size());
+        auto num_threads = ThreadManager::parse_concurrency(value);
+        if (!num_threads.has_value()) {
+            std::cerr << "Invalid number of threads \"" << value
+                      << "\", usage: --threads=N with N >= 1." << std::endl;
+            exit(1);
+        }
+        ThreadManager::override_concurrency(num_threads.value());
+        argc--;
+        argv++;
+    }
     constexpr auto N = std::tuple_size_v<ArgsT>;
     if (N != argc) {
         std::cerr << "Invalid number of arguments expected " << N << " got "
@@ -34,7 +52,8 @@ int main(int argc, char *argv[]) {
 
     auto end = time_utils::now();
 
-    std::cout << result << std::endl;
+    print_result(std::cout, result);
+    std::cout << std::endl;
 
     auto duration = time_utils::time_delta(start, end);
     std::cerr << "Execution time: " << duration << std::endl;
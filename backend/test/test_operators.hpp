@@ -9,8 +9,11 @@
 
 #include <gtest/gtest.h>
 
+#include <algorithm>
+#include <atomic>
 #include <compare>
 #include <functional>
+#include <limits>
 #include <vector>
 
 class BinaryOperatorsTests
@@ -53,6 +56,11 @@ INSTANTIATE_TEST_SUITE_P(
                         [](Int x, Int y) { return x << y; }),
         std::make_tuple(Right_Shift__BuiltIn_G,
                         [](Int x, Int y) { return x >> y; }),
+        std::make_tuple(Logical_Right_Shift__BuiltIn_G,
+                        [](Int x, Int y) {
+                            return static_cast<Int>(
+                                static_cast<uint64_t>(x) >> y);
+                        }),
         std::make_tuple(Spaceship__BuiltIn_G,
                         [](Int x, Int y) {
                             const auto o = std::compare_three_way()(x, y);
@@ -66,7 +74,56 @@ INSTANTIATE_TEST_SUITE_P(
                         }),
         std::make_tuple(Bitwise_And__BuiltIn_G, std::bit_and<Int>()),
         std::make_tuple(Bitwise_Or__BuiltIn_G, std::bit_or<Int>()),
-        std::make_tuple(Bitwise_Xor__BuiltIn_G, std::bit_xor<Int>())));
+        std::make_tuple(Bitwise_Xor__BuiltIn_G, std::bit_xor<Int>()),
+        std::make_tuple(Minimum__BuiltIn_G,
+                        [](Int x, Int y) { return std::min(x, y); }),
+        std::make_tuple(Maximum__BuiltIn_G,
+                        [](Int x, Int y) { return std::max(x, y); }),
+        std::make_tuple(Saturating_Plus__BuiltIn_G, std::plus<Int>()),
+        std::make_tuple(Saturating_Minus__BuiltIn_G, std::minus<Int>()),
+        std::make_tuple(Saturating_Multiply__BuiltIn_G,
+                        std::multiplies<Int>()),
+        std::make_tuple(Wrapping_Plus__BuiltIn_G, std::plus<Int>()),
+        std::make_tuple(Wrapping_Minus__BuiltIn_G, std::minus<Int>()),
+        std::make_tuple(Wrapping_Multiply__BuiltIn_G,
+                        std::multiplies<Int>()),
+        std::make_tuple(Checked_Plus__BuiltIn_G, std::plus<Int>()),
+        std::make_tuple(Checked_Minus__BuiltIn_G, std::minus<Int>()),
+        std::make_tuple(Checked_Multiply__BuiltIn_G,
+                        std::multiplies<Int>())));
+
+TEST(OverflowOperatorsTests, OverflowBehavior) {
+    const Int max = std::numeric_limits<Int>::max();
+    const Int min = std::numeric_limits<Int>::min();
+    const std::vector<std::tuple<FnT<Int, Int, Int>, Int, Int, Int>> cases{
+        {Saturating_Plus__BuiltIn_G, max, 1, max},
+        {Saturating_Plus__BuiltIn_G, min, -1, min},
+        {Saturating_Minus__BuiltIn_G, min, 1, min},
+        {Saturating_Minus__BuiltIn_G, max, -1, max},
+        {Saturating_Multiply__BuiltIn_G, max, 2, max},
+        {Saturating_Multiply__BuiltIn_G, max, -2, min},
+        {Wrapping_Plus__BuiltIn_G, max, 1, min},
+        {Wrapping_Minus__BuiltIn_G, min, 1, max},
+        {Wrapping_Multiply__BuiltIn_G, max, 2, -2},
+    };
+    for (auto [op, x, y, expected] : cases) {
+        auto result = WorkManager::run(op, x, y);
+        ASSERT_EQ(result->value(), expected);
+    }
+}
+
+TEST(OverflowOperatorsTests, CheckedAborts) {
+    const Int max = std::numeric_limits<Int>::max();
+    const Int min = std::numeric_limits<Int>::min();
+    ASSERT_DEATH(WorkManager::run(Checked_Plus__BuiltIn_G, max, Int{1}),
+                 "Integer overflow");
+    ASSERT_DEATH(WorkManager::run(Checked_Minus__BuiltIn_G, min, Int{1}),
+                 "Integer overflow");
+    ASSERT_DEATH(WorkManager::run(Checked_Multiply__BuiltIn_G, max, Int{2}),
+                 "Integer overflow");
+    ASSERT_DEATH(Checked_Fused_Multiply_Add__BuiltIn(max, 1, 1),
+                 "Integer overflow");
+}
 
 class UnaryOperatorsTests
     : public ::testing::TestWithParam<
@@ -87,7 +144,11 @@ INSTANTIATE_TEST_SUITE_P(
     UnaryOperators, UnaryOperatorsTests,
     ::testing::Values(
         std::make_tuple(Increment__BuiltIn_G, [](Int x) { return ++x; }),
-        std::make_tuple(Decrement__BuiltIn_G, [](Int x) { return --x; })));
+        std::make_tuple(Decrement__BuiltIn_G, [](Int x) { return --x; }),
+        std::make_tuple(Bitwise_Not__BuiltIn_G, [](Int x) { return ~x; }),
+        std::make_tuple(Negative__BuiltIn_G, [](Int x) { return -x; }),
+        std::make_tuple(Absolute__BuiltIn_G,
+                        [](Int x) { return x < 0 ? -x : x; })));
 
 class BinaryComparisonsTests
     : public ::testing::TestWithParam<
@@ -117,6 +178,19 @@ INSTANTIATE_TEST_SUITE_P(
         std::make_tuple(Comparison_EQ__BuiltIn_G, std::equal_to<Int>()),
         std::make_tuple(Comparison_NE__BuiltIn_G, std::not_equal_to<Int>())));
 
+TEST(FusedMultiplyAddTests, OperatorCorrectness) {
+    const std::vector<Int> xs{-1000009LL, -55, 24, 200, 10024};
+    for (Int x : xs) {
+        for (Int y : xs) {
+            for (Int z : {-8, 4, 17}) {
+                auto result =
+                    WorkManager::run(Fused_Multiply_Add__BuiltIn_G, x, y, z);
+                ASSERT_EQ(result->value(), x * y + z);
+            }
+        }
+    }
+}
+
 TEST(NegationTests, OperatorCorrectness) {
     auto fn = Negation__BuiltIn_G;
     {
@@ -128,3 +202,181 @@ TEST(NegationTests, OperatorCorrectness) {
         ASSERT_EQ(result->value(), true);
     }
 }
+
+TEST(AssertTests, OperatorCorrectness) {
+    auto result = WorkManager::run(Assert__BuiltIn_G, true);
+    ASSERT_EQ(result->value(), TupleT<>{});
+    ASSERT_DEATH(WorkManager::run(Assert__BuiltIn_G, false),
+                 "Assertion failed.");
+}
+
+TEST(BooleanOperatorsTests, OperatorCorrectness) {
+    for (const Bool &x : {false, true}) {
+        for (const Bool &y : {false, true}) {
+            {
+                auto result = WorkManager::run(Conjunction__BuiltIn_G, x, y);
+                ASSERT_EQ(result->value(), x && y);
+            }
+            {
+                auto result = WorkManager::run(Disjunction__BuiltIn_G, x, y);
+                ASSERT_EQ(result->value(), x || y);
+            }
+        }
+    }
+}
+
+TEST(StringOperatorsTests, OperatorCorrectness) {
+    const std::vector<Str> strings{Str(""), Str("a"), Str("a\0b", 3ULL),
+                                   Str("hello")};
+    for (const Str &x : strings) {
+        {
+            auto result = WorkManager::run(Length__BuiltIn_G, x);
+            ASSERT_EQ(result->value(), static_cast<Int>(x.size()));
+        }
+        for (const Str &y : strings) {
+            {
+                auto result = WorkManager::run(Concat__BuiltIn_G, x, y);
+                ASSERT_EQ(result->value(), x + y);
+            }
+            {
+                auto result =
+                    WorkManager::run(String_Comparison_EQ__BuiltIn_G, x, y);
+                ASSERT_EQ(result->value(), x == y);
+            }
+            {
+                auto result =
+                    WorkManager::run(String_Comparison_NE__BuiltIn_G, x, y);
+                ASSERT_EQ(result->value(), x != y);
+            }
+        }
+    }
+}
+
+TEST(CharOperatorsTests, OperatorCorrectness) {
+    const std::vector<Char> chars{0U, 97U, 233U, 0x10FFFFU};
+    for (const Char &x : chars) {
+        {
+            auto result = WorkManager::run(Ord__BuiltIn_G, x);
+            ASSERT_EQ(result->value(), static_cast<Int>(x));
+        }
+        {
+            auto result = WorkManager::run(Chr__BuiltIn_G, static_cast<Int>(x));
+            ASSERT_EQ(result->value(), x);
+        }
+        for (const Char &y : chars) {
+            {
+                auto result =
+                    WorkManager::run(Char_Comparison_EQ__BuiltIn_G, x, y);
+                ASSERT_EQ(result->value(), x == y);
+            }
+            {
+                auto result =
+                    WorkManager::run(Char_Comparison_NE__BuiltIn_G, x, y);
+                ASSERT_EQ(result->value(), x != y);
+            }
+        }
+    }
+}
+
+TEST(CharOperatorsTests, InvalidCodepoints) {
+    for (const Int &x : std::vector<Int>{-1, 0xD800, 0xDFFF, 0x110000}) {
+        ASSERT_THROW(Chr__BuiltIn(x), std::invalid_argument);
+    }
+}
+
+TEST(ArrayOperatorsTests, OperatorCorrectness) {
+    auto empty = WorkManager::run(Array_Empty__BuiltIn_G<Int>)->value();
+    ASSERT_EQ(WorkManager::run(Array_Length__BuiltIn_G<Int>, empty)->value(), 0);
+    auto array =
+        WorkManager::run(Array_Push__BuiltIn_G<Int>, empty, Int{5})->value();
+    array = WorkManager::run(Array_Push__BuiltIn_G<Int>, array, Int{8})->value();
+    ASSERT_EQ(WorkManager::run(Array_Length__BuiltIn_G<Int>, array)->value(), 2);
+    ASSERT_EQ(WorkManager::run(Array_Get__BuiltIn_G<Int>, array, Int{0})->value(),
+              5);
+    ASSERT_EQ(WorkManager::run(Array_Get__BuiltIn_G<Int>, array, Int{1})->value(),
+              8);
+    // Pushing does not modify the original array.
+    ASSERT_EQ(Array_Length__BuiltIn<Int>(empty), 0);
+}
+
+TEST(ArrayOperatorsTests, OutOfBounds) {
+    auto array = Array_Push__BuiltIn<Int>(Array_Empty__BuiltIn<Int>(), 5);
+    for (const Int &i : std::vector<Int>{-1, 1, 100}) {
+        ASSERT_THROW(Array_Get__BuiltIn<Int>(array, i), std::out_of_range);
+    }
+}
+
+TEST(ErrorOperatorTests, Aborts) {
+    ASSERT_DEATH(Error__BuiltIn<Int>("Unreachable case."), "Unreachable case.");
+    ASSERT_DEATH(WorkManager::run(Error__BuiltIn_G<Bool>, Str("failure")),
+                 "failure");
+}
+
+// Count calls to check how many tasks the parallel built-ins spawn.
+static std::atomic<unsigned> parallel_calls{0};
+
+struct CountedIncrement : TypedClosureI<Empty, Int, Int> {
+    using TypedClosureI<Empty, Int, Int>::TypedClosureI;
+    LazyT<Int> body(LazyT<Int> &x) override {
+        parallel_calls++;
+        WorkManager::await(x);
+        return make_lazy<Int>(x->value() + 1);
+    }
+    constexpr std::size_t lower_size_bound() const override { return 1; };
+    constexpr std::size_t upper_size_bound() const override { return 1; };
+    static std::unique_ptr<TypedFnI<Int, Int>> init(const ArgsT &args) {
+        return std::make_unique<CountedIncrement>(args);
+    }
+    constexpr bool is_recursive() const override { return false; };
+};
+
+struct CountedPlus : TypedClosureI<Empty, Int, Int, Int> {
+    using TypedClosureI<Empty, Int, Int, Int>::TypedClosureI;
+    LazyT<Int> body(LazyT<Int> &x, LazyT<Int> &y) override {
+        parallel_calls++;
+        WorkManager::enqueue(x);
+        WorkManager::enqueue(y);
+        WorkManager::await(x, y);
+        return make_lazy<Int>(x->value() + y->value());
+    }
+    constexpr std::size_t lower_size_bound() const override { return 1; };
+    constexpr std::size_t upper_size_bound() const override { return 1; };
+    static std::unique_ptr<TypedFnI<Int, Int, Int>> init(const ArgsT &args) {
+        return std::make_unique<CountedPlus>(args);
+    }
+    constexpr bool is_recursive() const override { return false; };
+};
+
+TEST(ParallelArrayOperatorsTests, MapSpawnsTaskPerElement) {
+    parallel_calls = 0;
+    FnT<Int, Int> increment =
+        std::make_shared<TypedClosureG<Empty, Int, Int>>(CountedIncrement::init);
+    ArrayT<Int> array =
+        std::make_shared<const std::vector<Int>>(std::vector<Int>{1, 2, 3, 4});
+    auto result = WorkManager::run(Array_Parallel_Map__BuiltIn_G<Int, Int>,
+                                   increment, array)
+                      ->value();
+    ASSERT_EQ(*result, (std::vector<Int>{2, 3, 4, 5}));
+    ASSERT_EQ(parallel_calls.load(), 4u);
+}
+
+TEST(ParallelArrayOperatorsTests, ReduceSpawnsTaskPerPair) {
+    parallel_calls = 0;
+    FnT<Int, Int, Int> plus =
+        std::make_shared<TypedClosureG<Empty, Int, Int, Int>>(CountedPlus::init);
+    ArrayT<Int> array =
+        std::make_shared<const std::vector<Int>>(std::vector<Int>{1, 2, 3, 4});
+    auto result =
+        WorkManager::run(Array_Parallel_Reduce__BuiltIn_G<Int>, plus, array);
+    ASSERT_EQ(result->value(), 10);
+    // Two pairs are combined and then their results are combined.
+    ASSERT_EQ(parallel_calls.load(), 3u);
+}
+
+TEST(ParallelArrayOperatorsTests, ReduceEmpty) {
+    FnT<Int, Int, Int> plus =
+        std::make_shared<TypedClosureG<Empty, Int, Int, Int>>(CountedPlus::init);
+    ASSERT_THROW(Array_Parallel_Reduce__BuiltIn<Int>(
+                     plus, Array_Empty__BuiltIn<Int>()),
+                 std::invalid_argument);
+}
@@ -1,6 +1,11 @@
 #pragma once
 
+#include "fn/fn_gen.tpp"
+#include "fn/fn_inst.tpp"
+#include "fn/operators.hpp"
+#include "lazy/lazy.tpp"
 #include "system/thread_manager.tpp"
+#include "system/work_manager.tpp"
 
 #include <gtest/gtest.h>
 
@@ -39,3 +44,22 @@ TEST(ThreadManagerTest, NumCpusOverride) {
     ASSERT_EQ(ThreadManager::available_concurrency(),
               std::thread::hardware_concurrency());
 }
+
+TEST(ThreadManagerTest, ParseConcurrency) {
+    ASSERT_EQ(ThreadManager::parse_concurrency("1"), 1);
+    ASSERT_EQ(ThreadManager::parse_concurrency("12"), 12);
+    for (auto value : {"", "0", "-2", "+3", "two", "4x", " 4"}) {
+        ASSERT_FALSE(ThreadManager::parse_concurrency(value).has_value())
+            << value;
+    }
+}
+
+TEST(ThreadManagerTest, SingleThreadRunsSequentially) {
+    ThreadManager::override_concurrency(
+        ThreadManager::parse_concurrency("1").value());
+    auto result = WorkManager::run(Plus__BuiltIn_G, Int{2}, Int{3});
+    ASSERT_EQ(result->value(), 5);
+    // The sequential path runs on the calling thread as CPU 0.
+    ASSERT_EQ(ThreadManager::get_id(), 0);
+    ThreadManager::reset_concurrency_override();
+}
@@ -6,6 +6,7 @@
 #include <gtest/gtest.h>
 
 #include <memory>
+#include <sstream>
 #include <type_traits>
 
 TEST(VariantDestructorTests, ContainedIntegerTest) {
@@ -44,3 +45,10 @@ TEST(VariantDestructorTests, DoubleReferenceTest) {
                   &(*reinterpret_cast<std::shared_ptr<T> *>(&u.value))->value),
               4);
 }
+
+TEST(VariantTests, VoidVariantTest) {
+    static_assert(std::is_empty_v<VariantT<>>);
+    std::stringstream stream;
+    stream << VariantT<>{};
+    ASSERT_EQ(stream.str(), "[]");
+}
@@ -0,0 +1,47 @@
+use clap::Args;
+
+use crate::{OutputRadix, Target};
+
+// Flags that only change the emitted code live here rather than in the translation args (the pipeline's
+// `compilation_args`), which run before emission and are part of the compilation cache key.
+#[derive(Args, Clone, Default)]
+pub struct EmissionArgs {
+    /// Instrument the emitted code with runtime assertions on match tags and trace each fn call to the stderr.
+    #[arg(long)]
+    pub debug: bool,
+    /// Print a JSON table mapping lines of the emitted code to registers to the stderr.
+    #[arg(long)]
+    pub debug_info: bool,
+    /// Abort the program when `+`, `-` or `*` overflow instead of wrapping.
+    #[arg(long)]
+    pub checked_arithmetic: bool,
+    /// Base to print an integer result of the program in.
+    #[arg(long, value_enum, default_value_t)]
+    pub output_radix: OutputRadix,
+    /// Language to emit the program in.
+    #[arg(long, value_enum, default_value_t)]
+    pub target: Target,
+}
+
+impl EmissionArgs {
+    /// Preset for instrumented builds.
+    pub fn debug_preset() -> Self {
+        EmissionArgs {
+            debug: true,
+            debug_info: false,
+            checked_arithmetic: false,
+            output_radix: OutputRadix::default(),
+            target: Target::default(),
+        }
+    }
+    /// Preset for stripped builds.
+    pub fn release_preset() -> Self {
+        EmissionArgs {
+            debug: false,
+            debug_info: false,
+            checked_arithmetic: false,
+            output_radix: OutputRadix::default(),
+            target: Target::default(),
+        }
+    }
+}
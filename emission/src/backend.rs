@@ -0,0 +1,27 @@
+use clap::ValueEnum;
+use translation::Program;
+
+/// Languages that programs can be emitted in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    /// C++ code for the runtime in `backend/`.
+    #[default]
+    Cpp,
+}
+
+/// Base that the integer result of a program is printed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputRadix {
+    #[default]
+    Dec,
+    /// Hexadecimal with a `0x` prefix.
+    Hex,
+    /// Binary with a `0b` prefix.
+    Bin,
+}
+
+/// Code generator for a target language.
+pub trait Backend {
+    /// Emit the code for a program, marking statements with their memory if source maps are requested.
+    fn emit(&self, program: Program) -> String;
+}
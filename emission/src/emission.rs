@@ -4,19 +4,32 @@ use std::collections::{HashMap, HashSet};
 use std::convert::identity;
 
 use translation::{
-    Allocation, Assignment, Await, Boolean, BuiltIn, ClosureInstantiation, ConstructorCall,
+    Allocation, Assignment, Await, Boolean, BuiltIn, Char, ClosureInstantiation, ConstructorCall,
     Declaration, ElementAccess, Enqueue, Expression, FnCall, FnDef, Id, IfStatement, Integer,
-    MachineType, MatchStatement, Memory, Name, Program, Statement, TupleExpression, TupleType,
-    TypeDef, UnionType, Value,
+    MachineType, MatchStatement, Memory, Name, Program, Statement, Str, TailCall, TupleExpression,
+    TupleType, TypeDef, UnionType, Value,
 };
 
-use crate::type_formatter::TypeFormatter;
+use crate::{type_formatter::TypeFormatter, Backend, EmissionArgs, OutputRadix, Target};
 
 type Code = String;
 
-pub struct Emitter {}
+/// Lines of emitted code (numbered from 1) paired with the memory that they use.
+pub type SourceMap = Vec<(usize, Memory)>;
 
-impl Emitter {
+// Delimit annotations with characters that never appear in emitted code.
+const ANNOTATION_START: char = '\u{1}';
+const ANNOTATION_END: char = '\u{2}';
+
+/// Emit C++ code for the runtime in `backend/`.
+pub struct CppBackend {
+    debug: bool,
+    debug_info: bool,
+    checked_arithmetic: bool,
+    output_radix: OutputRadix,
+}
+
+impl CppBackend {
     fn emit_type(&self, type_: &MachineType) -> Code {
         format!("{}", TypeFormatter(type_))
     }
@@ -106,11 +119,39 @@ impl Emitter {
             itertools::join(constructor_definitions, "\n"),
         )
     }
+    /// Name of a built-in fn, using the overflow-checked operators if requested.
+    fn emit_builtin_fn_name(&self, name: Name) -> Name {
+        match name.as_str() {
+            "Plus__BuiltIn"
+            | "Minus__BuiltIn"
+            | "Multiply__BuiltIn"
+            | "Fused_Multiply_Add__BuiltIn"
+                if self.checked_arithmetic =>
+            {
+                format!("Checked_{name}")
+            }
+            _ => name,
+        }
+    }
+    /// Emit the explicit template arguments of a generic built-in fn.
+    fn emit_template_arguments(&self, types: &[MachineType]) -> Code {
+        format!(
+            "<{}>",
+            types.iter().map(|type_| self.emit_type(type_)).join(",")
+        )
+    }
     fn emit_value_type(&self, value: &Value) -> Code {
         match value {
             Value::BuiltIn(BuiltIn::Boolean(_)) => Code::from("Bool"),
             Value::BuiltIn(BuiltIn::Integer(_)) => Code::from("Int"),
-            Value::BuiltIn(BuiltIn::BuiltInFn(name)) => format!("decltype({name}_G)"),
+            Value::BuiltIn(BuiltIn::Str(_)) => Code::from("Str"),
+            Value::BuiltIn(BuiltIn::Char(_)) => Code::from("Char"),
+            Value::BuiltIn(BuiltIn::BuiltInFn(name)) => {
+                format!("decltype({}_G)", self.emit_builtin_fn_name(name.clone()))
+            }
+            Value::BuiltIn(BuiltIn::GenericBuiltInFn(name, types)) => {
+                format!("decltype({name}_G{})", self.emit_template_arguments(types))
+            }
             Value::Memory(Memory(id)) => format!("decltype({id})"),
         }
     }
@@ -123,11 +164,42 @@ impl Emitter {
             BuiltIn::Boolean(Boolean { value }) => {
                 format!("{value_type}{{{value}}}")
             }
+            BuiltIn::Str(Str { value }) => {
+                // Pass the length explicitly so that null characters are preserved.
+                format!(
+                    "{value_type}(\"{}\", {}ULL)",
+                    Self::escape_string(&value),
+                    value.len()
+                )
+            }
+            BuiltIn::Char(Char { value }) => {
+                format!("{value_type}{{{value}U}}")
+            }
             BuiltIn::BuiltInFn(name) => {
+                let name = self.emit_builtin_fn_name(name);
                 format!("make_lazy<{value_type}>({name}_G)")
             }
+            BuiltIn::GenericBuiltInFn(name, types) => {
+                let template_arguments = self.emit_template_arguments(&types);
+                format!("make_lazy<{value_type}>({name}_G{template_arguments})")
+            }
         }
     }
+    /// Escape a string for use in a C++ string literal.
+    fn escape_string(value: &str) -> Code {
+        value
+            .bytes()
+            .map(|byte| match byte {
+                b'"' => Code::from("\\\""),
+                b'\\' => Code::from("\\\\"),
+                b'\n' => Code::from("\\n"),
+                b'\t' => Code::from("\\t"),
+                b'\r' => Code::from("\\r"),
+                b' '..=b'~' => Code::from(byte as char),
+                _ => format!("\\{byte:03o}"),
+            })
+            .collect()
+    }
     fn emit_memory(&self, Memory(id): Memory) -> Code {
         id
     }
@@ -175,8 +247,12 @@ impl Emitter {
     fn emit_fn_call(&self, fn_call: FnCall) -> Code {
         match fn_call.fn_ {
             Value::BuiltIn(built_in) => {
-                let BuiltIn::BuiltInFn(name) = built_in else {
-                    panic!("Attempt to call non-fn built-in.")
+                let name = match built_in {
+                    BuiltIn::BuiltInFn(name) => self.emit_builtin_fn_name(name),
+                    BuiltIn::GenericBuiltInFn(name, types) => {
+                        format!("{name}{}", self.emit_template_arguments(&types))
+                    }
+                    _ => panic!("Attempt to call non-fn built-in."),
                 };
                 let args_code = fn_call
                     .args
@@ -276,6 +352,7 @@ impl Emitter {
             "auto {subject} = extract_lazy({});",
             self.emit_value(match_statement.expression.0)
         );
+        let num_branches = match_statement.branches.len();
         let branches_code = match_statement
             .branches
             .into_iter()
@@ -298,14 +375,109 @@ impl Emitter {
                 format!("case {i}ULL : {{ {assignment_code} {statements_code} break; }}",)
             })
             .join("\n");
-        format!("{extraction} switch ({subject}.tag) {{ {branches_code} }}")
+        let check_code = if self.debug {
+            // Check the tag corresponds to a branch.
+            format!("assert({subject}.tag < {num_branches}ULL);")
+        } else {
+            Code::new()
+        };
+        format!("{extraction} {check_code} switch ({subject}.tag) {{ {branches_code} }}")
     }
     fn emit_enqueue(&self, enqueue: Enqueue) -> Code {
         let Enqueue(memory) = enqueue;
         format!("WorkManager::enqueue({});", self.emit_memory(memory))
     }
-    fn emit_statement(&self, statement: Statement, declared: &mut HashSet<Memory>) -> Code {
+    fn emit_tail_call(&self, tail_call: TailCall) -> Code {
+        let (arguments, values): (Vec<_>, Vec<_>) = tail_call
+            .arguments
+            .into_iter()
+            .map(|(memory, value)| {
+                (
+                    self.emit_memory(memory),
+                    format!("ensure_lazy({})", self.emit_value(value)),
+                )
+            })
+            .unzip();
+        // Assign all arguments at once in case new values depend on old ones.
+        let assignment_code = if arguments.is_empty() {
+            Code::new()
+        } else {
+            format!(
+                "std::tie({}) = std::make_tuple({});",
+                arguments.join(","),
+                values.join(",")
+            )
+        };
+        format!("{assignment_code} continue;")
+    }
+    /// Determine whether statements loop back to the start of the fn.
+    fn contains_tail_call(statements: &[Statement]) -> bool {
+        statements.iter().any(|statement| match statement {
+            Statement::TailCall(_) => true,
+            Statement::IfStatement(IfStatement {
+                condition: _,
+                branches,
+            }) => Self::contains_tail_call(&branches.0) || Self::contains_tail_call(&branches.1),
+            Statement::MatchStatement(MatchStatement {
+                expression: _,
+                branches,
+                auxiliary_memory: _,
+            }) => branches
+                .iter()
+                .any(|branch| Self::contains_tail_call(&branch.statements)),
+            _ => false,
+        })
+    }
+    /// Find the memory that a statement defines or uses directly.
+    fn statement_memories(statement: &Statement) -> Vec<Memory> {
+        let value_memories = |value: &Value| match value {
+            Value::Memory(memory) => vec![memory.clone()],
+            Value::BuiltIn(_) => Vec::new(),
+        };
         match statement {
+            Statement::Await(Await(memories)) => memories.clone(),
+            Statement::Declaration(Declaration { type_: _, memory }) => vec![memory.clone()],
+            Statement::Allocation(Allocation {
+                name: _,
+                fns,
+                target,
+            }) => std::iter::once(target)
+                .chain(fns.iter().map(|(memory, _)| memory))
+                .cloned()
+                .collect(),
+            Statement::Assignment(Assignment { target, value: _ }) => vec![target.clone()],
+            Statement::IfStatement(IfStatement {
+                condition,
+                branches: _,
+            }) => value_memories(condition),
+            Statement::MatchStatement(MatchStatement {
+                expression: (subject, _),
+                branches: _,
+                auxiliary_memory,
+            }) => value_memories(subject)
+                .into_iter()
+                .chain(std::iter::once(auxiliary_memory.clone()))
+                .collect(),
+            Statement::Enqueue(Enqueue(memory)) => vec![memory.clone()],
+            Statement::TailCall(TailCall { arguments }) => {
+                arguments.iter().map(|(memory, _)| memory.clone()).collect()
+            }
+        }
+    }
+    /// Mark the code for a statement with its memory so that its line can be found after emission.
+    fn annotate(&self, statement: &Statement, code: Code) -> Code {
+        if !self.debug_info {
+            return code;
+        }
+        let ids = Self::statement_memories(statement)
+            .into_iter()
+            .map(|memory| self.emit_memory(memory))
+            .join(",");
+        format!("{ANNOTATION_START}{ids}{ANNOTATION_END}{code}")
+    }
+    fn emit_statement(&self, statement: Statement, declared: &mut HashSet<Memory>) -> Code {
+        let annotation = statement.clone();
+        let code = match statement {
             Statement::Await(await_) => self.emit_await(await_),
             Statement::Assignment(assignment) => self.emit_assignment(assignment, &declared),
             Statement::IfStatement(if_statement) => {
@@ -317,7 +489,9 @@ impl Emitter {
                 self.emit_match_statement(match_statement, declared.clone())
             }
             Statement::Enqueue(enqueue) => self.emit_enqueue(enqueue),
-        }
+            Statement::TailCall(tail_call) => self.emit_tail_call(tail_call),
+        };
+        self.annotate(&annotation, code)
     }
     fn emit_statements(&self, statements: Vec<Statement>, mut declared: HashSet<Memory>) -> Code {
         let (forwarded, other_statements): (Vec<_>, Vec<_>) =
@@ -335,13 +509,19 @@ impl Emitter {
         // Add declarations before any other statements.
         let declarations_code = declarations
             .into_iter()
-            .map(|declaration| self.emit_declaration(declaration, &mut declared))
+            .map(|declaration| {
+                let code = self.emit_declaration(declaration.clone(), &mut declared);
+                self.annotate(&declaration.into(), code)
+            })
             .join("\n");
 
         // Add allocations after declarations but before any other statements.
         let (allocation_codes, allocations): (Vec<_>, Vec<_>) = allocations
             .into_iter()
-            .map(|allocation| self.emit_allocation(allocation))
+            .map(|allocation| {
+                let (code, allocations) = self.emit_allocation(allocation.clone());
+                (self.annotate(&allocation.into(), code), allocations)
+            })
             .unzip();
         let allocations_code = allocation_codes.join("\n");
         let allocations: HashMap<Memory, (Code, usize)> =
@@ -385,8 +565,20 @@ impl Emitter {
         let name = fn_def.name;
         let return_type = fn_def.ret.1;
         let declared = HashSet::new();
+        let is_loop = Self::contains_tail_call(&fn_def.statements);
         let statements_code = self.emit_statements(fn_def.statements, declared);
         let return_code = format!("return ensure_lazy({});", self.emit_value(fn_def.ret.0));
+        let trace_code = if self.debug {
+            // Trace each call to the stderr.
+            format!("std::cerr << \"trace: {name}\\n\";")
+        } else {
+            Code::new()
+        };
+        let body_code = if is_loop {
+            format!("{trace_code} while (true) {{ {statements_code} {return_code} }}")
+        } else {
+            format!("{trace_code} {statements_code} {return_code}")
+        };
         let external_types = &std::iter::once(return_type.clone())
             .chain(fn_def.arguments.iter().map(|(_, type_)| type_.clone()))
             .map(|type_| self.emit_type(&type_))
@@ -440,7 +632,7 @@ impl Emitter {
             "static std::unique_ptr<TypedFnI<{external_types}>> init(const ArgsT &args{env_ptr}) {{ return std::make_unique<{name}>({replication_args}); }}"
         );
         format!(
-            "{declaration_code} {{ {constructor_code} {header_code} {{ {statements_code} {return_code} }} {size_code} {recursive_code} {initialization_code} {instance} }};"
+            "{declaration_code} {{ {constructor_code} {header_code} {{ {body_code} }} {size_code} {recursive_code} {initialization_code} {instance} }};"
         )
     }
     fn emit_fn_defs(&self, fn_defs: Vec<FnDef>) -> Code {
@@ -449,15 +641,84 @@ impl Emitter {
             .map(|fn_def| self.emit_fn_def(fn_def))
             .join("\n")
     }
+    /// Define `print_result`, which `main` uses to print the result of the program.
+    fn emit_print_result(&self) -> Code {
+        let integer_code = match self.output_radix {
+            OutputRadix::Dec => return String::from("template <typename T> void print_result(std::ostream &os, const T &result) { os << result; }"),
+            OutputRadix::Hex => "os << \"0x\" << std::hex << result->value() << std::dec;",
+            OutputRadix::Bin => "os << \"0b\" << binary_digits(result->value());",
+        };
+        format!("template <typename T> void print_result(std::ostream &os, const T &result) {{ if constexpr (std::is_same_v<remove_lazy_t<T>, Int>) {{ {integer_code} }} else {{ os << result; }} }}")
+    }
     fn emit_program(&self, program: Program) -> Code {
         let type_def_code = self.emit_type_defs(program.type_defs);
         let fn_def_code = self.emit_fn_defs(program.fn_defs);
+        let print_result_code = self.emit_print_result();
         // Add header with all libraries.
-        format!("#include \"main/include.hpp\"\n\n{type_def_code} {fn_def_code}")
+        format!(
+            "#include \"main/include.hpp\"\n\n{type_def_code} {fn_def_code}\n{print_result_code}"
+        )
     }
-    pub fn emit(program: Program) -> Code {
-        let emitter = Emitter {};
-        emitter.emit_program(program)
+}
+
+impl Backend for CppBackend {
+    fn emit(&self, program: Program) -> Code {
+        self.emit_program(program)
+    }
+}
+
+/// Emit programs with the backend for the selected target.
+pub struct Emitter {}
+
+impl Emitter {
+    fn backend(args: &EmissionArgs) -> Box<dyn Backend> {
+        match args.target {
+            Target::Cpp => Box::new(CppBackend {
+                debug: args.debug,
+                debug_info: args.debug_info,
+                checked_arithmetic: args.checked_arithmetic,
+                output_radix: args.output_radix,
+            }),
+        }
+    }
+    /// Remove annotations from emitted code and record the lines that they were on.
+    fn extract_source_map(code: Code) -> (Code, SourceMap) {
+        let mut source_map = SourceMap::new();
+        let code = code
+            .split('\n')
+            .enumerate()
+            .map(|(i, line)| {
+                let mut stripped = Code::new();
+                let mut rest = line;
+                while let Some((before, after)) = rest.split_once(ANNOTATION_START) {
+                    let (ids, after) = after
+                        .split_once(ANNOTATION_END)
+                        .expect("Annotation was not closed.");
+                    stripped.push_str(before);
+                    source_map.extend(
+                        ids.split(',')
+                            .filter(|id| !id.is_empty())
+                            .map(|id| (i + 1, Memory(Id::from(id)))),
+                    );
+                    rest = after;
+                }
+                stripped.push_str(rest);
+                stripped
+            })
+            .join("\n");
+        (code, source_map)
+    }
+    pub fn emit(program: Program, args: EmissionArgs) -> Code {
+        Self::emit_with_source_map(program, args).0
+    }
+    /// Emit a program, also returning the memory used on each line if `debug_info` is set.
+    pub fn emit_with_source_map(program: Program, args: EmissionArgs) -> (Code, SourceMap) {
+        let code = Self::backend(&args).emit(program);
+        if args.debug_info {
+            Self::extract_source_map(code)
+        } else {
+            (code, SourceMap::new())
+        }
     }
 }
 
@@ -469,10 +730,15 @@ mod tests {
     use regex::Regex;
     use test_case::test_case;
     use translation::{
-        Allocation, AtomicType, AtomicTypeEnum, Enqueue, FnType, Id, MatchBranch, Name,
+        Allocation, ArrayType, AtomicType, AtomicTypeEnum, Enqueue, FnType, Id, MatchBranch, Name,
     };
 
-    const EMITTER: Lazy<Emitter> = Lazy::new(|| Emitter {});
+    const EMITTER: Lazy<CppBackend> = Lazy::new(|| CppBackend {
+        debug: false,
+        debug_info: false,
+        checked_arithmetic: false,
+        output_radix: OutputRadix::Dec,
+    });
 
     /// Remove spaces between non-words for easier equality checking.
     fn normalize_code(code: Code) -> Code {
@@ -641,6 +907,11 @@ mod tests {
         "VariantT<Cons_Int,Nil_Int>";
         "list int type"
     )]
+    #[test_case(
+        ArrayType(Box::new(TupleType(vec![AtomicType(AtomicTypeEnum::INT).into(), AtomicType(AtomicTypeEnum::BOOL).into()]).into())).into(),
+        "ArrayT<TupleT<Int,Bool>>";
+        "array type"
+    )]
     fn test_type_emission(type_: MachineType, expected: &str) {
         let code = EMITTER.emit_type(&type_);
         let expected_code = Code::from(expected);
@@ -775,6 +1046,26 @@ mod tests {
         "Bool{false}";
         "false emission"
     )]
+    #[test_case(
+        Str{value: String::new()}.into(),
+        r#"Str("", 0ULL)"#;
+        "empty string emission"
+    )]
+    #[test_case(
+        Str{value: String::from("say \"hi\"\n")}.into(),
+        r#"Str("say \"hi\"\n", 9ULL)"#;
+        "escaped string emission"
+    )]
+    #[test_case(
+        Str{value: String::from("a\0é")}.into(),
+        r#"Str("a\000\303\251", 4ULL)"#;
+        "non-printable string emission"
+    )]
+    #[test_case(
+        Char{value: 233}.into(),
+        "Char{233U}";
+        "char emission"
+    )]
     #[test_case(
         BuiltIn::BuiltInFn(
             Name::from("Plus__BuiltIn"),
@@ -789,6 +1080,14 @@ mod tests {
         "make_lazy<decltype(Comparison_GE__BuiltIn_G)>(Comparison_GE__BuiltIn_G)";
         "builtin greater than or equal to emission"
     )]
+    #[test_case(
+        BuiltIn::GenericBuiltInFn(
+            Name::from("Array_Get__BuiltIn"),
+            vec![AtomicType(AtomicTypeEnum::BOOL).into()]
+        ),
+        "make_lazy<decltype(Array_Get__BuiltIn_G<Bool>)>(Array_Get__BuiltIn_G<Bool>)";
+        "generic builtin emission"
+    )]
     fn test_builtin_emission(value: BuiltIn, expected: &str) {
         let code = EMITTER.emit_builtin(value);
         let expected_code = Code::from(expected);
@@ -826,6 +1125,16 @@ mod tests {
         "Bool";
         "builtin boolean type"
     )]
+    #[test_case(
+        BuiltIn::Str(Str{value: String::from("abc")}).into(),
+        "Str";
+        "builtin string type"
+    )]
+    #[test_case(
+        BuiltIn::Char(Char{value: 97}).into(),
+        "Char";
+        "builtin char type"
+    )]
     fn test_value_type(value: Value, expected: &str) {
         let code = EMITTER.emit_value_type(&value);
         let expected_code = Code::from(expected);
@@ -928,6 +1237,24 @@ mod tests {
         "auto call = Plus__BuiltIn(extract_lazy(arg1), extract_lazy(arg2));";
         "built-in fn call"
     )]
+    #[test_case(
+        Assignment {
+            target: Memory(Id::from("length")),
+            value: FnCall{
+                fn_: BuiltIn::GenericBuiltInFn(
+                    Name::from("Array_Length__BuiltIn"),
+                    vec![AtomicType(AtomicTypeEnum::INT).into()]
+                ).into(),
+                fn_type: FnType(
+                    vec![ArrayType(Box::new(AtomicType(AtomicTypeEnum::INT).into())).into()],
+                    Box::new(AtomicType(AtomicTypeEnum::INT).into()),
+                ),
+                args: vec![Memory(Id::from("array")).into()]
+            }.into(),
+        },
+        "auto length = Array_Length__BuiltIn<Int>(extract_lazy(array));";
+        "generic built-in fn call"
+    )]
     #[test_case(
         Assignment {
             target: Memory(Id::from("res")),
@@ -1421,6 +1748,37 @@ mod tests {
         assert_eq_code(code, expected_code);
     }
 
+    #[test]
+    fn test_debug_match_statement_emission() {
+        let statements = vec![MatchStatement {
+            expression: (
+                Memory(Id::from("bull")).into(),
+                UnionType(vec![Name::from("Twoo"), Name::from("Faws")]),
+            ),
+            auxiliary_memory: Memory(Id::from("tmp")),
+            branches: vec![
+                MatchBranch {
+                    target: None,
+                    statements: Vec::new(),
+                },
+                MatchBranch {
+                    target: None,
+                    statements: Vec::new(),
+                },
+            ],
+        }
+        .into()];
+        let emitter = CppBackend {
+            debug: true,
+            debug_info: false,
+            checked_arithmetic: false,
+            output_radix: OutputRadix::Dec,
+        };
+        let code = emitter.emit_statements(statements, HashSet::new());
+        let expected_code = Code::from("auto tmp = extract_lazy(bull); assert(tmp.tag < 2ULL); switch (tmp.tag) { case 0ULL: { break; } case 1ULL: { break; }}");
+        assert_eq_code(code, expected_code);
+    }
+
     #[test_case(
         FnDef {
             env: Vec::new(),
@@ -1588,6 +1946,47 @@ mod tests {
         "struct Apply : TypedClosureI<TupleT<Int>,Int,FnT<Int,Int>,Int>{ using TypedClosureI<TupleT<Int>,Int,FnT<Int,Int>,Int>::TypedClosureI; LazyT<Int> body(LazyT<FnT<Int,Int>> &f, LazyT<Int> &x) override { auto y = fn_call(extract_lazy(f),x); return ensure_lazy(y);} constexpr std::size_t lower_size_bound() const override {return 150;}; constexpr std::size_t upper_size_bound() const override {return 150;}; constexpr bool is_recursive() const override {return true;}; static std::unique_ptr<TypedFnI<Int,FnT<Int,Int>,Int>> init(const ArgsT&args,const EnvT&env) {return std::make_unique<Apply>(args,env);}};";
         "higher order fn"
     )]
+    #[test_case(
+        FnDef{
+            env: Vec::new(),
+            name: Name::from("Loop"),
+            arguments: vec![
+                (Memory(Id::from("b")), AtomicType(AtomicTypeEnum::BOOL).into()),
+                (Memory(Id::from("x")), AtomicType(AtomicTypeEnum::INT).into()),
+            ],
+            statements: vec![
+                Declaration {
+                    type_: AtomicType(AtomicTypeEnum::INT).into(),
+                    memory: Memory(Id::from("res"))
+                }.into(),
+                Await(vec![Memory(Id::from("b"))]).into(),
+                IfStatement {
+                    condition: Memory(Id::from("b")).into(),
+                    branches: (
+                        vec![
+                            TailCall {
+                                arguments: vec![
+                                    (Memory(Id::from("b")), Boolean{value: false}.into()),
+                                    (Memory(Id::from("x")), Memory(Id::from("x")).into()),
+                                ]
+                            }.into()
+                        ],
+                        vec![
+                            Assignment {
+                                target: Memory(Id::from("res")),
+                                value: Value::from(Memory(Id::from("x"))).into(),
+                            }.into()
+                        ]
+                    )
+                }.into(),
+            ],
+            ret: (Memory(Id::from("res")).into(), AtomicType(AtomicTypeEnum::INT).into()),
+            size_bounds: (20, 30),
+            is_recursive: true
+        },
+        "struct Loop : TypedClosureI<Empty, Int, Bool, Int> { using TypedClosureI<Empty, Int, Bool, Int>::TypedClosureI; LazyT<Int> body(LazyT<Bool> &b, LazyT<Int> &x) override { while (true) { LazyT<Int> res; WorkManager::await(b); if (extract_lazy(b)) { std::tie(b, x) = std::make_tuple(ensure_lazy(Bool{false}), ensure_lazy(x)); continue; } else { res = ensure_lazy(x); } return ensure_lazy(res); } } constexpr std::size_t lower_size_bound() const override { return 20; }; constexpr std::size_t upper_size_bound() const override { return 30; }; constexpr bool is_recursive() const override { return true; }; static std::unique_ptr<TypedFnI<Int, Bool, Int>> init(const ArgsT &args) { return std::make_unique<Loop>(args); } static inline FnT<Int,Bool,Int>G = std::make_shared<TypedClosureG<Empty,Int,Bool,Int>>(init);};";
+        "tail recursive loop"
+    )]
     fn test_fn_def_emission(fn_def: FnDef, expected: &str) {
         let code = EMITTER.emit_fn_def(fn_def);
         let expected_code = Code::from(expected);
@@ -1668,12 +2067,154 @@ mod tests {
                 }
             ],
         },
-        "#include \"main/include.hpp\" struct Twoo; struct Faws; typedef VariantT<Twoo,Faws>Bull; struct Twoo {Empty value;}; struct Faws {Empty value;}; struct Main : TypedClosureI<Empty,Int> {using TypedClosureI<Empty,Int>::TypedClosureI; LazyT<Int> body() override { auto call = Plus__BuiltIn(extract_lazy(x),extract_lazy(y)); return ensure_lazy(call);} constexpr std::size_t lower_size_bound() const override { return 50; }; constexpr std::size_t upper_size_bound() const override { return 50; }; constexpr bool is_recursive() const override { return false; }; static std::unique_ptr<TypedFnI<Int>> init(const ArgsT &args) {return std::make_unique<Main>(args);} static inline FnT<Int>G = std::make_shared<TypedClosureG<Empty,Int>>(init);}; struct PreMain : TypedClosureI<Empty,Int> {using TypedClosureI<Empty,Int>::TypedClosureI; LazyT<Int> body() override { auto x = Int{9LL}; auto y = Int{5LL}; auto main = fn_call(extract_lazy(Main)); return ensure_lazy(main); } constexpr std::size_t lower_size_bound() const override { return 40; }; constexpr std::size_t upper_size_bound() const override { return 60; }; constexpr bool is_recursive() const override { return false; }; static std::unique_ptr<TypedFnI<Int>>init(const ArgsT&args) {return std::make_unique<PreMain>(args);} static inline FnT<Int> G = std::make_shared<TypedClosureG<Empty,Int>>(init);};";
+        "#include \"main/include.hpp\" struct Twoo; struct Faws; typedef VariantT<Twoo,Faws>Bull; struct Twoo {Empty value;}; struct Faws {Empty value;}; struct Main : TypedClosureI<Empty,Int> {using TypedClosureI<Empty,Int>::TypedClosureI; LazyT<Int> body() override { auto call = Plus__BuiltIn(extract_lazy(x),extract_lazy(y)); return ensure_lazy(call);} constexpr std::size_t lower_size_bound() const override { return 50; }; constexpr std::size_t upper_size_bound() const override { return 50; }; constexpr bool is_recursive() const override { return false; }; static std::unique_ptr<TypedFnI<Int>> init(const ArgsT &args) {return std::make_unique<Main>(args);} static inline FnT<Int>G = std::make_shared<TypedClosureG<Empty,Int>>(init);}; struct PreMain : TypedClosureI<Empty,Int> {using TypedClosureI<Empty,Int>::TypedClosureI; LazyT<Int> body() override { auto x = Int{9LL}; auto y = Int{5LL}; auto main = fn_call(extract_lazy(Main)); return ensure_lazy(main); } constexpr std::size_t lower_size_bound() const override { return 40; }; constexpr std::size_t upper_size_bound() const override { return 60; }; constexpr bool is_recursive() const override { return false; }; static std::unique_ptr<TypedFnI<Int>>init(const ArgsT&args) {return std::make_unique<PreMain>(args);} static inline FnT<Int> G = std::make_shared<TypedClosureG<Empty,Int>>(init);}; template <typename T> void print_result(std::ostream &os, const T &result) { os << result; }";
         "main program"
     )]
     fn test_program_emission(program: Program, expected: &str) {
-        let code = Emitter::emit(program);
+        let code = Emitter::emit(program, EmissionArgs::release_preset());
         let expected_code = Code::from(expected);
         assert_eq_code(code, expected_code);
     }
+
+    #[test]
+    fn test_source_map() {
+        let program = Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![FnDef {
+                env: Vec::new(),
+                name: Name::from("PreMain"),
+                arguments: Vec::new(),
+                statements: vec![
+                    Assignment {
+                        target: Memory(Id::from("x")),
+                        value: Expression::Value(Value::BuiltIn(Integer { value: 9 }.into())),
+                    }
+                    .into(),
+                    Assignment {
+                        target: Memory(Id::from("y")),
+                        value: Expression::Value(Value::BuiltIn(Integer { value: 5 }.into())),
+                    }
+                    .into(),
+                ],
+                ret: (
+                    Memory(Id::from("y")).into(),
+                    AtomicType(AtomicTypeEnum::INT).into(),
+                ),
+                size_bounds: (1, 1),
+                is_recursive: false,
+            }],
+        };
+        let (code, source_map) = Emitter::emit_with_source_map(
+            program.clone(),
+            EmissionArgs {
+                debug: false,
+                debug_info: true,
+                checked_arithmetic: false,
+                output_radix: OutputRadix::Dec,
+                target: Target::Cpp,
+            },
+        );
+        assert_eq!(code, Emitter::emit(program, EmissionArgs::release_preset()));
+        let lines = code.split('\n').collect_vec();
+        assert_eq!(
+            source_map
+                .iter()
+                .map(|(_, Memory(id))| id.clone())
+                .collect_vec(),
+            vec![Id::from("x"), Id::from("y")]
+        );
+        for (line, Memory(id)) in source_map {
+            assert!(lines[line - 1].contains(&format!("auto {id} =")));
+        }
+    }
+    #[test]
+    fn test_checked_arithmetic() {
+        let int: MachineType = AtomicType(AtomicTypeEnum::INT).into();
+        let binary_type = FnType(vec![int.clone(), int.clone()], Box::new(int.clone()));
+        let call = |target: &str, name: &str| -> Statement {
+            Assignment {
+                target: Memory(Id::from(target)),
+                value: FnCall {
+                    fn_: BuiltIn::BuiltInFn(Name::from(name)).into(),
+                    fn_type: binary_type.clone(),
+                    args: vec![Memory(Id::from("x")).into(), Memory(Id::from("y")).into()],
+                }
+                .into(),
+            }
+            .into()
+        };
+        let program = Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![FnDef {
+                env: Vec::new(),
+                name: Name::from("PreMain"),
+                arguments: vec![
+                    (Memory(Id::from("x")), int.clone()),
+                    (Memory(Id::from("y")), int.clone()),
+                ],
+                statements: vec![
+                    call("sum", "Plus__BuiltIn"),
+                    call("quotient", "Divide__BuiltIn"),
+                    Assignment {
+                        target: Memory(Id::from("product")),
+                        value: Expression::Value(
+                            BuiltIn::BuiltInFn(Name::from("Multiply__BuiltIn")).into(),
+                        ),
+                    }
+                    .into(),
+                ],
+                ret: (Memory(Id::from("sum")).into(), int),
+                size_bounds: (1, 1),
+                is_recursive: false,
+            }],
+        };
+        let unchecked = Emitter::emit(program.clone(), EmissionArgs::release_preset());
+        let checked = Emitter::emit(
+            program,
+            EmissionArgs {
+                checked_arithmetic: true,
+                ..EmissionArgs::release_preset()
+            },
+        );
+        assert!(!unchecked.contains("Checked_"));
+        assert!(checked.contains("Checked_Plus__BuiltIn(extract_lazy(x),extract_lazy(y))"));
+        assert!(checked.contains(
+            "make_lazy<decltype(Checked_Multiply__BuiltIn_G)>(Checked_Multiply__BuiltIn_G)"
+        ));
+        assert!(!checked.contains("Checked_Divide__BuiltIn"));
+        assert_eq!(checked.replace("Checked_", ""), unchecked);
+    }
+
+    #[test_case(OutputRadix::Dec, None; "decimal")]
+    #[test_case(OutputRadix::Hex, Some("os << \"0x\" << std::hex"); "hexadecimal")]
+    #[test_case(OutputRadix::Bin, Some("os << \"0b\" << binary_digits"); "binary")]
+    fn test_output_radix(output_radix: OutputRadix, integer_code: Option<&str>) {
+        let program = Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![FnDef {
+                env: Vec::new(),
+                name: Name::from("PreMain"),
+                arguments: Vec::new(),
+                statements: Vec::new(),
+                ret: (
+                    Value::BuiltIn(Integer { value: 255 }.into()),
+                    AtomicType(AtomicTypeEnum::INT).into(),
+                ),
+                size_bounds: (1, 1),
+                is_recursive: false,
+            }],
+        };
+        let code = Emitter::emit(
+            program,
+            EmissionArgs {
+                output_radix,
+                ..EmissionArgs::release_preset()
+            },
+        );
+        assert!(code.contains("void print_result(std::ostream &os, const T &result)"));
+        match integer_code {
+            Some(integer_code) => assert!(code.contains(integer_code)),
+            None => assert!(!code.contains("0x") && !code.contains("0b")),
+        }
+    }
 }
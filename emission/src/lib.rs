@@ -1,4 +1,8 @@
+mod args;
+mod backend;
 mod emission;
 mod type_formatter;
 
-pub use emission::Emitter;
+pub use args::EmissionArgs;
+pub use backend::{Backend, OutputRadix, Target};
+pub use emission::{CppBackend, Emitter, SourceMap};
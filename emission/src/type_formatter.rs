@@ -1,6 +1,8 @@
 use std::fmt::{self, Formatter};
 
-use translation::{AtomicType, AtomicTypeEnum, FnType, MachineType, TupleType, UnionType};
+use translation::{
+    ArrayType, AtomicType, AtomicTypeEnum, FnType, MachineType, TupleType, UnionType,
+};
 
 /// Convert type to C++ code.
 pub struct TypeFormatter<'a>(pub &'a MachineType);
@@ -10,6 +12,8 @@ impl fmt::Display for TypeFormatter<'_> {
             MachineType::AtomicType(AtomicType(atomic)) => match atomic {
                 AtomicTypeEnum::INT => write!(f, "Int"),
                 AtomicTypeEnum::BOOL => write!(f, "Bool"),
+                AtomicTypeEnum::STRING => write!(f, "Str"),
+                AtomicTypeEnum::CHAR => write!(f, "Char"),
             },
             MachineType::TupleType(TupleType(types)) => {
                 write!(f, "TupleT<{}>", TypesFormatter(types))
@@ -33,6 +37,9 @@ impl fmt::Display for TypeFormatter<'_> {
             MachineType::UnionType(UnionType(type_names)) => {
                 write!(f, "VariantT<{}>", type_names.join(","))
             }
+            MachineType::ArrayType(ArrayType(type_)) => {
+                write!(f, "ArrayT<{}>", TypeFormatter(type_))
+            }
             MachineType::NamedType(name) => write!(f, "{}", name),
         }
     }
@@ -1,45 +1,140 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use quote::{quote, ToTokens};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Generics, Ident, Type, Variant};
 
-#[proc_macro_derive(FromVariants)]
+#[proc_macro_derive(FromVariants, attributes(from))]
 /// Automatically generate a `.into()` methods for all structs that share their field name.
+/// Variants marked with `#[from]` generate a method for their single field regardless of its name.
 pub fn derive_from_variants(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
+    expand_from_variants(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let mut implementations = Vec::new();
+fn expand_from_variants(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let data_enum = match input.data {
+        Data::Enum(data_enum) => data_enum,
+        Data::Struct(data_struct) => {
+            return Err(syn::Error::new_spanned(
+                data_struct.struct_token,
+                "This macro only works on enums",
+            ))
+        }
+        Data::Union(data_union) => {
+            return Err(syn::Error::new_spanned(
+                data_union.union_token,
+                "This macro only works on enums",
+            ))
+        }
+    };
 
-    if let Data::Enum(data_enum) = input.data {
-        for variant in data_enum.variants {
-            let variant_name = variant.ident;
-
-            if let Fields::Unnamed(fields) = variant.fields {
-                // Check for a single unnamed field.
-                if fields.unnamed.len() == 1 {
-                    let field_type = &fields.unnamed[0].ty;
-                    let field_type_name = quote!(#field_type).to_string();
-
-                    // Check the field matches the type.
-                    if field_type_name == variant_name.to_string() {
-                        implementations.push(quote! {
-                            impl From<#field_type> for #name {
-                                fn from(value: #field_type) -> Self {
-                                    #name::#variant_name(value)
-                                }
-                            }
-                        });
+    let mut implementations = Vec::new();
+    // Field types are compared by their tokens.
+    let mut field_types: Vec<String> = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for variant in &data_enum.variants {
+        let result =
+            implement_variant(&input.ident, &input.generics, variant).and_then(|implementation| {
+                match implementation {
+                    // Two variants with the same field type would generate conflicting implementations.
+                    Some((field_type, _))
+                        if field_types.contains(&field_type.to_token_stream().to_string()) =>
+                    {
+                        Err(syn::Error::new_spanned(
+                            field_type,
+                            "Another variant already converts from this field type",
+                        ))
                     }
+                    _ => Ok(implementation),
                 }
+            });
+        match result {
+            Ok(Some((field_type, implementation))) => {
+                field_types.push(field_type.to_token_stream().to_string());
+                implementations.push(implementation);
             }
+            Ok(None) => {}
+            // Report every invalid variant at once.
+            Err(error) => match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            },
         }
-    } else {
-        panic!("This macro only works on enums");
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
     }
 
-    let output = quote! {
+    Ok(quote! {
         #(#implementations)*
+    })
+}
+
+/// Determine whether a variant is marked with `#[from]`.
+fn is_marked(variant: &Variant) -> syn::Result<bool> {
+    let mut marked = false;
+    for attr in &variant.attrs {
+        if attr.path().is_ident("from") {
+            attr.meta.require_path_only()?;
+            marked = true;
+        }
+    }
+    Ok(marked)
+}
+
+/// Generate the `From` implementation for a single variant, if it has one, along with the type it converts from.
+fn implement_variant<'a>(
+    name: &Ident,
+    generics: &Generics,
+    variant: &'a Variant,
+) -> syn::Result<Option<(&'a Type, proc_macro2::TokenStream)>> {
+    let variant_name = &variant.ident;
+    let marked = is_marked(variant)?;
+
+    // Check for a single field.
+    if variant.fields.len() != 1 {
+        return if marked {
+            Err(syn::Error::new_spanned(
+                variant,
+                "`#[from]` variants must have exactly one field",
+            ))
+        } else {
+            Ok(None)
+        };
+    }
+    let field = variant.fields.iter().next().unwrap();
+    let field_type = &field.ty;
+
+    if marked {
+        if let Type::Verbatim(_) | Type::Infer(_) | Type::ImplTrait(_) = field_type {
+            return Err(syn::Error::new_spanned(
+                field_type,
+                "Unable to generate a conversion from this field type",
+            ));
+        }
+    }
+
+    // Check the field matches the type or the variant is marked.
+    let matches_name = matches!(field_type, Type::Path(type_path) if type_path.qself.is_none() && type_path.path.is_ident(variant_name));
+    let constructor = match &variant.fields {
+        Fields::Unnamed(_) if marked || matches_name => quote!(#name::#variant_name(value)),
+        Fields::Named(_) if marked => {
+            let field_name = &field.ident;
+            quote!(#name::#variant_name { #field_name: value })
+        }
+        _ => return Ok(None),
     };
 
-    output.into()
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(Some((
+        field_type,
+        quote! {
+            impl #impl_generics From<#field_type> for #name #type_generics #where_clause {
+                fn from(value: #field_type) -> Self {
+                    #constructor
+                }
+            }
+        },
+    )))
 }
@@ -0,0 +1,44 @@
+use from_variants::FromVariants;
+
+#[derive(Debug, PartialEq)]
+struct Leaf;
+
+#[derive(FromVariants, Debug, PartialEq)]
+enum Tree<T>
+where
+    T: Clone,
+{
+    Leaf(Leaf),
+    #[from]
+    Node(Box<T>),
+    #[from]
+    Label {
+        name: String,
+    },
+}
+
+#[test]
+fn test_matching_variant() {
+    assert_eq!(Tree::<i64>::from(Leaf), Tree::Leaf(Leaf));
+}
+
+#[test]
+fn test_marked_unnamed_variant() {
+    assert_eq!(Tree::from(Box::new(5)), Tree::Node(Box::new(5)));
+}
+
+#[test]
+fn test_marked_named_variant() {
+    assert_eq!(
+        Tree::<i64>::from(String::from("root")),
+        Tree::Label {
+            name: String::from("root")
+        }
+    );
+}
+
+#[test]
+fn test_compile_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,11 @@
+use from_variants::FromVariants;
+
+#[derive(FromVariants)]
+enum Value {
+    #[from]
+    Count(i64),
+    #[from]
+    Total { amount: i64 },
+}
+
+fn main() {}
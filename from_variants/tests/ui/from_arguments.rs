@@ -0,0 +1,9 @@
+use from_variants::FromVariants;
+
+#[derive(FromVariants)]
+enum Value {
+    #[from(i64)]
+    Count(i64),
+}
+
+fn main() {}
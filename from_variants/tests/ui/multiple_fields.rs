@@ -0,0 +1,11 @@
+use from_variants::FromVariants;
+
+#[derive(FromVariants)]
+enum Value {
+    #[from]
+    Pair(i64, i64),
+    #[from]
+    Empty,
+}
+
+fn main() {}
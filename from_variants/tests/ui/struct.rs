@@ -0,0 +1,6 @@
+use from_variants::FromVariants;
+
+#[derive(FromVariants)]
+struct Wrapper(i64);
+
+fn main() {}
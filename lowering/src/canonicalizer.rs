@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::{
+    IntermediateArg, IntermediateAssignment, IntermediateBlock, IntermediateCtorCall,
+    IntermediateElementAccess, IntermediateExpression, IntermediateFnCall, IntermediateIf,
+    IntermediateLambda, IntermediateMatch, IntermediateMatchBranch, IntermediateMemory,
+    IntermediateProgram, IntermediateStatement, IntermediateTupleExpression, IntermediateValue,
+    Register,
+};
+
+/// Renumber registers in the order in which they are first seen.
+pub struct Canonicalizer {
+    registers: HashMap<Register, Register>,
+}
+
+impl Canonicalizer {
+    fn canonicalize_register(&mut self, register: &mut Register) {
+        let next = Register(self.registers.len());
+        *register = self
+            .registers
+            .entry(register.clone())
+            .or_insert(next)
+            .clone();
+    }
+    fn canonicalize_args(&mut self, args: &mut Vec<IntermediateArg>) {
+        for IntermediateArg { type_: _, register } in args {
+            self.canonicalize_register(register);
+        }
+    }
+    fn canonicalize_value(&mut self, value: &mut IntermediateValue) {
+        match value {
+            IntermediateValue::IntermediateBuiltIn(_) => {}
+            IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register })
+            | IntermediateValue::IntermediateArg(IntermediateArg { type_: _, register }) => {
+                self.canonicalize_register(register)
+            }
+        }
+    }
+    fn canonicalize_values(&mut self, values: &mut Vec<IntermediateValue>) {
+        for value in values {
+            self.canonicalize_value(value);
+        }
+    }
+    fn canonicalize_expression(&mut self, expression: &mut IntermediateExpression) {
+        match expression {
+            IntermediateExpression::IntermediateValue(value)
+            | IntermediateExpression::IntermediateElementAccess(IntermediateElementAccess {
+                value,
+                idx: _,
+            }) => self.canonicalize_value(value),
+            IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
+                values,
+            )) => self.canonicalize_values(values),
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_, args }) => {
+                self.canonicalize_value(fn_);
+                self.canonicalize_values(args);
+            }
+            IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
+                idx: _,
+                data,
+                type_: _,
+            }) => {
+                if let Some(data) = data {
+                    self.canonicalize_value(data);
+                }
+            }
+            IntermediateExpression::IntermediateLambda(lambda) => self.canonicalize_lambda(lambda),
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => {
+                self.canonicalize_value(condition);
+                self.canonicalize_block(&mut branches.0);
+                self.canonicalize_block(&mut branches.1);
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch { subject, branches }) => {
+                self.canonicalize_value(subject);
+                for IntermediateMatchBranch { target, block } in branches {
+                    if let Some(IntermediateArg { type_: _, register }) = target {
+                        self.canonicalize_register(register);
+                    }
+                    self.canonicalize_block(block);
+                }
+            }
+        }
+    }
+    fn canonicalize_block(&mut self, block: &mut IntermediateBlock) {
+        for statement in &mut block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            self.canonicalize_register(register);
+            self.canonicalize_expression(expression);
+        }
+        self.canonicalize_value(&mut block.ret);
+    }
+    fn canonicalize_lambda(&mut self, lambda: &mut IntermediateLambda) {
+        self.canonicalize_args(&mut lambda.args);
+        self.canonicalize_block(&mut lambda.block);
+    }
+}
+
+impl IntermediateProgram {
+    /// Rename registers to `0, 1, 2, ...` from left to right so that lowering the same program always gives the same output.
+    /// The new registers may clash with ones in other programs, so only use this for comparing or printing programs.
+    pub fn canonicalize(&mut self) {
+        let mut canonicalizer = Canonicalizer {
+            registers: HashMap::new(),
+        };
+        canonicalizer.canonicalize_lambda(&mut self.main);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use type_checker::{
+        ParametricType, TypeDefinitions, TypedAccess, TypedAssignment, TypedBlock, TypedExpression,
+        TypedFunctionCall, TypedLambdaDef, TypedProgram, TypedVariable, TYPE_INT,
+    };
+
+    use crate::Lowerer;
+
+    /// `main(arg: int) -> int { id = (x: int) -> int { x }; id(arg) }`
+    fn identity_program() -> TypedProgram {
+        let variable = || -> TypedVariable {
+            ParametricType {
+                type_: TYPE_INT,
+                parameters: Vec::new(),
+            }
+            .into()
+        };
+        let access = |variable: &TypedVariable| -> TypedExpression {
+            TypedAccess {
+                variable: variable.clone(),
+                parameters: Vec::new(),
+            }
+            .into()
+        };
+        let id = variable();
+        let x = variable();
+        let arg = variable();
+        TypedProgram {
+            type_definitions: TypeDefinitions::new(),
+            main: TypedLambdaDef {
+                parameters: vec![arg.clone()],
+                body: TypedBlock {
+                    statements: vec![TypedAssignment {
+                        variable: id.clone(),
+                        expression: TypedExpression::from(TypedLambdaDef {
+                            parameters: vec![x.clone()],
+                            return_type: Box::new(TYPE_INT),
+                            body: TypedBlock {
+                                statements: Vec::new(),
+                                expression: Box::new(access(&x)),
+                            },
+                        })
+                        .into(),
+                    }
+                    .into()],
+                    expression: Box::new(
+                        TypedFunctionCall {
+                            function: Box::new(access(&id)),
+                            arguments: vec![access(&arg)],
+                        }
+                        .into(),
+                    ),
+                },
+                return_type: Box::new(TYPE_INT),
+            },
+            docs: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_independent_lowerings() {
+        let mut first = Lowerer::lower(identity_program());
+        let mut second = Lowerer::lower(identity_program());
+        assert_ne!(first.pretty(), second.pretty());
+        first.canonicalize();
+        second.canonicalize();
+        assert_eq!(first.pretty(), second.pretty());
+        assert_eq!(first.main, second.main);
+    }
+}
@@ -72,11 +72,13 @@ impl CopyPropagator {
             IntermediateExpression::IntermediateElementAccess(IntermediateElementAccess {
                 value,
                 idx,
-            }) => IntermediateElementAccess {
-                value: self.propagate_copies_in_value(value),
-                idx,
+            }) => {
+                let value = self.propagate_copies_in_value(value);
+                match self.tuple_element(&value, idx) {
+                    Some(element) => self.propagate_copies_in_value(element).into(),
+                    None => IntermediateElementAccess { value, idx }.into(),
+                }
             }
-            .into(),
             IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
                 values,
             )) => IntermediateTupleExpression(self.propagate_copies_in_values(values)).into(),
@@ -144,6 +146,16 @@ impl CopyPropagator {
                             // Inline value recursively.
                             self.propagate_copies_in_value(value.clone())
                         }
+                        IntermediateExpression::IntermediateElementAccess(
+                            IntermediateElementAccess { value, idx },
+                        ) => {
+                            // Inline accesses into known tuples recursively.
+                            let value = self.propagate_copies_in_value(value.clone());
+                            match self.tuple_element(&value, *idx) {
+                                Some(element) => self.propagate_copies_in_value(element),
+                                None => memory.into(),
+                            }
+                        }
                         _ => memory.into(),
                     }
                 } else {
@@ -152,6 +164,20 @@ impl CopyPropagator {
             }
         }
     }
+    /// Find the value of an element of a tuple that is assigned to memory.
+    fn tuple_element(&self, value: &IntermediateValue, idx: usize) -> Option<IntermediateValue> {
+        let IntermediateValue::IntermediateMemory(IntermediateMemory { register, type_: _ }) =
+            value
+        else {
+            return None;
+        };
+        match self.memory.get(register) {
+            Some(IntermediateExpression::IntermediateTupleExpression(
+                IntermediateTupleExpression(values),
+            )) => values.get(idx).cloned(),
+            _ => None,
+        }
+    }
     pub fn propagate_copies_in_values(
         &self,
         values: Vec<IntermediateValue>,
@@ -175,6 +201,16 @@ impl CopyPropagator {
                 if matches!(&expression, IntermediateExpression::IntermediateValue(_)) {
                     return None;
                 }
+                // Remove accesses into known tuples, which are replaced by the element.
+                if let IntermediateExpression::IntermediateElementAccess(
+                    IntermediateElementAccess { value, idx },
+                ) = &expression
+                {
+                    let value = self.propagate_copies_in_value(value.clone());
+                    if self.tuple_element(&value, *idx).is_some() {
+                        return None;
+                    }
+                }
                 let condensed_expression = self.propagate_copies_in_expression(expression.clone());
                 let expression = condensed_expression;
                 Some(IntermediateStatement::IntermediateAssignment(
@@ -206,3 +242,180 @@ impl CopyPropagator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Id, Integer};
+    use test_case::test_case;
+    use type_checker::AtomicTypeEnum;
+
+    #[test_case(
+        {
+            let a = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let b = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let tuple_type = IntermediateType::from(IntermediateTupleType(vec![
+                AtomicTypeEnum::INT.into(),
+                AtomicTypeEnum::BOOL.into(),
+            ]));
+            let t = IntermediateMemory::from(tuple_type.clone());
+            let x = IntermediateMemory::from(tuple_type);
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let tuple = IntermediateAssignment {
+                register: t.register.clone(),
+                expression: IntermediateTupleExpression(vec![a.clone().into(), b.into()]).into(),
+            };
+            (
+                IntermediateBlock {
+                    statements: vec![
+                        tuple.clone().into(),
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: IntermediateValue::from(t).into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: y.register.clone(),
+                            expression: IntermediateElementAccess {
+                                value: x.into(),
+                                idx: 0,
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: y.into(),
+                },
+                IntermediateBlock {
+                    statements: vec![tuple.into()],
+                    ret: a.into(),
+                },
+            )
+        };
+        "access through copy"
+    )]
+    #[test_case(
+        {
+            let a = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let tuple_type = IntermediateType::from(IntermediateTupleType(vec![
+                AtomicTypeEnum::INT.into(),
+                AtomicTypeEnum::INT.into(),
+            ]));
+            let t = IntermediateMemory::from(tuple_type.clone());
+            let x = IntermediateMemory::from(tuple_type);
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let z = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let tuple = IntermediateAssignment {
+                register: t.register.clone(),
+                expression: IntermediateTupleExpression(vec![
+                    Integer { value: 3 }.into(),
+                    a.clone().into(),
+                ])
+                .into(),
+            };
+            let negate = BuiltInFn(
+                Id::from("-"),
+                IntermediateFnType(
+                    vec![AtomicTypeEnum::INT.into()],
+                    Box::new(AtomicTypeEnum::INT.into()),
+                ),
+            );
+            (
+                IntermediateBlock {
+                    statements: vec![
+                        tuple.clone().into(),
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: IntermediateValue::from(t).into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: y.register.clone(),
+                            expression: IntermediateElementAccess {
+                                value: x.into(),
+                                idx: 1,
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: z.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: negate.clone().into(),
+                                args: vec![y.into()],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: z.clone().into(),
+                },
+                IntermediateBlock {
+                    statements: vec![
+                        tuple.into(),
+                        IntermediateAssignment {
+                            register: z.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: negate.into(),
+                                args: vec![a.into()],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: z.into(),
+                },
+            )
+        };
+        "access used as argument"
+    )]
+    #[test_case(
+        {
+            let t = IntermediateArg::from(IntermediateType::from(IntermediateTupleType(vec![
+                AtomicTypeEnum::INT.into(),
+            ])));
+            let x = IntermediateMemory::from(t.type_.clone());
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let access = IntermediateAssignment {
+                register: y.register.clone(),
+                expression: IntermediateElementAccess {
+                    value: t.clone().into(),
+                    idx: 0,
+                }
+                .into(),
+            };
+            (
+                IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: IntermediateValue::from(t).into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: y.register.clone(),
+                            expression: IntermediateElementAccess {
+                                value: x.into(),
+                                idx: 0,
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: y.clone().into(),
+                },
+                IntermediateBlock {
+                    statements: vec![access.into()],
+                    ret: y.into(),
+                },
+            )
+        };
+        "access into unknown tuple"
+    )]
+    fn test_propagate_element_accesses(blocks: (IntermediateBlock, IntermediateBlock)) {
+        let (block, expected) = blocks;
+        let copy_propagator = CopyPropagator::from_statements(&block.statements);
+        assert_eq!(copy_propagator.propagate_copies_in_block(block), expected);
+    }
+}
@@ -0,0 +1,286 @@
+use itertools::{
+    Either::{Left, Right},
+    EitherOrBoth::{Both, Left as OnlyLeft, Right as OnlyRight},
+    Itertools,
+};
+
+use crate::{
+    ExpressionEqualityChecker, IntermediateAssignment, IntermediateExpression, IntermediateLambda,
+    IntermediateProgram, IntermediateStatement, Register,
+};
+
+/// A statement that differs between two programs.
+/// `function` is the register that the enclosing fn is assigned to in the first program (after canonical renumbering), or `None` for `main`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difference {
+    Added {
+        function: Option<Register>,
+        statement: IntermediateStatement,
+    },
+    Removed {
+        function: Option<Register>,
+        statement: IntermediateStatement,
+    },
+    Changed {
+        function: Option<Register>,
+        before: Box<IntermediateStatement>,
+        after: Box<IntermediateStatement>,
+    },
+}
+
+/// Alignment of a statement from the first and/or second block.
+enum Step {
+    Matched,
+    Removed(usize),
+    Added(usize),
+}
+
+struct Differ {
+    differences: Vec<Difference>,
+}
+
+impl Differ {
+    fn expression(statement: &IntermediateStatement) -> &IntermediateExpression {
+        let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            expression,
+            register: _,
+        }) = statement;
+        expression
+    }
+    /// Align statements with a longest common subsequence of equal expressions.
+    fn align(s1: &[IntermediateStatement], s2: &[IntermediateStatement]) -> Vec<Step> {
+        let equal = s1
+            .iter()
+            .map(|x| {
+                s2.iter()
+                    .map(|y| {
+                        ExpressionEqualityChecker::equal(Self::expression(x), Self::expression(y))
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        // Lengths of the longest common subsequences of the suffixes.
+        let mut lengths = vec![vec![0; s2.len() + 1]; s1.len() + 1];
+        for i in (0..s1.len()).rev() {
+            for j in (0..s2.len()).rev() {
+                lengths[i][j] = if equal[i][j] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        let mut steps = Vec::new();
+        while i < s1.len() || j < s2.len() {
+            if i < s1.len() && j < s2.len() && equal[i][j] {
+                steps.push(Step::Matched);
+                i += 1;
+                j += 1;
+            } else if j == s2.len() || (i < s1.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+                steps.push(Step::Removed(i));
+                i += 1;
+            } else {
+                steps.push(Step::Added(j));
+                j += 1;
+            }
+        }
+        steps
+    }
+    fn diff_statements(
+        &mut self,
+        function: Option<Register>,
+        s1: &[IntermediateStatement],
+        s2: &[IntermediateStatement],
+    ) {
+        let steps = Self::align(s1, s2);
+        for (is_matched, gap) in &steps
+            .into_iter()
+            .chunk_by(|step| matches!(step, Step::Matched))
+        {
+            if is_matched {
+                continue;
+            }
+            let (removed, added): (Vec<_>, Vec<_>) = gap.partition_map(|step| match step {
+                Step::Removed(i) => Left(&s1[i]),
+                Step::Added(j) => Right(&s2[j]),
+                Step::Matched => unreachable!(),
+            });
+            // Statements removed and added in the same place are reported as changes.
+            for pair in removed.into_iter().zip_longest(added) {
+                match pair {
+                    Both(before, after) => self.diff_statement(function.clone(), before, after),
+                    OnlyLeft(statement) => self.differences.push(Difference::Removed {
+                        function: function.clone(),
+                        statement: statement.clone(),
+                    }),
+                    OnlyRight(statement) => self.differences.push(Difference::Added {
+                        function: function.clone(),
+                        statement: statement.clone(),
+                    }),
+                }
+            }
+        }
+    }
+    fn diff_statement(
+        &mut self,
+        function: Option<Register>,
+        before: &IntermediateStatement,
+        after: &IntermediateStatement,
+    ) {
+        let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            expression: e1,
+            register,
+        }) = before;
+        let differences = self.differences.len();
+        if let (
+            IntermediateExpression::IntermediateLambda(IntermediateLambda {
+                args: a1,
+                block: b1,
+            }),
+            IntermediateExpression::IntermediateLambda(IntermediateLambda {
+                args: a2,
+                block: b2,
+            }),
+        ) = (e1, Self::expression(after))
+        {
+            // Report the changes inside fns with the same signature.
+            if a1.len() == a2.len() {
+                self.diff_statements(Some(register.clone()), &b1.statements, &b2.statements);
+            }
+        }
+        if self.differences.len() == differences {
+            self.differences.push(Difference::Changed {
+                function,
+                before: Box::new(before.clone()),
+                after: Box::new(after.clone()),
+            });
+        }
+    }
+}
+
+/// Find the statements that were added, removed or changed in each fn from `a` to `b`, ignoring register names.
+/// This is intended for debugging optimizations and testing.
+pub fn diff(a: &IntermediateProgram, b: &IntermediateProgram) -> Vec<Difference> {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    a.canonicalize();
+    b.canonicalize();
+    let mut differ = Differ {
+        differences: Vec::new(),
+    };
+    differ.diff_statements(None, &a.main.block.statements, &b.main.block.statements);
+    differ.differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        test_utils::program, BuiltInFn, Id, Integer, IntermediateFnCall, IntermediateFnType,
+        IntermediateMemory, IntermediateType, IntermediateValue,
+    };
+    use test_case::test_case;
+    use type_checker::AtomicTypeEnum;
+
+    /// `memory = ++value`
+    fn increment(memory: &IntermediateMemory, value: IntermediateValue) -> IntermediateStatement {
+        IntermediateAssignment {
+            register: memory.register.clone(),
+            expression: IntermediateFnCall {
+                fn_: BuiltInFn(
+                    Id::from("++"),
+                    IntermediateFnType(
+                        vec![AtomicTypeEnum::INT.into()],
+                        Box::new(AtomicTypeEnum::INT.into()),
+                    ),
+                )
+                .into(),
+                args: vec![value],
+            }
+            .into(),
+        }
+        .into()
+    }
+
+    /// Build `main` from statements that assign to fresh memories, returning the first memory.
+    fn memory_program(
+        statements: impl Fn(&[IntermediateMemory]) -> Vec<IntermediateStatement>,
+    ) -> IntermediateProgram {
+        let memories = (0..4)
+            .map(|_| IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT)))
+            .collect_vec();
+        program(
+            Vec::new(),
+            statements(&memories),
+            memories[0].clone().into(),
+        )
+    }
+
+    fn original(memories: &[IntermediateMemory]) -> Vec<IntermediateStatement> {
+        vec![
+            increment(&memories[0], Integer { value: 1 }.into()),
+            increment(&memories[1], memories[0].clone().into()),
+            increment(&memories[2], Integer { value: 2 }.into()),
+        ]
+    }
+
+    #[test_case(
+        original,
+        Vec::new();
+        "unchanged"
+    )]
+    #[test_case(
+        |memories| vec![
+            increment(&memories[0], Integer { value: 1 }.into()),
+            increment(&memories[2], Integer { value: 2 }.into()),
+        ],
+        vec!["removed"];
+        "removed statement"
+    )]
+    #[test_case(
+        |memories| {
+            let mut statements = original(memories);
+            statements.push(increment(&memories[3], Integer { value: 3 }.into()));
+            statements
+        },
+        vec!["added"];
+        "added statement"
+    )]
+    #[test_case(
+        |memories| vec![
+            increment(&memories[0], Integer { value: 1 }.into()),
+            increment(&memories[1], memories[0].clone().into()),
+            increment(&memories[2], Integer { value: 5 }.into()),
+        ],
+        vec!["changed"];
+        "changed statement"
+    )]
+    fn test_diff(
+        statements: impl Fn(&[IntermediateMemory]) -> Vec<IntermediateStatement>,
+        expected: Vec<&str>,
+    ) {
+        let differences = diff(&memory_program(original), &memory_program(statements));
+        dbg!(&differences);
+        let kinds = differences
+            .iter()
+            .map(|difference| match difference {
+                Difference::Added {
+                    function: None,
+                    statement: _,
+                } => "added",
+                Difference::Removed {
+                    function: None,
+                    statement: _,
+                } => "removed",
+                Difference::Changed {
+                    function: None,
+                    before: _,
+                    after: _,
+                } => "changed",
+                _ => "nested",
+            })
+            .collect_vec();
+        assert_eq!(kinds, expected);
+    }
+}
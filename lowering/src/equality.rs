@@ -0,0 +1,389 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::intermediate_nodes::*;
+use type_checker::{AtomicTypeEnum, Boolean, Id};
+
+type Comparators = HashMap<*const RefCell<IntermediateType>, IntermediateMemory>;
+
+/// Generate fns that compare values of a type structurally.
+pub struct EqualityGenerator {
+    comparators: Comparators,
+    statements: Vec<IntermediateStatement>,
+}
+
+impl EqualityGenerator {
+    fn new() -> Self {
+        EqualityGenerator {
+            comparators: Comparators::new(),
+            statements: Vec::new(),
+        }
+    }
+    /// Generate the statements that define a comparison fn for a type and the value that stores it.
+    pub fn generate(type_: &IntermediateType) -> (Vec<IntermediateStatement>, IntermediateValue) {
+        let mut generator = EqualityGenerator::new();
+        let comparator = generator.comparator(type_);
+        (generator.statements, comparator.into())
+    }
+    fn assign(
+        expression: IntermediateExpression,
+        statements: &mut Vec<IntermediateStatement>,
+    ) -> IntermediateValue {
+        let assignment = IntermediateAssignment::from(expression);
+        statements.push(assignment.clone().into());
+        assignment.into()
+    }
+    /// Define a fn that compares two values of a type, reusing the fn for referenced types.
+    fn comparator(&mut self, type_: &IntermediateType) -> IntermediateMemory {
+        let (key, body_type) = match type_ {
+            IntermediateType::Reference(reference) => {
+                (Some(Rc::as_ptr(reference)), reference.borrow().clone())
+            }
+            _ => (None, type_.clone()),
+        };
+        if let Some(comparator) = key.and_then(|key| self.comparators.get(&key)) {
+            return comparator.clone();
+        }
+        let memory = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![body_type.clone(), body_type.clone()],
+            Box::new(AtomicTypeEnum::BOOL.into()),
+        )));
+        // Register the comparator before generating its body so that recursive types call it.
+        if let Some(key) = key {
+            self.comparators.insert(key, memory.clone());
+        }
+        // Arguments use the underlying type so that they can be matched on.
+        let x = IntermediateArg::from(body_type.clone());
+        let y = IntermediateArg::from(body_type.clone());
+        let mut statements = Vec::new();
+        let ret = self.compare(
+            &body_type,
+            x.clone().into(),
+            y.clone().into(),
+            &mut statements,
+        );
+        self.statements.push(
+            IntermediateAssignment {
+                register: memory.register.clone(),
+                expression: IntermediateLambda {
+                    args: vec![x, y],
+                    block: IntermediateBlock { statements, ret },
+                }
+                .into(),
+            }
+            .into(),
+        );
+        memory
+    }
+    /// Add statements that compare two values, returning whether they are equal.
+    fn compare(
+        &mut self,
+        type_: &IntermediateType,
+        x: IntermediateValue,
+        y: IntermediateValue,
+        statements: &mut Vec<IntermediateStatement>,
+    ) -> IntermediateValue {
+        match type_ {
+            IntermediateType::AtomicType(AtomicType(AtomicTypeEnum::BOOL)) => {
+                let not_y = IntermediateAssignment::from(IntermediateExpression::from(
+                    IntermediateFnCall {
+                        fn_: BuiltInFn(
+                            Id::from("!"),
+                            IntermediateFnType(
+                                vec![AtomicTypeEnum::BOOL.into()],
+                                Box::new(AtomicTypeEnum::BOOL.into()),
+                            ),
+                        )
+                        .into(),
+                        args: vec![y.clone()],
+                    },
+                ));
+                Self::assign(
+                    IntermediateIf {
+                        condition: x,
+                        branches: (y.into(), (vec![not_y.clone().into()], not_y.into()).into()),
+                    }
+                    .into(),
+                    statements,
+                )
+            }
+            IntermediateType::AtomicType(atomic_type) => Self::assign(
+                IntermediateFnCall {
+                    fn_: BuiltInFn(
+                        Id::from("=="),
+                        IntermediateFnType(
+                            vec![atomic_type.clone().into(), atomic_type.clone().into()],
+                            Box::new(AtomicTypeEnum::BOOL.into()),
+                        ),
+                    )
+                    .into(),
+                    args: vec![x, y],
+                }
+                .into(),
+                statements,
+            ),
+            IntermediateType::IntermediateTupleType(IntermediateTupleType(types)) => {
+                self.compare_elements(types, 0, x, y, statements)
+            }
+            IntermediateType::IntermediateUnionType(IntermediateUnionType(variants)) => {
+                let mut branches = Vec::new();
+                for (i, variant) in variants.iter().enumerate() {
+                    let x_data = variant.clone().map(IntermediateArg::from);
+                    let y_data = variant.clone().map(IntermediateArg::from);
+                    let mut data_statements = Vec::new();
+                    let equal = match (variant, &x_data, &y_data) {
+                        (Some(type_), Some(x_data), Some(y_data)) => self.compare(
+                            type_,
+                            x_data.clone().into(),
+                            y_data.clone().into(),
+                            &mut data_statements,
+                        ),
+                        _ => Boolean { value: true }.into(),
+                    };
+                    let mut data_block = Some((data_statements, equal).into());
+                    // Values with different variants are never equal.
+                    let y_branches = (0..variants.len())
+                        .map(|j| IntermediateMatchBranch {
+                            target: if i == j { y_data.clone() } else { None },
+                            block: if i == j {
+                                data_block.take().unwrap()
+                            } else {
+                                IntermediateValue::from(Boolean { value: false }).into()
+                            },
+                        })
+                        .collect();
+                    let mut variant_statements = Vec::new();
+                    let ret = Self::assign(
+                        IntermediateMatch {
+                            subject: y.clone(),
+                            branches: y_branches,
+                        }
+                        .into(),
+                        &mut variant_statements,
+                    );
+                    branches.push(IntermediateMatchBranch {
+                        target: x_data,
+                        block: (variant_statements, ret).into(),
+                    });
+                }
+                Self::assign(
+                    IntermediateMatch {
+                        subject: x,
+                        branches,
+                    }
+                    .into(),
+                    statements,
+                )
+            }
+            IntermediateType::Reference(_) => {
+                let comparator = self.comparator(type_);
+                Self::assign(
+                    IntermediateFnCall {
+                        fn_: comparator.into(),
+                        args: vec![x, y],
+                    }
+                    .into(),
+                    statements,
+                )
+            }
+            IntermediateType::IntermediateFnType(_) => {
+                panic!("Fns cannot be compared.")
+            }
+            IntermediateType::IntermediateArrayType(_) => {
+                panic!("Arrays cannot be compared.")
+            }
+        }
+    }
+    /// Compare tuple elements in order, stopping at the first that differ.
+    fn compare_elements(
+        &mut self,
+        types: &Vec<IntermediateType>,
+        idx: usize,
+        x: IntermediateValue,
+        y: IntermediateValue,
+        statements: &mut Vec<IntermediateStatement>,
+    ) -> IntermediateValue {
+        let Some(type_) = types.get(idx) else {
+            return Boolean { value: true }.into();
+        };
+        let element = |value: &IntermediateValue| {
+            IntermediateElementAccess {
+                value: value.clone(),
+                idx,
+            }
+            .into()
+        };
+        let x_element = Self::assign(element(&x), statements);
+        let y_element = Self::assign(element(&y), statements);
+        let equal = self.compare(type_, x_element, y_element, statements);
+        if idx + 1 == types.len() {
+            return equal;
+        }
+        let mut rest_statements = Vec::new();
+        let rest = self.compare_elements(types, idx + 1, x, y, &mut rest_statements);
+        Self::assign(
+            IntermediateIf {
+                condition: equal,
+                branches: (
+                    (rest_statements, rest).into(),
+                    IntermediateValue::from(Boolean { value: false }).into(),
+                ),
+            }
+            .into(),
+            statements,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{ExpressionEqualityChecker, IntermediateProgram, RecursiveFnFinder};
+    use test_case::test_case;
+
+    fn generated_lambda(type_: &IntermediateType) -> IntermediateLambda {
+        let (statements, _) = EqualityGenerator::generate(type_);
+        let [IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            register: _,
+            expression: IntermediateExpression::IntermediateLambda(lambda),
+        })] = &statements[..]
+        else {
+            panic!("Expected a single comparator.")
+        };
+        lambda.clone()
+    }
+
+    #[test_case(
+        {
+            let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let equal = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            (
+                AtomicTypeEnum::INT.into(),
+                IntermediateLambda {
+                    args: vec![x.clone(), y.clone()],
+                    block: IntermediateBlock {
+                        statements: vec![IntermediateAssignment {
+                            register: equal.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: BuiltInFn(
+                                    Id::from("=="),
+                                    IntermediateFnType(
+                                        vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                                        Box::new(AtomicTypeEnum::BOOL.into()),
+                                    ),
+                                ).into(),
+                                args: vec![x.into(), y.into()],
+                            }.into(),
+                        }.into()],
+                        ret: equal.into(),
+                    },
+                },
+            )
+        };
+        "integer"
+    )]
+    #[test_case(
+        {
+            let type_ = IntermediateType::from(IntermediateTupleType(Vec::new()));
+            (
+                type_.clone(),
+                IntermediateLambda {
+                    args: vec![type_.clone().into(), type_.into()],
+                    block: IntermediateValue::from(Boolean { value: true }).into(),
+                },
+            )
+        };
+        "empty tuple"
+    )]
+    #[test_case(
+        {
+            let type_ = IntermediateType::from(IntermediateUnionType(vec![None, None]));
+            let x = IntermediateArg::from(type_.clone());
+            let y = IntermediateArg::from(type_.clone());
+            let x_match = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let y_matches = [
+                IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL)),
+                IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL)),
+            ];
+            let branch = |i: usize| IntermediateMatchBranch {
+                target: None,
+                block: (
+                    vec![IntermediateAssignment {
+                        register: y_matches[i].register.clone(),
+                        expression: IntermediateMatch {
+                            subject: y.clone().into(),
+                            branches: (0..2)
+                                .map(|j| IntermediateMatchBranch {
+                                    target: None,
+                                    block: IntermediateValue::from(Boolean { value: i == j }).into(),
+                                })
+                                .collect(),
+                        }.into(),
+                    }.into()],
+                    y_matches[i].clone().into(),
+                ).into(),
+            };
+            (
+                type_.clone(),
+                IntermediateLambda {
+                    args: vec![x.clone(), y.clone()],
+                    block: IntermediateBlock {
+                        statements: vec![IntermediateAssignment {
+                            register: x_match.register.clone(),
+                            expression: IntermediateMatch {
+                                subject: x.clone().into(),
+                                branches: vec![branch(0), branch(1)],
+                            }.into(),
+                        }.into()],
+                        ret: x_match.into(),
+                    },
+                },
+            )
+        };
+        "enum"
+    )]
+    fn test_generate_comparator(type_expected: (IntermediateType, IntermediateLambda)) {
+        let (type_, expected) = type_expected;
+        ExpressionEqualityChecker::assert_equal(&generated_lambda(&type_).into(), &expected.into());
+    }
+
+    #[test]
+    fn test_generate_recursive_comparator() {
+        // `type List = Cons (int, List) | Nil`
+        let reference = Rc::new(RefCell::new(IntermediateTupleType(Vec::new()).into()));
+        let union_type = IntermediateType::from(IntermediateUnionType(vec![
+            Some(
+                IntermediateTupleType(vec![
+                    AtomicTypeEnum::INT.into(),
+                    IntermediateType::Reference(reference.clone()),
+                ])
+                .into(),
+            ),
+            None,
+        ]));
+        *reference.borrow_mut() = union_type.clone();
+        let (statements, comparator) = EqualityGenerator::generate(&union_type);
+        // The referenced type is compared by a single recursive fn.
+        assert_eq!(statements.len(), 2);
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: statements.clone(),
+                    ret: comparator,
+                },
+            },
+            types: vec![reference],
+        };
+        let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            register: _,
+            expression: IntermediateExpression::IntermediateLambda(lambda),
+        }) = &statements[0]
+        else {
+            panic!("Expected a comparator.")
+        };
+        let recursive_fns = RecursiveFnFinder::recursive_fns(&program);
+        assert_eq!(recursive_fns.get(lambda), Some(&true));
+    }
+}
@@ -21,8 +21,12 @@ pub struct ExpressionEqualityChecker {
 
 impl ExpressionEqualityChecker {
     pub fn assert_equal(e1: &IntermediateExpression, e2: &IntermediateExpression) {
+        assert!(Self::equal(e1, e2), "{e1:#?} != {e2:#?}")
+    }
+    /// Determine whether two expressions are equal up to renaming registers.
+    pub fn equal(e1: &IntermediateExpression, e2: &IntermediateExpression) -> bool {
         let mut expression_equality_checker = Self::new();
-        expression_equality_checker.assert_equal_expression(e1, e2)
+        expression_equality_checker.equal_expression(e1, e2)
     }
     fn new() -> Self {
         ExpressionEqualityChecker {
@@ -32,7 +36,7 @@ impl ExpressionEqualityChecker {
             right_history: HashMap::new(),
         }
     }
-    fn assert_equal_memory(&mut self, m1: &IntermediateMemory, m2: &IntermediateMemory) {
+    fn equal_memory(&mut self, m1: &IntermediateMemory, m2: &IntermediateMemory) -> bool {
         let IntermediateMemory {
             register: r1,
             type_: _,
@@ -41,9 +45,9 @@ impl ExpressionEqualityChecker {
             register: r2,
             type_: _,
         } = m2;
-        self.assert_equal_registers(r1, r2)
+        self.equal_registers(r1, r2)
     }
-    fn assert_equal_arg(&mut self, a1: &IntermediateArg, a2: &IntermediateArg) {
+    fn equal_arg(&mut self, a1: &IntermediateArg, a2: &IntermediateArg) -> bool {
         let IntermediateArg {
             register: r1,
             type_: _,
@@ -52,31 +56,34 @@ impl ExpressionEqualityChecker {
             register: r2,
             type_: _,
         } = a2;
-        self.assert_equal_registers(r1, r2)
+        self.equal_registers(r1, r2)
     }
-    fn assert_equal_args(&mut self, a1: &Vec<IntermediateArg>, a2: &Vec<IntermediateArg>) {
-        assert_eq!(a1.len(), a2.len());
-        for (a1, a2) in a1.iter().zip_eq(a2.iter()) {
-            self.assert_equal_arg(a1, a2)
-        }
+    fn equal_args(&mut self, a1: &Vec<IntermediateArg>, a2: &Vec<IntermediateArg>) -> bool {
+        a1.len() == a2.len()
+            && a1
+                .iter()
+                .zip_eq(a2.iter())
+                .all(|(a1, a2)| self.equal_arg(a1, a2))
     }
-    fn assert_equal_registers(&mut self, r1: &Register, r2: &Register) {
+    fn equal_registers(&mut self, r1: &Register, r2: &Register) -> bool {
         if self.left_history.get(&r1) == Some(&r2) {
             // Registers have already been deemed equal.
-            return;
+            return true;
         }
         // Check that the registers have not been found unequal.
-        assert!(!matches!(self.left_history.get(&r1), Some(_)));
-        assert!(!matches!(self.right_history.get(&r2), Some(_)));
+        if self.left_history.contains_key(&r1) || self.right_history.contains_key(&r2) {
+            return false;
+        }
         // Assume registers are equal.
         self.left_history.insert(r1.clone(), r2.clone());
         self.right_history.insert(r2.clone(), r1.clone());
+        true
     }
-    fn assert_equal_assignment(
+    fn equal_assignment(
         &mut self,
         m1: &IntermediateAssignment,
         m2: &IntermediateAssignment,
-    ) {
+    ) -> bool {
         let IntermediateAssignment {
             expression: e1,
             register: r1,
@@ -86,37 +93,40 @@ impl ExpressionEqualityChecker {
             register: r2,
         } = m2;
         if self.left_true_history.get(&r1) == Some(&r2) {
-            return;
+            return true;
         }
         if self.left_history.get(&r1) == Some(&r2) {
             // If two registers have been assumed as equal, keep this assumption.
             self.left_true_history.insert(r1.clone(), r2.clone());
             self.right_true_history.insert(r2.clone(), r1.clone());
-            self.assert_equal_expression(&e1, &e2)
+            self.equal_expression(&e1, &e2)
         } else {
             // Ensure there are no existing assumptions about equality.
-            assert!(!matches!(self.left_true_history.get(&r1), Some(_)));
-            assert!(!matches!(self.right_true_history.get(&r2), Some(_)));
-            assert!(!matches!(self.left_history.get(&r1), Some(_)));
-            assert!(!matches!(self.right_history.get(&r2), Some(_)));
+            if self.left_true_history.contains_key(&r1)
+                || self.right_true_history.contains_key(&r2)
+                || self.left_history.contains_key(&r1)
+                || self.right_history.contains_key(&r2)
+            {
+                return false;
+            }
             // Assume that the registers are equal from here onwards.
             self.left_history.insert(r1.clone(), r2.clone());
             self.right_history.insert(r2.clone(), r1.clone());
             self.left_true_history.insert(r1.clone(), r2.clone());
             self.right_true_history.insert(r2.clone(), r1.clone());
-            self.assert_equal_expression(&e1, &e2)
+            self.equal_expression(&e1, &e2)
         }
     }
-    fn assert_equal_expression(
+    fn equal_expression(
         &mut self,
         e1: &IntermediateExpression,
         e2: &IntermediateExpression,
-    ) {
+    ) -> bool {
         match (e1, e2) {
             (
                 IntermediateExpression::IntermediateValue(v1),
                 IntermediateExpression::IntermediateValue(v2),
-            ) => self.assert_equal_value(&v1, &v2),
+            ) => self.equal_value(&v1, &v2),
             (
                 IntermediateExpression::IntermediateElementAccess(IntermediateElementAccess {
                     value: v1,
@@ -126,10 +136,7 @@ impl ExpressionEqualityChecker {
                     value: v2,
                     idx: i2,
                 }),
-            ) => {
-                assert_eq!(i1, i2);
-                self.assert_equal_value(&v1, &v2)
-            }
+            ) => i1 == i2 && self.equal_value(&v1, &v2),
             (
                 IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
                     values1,
@@ -137,7 +144,7 @@ impl ExpressionEqualityChecker {
                 IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
                     values2,
                 )),
-            ) => self.assert_equal_values(&values1, &values2),
+            ) => self.equal_values(&values1, &values2),
             (
                 IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
                     fn_: v1,
@@ -147,10 +154,7 @@ impl ExpressionEqualityChecker {
                     fn_: v2,
                     args: a2,
                 }),
-            ) => {
-                self.assert_equal_values(&a1, &a2);
-                self.assert_equal_value(&v1, &v2)
-            }
+            ) => self.equal_values(&a1, &a2) && self.equal_value(&v1, &v2),
             (
                 IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
                     idx: i1,
@@ -163,13 +167,13 @@ impl ExpressionEqualityChecker {
                     type_: t2,
                 }),
             ) => {
-                assert_eq!(i1, i2);
-                match (d1, d2) {
-                    (None, None) => {}
-                    (Some(d1), Some(d2)) => self.assert_equal_value(d1, d2),
-                    _ => assert!(false),
-                }
-                assert_eq!(t1, t2)
+                i1 == i2
+                    && match (d1, d2) {
+                        (None, None) => true,
+                        (Some(d1), Some(d2)) => self.equal_value(d1, d2),
+                        _ => false,
+                    }
+                    && t1 == t2
             }
             (
                 IntermediateExpression::IntermediateLambda(IntermediateLambda {
@@ -180,10 +184,7 @@ impl ExpressionEqualityChecker {
                     args: a2,
                     block: b2,
                 }),
-            ) => {
-                self.assert_equal_args(a1, a2);
-                self.assert_equal_block(&b1, &b2);
-            }
+            ) => self.equal_args(a1, a2) && self.equal_block(&b1, &b2),
             (
                 IntermediateExpression::IntermediateIf(IntermediateIf {
                     condition: c1,
@@ -194,9 +195,9 @@ impl ExpressionEqualityChecker {
                     branches: b2,
                 }),
             ) => {
-                self.assert_equal_value(c1, c2);
-                self.assert_equal_block(&b1.0, &b2.0);
-                self.assert_equal_block(&b1.1, &b2.1);
+                self.equal_value(c1, c2)
+                    && self.equal_block(&b1.0, &b2.0)
+                    && self.equal_block(&b1.1, &b2.1)
             }
             (
                 IntermediateExpression::IntermediateMatch(IntermediateMatch {
@@ -207,68 +208,64 @@ impl ExpressionEqualityChecker {
                     subject: s2,
                     branches: b2,
                 }),
-            ) => {
-                self.assert_equal_value(s1, s2);
-                self.assert_equal_branches(b1, b2);
-            }
-            _ => assert!(false),
+            ) => self.equal_value(s1, s2) && self.equal_branches(b1, b2),
+            _ => false,
         }
     }
-    fn assert_equal_block(&mut self, b1: &IntermediateBlock, b2: &IntermediateBlock) {
-        self.assert_equal_statements(&b1.statements, &b2.statements);
-        self.assert_equal_value(&b1.ret, &b2.ret)
+    fn equal_block(&mut self, b1: &IntermediateBlock, b2: &IntermediateBlock) -> bool {
+        self.equal_statements(&b1.statements, &b2.statements) && self.equal_value(&b1.ret, &b2.ret)
     }
-    fn assert_equal_value(&mut self, v1: &IntermediateValue, v2: &IntermediateValue) {
+    fn equal_value(&mut self, v1: &IntermediateValue, v2: &IntermediateValue) -> bool {
         match (v1, v2) {
             (
                 IntermediateValue::IntermediateBuiltIn(b1),
                 IntermediateValue::IntermediateBuiltIn(b2),
-            ) => assert_eq!(b1, b2),
+            ) => b1 == b2,
             (IntermediateValue::IntermediateArg(a1), IntermediateValue::IntermediateArg(a2)) => {
-                self.assert_equal_arg(a1, a2);
+                self.equal_arg(a1, a2)
             }
             (
                 IntermediateValue::IntermediateMemory(m1),
                 IntermediateValue::IntermediateMemory(m2),
-            ) => self.assert_equal_memory(m1, m2),
-            _ => {
-                assert!(false);
-            }
+            ) => self.equal_memory(m1, m2),
+            _ => false,
         }
     }
-    fn assert_equal_values(
+    fn equal_values(
         &mut self,
         values1: &Vec<IntermediateValue>,
         values2: &Vec<IntermediateValue>,
-    ) {
-        assert_eq!(values1.len(), values2.len());
-        for (v1, v2) in values1.iter().zip_eq(values2.iter()) {
-            self.assert_equal_value(v1, v2)
-        }
+    ) -> bool {
+        values1.len() == values2.len()
+            && values1
+                .iter()
+                .zip_eq(values2.iter())
+                .all(|(v1, v2)| self.equal_value(v1, v2))
     }
-    fn assert_equal_statements(
+    fn equal_statements(
         &mut self,
         statements1: &Vec<IntermediateStatement>,
         statements2: &Vec<IntermediateStatement>,
-    ) {
-        assert_eq!(statements1.len(), statements2.len());
-        for (s1, s2) in statements1.iter().zip_eq(statements2.iter()) {
-            self.assert_equal_statement(s1, s2)
-        }
+    ) -> bool {
+        statements1.len() == statements2.len()
+            && statements1
+                .iter()
+                .zip_eq(statements2.iter())
+                .all(|(s1, s2)| self.equal_statement(s1, s2))
     }
-    fn assert_equal_statement(&mut self, s1: &IntermediateStatement, s2: &IntermediateStatement) {
+    fn equal_statement(&mut self, s1: &IntermediateStatement, s2: &IntermediateStatement) -> bool {
         match (s1, s2) {
             (
                 IntermediateStatement::IntermediateAssignment(m1),
                 IntermediateStatement::IntermediateAssignment(m2),
-            ) => self.assert_equal_assignment(m1, m2),
+            ) => self.equal_assignment(m1, m2),
         }
     }
-    fn assert_equal_branch(
+    fn equal_branch(
         &mut self,
         branch1: &IntermediateMatchBranch,
         branch2: &IntermediateMatchBranch,
-    ) {
+    ) -> bool {
         let IntermediateMatchBranch {
             target: t1,
             block: b1,
@@ -278,20 +275,20 @@ impl ExpressionEqualityChecker {
             block: b2,
         } = branch2;
         (match (t1, t2) {
-            (None, None) => {}
-            (Some(a1), Some(a2)) => self.assert_equal_arg(a1, a2),
-            _ => assert!(false),
-        });
-        self.assert_equal_block(b1, b2)
+            (None, None) => true,
+            (Some(a1), Some(a2)) => self.equal_arg(a1, a2),
+            _ => false,
+        }) && self.equal_block(b1, b2)
     }
-    fn assert_equal_branches(
+    fn equal_branches(
         &mut self,
         branches1: &Vec<IntermediateMatchBranch>,
         branches2: &Vec<IntermediateMatchBranch>,
-    ) {
-        assert_eq!(branches1.len(), branches2.len());
-        for (b1, b2) in branches1.iter().zip_eq(branches2.iter()) {
-            self.assert_equal_branch(b1, b2)
-        }
+    ) -> bool {
+        branches1.len() == branches2.len()
+            && branches1
+                .iter()
+                .zip_eq(branches2.iter())
+                .all(|(b1, b2)| self.equal_branch(b1, b2))
     }
 }
@@ -0,0 +1,267 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    IntermediateArg, IntermediateAssignment, IntermediateBlock, IntermediateCtorCall,
+    IntermediateElementAccess, IntermediateExpression, IntermediateFnCall, IntermediateIf,
+    IntermediateLambda, IntermediateMatch, IntermediateMatchBranch, IntermediateMemory,
+    IntermediateStatement, IntermediateTupleExpression, IntermediateValue, Register,
+};
+
+/// Hash expressions so that expressions deemed equal by the `ExpressionEqualityChecker` hash equal.
+pub struct ExpressionHasher {
+    // Registers are replaced with the order in which they are first seen.
+    registers: HashMap<Register, usize>,
+    state: DefaultHasher,
+}
+
+impl ExpressionHasher {
+    /// Hash an expression up to renaming registers.
+    pub fn hash(expression: &IntermediateExpression) -> u64 {
+        let mut hasher = ExpressionHasher {
+            registers: HashMap::new(),
+            state: DefaultHasher::new(),
+        };
+        hasher.hash_expression(expression);
+        hasher.state.finish()
+    }
+    fn hash_register(&mut self, register: &Register) {
+        let next = self.registers.len();
+        let idx = *self.registers.entry(register.clone()).or_insert(next);
+        idx.hash(&mut self.state);
+    }
+    fn hash_args(&mut self, args: &Vec<IntermediateArg>) {
+        args.len().hash(&mut self.state);
+        for IntermediateArg { type_: _, register } in args {
+            self.hash_register(register);
+        }
+    }
+    fn hash_value(&mut self, value: &IntermediateValue) {
+        match value {
+            IntermediateValue::IntermediateBuiltIn(built_in) => {
+                0u8.hash(&mut self.state);
+                built_in.hash(&mut self.state);
+            }
+            IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register }) => {
+                1u8.hash(&mut self.state);
+                self.hash_register(register);
+            }
+            IntermediateValue::IntermediateArg(IntermediateArg { type_: _, register }) => {
+                2u8.hash(&mut self.state);
+                self.hash_register(register);
+            }
+        }
+    }
+    fn hash_values(&mut self, values: &Vec<IntermediateValue>) {
+        values.len().hash(&mut self.state);
+        for value in values {
+            self.hash_value(value);
+        }
+    }
+    fn hash_expression(&mut self, expression: &IntermediateExpression) {
+        match expression {
+            IntermediateExpression::IntermediateValue(value) => {
+                0u8.hash(&mut self.state);
+                self.hash_value(value);
+            }
+            IntermediateExpression::IntermediateElementAccess(IntermediateElementAccess {
+                value,
+                idx,
+            }) => {
+                1u8.hash(&mut self.state);
+                idx.hash(&mut self.state);
+                self.hash_value(value);
+            }
+            IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
+                values,
+            )) => {
+                2u8.hash(&mut self.state);
+                self.hash_values(values);
+            }
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_, args }) => {
+                3u8.hash(&mut self.state);
+                self.hash_value(fn_);
+                self.hash_values(args);
+            }
+            IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
+                idx,
+                data,
+                type_,
+            }) => {
+                4u8.hash(&mut self.state);
+                idx.hash(&mut self.state);
+                data.is_some().hash(&mut self.state);
+                if let Some(data) = data {
+                    self.hash_value(data);
+                }
+                type_.hash(&mut self.state);
+            }
+            IntermediateExpression::IntermediateLambda(IntermediateLambda { args, block }) => {
+                5u8.hash(&mut self.state);
+                self.hash_args(args);
+                self.hash_block(block);
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => {
+                6u8.hash(&mut self.state);
+                self.hash_value(condition);
+                self.hash_block(&branches.0);
+                self.hash_block(&branches.1);
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch { subject, branches }) => {
+                7u8.hash(&mut self.state);
+                self.hash_value(subject);
+                branches.len().hash(&mut self.state);
+                for IntermediateMatchBranch { target, block } in branches {
+                    target.is_some().hash(&mut self.state);
+                    if let Some(IntermediateArg { type_: _, register }) = target {
+                        self.hash_register(register);
+                    }
+                    self.hash_block(block);
+                }
+            }
+        }
+    }
+    fn hash_block(&mut self, block: &IntermediateBlock) {
+        block.statements.len().hash(&mut self.state);
+        for statement in &block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            self.hash_register(register);
+            self.hash_expression(expression);
+        }
+        self.hash_value(&block.ret);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        AtomicTypeEnum, BuiltInFn, ExpressionEqualityChecker, Id, Integer, IntermediateFnType,
+        IntermediateType, IntermediateUnionType,
+    };
+    use test_case::test_case;
+
+    fn int() -> IntermediateType {
+        AtomicTypeEnum::INT.into()
+    }
+
+    fn add(x: IntermediateValue, y: IntermediateValue) -> IntermediateExpression {
+        IntermediateFnCall {
+            fn_: BuiltInFn(
+                Id::from("+"),
+                IntermediateFnType(vec![int(), int()], Box::new(int())),
+            )
+            .into(),
+            args: vec![x, y],
+        }
+        .into()
+    }
+
+    /// `(x: int, y: int) -> int { z = x + y; z }` with the arguments to `+` chosen by `select`.
+    fn sum(
+        select: fn(IntermediateArg, IntermediateArg) -> (IntermediateArg, IntermediateArg),
+    ) -> IntermediateExpression {
+        let x = IntermediateArg::from(int());
+        let y = IntermediateArg::from(int());
+        let z = IntermediateMemory::from(int());
+        let (lhs, rhs) = select(x.clone(), y.clone());
+        IntermediateLambda {
+            args: vec![x, y],
+            block: IntermediateBlock {
+                statements: vec![IntermediateAssignment {
+                    register: z.register.clone(),
+                    expression: add(lhs.into(), rhs.into()),
+                }
+                .into()],
+                ret: z.into(),
+            },
+        }
+        .into()
+    }
+
+    #[test_case(
+        sum(|x, y| (x, y)),
+        sum(|x, y| (x, y)),
+        true;
+        "alpha-equivalent lambdas"
+    )]
+    #[test_case(
+        sum(|x, y| (x, y)),
+        sum(|x, y| (y, x)),
+        false;
+        "swapped arguments"
+    )]
+    #[test_case(
+        sum(|x, _| (x.clone(), x)),
+        sum(|x, y| (x, y)),
+        false;
+        "repeated argument"
+    )]
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(int());
+            add(x.into(), Integer { value: 1 }.into())
+        },
+        {
+            let y = IntermediateMemory::from(int());
+            add(y.into(), Integer { value: 1 }.into())
+        },
+        true;
+        "renamed free registers"
+    )]
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(int());
+            add(x.into(), Integer { value: 1 }.into())
+        },
+        {
+            let x = IntermediateMemory::from(int());
+            add(x.into(), Integer { value: 2 }.into())
+        },
+        false;
+        "different constants"
+    )]
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(int());
+            IntermediateTupleExpression(vec![x.clone().into(), x.into()]).into()
+        },
+        {
+            let x = IntermediateMemory::from(int());
+            let y = IntermediateMemory::from(int());
+            IntermediateTupleExpression(vec![x.into(), y.into()]).into()
+        },
+        false;
+        "aliased tuple elements"
+    )]
+    #[test_case(
+        IntermediateCtorCall {
+            idx: 0,
+            data: None,
+            type_: IntermediateUnionType(vec![None, None]),
+        }.into(),
+        IntermediateCtorCall {
+            idx: 1,
+            data: None,
+            type_: IntermediateUnionType(vec![None, None]),
+        }.into(),
+        false;
+        "different variants"
+    )]
+    fn test_hash_expressions(e1: IntermediateExpression, e2: IntermediateExpression, equal: bool) {
+        assert_eq!(ExpressionEqualityChecker::equal(&e1, &e2), equal);
+        assert_eq!(
+            ExpressionHasher::hash(&e1) == ExpressionHasher::hash(&e2),
+            equal
+        );
+    }
+}
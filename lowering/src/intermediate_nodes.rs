@@ -9,17 +9,19 @@ use std::{
 };
 
 use from_variants::FromVariants;
-use type_checker::{AtomicTypeEnum, Boolean, Id, Integer};
+use serde::{Deserialize, Deserializer, Serialize};
+use type_checker::{AtomicTypeEnum, Boolean, Char, Id, Integer, Str};
 
 use crate::type_equality_checker::TypeEqualityChecker;
 
-#[derive(Clone, FromVariants, Eq)]
+#[derive(Serialize, Deserialize, Clone, FromVariants, Eq)]
 pub enum IntermediateType {
     AtomicType(AtomicType),
     IntermediateTupleType(IntermediateTupleType),
     IntermediateFnType(IntermediateFnType),
     IntermediateUnionType(IntermediateUnionType),
-    Reference(Rc<RefCell<IntermediateType>>),
+    IntermediateArrayType(IntermediateArrayType),
+    Reference(#[serde(with = "crate::serialization::reference")] Rc<RefCell<IntermediateType>>),
 }
 
 impl fmt::Debug for IntermediateType {
@@ -35,6 +37,9 @@ impl fmt::Debug for IntermediateType {
             Self::IntermediateUnionType(arg0) => {
                 f.debug_tuple("IntermediateUnionType").field(arg0).finish()
             }
+            Self::IntermediateArrayType(arg0) => {
+                f.debug_tuple("IntermediateArrayType").field(arg0).finish()
+            }
             Self::Reference(r) => f.debug_tuple("Reference").field(&r.as_ptr()).finish(),
         }
     }
@@ -59,26 +64,44 @@ impl PartialEq for IntermediateType {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct AtomicType(pub AtomicTypeEnum);
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateTupleType(pub Vec<IntermediateType>);
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateFnType(pub Vec<IntermediateType>, pub Box<IntermediateType>);
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateUnionType(pub Vec<Option<IntermediateType>>);
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntermediateArrayType(pub Box<IntermediateType>);
+
 static REGISTER_ID: AtomicUsize = AtomicUsize::new(0);
-#[derive(Clone, Ord, Hash, Eq, PartialEq, PartialOrd)]
-pub struct Register(usize);
+#[derive(Clone, Ord, Hash, Eq, PartialEq, PartialOrd, Serialize)]
+pub struct Register(pub(crate) usize);
 
 impl Register {
     pub fn new() -> Self {
         Self(REGISTER_ID.fetch_add(1, Ordering::Relaxed))
     }
+    /// Restart numbering new registers from zero.
+    /// Registers created before the reset may clash with new ones, so this is only available in tests.
+    #[cfg(test)]
+    pub fn reset_counter() {
+        REGISTER_ID.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<'de> Deserialize<'de> for Register {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = usize::deserialize(deserializer)?;
+        // Keep new registers distinct from the ones that were deserialized.
+        REGISTER_ID.fetch_max(id + 1, Ordering::Relaxed);
+        Ok(Self(id))
+    }
 }
 
 impl fmt::Debug for Register {
@@ -154,6 +177,18 @@ impl From<Boolean> for IntermediateValue {
     }
 }
 
+impl From<Str> for IntermediateValue {
+    fn from(value: Str) -> IntermediateValue {
+        IntermediateBuiltIn::from(value).into()
+    }
+}
+
+impl From<Char> for IntermediateValue {
+    fn from(value: Char) -> IntermediateValue {
+        IntermediateBuiltIn::from(value).into()
+    }
+}
+
 impl From<BuiltInFn> for IntermediateValue {
     fn from(value: BuiltInFn) -> IntermediateValue {
         IntermediateBuiltIn::from(value).into()
@@ -170,10 +205,12 @@ impl From<IntermediateAssignment> for IntermediateValue {
     }
 }
 
-#[derive(Clone, FromVariants, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, FromVariants, PartialEq, Eq, Hash)]
 pub enum IntermediateBuiltIn {
     Integer(Integer),
     Boolean(Boolean),
+    Str(Str),
+    Char(Char),
     BuiltInFn(BuiltInFn),
 }
 
@@ -182,6 +219,8 @@ impl IntermediateBuiltIn {
         match self {
             IntermediateBuiltIn::Integer(_) => AtomicTypeEnum::INT.into(),
             IntermediateBuiltIn::Boolean(_) => AtomicTypeEnum::BOOL.into(),
+            IntermediateBuiltIn::Str(_) => AtomicTypeEnum::STRING.into(),
+            IntermediateBuiltIn::Char(_) => AtomicTypeEnum::CHAR.into(),
             IntermediateBuiltIn::BuiltInFn(BuiltInFn(_, type_)) => type_.clone().into(),
         }
     }
@@ -192,15 +231,17 @@ impl fmt::Debug for IntermediateBuiltIn {
         match self {
             Self::Integer(Integer { value }) => f.debug_tuple("Integer").field(value).finish(),
             Self::Boolean(Boolean { value }) => f.debug_tuple("Boolean").field(value).finish(),
+            Self::Str(Str { value }) => f.debug_tuple("Str").field(value).finish(),
+            Self::Char(Char { value }) => f.debug_tuple("Char").field(value).finish(),
             Self::BuiltInFn(BuiltInFn(name, _)) => f.debug_tuple("BuiltInFn").field(name).finish(),
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct BuiltInFn(pub Id, pub IntermediateFnType);
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateAssignment {
     pub expression: IntermediateExpression,
     pub register: Register,
@@ -224,7 +265,7 @@ impl From<IntermediateValue> for IntermediateAssignment {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, FromVariants, Hash)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, FromVariants, Hash)]
 pub enum IntermediateExpression {
     IntermediateValue(IntermediateValue),
     IntermediateElementAccess(IntermediateElementAccess),
@@ -394,14 +435,14 @@ impl From<IntermediateBuiltIn> for IntermediateExpression {
     }
 }
 
-#[derive(Clone, FromVariants, PartialEq, Eq, Debug, Hash)]
+#[derive(Serialize, Deserialize, Clone, FromVariants, PartialEq, Eq, Debug, Hash)]
 pub enum IntermediateValue {
     IntermediateBuiltIn(IntermediateBuiltIn),
     IntermediateMemory(IntermediateMemory),
     IntermediateArg(IntermediateArg),
 }
 
-#[derive(Clone, Eq)]
+#[derive(Serialize, Deserialize, Clone, Eq)]
 pub struct IntermediateMemory {
     pub type_: IntermediateType,
     pub register: Register,
@@ -440,7 +481,7 @@ impl From<IntermediateType> for IntermediateMemory {
     }
 }
 
-#[derive(Clone, Eq)]
+#[derive(Serialize, Deserialize, Clone, Eq)]
 pub struct IntermediateArg {
     pub type_: IntermediateType,
     pub register: Register,
@@ -479,7 +520,7 @@ impl Hash for IntermediateArg {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateElementAccess {
     pub value: IntermediateValue,
     pub idx: usize,
@@ -496,7 +537,7 @@ impl IntermediateElementAccess {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateTupleExpression(pub Vec<IntermediateValue>);
 
 impl IntermediateTupleExpression {
@@ -505,7 +546,7 @@ impl IntermediateTupleExpression {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateFnCall {
     pub fn_: IntermediateValue,
     pub args: Vec<IntermediateValue>,
@@ -521,7 +562,7 @@ impl IntermediateFnCall {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateCtorCall {
     pub idx: usize,
     pub data: Option<IntermediateValue>,
@@ -534,7 +575,7 @@ impl IntermediateCtorCall {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateBlock {
     pub statements: Vec<IntermediateStatement>,
     pub ret: IntermediateValue,
@@ -577,7 +618,7 @@ impl From<(Vec<IntermediateStatement>, IntermediateValue)> for IntermediateBlock
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateLambda {
     pub args: Vec<IntermediateArg>,
     pub block: IntermediateBlock,
@@ -627,12 +668,19 @@ impl IntermediateLambda {
             })
             .collect()
     }
+    /// Find the registers captured by the lambda: those that are used but not bound by its arguments or statements.
+    pub fn free_variables(&self) -> HashSet<Register> {
+        self.find_open_vars()
+            .iter()
+            .filter_map(IntermediateValue::register)
+            .collect()
+    }
     pub fn substitute(&mut self, substitution: &Substitution) {
         self.block.substitute(substitution)
     }
 }
 
-#[derive(Clone, PartialEq, FromVariants, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, FromVariants, Eq, Hash)]
 pub enum IntermediateStatement {
     IntermediateAssignment(IntermediateAssignment),
 }
@@ -704,7 +752,7 @@ impl IntermediateStatement {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateIf {
     pub condition: IntermediateValue,
     pub branches: (IntermediateBlock, IntermediateBlock),
@@ -716,7 +764,7 @@ impl IntermediateIf {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateMatch {
     pub subject: IntermediateValue,
     pub branches: Vec<IntermediateMatchBranch>,
@@ -728,7 +776,7 @@ impl IntermediateMatch {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct IntermediateMatchBranch {
     pub target: Option<IntermediateArg>,
     pub block: IntermediateBlock,
@@ -739,3 +787,285 @@ pub struct IntermediateProgram {
     pub main: IntermediateLambda,
     pub types: Vec<Rc<RefCell<IntermediateType>>>,
 }
+
+/// Indentation for each level of nesting in pretty-printed code.
+const INDENT: &str = "    ";
+
+impl Register {
+    fn pretty(&self) -> String {
+        format!("%{}", self.0)
+    }
+}
+
+impl IntermediateValue {
+    fn pretty(&self) -> String {
+        match self {
+            IntermediateValue::IntermediateBuiltIn(built_in) => match built_in {
+                IntermediateBuiltIn::Integer(Integer { value }) => value.to_string(),
+                IntermediateBuiltIn::Boolean(Boolean { value }) => value.to_string(),
+                IntermediateBuiltIn::Str(Str { value }) => format!("{value:?}"),
+                IntermediateBuiltIn::Char(Char { value }) => match char::from_u32(*value) {
+                    Some(character) => format!("{character:?}"),
+                    None => format!("chr({value})"),
+                },
+                IntermediateBuiltIn::BuiltInFn(BuiltInFn(id, _)) => id.clone(),
+            },
+            IntermediateValue::IntermediateMemory(IntermediateMemory { register, .. })
+            | IntermediateValue::IntermediateArg(IntermediateArg { register, .. }) => {
+                register.pretty()
+            }
+        }
+    }
+    fn pretty_all(values: &[Self]) -> String {
+        values.iter().map(Self::pretty).join(", ")
+    }
+}
+
+impl IntermediateExpression {
+    fn pretty(&self, depth: usize) -> String {
+        match self {
+            IntermediateExpression::IntermediateValue(value) => value.pretty(),
+            IntermediateExpression::IntermediateElementAccess(IntermediateElementAccess {
+                value,
+                idx,
+            }) => format!("{}.{idx}", value.pretty()),
+            IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
+                values,
+            )) => format!("({})", IntermediateValue::pretty_all(values)),
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_, args }) => {
+                format!("{}({})", fn_.pretty(), IntermediateValue::pretty_all(args))
+            }
+            IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
+                idx,
+                data,
+                type_: _,
+            }) => match data {
+                Some(data) => format!("#{idx}({})", data.pretty()),
+                None => format!("#{idx}"),
+            },
+            IntermediateExpression::IntermediateLambda(lambda) => lambda.pretty_at(depth),
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => format!(
+                "if {} {} else {}",
+                condition.pretty(),
+                branches.0.pretty(depth),
+                branches.1.pretty(depth)
+            ),
+            IntermediateExpression::IntermediateMatch(IntermediateMatch { subject, branches }) => {
+                let indent = INDENT.repeat(depth + 1);
+                let branches = branches
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, IntermediateMatchBranch { target, block })| {
+                        let target = match target {
+                            Some(target) => format!("({})", target.register.pretty()),
+                            None => String::new(),
+                        };
+                        format!("{indent}#{idx}{target} => {}\n", block.pretty(depth + 1))
+                    })
+                    .join("");
+                format!(
+                    "match {} {{\n{branches}{}}}",
+                    subject.pretty(),
+                    INDENT.repeat(depth)
+                )
+            }
+        }
+    }
+}
+
+impl IntermediateBlock {
+    /// Render a block with its contents indented one level deeper than `depth`.
+    fn pretty(&self, depth: usize) -> String {
+        let indent = INDENT.repeat(depth + 1);
+        let statements = self
+            .statements
+            .iter()
+            .map(|statement| match statement {
+                IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                    expression,
+                    register,
+                }) => format!(
+                    "{indent}{} = {}\n",
+                    register.pretty(),
+                    expression.pretty(depth + 1)
+                ),
+            })
+            .join("");
+        format!(
+            "{{\n{statements}{indent}{}\n{}}}",
+            self.ret.pretty(),
+            INDENT.repeat(depth)
+        )
+    }
+}
+
+impl IntermediateLambda {
+    fn pretty_at(&self, depth: usize) -> String {
+        let args = self.args.iter().map(|arg| arg.register.pretty()).join(", ");
+        format!("fn({args}) {}", self.block.pretty(depth))
+    }
+    /// Render the lambda as indented code with registers written as `%N`.
+    pub fn pretty(&self) -> String {
+        self.pretty_at(0)
+    }
+}
+
+impl IntermediateProgram {
+    /// Render the main function as indented code with registers written as `%N`.
+    pub fn pretty(&self) -> String {
+        self.main.pretty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pretty_lambda() {
+        let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let sum = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let lambda = IntermediateLambda {
+            args: vec![arg.clone()],
+            block: IntermediateBlock {
+                statements: vec![IntermediateAssignment {
+                    register: sum.register.clone(),
+                    expression: IntermediateFnCall {
+                        fn_: BuiltInFn(
+                            Id::from("+"),
+                            IntermediateFnType(
+                                vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                                Box::new(AtomicTypeEnum::INT.into()),
+                            ),
+                        )
+                        .into(),
+                        args: vec![arg.clone().into(), Integer { value: 1 }.into()],
+                    }
+                    .into(),
+                }
+                .into()],
+                ret: sum.clone().into(),
+            },
+        };
+        let (arg, sum) = (arg.register.pretty(), sum.register.pretty());
+        assert_eq!(
+            lambda.pretty(),
+            format!("fn({arg}) {{\n    {sum} = +({arg}, 1)\n    {sum}\n}}")
+        );
+    }
+
+    #[test]
+    fn test_free_variables() {
+        let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let captured = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let sum = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let lambda = IntermediateLambda {
+            args: vec![arg.clone()],
+            block: IntermediateBlock {
+                statements: vec![IntermediateAssignment {
+                    register: sum.register.clone(),
+                    expression: IntermediateFnCall {
+                        fn_: BuiltInFn(
+                            Id::from("+"),
+                            IntermediateFnType(
+                                vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                                Box::new(AtomicTypeEnum::INT.into()),
+                            ),
+                        )
+                        .into(),
+                        args: vec![arg.clone().into(), captured.clone().into()],
+                    }
+                    .into(),
+                }
+                .into()],
+                ret: sum.clone().into(),
+            },
+        };
+        assert_eq!(
+            lambda.free_variables(),
+            HashSet::from([captured.register.clone()])
+        );
+    }
+
+    #[test]
+    fn test_pretty_nested_blocks() {
+        let bull = IntermediateUnionType(vec![None, None]);
+        let subject = IntermediateArg::from(IntermediateType::from(bull.clone()));
+        let target = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+        let condition = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+        let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let lambda = IntermediateLambda {
+            args: vec![subject.clone()],
+            block: IntermediateBlock {
+                statements: vec![
+                    IntermediateAssignment {
+                        register: condition.register.clone(),
+                        expression: IntermediateMatch {
+                            subject: subject.clone().into(),
+                            branches: vec![
+                                IntermediateMatchBranch {
+                                    target: Some(target.clone()),
+                                    block: IntermediateValue::from(target.clone()).into(),
+                                },
+                                IntermediateMatchBranch {
+                                    target: None,
+                                    block: IntermediateValue::from(Boolean { value: false }).into(),
+                                },
+                            ],
+                        }
+                        .into(),
+                    }
+                    .into(),
+                    IntermediateAssignment {
+                        register: result.register.clone(),
+                        expression: IntermediateIf {
+                            condition: condition.clone().into(),
+                            branches: (
+                                IntermediateValue::from(Integer { value: 1 }).into(),
+                                IntermediateValue::from(Integer { value: 0 }).into(),
+                            ),
+                        }
+                        .into(),
+                    }
+                    .into(),
+                ],
+                ret: result.clone().into(),
+            },
+        };
+        let (subject, target, condition, result) = (
+            subject.register.pretty(),
+            target.register.pretty(),
+            condition.register.pretty(),
+            result.register.pretty(),
+        );
+        assert_eq!(
+            IntermediateProgram {
+                main: lambda,
+                types: Vec::new()
+            }
+            .pretty(),
+            [
+                format!("fn({subject}) {{"),
+                format!("    {condition} = match {subject} {{"),
+                format!("        #0({target}) => {{"),
+                format!("            {target}"),
+                String::from("        }"),
+                String::from("        #1 => {"),
+                String::from("            false"),
+                String::from("        }"),
+                String::from("    }"),
+                format!("    {result} = if {condition} {{"),
+                String::from("        1"),
+                String::from("    } else {"),
+                String::from("        0"),
+                String::from("    }"),
+                format!("    {result}"),
+                String::from("}"),
+            ]
+            .join("\n")
+        );
+    }
+}
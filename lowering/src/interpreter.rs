@@ -0,0 +1,560 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use crate::{
+    Boolean, BuiltInFn, Id, Integer, IntermediateAssignment, IntermediateBlock,
+    IntermediateBuiltIn, IntermediateCtorCall, IntermediateElementAccess, IntermediateExpression,
+    IntermediateFnCall, IntermediateIf, IntermediateLambda, IntermediateMatch,
+    IntermediateMatchBranch, IntermediateMemory, IntermediateProgram, IntermediateStatement,
+    IntermediateTupleExpression, IntermediateValue, Register, Str,
+};
+
+/// Result of evaluating intermediate code.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    BuiltIn(IntermediateBuiltIn),
+    Tuple(Vec<Value>),
+    Variant(usize, Option<Box<Value>>),
+    Closure(Closure),
+}
+
+impl From<IntermediateBuiltIn> for Value {
+    fn from(value: IntermediateBuiltIn) -> Self {
+        Value::BuiltIn(value)
+    }
+}
+
+impl From<Integer> for Value {
+    fn from(value: Integer) -> Self {
+        IntermediateBuiltIn::from(value).into()
+    }
+}
+
+impl From<Boolean> for Value {
+    fn from(value: Boolean) -> Self {
+        IntermediateBuiltIn::from(value).into()
+    }
+}
+
+/// Lambda together with the frame it was defined in.
+#[derive(Clone)]
+pub struct Closure {
+    lambda: IntermediateLambda,
+    frame: Rc<Frame>,
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Closure").field(&self.lambda.args).finish()
+    }
+}
+
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        self.lambda == other.lambda && Rc::ptr_eq(&self.frame, &other.frame)
+    }
+}
+
+/// Registers assigned during a single fn call.
+/// Frames are shared with the closures defined inside them so that recursive fns can see themselves.
+#[derive(Default)]
+struct Frame {
+    values: RefCell<HashMap<Register, Value>>,
+    parent: Option<Rc<Frame>>,
+}
+
+impl Frame {
+    fn child(parent: &Rc<Frame>) -> Rc<Frame> {
+        Rc::new(Frame {
+            values: RefCell::new(HashMap::new()),
+            parent: Some(parent.clone()),
+        })
+    }
+    fn assign(&self, register: Register, value: Value) {
+        self.values.borrow_mut().insert(register, value);
+    }
+    fn lookup(&self, register: &Register) -> Value {
+        match self.values.borrow().get(register) {
+            Some(value) => value.clone(),
+            None => match &self.parent {
+                Some(parent) => parent.lookup(register),
+                None => panic!("Register {register:?} read before assignment."),
+            },
+        }
+    }
+}
+
+/// Tree-walking evaluator for intermediate programs, used to check that transformations preserve results.
+pub struct Interpreter {}
+
+impl Interpreter {
+    /// Divide with `op`, which wraps on overflow, panicking when dividing by zero.
+    fn divide(id: &Id, x: i64, y: i64, op: fn(i64, i64) -> i64) -> i64 {
+        if y == 0 {
+            panic!("Division by zero in {id}.")
+        }
+        op(x, y)
+    }
+    /// Raise `x` to the power of `y` by squaring, returning `0` for negative powers, as in the runtime.
+    fn power(x: i64, y: i64) -> i64 {
+        if y < 0 {
+            return 0;
+        }
+        let (mut result, mut base, mut exponent) = (1i64, x, y);
+        while exponent != 0 {
+            if exponent & 1 == 1 {
+                result = result.wrapping_mul(base);
+            }
+            exponent >>= 1;
+            base = base.wrapping_mul(base);
+        }
+        result
+    }
+    /// Call a built-in fn, where integer arithmetic wraps on overflow to match the runtime.
+    fn call_built_in(id: &Id, args: Vec<Value>) -> Value {
+        let integers = args
+            .iter()
+            .map(|arg| match arg {
+                Value::BuiltIn(IntermediateBuiltIn::Integer(Integer { value })) => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let booleans = args
+            .iter()
+            .map(|arg| match arg {
+                Value::BuiltIn(IntermediateBuiltIn::Boolean(Boolean { value })) => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let integer = |value: i64| Integer { value }.into();
+        let boolean = |value: bool| Boolean { value }.into();
+        match (id.as_str(), integers.as_deref(), booleans.as_deref()) {
+            ("**", Some(&[x, y]), _) => integer(Self::power(x, y)),
+            ("+", Some(&[x, y]), _) => integer(x.wrapping_add(y)),
+            ("-", Some(&[x, y]), _) => integer(x.wrapping_sub(y)),
+            ("*", Some(&[x, y]), _) => integer(x.wrapping_mul(y)),
+            ("/", Some(&[x, y]), _) => integer(Self::divide(id, x, y, i64::wrapping_div)),
+            ("%", Some(&[x, y]), _) => integer(Self::divide(id, x, y, i64::wrapping_rem)),
+            (">>", Some(&[x, y]), _) => integer(x.wrapping_shr(y as u32)),
+            // Shift in zeros rather than copies of the sign bit.
+            (">>>", Some(&[x, y]), _) => integer((x as u64).wrapping_shr(y as u32) as i64),
+            ("<<", Some(&[x, y]), _) => integer(x.wrapping_shl(y as u32)),
+            ("&", Some(&[x, y]), _) => integer(x & y),
+            ("|", Some(&[x, y]), _) => integer(x | y),
+            ("^", Some(&[x, y]), _) => integer(x ^ y),
+            ("<=>", Some(&[x, y]), _) => integer(x.cmp(&y) as i64),
+            ("fma", Some(&[x, y, z]), _) => integer(x.wrapping_mul(y).wrapping_add(z)),
+            ("min", Some(&[x, y]), _) => integer(x.min(y)),
+            ("max", Some(&[x, y]), _) => integer(x.max(y)),
+            ("+sat", Some(&[x, y]), _) => integer(x.saturating_add(y)),
+            ("-sat", Some(&[x, y]), _) => integer(x.saturating_sub(y)),
+            ("*sat", Some(&[x, y]), _) => integer(x.saturating_mul(y)),
+            ("+wrap", Some(&[x, y]), _) => integer(x.wrapping_add(y)),
+            ("-wrap", Some(&[x, y]), _) => integer(x.wrapping_sub(y)),
+            ("*wrap", Some(&[x, y]), _) => integer(x.wrapping_mul(y)),
+            ("-", Some(&[x]), _) => integer(x.wrapping_neg()),
+            ("++", Some(&[x]), _) => integer(x.wrapping_add(1)),
+            ("--", Some(&[x]), _) => integer(x.wrapping_sub(1)),
+            ("~", Some(&[x]), _) => integer(!x),
+            ("abs", Some(&[x]), _) => integer(x.wrapping_abs()),
+            ("<", Some(&[x, y]), _) => boolean(x < y),
+            ("<=", Some(&[x, y]), _) => boolean(x <= y),
+            (">", Some(&[x, y]), _) => boolean(x > y),
+            (">=", Some(&[x, y]), _) => boolean(x >= y),
+            ("==", Some(&[x, y]), _) => boolean(x == y),
+            ("!=", Some(&[x, y]), _) => boolean(x != y),
+            ("&&", _, Some(&[x, y])) => boolean(x && y),
+            ("||", _, Some(&[x, y])) => boolean(x || y),
+            ("!", _, Some(&[x])) => boolean(!x),
+            ("assert", _, Some(&[x])) => {
+                if !x {
+                    panic!("Assertion failed.")
+                }
+                Value::Tuple(Vec::new())
+            }
+            ("error", _, _) => match args.as_slice() {
+                [Value::BuiltIn(IntermediateBuiltIn::Str(Str { value }))] => panic!("{value}"),
+                _ => panic!("Invalid arguments to error: {args:?}."),
+            },
+            _ => panic!("Unsupported call to built-in fn {id} with {args:?}."),
+        }
+    }
+    fn call(fn_: Value, args: Vec<Value>) -> Value {
+        match fn_ {
+            Value::Closure(Closure { lambda, frame }) => {
+                let frame = Frame::child(&frame);
+                for (arg, value) in lambda.args.iter().zip(args) {
+                    frame.assign(arg.register.clone(), value);
+                }
+                Self::evaluate_block(&lambda.block, &frame)
+            }
+            Value::BuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(id, _))) => {
+                Self::call_built_in(&id, args)
+            }
+            fn_ => panic!("Calling non-function {fn_:?}."),
+        }
+    }
+    fn evaluate_value(value: &IntermediateValue, frame: &Rc<Frame>) -> Value {
+        match value {
+            IntermediateValue::IntermediateBuiltIn(built_in) => built_in.clone().into(),
+            IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register }) => {
+                frame.lookup(register)
+            }
+            IntermediateValue::IntermediateArg(arg) => frame.lookup(&arg.register),
+        }
+    }
+    fn evaluate_values(values: &[IntermediateValue], frame: &Rc<Frame>) -> Vec<Value> {
+        values
+            .iter()
+            .map(|value| Self::evaluate_value(value, frame))
+            .collect()
+    }
+    fn evaluate_expression(expression: &IntermediateExpression, frame: &Rc<Frame>) -> Value {
+        match expression {
+            IntermediateExpression::IntermediateValue(value) => Self::evaluate_value(value, frame),
+            IntermediateExpression::IntermediateElementAccess(IntermediateElementAccess {
+                value,
+                idx,
+            }) => match Self::evaluate_value(value, frame) {
+                Value::Tuple(mut values) => values.swap_remove(*idx),
+                value => panic!("Accessing non-tuple {value:?}."),
+            },
+            IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
+                values,
+            )) => Value::Tuple(Self::evaluate_values(values, frame)),
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_, args }) => {
+                Self::call(
+                    Self::evaluate_value(fn_, frame),
+                    Self::evaluate_values(args, frame),
+                )
+            }
+            IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
+                idx,
+                data,
+                type_: _,
+            }) => Value::Variant(
+                *idx,
+                data.as_ref()
+                    .map(|data| Box::new(Self::evaluate_value(data, frame))),
+            ),
+            IntermediateExpression::IntermediateLambda(lambda) => Value::Closure(Closure {
+                lambda: lambda.clone(),
+                frame: frame.clone(),
+            }),
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => match Self::evaluate_value(condition, frame) {
+                Value::BuiltIn(IntermediateBuiltIn::Boolean(Boolean { value: true })) => {
+                    Self::evaluate_block(&branches.0, frame)
+                }
+                Value::BuiltIn(IntermediateBuiltIn::Boolean(Boolean { value: false })) => {
+                    Self::evaluate_block(&branches.1, frame)
+                }
+                condition => panic!("Non-boolean condition {condition:?}."),
+            },
+            IntermediateExpression::IntermediateMatch(IntermediateMatch { subject, branches }) => {
+                let Value::Variant(idx, data) = Self::evaluate_value(subject, frame) else {
+                    panic!("Matching non-variant.")
+                };
+                let IntermediateMatchBranch { target, block } = &branches[idx];
+                if let (Some(target), Some(data)) = (target, data) {
+                    frame.assign(target.register.clone(), *data);
+                }
+                Self::evaluate_block(block, frame)
+            }
+        }
+    }
+    fn evaluate_block(block: &IntermediateBlock, frame: &Rc<Frame>) -> Value {
+        for statement in &block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            let value = Self::evaluate_expression(expression, frame);
+            frame.assign(register.clone(), value);
+        }
+        Self::evaluate_value(&block.ret, frame)
+    }
+    /// Call the main fn of a program with the given arguments.
+    /// Panics on runtime errors, such as division by zero or calls to `error`.
+    pub fn run(program: &IntermediateProgram, args: Vec<Value>) -> Value {
+        Self::call(
+            Value::Closure(Closure {
+                lambda: program.main.clone(),
+                frame: Rc::default(),
+            }),
+            args,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        test_utils::program, AtomicTypeEnum, IntermediateArg, IntermediateFnType, IntermediateType,
+        IntermediateUnionType,
+    };
+    use test_case::test_case;
+
+    fn int() -> IntermediateType {
+        AtomicTypeEnum::INT.into()
+    }
+
+    fn built_in(id: &str, args: Vec<IntermediateValue>) -> IntermediateExpression {
+        let ret = match id {
+            "<" | "==" => AtomicTypeEnum::BOOL,
+            _ => AtomicTypeEnum::INT,
+        };
+        IntermediateFnCall {
+            fn_: BuiltInFn(
+                Id::from(id),
+                IntermediateFnType(args.iter().map(|_| int()).collect(), Box::new(ret.into())),
+            )
+            .into(),
+            args,
+        }
+        .into()
+    }
+
+    fn assign(
+        memory: &IntermediateMemory,
+        expression: IntermediateExpression,
+    ) -> IntermediateStatement {
+        IntermediateAssignment {
+            register: memory.register.clone(),
+            expression,
+        }
+        .into()
+    }
+
+    #[test_case(
+        {
+            let x = IntermediateArg::from(int());
+            let y = IntermediateMemory::from(int());
+            let z = IntermediateMemory::from(int());
+            program(
+                vec![x.clone()],
+                vec![
+                    assign(&y, built_in("*", vec![x.into(), Integer { value: 3 }.into()])),
+                    assign(&z, built_in("+", vec![y.into(), Integer { value: 1 }.into()])),
+                ],
+                z.into(),
+            )
+        },
+        vec![Integer { value: 4 }.into()],
+        Integer { value: 13 }.into();
+        "arithmetic"
+    )]
+    #[test_case(
+        {
+            let tuple = IntermediateMemory::from(IntermediateType::from(
+                crate::IntermediateTupleType(vec![int(), AtomicTypeEnum::BOOL.into()])
+            ));
+            let element = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            program(
+                Vec::new(),
+                vec![
+                    assign(&tuple, IntermediateTupleExpression(vec![
+                        Integer { value: 1 }.into(),
+                        Boolean { value: true }.into(),
+                    ]).into()),
+                    assign(&element, IntermediateElementAccess {
+                        value: tuple.into(),
+                        idx: 1,
+                    }.into()),
+                ],
+                element.into(),
+            )
+        },
+        Vec::new(),
+        Boolean { value: true }.into();
+        "tuple access"
+    )]
+    #[test_case(
+        {
+            let option = IntermediateUnionType(vec![None, Some(int())]);
+            let value = IntermediateMemory::from(IntermediateType::from(option.clone()));
+            let x = IntermediateArg::from(int());
+            let result = IntermediateMemory::from(int());
+            program(
+                Vec::new(),
+                vec![
+                    assign(&value, IntermediateCtorCall {
+                        idx: 1,
+                        data: Some(Integer { value: 5 }.into()),
+                        type_: option,
+                    }.into()),
+                    assign(&result, IntermediateMatch {
+                        subject: value.into(),
+                        branches: vec![
+                            IntermediateMatchBranch {
+                                target: None,
+                                block: IntermediateValue::from(Integer { value: 0 }).into(),
+                            },
+                            IntermediateMatchBranch {
+                                target: Some(x.clone()),
+                                block: IntermediateValue::from(x).into(),
+                            },
+                        ],
+                    }.into()),
+                ],
+                result.into(),
+            )
+        },
+        Vec::new(),
+        Integer { value: 5 }.into();
+        "match"
+    )]
+    #[test_case(
+        {
+            // factorial = (n: int) -> int { if n < 1 { 1 } else { n * factorial(n - 1) } }
+            let factorial = IntermediateMemory::from(IntermediateType::from(
+                IntermediateFnType(vec![int()], Box::new(int()))
+            ));
+            let n = IntermediateArg::from(int());
+            let base = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let predecessor = IntermediateMemory::from(int());
+            let recursion = IntermediateMemory::from(int());
+            let product = IntermediateMemory::from(int());
+            let result = IntermediateMemory::from(int());
+            let call = IntermediateMemory::from(int());
+            program(
+                Vec::new(),
+                vec![
+                    assign(&factorial, IntermediateLambda {
+                        args: vec![n.clone()],
+                        block: IntermediateBlock {
+                            statements: vec![
+                                assign(&base, built_in("<", vec![n.clone().into(), Integer { value: 1 }.into()])),
+                                assign(&result, IntermediateIf {
+                                    condition: base.into(),
+                                    branches: (
+                                        IntermediateValue::from(Integer { value: 1 }).into(),
+                                        IntermediateBlock {
+                                            statements: vec![
+                                                assign(&predecessor, built_in("-", vec![n.clone().into(), Integer { value: 1 }.into()])),
+                                                assign(&recursion, IntermediateFnCall {
+                                                    fn_: factorial.clone().into(),
+                                                    args: vec![predecessor.into()],
+                                                }.into()),
+                                                assign(&product, built_in("*", vec![n.into(), recursion.into()])),
+                                            ],
+                                            ret: product.into(),
+                                        },
+                                    ),
+                                }.into()),
+                            ],
+                            ret: result.into(),
+                        },
+                    }.into()),
+                    assign(&call, IntermediateFnCall {
+                        fn_: factorial.into(),
+                        args: vec![Integer { value: 5 }.into()],
+                    }.into()),
+                ],
+                call.into(),
+            )
+        },
+        Vec::new(),
+        Integer { value: 120 }.into();
+        "recursion"
+    )]
+    #[test_case(
+        {
+            // adder = (x: int) -> (int -> int) { (y: int) -> int { x + y } }
+            let adder = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+                vec![int()],
+                Box::new(IntermediateFnType(vec![int()], Box::new(int())).into()),
+            )));
+            let x = IntermediateArg::from(int());
+            let y = IntermediateArg::from(int());
+            let sum = IntermediateMemory::from(int());
+            let lambda = IntermediateMemory::from(IntermediateType::from(
+                IntermediateFnType(vec![int()], Box::new(int()))
+            ));
+            let one = IntermediateMemory::from(lambda.type_());
+            let two = IntermediateMemory::from(lambda.type_());
+            let result = IntermediateMemory::from(int());
+            program(
+                Vec::new(),
+                vec![
+                    assign(&adder, IntermediateLambda {
+                        args: vec![x.clone()],
+                        block: IntermediateBlock {
+                            statements: vec![assign(&lambda, IntermediateLambda {
+                                args: vec![y.clone()],
+                                block: IntermediateBlock {
+                                    statements: vec![assign(&sum, built_in("+", vec![x.into(), y.into()]))],
+                                    ret: sum.into(),
+                                },
+                            }.into())],
+                            ret: lambda.into(),
+                        },
+                    }.into()),
+                    assign(&one, IntermediateFnCall {
+                        fn_: adder.clone().into(),
+                        args: vec![Integer { value: 1 }.into()],
+                    }.into()),
+                    assign(&two, IntermediateFnCall {
+                        fn_: adder.into(),
+                        args: vec![Integer { value: 2 }.into()],
+                    }.into()),
+                    assign(&result, IntermediateFnCall {
+                        fn_: one.into(),
+                        args: vec![Integer { value: 10 }.into()],
+                    }.into()),
+                ],
+                result.into(),
+            )
+        },
+        Vec::new(),
+        Integer { value: 11 }.into();
+        "closure capture"
+    )]
+    fn test_run(program: IntermediateProgram, args: Vec<Value>, expected: Value) {
+        assert_eq!(Interpreter::run(&program, args), expected);
+    }
+
+    #[test_case(">>", 20, 5; "arithmetic shift of positive")]
+    #[test_case(">>>", 20, 5; "logical shift of positive")]
+    #[test_case(">>", -8, -2; "arithmetic shift of negative")]
+    #[test_case(">>>", -8, 0x3FFF_FFFF_FFFF_FFFE; "logical shift of negative")]
+    #[test_case("+", i64::MAX, i64::MIN + 1; "wrapping addition")]
+    #[test_case("*", i64::MAX, -2; "wrapping multiplication")]
+    #[test_case("**", 1 << 32, 0; "wrapping power")]
+    #[test_case("**", 3, 9; "power")]
+    fn test_run_integer_operators(id: &str, value: i64, expected: i64) {
+        let x = IntermediateArg::from(int());
+        let y = IntermediateMemory::from(int());
+        let program = program(
+            vec![x.clone()],
+            vec![assign(
+                &y,
+                built_in(id, vec![x.into(), Integer { value: 2 }.into()]),
+            )],
+            y.into(),
+        );
+        assert_eq!(
+            Interpreter::run(&program, vec![Integer { value }.into()]),
+            Integer { value: expected }.into()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero in /.")]
+    fn test_run_division_by_zero() {
+        let x = IntermediateArg::from(int());
+        let y = IntermediateMemory::from(int());
+        let program = program(
+            vec![x.clone()],
+            vec![assign(
+                &y,
+                built_in("/", vec![Integer { value: 1 }.into(), x.into()]),
+            )],
+            y.into(),
+        );
+        Interpreter::run(&program, vec![Integer { value: 0 }.into()]);
+    }
+}
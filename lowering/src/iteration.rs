@@ -0,0 +1,137 @@
+use crate::intermediate_nodes::*;
+use type_checker::{AtomicTypeEnum, Id, Integer};
+
+/// Generate the tail-recursive fns that implement the `iterate` built-in.
+pub struct IterationGenerator {}
+
+impl IterationGenerator {
+    fn integer_operator(id: &str, ret: AtomicTypeEnum) -> IntermediateValue {
+        BuiltInFn(
+            Id::from(id),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                Box::new(ret.into()),
+            ),
+        )
+        .into()
+    }
+    /// Generate the statements that define `iterate` for values of a type and the value that stores it.
+    /// The fn calls itself in tail position, so it is compiled into a loop rather than a chain of calls.
+    pub fn generate(type_: &IntermediateType) -> (Vec<IntermediateStatement>, IntermediateValue) {
+        let fn_type = IntermediateFnType(vec![type_.clone()], Box::new(type_.clone()));
+        let iterate = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![
+                AtomicTypeEnum::INT.into(),
+                fn_type.clone().into(),
+                type_.clone(),
+            ],
+            Box::new(type_.clone()),
+        )));
+        let n = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let f = IntermediateArg::from(IntermediateType::from(fn_type));
+        let x = IntermediateArg::from(type_.clone());
+
+        // `done = n <= 0`
+        let done = IntermediateAssignment::from(IntermediateExpression::from(IntermediateFnCall {
+            fn_: Self::integer_operator("<=", AtomicTypeEnum::BOOL),
+            args: vec![n.clone().into(), Integer { value: 0 }.into()],
+        }));
+        // `iterate(n - 1, f, f(x))`
+        let remaining =
+            IntermediateAssignment::from(IntermediateExpression::from(IntermediateFnCall {
+                fn_: Self::integer_operator("-", AtomicTypeEnum::INT),
+                args: vec![n.clone().into(), Integer { value: 1 }.into()],
+            }));
+        let next = IntermediateAssignment::from(IntermediateExpression::from(IntermediateFnCall {
+            fn_: f.clone().into(),
+            args: vec![x.clone().into()],
+        }));
+        let rest = IntermediateAssignment::from(IntermediateExpression::from(IntermediateFnCall {
+            fn_: iterate.clone().into(),
+            args: vec![
+                remaining.clone().into(),
+                f.clone().into(),
+                next.clone().into(),
+            ],
+        }));
+        let result = IntermediateAssignment::from(IntermediateExpression::from(IntermediateIf {
+            condition: done.clone().into(),
+            branches: (
+                IntermediateValue::from(x.clone()).into(),
+                (
+                    vec![remaining.into(), next.into(), rest.clone().into()],
+                    rest.into(),
+                )
+                    .into(),
+            ),
+        }));
+        let statements = vec![IntermediateAssignment {
+            register: iterate.register.clone(),
+            expression: IntermediateLambda {
+                args: vec![n, f, x],
+                block: IntermediateBlock {
+                    statements: vec![done.into(), result.clone().into()],
+                    ret: result.into(),
+                },
+            }
+            .into(),
+        }
+        .into()];
+        (statements, iterate.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{IntermediateProgram, Interpreter, RecursiveFnFinder, Value};
+
+    #[test]
+    fn test_generate_iterate() {
+        let type_ = IntermediateType::from(AtomicTypeEnum::INT);
+        let (statements, iterate) = IterationGenerator::generate(&type_);
+        let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            register,
+            expression: IntermediateExpression::IntermediateLambda(_),
+        }) = &statements[0]
+        else {
+            panic!("Expected a single fn.")
+        };
+        // `iterate(3, ++, 4)`
+        let result =
+            IntermediateAssignment::from(IntermediateExpression::from(IntermediateFnCall {
+                fn_: iterate,
+                args: vec![
+                    Integer { value: 3 }.into(),
+                    BuiltInFn(
+                        Id::from("++"),
+                        IntermediateFnType(vec![type_.clone()], Box::new(type_.clone())),
+                    )
+                    .into(),
+                    Integer { value: 4 }.into(),
+                ],
+            }));
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: statements
+                        .clone()
+                        .into_iter()
+                        .chain([result.clone().into()])
+                        .collect(),
+                    ret: result.into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        assert!(RecursiveFnFinder::recursive_fns(&program)
+            .tail_recursive
+            .contains(register));
+        assert_eq!(
+            Interpreter::run(&program, Vec::new()),
+            Value::from(IntermediateBuiltIn::from(Integer { value: 7 }))
+        );
+    }
+}
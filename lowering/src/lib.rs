@@ -1,15 +1,32 @@
+mod canonicalizer;
 mod copy_propagation;
+mod diff;
+mod equality;
 mod expression_equality_checker;
+mod expression_hasher;
 mod fn_inst;
 mod intermediate_nodes;
+mod interpreter;
+mod iteration;
 mod lower;
+mod purity;
 mod recursive_fn_finder;
+mod serialization;
+#[cfg(test)]
+mod test_utils;
 mod type_equality_checker;
 
+pub use canonicalizer::Canonicalizer;
 pub use copy_propagation::CopyPropagator;
+pub use diff::{diff, Difference};
+pub use equality::EqualityGenerator;
 pub use expression_equality_checker::ExpressionEqualityChecker;
+pub use expression_hasher::ExpressionHasher;
 pub use fn_inst::{FnDefs, FnInst};
 pub use intermediate_nodes::*;
+pub use interpreter::{Closure, Interpreter, Value};
+pub use iteration::IterationGenerator;
 pub use lower::Lowerer;
+pub use purity::{PureFns, PurityAnalyzer, DIVERGING_BUILT_INS, IMPURE_BUILT_INS};
 pub use recursive_fn_finder::{RecursiveFnFinder, RecursiveFns};
-pub use type_checker::{AtomicTypeEnum, Boolean, Id, Integer, DEFAULT_CONTEXT};
+pub use type_checker::{AtomicTypeEnum, Boolean, Char, Id, Integer, Str, DEFAULT_CONTEXT};
@@ -9,7 +9,9 @@ use std::{
 
 use crate::{
     copy_propagation::{CopyPropagator, MemoryMap},
+    equality::EqualityGenerator,
     intermediate_nodes::*,
+    iteration::IterationGenerator,
 };
 use itertools::{zip_eq, Itertools};
 use type_checker::*;
@@ -30,6 +32,10 @@ pub struct Lowerer {
 
 impl Lowerer {
     pub fn new() -> Self {
+        Self::with_context(&TypeContext::new())
+    }
+    /// Create a lowerer that treats the fns in `extra` as built-ins, in addition to the default context.
+    pub fn with_context(extra: &TypeContext) -> Self {
         let mut lowerer = Lowerer {
             scope: Scope::new(),
             history: History::new(),
@@ -38,18 +44,30 @@ impl Lowerer {
             statements: Vec::new(),
             memory: MemoryMap::new(),
         };
-        // Add default context to scope.
-        let scope = DEFAULT_CONTEXT.with(|context| {
-            Scope::from_iter(context.iter().map(|(id, var)| {
-                let IntermediateType::IntermediateFnType(type_) =
-                    lowerer.lower_type(&var.type_.type_)
-                else {
-                    panic!("Default functions have incorrect types.")
-                };
-                let variable = var.variable.clone();
-                ((variable, Vec::new()), BuiltInFn(id.clone(), type_).into())
-            }))
-        });
+        // Add default context and overloads to scope.
+        let context = DEFAULT_CONTEXT.with(|context| (**context).clone());
+        let overloads = STRING_COMPARISONS.with(|overloads| (**overloads).clone());
+        let char_overloads = CHAR_COMPARISONS.with(|overloads| (**overloads).clone());
+        let unary_operators = UNARY_OPERATORS.with(|overloads| (**overloads).clone());
+        let scope = Scope::from_iter(
+            context
+                .iter()
+                .chain(overloads.iter())
+                .chain(char_overloads.iter())
+                .chain(unary_operators.iter())
+                .chain(extra.iter())
+                // Generic built-ins are generated for each instantiation.
+                .filter(|(_, var)| var.type_.parameters.is_empty())
+                .map(|(id, var)| {
+                    let IntermediateType::IntermediateFnType(type_) =
+                        lowerer.lower_type(&var.type_.type_)
+                    else {
+                        panic!("Default functions have incorrect types.")
+                    };
+                    let variable = var.variable.clone();
+                    ((variable, Vec::new()), BuiltInFn(id.clone(), type_).into())
+                }),
+        );
         lowerer.scope = scope;
         lowerer
     }
@@ -78,6 +96,8 @@ impl Lowerer {
         match expression {
             TypedExpression::Integer(integer) => IntermediateBuiltIn::Integer(integer).into(),
             TypedExpression::Boolean(boolean) => IntermediateBuiltIn::Boolean(boolean).into(),
+            TypedExpression::Str(string) => IntermediateBuiltIn::Str(string).into(),
+            TypedExpression::Char(character) => IntermediateBuiltIn::Char(character).into(),
             TypedExpression::TypedTuple(tuple) => {
                 let tuple = self.lower_tuple(tuple).into();
                 self.get_cached_value(tuple)
@@ -86,11 +106,25 @@ impl Lowerer {
                 let element_access = self.lower_element_access(element_access).into();
                 self.get_cached_value(element_access)
             }
-            TypedExpression::TypedAccess(access) => self.lower_access(access),
-            TypedExpression::TypedFunctionCall(fn_call) => {
-                let fn_call = self.lower_fn_call(fn_call).into();
-                self.get_cached_value(fn_call)
+            TypedExpression::TypedRecord(record) => {
+                let tuple = self.lower_record(record).into();
+                self.get_cached_value(tuple)
+            }
+            TypedExpression::TypedFieldAccess(field_access) => {
+                let element_access = self.lower_field_access(field_access).into();
+                self.get_cached_value(element_access)
             }
+            TypedExpression::TypedAccess(access) => self.lower_access(access),
+            TypedExpression::TypedFunctionCall(fn_call) => match Self::short_circuit(&fn_call) {
+                Some(if_) => {
+                    let if_ = self.lower_if(if_).into();
+                    self.get_cached_value(if_)
+                }
+                None => {
+                    let fn_call = self.lower_fn_call(fn_call).into();
+                    self.get_cached_value(fn_call)
+                }
+            },
             TypedExpression::TypedLambdaDef(fn_def) => {
                 let lambda_def = self.lower_lambda_def(fn_def).into();
                 self.get_cached_value(lambda_def)
@@ -126,6 +160,54 @@ impl Lowerer {
             idx: index,
         }
     }
+    fn lower_record(&mut self, TypedRecord { fields }: TypedRecord) -> IntermediateTupleExpression {
+        // Evaluate fields in the order they are written but store them in order of their names.
+        let values = fields
+            .into_iter()
+            .map(|(id, expression)| (id, self.lower_expression(expression)))
+            .collect::<BTreeMap<_, _>>();
+        IntermediateTupleExpression(values.into_values().collect())
+    }
+    fn lower_field_access(
+        &mut self,
+        TypedFieldAccess { expression, field }: TypedFieldAccess,
+    ) -> IntermediateElementAccess {
+        let Type::TypeRecord(record) = expression.type_() else {
+            panic!("Field access on a value that is not a record.")
+        };
+        let idx = record
+            .index(&field)
+            .expect("Field access to a missing field.");
+        IntermediateElementAccess {
+            value: self.lower_expression(*expression),
+            idx,
+        }
+    }
+    /// Determine whether a variable is a particular built-in fn.
+    fn is_built_in(id: &str, variable: &Variable) -> bool {
+        DEFAULT_CONTEXT.with(|context| {
+            context
+                .get(&Id::from(id))
+                .is_some_and(|built_in| &built_in.variable == variable)
+        })
+    }
+    /// Add generated fn definitions to the current block.
+    fn add_generated_statements(&mut self, statements: Vec<IntermediateStatement>) {
+        for statement in statements {
+            let IntermediateStatement::IntermediateAssignment(assignment) = &statement;
+            self.update_memory(assignment.register.clone(), assignment.expression.clone());
+            self.statements.push(statement);
+        }
+    }
+    /// Find the type of a generic built-in fn (other than `eq` and `iterate`) that a variable refers to.
+    fn generic_built_in(variable: &Variable) -> Option<(Id, ParametricType)> {
+        DEFAULT_CONTEXT.with(|context| {
+            context.iter().find_map(|(id, built_in)| {
+                (&built_in.variable == variable && !built_in.type_.parameters.is_empty())
+                    .then(|| (id.clone(), built_in.type_.clone()))
+            })
+        })
+    }
     fn lower_access(
         &mut self,
         TypedAccess {
@@ -137,6 +219,30 @@ impl Lowerer {
             .scope
             .contains_key(&(variable.variable.clone(), parameters.clone()))
         {
+            if Self::is_built_in("eq", &variable.variable) {
+                let type_ = self.lower_type(&parameters[0]);
+                let (statements, comparator) = EqualityGenerator::generate(&type_);
+                self.add_generated_statements(statements);
+                return comparator;
+            }
+            if Self::is_built_in("iterate", &variable.variable) {
+                let type_ = self.lower_type(&parameters[0]);
+                let (statements, iterate) = IterationGenerator::generate(&type_);
+                self.add_generated_statements(statements);
+                return iterate;
+            }
+            if let Some((id, type_)) = Self::generic_built_in(&variable.variable) {
+                // Generic built-ins are given the type of their instantiation.
+                let IntermediateType::IntermediateFnType(fn_type) =
+                    self.lower_type(&type_.instantiate(&parameters))
+                else {
+                    panic!("Generic built-ins have incorrect types.")
+                };
+                let built_in = IntermediateValue::from(BuiltInFn(id, fn_type));
+                self.scope
+                    .insert((variable.variable, parameters), built_in.clone());
+                return built_in;
+            }
             let uninstantiated = &self.uninstantiated[&variable.variable];
             let (memory, expression) = self
                 .add_placeholder_assignment(uninstantiated.clone(), Some(parameters.clone()))
@@ -146,6 +252,43 @@ impl Lowerer {
         };
         self.scope[&(variable.variable, parameters)].clone()
     }
+    /// Desugar a call to `&&` or `||` into an `if` so that the right operand is only evaluated when needed.
+    fn short_circuit(
+        TypedFunctionCall {
+            function,
+            arguments,
+        }: &TypedFunctionCall,
+    ) -> Option<TypedIf> {
+        let TypedExpression::TypedAccess(TypedAccess {
+            variable,
+            parameters: _,
+        }) = &**function
+        else {
+            return None;
+        };
+        let [left, right] = &arguments[..] else {
+            return None;
+        };
+        let operator = DEFAULT_CONTEXT.with(|context| {
+            ["&&", "||"]
+                .into_iter()
+                .find(|operator| context[&Id::from(*operator)].variable == variable.variable)
+        })?;
+        let block = |expression: TypedExpression| TypedBlock {
+            statements: Vec::new(),
+            expression: Box::new(expression),
+        };
+        let (true_block, false_block) = if operator == "&&" {
+            (block(right.clone()), block(Boolean { value: false }.into()))
+        } else {
+            (block(Boolean { value: true }.into()), block(right.clone()))
+        };
+        Some(TypedIf {
+            condition: Box::new(left.clone()),
+            true_block,
+            false_block,
+        })
+    }
     fn lower_fn_call(
         &mut self,
         TypedFunctionCall {
@@ -199,7 +342,13 @@ impl Lowerer {
         let lower_data = match &arguments[..] {
             [] => None,
             [argument] => Some(self.lower_expression(argument.clone())),
-            _ => panic!("Multiple arguments in constructor call."),
+            // Multiple arguments are stored as a tuple.
+            _ => {
+                let tuple = self.lower_tuple(TypedTuple {
+                    expressions: arguments,
+                });
+                Some(self.get_cached_value(tuple.into()))
+            }
         };
         IntermediateCtorCall {
             idx,
@@ -315,6 +464,15 @@ impl Lowerer {
                 instances: clear_names(types),
             }),
             Type::TypeTuple(TypeTuple(types)) => Type::from(TypeTuple(clear_names(types))),
+            Type::TypeRecord(TypeRecord(fields)) => Type::from(TypeRecord(
+                fields
+                    .iter()
+                    .map(|(id, type_)| (id.clone(), self.clear_names(type_)))
+                    .collect(),
+            )),
+            Type::TypeArray(TypeArray(type_)) => {
+                Type::from(TypeArray(Box::new(self.clear_names(type_))))
+            }
             Type::TypeFn(TypeFn(args, ret)) => {
                 Type::TypeFn(TypeFn(clear_names(args), Box::new(self.clear_names(&*ret))))
             }
@@ -401,6 +559,15 @@ impl Lowerer {
             Type::TypeTuple(TypeTuple(types)) => {
                 IntermediateTupleType(self.lower_types_internal(types, visited_references)).into()
             }
+            // Records are stored as tuples with their fields ordered by name.
+            Type::TypeRecord(TypeRecord(fields)) => IntermediateTupleType(
+                self.lower_types_internal(&fields.values().cloned().collect(), visited_references),
+            )
+            .into(),
+            Type::TypeArray(TypeArray(type_)) => IntermediateArrayType(Box::new(
+                self.lower_type_internal(type_, visited_references),
+            ))
+            .into(),
             Type::TypeFn(TypeFn(args, ret)) => IntermediateFnType(
                 self.lower_types_internal(args, visited_references.clone()),
                 Box::new(self.lower_type_internal(&*ret, visited_references)),
@@ -471,6 +638,15 @@ impl Lowerer {
             self.perform_assignment(placeholder, expression);
         }
     }
+    /// Lower a single top-level statement, keeping the bindings from previous calls in scope.
+    /// Statements are lowered one at a time, so (unlike `lower`) a statement cannot refer to statements that are lowered after it.
+    pub fn lower_statement(&mut self, statement: TypedStatement) -> Vec<IntermediateStatement> {
+        let statements = std::mem::take(&mut self.statements);
+        self.lower_statements(vec![statement]);
+        let intermediate_statements = std::mem::replace(&mut self.statements, statements);
+        CopyPropagator::from_memory_map(self.memory.clone())
+            .propagate_copies_in_statements(intermediate_statements)
+    }
     fn lower_program(&mut self, program: TypedProgram) -> IntermediateProgram {
         let main = self.lower_lambda_def(program.main);
         let copy_propagator = CopyPropagator::from_memory_map(self.memory.clone());
@@ -488,6 +664,11 @@ impl Lowerer {
         let mut lowerer = Lowerer::new();
         lowerer.lower_program(program)
     }
+    /// Lower a program that was type checked with extra built-in fns.
+    pub fn lower_with_context(program: TypedProgram, extra: &TypeContext) -> IntermediateProgram {
+        let mut lowerer = Lowerer::with_context(extra);
+        lowerer.lower_program(program)
+    }
 }
 
 #[cfg(test)]
@@ -516,6 +697,22 @@ mod tests {
         );
         "boolean"
     )]
+    #[test_case(
+        TypedExpression::Str(Str { value: String::from("a\tb") }),
+        (
+            IntermediateBuiltIn::Str(Str { value: String::from("a\tb") }).into(),
+            Vec::new()
+        );
+        "string"
+    )]
+    #[test_case(
+        TypedExpression::Char(Char { value: 97 }),
+        (
+            IntermediateBuiltIn::Char(Char { value: 97 }).into(),
+            Vec::new()
+        );
+        "char"
+    )]
     #[test_case(
         TypedTuple{
             expressions: Vec::new()
@@ -610,6 +807,37 @@ mod tests {
         };
         "operator call"
     )]
+    #[test_case(
+        TypedFunctionCall{
+            function: Box::new(
+                TypedAccess {
+                    variable: STRING_COMPARISONS.with(|overloads| overloads.get(&Id::from("==")).unwrap().clone()),
+                    parameters :Vec::new()
+                }.into()
+            ),
+            arguments: vec![
+                Str{value: String::new()}.into(),
+                Str{value: String::from("a")}.into(),
+            ]
+        }.into(),
+        {
+            let memory: IntermediateAssignment = IntermediateExpression::IntermediateFnCall(IntermediateFnCall{
+                fn_: BuiltInFn(
+                        Id::from("=="),
+                        IntermediateFnType(
+                            vec![AtomicTypeEnum::STRING.into(), AtomicTypeEnum::STRING.into()],
+                            Box::new(AtomicTypeEnum::BOOL.into())
+                        )
+                    ).into(),
+                args: vec![
+                    IntermediateBuiltIn::Str(Str { value: String::new() }).into(),
+                    IntermediateBuiltIn::Str(Str { value: String::from("a") }).into(),
+                ]
+            }).into();
+            (memory.clone().into(), vec![memory.into()])
+        };
+        "string comparison call"
+    )]
     #[test_case(
         {
             let parameters = vec![
@@ -684,6 +912,67 @@ mod tests {
         };
         "element access"
     )]
+    #[test_case(
+        TypedRecord{
+            fields: vec![
+                (Id::from("name"), Integer{value: 1}.into()),
+                (Id::from("age"), Boolean{value: true}.into()),
+            ]
+        }.into(),
+        {
+            let memory: IntermediateAssignment = IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
+                vec![
+                    IntermediateBuiltIn::Boolean(Boolean { value: true }).into(),
+                    IntermediateBuiltIn::Integer(Integer { value: 1 }).into(),
+                ]
+            )).into();
+            (memory.clone().into(), vec![memory.into()])
+        };
+        "record"
+    )]
+    #[test_case(
+        {
+            let arg: TypedVariable = Type::from(TypeRecord(BTreeMap::from([
+                (Id::from("name"), TYPE_INT),
+                (Id::from("age"), TYPE_BOOL),
+            ]))).into();
+            TypedLambdaDef{
+                parameters: vec![arg.clone()],
+                return_type: Box::new(TYPE_INT),
+                body: TypedBlock{
+                    statements: Vec::new(),
+                    expression: Box::new(TypedFieldAccess{
+                        expression: Box::new(TypedAccess{
+                            variable: arg,
+                            parameters: Vec::new()
+                        }.into()),
+                        field: Id::from("name")
+                    }.into())
+                }
+            }.into()
+        },
+        {
+            let arg: IntermediateArg = IntermediateType::from(IntermediateTupleType(vec![
+                IntermediateType::from(AtomicTypeEnum::BOOL),
+                IntermediateType::from(AtomicTypeEnum::INT)
+            ])).into();
+            let result: IntermediateAssignment = IntermediateExpression::from(IntermediateElementAccess{
+                value: arg.clone().into(),
+                idx: 1
+            }).into();
+            let memory: IntermediateAssignment = IntermediateExpression::IntermediateLambda(IntermediateLambda {
+                args: vec![arg.clone()],
+                block: IntermediateBlock {
+                    statements: vec![
+                        result.clone().into()
+                    ],
+                    ret: result.into(),
+                }
+            }).into();
+            (memory.clone().into(), vec![memory.into()])
+        };
+        "field access"
+    )]
     #[test_case(
         {
             let parameters = vec![
@@ -836,6 +1125,46 @@ mod tests {
         };
         "data-value constructor"
     )]
+    #[test_case(
+        TypedConstructorCall{
+            idx: 0,
+            output_type: Type::from(TypeUnion{
+                id: Id::from("Pair_Int"),
+                variants: vec![
+                    Some(Type::from(TypeTuple(vec![TYPE_INT, TYPE_BOOL]))),
+                ],
+            }),
+            arguments: vec![
+                Integer{value: 8}.into(),
+                Boolean{value: false}.into()
+            ]
+        }.into(),
+        {
+            let tuple: IntermediateAssignment = IntermediateExpression::from(
+                IntermediateTupleExpression(vec![
+                    IntermediateBuiltIn::from(Integer{value: 8}).into(),
+                    IntermediateBuiltIn::from(Boolean{value: false}).into()
+                ])
+            ).into();
+            let memory: IntermediateAssignment = IntermediateExpression::from(
+                IntermediateCtorCall{
+                    idx: 0,
+                    data: Some(tuple.clone().into()),
+                    type_: IntermediateUnionType(vec![
+                        Some(IntermediateTupleType(vec![
+                            AtomicTypeEnum::INT.into(),
+                            AtomicTypeEnum::BOOL.into()
+                        ]).into())
+                    ])
+                }
+            ).into();
+            (
+                memory.clone().into(),
+                vec![tuple.into(), memory.into()]
+            )
+        };
+        "multi-field constructor"
+    )]
     #[test_case(
         {
             let reference = Rc::new(RefCell::new(ParametricType::new()));
@@ -1398,6 +1727,79 @@ mod tests {
         };
         "match statement value and default"
     )]
+    #[test_case(
+        {
+            let arg = TypedVariable::from(Type::from(TypeUnion{id: Id::from("Option"),variants: vec![Some(TYPE_INT),None]}));
+            TypedLambdaDef{
+                parameters: vec![arg.clone()],
+                body: TypedBlock{
+                    statements: Vec::new(),
+                    expression: Box::new(TypedMatch{
+                        subject: Box::new(
+                            TypedAccess{
+                                variable: arg,
+                                parameters: Vec::new()
+                            }.into(),
+                        ),
+                        blocks: vec![
+                            TypedMatchBlock{
+                                matches: vec![
+                                    TypedMatchItem {
+                                        type_idx: 1,
+                                        assignee: None
+                                    },
+                                    TypedMatchItem {
+                                        type_idx: 0,
+                                        assignee: None
+                                    },
+                                ],
+                                block: TypedBlock {
+                                    statements: Vec::new(),
+                                    expression: Box::new(
+                                        Integer{value: 0}.into()
+                                    )
+                                }
+                            },
+                        ],
+                    }.into())
+                },
+                return_type: Box::new(TYPE_INT)
+            }.into()
+        },
+        {
+            let arg: IntermediateArg = IntermediateType::from(IntermediateUnionType(vec![Some(AtomicTypeEnum::INT.into()),None])).into();
+            let return_address: IntermediateAssignment = IntermediateValue::from(IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT))).into();
+            let memory: IntermediateAssignment = IntermediateExpression::IntermediateLambda(IntermediateLambda {
+                args: vec![arg.clone()],
+                block: IntermediateBlock{
+                    statements: vec![
+                        IntermediateAssignment{
+                            register: return_address.register.clone(),
+                            expression: IntermediateMatch{
+                                subject: arg.into(),
+                                branches: vec![
+                                    IntermediateMatchBranch{
+                                        target: None,
+                                        block: IntermediateValue::from(Integer{value: 0}).into()
+                                    },
+                                    IntermediateMatchBranch{
+                                        target: None,
+                                        block: IntermediateValue::from(Integer{value: 0}).into()
+                                    },
+                                ]
+                            }.into()
+                        }.into()
+                    ],
+                    ret: return_address.clone().into()
+                }
+            }).into();
+            (
+                memory.clone().into(),
+                vec![memory.into()]
+            )
+        };
+        "match statement wildcard"
+    )]
     fn test_lower_expression(
         expression: TypedExpression,
         value_statements: (IntermediateValue, Vec<IntermediateStatement>),
@@ -1428,6 +1830,183 @@ mod tests {
         ExpressionEqualityChecker::assert_equal(&expected_fn, &efficient_fn.into())
     }
 
+    #[test]
+    fn test_lower_extra_built_in() {
+        let extra = TypeContext::from([(
+            Id::from("double"),
+            Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_INT))).into(),
+        )]);
+        let expression = TypedFunctionCall {
+            function: Box::new(
+                TypedAccess {
+                    variable: extra[&Id::from("double")].clone(),
+                    parameters: Vec::new(),
+                }
+                .into(),
+            ),
+            arguments: vec![Integer { value: 3 }.into()],
+        }
+        .into();
+        let mut lowerer = Lowerer::with_context(&extra);
+        lowerer.lower_expression(expression);
+        let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            register: _,
+            expression,
+        }) = &lowerer.statements[0];
+        ExpressionEqualityChecker::assert_equal(
+            expression,
+            &IntermediateFnCall {
+                fn_: BuiltInFn(
+                    Id::from("double"),
+                    IntermediateFnType(
+                        vec![AtomicTypeEnum::INT.into()],
+                        Box::new(AtomicTypeEnum::INT.into()),
+                    ),
+                )
+                .into(),
+                args: vec![IntermediateBuiltIn::Integer(Integer { value: 3 }).into()],
+            }
+            .into(),
+        );
+    }
+
+    #[test_case("&&", true; "and")]
+    #[test_case("||", false; "or")]
+    fn test_short_circuit(operator: &str, right_in_true_branch: bool) {
+        let built_in = |id: &str| {
+            DEFAULT_CONTEXT.with(|context| TypedAccess {
+                variable: context[&Id::from(id)].clone(),
+                parameters: Vec::new(),
+            })
+        };
+        // `x && (1 / 0 == 0)` or `x || (1 / 0 == 0)`
+        let x = TypedVariable::from(TYPE_BOOL);
+        let division = TypedFunctionCall {
+            function: Box::new(built_in("/").into()),
+            arguments: vec![Integer { value: 1 }.into(), Integer { value: 0 }.into()],
+        };
+        let right = TypedFunctionCall {
+            function: Box::new(built_in("==").into()),
+            arguments: vec![division.into(), Integer { value: 0 }.into()],
+        };
+        let expression = TypedFunctionCall {
+            function: Box::new(built_in(operator).into()),
+            arguments: vec![
+                TypedAccess {
+                    variable: x.clone(),
+                    parameters: Vec::new(),
+                }
+                .into(),
+                right.into(),
+            ],
+        }
+        .into();
+        let mut lowerer = Lowerer::new();
+        let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+        lowerer
+            .scope
+            .insert((x.variable, Vec::new()), arg.clone().into());
+        lowerer.lower_expression(expression);
+        // The division only happens inside the branch that evaluates the right operand.
+        let [IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            register: _,
+            expression:
+                IntermediateExpression::IntermediateIf(IntermediateIf {
+                    condition,
+                    branches: (true_block, false_block),
+                }),
+        })] = &lowerer.statements[..]
+        else {
+            panic!("Expected a single if statement.")
+        };
+        assert_eq!(condition, &IntermediateValue::from(arg));
+        let (right_block, constant_block) = if right_in_true_branch {
+            (true_block, false_block)
+        } else {
+            (false_block, true_block)
+        };
+        assert!(right_block.statements.iter().any(|statement| matches!(
+            statement,
+            IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                register: _,
+                expression: IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                    fn_: IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(
+                        BuiltInFn(id, _)
+                    )),
+                    args: _,
+                }),
+            }) if id == "/"
+        )));
+        assert_eq!(
+            constant_block,
+            &IntermediateBlock::from(IntermediateValue::from(Boolean {
+                value: !right_in_true_branch
+            }))
+        );
+    }
+
+    fn bool_array() -> IntermediateType {
+        IntermediateArrayType(Box::new(AtomicTypeEnum::BOOL.into())).into()
+    }
+
+    #[test_case("empty", IntermediateFnType(Vec::new(), Box::new(bool_array())); "empty")]
+    #[test_case(
+        "push",
+        IntermediateFnType(vec![bool_array(), AtomicTypeEnum::BOOL.into()], Box::new(bool_array()));
+        "push"
+    )]
+    #[test_case(
+        "get",
+        IntermediateFnType(vec![bool_array(), AtomicTypeEnum::INT.into()], Box::new(AtomicTypeEnum::BOOL.into()));
+        "get"
+    )]
+    #[test_case(
+        "len",
+        IntermediateFnType(vec![bool_array()], Box::new(AtomicTypeEnum::INT.into()));
+        "length"
+    )]
+    fn test_lower_array_built_in(name: &str, type_: IntermediateFnType) {
+        let expression = TypedAccess {
+            variable: DEFAULT_CONTEXT.with(|context| context[&Id::from(name)].clone()),
+            parameters: vec![TYPE_BOOL],
+        }
+        .into();
+        let mut lowerer = Lowerer::new();
+        let value = lowerer.lower_expression(expression);
+        assert!(lowerer.statements.is_empty());
+        assert_eq!(value, BuiltInFn(Id::from(name), type_).into());
+    }
+
+    #[test]
+    fn test_lower_parallel_built_in() {
+        let expression = TypedAccess {
+            variable: DEFAULT_CONTEXT.with(|context| context[&Id::from("pmap")].clone()),
+            parameters: vec![TYPE_INT, TYPE_BOOL],
+        }
+        .into();
+        let mut lowerer = Lowerer::new();
+        let value = lowerer.lower_expression(expression);
+        assert!(lowerer.statements.is_empty());
+        assert_eq!(
+            value,
+            BuiltInFn(
+                Id::from("pmap"),
+                IntermediateFnType(
+                    vec![
+                        IntermediateFnType(
+                            vec![AtomicTypeEnum::INT.into()],
+                            Box::new(AtomicTypeEnum::BOOL.into())
+                        )
+                        .into(),
+                        IntermediateArrayType(Box::new(AtomicTypeEnum::INT.into())).into()
+                    ],
+                    Box::new(bool_array())
+                )
+            )
+            .into()
+        );
+    }
+
     #[test]
     fn test_projection_equalities() {
         let p0 = {
@@ -2559,7 +3138,8 @@ mod tests {
                         )
                     },
                     return_type: Box::new(TYPE_INT)
-                }
+                },
+                docs: BTreeMap::new(),
             }
         },
         {
@@ -2647,7 +3227,8 @@ mod tests {
                         )
                     },
                     return_type: Box::new(TYPE_INT)
-                }
+                },
+                docs: BTreeMap::new(),
             }
         },
         {
@@ -2794,7 +3375,8 @@ mod tests {
                     },
                     parameters: Vec::new(),
                     return_type: Box::new(TYPE_INT)
-                }
+                },
+                docs: BTreeMap::new(),
             }
         },
         {
@@ -2931,7 +3513,8 @@ mod tests {
                             }.into()
                         )
                     }
-                }
+                },
+                docs: BTreeMap::new(),
             }
         },
         {
@@ -2990,4 +3573,75 @@ mod tests {
         ExpressionEqualityChecker::assert_equal(&lower_program.main.into(), &expected.main.into());
         assert_eq!(lower_program.types, expected.types)
     }
+
+    #[test]
+    fn test_lower_statement_incrementally() {
+        let x: TypedVariable = Type::from(TypeTuple(vec![TYPE_INT, TYPE_BOOL])).into();
+        let y: TypedVariable = Type::from(TypeTuple(vec![
+            Type::from(TypeTuple(vec![TYPE_INT, TYPE_BOOL])),
+            TYPE_INT,
+        ]))
+        .into();
+        let statements: Vec<TypedStatement> = vec![
+            TypedAssignment {
+                variable: x.clone(),
+                expression: TypedExpression::from(TypedTuple {
+                    expressions: vec![Integer { value: 3 }.into(), Boolean { value: false }.into()],
+                })
+                .into(),
+            }
+            .into(),
+            TypedAssignment {
+                variable: y.clone(),
+                expression: TypedExpression::from(TypedTuple {
+                    expressions: vec![
+                        TypedAccess {
+                            variable: x,
+                            parameters: Vec::new(),
+                        }
+                        .into(),
+                        Integer { value: 4 }.into(),
+                    ],
+                })
+                .into(),
+            }
+            .into(),
+        ];
+        let ret: TypedExpression = TypedAccess {
+            variable: y.clone(),
+            parameters: Vec::new(),
+        }
+        .into();
+
+        let mut lowerer = Lowerer::new();
+        let incremental_statements = statements
+            .clone()
+            .into_iter()
+            .flat_map(|statement| lowerer.lower_statement(statement))
+            .collect::<Vec<_>>();
+        assert_eq!(incremental_statements.len(), 2);
+        let value = lowerer.lower_expression(ret.clone());
+        let incremental = IntermediateLambda {
+            args: Vec::new(),
+            block: IntermediateBlock {
+                statements: incremental_statements,
+                ret: CopyPropagator::from_memory_map(lowerer.memory.clone())
+                    .propagate_copies_in_value(value),
+            },
+        };
+
+        let single_shot = Lowerer::lower(TypedProgram {
+            type_definitions: TypeDefinitions::new(),
+            main: TypedLambdaDef {
+                parameters: Vec::new(),
+                return_type: Box::new(y.type_.type_),
+                body: TypedBlock {
+                    statements,
+                    expression: Box::new(ret),
+                },
+            },
+            docs: BTreeMap::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(&incremental.into(), &single_shot.main.into());
+    }
 }
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use crate::{
+    fn_inst::{FnDefs, FnInst},
+    BuiltInFn, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn,
+    IntermediateExpression, IntermediateFnCall, IntermediateIf, IntermediateLambda,
+    IntermediateMatch, IntermediateMemory, IntermediateProgram, IntermediateStatement,
+    IntermediateValue,
+};
+use itertools::Either::{Left, Right};
+
+pub type PureFns = HashMap<IntermediateLambda, bool>;
+
+/// Names of built-in fns that have side-effects, which must be kept even when their results are unused.
+pub const IMPURE_BUILT_INS: &[&str] = &["error", "assert"];
+/// Names of built-in fns that never return, so any code after a call to them is unreachable.
+pub const DIVERGING_BUILT_INS: &[&str] = &["error"];
+
+pub struct PurityAnalyzer {
+    fn_defs: FnDefs,
+    pure_fns: PureFns,
+}
+
+impl PurityAnalyzer {
+    /// Prepare to analyze expressions from a program.
+    pub fn new(program: &IntermediateProgram) -> Self {
+        let mut fn_defs = FnDefs::new();
+        FnInst::collect_fn_defs_from_statements(&program.main.block.statements, &mut fn_defs);
+        PurityAnalyzer {
+            fn_defs,
+            pure_fns: PureFns::new(),
+        }
+    }
+    /// Determine whether evaluating an expression is free of side-effects.
+    pub fn is_pure(&mut self, expression: &IntermediateExpression) -> bool {
+        match expression {
+            IntermediateExpression::IntermediateValue(_)
+            | IntermediateExpression::IntermediateElementAccess(_)
+            | IntermediateExpression::IntermediateTupleExpression(_)
+            | IntermediateExpression::IntermediateCtorCall(_)
+            | IntermediateExpression::IntermediateLambda(_) => true,
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_, args: _ }) => {
+                self.is_pure_call(fn_)
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition: _,
+                branches,
+            }) => self.is_pure_block(&branches.0) && self.is_pure_block(&branches.1),
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => branches
+                .iter()
+                .all(|branch| self.is_pure_block(&branch.block)),
+        }
+    }
+    /// Determine whether calling a fn is free of side-effects.
+    fn is_pure_call(&mut self, fn_: &IntermediateValue) -> bool {
+        match fn_ {
+            IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(built_in)) => {
+                Self::is_pure_built_in(built_in)
+            }
+            IntermediateValue::IntermediateBuiltIn(_) => true,
+            IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register }) => {
+                match FnInst::get_root_fn(&self.fn_defs, register) {
+                    Some(Left(lambda)) => self.is_pure_fn(&lambda),
+                    Some(Right(built_in)) => Self::is_pure_built_in(&built_in),
+                    // Assume the worst about fns that cannot be traced.
+                    None => false,
+                }
+            }
+            IntermediateValue::IntermediateArg(_) => false,
+        }
+    }
+    fn is_pure_built_in(BuiltInFn(name, _): &BuiltInFn) -> bool {
+        !IMPURE_BUILT_INS.contains(&name.as_str())
+    }
+    fn is_pure_fn(&mut self, lambda: &IntermediateLambda) -> bool {
+        if let Some(is_pure) = self.pure_fns.get(lambda) {
+            return *is_pure;
+        }
+        // Recursion on its own does not introduce side-effects.
+        self.pure_fns.insert(lambda.clone(), true);
+        let is_pure = self.is_pure_block(&lambda.block);
+        self.pure_fns.insert(lambda.clone(), is_pure);
+        is_pure
+    }
+    fn is_pure_block(&mut self, block: &IntermediateBlock) -> bool {
+        block.statements.iter().all(|statement| match statement {
+            IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register: _,
+            }) => self.is_pure(expression),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        IntermediateArg, IntermediateFnType, IntermediateMatchBranch, IntermediateType,
+        IntermediateUnionType,
+    };
+
+    use super::*;
+
+    use test_case::test_case;
+    use type_checker::{AtomicTypeEnum, Id, Integer};
+
+    #[test_case(
+        {
+            (
+                Vec::new(),
+                IntermediateFnCall {
+                    fn_: IntermediateBuiltIn::from(BuiltInFn(
+                        Id::from("++"),
+                        IntermediateFnType(
+                            vec![AtomicTypeEnum::INT.into()],
+                            Box::new(AtomicTypeEnum::INT.into()),
+                        )
+                    )).into(),
+                    args: vec![Integer{value: 3}.into()]
+                }.into(),
+                true
+            )
+        };
+        "built-in fn call"
+    )]
+    #[test_case(
+        {
+            (
+                Vec::new(),
+                IntermediateFnCall {
+                    fn_: IntermediateArg::from(IntermediateType::from(IntermediateFnType(
+                        Vec::new(),
+                        Box::new(AtomicTypeEnum::INT.into()),
+                    ))).into(),
+                    args: Vec::new()
+                }.into(),
+                false
+            )
+        };
+        "unknown fn call"
+    )]
+    #[test_case(
+        {
+            let fn_ = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+                Vec::new(),
+                Box::new(AtomicTypeEnum::INT.into()),
+            )));
+            (
+                vec![
+                    IntermediateAssignment {
+                        register: fn_.register.clone(),
+                        expression: IntermediateLambda {
+                            args: Vec::new(),
+                            block: IntermediateBlock {
+                                statements: Vec::new(),
+                                ret: Integer{value: 5}.into()
+                            }
+                        }.into()
+                    }.into()
+                ],
+                IntermediateFnCall {
+                    fn_: fn_.clone().into(),
+                    args: Vec::new()
+                }.into(),
+                true
+            )
+        };
+        "pure lambda call"
+    )]
+    #[test_case(
+        {
+            let fn_ = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+                Vec::new(),
+                Box::new(AtomicTypeEnum::INT.into()),
+            )));
+            let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            (
+                vec![
+                    IntermediateAssignment {
+                        register: fn_.register.clone(),
+                        expression: IntermediateLambda {
+                            args: Vec::new(),
+                            block: IntermediateBlock {
+                                statements: vec![
+                                    IntermediateAssignment {
+                                        register: call.register.clone(),
+                                        expression: IntermediateFnCall {
+                                            fn_: fn_.clone().into(),
+                                            args: Vec::new()
+                                        }.into()
+                                    }.into()
+                                ],
+                                ret: call.clone().into()
+                            }
+                        }.into()
+                    }.into()
+                ],
+                IntermediateFnCall {
+                    fn_: fn_.clone().into(),
+                    args: Vec::new()
+                }.into(),
+                true
+            )
+        };
+        "recursive lambda call"
+    )]
+    #[test_case(
+        {
+            let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            (
+                Vec::new(),
+                IntermediateMatch {
+                    subject: IntermediateArg::from(
+                        IntermediateType::from(IntermediateUnionType(vec![None, None]))
+                    ).into(),
+                    branches: vec![
+                        IntermediateMatchBranch {
+                            target: None,
+                            block: IntermediateBlock {
+                                statements: Vec::new(),
+                                ret: Integer{value: 0}.into()
+                            }
+                        },
+                        IntermediateMatchBranch {
+                            target: None,
+                            block: IntermediateBlock {
+                                statements: vec![
+                                    IntermediateAssignment {
+                                        register: call.register.clone(),
+                                        expression: IntermediateFnCall {
+                                            fn_: IntermediateArg::from(
+                                                IntermediateType::from(IntermediateFnType(
+                                                    Vec::new(),
+                                                    Box::new(AtomicTypeEnum::INT.into()),
+                                                ))
+                                            ).into(),
+                                            args: Vec::new()
+                                        }.into()
+                                    }.into()
+                                ],
+                                ret: call.clone().into()
+                            }
+                        },
+                    ]
+                }.into(),
+                false
+            )
+        };
+        "impure match branch"
+    )]
+    fn test_purity(
+        statements_expression_purity: (Vec<IntermediateStatement>, IntermediateExpression, bool),
+    ) {
+        let (statements, expression, expected) = statements_expression_purity;
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements,
+                    ret: Integer { value: 0 }.into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        let mut analyzer = PurityAnalyzer::new(&program);
+        assert_eq!(analyzer.is_pure(&expression), expected);
+    }
+}
@@ -1,13 +1,29 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     fn_inst::{FnDefs, FnInst},
-    IntermediateAssignment, IntermediateExpression, IntermediateFnCall, IntermediateLambda,
-    IntermediateMemory, IntermediateProgram, IntermediateStatement, IntermediateValue,
+    IntermediateAssignment, IntermediateBlock, IntermediateExpression, IntermediateFnCall,
+    IntermediateLambda, IntermediateMemory, IntermediateProgram, IntermediateStatement,
+    IntermediateValue, Register,
 };
 use itertools::Either::{Left, Right};
 
-pub type RecursiveFns = HashMap<IntermediateLambda, bool>;
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecursiveFns {
+    /// Whether each function is recursive.
+    pub recursive: HashMap<IntermediateLambda, bool>,
+    /// Registers of functions that call themselves only in tail position.
+    pub tail_recursive: HashSet<Register>,
+}
+
+impl RecursiveFns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get(&self, lambda: &IntermediateLambda) -> Option<&bool> {
+        self.recursive.get(lambda)
+    }
+}
 
 pub struct RecursiveFnFinder {
     fn_defs: FnDefs,
@@ -22,15 +38,80 @@ impl RecursiveFnFinder {
         };
         FnInst::collect_fn_defs_from_statements(&lambda.block.statements, &mut finder.fn_defs);
         let mut recursive_fns = RecursiveFns::new();
-        for value in finder.fn_defs.values() {
+        for (register, value) in finder.fn_defs.iter() {
             if let FnInst::Lambda(lambda) = value {
-                finder.find(&lambda, &mut recursive_fns);
+                finder.find(&lambda, &mut recursive_fns.recursive);
+                if let Ok(calls) = finder.self_calls(register, &lambda.block, true) {
+                    if calls > 0 {
+                        recursive_fns.tail_recursive.insert(register.clone());
+                    }
+                }
             }
         }
         recursive_fns
     }
+    /// Follow references to find the register where a function is defined.
+    fn root_register<'a>(&'a self, mut register: &'a Register) -> &'a Register {
+        while let Some(FnInst::Ref(reference)) = self.fn_defs.get(register) {
+            register = reference;
+        }
+        register
+    }
+    /// Count the calls a function makes to itself, failing if any are not in tail position.
+    fn self_calls(
+        &self,
+        fn_: &Register,
+        block: &IntermediateBlock,
+        tail: bool,
+    ) -> Result<usize, ()> {
+        let mut calls = 0;
+        for statement in &block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            // The value assigned to the return register is returned from the function.
+            let tail = tail
+                && matches!(&block.ret, IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register: ret }) if ret == register);
+            calls += match expression {
+                IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                    fn_:
+                        IntermediateValue::IntermediateMemory(IntermediateMemory {
+                            type_: _,
+                            register: callee,
+                        }),
+                    args: _,
+                }) if self.root_register(callee) == fn_ => {
+                    if tail {
+                        1
+                    } else {
+                        return Err(());
+                    }
+                }
+                IntermediateExpression::IntermediateIf(if_) => {
+                    self.self_calls(fn_, &if_.branches.0, tail)?
+                        + self.self_calls(fn_, &if_.branches.1, tail)?
+                }
+                IntermediateExpression::IntermediateMatch(match_) => match_
+                    .branches
+                    .iter()
+                    .map(|branch| self.self_calls(fn_, &branch.block, tail))
+                    .sum::<Result<usize, ()>>()?,
+                // Calls from inside another function are never in tail position.
+                IntermediateExpression::IntermediateLambda(lambda) => {
+                    self.self_calls(fn_, &lambda.block, false)?
+                }
+                _ => 0,
+            };
+        }
+        Ok(calls)
+    }
     /// Find out whether a lambda is recursive.
-    fn find(&self, lambda: &IntermediateLambda, recursive_fns: &mut RecursiveFns) -> bool {
+    fn find(
+        &self,
+        lambda: &IntermediateLambda,
+        recursive_fns: &mut HashMap<IntermediateLambda, bool>,
+    ) -> bool {
         if recursive_fns.contains_key(lambda) {
             return recursive_fns[lambda];
         }
@@ -85,7 +166,7 @@ mod tests {
     use crate::{
         IntermediateArg, IntermediateAssignment, IntermediateBlock, IntermediateFnCall,
         IntermediateFnType, IntermediateIf, IntermediateLambda, IntermediateMemory,
-        IntermediateStatement, IntermediateType, Register,
+        IntermediateStatement, IntermediateTupleExpression, IntermediateType, Register,
     };
 
     use super::*;
@@ -628,6 +709,197 @@ mod tests {
             },
             types: Vec::new(),
         });
-        assert_eq!(recursive_fns, expected);
+        assert_eq!(recursive_fns.recursive, expected);
+    }
+
+    fn fn_memory() -> IntermediateMemory {
+        IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![AtomicTypeEnum::BOOL.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        )))
+    }
+
+    fn self_call(
+        fn_: &IntermediateMemory,
+        arg: &IntermediateArg,
+    ) -> (IntermediateMemory, IntermediateStatement) {
+        let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let statement = IntermediateAssignment {
+            register: call.register.clone(),
+            expression: IntermediateFnCall {
+                fn_: fn_.clone().into(),
+                args: vec![arg.clone().into()],
+            }
+            .into(),
+        }
+        .into();
+        (call, statement)
+    }
+
+    #[test_case(
+        {
+            let f = fn_memory();
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let (call, statement) = self_call(&f, &arg);
+            (
+                vec![IntermediateAssignment {
+                    register: f.register.clone(),
+                    expression: IntermediateLambda {
+                        args: vec![arg.clone()],
+                        block: IntermediateBlock {
+                            statements: vec![statement],
+                            ret: call.into(),
+                        },
+                    }
+                    .into(),
+                }
+                .into()],
+                HashSet::from([f.register.clone()]),
+            )
+        };
+        "direct tail call"
+    )]
+    #[test_case(
+        {
+            let f = fn_memory();
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let (call, statement) = self_call(&f, &arg);
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            (
+                vec![IntermediateAssignment {
+                    register: f.register.clone(),
+                    expression: IntermediateLambda {
+                        args: vec![arg.clone()],
+                        block: IntermediateBlock {
+                            statements: vec![IntermediateAssignment {
+                                register: result.register.clone(),
+                                expression: IntermediateIf {
+                                    condition: arg.clone().into(),
+                                    branches: (
+                                        (vec![statement], call.into()).into(),
+                                        IntermediateValue::from(Integer { value: 0 }).into(),
+                                    ),
+                                }
+                                .into(),
+                            }
+                            .into()],
+                            ret: result.into(),
+                        },
+                    }
+                    .into(),
+                }
+                .into()],
+                HashSet::from([f.register.clone()]),
+            )
+        };
+        "tail call in branch"
+    )]
+    #[test_case(
+        {
+            let f = fn_memory();
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let (call, statement) = self_call(&f, &arg);
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            (
+                vec![IntermediateAssignment {
+                    register: f.register.clone(),
+                    expression: IntermediateLambda {
+                        args: vec![arg.clone()],
+                        block: IntermediateBlock {
+                            statements: vec![
+                                statement,
+                                IntermediateAssignment {
+                                    register: result.register.clone(),
+                                    expression: IntermediateTupleExpression(vec![
+                                        call.into(),
+                                    ])
+                                    .into(),
+                                }
+                                .into(),
+                            ],
+                            ret: result.into(),
+                        },
+                    }
+                    .into(),
+                }
+                .into()],
+                HashSet::new(),
+            )
+        };
+        "call result used"
+    )]
+    #[test_case(
+        {
+            let f = fn_memory();
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let (call, statement) = self_call(&f, &arg);
+            let g = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+                Vec::new(),
+                Box::new(AtomicTypeEnum::INT.into()),
+            )));
+            (
+                vec![IntermediateAssignment {
+                    register: f.register.clone(),
+                    expression: IntermediateLambda {
+                        args: vec![arg.clone()],
+                        block: IntermediateBlock {
+                            statements: vec![IntermediateAssignment {
+                                register: g.register.clone(),
+                                expression: IntermediateLambda {
+                                    args: Vec::new(),
+                                    block: IntermediateBlock {
+                                        statements: vec![statement],
+                                        ret: call.into(),
+                                    },
+                                }
+                                .into(),
+                            }
+                            .into()],
+                            ret: Integer { value: 0 }.into(),
+                        },
+                    }
+                    .into(),
+                }
+                .into()],
+                HashSet::new(),
+            )
+        };
+        "call in nested fn"
+    )]
+    #[test_case(
+        {
+            let f = fn_memory();
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            (
+                vec![IntermediateAssignment {
+                    register: f.register.clone(),
+                    expression: IntermediateLambda {
+                        args: vec![arg.clone()],
+                        block: IntermediateBlock {
+                            statements: Vec::new(),
+                            ret: Integer { value: 0 }.into(),
+                        },
+                    }
+                    .into(),
+                }
+                .into()],
+                HashSet::new(),
+            )
+        };
+        "non-recursive fn"
+    )]
+    fn test_tail_recursive_fns(fns: (Vec<IntermediateStatement>, HashSet<Register>)) {
+        let (statements, expected) = fns;
+        let recursive_fns = RecursiveFnFinder::recursive_fns(&IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements,
+                    ret: Integer { value: 0 }.into(),
+                },
+            },
+            types: Vec::new(),
+        });
+        assert_eq!(recursive_fns.tail_recursive, expected);
     }
 }
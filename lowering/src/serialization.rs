@@ -0,0 +1,225 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::de::Error as DeError;
+use serde::ser::{Error as SerError, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{IntermediateLambda, IntermediateProgram, IntermediateTupleType, IntermediateType};
+
+type Reference = Rc<RefCell<IntermediateType>>;
+
+/// Table of referenced types that are assigned an id during serialization.
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<*const RefCell<IntermediateType>, usize>,
+    references: Vec<Reference>,
+}
+
+thread_local! {
+    static INTERNER: RefCell<Option<Interner>> = const { RefCell::new(None) };
+    static REFERENCES: RefCell<Option<Vec<Reference>>> = const { RefCell::new(None) };
+}
+
+fn reference_id(reference: &Reference) -> Result<usize, String> {
+    INTERNER.with(|interner| match interner.borrow_mut().as_mut() {
+        Some(interner) => {
+            let idx = interner.references.len();
+            Ok(*interner
+                .ids
+                .entry(Rc::as_ptr(reference))
+                .or_insert_with(|| {
+                    // Keep the type alive so that its address is not reused.
+                    interner.references.push(reference.clone());
+                    idx
+                }))
+        }
+        None => Err(String::from(
+            "Referenced types can only be serialized as part of a program.",
+        )),
+    })
+}
+
+fn lookup_reference(idx: usize) -> Result<Reference, String> {
+    REFERENCES.with(|references| match references.borrow().as_ref() {
+        Some(references) => references
+            .get(idx)
+            .cloned()
+            .ok_or_else(|| format!("Unknown type reference {idx}.")),
+        None => Err(String::from(
+            "Referenced types can only be deserialized as part of a program.",
+        )),
+    })
+}
+
+/// Serialize referenced types by their index in the program's table of references.
+pub mod reference {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        reference: &Reference,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(reference_id(reference).map_err(S::Error::custom)? as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Reference, D::Error> {
+        lookup_reference(usize::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// Serialize a program with each referenced type emitted once in a table.
+impl Serialize for IntermediateProgram {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let previous = INTERNER.with(|interner| interner.replace(Some(Interner::default())));
+        let tables = (|| {
+            let main = serde_json::to_value(&self.main).map_err(|e| e.to_string())?;
+            let types = self
+                .types
+                .iter()
+                .map(reference_id)
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut references = Vec::new();
+            // Serializing a referenced type can reference more types.
+            while let Some(reference) = INTERNER.with(|interner| {
+                interner
+                    .borrow()
+                    .as_ref()
+                    .and_then(|interner| interner.references.get(references.len()).cloned())
+            }) {
+                references
+                    .push(serde_json::to_value(&*reference.borrow()).map_err(|e| e.to_string())?);
+            }
+            Ok((main, types, references))
+        })();
+        INTERNER.with(|interner| interner.replace(previous));
+        let (main, types, references) = tables.map_err(|e: String| S::Error::custom(e))?;
+        let mut program = serializer.serialize_struct("IntermediateProgram", 3)?;
+        program.serialize_field("main", &main)?;
+        program.serialize_field("types", &types)?;
+        program.serialize_field("references", &references)?;
+        program.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for IntermediateProgram {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Tables {
+            main: serde_json::Value,
+            types: Vec<usize>,
+            references: Vec<serde_json::Value>,
+        }
+        let Tables {
+            main,
+            types,
+            references,
+        } = Tables::deserialize(deserializer)?;
+        // Create every reference before filling them in so that they can refer to each other.
+        let placeholders = references
+            .iter()
+            .map(|_| Rc::new(RefCell::new(IntermediateTupleType(Vec::new()).into())))
+            .collect::<Vec<Reference>>();
+        let previous = REFERENCES.with(|references| references.replace(Some(placeholders.clone())));
+        let program = (|| {
+            for (placeholder, reference) in placeholders.iter().zip(references) {
+                let type_ = serde_json::from_value::<IntermediateType>(reference)
+                    .map_err(|e| e.to_string())?;
+                *placeholder.borrow_mut() = type_;
+            }
+            let main =
+                serde_json::from_value::<IntermediateLambda>(main).map_err(|e| e.to_string())?;
+            let types = types
+                .into_iter()
+                .map(lookup_reference)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(IntermediateProgram { main, types })
+        })();
+        REFERENCES.with(|references| references.replace(previous));
+        program.map_err(|e: String| D::Error::custom(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        ExpressionEqualityChecker, IntermediateAssignment, IntermediateBlock, IntermediateCtorCall,
+        IntermediateMemory, IntermediateUnionType, Register,
+    };
+    use type_checker::AtomicTypeEnum;
+
+    #[test]
+    fn test_recursive_program_round_trip() {
+        // `type List = Cons (int, List) | Nil; main = () -> List { Nil }`
+        let reference = Rc::new(RefCell::new(IntermediateTupleType(Vec::new()).into()));
+        let union_type = IntermediateUnionType(vec![
+            Some(
+                IntermediateTupleType(vec![
+                    AtomicTypeEnum::INT.into(),
+                    IntermediateType::Reference(reference.clone()),
+                ])
+                .into(),
+            ),
+            None,
+        ]);
+        *reference.borrow_mut() = union_type.clone().into();
+        let nil = IntermediateMemory::from(IntermediateType::from(union_type.clone()));
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![IntermediateAssignment {
+                        register: nil.register.clone(),
+                        expression: IntermediateCtorCall {
+                            idx: 1,
+                            data: None,
+                            type_: union_type,
+                        }
+                        .into(),
+                    }
+                    .into()],
+                    ret: nil.clone().into(),
+                },
+            },
+            types: vec![reference],
+        };
+        let json = serde_json::to_string(&program).unwrap();
+        let deserialized = serde_json::from_str::<IntermediateProgram>(&json).unwrap();
+        ExpressionEqualityChecker::assert_equal(
+            &program.main.clone().into(),
+            &deserialized.main.clone().into(),
+        );
+        assert_eq!(program.types, deserialized.types);
+        let IntermediateType::IntermediateUnionType(IntermediateUnionType(variants)) =
+            &*deserialized.types[0].borrow()
+        else {
+            panic!("Reference is not a union type.")
+        };
+        let Some(IntermediateType::IntermediateTupleType(IntermediateTupleType(types))) =
+            &variants[0]
+        else {
+            panic!("Variant is not a tuple.")
+        };
+        assert!(
+            matches!(&types[1], IntermediateType::Reference(reference) if Rc::ptr_eq(reference, &deserialized.types[0]))
+        );
+    }
+
+    #[test]
+    fn test_deserialized_register_is_not_reused() {
+        let register = serde_json::from_str::<Register>("1000000000").unwrap();
+        assert!(Register::new() > register);
+    }
+
+    #[test]
+    fn test_reference_outside_program() {
+        let type_ = IntermediateType::Reference(Rc::new(RefCell::new(
+            IntermediateTupleType(Vec::new()).into(),
+        )));
+        assert!(serde_json::to_value(&type_).is_err());
+        assert!(serde_json::from_str::<IntermediateType>(r#"{"Reference":0}"#).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{
+    IntermediateArg, IntermediateBlock, IntermediateLambda, IntermediateProgram,
+    IntermediateStatement, IntermediateValue,
+};
+
+/// A program without types whose `main` takes `args`, runs `statements` and returns `ret`.
+pub fn program(
+    args: Vec<IntermediateArg>,
+    statements: Vec<IntermediateStatement>,
+    ret: IntermediateValue,
+) -> IntermediateProgram {
+    IntermediateProgram {
+        main: IntermediateLambda {
+            args,
+            block: IntermediateBlock { statements, ret },
+        },
+        types: Vec::new(),
+    }
+}
@@ -2,7 +2,10 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
-use crate::{IntermediateFnType, IntermediateTupleType, IntermediateType, IntermediateUnionType};
+use crate::{
+    IntermediateArrayType, IntermediateFnType, IntermediateTupleType, IntermediateType,
+    IntermediateUnionType,
+};
 
 /// Check whether two types are equivalent, keeping track of references equal references.
 pub struct TypeEqualityChecker {
@@ -37,6 +40,10 @@ impl TypeEqualityChecker {
                         _ => false,
                     })
             }
+            (
+                IntermediateType::IntermediateArrayType(IntermediateArrayType(t1)),
+                IntermediateType::IntermediateArrayType(IntermediateArrayType(t2)),
+            ) => self.equal_type(t1, t2),
             (IntermediateType::Reference(r1), IntermediateType::Reference(r2)) => {
                 let p1 = r1.as_ptr();
                 let p2 = r2.as_ptr();
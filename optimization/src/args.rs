@@ -1,25 +1,86 @@
 use clap::{arg, Args};
 
-#[derive(Args)]
+/// Default number of nested inlines along one call chain, shared by the flag and the presets below `-O3`.
+const DEFAULT_MAX_INLINE_DEPTH: usize = 3;
+
+#[derive(Args, Debug, PartialEq)]
 pub struct InliningArgs {
     #[arg(long, default_value_t = 1000)]
     pub inlining_depth: usize,
+    /// Only inline a fn when the caller's estimated size stays below this budget.
+    #[arg(long)]
+    pub inline_budget: Option<usize>,
+    /// Stop inlining calls that were exposed by this many nested inlines.
+    #[arg(long, default_value_t = DEFAULT_MAX_INLINE_DEPTH)]
+    pub max_inline_depth: usize,
 }
 
-#[derive(Args)]
+#[derive(Args, Debug, PartialEq)]
 pub struct DeadCodeAnalysisArgs {
     #[arg(long)]
     pub no_dead_code_analysis: bool,
 }
 
-#[derive(Args)]
+#[derive(Args, Debug, PartialEq)]
 pub struct EquivalentExpressionEliminationArgs {
     #[arg(long)]
     pub no_equivalent_expression_elimination: bool,
 }
 
-#[derive(Args)]
+#[derive(Args, Debug, PartialEq)]
+pub struct CommonSubexpressionEliminationArgs {
+    #[arg(long)]
+    pub no_common_subexpression_elimination: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct NewtypeEliminationArgs {
+    #[arg(long)]
+    pub no_newtype_elimination: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct BranchEliminationArgs {
+    #[arg(long)]
+    pub no_branch_elimination: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct ConstantFoldingArgs {
+    #[arg(long)]
+    pub no_constant_folding: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct ComparisonFoldingArgs {
+    #[arg(long)]
+    pub no_comparison_folding: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct StrengthReductionArgs {
+    #[arg(long)]
+    pub no_strength_reduction: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct FmaFusionArgs {
+    #[arg(long)]
+    pub no_fma_fusion: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
+pub struct UnreachableCodeEliminationArgs {
+    #[arg(long)]
+    pub no_unreachable_code_elimination: bool,
+}
+
+#[derive(Args, Debug, PartialEq)]
 pub struct OptimizationArgs {
+    /// Rerun the optimizations until the program stops changing or this many iterations have run.
+    #[arg(long, default_value_t = 4)]
+    pub max_iterations: usize,
+
     #[command(flatten)]
     pub inlining_args: InliningArgs,
 
@@ -28,4 +89,83 @@ pub struct OptimizationArgs {
 
     #[command(flatten)]
     pub equivalent_elimination_args: EquivalentExpressionEliminationArgs,
+
+    #[command(flatten)]
+    pub common_subexpression_elimination_args: CommonSubexpressionEliminationArgs,
+
+    #[command(flatten)]
+    pub newtype_elimination_args: NewtypeEliminationArgs,
+
+    #[command(flatten)]
+    pub branch_elimination_args: BranchEliminationArgs,
+
+    #[command(flatten)]
+    pub constant_folding_args: ConstantFoldingArgs,
+
+    #[command(flatten)]
+    pub comparison_folding_args: ComparisonFoldingArgs,
+
+    #[command(flatten)]
+    pub strength_reduction_args: StrengthReductionArgs,
+
+    #[command(flatten)]
+    pub fma_fusion_args: FmaFusionArgs,
+
+    #[command(flatten)]
+    pub unreachable_code_elimination_args: UnreachableCodeEliminationArgs,
+}
+
+impl OptimizationArgs {
+    /// Preset for the optimization level `n`, as selected by `-O0` to `-O3`.
+    /// `0` disables all passes, `1` runs a single iteration of the cheap passes without inlining,
+    /// `2` matches the defaults and `3` iterates until a fixpoint (at most 16 times) with aggressive inlining up to 8 nested inlines.
+    /// Levels above `3` are treated as `3`.
+    pub fn level(n: u8) -> Self {
+        let (max_iterations, inlining_depth, max_inline_depth) = match n {
+            0 => (0, 0, DEFAULT_MAX_INLINE_DEPTH),
+            1 => (1, 0, DEFAULT_MAX_INLINE_DEPTH),
+            2 => (4, 1000, DEFAULT_MAX_INLINE_DEPTH),
+            _ => (16, 10000, 8),
+        };
+        let disabled = n == 0;
+        let expensive_disabled = n <= 1;
+        OptimizationArgs {
+            max_iterations,
+            inlining_args: InliningArgs {
+                inlining_depth,
+                inline_budget: None,
+                max_inline_depth,
+            },
+            dead_code_analysis_args: DeadCodeAnalysisArgs {
+                no_dead_code_analysis: disabled,
+            },
+            equivalent_elimination_args: EquivalentExpressionEliminationArgs {
+                no_equivalent_expression_elimination: expensive_disabled,
+            },
+            common_subexpression_elimination_args: CommonSubexpressionEliminationArgs {
+                no_common_subexpression_elimination: expensive_disabled,
+            },
+            newtype_elimination_args: NewtypeEliminationArgs {
+                no_newtype_elimination: disabled,
+            },
+            branch_elimination_args: BranchEliminationArgs {
+                no_branch_elimination: disabled,
+            },
+            constant_folding_args: ConstantFoldingArgs {
+                no_constant_folding: disabled,
+            },
+            comparison_folding_args: ComparisonFoldingArgs {
+                no_comparison_folding: disabled,
+            },
+            strength_reduction_args: StrengthReductionArgs {
+                no_strength_reduction: disabled,
+            },
+            fma_fusion_args: FmaFusionArgs {
+                no_fma_fusion: expensive_disabled,
+            },
+            unreachable_code_elimination_args: UnreachableCodeEliminationArgs {
+                no_unreachable_code_elimination: disabled,
+            },
+        }
+    }
 }
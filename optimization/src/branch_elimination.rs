@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use lowering::{
+    Boolean, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn, IntermediateCtorCall,
+    IntermediateExpression, IntermediateIf, IntermediateLambda, IntermediateMatch,
+    IntermediateMatchBranch, IntermediateMemory, IntermediateProgram, IntermediateStatement,
+    IntermediateValue, Register,
+};
+
+use crate::refresher::Refresher;
+
+type Constructors = HashMap<Register, (usize, Option<IntermediateValue>)>;
+
+/// Replace branches whose outcome is known at compile time with the taken branch.
+pub struct BranchEliminator {
+    constructors: Constructors,
+}
+
+impl BranchEliminator {
+    fn new() -> Self {
+        BranchEliminator {
+            constructors: Constructors::new(),
+        }
+    }
+    /// Record the variant and data of all constructor calls.
+    fn collect_constructors(&mut self, statements: &Vec<IntermediateStatement>) {
+        for statement in statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            match expression {
+                IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
+                    idx,
+                    data,
+                    type_: _,
+                }) => {
+                    self.constructors
+                        .insert(register.clone(), (*idx, data.clone()));
+                }
+                IntermediateExpression::IntermediateLambda(lambda) => {
+                    self.collect_constructors(&lambda.block.statements);
+                }
+                IntermediateExpression::IntermediateIf(if_) => {
+                    self.collect_constructors(&if_.branches.0.statements);
+                    self.collect_constructors(&if_.branches.1.statements);
+                }
+                IntermediateExpression::IntermediateMatch(match_) => {
+                    for branch in &match_.branches {
+                        self.collect_constructors(&branch.block.statements);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    fn eliminate_from_block(&self, block: IntermediateBlock) -> IntermediateBlock {
+        let IntermediateBlock { statements, ret } = block;
+        IntermediateBlock {
+            statements: statements
+                .into_iter()
+                .flat_map(|statement| self.eliminate_from_statement(statement))
+                .collect(),
+            ret,
+        }
+    }
+    /// Splice the statements from a taken branch into the enclosing block.
+    fn splice(
+        &self,
+        args: Vec<IntermediateAssignment>,
+        block: IntermediateBlock,
+        register: Register,
+    ) -> Vec<IntermediateStatement> {
+        let IntermediateBlock { statements, ret } = self.eliminate_from_block(block);
+        args.into_iter()
+            .map(IntermediateStatement::from)
+            .chain(statements)
+            .chain([IntermediateAssignment {
+                register,
+                expression: ret.into(),
+            }
+            .into()])
+            .collect_vec()
+    }
+    fn eliminate_from_statement(
+        &self,
+        statement: IntermediateStatement,
+    ) -> Vec<IntermediateStatement> {
+        let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            expression,
+            register,
+        }) = statement;
+        let expression = match expression {
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition:
+                    IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::Boolean(Boolean {
+                        value,
+                    })),
+                branches,
+            }) => {
+                let block = if value { branches.0 } else { branches.1 };
+                return self.splice(Vec::new(), block, register);
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject:
+                    IntermediateValue::IntermediateMemory(IntermediateMemory {
+                        type_: _,
+                        register: subject,
+                    }),
+                branches,
+            }) if self.constructors.contains_key(&subject) => {
+                let (idx, data) = self.constructors[&subject].clone();
+                let IntermediateMatchBranch { target, block } =
+                    branches.into_iter().nth(idx).unwrap();
+                // Treat the branch as a fn of its target so that the target can be assigned to.
+                let mut lambda = IntermediateLambda {
+                    args: target.into_iter().collect(),
+                    block,
+                };
+                Refresher::refresh_for_inlining(&mut lambda);
+                let IntermediateLambda { args, block } = lambda;
+                let args = args
+                    .into_iter()
+                    .zip(data)
+                    .map(|(arg, value)| IntermediateAssignment {
+                        register: arg.register,
+                        expression: value.into(),
+                    })
+                    .collect_vec();
+                return self.splice(args, block, register);
+            }
+            IntermediateExpression::IntermediateLambda(IntermediateLambda { args, block }) => {
+                IntermediateLambda {
+                    args,
+                    block: self.eliminate_from_block(block),
+                }
+                .into()
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => IntermediateIf {
+                condition,
+                branches: (
+                    self.eliminate_from_block(branches.0),
+                    self.eliminate_from_block(branches.1),
+                ),
+            }
+            .into(),
+            IntermediateExpression::IntermediateMatch(IntermediateMatch { subject, branches }) => {
+                IntermediateMatch {
+                    subject,
+                    branches: branches
+                        .into_iter()
+                        .map(
+                            |IntermediateMatchBranch { target, block }| IntermediateMatchBranch {
+                                target,
+                                block: self.eliminate_from_block(block),
+                            },
+                        )
+                        .collect(),
+                }
+                .into()
+            }
+            expression => expression,
+        };
+        vec![IntermediateAssignment {
+            expression,
+            register,
+        }
+        .into()]
+    }
+    /// Replace `if`s with constant conditions and matches on known constructors with the taken branch.
+    pub fn eliminate_branches(program: IntermediateProgram) -> IntermediateProgram {
+        let IntermediateProgram { main, types } = program;
+        let mut eliminator = BranchEliminator::new();
+        eliminator.collect_constructors(&main.block.statements);
+        let IntermediateLambda { args, block } = main;
+        IntermediateProgram {
+            main: IntermediateLambda {
+                args,
+                block: eliminator.eliminate_from_block(block),
+            },
+            types,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lowering::{
+        AtomicTypeEnum, BuiltInFn, ExpressionEqualityChecker, Id, Integer, IntermediateArg,
+        IntermediateFnCall, IntermediateFnType, IntermediateType, IntermediateUnionType,
+    };
+    use test_case::test_case;
+
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let if_ = |condition: bool| IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: IntermediateIf {
+                                condition: Boolean { value: condition }.into(),
+                                branches: (
+                                    IntermediateValue::from(Integer { value: 1 }).into(),
+                                    IntermediateValue::from(Integer { value: 2 }).into(),
+                                )
+                            }.into()
+                        }.into()
+                    ],
+                    ret: x.clone().into()
+                }
+            };
+            (
+                if_(true),
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: x.register.clone(),
+                                expression: IntermediateValue::from(Integer { value: 1 }).into()
+                            }.into()
+                        ],
+                        ret: x.clone().into()
+                    }
+                }
+            )
+        };
+        "constant true condition"
+    )]
+    #[test_case(
+        {
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let negation = IntermediateAssignment {
+                register: y.register.clone(),
+                expression: IntermediateFnCall {
+                    fn_: BuiltInFn(
+                        Id::from("-"),
+                        IntermediateFnType(
+                            vec![AtomicTypeEnum::INT.into()],
+                            Box::new(AtomicTypeEnum::INT.into())
+                        )
+                    ).into(),
+                    args: vec![arg.clone().into()]
+                }.into()
+            };
+            (
+                IntermediateLambda {
+                    args: vec![arg.clone()],
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: x.register.clone(),
+                                expression: IntermediateIf {
+                                    condition: Boolean { value: false }.into(),
+                                    branches: (
+                                        IntermediateValue::from(arg.clone()).into(),
+                                        (vec![negation.clone().into()], y.clone().into()).into(),
+                                    )
+                                }.into()
+                            }.into()
+                        ],
+                        ret: x.clone().into()
+                    }
+                },
+                IntermediateLambda {
+                    args: vec![arg.clone()],
+                    block: IntermediateBlock {
+                        statements: vec![
+                            negation.into(),
+                            IntermediateAssignment {
+                                register: x.register.clone(),
+                                expression: IntermediateValue::from(y.clone()).into()
+                            }.into()
+                        ],
+                        ret: x.clone().into()
+                    }
+                }
+            )
+        };
+        "constant false condition"
+    )]
+    #[test_case(
+        {
+            let either = IntermediateUnionType(vec![
+                Some(AtomicTypeEnum::INT.into()),
+                Some(AtomicTypeEnum::BOOL.into()),
+            ]);
+            let value = IntermediateMemory::from(IntermediateType::from(either.clone()));
+            let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let b = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let ctor = IntermediateAssignment {
+                register: value.register.clone(),
+                expression: IntermediateCtorCall {
+                    idx: 1,
+                    data: Some(Boolean { value: true }.into()),
+                    type_: either.clone(),
+                }.into()
+            };
+            (
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            ctor.clone().into(),
+                            IntermediateAssignment {
+                                register: result.register.clone(),
+                                expression: IntermediateMatch {
+                                    subject: value.clone().into(),
+                                    branches: vec![
+                                        IntermediateMatchBranch {
+                                            target: Some(x.clone()),
+                                            block: IntermediateValue::from(Boolean { value: false }).into()
+                                        },
+                                        IntermediateMatchBranch {
+                                            target: Some(y.clone()),
+                                            block: IntermediateValue::from(y.clone()).into()
+                                        },
+                                    ]
+                                }.into()
+                            }.into(),
+                        ],
+                        ret: result.clone().into()
+                    }
+                },
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            ctor.into(),
+                            IntermediateAssignment {
+                                register: b.register.clone(),
+                                expression: IntermediateValue::from(Boolean { value: true }).into()
+                            }.into(),
+                            IntermediateAssignment {
+                                register: result.register.clone(),
+                                expression: IntermediateValue::from(b.clone()).into()
+                            }.into(),
+                        ],
+                        ret: result.clone().into()
+                    }
+                }
+            )
+        };
+        "known constructor"
+    )]
+    #[test_case(
+        {
+            let either = IntermediateUnionType(vec![None, None]);
+            let arg = IntermediateArg::from(IntermediateType::from(either.clone()));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: vec![arg.clone()],
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: result.register.clone(),
+                            expression: IntermediateMatch {
+                                subject: arg.clone().into(),
+                                branches: vec![
+                                    IntermediateMatchBranch {
+                                        target: None,
+                                        block: IntermediateValue::from(Integer { value: 0 }).into()
+                                    },
+                                    IntermediateMatchBranch {
+                                        target: None,
+                                        block: IntermediateValue::from(Integer { value: 1 }).into()
+                                    },
+                                ]
+                            }.into()
+                        }.into(),
+                    ],
+                    ret: result.clone().into()
+                }
+            };
+            (main.clone(), main)
+        };
+        "unknown subject"
+    )]
+    #[test_case(
+        {
+            let option = IntermediateUnionType(vec![None, Some(AtomicTypeEnum::INT.into())]);
+            let value = IntermediateMemory::from(IntermediateType::from(option.clone()));
+            let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let ctor = IntermediateAssignment {
+                register: value.register.clone(),
+                expression: IntermediateCtorCall {
+                    idx: 1,
+                    data: Some(Integer { value: 5 }.into()),
+                    type_: option.clone(),
+                }.into()
+            };
+            (
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            ctor.clone().into(),
+                            IntermediateAssignment {
+                                register: result.register.clone(),
+                                expression: IntermediateMatch {
+                                    subject: value.clone().into(),
+                                    branches: vec![
+                                        IntermediateMatchBranch {
+                                            target: None,
+                                            block: IntermediateValue::from(Integer { value: 0 }).into()
+                                        },
+                                        IntermediateMatchBranch {
+                                            target: Some(x.clone()),
+                                            block: IntermediateValue::from(x.clone()).into()
+                                        },
+                                    ]
+                                }.into()
+                            }.into(),
+                        ],
+                        ret: result.clone().into()
+                    }
+                },
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            ctor.into(),
+                            IntermediateAssignment {
+                                register: y.register.clone(),
+                                expression: IntermediateValue::from(Integer { value: 5 }).into()
+                            }.into(),
+                            IntermediateAssignment {
+                                register: result.register.clone(),
+                                expression: IntermediateValue::from(y.clone()).into()
+                            }.into(),
+                        ],
+                        ret: result.clone().into()
+                    }
+                }
+            )
+        };
+        "literal some"
+    )]
+    #[test_case(
+        {
+            let option = IntermediateUnionType(vec![None, Some(AtomicTypeEnum::INT.into())]);
+            let f = IntermediateArg::from(IntermediateType::from(IntermediateFnType(
+                Vec::new(),
+                Box::new(option.clone().into())
+            )));
+            let value = IntermediateMemory::from(IntermediateType::from(option.clone()));
+            let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: vec![f.clone()],
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: value.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: f.clone().into(),
+                                args: Vec::new(),
+                            }.into()
+                        }.into(),
+                        IntermediateAssignment {
+                            register: result.register.clone(),
+                            expression: IntermediateMatch {
+                                subject: value.clone().into(),
+                                branches: vec![
+                                    IntermediateMatchBranch {
+                                        target: None,
+                                        block: IntermediateValue::from(Integer { value: 0 }).into()
+                                    },
+                                    IntermediateMatchBranch {
+                                        target: Some(x.clone()),
+                                        block: IntermediateValue::from(x.clone()).into()
+                                    },
+                                ]
+                            }.into()
+                        }.into(),
+                    ],
+                    ret: result.clone().into()
+                }
+            };
+            (main.clone(), main)
+        };
+        "fn call subject"
+    )]
+    #[test_case(
+        {
+            let f = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+                Vec::new(),
+                Box::new(AtomicTypeEnum::INT.into())
+            )));
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let lambda = |statement: IntermediateStatement| IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: f.register.clone(),
+                            expression: IntermediateLambda {
+                                args: Vec::new(),
+                                block: IntermediateBlock {
+                                    statements: vec![statement],
+                                    ret: x.clone().into()
+                                }
+                            }.into()
+                        }.into()
+                    ],
+                    ret: f.clone().into()
+                }
+            };
+            (
+                lambda(IntermediateAssignment {
+                    register: x.register.clone(),
+                    expression: IntermediateIf {
+                        condition: Boolean { value: false }.into(),
+                        branches: (
+                            IntermediateValue::from(Integer { value: 1 }).into(),
+                            IntermediateValue::from(Integer { value: 2 }).into(),
+                        )
+                    }.into()
+                }.into()),
+                lambda(IntermediateAssignment {
+                    register: x.register.clone(),
+                    expression: IntermediateValue::from(Integer { value: 2 }).into()
+                }.into()),
+            )
+        };
+        "condition inside lambda"
+    )]
+    fn test_eliminate_branches(main_expected: (IntermediateLambda, IntermediateLambda)) {
+        let (main, expected_main) = main_expected;
+        let program = BranchEliminator::eliminate_branches(IntermediateProgram {
+            main,
+            types: Vec::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(&program.main.into(), &expected_main.into())
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+
+use lowering::{
+    Boolean, BuiltInFn, Char, CopyPropagator, Id, Integer, IntermediateArg, IntermediateAssignment,
+    IntermediateBlock, IntermediateBuiltIn, IntermediateCtorCall, IntermediateElementAccess,
+    IntermediateExpression, IntermediateFnCall, IntermediateIf, IntermediateMatch,
+    IntermediateMatchBranch, IntermediateMemory, IntermediateProgram, IntermediateStatement,
+    IntermediateTupleExpression, IntermediateValue, PurityAnalyzer, Register, Str,
+};
+
+/// A value without its type, which is determined by its register or literal.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum CanonicalValue {
+    Register(Register),
+    Integer(Integer),
+    Boolean(Boolean),
+    Str(Str),
+    Char(Char),
+    BuiltInFn(Id),
+}
+
+impl From<&IntermediateValue> for CanonicalValue {
+    fn from(value: &IntermediateValue) -> Self {
+        match value {
+            IntermediateValue::IntermediateBuiltIn(built_in) => match built_in {
+                IntermediateBuiltIn::Integer(integer) => CanonicalValue::Integer(integer.clone()),
+                IntermediateBuiltIn::Boolean(boolean) => CanonicalValue::Boolean(boolean.clone()),
+                IntermediateBuiltIn::Str(str) => CanonicalValue::Str(str.clone()),
+                IntermediateBuiltIn::Char(char) => CanonicalValue::Char(*char),
+                IntermediateBuiltIn::BuiltInFn(BuiltInFn(id, _)) => {
+                    CanonicalValue::BuiltInFn(id.clone())
+                }
+            },
+            IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register })
+            | IntermediateValue::IntermediateArg(IntermediateArg { type_: _, register }) => {
+                CanonicalValue::Register(register.clone())
+            }
+        }
+    }
+}
+
+fn canonical_values(values: &[IntermediateValue]) -> Vec<CanonicalValue> {
+    values.iter().map(CanonicalValue::from).collect()
+}
+
+/// A shareable expression without types, so that it can be used as a key.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum CanonicalExpression {
+    ElementAccess(CanonicalValue, usize),
+    Tuple(Vec<CanonicalValue>),
+    CtorCall(usize, Option<CanonicalValue>),
+    FnCall(CanonicalValue, Vec<CanonicalValue>),
+}
+
+/// Expressions keyed by their canonical form, alongside the register holding their result.
+type AvailableExpressions = HashMap<CanonicalExpression, (IntermediateExpression, Register)>;
+
+/// Replace repeated expressions within a block with the register that first computed them.
+pub struct CommonSubexpressionEliminator {
+    analyzer: PurityAnalyzer,
+    substitution: HashMap<Register, Register>,
+}
+
+impl CommonSubexpressionEliminator {
+    fn new(program: &IntermediateProgram) -> Self {
+        CommonSubexpressionEliminator {
+            analyzer: PurityAnalyzer::new(program),
+            substitution: HashMap::new(),
+        }
+    }
+    /// Find the canonical form of an expression that can share its result with an identical expression.
+    fn canonical_form(
+        &mut self,
+        expression: &IntermediateExpression,
+    ) -> Option<CanonicalExpression> {
+        match expression {
+            IntermediateExpression::IntermediateElementAccess(IntermediateElementAccess {
+                value,
+                idx,
+            }) => Some(CanonicalExpression::ElementAccess(value.into(), *idx)),
+            IntermediateExpression::IntermediateTupleExpression(IntermediateTupleExpression(
+                values,
+            )) => Some(CanonicalExpression::Tuple(canonical_values(values))),
+            IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
+                idx,
+                data,
+                type_: _,
+            }) => Some(CanonicalExpression::CtorCall(
+                *idx,
+                data.as_ref().map(CanonicalValue::from),
+            )),
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_, args }) => self
+                .analyzer
+                .is_pure(expression)
+                .then(|| CanonicalExpression::FnCall(fn_.into(), canonical_values(args))),
+            // Values are handled by copy propagation and compound expressions are never identical.
+            IntermediateExpression::IntermediateValue(_)
+            | IntermediateExpression::IntermediateLambda(_)
+            | IntermediateExpression::IntermediateIf(_)
+            | IntermediateExpression::IntermediateMatch(_) => None,
+        }
+    }
+    /// Eliminate expressions from a block, using those available from enclosing blocks.
+    fn eliminate_from_block(
+        &mut self,
+        block: &mut IntermediateBlock,
+        mut available: AvailableExpressions,
+    ) {
+        for statement in &mut block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            expression.substitute(&self.substitution);
+            match expression {
+                // Expressions in a lambda body are not reused from outside to avoid extending its environment.
+                IntermediateExpression::IntermediateLambda(lambda) => {
+                    self.eliminate_from_block(&mut lambda.block, AvailableExpressions::new());
+                }
+                // Expressions from one branch are never available in another.
+                IntermediateExpression::IntermediateIf(IntermediateIf {
+                    condition: _,
+                    branches,
+                }) => {
+                    self.eliminate_from_block(&mut branches.0, available.clone());
+                    self.eliminate_from_block(&mut branches.1, available.clone());
+                }
+                IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                    subject: _,
+                    branches,
+                }) => {
+                    for IntermediateMatchBranch { target: _, block } in branches {
+                        self.eliminate_from_block(block, available.clone());
+                    }
+                }
+                _ => {}
+            }
+            let Some(key) = self.canonical_form(expression) else {
+                continue;
+            };
+            match available.get(&key) {
+                // Constructors with the same data may still build different union types.
+                Some((shared, existing)) if shared == expression => {
+                    self.substitution.insert(register.clone(), existing.clone());
+                    *expression = IntermediateValue::from(IntermediateMemory {
+                        type_: expression.type_(),
+                        register: existing.clone(),
+                    })
+                    .into();
+                }
+                Some(_) => {}
+                None => {
+                    available.insert(key, (expression.clone(), register.clone()));
+                }
+            }
+        }
+        block.ret = block.ret.substitute(&self.substitution);
+    }
+    /// Reuse the results of identical pure expressions in the same block or an enclosing block.
+    pub fn eliminate_common_subexpressions(program: IntermediateProgram) -> IntermediateProgram {
+        let mut eliminator = CommonSubexpressionEliminator::new(&program);
+        let IntermediateProgram { mut main, types } = program;
+        eliminator.eliminate_from_block(&mut main.block, AvailableExpressions::new());
+        let copy_propagator = CopyPropagator::from_statements(&main.block.statements);
+        let IntermediateExpression::IntermediateLambda(main) =
+            copy_propagator.propagate_copies_in_expression(main.into())
+        else {
+            panic!("Main function changed form.")
+        };
+        IntermediateProgram { main, types }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lowering::{
+        AtomicTypeEnum, ExpressionEqualityChecker, IntermediateFnType, IntermediateLambda,
+        IntermediateTupleType, IntermediateType, IntermediateUnionType,
+    };
+    use test_case::test_case;
+
+    fn pair() -> IntermediateArg {
+        IntermediateArg::from(IntermediateType::from(IntermediateTupleType(vec![
+            AtomicTypeEnum::INT.into(),
+            AtomicTypeEnum::INT.into(),
+        ])))
+    }
+
+    fn first(arg: &IntermediateArg) -> IntermediateExpression {
+        IntermediateElementAccess {
+            value: arg.clone().into(),
+            idx: 0,
+        }
+        .into()
+    }
+
+    fn plus(x: &IntermediateMemory, y: &IntermediateMemory) -> IntermediateExpression {
+        IntermediateFnCall {
+            fn_: BuiltInFn(
+                Id::from("+"),
+                IntermediateFnType(
+                    vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                    Box::new(AtomicTypeEnum::INT.into()),
+                ),
+            )
+            .into(),
+            args: vec![x.clone().into(), y.clone().into()],
+        }
+        .into()
+    }
+
+    #[test_case(
+        {
+            let arg = pair();
+            let a = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let b = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let c = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            (
+                IntermediateLambda {
+                    args: vec![arg.clone()],
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: a.register.clone(),
+                                expression: first(&arg)
+                            }.into(),
+                            IntermediateAssignment {
+                                register: b.register.clone(),
+                                expression: first(&arg)
+                            }.into(),
+                            IntermediateAssignment {
+                                register: c.register.clone(),
+                                expression: plus(&a, &b)
+                            }.into(),
+                        ],
+                        ret: c.clone().into()
+                    }
+                },
+                IntermediateLambda {
+                    args: vec![arg.clone()],
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: a.register.clone(),
+                                expression: first(&arg)
+                            }.into(),
+                            IntermediateAssignment {
+                                register: c.register.clone(),
+                                expression: plus(&a, &a)
+                            }.into(),
+                        ],
+                        ret: c.clone().into()
+                    }
+                }
+            )
+        };
+        "repeated element access"
+    )]
+    #[test_case(
+        {
+            let arg = pair();
+            let a = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let b = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let c = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: vec![arg.clone()],
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: c.register.clone(),
+                            expression: IntermediateIf {
+                                condition: Boolean { value: true }.into(),
+                                branches: (
+                                    (
+                                        vec![IntermediateAssignment {
+                                            register: a.register.clone(),
+                                            expression: first(&arg)
+                                        }.into()],
+                                        a.clone().into()
+                                    ).into(),
+                                    (
+                                        vec![IntermediateAssignment {
+                                            register: b.register.clone(),
+                                            expression: first(&arg)
+                                        }.into()],
+                                        b.clone().into()
+                                    ).into(),
+                                )
+                            }.into()
+                        }.into(),
+                    ],
+                    ret: c.clone().into()
+                }
+            };
+            (main.clone(), main)
+        };
+        "separate branches"
+    )]
+    #[test_case(
+        {
+            let arg = pair();
+            let a = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let b = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let c = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = |statements: Vec<IntermediateStatement>, ret: IntermediateMemory| {
+                IntermediateLambda {
+                    args: vec![arg.clone()],
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: a.register.clone(),
+                                expression: first(&arg)
+                            }.into(),
+                            IntermediateAssignment {
+                                register: c.register.clone(),
+                                expression: IntermediateIf {
+                                    condition: Boolean { value: true }.into(),
+                                    branches: (
+                                        (statements, ret.into()).into(),
+                                        IntermediateValue::from(a.clone()).into(),
+                                    )
+                                }.into()
+                            }.into(),
+                        ],
+                        ret: c.clone().into()
+                    }
+                }
+            };
+            (
+                main(
+                    vec![IntermediateAssignment {
+                        register: b.register.clone(),
+                        expression: first(&arg)
+                    }.into()],
+                    b.clone()
+                ),
+                main(Vec::new(), a.clone())
+            )
+        };
+        "enclosing block"
+    )]
+    #[test_case(
+        {
+            let f = IntermediateArg::from(IntermediateType::from(IntermediateFnType(
+                Vec::new(),
+                Box::new(AtomicTypeEnum::INT.into())
+            )));
+            let a = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let b = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let c = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let call = IntermediateExpression::from(IntermediateFnCall {
+                fn_: f.clone().into(),
+                args: Vec::new()
+            });
+            let main = IntermediateLambda {
+                args: vec![f.clone()],
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: a.register.clone(),
+                            expression: call.clone()
+                        }.into(),
+                        IntermediateAssignment {
+                            register: b.register.clone(),
+                            expression: call.clone()
+                        }.into(),
+                        IntermediateAssignment {
+                            register: c.register.clone(),
+                            expression: plus(&a, &b)
+                        }.into(),
+                    ],
+                    ret: c.clone().into()
+                }
+            };
+            (main.clone(), main)
+        };
+        "unknown fn calls"
+    )]
+    #[test_case(
+        {
+            let a = IntermediateMemory::from(IntermediateType::from(
+                IntermediateUnionType(vec![None])
+            ));
+            let b = IntermediateMemory::from(IntermediateType::from(
+                IntermediateUnionType(vec![None, None])
+            ));
+            let main = IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: a.register.clone(),
+                            expression: IntermediateCtorCall {
+                                idx: 0,
+                                data: None,
+                                type_: IntermediateUnionType(vec![None])
+                            }.into()
+                        }.into(),
+                        IntermediateAssignment {
+                            register: b.register.clone(),
+                            expression: IntermediateCtorCall {
+                                idx: 0,
+                                data: None,
+                                type_: IntermediateUnionType(vec![None, None])
+                            }.into()
+                        }.into(),
+                    ],
+                    ret: b.clone().into()
+                }
+            };
+            (main.clone(), main)
+        };
+        "constructors of different unions"
+    )]
+    fn test_eliminate_common_subexpressions(
+        main_expected: (IntermediateLambda, IntermediateLambda),
+    ) {
+        let (main, expected_main) = main_expected;
+        let program =
+            CommonSubexpressionEliminator::eliminate_common_subexpressions(IntermediateProgram {
+                main,
+                types: Vec::new(),
+            });
+        ExpressionEqualityChecker::assert_equal(&program.main.into(), &expected_main.into())
+    }
+}
@@ -0,0 +1,195 @@
+use lowering::{
+    Boolean, BuiltInFn, Id, Integer, IntermediateAssignment, IntermediateBlock,
+    IntermediateBuiltIn, IntermediateExpression, IntermediateFnCall, IntermediateIf,
+    IntermediateMatch, IntermediateMatchBranch, IntermediateProgram, IntermediateStatement,
+    IntermediateValue, Register,
+};
+
+/// A comparison whose result does not depend on the values of its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantComparison {
+    /// Register that the result of the comparison is assigned to.
+    pub register: Register,
+    /// Result of the comparison.
+    pub value: bool,
+}
+
+/// Find comparisons that are always true or always false, which are likely to be bugs.
+pub struct ComparisonLinter {
+    fold: bool,
+    comparisons: Vec<ConstantComparison>,
+}
+
+impl ComparisonLinter {
+    /// Determine the result of comparing two values, if it is known at compile time.
+    fn decide(id: &Id, args: &[IntermediateValue]) -> Option<bool> {
+        let [x, y] = args else {
+            return None;
+        };
+        if x.register().is_some() && x.register() == y.register() {
+            return match id.as_str() {
+                "==" | "<=" | ">=" => Some(true),
+                "!=" | "<" | ">" => Some(false),
+                _ => None,
+            };
+        }
+        let (
+            IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::Integer(Integer {
+                value: x,
+            })),
+            IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::Integer(Integer {
+                value: y,
+            })),
+        ) = (x, y)
+        else {
+            return None;
+        };
+        match id.as_str() {
+            "==" => Some(x == y),
+            "!=" => Some(x != y),
+            "<" => Some(x < y),
+            "<=" => Some(x <= y),
+            ">" => Some(x > y),
+            ">=" => Some(x >= y),
+            _ => None,
+        }
+    }
+    fn lint_expression(&mut self, expression: &mut IntermediateExpression, register: &Register) {
+        match expression {
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                fn_:
+                    IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                        id,
+                        _,
+                    ))),
+                args,
+            }) => {
+                if let Some(value) = Self::decide(id, args) {
+                    self.comparisons.push(ConstantComparison {
+                        register: register.clone(),
+                        value,
+                    });
+                    if self.fold {
+                        *expression = IntermediateValue::from(Boolean { value }).into();
+                    }
+                }
+            }
+            IntermediateExpression::IntermediateLambda(lambda) => {
+                self.lint_block(&mut lambda.block)
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition: _,
+                branches,
+            }) => {
+                self.lint_block(&mut branches.0);
+                self.lint_block(&mut branches.1);
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => {
+                for IntermediateMatchBranch { target: _, block } in branches {
+                    self.lint_block(block);
+                }
+            }
+            IntermediateExpression::IntermediateValue(_)
+            | IntermediateExpression::IntermediateElementAccess(_)
+            | IntermediateExpression::IntermediateTupleExpression(_)
+            | IntermediateExpression::IntermediateFnCall(_)
+            | IntermediateExpression::IntermediateCtorCall(_) => {}
+        }
+    }
+    fn lint_block(&mut self, block: &mut IntermediateBlock) {
+        for statement in &mut block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            self.lint_expression(expression, register);
+        }
+    }
+    /// Find comparisons of a register with itself or of two integer literals.
+    pub fn lint(program: &IntermediateProgram) -> Vec<ConstantComparison> {
+        let mut linter = ComparisonLinter {
+            fold: false,
+            comparisons: Vec::new(),
+        };
+        linter.lint_block(&mut program.main.block.clone());
+        linter.comparisons
+    }
+    /// Replace comparisons whose result is known with boolean literals, returning the replaced comparisons.
+    pub fn fold_comparisons(
+        program: IntermediateProgram,
+    ) -> (IntermediateProgram, Vec<ConstantComparison>) {
+        let IntermediateProgram { mut main, types } = program;
+        let mut linter = ComparisonLinter {
+            fold: true,
+            comparisons: Vec::new(),
+        };
+        linter.lint_block(&mut main.block);
+        (IntermediateProgram { main, types }, linter.comparisons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{assign, call, program};
+    use lowering::{
+        AtomicTypeEnum, ExpressionEqualityChecker, IntermediateArg, IntermediateMemory,
+        IntermediateType,
+    };
+    use test_case::test_case;
+
+    #[test_case(|x, _| call("==", vec![x.clone(), x]), Some(true); "equal to itself")]
+    #[test_case(|x, _| call("<", vec![x.clone(), x]), Some(false); "less than itself")]
+    #[test_case(|x, _| call(">=", vec![x.clone(), x]), Some(true); "at least itself")]
+    #[test_case(
+        |_, _| call("!=", vec![Integer { value: 5 }.into(), Integer { value: 5 }.into()]),
+        Some(false);
+        "equal literals"
+    )]
+    #[test_case(|x, y| call("==", vec![x, y]), None; "different arguments")]
+    #[test_case(
+        |x, _| call("==", vec![x, Integer { value: 5 }.into()]),
+        None;
+        "argument and literal"
+    )]
+    fn test_fold_comparisons(
+        expression: fn(IntermediateValue, IntermediateValue) -> IntermediateExpression,
+        value: Option<bool>,
+    ) {
+        let args = [
+            IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT)),
+            IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT)),
+        ];
+        let z = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+        let expression = expression(args[0].clone().into(), args[1].clone().into());
+        let original = program(
+            args.to_vec(),
+            vec![assign(&z, expression.clone())],
+            z.clone().into(),
+        );
+        let comparisons = value
+            .map(|value| ConstantComparison {
+                register: z.register.clone(),
+                value,
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(ComparisonLinter::lint(&original), comparisons);
+        let (folded, folded_comparisons) = ComparisonLinter::fold_comparisons(original);
+        assert_eq!(folded_comparisons, comparisons);
+        let expected = match value {
+            Some(value) => IntermediateValue::from(Boolean { value }).into(),
+            None => expression,
+        };
+        ExpressionEqualityChecker::assert_equal(
+            &folded.main.into(),
+            &program(args.to_vec(), vec![assign(&z, expected)], z.into())
+                .main
+                .into(),
+        )
+    }
+}
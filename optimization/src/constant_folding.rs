@@ -0,0 +1,613 @@
+use std::collections::HashMap;
+
+use lowering::{
+    AtomicTypeEnum, Boolean, BuiltInFn, Id, Integer, IntermediateAssignment, IntermediateBlock,
+    IntermediateBuiltIn, IntermediateExpression, IntermediateFnCall, IntermediateFnType,
+    IntermediateIf, IntermediateMatch, IntermediateMatchBranch, IntermediateMemory,
+    IntermediateProgram, IntermediateStatement, IntermediateTupleExpression, IntermediateTupleType,
+    IntermediateValue, Register, Str,
+};
+
+type Constants = HashMap<Register, IntermediateBuiltIn>;
+
+/// Evaluate calls to built-in fns with constant arguments at compile time.
+pub struct ConstantFolder {
+    constants: Constants,
+}
+
+impl ConstantFolder {
+    fn new() -> Self {
+        ConstantFolder {
+            constants: Constants::new(),
+        }
+    }
+    /// Find the constant that a value is known to hold.
+    fn constant(&self, value: &IntermediateValue) -> Option<IntermediateBuiltIn> {
+        match value {
+            IntermediateValue::IntermediateBuiltIn(built_in) => Some(built_in.clone()),
+            IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register }) => {
+                self.constants.get(register).cloned()
+            }
+            IntermediateValue::IntermediateArg(_) => None,
+        }
+    }
+    /// Evaluate a built-in fn, unless the result is undefined at runtime.
+    fn evaluate(id: &Id, args: &[IntermediateBuiltIn]) -> Option<IntermediateBuiltIn> {
+        let integers = args
+            .iter()
+            .map(|arg| match arg {
+                IntermediateBuiltIn::Integer(Integer { value }) => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let booleans = args
+            .iter()
+            .map(|arg| match arg {
+                IntermediateBuiltIn::Boolean(Boolean { value }) => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let integer = |value: Option<i64>| value.map(|value| Integer { value }.into());
+        let boolean = |value: bool| Some(Boolean { value }.into());
+        match (id.as_str(), integers.as_deref(), booleans.as_deref()) {
+            ("+", Some(&[x, y]), _) => integer(x.checked_add(y)),
+            ("-", Some(&[x, y]), _) => integer(x.checked_sub(y)),
+            ("*", Some(&[x, y]), _) => integer(x.checked_mul(y)),
+            ("/", Some(&[x, y]), _) => integer(x.checked_div(y)),
+            ("%", Some(&[x, y]), _) => integer(x.checked_rem(y)),
+            ("&", Some(&[x, y]), _) => integer(Some(x & y)),
+            ("|", Some(&[x, y]), _) => integer(Some(x | y)),
+            ("^", Some(&[x, y]), _) => integer(Some(x ^ y)),
+            ("fma", Some(&[x, y, z]), _) => {
+                integer(x.checked_mul(y).and_then(|p| p.checked_add(z)))
+            }
+            ("min", Some(&[x, y]), _) => integer(Some(x.min(y))),
+            ("max", Some(&[x, y]), _) => integer(Some(x.max(y))),
+            ("+sat", Some(&[x, y]), _) => integer(Some(x.saturating_add(y))),
+            ("-sat", Some(&[x, y]), _) => integer(Some(x.saturating_sub(y))),
+            ("*sat", Some(&[x, y]), _) => integer(Some(x.saturating_mul(y))),
+            ("+wrap", Some(&[x, y]), _) => integer(Some(x.wrapping_add(y))),
+            ("-wrap", Some(&[x, y]), _) => integer(Some(x.wrapping_sub(y))),
+            ("*wrap", Some(&[x, y]), _) => integer(Some(x.wrapping_mul(y))),
+            // Three-way comparisons evaluate to -1, 0 or 1.
+            ("<=>", Some(&[x, y]), _) => integer(Some(x.cmp(&y) as i64)),
+            ("-", Some(&[x]), _) => integer(x.checked_neg()),
+            ("++", Some(&[x]), _) => integer(x.checked_add(1)),
+            ("--", Some(&[x]), _) => integer(x.checked_sub(1)),
+            ("~", Some(&[x]), _) => integer(Some(!x)),
+            ("abs", Some(&[x]), _) => integer(x.checked_abs()),
+            ("<", Some(&[x, y]), _) => boolean(x < y),
+            ("<=", Some(&[x, y]), _) => boolean(x <= y),
+            (">", Some(&[x, y]), _) => boolean(x > y),
+            (">=", Some(&[x, y]), _) => boolean(x >= y),
+            ("==", Some(&[x, y]), _) => boolean(x == y),
+            ("!=", Some(&[x, y]), _) => boolean(x != y),
+            ("&&", _, Some(&[x, y])) => boolean(x && y),
+            ("||", _, Some(&[x, y])) => boolean(x || y),
+            ("!", _, Some(&[x])) => boolean(!x),
+            _ => None,
+        }
+    }
+    /// Call to `error` that replaces an assertion that always fails.
+    fn assertion_failure() -> IntermediateExpression {
+        IntermediateFnCall {
+            fn_: BuiltInFn(
+                Id::from("error"),
+                IntermediateFnType(
+                    vec![AtomicTypeEnum::STRING.into()],
+                    Box::new(IntermediateTupleType(Vec::new()).into()),
+                ),
+            )
+            .into(),
+            args: vec![Str {
+                value: String::from("Assertion failed."),
+            }
+            .into()],
+        }
+        .into()
+    }
+    /// Fold an expression, returning the constant it evaluates to, if any.
+    fn fold_expression(
+        &mut self,
+        expression: &mut IntermediateExpression,
+    ) -> Option<IntermediateBuiltIn> {
+        match expression {
+            IntermediateExpression::IntermediateValue(value) => self.constant(value),
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                fn_:
+                    IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                        id,
+                        _,
+                    ))),
+                args,
+            }) if id == "assert" => {
+                // Passing assertions do nothing and failing ones always abort.
+                if let [condition] = args.as_slice() {
+                    match self.constant(condition) {
+                        Some(IntermediateBuiltIn::Boolean(Boolean { value: true })) => {
+                            *expression = IntermediateTupleExpression(Vec::new()).into();
+                        }
+                        Some(IntermediateBuiltIn::Boolean(Boolean { value: false })) => {
+                            *expression = Self::assertion_failure();
+                        }
+                        _ => {}
+                    }
+                }
+                None
+            }
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                fn_:
+                    IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                        id,
+                        _,
+                    ))),
+                args,
+            }) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.constant(arg))
+                    .collect::<Option<Vec<_>>>()?;
+                let value = Self::evaluate(id, &args)?;
+                *expression = IntermediateValue::from(value.clone()).into();
+                Some(value)
+            }
+            IntermediateExpression::IntermediateLambda(lambda) => {
+                self.fold_block(&mut lambda.block);
+                None
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => {
+                // Expose constant conditions to branch elimination.
+                if let Some(constant) = self.constant(condition) {
+                    *condition = constant.into();
+                }
+                self.fold_block(&mut branches.0);
+                self.fold_block(&mut branches.1);
+                None
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => {
+                for IntermediateMatchBranch { target: _, block } in branches {
+                    self.fold_block(block);
+                }
+                None
+            }
+            IntermediateExpression::IntermediateElementAccess(_)
+            | IntermediateExpression::IntermediateTupleExpression(_)
+            | IntermediateExpression::IntermediateFnCall(_)
+            | IntermediateExpression::IntermediateCtorCall(_) => None,
+        }
+    }
+    fn fold_block(&mut self, block: &mut IntermediateBlock) {
+        for statement in &mut block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            if let Some(constant) = self.fold_expression(expression) {
+                self.constants.insert(register.clone(), constant);
+            }
+        }
+        if let Some(constant) = self.constant(&block.ret) {
+            block.ret = constant.into();
+        }
+    }
+    /// Replace calls to built-in fns with constant arguments with their results.
+    pub fn fold_constants(program: IntermediateProgram) -> IntermediateProgram {
+        let IntermediateProgram { mut main, types } = program;
+        let mut folder = ConstantFolder::new();
+        folder.fold_block(&mut main.block);
+        IntermediateProgram { main, types }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{dead_code_analysis::DeadCodeAnalyzer, test_utils::call};
+    use lowering::{
+        AtomicTypeEnum, ExpressionEqualityChecker, IntermediateArg, IntermediateFnType,
+        IntermediateLambda, IntermediateType,
+    };
+    use test_case::test_case;
+
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = |expression: IntermediateExpression, ret: IntermediateValue| IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression,
+                        }.into(),
+                    ],
+                    ret,
+                },
+            };
+            (
+                main(call("abs", vec![Integer { value: -5 }.into()]), x.clone().into()),
+                main(IntermediateValue::from(Integer { value: 5 }).into(), Integer { value: 5 }.into()),
+            )
+        };
+        "abs"
+    )]
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = |expressions: [IntermediateExpression; 2], ret: IntermediateValue| {
+                let [x_expression, y_expression] = expressions;
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: x.register.clone(),
+                                expression: x_expression,
+                            }.into(),
+                            IntermediateAssignment {
+                                register: y.register.clone(),
+                                expression: y_expression,
+                            }.into(),
+                        ],
+                        ret,
+                    },
+                }
+            };
+            (
+                main(
+                    [
+                        call("min", vec![Integer { value: 3 }.into(), Integer { value: 4 }.into()]),
+                        call("max", vec![x.clone().into(), Integer { value: -7 }.into()]),
+                    ],
+                    y.clone().into()
+                ),
+                main(
+                    [
+                        IntermediateValue::from(Integer { value: 3 }).into(),
+                        IntermediateValue::from(Integer { value: 3 }).into(),
+                    ],
+                    Integer { value: 3 }.into()
+                ),
+            )
+        };
+        "chained min max"
+    )]
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: call("abs", vec![Integer { value: i64::MIN }.into()]),
+                        }.into(),
+                        IntermediateAssignment {
+                            register: y.register.clone(),
+                            expression: call("/", vec![Integer { value: 1 }.into(), Integer { value: 0 }.into()]),
+                        }.into(),
+                    ],
+                    ret: y.clone().into(),
+                },
+            };
+            (main.clone(), main)
+        };
+        "undefined results"
+    )]
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = |expressions: [IntermediateExpression; 2], ret: IntermediateValue| {
+                let [x_expression, y_expression] = expressions;
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: x.register.clone(),
+                                expression: x_expression,
+                            }.into(),
+                            IntermediateAssignment {
+                                register: y.register.clone(),
+                                expression: y_expression,
+                            }.into(),
+                        ],
+                        ret,
+                    },
+                }
+            };
+            (
+                main(
+                    [
+                        call("+wrap", vec![Integer { value: i64::MAX }.into(), Integer { value: 1 }.into()]),
+                        call("*wrap", vec![x.clone().into(), Integer { value: 2 }.into()]),
+                    ],
+                    y.clone().into()
+                ),
+                main(
+                    [
+                        IntermediateValue::from(Integer { value: i64::MIN }).into(),
+                        IntermediateValue::from(Integer { value: 0 }).into(),
+                    ],
+                    Integer { value: 0 }.into()
+                ),
+            )
+        };
+        "wrapping overflow"
+    )]
+    #[test_case(
+        {
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = |expressions: [IntermediateExpression; 2], ret: IntermediateValue| {
+                let [x_expression, y_expression] = expressions;
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: vec![
+                            IntermediateAssignment {
+                                register: x.register.clone(),
+                                expression: x_expression,
+                            }.into(),
+                            IntermediateAssignment {
+                                register: y.register.clone(),
+                                expression: y_expression,
+                            }.into(),
+                        ],
+                        ret,
+                    },
+                }
+            };
+            (
+                main(
+                    [
+                        call("-sat", vec![Integer { value: i64::MIN }.into(), Integer { value: 1 }.into()]),
+                        call("*sat", vec![x.clone().into(), Integer { value: 2 }.into()]),
+                    ],
+                    y.clone().into()
+                ),
+                main(
+                    [
+                        IntermediateValue::from(Integer { value: i64::MIN }).into(),
+                        IntermediateValue::from(Integer { value: i64::MIN }).into(),
+                    ],
+                    Integer { value: i64::MIN }.into()
+                ),
+            )
+        };
+        "saturating overflow"
+    )]
+    #[test_case(
+        {
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: vec![arg.clone()],
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: call("max", vec![arg.clone().into(), Integer { value: 0 }.into()]),
+                        }.into(),
+                    ],
+                    ret: x.clone().into(),
+                },
+            };
+            (main.clone(), main)
+        };
+        "unknown argument"
+    )]
+    #[test_case(
+        {
+            let c = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = |expression: IntermediateExpression, condition: IntermediateValue| IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: c.register.clone(),
+                            expression,
+                        }.into(),
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: IntermediateIf {
+                                condition,
+                                branches: (
+                                    IntermediateValue::from(Integer { value: 1 }).into(),
+                                    IntermediateValue::from(Integer { value: 2 }).into(),
+                                ),
+                            }.into(),
+                        }.into(),
+                    ],
+                    ret: x.clone().into(),
+                },
+            };
+            (
+                main(
+                    call("<", vec![Integer { value: 1 }.into(), Integer { value: 2 }.into()]),
+                    c.clone().into()
+                ),
+                main(
+                    IntermediateValue::from(Boolean { value: true }).into(),
+                    Boolean { value: true }.into()
+                ),
+            )
+        };
+        "constant condition"
+    )]
+    fn test_fold_constants(main_expected: (IntermediateLambda, IntermediateLambda)) {
+        let (main, expected_main) = main_expected;
+        let program = ConstantFolder::fold_constants(IntermediateProgram {
+            main,
+            types: Vec::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(&program.main.into(), &expected_main.into())
+    }
+
+    #[test_case(3, 5, -1; "less")]
+    #[test_case(4, 4, 0; "equal")]
+    #[test_case(i64::MAX, i64::MIN, 1; "greater")]
+    fn test_fold_three_way_comparison(x: i64, y: i64, expected: i64) {
+        let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let main =
+            |expression: IntermediateExpression, ret: IntermediateValue| IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![IntermediateAssignment {
+                        register: result.register.clone(),
+                        expression,
+                    }
+                    .into()],
+                    ret,
+                },
+            };
+        let program = ConstantFolder::fold_constants(IntermediateProgram {
+            main: main(
+                call(
+                    "<=>",
+                    vec![Integer { value: x }.into(), Integer { value: y }.into()],
+                ),
+                result.clone().into(),
+            ),
+            types: Vec::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(
+            &program.main.into(),
+            &main(
+                IntermediateValue::from(Integer { value: expected }).into(),
+                Integer { value: expected }.into(),
+            )
+            .into(),
+        )
+    }
+
+    #[test_case(2, 3, 4, Some(10); "positive")]
+    #[test_case(-5, 6, 7, Some(-23); "negative product")]
+    #[test_case(i64::MAX, 2, 0, None; "overflowing product")]
+    #[test_case(i64::MAX, 1, 1, None; "overflowing sum")]
+    fn test_fold_fused_multiply_add(x: i64, y: i64, z: i64, expected: Option<i64>) {
+        let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let main =
+            |expression: IntermediateExpression, ret: IntermediateValue| IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![IntermediateAssignment {
+                        register: result.register.clone(),
+                        expression,
+                    }
+                    .into()],
+                    ret,
+                },
+            };
+        let fma = || {
+            call(
+                "fma",
+                vec![
+                    Integer { value: x }.into(),
+                    Integer { value: y }.into(),
+                    Integer { value: z }.into(),
+                ],
+            )
+        };
+        let program = ConstantFolder::fold_constants(IntermediateProgram {
+            main: main(fma(), result.clone().into()),
+            types: Vec::new(),
+        });
+        let expected_main = match expected {
+            Some(value) => main(
+                IntermediateValue::from(Integer { value }).into(),
+                Integer { value }.into(),
+            ),
+            None => main(fma(), result.clone().into()),
+        };
+        ExpressionEqualityChecker::assert_equal(&program.main.into(), &expected_main.into())
+    }
+
+    fn assertion(condition: IntermediateValue) -> IntermediateExpression {
+        IntermediateFnCall {
+            fn_: BuiltInFn(
+                Id::from("assert"),
+                IntermediateFnType(
+                    vec![AtomicTypeEnum::BOOL.into()],
+                    Box::new(IntermediateTupleType(Vec::new()).into()),
+                ),
+            )
+            .into(),
+            args: vec![condition],
+        }
+        .into()
+    }
+
+    #[test_case(
+        |_| assertion(Boolean { value: true }.into()),
+        |_| IntermediateTupleExpression(Vec::new()).into(),
+        false;
+        "passing assertion"
+    )]
+    #[test_case(
+        |_| assertion(Boolean { value: false }.into()),
+        |_| ConstantFolder::assertion_failure(),
+        true;
+        "failing assertion"
+    )]
+    #[test_case(
+        assertion,
+        assertion,
+        true;
+        "runtime assertion"
+    )]
+    fn test_fold_assertions(
+        expression: fn(IntermediateValue) -> IntermediateExpression,
+        expected: fn(IntermediateValue) -> IntermediateExpression,
+        kept: bool,
+    ) {
+        let condition = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+        let result =
+            IntermediateMemory::from(IntermediateType::from(IntermediateTupleType(Vec::new())));
+        let main = |statements: Vec<IntermediateStatement>| IntermediateLambda {
+            args: vec![condition.clone()],
+            block: IntermediateBlock {
+                statements,
+                ret: Integer { value: 0 }.into(),
+            },
+        };
+        let assignment = |expression: IntermediateExpression| {
+            vec![IntermediateAssignment {
+                register: result.register.clone(),
+                expression,
+            }
+            .into()]
+        };
+        let program = ConstantFolder::fold_constants(IntermediateProgram {
+            main: main(assignment(expression(condition.clone().into()))),
+            types: Vec::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(
+            &program.main.clone().into(),
+            &main(assignment(expected(condition.clone().into()))).into(),
+        );
+        // Only assertions that might fail are kept by dead code analysis.
+        let (program, _) = DeadCodeAnalyzer::remove_dead_code(program);
+        let expected_statements = if kept {
+            assignment(expected(condition.clone().into()))
+        } else {
+            Vec::new()
+        };
+        ExpressionEqualityChecker::assert_equal(
+            &program.main.into(),
+            &main(expected_statements).into(),
+        )
+    }
+}
@@ -7,18 +7,23 @@ use std::{
 
 use itertools::{zip_eq, Itertools};
 use lowering::{
-    IntermediateArg, IntermediateAssignment, IntermediateBlock, IntermediateExpression,
-    IntermediateFnCall, IntermediateFnType, IntermediateIf, IntermediateLambda, IntermediateMatch,
-    IntermediateMatchBranch, IntermediateMemory, IntermediateProgram, IntermediateStatement,
-    IntermediateTupleExpression, IntermediateType, IntermediateValue, Register,
+    BuiltInFn, Id, IntermediateArg, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn,
+    IntermediateExpression, IntermediateFnCall, IntermediateFnType, IntermediateIf,
+    IntermediateLambda, IntermediateMatch, IntermediateMatchBranch, IntermediateMemory,
+    IntermediateProgram, IntermediateStatement, IntermediateTupleExpression, IntermediateType,
+    IntermediateValue, Register, IMPURE_BUILT_INS,
 };
 
+use crate::stats::OptimizationStats;
+
 pub struct DeadCodeAnalyzer {
     single_constraints: HashMap<Register, HashSet<Register>>,
     double_constraints: HashMap<(Register, Register), HashSet<Register>>,
     fn_args: HashMap<Register, Vec<Register>>,
     variables: HashSet<Register>,
     fn_updates: HashMap<Register, Register>,
+    effectful_built_ins: HashSet<Id>,
+    effectful_fns: HashSet<Register>,
 }
 
 impl DeadCodeAnalyzer {
@@ -29,6 +34,8 @@ impl DeadCodeAnalyzer {
             fn_args: HashMap::new(),
             variables: HashSet::new(),
             fn_updates: HashMap::new(),
+            effectful_built_ins: IMPURE_BUILT_INS.iter().map(|id| Id::from(*id)).collect(),
+            effectful_fns: HashSet::new(),
         }
     }
     fn used_value(&mut self, value: &IntermediateValue) -> Option<Register> {
@@ -195,6 +202,67 @@ impl DeadCodeAnalyzer {
             }
         }
     }
+    /// Find registers that must be kept because evaluating their expressions has side-effects.
+    fn find_effects(&mut self, statements: &Vec<IntermediateStatement>) -> Vec<Register> {
+        let mut effects = Vec::new();
+        for statement in statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            let (nested_effects, is_effectful) = match expression {
+                IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_, args: _ }) => {
+                    let is_effectful = match fn_ {
+                        IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(
+                            BuiltInFn(id, _),
+                        )) => self.effectful_built_ins.contains(id),
+                        IntermediateValue::IntermediateMemory(memory) => {
+                            self.effectful_fns.contains(&memory.register)
+                        }
+                        _ => false,
+                    };
+                    (Vec::new(), is_effectful)
+                }
+                IntermediateExpression::IntermediateLambda(IntermediateLambda {
+                    args: _,
+                    block,
+                }) => {
+                    let effects = self.find_effects(&block.statements);
+                    // Calling the fn has side-effects but defining it does not.
+                    if !effects.is_empty() {
+                        self.effectful_fns.insert(register.clone());
+                    }
+                    (effects, false)
+                }
+                IntermediateExpression::IntermediateIf(IntermediateIf {
+                    condition: _,
+                    branches,
+                }) => {
+                    let mut effects = self.find_effects(&branches.0.statements);
+                    effects.extend(self.find_effects(&branches.1.statements));
+                    let is_effectful = !effects.is_empty();
+                    (effects, is_effectful)
+                }
+                IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                    subject: _,
+                    branches,
+                }) => {
+                    let effects = branches
+                        .iter()
+                        .flat_map(|branch| self.find_effects(&branch.block.statements))
+                        .collect_vec();
+                    let is_effectful = !effects.is_empty();
+                    (effects, is_effectful)
+                }
+                _ => (Vec::new(), false),
+            };
+            effects.extend(nested_effects);
+            if is_effectful {
+                effects.push(register.clone());
+            }
+        }
+        effects
+    }
     fn solve_constraints(&self, initial_solution: Vec<Register>) -> HashSet<Register> {
         let mut solution = HashSet::from_iter(initial_solution.clone());
         let mut new_variables = VecDeque::from(initial_solution);
@@ -442,29 +510,33 @@ impl DeadCodeAnalyzer {
             })
             .collect_vec()
     }
-    pub fn remove_dead_code(program: IntermediateProgram) -> IntermediateProgram {
-        let mut optimizer = DeadCodeAnalyzer::new();
+    /// Remove dead code and count the statements that were removed.
+    pub fn remove_dead_code(program: IntermediateProgram) -> (IntermediateProgram, usize) {
+        let statements = OptimizationStats::count_statements(&program);
+        let program = DeadCodeAnalyzer::new().remove_dead_code_from_program(program);
+        let removed = statements.saturating_sub(OptimizationStats::count_statements(&program));
+        (program, removed)
+    }
+    fn remove_dead_code_from_program(
+        mut self,
+        program: IntermediateProgram,
+    ) -> IntermediateProgram {
         let IntermediateLambda {
             args,
             block: IntermediateBlock { statements, ret },
         } = program.main;
-        optimizer.generate_constraints(&statements);
-        let IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register }) = &ret
-        else {
-            return IntermediateProgram {
-                main: IntermediateLambda {
-                    args,
-                    block: IntermediateBlock {
-                        statements: Vec::new(),
-                        ret,
-                    },
-                },
-                types: program.types,
-            };
+        self.generate_constraints(&statements);
+        // Repeat until every fn that calls an effectful fn has been found.
+        let mut initial_solution = loop {
+            let effectful_fns = self.effectful_fns.len();
+            let effects = self.find_effects(&statements);
+            if self.effectful_fns.len() == effectful_fns {
+                break effects;
+            }
         };
-        let initial_solution = vec![register.clone()];
-        optimizer.variables = optimizer.solve_constraints(initial_solution);
-        let statements = optimizer.remove_redundancy(statements);
+        initial_solution.extend(self.used_value(&ret));
+        self.variables = self.solve_constraints(initial_solution);
+        let statements = self.remove_redundancy(statements);
         IntermediateProgram {
             main: IntermediateLambda {
                 args,
@@ -2523,7 +2595,7 @@ mod tests {
     )]
     fn test_remove_program_dead_code(program_expected: (IntermediateProgram, IntermediateProgram)) {
         let (program, expected_program) = program_expected;
-        let optimized_program = DeadCodeAnalyzer::remove_dead_code(program);
+        let (optimized_program, _) = DeadCodeAnalyzer::remove_dead_code(program);
         dbg!(&expected_program, &optimized_program);
         assert_eq!(optimized_program.types, expected_program.types);
         ExpressionEqualityChecker::assert_equal(
@@ -2531,4 +2603,144 @@ mod tests {
             &expected_program.main.into(),
         )
     }
+
+    fn print_call(value: IntermediateValue) -> IntermediateExpression {
+        IntermediateFnCall {
+            fn_: BuiltInFn(
+                Id::from("print"),
+                IntermediateFnType(
+                    vec![AtomicTypeEnum::INT.into()],
+                    Box::new(IntermediateTupleType(Vec::new()).into()),
+                ),
+            )
+            .into(),
+            args: vec![value],
+        }
+        .into()
+    }
+
+    #[test_case(
+        {
+            let printed = IntermediateMemory::from(IntermediateType::from(IntermediateTupleType(Vec::new())));
+            let unused = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = |statements: Vec<IntermediateStatement>| IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements,
+                    ret: Integer { value: 0 }.into(),
+                },
+            };
+            (
+                main(vec![
+                    IntermediateAssignment {
+                        register: printed.register.clone(),
+                        expression: print_call(Integer { value: 1 }.into()),
+                    }.into(),
+                    IntermediateAssignment {
+                        register: unused.register.clone(),
+                        expression: IntermediateTupleExpression(Vec::new()).into(),
+                    }.into(),
+                ]),
+                main(vec![
+                    IntermediateAssignment {
+                        register: printed.register.clone(),
+                        expression: print_call(Integer { value: 1 }.into()),
+                    }.into(),
+                ]),
+            )
+        };
+        "unused effectful call"
+    )]
+    #[test_case(
+        {
+            let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let printed = IntermediateMemory::from(IntermediateType::from(IntermediateTupleType(Vec::new())));
+            let fn_ = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+                vec![AtomicTypeEnum::INT.into()],
+                Box::new(AtomicTypeEnum::INT.into()),
+            )));
+            let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: fn_.register.clone(),
+                            expression: IntermediateLambda {
+                                args: vec![arg.clone()],
+                                block: IntermediateBlock {
+                                    statements: vec![
+                                        IntermediateAssignment {
+                                            register: printed.register.clone(),
+                                            expression: print_call(arg.clone().into()),
+                                        }.into(),
+                                    ],
+                                    ret: arg.clone().into(),
+                                },
+                            }.into(),
+                        }.into(),
+                        IntermediateAssignment {
+                            register: call.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: fn_.clone().into(),
+                                args: vec![Integer { value: 2 }.into()],
+                            }.into(),
+                        }.into(),
+                    ],
+                    ret: Boolean { value: true }.into(),
+                },
+            };
+            (main.clone(), main)
+        };
+        "call to effectful fn"
+    )]
+    #[test_case(
+        {
+            let printed = IntermediateMemory::from(IntermediateType::from(IntermediateTupleType(Vec::new())));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: result.register.clone(),
+                            expression: IntermediateIf {
+                                condition: Boolean { value: true }.into(),
+                                branches: (
+                                    (
+                                        vec![
+                                            IntermediateAssignment {
+                                                register: printed.register.clone(),
+                                                expression: print_call(Integer { value: 3 }.into()),
+                                            }.into(),
+                                        ],
+                                        Integer { value: 3 }.into(),
+                                    ).into(),
+                                    IntermediateValue::from(Integer { value: 4 }).into(),
+                                ),
+                            }.into(),
+                        }.into(),
+                    ],
+                    ret: Integer { value: 0 }.into(),
+                },
+            };
+            (main.clone(), main)
+        };
+        "effectful call in branch"
+    )]
+    fn test_remove_dead_code_with_effects(main_expected: (IntermediateLambda, IntermediateLambda)) {
+        let (main, expected_main) = main_expected;
+        let analyzer = DeadCodeAnalyzer {
+            effectful_built_ins: HashSet::from([Id::from("print")]),
+            ..DeadCodeAnalyzer::new()
+        };
+        let optimized_program = analyzer.remove_dead_code_from_program(IntermediateProgram {
+            main,
+            types: Vec::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(
+            &optimized_program.main.into(),
+            &expected_main.into(),
+        )
+    }
 }
@@ -0,0 +1,113 @@
+use lowering::{
+    BuiltInFn, Integer, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn,
+    IntermediateExpression, IntermediateFnCall, IntermediateIf, IntermediateMatch,
+    IntermediateMatchBranch, IntermediateProgram, IntermediateStatement, IntermediateValue,
+    Register,
+};
+
+/// A call to `/` or `%` with a literal zero divisor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivisionByZero {
+    /// Register that the result of the division is assigned to.
+    pub register: Register,
+}
+
+/// Reject divisions by a literal zero before they reach the runtime.
+pub struct DivisionChecker {}
+
+impl DivisionChecker {
+    fn is_division_by_zero(expression: &IntermediateExpression) -> bool {
+        matches!(
+            expression,
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                fn_: IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(id, _))),
+                args,
+            }) if (id == "/" || id == "%")
+                && matches!(
+                    args.get(1),
+                    Some(IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::Integer(Integer { value: 0 })))
+                )
+        )
+    }
+    fn check_expression(expression: &IntermediateExpression) -> Result<(), DivisionByZero> {
+        match expression {
+            IntermediateExpression::IntermediateLambda(lambda) => Self::check_block(&lambda.block),
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition: _,
+                branches,
+            }) => {
+                Self::check_block(&branches.0)?;
+                Self::check_block(&branches.1)
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => branches
+                .iter()
+                .try_for_each(|IntermediateMatchBranch { target: _, block }| {
+                    Self::check_block(block)
+                }),
+            IntermediateExpression::IntermediateValue(_)
+            | IntermediateExpression::IntermediateElementAccess(_)
+            | IntermediateExpression::IntermediateTupleExpression(_)
+            | IntermediateExpression::IntermediateFnCall(_)
+            | IntermediateExpression::IntermediateCtorCall(_) => Ok(()),
+        }
+    }
+    fn check_block(block: &IntermediateBlock) -> Result<(), DivisionByZero> {
+        for statement in &block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            if Self::is_division_by_zero(expression) {
+                return Err(DivisionByZero {
+                    register: register.clone(),
+                });
+            }
+            Self::check_expression(expression)?;
+        }
+        Ok(())
+    }
+    /// Find the first division or modulo by a literal zero in a program.
+    pub fn check(program: &IntermediateProgram) -> Result<(), DivisionByZero> {
+        Self::check_block(&program.main.block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{assign, call, program};
+    use lowering::{AtomicTypeEnum, IntermediateArg, IntermediateMemory, IntermediateType};
+    use test_case::test_case;
+
+    #[test_case("%", vec![Integer { value: 10 }.into(), Integer { value: 0 }.into()], true; "modulo by zero")]
+    #[test_case("/", vec![Integer { value: 10 }.into(), Integer { value: 0 }.into()], true; "division by zero")]
+    #[test_case("/", vec![Integer { value: 0 }.into(), Integer { value: 10 }.into()], false; "division of zero")]
+    #[test_case("*", vec![Integer { value: 10 }.into(), Integer { value: 0 }.into()], false; "multiplication by zero")]
+    #[test_case(
+        "%",
+        vec![
+            Integer { value: 10 }.into(),
+            IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT)).into()
+        ],
+        false;
+        "runtime divisor"
+    )]
+    fn test_division_check(id: &str, args: Vec<IntermediateValue>, rejected: bool) {
+        let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let register = result.register.clone();
+        let result = DivisionChecker::check(&program(
+            Vec::new(),
+            vec![assign(&result, call(id, args))],
+            result.into(),
+        ));
+        if rejected {
+            assert_eq!(result, Err(DivisionByZero { register }));
+        } else {
+            assert_eq!(result, Ok(()));
+        }
+    }
+}
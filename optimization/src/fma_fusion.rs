@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use lowering::{
+    AtomicTypeEnum, BuiltInFn, Id, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn,
+    IntermediateExpression, IntermediateFnCall, IntermediateFnType, IntermediateIf,
+    IntermediateLambda, IntermediateMatch, IntermediateMatchBranch, IntermediateProgram,
+    IntermediateStatement, IntermediateValue, Register,
+};
+
+/// Fuse multiplications followed by additions into calls to `fma`.
+pub struct FmaFuser {
+    // Operands of each multiplication, indexed by the register that stores the product.
+    products: HashMap<Register, (IntermediateValue, IntermediateValue)>,
+    uses: HashMap<Register, usize>,
+}
+
+impl FmaFuser {
+    fn new() -> Self {
+        FmaFuser {
+            products: HashMap::new(),
+            uses: HashMap::new(),
+        }
+    }
+    fn register(value: &IntermediateValue) -> Option<&Register> {
+        match value {
+            IntermediateValue::IntermediateMemory(memory) => Some(&memory.register),
+            IntermediateValue::IntermediateArg(arg) => Some(&arg.register),
+            IntermediateValue::IntermediateBuiltIn(_) => None,
+        }
+    }
+    fn count_value(&mut self, value: &IntermediateValue) {
+        if let Some(register) = Self::register(value) {
+            *self.uses.entry(register.clone()).or_default() += 1;
+        }
+    }
+    fn count_expression(&mut self, expression: &IntermediateExpression) {
+        for value in expression.values() {
+            self.count_value(&value);
+        }
+        match expression {
+            IntermediateExpression::IntermediateLambda(IntermediateLambda { args: _, block }) => {
+                self.count_block(block)
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition: _,
+                branches,
+            }) => {
+                self.count_block(&branches.0);
+                self.count_block(&branches.1);
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => {
+                for IntermediateMatchBranch { target: _, block } in branches {
+                    self.count_block(block);
+                }
+            }
+            _ => {}
+        }
+    }
+    fn count_block(&mut self, block: &IntermediateBlock) {
+        for statement in &block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register: _,
+            }) = statement;
+            self.count_expression(expression);
+        }
+        self.count_value(&block.ret);
+    }
+    /// Find the operands of a product that is only used once.
+    fn product(&self, value: &IntermediateValue) -> Option<(IntermediateValue, IntermediateValue)> {
+        let register = Self::register(value)?;
+        if self.uses.get(register) != Some(&1) {
+            return None;
+        }
+        self.products.get(register).cloned()
+    }
+    /// Find the arguments to `fma` that compute the same result as adding `x` and `y`.
+    fn fuse(&self, x: &IntermediateValue, y: &IntermediateValue) -> Option<Vec<IntermediateValue>> {
+        let ((a, b), c) = match self.product(x) {
+            Some(product) => (product, y),
+            None => (self.product(y)?, x),
+        };
+        Some(vec![a, b, c.clone()])
+    }
+    fn fuse_expression(&mut self, expression: &mut IntermediateExpression, register: &Register) {
+        match expression {
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                fn_:
+                    IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                        id,
+                        type_,
+                    ))),
+                args,
+            }) => match (id.as_str(), args.as_slice()) {
+                ("*", [x, y]) => {
+                    self.products
+                        .insert(register.clone(), (x.clone(), y.clone()));
+                }
+                ("+", [x, y]) => {
+                    if let Some(fma_args) = self.fuse(x, y) {
+                        *id = Id::from("fma");
+                        *type_ = IntermediateFnType(
+                            vec![AtomicTypeEnum::INT.into(); 3],
+                            Box::new(AtomicTypeEnum::INT.into()),
+                        );
+                        *args = fma_args;
+                    }
+                }
+                _ => {}
+            },
+            IntermediateExpression::IntermediateLambda(IntermediateLambda { args: _, block }) => {
+                self.fuse_block(block)
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition: _,
+                branches,
+            }) => {
+                self.fuse_block(&mut branches.0);
+                self.fuse_block(&mut branches.1);
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => {
+                for IntermediateMatchBranch { target: _, block } in branches {
+                    self.fuse_block(block);
+                }
+            }
+            _ => {}
+        }
+    }
+    fn fuse_block(&mut self, block: &mut IntermediateBlock) {
+        for statement in &mut block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            self.fuse_expression(expression, register);
+        }
+    }
+    /// Rewrite `x = a * b; y = x + c` to `y = fma(a, b, c)` when `x` is not used elsewhere.
+    /// The multiplication is left for dead code analysis to remove.
+    pub fn fuse_multiply_adds(program: IntermediateProgram) -> IntermediateProgram {
+        let IntermediateProgram { mut main, types } = program;
+        let mut fuser = FmaFuser::new();
+        fuser.count_block(&main.block);
+        fuser.fuse_block(&mut main.block);
+        IntermediateProgram { main, types }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{assign, call, program};
+    use lowering::{
+        ExpressionEqualityChecker, Integer, IntermediateArg, IntermediateMemory, IntermediateType,
+    };
+    use test_case::test_case;
+
+    /// `(a: int, b: int) -> <ret> { x = a * b; y = <sum>; <ret> }`
+    fn fusion_program(
+        sum: fn(IntermediateValue, IntermediateValue, IntermediateValue) -> IntermediateExpression,
+        ret: fn(IntermediateValue, IntermediateValue) -> IntermediateValue,
+        [a, b]: &[IntermediateArg; 2],
+        [x, y]: &[IntermediateMemory; 2],
+    ) -> IntermediateProgram {
+        program(
+            vec![a.clone(), b.clone()],
+            vec![
+                assign(x, call("*", vec![a.clone().into(), b.clone().into()])),
+                assign(y, sum(a.clone().into(), b.clone().into(), x.clone().into())),
+            ],
+            ret(x.clone().into(), y.clone().into()),
+        )
+    }
+
+    #[test_case(
+        |_, _, x| call("+", vec![x, Integer { value: 1 }.into()]),
+        |a, b, _| call("fma", vec![a, b, Integer { value: 1 }.into()]),
+        |_, y| y;
+        "product on the left"
+    )]
+    #[test_case(
+        |a, _, x| call("+", vec![a, x]),
+        |a, b, _| call("fma", vec![a.clone(), b, a]),
+        |_, y| y;
+        "product on the right"
+    )]
+    #[test_case(
+        |_, _, x| call("-", vec![x, Integer { value: 1 }.into()]),
+        |_, _, x| call("-", vec![x, Integer { value: 1 }.into()]),
+        |_, y| y;
+        "subtraction"
+    )]
+    #[test_case(
+        |_, _, x| call("+", vec![x, Integer { value: 1 }.into()]),
+        |_, _, x| call("+", vec![x, Integer { value: 1 }.into()]),
+        |x, _| x;
+        "product used elsewhere"
+    )]
+    fn test_fuse_multiply_adds(
+        sum: fn(IntermediateValue, IntermediateValue, IntermediateValue) -> IntermediateExpression,
+        expected_sum: fn(
+            IntermediateValue,
+            IntermediateValue,
+            IntermediateValue,
+        ) -> IntermediateExpression,
+        ret: fn(IntermediateValue, IntermediateValue) -> IntermediateValue,
+    ) {
+        let args = [
+            IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT)),
+            IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT)),
+        ];
+        let memory = [
+            IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT)),
+            IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT)),
+        ];
+        let fused = FmaFuser::fuse_multiply_adds(fusion_program(sum, ret, &args, &memory));
+        ExpressionEqualityChecker::assert_equal(
+            &fused.main.into(),
+            &fusion_program(expected_sum, ret, &args, &memory)
+                .main
+                .into(),
+        )
+    }
+}
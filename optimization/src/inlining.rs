@@ -3,9 +3,9 @@ use lowering::{
     FnDefs, FnInst, IntermediateAssignment, IntermediateBlock, IntermediateExpression,
     IntermediateFnCall, IntermediateIf, IntermediateLambda, IntermediateMatch,
     IntermediateMatchBranch, IntermediateMemory, IntermediateProgram, IntermediateStatement,
-    IntermediateValue,
+    IntermediateValue, RecursiveFnFinder, RecursiveFns, Register,
 };
-use std::convert::identity;
+use std::{collections::HashMap, convert::identity};
 
 use crate::{redundancy_elimination::RedundancyEliminator, refresher::Refresher};
 use itertools::Either::{Left, Right};
@@ -14,31 +14,69 @@ use translation::CodeSizeEstimator;
 pub struct Inliner {
     fn_defs: FnDefs,
     size_limit: usize,
+    /// Maximum size of a caller after inlining.
+    inline_budget: usize,
+    recursive_fns: RecursiveFns,
+    /// Recursive fns whose bodies are currently being inlined into.
+    enclosing_fns: Vec<IntermediateLambda>,
+    /// Estimated size of the innermost enclosing fn, including anything already inlined.
+    caller_size: usize,
+    /// Number of fn calls that have been inlined.
+    inlined: usize,
+    /// Maximum number of nested inlines along one call chain.
+    max_depth: usize,
+    /// Number of nested inlines that exposed the call assigned to each register (calls without an entry have depth `0`).
+    depths: HashMap<Register, usize>,
 }
 
 // Define exit limit in case of fns that are repeatedly inlined but do not increase in size.
 const MAX_INLINING_ITERATIONS: usize = 1000;
 
 impl Inliner {
+    /// Inline fns below the size limit, provided that their callers stay under the budget and the calls were exposed by fewer than `max_inline_depth` nested inlines.
+    /// Also count the calls that were inlined.
     pub fn inline_up_to_size(
         program: IntermediateProgram,
         size_limit: Option<usize>,
-    ) -> IntermediateProgram {
+        inline_budget: Option<usize>,
+        max_inline_depth: Option<usize>,
+    ) -> (IntermediateProgram, usize) {
         let mut should_continue = true;
         let mut program = program;
         let mut i = 0;
-        while should_continue && i < MAX_INLINING_ITERATIONS {
-            (program.main, should_continue) = Inliner::inline_iteration(program.main, size_limit);
+        let mut inlined = 0;
+        // Depths are tracked by register, which redundancy elimination renames, so a bounded inliner runs a single iteration.
+        let max_iterations = if max_inline_depth.is_some() {
+            1
+        } else {
+            MAX_INLINING_ITERATIONS
+        };
+        while should_continue && i < max_iterations {
+            let iteration_inlined;
+            (program.main, should_continue, iteration_inlined) = Inliner::inline_iteration(
+                program.main,
+                size_limit,
+                inline_budget,
+                max_inline_depth,
+            );
+            inlined += iteration_inlined;
             // Clean up with equivalent expression elimination after each iteration.
-            program = RedundancyEliminator::eliminate_redundancy(program);
+            (program, _) = RedundancyEliminator::eliminate_redundancy(program);
             i += 1;
         }
-        program
+        (program, inlined)
     }
     fn new() -> Self {
         Inliner {
             fn_defs: FnDefs::new(),
-            size_limit: usize::max_value(),
+            size_limit: usize::MAX,
+            inline_budget: usize::MAX,
+            recursive_fns: RecursiveFns::new(),
+            enclosing_fns: Vec::new(),
+            caller_size: 0,
+            inlined: 0,
+            max_depth: usize::MAX,
+            depths: HashMap::new(),
         }
     }
 
@@ -70,24 +108,38 @@ impl Inliner {
     fn inline_iteration(
         lambda: IntermediateLambda,
         size_limit: Option<usize>,
-    ) -> (IntermediateLambda, bool) {
+        inline_budget: Option<usize>,
+        max_inline_depth: Option<usize>,
+    ) -> (IntermediateLambda, bool, usize) {
         // If the lambda is already too big, do nothing.
         let bounds = CodeSizeEstimator::estimate_size(&lambda);
         if let Some(size) = size_limit {
             if bounds.1 >= size {
-                return (lambda, false);
+                return (lambda, false, 0);
             }
         }
+        let program = IntermediateProgram {
+            main: lambda,
+            types: Vec::new(),
+        };
+        let recursive_fns = RecursiveFnFinder::recursive_fns(&program);
         let IntermediateLambda {
             args,
             block: IntermediateBlock { statements, ret },
-        } = lambda;
-        // Register statements and set size limit.
+        } = program.main;
+        // Register statements and set size limits.
         let mut inliner = Inliner::from(&statements);
         if let Some(size) = size_limit {
             inliner.size_limit = size;
         }
-        let inliner = inliner;
+        if let Some(budget) = inline_budget {
+            inliner.inline_budget = budget;
+        }
+        if let Some(depth) = max_inline_depth {
+            inliner.max_depth = depth;
+        }
+        inliner.recursive_fns = recursive_fns;
+        inliner.caller_size = bounds.1;
         // Inline statements that are below a certain size.
         let (statements, should_continue) = inliner.inline_statements(statements);
         (
@@ -96,10 +148,11 @@ impl Inliner {
                 block: IntermediateBlock { statements, ret },
             },
             should_continue,
+            inliner.inlined,
         )
     }
     fn inline_statements(
-        &self,
+        &mut self,
         statements: Vec<IntermediateStatement>,
     ) -> (Vec<IntermediateStatement>, bool) {
         let (statements, continues): (Vec<_>, Vec<_>) = statements
@@ -109,7 +162,7 @@ impl Inliner {
         (statements.concat(), continues.into_iter().any(identity))
     }
     fn inline_statement(
-        &self,
+        &mut self,
         statement: IntermediateStatement,
     ) -> (Vec<IntermediateStatement>, bool) {
         match statement {
@@ -118,8 +171,49 @@ impl Inliner {
             }
         }
     }
+    /// Determine whether a callee can be inlined into the current caller.
+    fn can_inline(&self, lambda: &IntermediateLambda) -> bool {
+        let size = CodeSizeEstimator::estimate_size(lambda).1;
+        size < self.size_limit
+            && self.caller_size.saturating_add(size) < self.inline_budget
+            // Recursive fns are never inlined into themselves.
+            && !self.enclosing_fns.contains(lambda)
+    }
+    /// Record that the calls in inlined statements were exposed by `depth` nested inlines.
+    fn record_depths(&mut self, statements: &[IntermediateStatement], depth: usize) {
+        for statement in statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            match expression {
+                IntermediateExpression::IntermediateFnCall(_) => {
+                    self.depths.insert(register.clone(), depth);
+                }
+                IntermediateExpression::IntermediateLambda(lambda) => {
+                    self.record_depths(&lambda.block.statements, depth)
+                }
+                IntermediateExpression::IntermediateIf(IntermediateIf {
+                    condition: _,
+                    branches,
+                }) => {
+                    self.record_depths(&branches.0.statements, depth);
+                    self.record_depths(&branches.1.statements, depth);
+                }
+                IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                    subject: _,
+                    branches,
+                }) => {
+                    for branch in branches {
+                        self.record_depths(&branch.block.statements, depth);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
     fn inline_assignment(
-        &self,
+        &mut self,
         IntermediateAssignment {
             expression,
             register,
@@ -127,20 +221,30 @@ impl Inliner {
     ) -> (Vec<IntermediateStatement>, bool) {
         let mut should_continue = false;
         let mut statements = Vec::new();
+        let depth = self.depths.get(&register).copied().unwrap_or(0);
         let expression = match expression {
             IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
                 fn_: IntermediateValue::IntermediateMemory(IntermediateMemory { type_, register }),
                 args,
             }) if self.fn_defs.contains_key(&register) => {
                 match FnInst::get_root_fn(&self.fn_defs, &register) {
-                    Some(Left(lambda))
-                        if CodeSizeEstimator::estimate_size(&lambda).1 < self.size_limit =>
-                    {
+                    Some(Left(lambda)) if depth < self.max_depth && self.can_inline(&lambda) => {
+                        self.caller_size += CodeSizeEstimator::estimate_size(&lambda).1;
+                        self.inlined += 1;
                         let IntermediateBlock {
                             statements: extra_statements,
                             ret: value,
                         } = self.inline(lambda.clone(), args);
-                        statements = extra_statements;
+                        // Calls in the inlined body are one inline deeper and are inlined straight away whilst their depth is known.
+                        self.record_depths(&extra_statements, depth + 1);
+                        let is_recursive = self.recursive_fns.get(&lambda) == Some(&true);
+                        if is_recursive {
+                            self.enclosing_fns.push(lambda.clone());
+                        }
+                        (statements, _) = self.inline_statements(extra_statements);
+                        if is_recursive {
+                            self.enclosing_fns.pop();
+                        }
                         should_continue = true;
                         value.into()
                     }
@@ -160,12 +264,24 @@ impl Inliner {
                 if CodeSizeEstimator::estimate_size(&lambda).1 < self.size_limit =>
             {
                 // Inline lambda if it is below the size limit.
+                let is_recursive = self.recursive_fns.get(&lambda) == Some(&true);
+                let caller_size = std::mem::replace(
+                    &mut self.caller_size,
+                    CodeSizeEstimator::estimate_size(&lambda).1,
+                );
+                if is_recursive {
+                    self.enclosing_fns.push(lambda.clone());
+                }
                 let IntermediateLambda {
                     args,
                     block: IntermediateBlock { statements, ret },
                 } = lambda;
                 let (statements, internal_continue) = self.inline_statements(statements);
                 should_continue |= internal_continue;
+                if is_recursive {
+                    self.enclosing_fns.pop();
+                }
+                self.caller_size = caller_size;
                 IntermediateLambda {
                     args,
                     block: IntermediateBlock { statements, ret },
@@ -1017,7 +1133,7 @@ mod tests {
                 statements,
             },
         };
-        let (optimized, should_continue) = Inliner::inline_iteration(lambda, None);
+        let (optimized, should_continue, _) = Inliner::inline_iteration(lambda, None, None, None);
         assert_eq!(expect_continue, should_continue);
 
         let expected = IntermediateLambda {
@@ -1073,12 +1189,14 @@ mod tests {
         let types = vec![Rc::new(RefCell::new(
             IntermediateUnionType(vec![Some(AtomicTypeEnum::INT.into()), None]).into(),
         ))];
-        let optimized = Inliner::inline_up_to_size(
+        let (optimized, _) = Inliner::inline_up_to_size(
             IntermediateProgram {
                 main,
                 types: types.clone(),
             },
             None,
+            None,
+            None,
         );
         dbg!(&simplified, &optimized.main);
         ExpressionEqualityChecker::assert_equal(&optimized.main.into(), &simplified.into());
@@ -1127,12 +1245,14 @@ mod tests {
         let types = vec![Rc::new(RefCell::new(
             IntermediateUnionType(vec![Some(AtomicTypeEnum::INT.into()), None]).into(),
         ))];
-        let optimized = Inliner::inline_up_to_size(
+        let (optimized, _) = Inliner::inline_up_to_size(
             IntermediateProgram {
                 main: main.clone(),
                 types: types.clone(),
             },
             Some(1),
+            None,
+            None,
         );
         dbg!(&main, &optimized.main);
         ExpressionEqualityChecker::assert_equal(&optimized.main.into(), &main.into());
@@ -1263,7 +1383,7 @@ mod tests {
         };
         for _ in 1..5 {
             let size = CodeSizeEstimator::estimate_size(&program.main);
-            program = Inliner::inline_up_to_size(program, Some(size.1));
+            (program, _) = Inliner::inline_up_to_size(program, Some(size.1), None, None);
             assert!(program.main.find_open_vars().is_empty());
         }
     }
@@ -1367,12 +1487,14 @@ mod tests {
             },
         };
         let current_size = CodeSizeEstimator::estimate_size(&recursive).1;
-        let optimized = Inliner::inline_up_to_size(
+        let (optimized, _) = Inliner::inline_up_to_size(
             IntermediateProgram {
                 main,
                 types: Vec::new(),
             },
             Some(current_size * 10),
+            None,
+            None,
         );
         dbg!(&optimized);
         let optimized_size = CodeSizeEstimator::estimate_size(&optimized.main).1;
@@ -1443,6 +1565,8 @@ mod tests {
                 types: Vec::new(),
             },
             Some(current_size * 10),
+            None,
+            None,
         );
 
         Inliner::inline_up_to_size(
@@ -1451,6 +1575,283 @@ mod tests {
                 types: Vec::new(),
             },
             None,
+            None,
+            None,
         );
     }
+
+    /// Build `main = () -> int { helper = (x: int) -> int { x + ... + x }; helper(1) }` with `n` additions and return it with `helper`.
+    fn helper_program(n: usize) -> (IntermediateLambda, IntermediateLambda) {
+        let helper = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        )));
+        let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let sums = (0..n)
+            .map(|_| IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT)))
+            .collect_vec();
+        let statements = sums
+            .iter()
+            .enumerate()
+            .map(|(i, sum)| {
+                let previous = if i == 0 {
+                    IntermediateValue::from(arg.clone())
+                } else {
+                    sums[i - 1].clone().into()
+                };
+                IntermediateAssignment {
+                    register: sum.register.clone(),
+                    expression: IntermediateFnCall {
+                        fn_: BuiltInFn(
+                            Id::from("+"),
+                            IntermediateFnType(
+                                vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                                Box::new(AtomicTypeEnum::INT.into()),
+                            ),
+                        )
+                        .into(),
+                        args: vec![previous, arg.clone().into()],
+                    }
+                    .into(),
+                }
+                .into()
+            })
+            .collect_vec();
+        let helper_lambda = IntermediateLambda {
+            args: vec![arg.clone()],
+            block: IntermediateBlock {
+                statements,
+                ret: sums.last().unwrap().clone().into(),
+            },
+        };
+        let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let main = IntermediateLambda {
+            args: Vec::new(),
+            block: IntermediateBlock {
+                statements: vec![
+                    IntermediateAssignment {
+                        register: helper.register.clone(),
+                        expression: helper_lambda.clone().into(),
+                    }
+                    .into(),
+                    IntermediateAssignment {
+                        register: call.register.clone(),
+                        expression: IntermediateFnCall {
+                            fn_: helper.clone().into(),
+                            args: vec![Integer { value: 1 }.into()],
+                        }
+                        .into(),
+                    }
+                    .into(),
+                ],
+                ret: call.clone().into(),
+            },
+        };
+        (main, helper_lambda)
+    }
+
+    #[test_case(1, true; "small helper")]
+    #[test_case(20, false; "large helper")]
+    fn test_budget_limited_inlining(additions: usize, inlined: bool) {
+        let (main, _) = helper_program(additions);
+        // Allow helpers with up to four additions to be inlined.
+        let budget = CodeSizeEstimator::estimate_size(&main).1
+            + CodeSizeEstimator::estimate_size(&helper_program(4).1).1;
+        let (optimized, _) = Inliner::inline_up_to_size(
+            IntermediateProgram {
+                main: main.clone(),
+                types: Vec::new(),
+            },
+            None,
+            Some(budget),
+            None,
+        );
+        dbg!(&main, &optimized.main);
+        let has_call = optimized.main.block.statements.iter().any(|statement| {
+            matches!(
+                statement,
+                IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                    expression: IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                        fn_: IntermediateValue::IntermediateMemory(_),
+                        args: _
+                    }),
+                    register: _
+                })
+            )
+        });
+        assert_eq!(has_call, !inlined);
+        if !inlined {
+            ExpressionEqualityChecker::assert_equal(&optimized.main.into(), &main.into());
+        }
+    }
+
+    #[test]
+    fn test_recursive_fn_not_inlined_into_itself() {
+        let recursive_fn = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        )));
+        let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let ret = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let recursive = IntermediateLambda {
+            args: vec![arg.clone()],
+            block: IntermediateBlock {
+                statements: vec![
+                    IntermediateAssignment {
+                        register: call.register.clone(),
+                        expression: IntermediateFnCall {
+                            fn_: recursive_fn.clone().into(),
+                            args: vec![arg.clone().into()],
+                        }
+                        .into(),
+                    }
+                    .into(),
+                    IntermediateAssignment {
+                        register: ret.register.clone(),
+                        expression: IntermediateFnCall {
+                            fn_: BuiltInFn(
+                                Id::from("+"),
+                                IntermediateFnType(
+                                    vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                                    Box::new(AtomicTypeEnum::INT.into()),
+                                ),
+                            )
+                            .into(),
+                            args: vec![call.clone().into(), arg.clone().into()],
+                        }
+                        .into(),
+                    }
+                    .into(),
+                ],
+                ret: ret.clone().into(),
+            },
+        };
+        let main = IntermediateLambda {
+            args: Vec::new(),
+            block: IntermediateBlock {
+                statements: vec![IntermediateAssignment {
+                    register: recursive_fn.register.clone(),
+                    expression: recursive.clone().into(),
+                }
+                .into()],
+                ret: recursive_fn.clone().into(),
+            },
+        };
+        let (optimized, _) = Inliner::inline_up_to_size(
+            IntermediateProgram {
+                main: main.clone(),
+                types: Vec::new(),
+            },
+            None,
+            None,
+            None,
+        );
+        dbg!(&main, &optimized.main);
+        ExpressionEqualityChecker::assert_equal(&optimized.main.into(), &main.into());
+    }
+
+    #[test_case(1, 1; "direct callee")]
+    #[test_case(2, 0; "nested callee")]
+    fn test_depth_limited_inlining(max_inline_depth: usize, remaining_calls: usize) {
+        let fn_type = IntermediateType::from(IntermediateFnType(
+            Vec::new(),
+            Box::new(AtomicTypeEnum::INT.into()),
+        ));
+        let [a, b, c] = [(); 3].map(|_| IntermediateMemory::from(fn_type.clone()));
+        // `callee = () -> int { result = callee(); result }`
+        let calling = |callee: &IntermediateMemory| {
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![IntermediateAssignment {
+                        register: result.register.clone(),
+                        expression: IntermediateFnCall {
+                            fn_: callee.clone().into(),
+                            args: Vec::new(),
+                        }
+                        .into(),
+                    }
+                    .into()],
+                    ret: result.into(),
+                },
+            }
+        };
+        let c_lambda = IntermediateLambda {
+            args: Vec::new(),
+            block: IntermediateBlock {
+                statements: Vec::new(),
+                ret: Integer { value: 1 }.into(),
+            },
+        };
+        let main = IntermediateLambda {
+            args: Vec::new(),
+            block: IntermediateBlock {
+                statements: vec![
+                    IntermediateAssignment {
+                        register: c.register.clone(),
+                        expression: c_lambda.into(),
+                    }
+                    .into(),
+                    IntermediateAssignment {
+                        register: b.register.clone(),
+                        expression: calling(&c).into(),
+                    }
+                    .into(),
+                    IntermediateAssignment {
+                        register: a.register.clone(),
+                        expression: calling(&b).into(),
+                    }
+                    .into(),
+                ],
+                ret: a.clone().into(),
+            },
+        };
+        let (optimized, _) = Inliner::inline_up_to_size(
+            IntermediateProgram {
+                main,
+                types: Vec::new(),
+            },
+            None,
+            None,
+            Some(max_inline_depth),
+        );
+        dbg!(&optimized.main);
+        // Inlining renames registers, so `a` is found through the value that `main` returns.
+        let IntermediateValue::IntermediateMemory(a) = &optimized.main.block.ret else {
+            panic!("`main` does not return `a`.")
+        };
+        let Some(IntermediateExpression::IntermediateLambda(a_lambda)) = optimized
+            .main
+            .block
+            .statements
+            .iter()
+            .find_map(|statement| {
+                let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                    expression,
+                    register,
+                }) = statement;
+                (register == &a.register).then_some(expression)
+            })
+        else {
+            panic!("`a` is no longer defined.")
+        };
+        // The call to `b` is always inlined, which exposes the call to `c`.
+        let calls = a_lambda
+            .block
+            .statements
+            .iter()
+            .filter(|statement| {
+                matches!(
+                    statement,
+                    IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                        expression: IntermediateExpression::IntermediateFnCall(_),
+                        register: _,
+                    })
+                )
+            })
+            .count();
+        assert_eq!(calls, remaining_calls);
+    }
 }
@@ -1,11 +1,26 @@
 #![feature(cmp_minmax)]
 
 mod args;
+mod branch_elimination;
+mod common_subexpression_elimination;
+mod comparison_lint;
+mod constant_folding;
 mod dead_code_analysis;
+mod division_check;
+mod fma_fusion;
 mod inlining;
+mod newtype_elimination;
 mod optimizer;
 mod redundancy_elimination;
 mod refresher;
+mod stats;
+mod strength_reduction;
+#[cfg(test)]
+mod test_utils;
+mod unreachable_code_elimination;
 
 pub use args::OptimizationArgs;
+pub use comparison_lint::{ComparisonLinter, ConstantComparison};
+pub use division_check::{DivisionByZero, DivisionChecker};
 pub use optimizer::Optimizer;
+pub use stats::OptimizationStats;
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use lowering::{
+    IntermediateAssignment, IntermediateBlock, IntermediateCtorCall, IntermediateExpression,
+    IntermediateIf, IntermediateLambda, IntermediateMatch, IntermediateMatchBranch,
+    IntermediateMemory, IntermediateProgram, IntermediateStatement, IntermediateUnionType,
+    IntermediateValue, Register,
+};
+
+use crate::refresher::Refresher;
+
+type Payloads = HashMap<Register, Option<IntermediateValue>>;
+
+/// Remove round-trips through single-variant union types.
+pub struct NewtypeEliminator {
+    payloads: Payloads,
+}
+
+impl NewtypeEliminator {
+    fn new() -> Self {
+        NewtypeEliminator {
+            payloads: Payloads::new(),
+        }
+    }
+    /// Record the data stored in all single-variant constructor calls.
+    fn collect_payloads(&mut self, statements: &Vec<IntermediateStatement>) {
+        for statement in statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            match expression {
+                IntermediateExpression::IntermediateCtorCall(IntermediateCtorCall {
+                    idx: _,
+                    data,
+                    type_: IntermediateUnionType(variants),
+                }) if variants.len() == 1 => {
+                    self.payloads.insert(register.clone(), data.clone());
+                }
+                IntermediateExpression::IntermediateLambda(lambda) => {
+                    self.collect_payloads(&lambda.block.statements);
+                }
+                IntermediateExpression::IntermediateIf(if_) => {
+                    self.collect_payloads(&if_.branches.0.statements);
+                    self.collect_payloads(&if_.branches.1.statements);
+                }
+                IntermediateExpression::IntermediateMatch(match_) => {
+                    for branch in &match_.branches {
+                        self.collect_payloads(&branch.block.statements);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    fn eliminate_from_block(&self, block: IntermediateBlock) -> IntermediateBlock {
+        let IntermediateBlock { statements, ret } = block;
+        IntermediateBlock {
+            statements: statements
+                .into_iter()
+                .flat_map(|statement| self.eliminate_from_statement(statement))
+                .collect(),
+            ret,
+        }
+    }
+    fn eliminate_from_statement(
+        &self,
+        statement: IntermediateStatement,
+    ) -> Vec<IntermediateStatement> {
+        let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            expression,
+            register,
+        }) = statement;
+        let expression = match expression {
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject:
+                    IntermediateValue::IntermediateMemory(IntermediateMemory {
+                        type_: _,
+                        register: subject,
+                    }),
+                branches,
+            }) if self.payloads.contains_key(&subject) && branches.len() == 1 => {
+                let IntermediateMatchBranch { target, block } =
+                    branches.into_iter().exactly_one().unwrap();
+                // Treat the branch as a fn of its target so that it can be inlined.
+                let mut lambda = IntermediateLambda {
+                    args: target.into_iter().collect(),
+                    block: self.eliminate_from_block(block),
+                };
+                Refresher::refresh_for_inlining(&mut lambda);
+                let IntermediateLambda {
+                    args,
+                    block: IntermediateBlock { statements, ret },
+                } = lambda;
+                return args
+                    .into_iter()
+                    .zip(self.payloads[&subject].clone())
+                    .map(|(arg, value)| {
+                        IntermediateAssignment {
+                            register: arg.register,
+                            expression: value.into(),
+                        }
+                        .into()
+                    })
+                    .chain(statements)
+                    .chain([IntermediateAssignment {
+                        register,
+                        expression: ret.into(),
+                    }
+                    .into()])
+                    .collect_vec();
+            }
+            IntermediateExpression::IntermediateLambda(IntermediateLambda { args, block }) => {
+                IntermediateLambda {
+                    args,
+                    block: self.eliminate_from_block(block),
+                }
+                .into()
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => IntermediateIf {
+                condition,
+                branches: (
+                    self.eliminate_from_block(branches.0),
+                    self.eliminate_from_block(branches.1),
+                ),
+            }
+            .into(),
+            IntermediateExpression::IntermediateMatch(IntermediateMatch { subject, branches }) => {
+                IntermediateMatch {
+                    subject,
+                    branches: branches
+                        .into_iter()
+                        .map(
+                            |IntermediateMatchBranch { target, block }| IntermediateMatchBranch {
+                                target,
+                                block: self.eliminate_from_block(block),
+                            },
+                        )
+                        .collect(),
+                }
+                .into()
+            }
+            expression => expression,
+        };
+        vec![IntermediateAssignment {
+            expression,
+            register,
+        }
+        .into()]
+    }
+    /// Replace matches on locally-constructed single-variant unions with their payload.
+    pub fn eliminate_newtypes(program: IntermediateProgram) -> IntermediateProgram {
+        let IntermediateProgram { main, types } = program;
+        let mut eliminator = NewtypeEliminator::new();
+        eliminator.collect_payloads(&main.block.statements);
+        let IntermediateLambda { args, block } = main;
+        IntermediateProgram {
+            main: IntermediateLambda {
+                args,
+                block: eliminator.eliminate_from_block(block),
+            },
+            types,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    use crate::{
+        dead_code_analysis::DeadCodeAnalyzer, redundancy_elimination::RedundancyEliminator,
+    };
+
+    use lowering::{
+        AtomicTypeEnum, ExpressionEqualityChecker, Integer, IntermediateArg, IntermediateBuiltIn,
+        IntermediateType,
+    };
+    use test_case::test_case;
+
+    #[test_case(
+        {
+            let opaque_int = IntermediateUnionType(vec![Some(AtomicTypeEnum::INT.into())]);
+            let value = IntermediateMemory::from(IntermediateType::from(opaque_int.clone()));
+            let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            (
+                IntermediateProgram {
+                    main: IntermediateLambda {
+                        args: Vec::new(),
+                        block: IntermediateBlock {
+                            statements: vec![
+                                IntermediateAssignment {
+                                    register: value.register.clone(),
+                                    expression: IntermediateCtorCall {
+                                        idx: 0,
+                                        data: Some(IntermediateBuiltIn::from(Integer{value: 5}).into()),
+                                        type_: opaque_int.clone(),
+                                    }.into()
+                                }.into(),
+                                IntermediateAssignment {
+                                    register: result.register.clone(),
+                                    expression: IntermediateMatch {
+                                        subject: value.clone().into(),
+                                        branches: vec![
+                                            IntermediateMatchBranch {
+                                                target: Some(x.clone()),
+                                                block: IntermediateBlock {
+                                                    statements: Vec::new(),
+                                                    ret: x.clone().into()
+                                                }
+                                            }
+                                        ]
+                                    }.into()
+                                }.into(),
+                            ],
+                            ret: result.clone().into()
+                        }
+                    },
+                    types: vec![Rc::new(RefCell::new(opaque_int.into()))]
+                },
+                IntermediateLambda {
+                    args: Vec::new(),
+                    block: IntermediateBlock {
+                        statements: Vec::new(),
+                        ret: IntermediateBuiltIn::from(Integer{value: 5}).into()
+                    }
+                }
+            )
+        };
+        "opaque int round trip"
+    )]
+    #[test_case(
+        {
+            let either = IntermediateUnionType(vec![
+                Some(AtomicTypeEnum::INT.into()),
+                Some(AtomicTypeEnum::INT.into()),
+            ]);
+            let value = IntermediateMemory::from(IntermediateType::from(either.clone()));
+            let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let y = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            let main = IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: value.register.clone(),
+                            expression: IntermediateCtorCall {
+                                idx: 1,
+                                data: Some(IntermediateBuiltIn::from(Integer{value: 5}).into()),
+                                type_: either.clone(),
+                            }.into()
+                        }.into(),
+                        IntermediateAssignment {
+                            register: result.register.clone(),
+                            expression: IntermediateMatch {
+                                subject: value.clone().into(),
+                                branches: vec![
+                                    IntermediateMatchBranch {
+                                        target: Some(x.clone()),
+                                        block: IntermediateBlock {
+                                            statements: Vec::new(),
+                                            ret: x.clone().into()
+                                        }
+                                    },
+                                    IntermediateMatchBranch {
+                                        target: Some(y.clone()),
+                                        block: IntermediateBlock {
+                                            statements: Vec::new(),
+                                            ret: y.clone().into()
+                                        }
+                                    },
+                                ]
+                            }.into()
+                        }.into(),
+                    ],
+                    ret: result.clone().into()
+                }
+            };
+            (
+                IntermediateProgram {
+                    main: main.clone(),
+                    types: vec![Rc::new(RefCell::new(either.into()))]
+                },
+                main
+            )
+        };
+        "multiple variants"
+    )]
+    fn test_eliminate_newtypes(program_expected: (IntermediateProgram, IntermediateLambda)) {
+        let (program, expected_main) = program_expected;
+        let program = NewtypeEliminator::eliminate_newtypes(program);
+        let (program, _) = DeadCodeAnalyzer::remove_dead_code(program);
+        let (program, _) = RedundancyEliminator::eliminate_redundancy(program);
+        ExpressionEqualityChecker::assert_equal(&program.main.into(), &expected_main.into())
+    }
+}
@@ -1,28 +1,504 @@
-use lowering::IntermediateProgram;
+use std::time::{Duration, Instant};
+
+use lowering::{ExpressionEqualityChecker, IntermediateProgram};
 
 use crate::{
-    args::OptimizationArgs, dead_code_analysis::DeadCodeAnalyzer, inlining::Inliner,
-    redundancy_elimination::RedundancyEliminator,
+    args::OptimizationArgs, branch_elimination::BranchEliminator,
+    common_subexpression_elimination::CommonSubexpressionEliminator,
+    comparison_lint::ComparisonLinter, constant_folding::ConstantFolder,
+    dead_code_analysis::DeadCodeAnalyzer, fma_fusion::FmaFuser, inlining::Inliner,
+    newtype_elimination::NewtypeEliminator, redundancy_elimination::RedundancyEliminator,
+    stats::OptimizationStats, strength_reduction::StrengthReducer,
+    unreachable_code_elimination::UnreachableCodeEliminator,
 };
 
 pub struct Optimizer {}
 
 impl Optimizer {
-    pub fn optimize(
+    /// Repeatedly optimize the program until it reaches a fixpoint or the iteration limit.
+    pub fn optimize(program: IntermediateProgram, args: OptimizationArgs) -> IntermediateProgram {
+        Self::optimize_with_stats(program, args).0
+    }
+    /// Optimize the program and count the changes made by each pass.
+    pub fn optimize_with_stats(
+        program: IntermediateProgram,
+        args: OptimizationArgs,
+    ) -> (IntermediateProgram, OptimizationStats) {
+        Self::optimize_with_timings(program, args, |_, _| {})
+    }
+    /// Optimize the program and call `on_pass` with the name and duration of each pass that runs.
+    pub fn optimize_with_timings(
         mut program: IntermediateProgram,
         args: OptimizationArgs,
+        mut on_pass: impl FnMut(&'static str, Duration),
+    ) -> (IntermediateProgram, OptimizationStats) {
+        let mut stats = OptimizationStats::default();
+        for iteration in 0..args.max_iterations {
+            let previous = program.main.clone();
+            // Inline depths are lost when registers are renamed, so only the first iteration inlines to keep call chains within `max_inline_depth`.
+            let inline = iteration == 0;
+            program = Self::optimize_once(program, &args, inline, &mut stats, &mut on_pass);
+            // Compare structurally because passes may rename registers without changing the program.
+            if ExpressionEqualityChecker::equal(&previous.into(), &program.main.clone().into()) {
+                break;
+            }
+        }
+        (program, stats)
+    }
+    /// Run a single pass and report how long it took.
+    fn timed<T>(
+        name: &'static str,
+        on_pass: &mut impl FnMut(&'static str, Duration),
+        pass: impl FnOnce() -> T,
+    ) -> T {
+        let start = Instant::now();
+        let result = pass();
+        on_pass(name, start.elapsed());
+        result
+    }
+    fn optimize_once(
+        mut program: IntermediateProgram,
+        args: &OptimizationArgs,
+        inline: bool,
+        stats: &mut OptimizationStats,
+        on_pass: &mut impl FnMut(&'static str, Duration),
     ) -> IntermediateProgram {
+        if !args.newtype_elimination_args.no_newtype_elimination {
+            program = Self::timed("newtype elimination", on_pass, || {
+                NewtypeEliminator::eliminate_newtypes(program)
+            });
+        }
+        if !args.constant_folding_args.no_constant_folding {
+            program = Self::timed("constant folding", on_pass, || {
+                ConstantFolder::fold_constants(program)
+            });
+        }
+        if !args.comparison_folding_args.no_comparison_folding {
+            program = Self::timed("comparison folding", on_pass, || {
+                ComparisonLinter::fold_comparisons(program).0
+            });
+        }
+        if !args.strength_reduction_args.no_strength_reduction {
+            program = Self::timed("strength reduction", on_pass, || {
+                StrengthReducer::reduce_strength(program)
+            });
+        }
+        // Fuse after strength reduction so that multiplications by powers of two become shifts.
+        if !args.fma_fusion_args.no_fma_fusion {
+            program = Self::timed("fma fusion", on_pass, || {
+                FmaFuser::fuse_multiply_adds(program)
+            });
+        }
+        if !args.branch_elimination_args.no_branch_elimination {
+            program = Self::timed("branch elimination", on_pass, || {
+                BranchEliminator::eliminate_branches(program)
+            });
+        }
+        if !args
+            .unreachable_code_elimination_args
+            .no_unreachable_code_elimination
+        {
+            program = Self::timed("unreachable code elimination", on_pass, || {
+                UnreachableCodeEliminator::eliminate_unreachable_code(program)
+            });
+        }
         if !args.dead_code_analysis_args.no_dead_code_analysis {
-            program = DeadCodeAnalyzer::remove_dead_code(program);
-            program = DeadCodeAnalyzer::remove_dead_code(program);
+            for _ in 0..2 {
+                let removed;
+                (program, removed) = Self::timed("dead code analysis", on_pass, || {
+                    DeadCodeAnalyzer::remove_dead_code(program)
+                });
+                stats.statements_removed += removed;
+            }
+        }
+        if !args
+            .common_subexpression_elimination_args
+            .no_common_subexpression_elimination
+        {
+            program = Self::timed("common subexpression elimination", on_pass, || {
+                CommonSubexpressionEliminator::eliminate_common_subexpressions(program)
+            });
         }
         if !args
             .equivalent_elimination_args
             .no_equivalent_expression_elimination
         {
-            program = RedundancyEliminator::eliminate_redundancy(program);
+            let eliminated;
+            (program, eliminated) = Self::timed("redundancy elimination", on_pass, || {
+                RedundancyEliminator::eliminate_redundancy(program)
+            });
+            stats.redundancies_eliminated += eliminated;
+        }
+        if inline {
+            let inlined;
+            (program, inlined) = Self::timed("inlining", on_pass, || {
+                Inliner::inline_up_to_size(
+                    program,
+                    Some(args.inlining_args.inlining_depth),
+                    args.inlining_args.inline_budget,
+                    Some(args.inlining_args.max_inline_depth),
+                )
+            });
+            stats.fns_inlined += inlined;
+        }
+        if !args.constant_folding_args.no_constant_folding {
+            // Inlining exposes more constant arguments.
+            program = Self::timed("constant folding", on_pass, || {
+                ConstantFolder::fold_constants(program)
+            });
+        }
+        if !args.branch_elimination_args.no_branch_elimination {
+            // Inlining exposes more constant conditions and constructors.
+            program = Self::timed("branch elimination", on_pass, || {
+                BranchEliminator::eliminate_branches(program)
+            });
         }
-        program = Inliner::inline_up_to_size(program, Some(args.inlining_args.inlining_depth));
         program
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::Parser;
+    use lowering::{
+        AtomicTypeEnum, Boolean, BuiltInFn, Id, Integer, IntermediateArg, IntermediateAssignment,
+        IntermediateBlock, IntermediateExpression, IntermediateFnCall, IntermediateFnType,
+        IntermediateIf, IntermediateLambda, IntermediateMemory, IntermediateStatement,
+        IntermediateType, IntermediateValue, Interpreter, Value,
+    };
+    use test_case::test_case;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        optimization_args: OptimizationArgs,
+    }
+
+    /// `sign = (x: int) -> int { if x < 0 { -1 } else { 1 } }; main = () -> int { sign(5) }`
+    fn sign_program() -> IntermediateProgram {
+        let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let negative = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+        let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let fn_type = IntermediateFnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        );
+        let sign = IntermediateMemory::from(IntermediateType::from(fn_type));
+        let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: sign.register.clone(),
+                            expression: IntermediateLambda {
+                                args: vec![x.clone()],
+                                block: IntermediateBlock {
+                                    statements: vec![
+                                        IntermediateAssignment {
+                                            register: negative.register.clone(),
+                                            expression: IntermediateFnCall {
+                                                fn_: BuiltInFn(
+                                                    Id::from("<"),
+                                                    IntermediateFnType(
+                                                        vec![
+                                                            AtomicTypeEnum::INT.into(),
+                                                            AtomicTypeEnum::INT.into(),
+                                                        ],
+                                                        Box::new(AtomicTypeEnum::BOOL.into()),
+                                                    ),
+                                                )
+                                                .into(),
+                                                args: vec![
+                                                    x.clone().into(),
+                                                    Integer { value: 0 }.into(),
+                                                ],
+                                            }
+                                            .into(),
+                                        }
+                                        .into(),
+                                        IntermediateAssignment {
+                                            register: result.register.clone(),
+                                            expression: IntermediateIf {
+                                                condition: negative.clone().into(),
+                                                branches: (
+                                                    IntermediateValue::from(Integer { value: -1 })
+                                                        .into(),
+                                                    IntermediateValue::from(Integer { value: 1 })
+                                                        .into(),
+                                                ),
+                                            }
+                                            .into(),
+                                        }
+                                        .into(),
+                                    ],
+                                    ret: result.clone().into(),
+                                },
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: call.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: sign.clone().into(),
+                                args: vec![Integer { value: 5 }.into()],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: call.clone().into(),
+                },
+            },
+            types: Vec::new(),
+        }
+    }
+
+    #[test_case(
+        1,
+        {
+            let negative = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: negative.register.clone(),
+                            expression: IntermediateValue::from(Boolean { value: false }).into(),
+                        }.into(),
+                        IntermediateAssignment {
+                            register: result.register.clone(),
+                            expression: IntermediateValue::from(Integer { value: 1 }).into(),
+                        }.into(),
+                    ],
+                    ret: result.into(),
+                },
+            }
+        };
+        "single iteration"
+    )]
+    #[test_case(
+        4,
+        IntermediateLambda {
+            args: Vec::new(),
+            block: IntermediateBlock {
+                statements: Vec::new(),
+                ret: Integer { value: 1 }.into(),
+            },
+        };
+        "fixpoint"
+    )]
+    fn test_optimization_iterations(max_iterations: usize, expected_main: IntermediateLambda) {
+        let args = Cli::parse_from(["optimizer", "--max-iterations", &max_iterations.to_string()])
+            .optimization_args;
+        let program = Optimizer::optimize(sign_program(), args);
+        // Constants exposed by inlining are only removed by a later round of dead code analysis.
+        ExpressionEqualityChecker::assert_equal(&program.main.into(), &expected_main.into());
+    }
+
+    /// `main = () -> int { dead = 3; id = (x: int) -> int { x }; id(5) }`
+    fn dead_binding_program() -> IntermediateProgram {
+        let dead = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let id = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        )));
+        let call = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: dead.register.clone(),
+                            expression: IntermediateValue::from(Integer { value: 3 }).into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: id.register.clone(),
+                            expression: IntermediateLambda {
+                                args: vec![x.clone()],
+                                block: IntermediateBlock {
+                                    statements: Vec::new(),
+                                    ret: x.into(),
+                                },
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: call.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: id.into(),
+                                args: vec![Integer { value: 5 }.into()],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: call.into(),
+                },
+            },
+            types: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_optimization_stats() {
+        let args = Cli::parse_from(["optimizer", "--max-iterations", "1"]).optimization_args;
+        let (_, stats) = Optimizer::optimize_with_stats(dead_binding_program(), args);
+        assert_eq!(
+            stats,
+            OptimizationStats {
+                statements_removed: 1,
+                fns_inlined: 1,
+                redundancies_eliminated: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_optimization_timings() {
+        let args = Cli::parse_from([
+            "optimizer",
+            "--max-iterations",
+            "1",
+            "--no-constant-folding",
+            "--no-comparison-folding",
+            "--no-strength-reduction",
+            "--no-fma-fusion",
+        ])
+        .optimization_args;
+        let mut passes = Vec::new();
+        Optimizer::optimize_with_timings(dead_binding_program(), args, |name, _| passes.push(name));
+        assert_eq!(
+            passes,
+            vec![
+                "newtype elimination",
+                "branch elimination",
+                "unreachable code elimination",
+                "dead code analysis",
+                "dead code analysis",
+                "common subexpression elimination",
+                "redundancy elimination",
+                "inlining",
+                "branch elimination",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_depth_preserved_across_iterations() {
+        let fn_type = IntermediateType::from(IntermediateFnType(
+            Vec::new(),
+            Box::new(AtomicTypeEnum::INT.into()),
+        ));
+        let [a, b, c] = [(); 3].map(|_| IntermediateMemory::from(fn_type.clone()));
+        // `callee = () -> int { result = callee(); result }`
+        let calling = |callee: &IntermediateMemory| {
+            let result = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+            IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![IntermediateAssignment {
+                        register: result.register.clone(),
+                        expression: IntermediateFnCall {
+                            fn_: callee.clone().into(),
+                            args: Vec::new(),
+                        }
+                        .into(),
+                    }
+                    .into()],
+                    ret: result.into(),
+                },
+            }
+        };
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: c.register.clone(),
+                            expression: IntermediateLambda {
+                                args: Vec::new(),
+                                block: IntermediateBlock {
+                                    statements: Vec::new(),
+                                    ret: Integer { value: 1 }.into(),
+                                },
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: b.register.clone(),
+                            expression: calling(&c).into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: a.register.clone(),
+                            expression: calling(&b).into(),
+                        }
+                        .into(),
+                    ],
+                    ret: a.into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        let args = Cli::parse_from([
+            "optimizer",
+            "--max-iterations",
+            "2",
+            "--max-inline-depth",
+            "1",
+        ])
+        .optimization_args;
+        let optimized = Optimizer::optimize(program, args);
+        dbg!(&optimized.main);
+        let IntermediateValue::IntermediateMemory(a) = &optimized.main.block.ret else {
+            panic!("`main` does not return `a`.")
+        };
+        let Some(IntermediateExpression::IntermediateLambda(a_lambda)) = optimized
+            .main
+            .block
+            .statements
+            .iter()
+            .find_map(|statement| {
+                let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                    expression,
+                    register,
+                }) = statement;
+                (register == &a.register).then_some(expression)
+            })
+        else {
+            panic!("`a` is no longer defined.")
+        };
+        // The second iteration must not inline the call to `c` that the first one exposed.
+        assert!(matches!(
+            &a_lambda.block.statements[..],
+            [IntermediateStatement::IntermediateAssignment(
+                IntermediateAssignment {
+                    expression: IntermediateExpression::IntermediateFnCall(_),
+                    register: _,
+                }
+            )]
+        ));
+    }
+
+    #[test_case(sign_program, Integer { value: 1 }; "sign")]
+    #[test_case(dead_binding_program, Integer { value: 5 }; "dead binding")]
+    fn test_optimization_preserves_result(program: fn() -> IntermediateProgram, expected: Integer) {
+        let args = Cli::parse_from(["optimizer"]).optimization_args;
+        let original = Interpreter::run(&program(), Vec::new());
+        let optimized = Interpreter::run(&Optimizer::optimize(program(), args), Vec::new());
+        assert_eq!(original, Value::from(expected));
+        assert_eq!(optimized, original);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use lowering::{
     IntermediateArg, IntermediateAssignment, IntermediateBlock, IntermediateCtorCall,
@@ -56,17 +56,29 @@ impl Refresher {
     }
     fn refresh_statements(&mut self, statements: &mut Vec<IntermediateStatement>) {
         self.register_statements(statements);
+        let mut assigned = HashSet::new();
         for statement in statements {
-            self.refresh_statement(statement);
+            self.refresh_statement(statement, &mut assigned);
         }
     }
-    fn refresh_statement(&mut self, statement: &mut IntermediateStatement) {
+    fn refresh_statement(
+        &mut self,
+        statement: &mut IntermediateStatement,
+        assigned: &mut HashSet<Register>,
+    ) {
         match statement {
             IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
                 expression,
                 register,
             }) => {
                 self.refresh_expression(expression);
+                // Registers that are assigned multiple times (e.g. by inlining the same fn twice) need a new address for each assignment.
+                if !assigned.insert(register.clone()) {
+                    self.registers.insert(
+                        register.clone(),
+                        IntermediateMemory::from(expression.type_()).into(),
+                    );
+                }
                 if let Some(IntermediateValue::IntermediateMemory(memory)) =
                     self.refresh_register(register)
                 {
@@ -163,8 +175,8 @@ impl Refresher {
 mod tests {
     use itertools::Itertools;
     use lowering::{
-        AtomicTypeEnum, BuiltInFn, ExpressionEqualityChecker, Id, IntermediateBuiltIn,
-        IntermediateFnType, IntermediateLambda, IntermediateType,
+        AtomicTypeEnum, BuiltInFn, ExpressionEqualityChecker, Id, Integer, IntermediateBuiltIn,
+        IntermediateFnType, IntermediateLambda, IntermediateTupleType, IntermediateType,
     };
 
     use super::*;
@@ -290,4 +302,95 @@ mod tests {
         dbg!(&lambda, &refreshed);
         ExpressionEqualityChecker::assert_equal(&refreshed.into(), &lambda.into());
     }
+
+    /// Inline `x -> { a = x + 1; b = a * 2; b }` into `(y, z) -> (f(y), f(z))`, optionally using new registers for the second copy.
+    fn inlined_copies(fresh: bool) -> IntermediateLambda {
+        let int = || IntermediateType::from(AtomicTypeEnum::INT);
+        let operator = |id: &str| {
+            IntermediateValue::from(BuiltInFn(
+                Id::from(id),
+                IntermediateFnType(vec![int(), int()], Box::new(int())),
+            ))
+        };
+        let y = IntermediateArg::from(int());
+        let z = IntermediateArg::from(int());
+        let a = IntermediateMemory::from(int());
+        let b = IntermediateMemory::from(int());
+        let copy = |x: &IntermediateArg, a: &IntermediateMemory, b: &IntermediateMemory| {
+            vec![
+                IntermediateStatement::from(IntermediateAssignment {
+                    register: a.register.clone(),
+                    expression: IntermediateFnCall {
+                        fn_: operator("+"),
+                        args: vec![x.clone().into(), Integer { value: 1 }.into()],
+                    }
+                    .into(),
+                }),
+                IntermediateAssignment {
+                    register: b.register.clone(),
+                    expression: IntermediateFnCall {
+                        fn_: operator("*"),
+                        args: vec![a.clone().into(), Integer { value: 2 }.into()],
+                    }
+                    .into(),
+                }
+                .into(),
+            ]
+        };
+        let (a2, b2) = if fresh {
+            (
+                IntermediateMemory::from(int()),
+                IntermediateMemory::from(int()),
+            )
+        } else {
+            (a.clone(), b.clone())
+        };
+        let first = IntermediateMemory::from(int());
+        let result = IntermediateMemory::from(IntermediateType::from(IntermediateTupleType(vec![
+            int(),
+            int(),
+        ])));
+        let mut statements = copy(&y, &a, &b);
+        statements.push(
+            IntermediateAssignment {
+                register: first.register.clone(),
+                expression: IntermediateValue::from(b.clone()).into(),
+            }
+            .into(),
+        );
+        statements.extend(copy(&z, &a2, &b2));
+        statements.push(
+            IntermediateAssignment {
+                register: result.register.clone(),
+                expression: IntermediateTupleExpression(vec![first.into(), b2.into()]).into(),
+            }
+            .into(),
+        );
+        IntermediateLambda {
+            args: vec![y, z],
+            block: IntermediateBlock {
+                statements,
+                ret: result.into(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_refresh_inlined_copies() {
+        let mut lambda = inlined_copies(false);
+        let expected = inlined_copies(true);
+        for _ in 0..2 {
+            Refresher::refresh(&mut lambda);
+            ExpressionEqualityChecker::assert_equal(
+                &lambda.clone().into(),
+                &expected.clone().into(),
+            );
+            let targets = IntermediateExpression::from(lambda.clone()).targets();
+            let registers = targets
+                .iter()
+                .chain(lambda.args.iter().map(|arg| &arg.register))
+                .collect_vec();
+            assert_eq!(registers.iter().unique().count(), registers.len());
+        }
+    }
 }
@@ -0,0 +1,63 @@
+use lowering::{
+    IntermediateAssignment, IntermediateBlock, IntermediateExpression, IntermediateIf,
+    IntermediateMatch, IntermediateMatchBranch, IntermediateProgram, IntermediateStatement,
+};
+
+/// Counts of the changes made by the optimizer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptimizationStats {
+    /// Statements removed by dead code analysis.
+    pub statements_removed: usize,
+    /// Fn calls replaced with the body of the fn.
+    pub fns_inlined: usize,
+    /// Statements removed by redundancy elimination.
+    pub redundancies_eliminated: usize,
+}
+
+impl OptimizationStats {
+    /// Count the statements in a program, including those in nested fns and branches.
+    pub fn count_statements(program: &IntermediateProgram) -> usize {
+        Self::count_block_statements(&program.main.block)
+    }
+    fn count_block_statements(block: &IntermediateBlock) -> usize {
+        block
+            .statements
+            .iter()
+            .map(|statement| {
+                let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                    expression,
+                    register: _,
+                }) = statement;
+                1 + Self::count_expression_statements(expression)
+            })
+            .sum()
+    }
+    fn count_expression_statements(expression: &IntermediateExpression) -> usize {
+        match expression {
+            IntermediateExpression::IntermediateLambda(lambda) => {
+                Self::count_block_statements(&lambda.block)
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition: _,
+                branches,
+            }) => {
+                Self::count_block_statements(&branches.0)
+                    + Self::count_block_statements(&branches.1)
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => branches
+                .iter()
+                .map(|IntermediateMatchBranch { target: _, block }| {
+                    Self::count_block_statements(block)
+                })
+                .sum(),
+            IntermediateExpression::IntermediateValue(_)
+            | IntermediateExpression::IntermediateElementAccess(_)
+            | IntermediateExpression::IntermediateTupleExpression(_)
+            | IntermediateExpression::IntermediateFnCall(_)
+            | IntermediateExpression::IntermediateCtorCall(_) => 0,
+        }
+    }
+}
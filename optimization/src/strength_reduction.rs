@@ -0,0 +1,283 @@
+use std::collections::HashSet;
+
+use lowering::{
+    BuiltInFn, Id, Integer, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn,
+    IntermediateExpression, IntermediateFnCall, IntermediateIf, IntermediateMatch,
+    IntermediateMatchBranch, IntermediateMemory, IntermediateProgram, IntermediateStatement,
+    IntermediateValue, Register,
+};
+
+/// Replace multiplication, division and modulo by powers of two with bitwise operations.
+pub struct StrengthReducer {
+    non_negative: HashSet<Register>,
+}
+
+impl StrengthReducer {
+    fn new() -> Self {
+        StrengthReducer {
+            non_negative: HashSet::new(),
+        }
+    }
+    /// Find the exponent of a literal power of two greater than one.
+    fn power_of_two(value: &IntermediateValue) -> Option<u32> {
+        match value {
+            IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::Integer(Integer {
+                value,
+            })) if *value > 1 && value.count_ones() == 1 => Some(value.trailing_zeros()),
+            _ => None,
+        }
+    }
+    /// Determine whether a value is known to be at least zero.
+    fn is_non_negative(&self, value: &IntermediateValue) -> bool {
+        match value {
+            IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::Integer(Integer {
+                value,
+            })) => *value >= 0,
+            IntermediateValue::IntermediateMemory(IntermediateMemory { type_: _, register }) => {
+                self.non_negative.contains(register)
+            }
+            IntermediateValue::IntermediateBuiltIn(_) | IntermediateValue::IntermediateArg(_) => {
+                false
+            }
+        }
+    }
+    /// Determine whether a call to a built-in fn always returns a value that is at least zero.
+    fn returns_non_negative(&self, id: &Id, args: &[IntermediateValue]) -> bool {
+        let non_negative = |idx: usize| self.is_non_negative(&args[idx]);
+        match (id.as_str(), args.len()) {
+            ("len", 1) => true,
+            ("&" | "max", 2) => non_negative(0) || non_negative(1),
+            ("/" | "%" | "min", 2) => non_negative(0) && non_negative(1),
+            (">>", 2) => non_negative(0),
+            _ => false,
+        }
+    }
+    /// Find a cheaper built-in fn and arguments that compute the same result as a call.
+    fn reduce(&self, id: &Id, args: &[IntermediateValue]) -> Option<(Id, Vec<IntermediateValue>)> {
+        let shift = |value: &IntermediateValue, exponent: u32| {
+            vec![
+                value.clone(),
+                Integer {
+                    value: exponent as i64,
+                }
+                .into(),
+            ]
+        };
+        match (id.as_str(), args) {
+            ("*", [x, y]) => {
+                let (value, exponent) = match Self::power_of_two(y) {
+                    Some(exponent) => (x, exponent),
+                    None => (y, Self::power_of_two(x)?),
+                };
+                Some((Id::from("<<"), shift(value, exponent)))
+            }
+            // Negative numbers round towards zero, so these only hold for non-negative values.
+            ("/", [x, c]) if self.is_non_negative(x) => {
+                let exponent = Self::power_of_two(c)?;
+                Some((Id::from(">>"), shift(x, exponent)))
+            }
+            ("%", [x, c]) if self.is_non_negative(x) => {
+                let exponent = Self::power_of_two(c)?;
+                let mask = (1i64 << exponent) - 1;
+                Some((
+                    Id::from("&"),
+                    vec![x.clone(), Integer { value: mask }.into()],
+                ))
+            }
+            _ => None,
+        }
+    }
+    /// Reduce an expression, returning whether it is known to be non-negative.
+    fn reduce_expression(&mut self, expression: &mut IntermediateExpression) -> bool {
+        match expression {
+            IntermediateExpression::IntermediateValue(value) => self.is_non_negative(value),
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                fn_:
+                    IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                        id,
+                        _,
+                    ))),
+                args,
+            }) => {
+                if let Some((reduced_id, reduced_args)) = self.reduce(id, args) {
+                    *id = reduced_id;
+                    *args = reduced_args;
+                }
+                self.returns_non_negative(id, args)
+            }
+            IntermediateExpression::IntermediateLambda(lambda) => {
+                self.reduce_block(&mut lambda.block);
+                false
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition: _,
+                branches,
+            }) => {
+                self.reduce_block(&mut branches.0);
+                self.reduce_block(&mut branches.1);
+                false
+            }
+            IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                subject: _,
+                branches,
+            }) => {
+                for IntermediateMatchBranch { target: _, block } in branches {
+                    self.reduce_block(block);
+                }
+                false
+            }
+            IntermediateExpression::IntermediateElementAccess(_)
+            | IntermediateExpression::IntermediateTupleExpression(_)
+            | IntermediateExpression::IntermediateFnCall(_)
+            | IntermediateExpression::IntermediateCtorCall(_) => false,
+        }
+    }
+    fn reduce_block(&mut self, block: &mut IntermediateBlock) {
+        for statement in &mut block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            if self.reduce_expression(expression) {
+                self.non_negative.insert(register.clone());
+            }
+        }
+    }
+    /// Rewrite `x * 2^k` to `x << k`.
+    /// When `x` is non-negative, also rewrite `x / 2^k` to `x >> k` and `x % 2^k` to `x & (2^k - 1)`.
+    pub fn reduce_strength(program: IntermediateProgram) -> IntermediateProgram {
+        let IntermediateProgram { mut main, types } = program;
+        let mut reducer = StrengthReducer::new();
+        reducer.reduce_block(&mut main.block);
+        IntermediateProgram { main, types }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::call;
+    use lowering::{
+        AtomicTypeEnum, ExpressionEqualityChecker, IntermediateArg, IntermediateLambda,
+        IntermediateType,
+    };
+    use test_case::test_case;
+
+    /// `(a: int) -> int { x = a & 255; y = <expression>; y }`
+    fn masked_program(
+        arg: &IntermediateArg,
+        expression: impl Fn(IntermediateValue) -> IntermediateExpression,
+    ) -> IntermediateLambda {
+        let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        IntermediateLambda {
+            args: vec![arg.clone()],
+            block: IntermediateBlock {
+                statements: vec![
+                    IntermediateAssignment {
+                        register: x.register.clone(),
+                        expression: call(
+                            "&",
+                            vec![arg.clone().into(), Integer { value: 255 }.into()],
+                        ),
+                    }
+                    .into(),
+                    IntermediateAssignment {
+                        register: y.register.clone(),
+                        expression: expression(x.clone().into()),
+                    }
+                    .into(),
+                ],
+                ret: y.clone().into(),
+            },
+        }
+    }
+
+    #[test_case(
+        |x| call("*", vec![x, Integer { value: 8 }.into()]),
+        |x| call("<<", vec![x, Integer { value: 3 }.into()]);
+        "multiplication"
+    )]
+    #[test_case(
+        |x| call("*", vec![Integer { value: 4 }.into(), x]),
+        |x| call("<<", vec![x, Integer { value: 2 }.into()]);
+        "multiplication on the left"
+    )]
+    #[test_case(
+        |x| call("/", vec![x, Integer { value: 4 }.into()]),
+        |x| call(">>", vec![x, Integer { value: 2 }.into()]);
+        "division"
+    )]
+    #[test_case(
+        |x| call("%", vec![x, Integer { value: 16 }.into()]),
+        |x| call("&", vec![x, Integer { value: 15 }.into()]);
+        "modulo"
+    )]
+    #[test_case(
+        |x| call("*", vec![x, Integer { value: 6 }.into()]),
+        |x| call("*", vec![x, Integer { value: 6 }.into()]);
+        "non power of two"
+    )]
+    #[test_case(
+        |x| call("/", vec![x, Integer { value: 1 }.into()]),
+        |x| call("/", vec![x, Integer { value: 1 }.into()]);
+        "division by one"
+    )]
+    fn test_reduce_non_negative(
+        expression: fn(IntermediateValue) -> IntermediateExpression,
+        expected: fn(IntermediateValue) -> IntermediateExpression,
+    ) {
+        let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let program = StrengthReducer::reduce_strength(IntermediateProgram {
+            main: masked_program(&arg, expression),
+            types: Vec::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(
+            &program.main.into(),
+            &masked_program(&arg, expected).into(),
+        )
+    }
+
+    #[test_case(
+        |x| call("*", vec![x, Integer { value: 2 }.into()]),
+        |x| call("<<", vec![x, Integer { value: 1 }.into()]);
+        "multiplication"
+    )]
+    #[test_case(
+        |x| call("/", vec![x, Integer { value: 4 }.into()]),
+        |x| call("/", vec![x, Integer { value: 4 }.into()]);
+        "division"
+    )]
+    #[test_case(
+        |x| call("%", vec![x, Integer { value: 4 }.into()]),
+        |x| call("%", vec![x, Integer { value: 4 }.into()]);
+        "modulo"
+    )]
+    fn test_reduce_signed(
+        expression: fn(IntermediateValue) -> IntermediateExpression,
+        expected: fn(IntermediateValue) -> IntermediateExpression,
+    ) {
+        let arg = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let main = |expression: IntermediateExpression| IntermediateLambda {
+            args: vec![arg.clone()],
+            block: IntermediateBlock {
+                statements: vec![IntermediateAssignment {
+                    register: y.register.clone(),
+                    expression,
+                }
+                .into()],
+                ret: y.clone().into(),
+            },
+        };
+        let program = StrengthReducer::reduce_strength(IntermediateProgram {
+            main: main(expression(arg.clone().into())),
+            types: Vec::new(),
+        });
+        ExpressionEqualityChecker::assert_equal(
+            &program.main.into(),
+            &main(expected(arg.clone().into())).into(),
+        )
+    }
+}
@@ -0,0 +1,61 @@
+use lowering::{
+    AtomicTypeEnum, BuiltInFn, Id, IntermediateArg, IntermediateAssignment, IntermediateBlock,
+    IntermediateExpression, IntermediateFnCall, IntermediateFnType, IntermediateLambda,
+    IntermediateMemory, IntermediateProgram, IntermediateStatement, IntermediateValue,
+};
+
+/// Call the built-in `id` with `args`, returning a value of type `ret`.
+pub fn call_returning(
+    id: &str,
+    args: Vec<IntermediateValue>,
+    ret: AtomicTypeEnum,
+) -> IntermediateExpression {
+    IntermediateFnCall {
+        fn_: BuiltInFn(
+            Id::from(id),
+            IntermediateFnType(
+                args.iter().map(|arg| arg.type_()).collect(),
+                Box::new(ret.into()),
+            ),
+        )
+        .into(),
+        args,
+    }
+    .into()
+}
+
+/// Call the built-in `id` with `args`, returning a `bool` from comparisons and an `int` otherwise.
+pub fn call(id: &str, args: Vec<IntermediateValue>) -> IntermediateExpression {
+    let ret = match id {
+        "<" | "<=" | ">" | ">=" | "==" | "!=" => AtomicTypeEnum::BOOL,
+        _ => AtomicTypeEnum::INT,
+    };
+    call_returning(id, args, ret)
+}
+
+/// `memory = expression`
+pub fn assign(
+    memory: &IntermediateMemory,
+    expression: IntermediateExpression,
+) -> IntermediateStatement {
+    IntermediateAssignment {
+        register: memory.register.clone(),
+        expression,
+    }
+    .into()
+}
+
+/// A program without types whose `main` takes `args`, runs `statements` and returns `ret`.
+pub fn program(
+    args: Vec<IntermediateArg>,
+    statements: Vec<IntermediateStatement>,
+    ret: IntermediateValue,
+) -> IntermediateProgram {
+    IntermediateProgram {
+        main: IntermediateLambda {
+            args,
+            block: IntermediateBlock { statements, ret },
+        },
+        types: Vec::new(),
+    }
+}
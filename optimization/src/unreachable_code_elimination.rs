@@ -0,0 +1,185 @@
+use lowering::{
+    BuiltInFn, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn,
+    IntermediateExpression, IntermediateFnCall, IntermediateFnType, IntermediateIf,
+    IntermediateLambda, IntermediateMatch, IntermediateMatchBranch, IntermediateMemory,
+    IntermediateProgram, IntermediateStatement, IntermediateValue, DIVERGING_BUILT_INS,
+};
+
+/// Remove statements that follow a call to a built-in that never returns.
+pub struct UnreachableCodeEliminator {}
+
+impl UnreachableCodeEliminator {
+    /// Determine whether an expression is a call to a diverging built-in, such as `error`.
+    fn diverges(expression: &IntermediateExpression) -> bool {
+        matches!(
+            expression,
+            IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                fn_: IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(BuiltInFn(id, _))),
+                args: _,
+            }) if DIVERGING_BUILT_INS.contains(&id.as_str())
+        )
+    }
+    fn eliminate_from_block(block: IntermediateBlock) -> IntermediateBlock {
+        let IntermediateBlock {
+            statements: original_statements,
+            ret,
+        } = block;
+        let mut statements = Vec::new();
+        for statement in original_statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                expression,
+                register,
+            }) = statement;
+            let mut expression = Self::eliminate_from_expression(expression);
+            if Self::diverges(&expression) {
+                // Diverging built-ins are generic in their return type, so the call can stand in for the return value.
+                let type_ = ret.type_();
+                if let IntermediateExpression::IntermediateFnCall(IntermediateFnCall {
+                    fn_:
+                        IntermediateValue::IntermediateBuiltIn(IntermediateBuiltIn::BuiltInFn(
+                            BuiltInFn(_, IntermediateFnType(_, ret_type)),
+                        )),
+                    args: _,
+                }) = &mut expression
+                {
+                    **ret_type = type_.clone();
+                }
+                statements.push(
+                    IntermediateAssignment {
+                        register: register.clone(),
+                        expression,
+                    }
+                    .into(),
+                );
+                return IntermediateBlock {
+                    statements,
+                    ret: IntermediateMemory { type_, register }.into(),
+                };
+            }
+            statements.push(
+                IntermediateAssignment {
+                    register,
+                    expression,
+                }
+                .into(),
+            );
+        }
+        IntermediateBlock { statements, ret }
+    }
+    fn eliminate_from_expression(expression: IntermediateExpression) -> IntermediateExpression {
+        match expression {
+            IntermediateExpression::IntermediateLambda(IntermediateLambda { args, block }) => {
+                IntermediateLambda {
+                    args,
+                    block: Self::eliminate_from_block(block),
+                }
+                .into()
+            }
+            IntermediateExpression::IntermediateIf(IntermediateIf {
+                condition,
+                branches,
+            }) => IntermediateIf {
+                condition,
+                branches: (
+                    Self::eliminate_from_block(branches.0),
+                    Self::eliminate_from_block(branches.1),
+                ),
+            }
+            .into(),
+            IntermediateExpression::IntermediateMatch(IntermediateMatch { subject, branches }) => {
+                IntermediateMatch {
+                    subject,
+                    branches: branches
+                        .into_iter()
+                        .map(
+                            |IntermediateMatchBranch { target, block }| IntermediateMatchBranch {
+                                target,
+                                block: Self::eliminate_from_block(block),
+                            },
+                        )
+                        .collect(),
+                }
+                .into()
+            }
+            expression => expression,
+        }
+    }
+    /// Drop the statements after a diverging call in each block and return the call's result instead.
+    pub fn eliminate_unreachable_code(program: IntermediateProgram) -> IntermediateProgram {
+        let IntermediateProgram { main, types } = program;
+        let IntermediateLambda { args, block } = main;
+        IntermediateProgram {
+            main: IntermediateLambda {
+                args,
+                block: Self::eliminate_from_block(block),
+            },
+            types,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::call_returning;
+    use lowering::{AtomicTypeEnum, ExpressionEqualityChecker, Integer, IntermediateType, Str};
+
+    #[test]
+    fn test_eliminate_after_error() {
+        let failure = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::BOOL));
+        let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let error = |ret| {
+            call_returning(
+                "error",
+                vec![Str {
+                    value: String::from("x"),
+                }
+                .into()],
+                ret,
+            )
+        };
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: failure.register.clone(),
+                            expression: error(AtomicTypeEnum::BOOL),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: call_returning(
+                                "+",
+                                vec![Integer { value: 1 }.into(), Integer { value: 2 }.into()],
+                                AtomicTypeEnum::INT,
+                            ),
+                        }
+                        .into(),
+                    ],
+                    ret: x.clone().into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        let optimized = UnreachableCodeEliminator::eliminate_unreachable_code(program);
+        let expected = IntermediateLambda {
+            args: Vec::new(),
+            block: IntermediateBlock {
+                statements: vec![IntermediateAssignment {
+                    register: failure.register.clone(),
+                    expression: error(AtomicTypeEnum::INT),
+                }
+                .into()],
+                ret: IntermediateMemory {
+                    type_: AtomicTypeEnum::INT.into(),
+                    register: failure.register,
+                }
+                .into(),
+            },
+        };
+        ExpressionEqualityChecker::assert_equal(&optimized.main.into(), &expected.into());
+    }
+}
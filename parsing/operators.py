@@ -18,6 +18,7 @@ class OperatorManager:
         "+": 7,
         "-": 7,
         ">>": 8,
+        ">>>": 8,
         "<<": 8,
         "::": 9,
         "++": 9,
@@ -40,7 +41,7 @@ class OperatorManager:
 
     LEFT_ASSOCIATIVE_OPERATORS = {"$", "@", "::", "**", "++", "--"}
     NON_ASSOCIATIVE_OPERATORS = {"<", ">", "<=", ">=", "<=>", "==", "!="}
-    OPERATOR_REGEX = r"^[&!+/\-^$<>@:*|%=.]+$"
+    OPERATOR_REGEX = r"^[&!+/\-^$<>@:*|%=.~]+$"
 
     @classmethod
     def check_operator(cls, operator: str) -> bool:
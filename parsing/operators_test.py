@@ -18,6 +18,7 @@ operators = [
     ("+", R, 7),
     ("-", R, 7),
     (">>", R, 8),
+    (">>>", R, 8),
     ("<<", R, 8),
     ("::", L, 9),
     ("++", L, 9),
@@ -882,6 +882,7 @@ from ast_nodes import (
         ("(x: int, y: bool) -> bool { a = 3 }", None, "expr"),
         ("++x", FunctionCall(Var("++"), [Var("x")]), "expr"),
         ("-x", FunctionCall(Var("-"), [Var("x")]), "expr"),
+        ("~x", FunctionCall(Var("~"), [Var("x")]), "expr"),
         ("__add__ x", None, "expr"),
         (
             "++ (++x)",
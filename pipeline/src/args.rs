@@ -1,7 +1,18 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use emission::EmissionArgs;
 use optimization::OptimizationArgs;
 use translation::TranslationArgs;
 
+/// Stages whose output can be printed or resumed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Stage {
+    Lower,
+    Optimize,
+    Compile,
+}
+
 #[derive(Parser)]
 pub struct Cli {
     #[command(flatten)]
@@ -9,4 +20,61 @@ pub struct Cli {
 
     #[command(flatten)]
     pub optimization_args: OptimizationArgs,
+
+    /// Use the preset for this optimization level (`-O0` to `-O3`) instead of the individual optimization flags.
+    #[arg(short = 'O', long, value_parser = clap::value_parser!(u8).range(0..=3))]
+    pub optimize: Option<u8>,
+
+    #[command(flatten)]
+    pub emission_args: EmissionArgs,
+
+    /// Write debug and release variants of the code into this directory.
+    #[arg(long)]
+    pub emit_both: Option<PathBuf>,
+
+    /// Call `main` with these integer arguments instead of reading them at runtime.
+    #[arg(long, num_args = 0.., allow_negative_numbers = true)]
+    pub main_args: Option<Vec<i64>>,
+
+    /// Reject programs with variables or functions that are never used, unless their names start with `_`.
+    #[arg(long)]
+    pub deny_unused_bindings: bool,
+
+    /// Reject variables that are assigned more than once in the same block.
+    #[arg(long)]
+    pub deny_shadowing: bool,
+
+    /// Reject bindings with the same name as a built-in operator instead of warning about them.
+    #[arg(long, conflicts_with = "allow_built_in_shadowing")]
+    pub deny_built_in_shadowing: bool,
+
+    /// Allow bindings with the same name as a built-in operator without a warning.
+    #[arg(long)]
+    pub allow_built_in_shadowing: bool,
+
+    /// Reject integer literals that do not fit into this many bits.
+    #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u32).range(1..=64))]
+    pub int_width: u32,
+
+    /// Print the program as JSON after this stage instead of emitting code.
+    #[arg(long, value_enum)]
+    pub stop_after: Option<Stage>,
+
+    /// Read the JSON output of this stage from stdin instead of a parsed program.
+    #[arg(long, value_enum)]
+    pub resume_after: Option<Stage>,
+
+    /// Print how long each stage and optimization pass took to the stderr.
+    #[arg(long)]
+    pub time: bool,
+}
+
+impl Cli {
+    /// Replace the optimization args with the preset for `--optimize`, if it was given.
+    pub fn with_optimization_level(mut self) -> Self {
+        if let Some(level) = self.optimize {
+            self.optimization_args = OptimizationArgs::level(level);
+        }
+        self
+    }
 }
@@ -1,38 +1,512 @@
-use std::io::{self, Read};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::Path,
+    process,
+    time::{Duration, Instant},
+};
 
 mod args;
 
-use args::Cli;
+use args::{Cli, Stage};
 use clap::Parser;
-use emission::Emitter;
-use lowering::Lowerer;
-use optimization::Optimizer;
-use translation::Translator;
-use type_checker::{Program, TypeChecker};
+use emission::{EmissionArgs, Emitter, SourceMap};
+use lowering::{IntermediateProgram, Lowerer, Register};
+use optimization::{ComparisonLinter, ConstantComparison, DivisionChecker, Optimizer};
+use serde::{de::DeserializeOwned, Serialize};
+use translation::{Memory, Translator};
+use type_checker::{
+    BuiltInShadowing, IntWidth, Program, Shadowing, TypeCheckError, TypeCheckOptions,
+    TypeCheckWarning, TypeChecker,
+};
+
+/// Emit debug and release variants of a translated program into a directory.
+fn emit_both(program: translation::Program, out_dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(out_dir)?;
+    for (filename, args) in [
+        ("debug.hpp", EmissionArgs::debug_preset()),
+        ("release.hpp", EmissionArgs::release_preset()),
+    ] {
+        let code = Emitter::emit(program.clone(), args);
+        fs::write(out_dir.join(filename), code)?;
+    }
+    Ok(())
+}
+
+/// Write the lines of the emitted code with the registers that they use to the stderr as JSON.
+fn report_source_map(source_map: SourceMap, registers: &HashMap<Memory, Register>) {
+    let entries = source_map
+        .into_iter()
+        .map(|(line, memory)| {
+            serde_json::json!({
+                "line": line,
+                "register": registers.get(&memory),
+                "memory": memory,
+            })
+        })
+        .collect::<Vec<_>>();
+    eprintln!(
+        "{}",
+        serde_json::to_string(&entries).expect("Failed to serialize source map")
+    )
+}
+
+/// Deserialize the JSON output of a stage.
+fn deserialize<T: DeserializeOwned>(input: &str) -> T {
+    let mut deserializer = serde_json::Deserializer::from_str(input);
+    // Intermediate representations are nested more deeply than the default limit.
+    deserializer.disable_recursion_limit();
+    T::deserialize(&mut deserializer).unwrap_or_else(|msg| panic!("{}", msg))
+}
+
+/// Write the output of a stage to the stdout as JSON.
+fn serialize<T: Serialize>(program: &T) {
+    println!(
+        "{}",
+        serde_json::to_string(program).expect("Failed to serialize program")
+    )
+}
+
+/// Total time spent in each stage, in the order that the stages first ran.
+#[derive(Default)]
+struct Timings(Vec<(String, Duration)>);
+
+impl Timings {
+    fn record(&mut self, stage: &str, duration: Duration) {
+        match self.0.iter_mut().find(|(name, _)| name == stage) {
+            Some((_, total)) => *total += duration,
+            None => self.0.push((String::from(stage), duration)),
+        }
+    }
+    /// Run a stage and add its duration to the total for that stage.
+    fn time<T>(&mut self, stage: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(stage, start.elapsed());
+        result
+    }
+    /// Write a summary of the durations to the stderr.
+    fn report(&self) {
+        let width = self.0.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, duration) in &self.0 {
+            eprintln!("{name:<width$} {duration:.3?}");
+        }
+    }
+}
+
+/// Run the stages up to and including lowering.
+fn lower(input: &str, options: TypeCheckOptions, timings: &mut Timings) -> IntermediateProgram {
+    // Deserialize the JSON from the stdin.
+    let program = Program::from_json(input).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        process::exit(1)
+    });
+    match timings.time("type checking", || {
+        TypeChecker::type_check_with_warnings(program, options)
+    }) {
+        Ok((type_checked_program, warnings)) => {
+            for warning in warnings {
+                match warning {
+                    TypeCheckWarning::BuiltInShadow { name } => {
+                        eprintln!("Warning: {name:?} shadows a built-in operator.")
+                    }
+                }
+            }
+            timings.time("lowering", || Lowerer::lower(type_checked_program))
+        }
+        Err(TypeCheckError::MissingMain) => {
+            eprintln!("Error: no `main` function defined.");
+            process::exit(1)
+        }
+        Err(e) => {
+            panic!("{:?}", e)
+        }
+    }
+}
 
 fn main() {
-    let args = Cli::parse();
+    let args = Cli::parse().with_optimization_level();
     let mut input = String::new();
     io::stdin()
         .read_to_string(&mut input)
         .expect("Failed to read from stdin");
-    // Deserialize the JSON from the stdin.
-    match serde_json::from_str::<Program>(&input) {
-        Ok(program) => match TypeChecker::type_check(program) {
-            Ok(type_checked_program) => {
-                let lowered_program = Lowerer::lower(type_checked_program);
-                let optimized_program =
-                    Optimizer::optimize(lowered_program, args.optimization_args);
-                let translated_program =
-                    Translator::translate(optimized_program, args.compilation_args);
-                let code = Emitter::emit(translated_program);
-                // Write code to the stdout.
-                println!("{}", code)
+    let time = args.time;
+    let mut timings = Timings::default();
+    run(args, &input, &mut timings);
+    if time {
+        timings.report();
+    }
+}
+
+/// Run the stages selected by the arguments on the input.
+fn run(args: Cli, input: &str, timings: &mut Timings) {
+    if let (Some(resume_after), Some(stop_after)) = (args.resume_after, args.stop_after) {
+        assert!(
+            resume_after < stop_after,
+            "Cannot stop after {stop_after:?} when resuming after {resume_after:?}."
+        );
+    }
+    // Registers are unknown when resuming from a translated program.
+    let (translated_program, registers) = match args.resume_after {
+        Some(Stage::Compile) => (deserialize::<translation::Program>(input), HashMap::new()),
+        resume_after => {
+            let optimized_program = match resume_after {
+                Some(Stage::Optimize) => deserialize::<IntermediateProgram>(input),
+                resume_after => {
+                    let lowered_program = match resume_after {
+                        Some(Stage::Lower) => deserialize::<IntermediateProgram>(input),
+                        _ => lower(
+                            input,
+                            TypeCheckOptions {
+                                main_args: args.main_args,
+                                deny_unused_bindings: args.deny_unused_bindings,
+                                shadowing: if args.deny_shadowing {
+                                    Shadowing::Deny
+                                } else {
+                                    Shadowing::LastWins
+                                },
+                                built_in_shadowing: if args.deny_built_in_shadowing {
+                                    BuiltInShadowing::Deny
+                                } else if args.allow_built_in_shadowing {
+                                    BuiltInShadowing::Allow
+                                } else {
+                                    BuiltInShadowing::Warn
+                                },
+                                int_width: IntWidth(args.int_width),
+                            },
+                            timings,
+                        ),
+                    };
+                    if args.stop_after == Some(Stage::Lower) {
+                        return serialize(&lowered_program);
+                    }
+                    if let Err(e) = DivisionChecker::check(&lowered_program) {
+                        panic!("{:?}", e)
+                    }
+                    for ConstantComparison { register, value } in
+                        ComparisonLinter::lint(&lowered_program)
+                    {
+                        eprintln!(
+                            "Warning: the comparison assigned to {register:?} is always {value}."
+                        );
+                    }
+                    Optimizer::optimize_with_timings(
+                        lowered_program,
+                        args.optimization_args,
+                        |pass, duration| timings.record(&format!("optimization: {pass}"), duration),
+                    )
+                    .0
+                }
+            };
+            if args.stop_after == Some(Stage::Optimize) {
+                return serialize(&optimized_program);
             }
-            Err(e) => {
-                panic!("{:?}", e)
+            timings.time("compilation", || {
+                if args.emission_args.debug_info {
+                    Translator::translate_with_registers(optimized_program, args.compilation_args)
+                } else {
+                    // Registers are only needed for the source map and are not cached.
+                    (
+                        Translator::translate(optimized_program, args.compilation_args),
+                        HashMap::new(),
+                    )
+                }
+            })
+        }
+    };
+    if args.stop_after == Some(Stage::Compile) {
+        return serialize(&translated_program);
+    }
+    match args.emit_both {
+        Some(out_dir) => {
+            // Share all stages before emission between the variants.
+            timings
+                .time("emission", || emit_both(translated_program, &out_dir))
+                .expect("Failed to write code")
+        }
+        None => {
+            let debug_info = args.emission_args.debug_info;
+            let (code, source_map) = timings.time("emission", || {
+                Emitter::emit_with_source_map(translated_program, args.emission_args)
+            });
+            // Write code to the stdout.
+            println!("{}", code);
+            if debug_info {
+                report_source_map(source_map, &registers);
             }
-        },
-        Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use emission::{OutputRadix, Target};
+    use lowering::{Integer, IntermediateBuiltIn, Interpreter, Value};
+    use optimization::OptimizationArgs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_emit_both() {
+        // `type Bull = Twoo | Faws; main = (x: int) -> int { b = if x > 0 { Twoo } else { Faws }; match b { Twoo => 1, Faws => 0 } };`
+        let input = r#"{"definitions":[
+            {"UnionTypeDefinition":{"variable":{"id":"Bull","generic_variables":[]},"items":[{"id":"Twoo","type_":null},{"id":"Faws","type_":null}]}},
+            {"Assignment":{"assignee":{"assignee":{"id":"main"},"generic_variables":[]},"expression":{"FunctionDefinition":{
+                "parameters":[{"assignee":{"id":"x"},"type_":{"AtomicType":{"type_":"INT"}}}],
+                "return_type":{"AtomicType":{"type_":"INT"}},
+                "body":{
+                    "assignments":[{"assignee":{"assignee":{"id":"b"},"generic_variables":[]},"expression":{"IfExpression":{
+                        "condition":{"FunctionCall":{"function":{"GenericVariable":{"id":">","type_instances":[]}},"arguments":[{"GenericVariable":{"id":"x","type_instances":[]}},{"Integer":{"value":0}}]}},
+                        "true_block":{"assignments":[],"expression":{"ConstructorCall":{"constructor":{"id":"Twoo","type_instances":[]},"arguments":[]}}},
+                        "false_block":{"assignments":[],"expression":{"ConstructorCall":{"constructor":{"id":"Faws","type_instances":[]},"arguments":[]}}}
+                    }}}],
+                    "expression":{"MatchExpression":{"subject":{"GenericVariable":{"id":"b","type_instances":[]}},"blocks":[
+                        {"matches":[{"type_name":"Twoo","assignee":null}],"block":{"assignments":[],"expression":{"Integer":{"value":1}}}},
+                        {"matches":[{"type_name":"Faws","assignee":null}],"block":{"assignments":[],"expression":{"Integer":{"value":0}}}}
+                    ]}}
+                }
+            }}}}
+        ]}"#;
+        let program = serde_json::from_str::<Program>(input).unwrap();
+        let type_checked_program = TypeChecker::type_check(program).unwrap();
+        let args = Cli::parse_from(["pipeline"]);
+        let lowered_program = Lowerer::lower(type_checked_program);
+        let optimized_program = Optimizer::optimize(lowered_program, args.optimization_args);
+        let translated_program = Translator::translate(optimized_program, args.compilation_args);
+
+        let out_dir = TempDir::new().expect("Could not create temp dir.");
+        emit_both(translated_program, out_dir.path()).expect("Failed to write code");
+        let debug = fs::read_to_string(out_dir.path().join("debug.hpp")).unwrap();
+        let release = fs::read_to_string(out_dir.path().join("release.hpp")).unwrap();
+        assert!(debug.contains("assert("));
+        assert!(!release.contains("assert("));
+    }
+
+    #[test]
+    fn test_emit_both_without_match() {
+        // `main = (x: int) -> int { x + 1 };`
+        let input = r#"{"definitions":[
+            {"Assignment":{"assignee":{"assignee":{"id":"main"},"generic_variables":[]},"expression":{"FunctionDefinition":{
+                "parameters":[{"assignee":{"id":"x"},"type_":{"AtomicType":{"type_":"INT"}}}],
+                "return_type":{"AtomicType":{"type_":"INT"}},
+                "body":{
+                    "assignments":[],
+                    "expression":{"FunctionCall":{"function":{"GenericVariable":{"id":"+","type_instances":[]}},"arguments":[{"GenericVariable":{"id":"x","type_instances":[]}},{"Integer":{"value":1}}]}}
+                }
+            }}}}
+        ]}"#;
+        let program = serde_json::from_str::<Program>(input).unwrap();
+        let type_checked_program = TypeChecker::type_check(program).unwrap();
+        let args = Cli::parse_from(["pipeline"]);
+        let lowered_program = Lowerer::lower(type_checked_program);
+        let optimized_program = Optimizer::optimize(lowered_program, args.optimization_args);
+        let translated_program = Translator::translate(optimized_program, args.compilation_args);
+
+        let out_dir = TempDir::new().expect("Could not create temp dir.");
+        emit_both(translated_program, out_dir.path()).expect("Failed to write code");
+        let debug = fs::read_to_string(out_dir.path().join("debug.hpp")).unwrap();
+        let release = fs::read_to_string(out_dir.path().join("release.hpp")).unwrap();
+        // Without a match there are no tag checks, but calls are still traced.
+        assert!(!debug.contains("assert("));
+        assert!(debug.contains("trace: "));
+        assert!(!release.contains("trace: "));
+    }
+
+    #[test]
+    fn test_resume_after_stop() {
+        // `main = (x: int) -> int { x + 1 }`
+        let input = r#"{"definitions":[
+            {"Assignment":{"assignee":{"assignee":{"id":"main"},"generic_variables":[]},"expression":{"FunctionDefinition":{
+                "parameters":[{"assignee":{"id":"x"},"type_":{"AtomicType":{"type_":"INT"}}}],
+                "return_type":{"AtomicType":{"type_":"INT"}},
+                "body":{"assignments":[],"expression":{"FunctionCall":{"function":{"GenericVariable":{"id":"+","type_instances":[]}},"arguments":[{"GenericVariable":{"id":"x","type_instances":[]}},{"Integer":{"value":1}}]}}}
+            }}}}
+        ]}"#;
+        let args = Cli::parse_from([
+            "pipeline",
+            "--resume-after",
+            "lower",
+            "--stop-after",
+            "compile",
+        ]);
+        assert_eq!(args.resume_after, Some(Stage::Lower));
+        assert_eq!(args.stop_after, Some(Stage::Compile));
+
+        let lowered_program = lower(input, TypeCheckOptions::default(), &mut Timings::default());
+        let json = serde_json::to_string(&lowered_program).unwrap();
+        let lowered_program = deserialize::<IntermediateProgram>(&json);
+        let optimized_program = Optimizer::optimize(lowered_program, args.optimization_args);
+        let json = serde_json::to_string(&optimized_program).unwrap();
+        let optimized_program = deserialize::<IntermediateProgram>(&json);
+        let translated_program = Translator::translate(optimized_program, args.compilation_args);
+        let json = serde_json::to_string(&translated_program).unwrap();
+        assert_eq!(
+            deserialize::<translation::Program>(&json),
+            translated_program
+        );
+        let code = Emitter::emit(translated_program, args.emission_args);
+        assert!(code.contains("Plus__BuiltIn"));
+    }
+
+    #[test]
+    fn test_partial_application() {
+        // `main = (x: int) -> int { add1 = +(1); add1(x) }`
+        let input = r#"{"definitions":[
+            {"Assignment":{"assignee":{"assignee":{"id":"main"},"generic_variables":[]},"expression":{"FunctionDefinition":{
+                "parameters":[{"assignee":{"id":"x"},"type_":{"AtomicType":{"type_":"INT"}}}],
+                "return_type":{"AtomicType":{"type_":"INT"}},
+                "body":{
+                    "assignments":[{"assignee":{"assignee":{"id":"add1"},"generic_variables":[]},"expression":{"FunctionCall":{"function":{"GenericVariable":{"id":"+","type_instances":[]}},"arguments":[{"Integer":{"value":1}}]}}}],
+                    "expression":{"FunctionCall":{"function":{"GenericVariable":{"id":"add1","type_instances":[]}},"arguments":[{"GenericVariable":{"id":"x","type_instances":[]}}]}}
+                }
+            }}}}
+        ]}"#;
+        let lowered_program = lower(input, TypeCheckOptions::default(), &mut Timings::default());
+        let args = vec![Value::from(IntermediateBuiltIn::from(Integer { value: 4 }))];
+        let expected = Value::from(IntermediateBuiltIn::from(Integer { value: 5 }));
+        assert_eq!(Interpreter::run(&lowered_program, args.clone()), expected);
+        let optimization_args = Cli::parse_from(["pipeline"]).optimization_args;
+        let optimized_program = Optimizer::optimize(lowered_program, optimization_args);
+        assert_eq!(Interpreter::run(&optimized_program, args), expected);
+    }
+
+    #[test]
+    fn test_iterate() {
+        // `main = (x: int) -> int { inc = (y: int) -> int { y + 1 }; iterate(3, inc, x) }`
+        let input = r#"{"definitions":[
+            {"Assignment":{"assignee":{"assignee":{"id":"main"},"generic_variables":[]},"expression":{"FunctionDefinition":{
+                "parameters":[{"assignee":{"id":"x"},"type_":{"AtomicType":{"type_":"INT"}}}],
+                "return_type":{"AtomicType":{"type_":"INT"}},
+                "body":{
+                    "assignments":[{"assignee":{"assignee":{"id":"inc"},"generic_variables":[]},"expression":{"FunctionDefinition":{
+                        "parameters":[{"assignee":{"id":"y"},"type_":{"AtomicType":{"type_":"INT"}}}],
+                        "return_type":{"AtomicType":{"type_":"INT"}},
+                        "body":{"assignments":[],"expression":{"FunctionCall":{"function":{"GenericVariable":{"id":"+","type_instances":[]}},"arguments":[{"GenericVariable":{"id":"y","type_instances":[]}},{"Integer":{"value":1}}]}}}
+                    }}}],
+                    "expression":{"FunctionCall":{"function":{"GenericVariable":{"id":"iterate","type_instances":[]}},"arguments":[{"Integer":{"value":3}},{"GenericVariable":{"id":"inc","type_instances":[]}},{"GenericVariable":{"id":"x","type_instances":[]}}]}}
+                }
+            }}}}
+        ]}"#;
+        let lowered_program = lower(input, TypeCheckOptions::default(), &mut Timings::default());
+        let args = vec![Value::from(IntermediateBuiltIn::from(Integer { value: 0 }))];
+        let expected = Value::from(IntermediateBuiltIn::from(Integer { value: 3 }));
+        assert_eq!(Interpreter::run(&lowered_program, args.clone()), expected);
+        let cli = Cli::parse_from(["pipeline"]);
+        // The iterations run in a loop instead of as a task each (before inlining unrolls it).
+        let translated_program =
+            Translator::translate(lowered_program.clone(), cli.compilation_args);
+        let code = Emitter::emit(translated_program, cli.emission_args);
+        let loop_ = code
+            .split("struct ")
+            .find(|fn_def| fn_def.contains("while (true)"))
+            .expect("Iterate was not compiled into a loop.");
+        // Only `inc` is called; the recursive call restarts the loop.
+        assert_eq!(loop_.matches("fn_call(").count(), 1);
+        assert!(loop_.contains("continue;"));
+        let optimized_program = Optimizer::optimize(lowered_program, cli.optimization_args);
+        assert_eq!(Interpreter::run(&optimized_program, args), expected);
+    }
+
+    #[test]
+    fn test_main_args() {
+        let args = Cli::parse_from(["pipeline", "--main-args", "3", "-4"]);
+        assert_eq!(args.main_args, Some(vec![3, -4]));
+        let args = Cli::parse_from(["pipeline", "--main-args"]);
+        assert_eq!(args.main_args, Some(Vec::new()));
+        let args = Cli::parse_from(["pipeline"]);
+        assert_eq!(args.main_args, None);
+    }
+
+    #[test]
+    fn test_timings() {
+        let mut timings = Timings::default();
+        timings.record("lowering", Duration::from_millis(2));
+        timings.record("optimization: inlining", Duration::from_millis(1));
+        assert_eq!(timings.time("lowering", || 5), 5);
+        timings.record("optimization: inlining", Duration::from_millis(3));
+        let stages = timings
+            .0
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(stages, vec!["lowering", "optimization: inlining"]);
+        assert!(timings.0[0].1 >= Duration::from_millis(2));
+        assert_eq!(timings.0[1].1, Duration::from_millis(4));
+    }
+
+    #[test]
+    fn test_optimization_level() {
+        let optimization_args = |flags: &[&str]| {
+            Cli::parse_from(["pipeline"].iter().chain(flags))
+                .with_optimization_level()
+                .optimization_args
+        };
+        assert_eq!(optimization_args(&[]), OptimizationArgs::level(2));
+        assert_eq!(optimization_args(&["-O2"]), OptimizationArgs::level(2));
+        assert_eq!(optimization_args(&["-O0"]), OptimizationArgs::level(0));
+        assert_eq!(
+            optimization_args(&["--optimize", "1"]),
+            OptimizationArgs::level(1)
+        );
+        assert_eq!(
+            optimization_args(&["-O3", "--max-iterations", "1"]),
+            OptimizationArgs::level(3)
+        );
+        assert_eq!(
+            optimization_args(&["--max-iterations", "1"]),
+            OptimizationArgs {
+                max_iterations: 1,
+                ..OptimizationArgs::level(2)
+            }
+        );
+        assert!(Cli::try_parse_from(["pipeline", "-O4"]).is_err());
+    }
+
+    #[test]
+    fn test_optimization_presets() {
+        let level = OptimizationArgs::level;
+        assert_eq!(level(0).max_iterations, 0);
+        assert!(level(0).constant_folding_args.no_constant_folding);
+        assert!(!level(1).dead_code_analysis_args.no_dead_code_analysis);
+        assert!(
+            level(1)
+                .common_subexpression_elimination_args
+                .no_common_subexpression_elimination
+        );
+        assert_eq!(level(1).inlining_args.inlining_depth, 0);
+        assert!(level(3).max_iterations > level(2).max_iterations);
+        assert!(level(3).inlining_args.inlining_depth > level(2).inlining_args.inlining_depth);
+        assert_eq!(
+            level(2).inlining_args,
+            Cli::parse_from(["pipeline"])
+                .optimization_args
+                .inlining_args
+        );
+        assert_eq!(level(3).inlining_args.max_inline_depth, 8);
+    }
+
+    #[test]
+    fn test_target() {
+        let args = Cli::parse_from(["pipeline"]);
+        assert_eq!(args.emission_args.target, Target::Cpp);
+        let args = Cli::parse_from(["pipeline", "--target", "cpp"]);
+        assert_eq!(args.emission_args.target, Target::Cpp);
+        assert!(Cli::try_parse_from(["pipeline", "--target", "bytecode"]).is_err());
+    }
+
+    #[test]
+    fn test_emission_flags() {
+        let args = Cli::parse_from([
+            "pipeline",
+            "--debug-info",
+            "--checked-arithmetic",
+            "--output-radix",
+            "hex",
+        ]);
+        assert!(args.emission_args.debug_info);
+        assert!(args.emission_args.checked_arithmetic);
+        assert_eq!(args.emission_args.output_radix, OutputRadix::Hex);
     }
 }
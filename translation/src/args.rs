@@ -1,7 +1,27 @@
+use std::path::PathBuf;
+
 use clap::{arg, Args};
+use serde::Serialize;
 
-#[derive(Args)]
+#[derive(Args, Serialize)]
 pub struct TranslationArgs {
     #[arg(long)]
+    #[serde(skip)]
     pub export_vector_file: Option<String>,
+    /// Evaluate impure arguments to a call from left to right.
+    #[arg(long)]
+    pub strict_arg_order: bool,
+    /// Call tail-recursive fns instead of compiling them into loops.
+    #[arg(long)]
+    pub no_tail_call_loops: bool,
+    /// Run calls to fns estimated to be smaller than this inline instead of as separate tasks.
+    #[arg(long, default_value_t = 0)]
+    pub min_task_size: usize,
+    /// Run consecutive independent calls as a single task while their combined estimated size is at most this.
+    #[arg(long, default_value_t = 0)]
+    pub batch_size: usize,
+    /// Reuse translations of the same program from this directory, saving new translations to it.
+    #[arg(long)]
+    #[serde(skip)]
+    pub cache_dir: Option<PathBuf>,
 }
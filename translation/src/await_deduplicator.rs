@@ -1,8 +1,11 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, iter};
 
 use itertools::Itertools;
 
-use crate::{Await, FnDef, IfStatement, MatchBranch, MatchStatement, Memory, Program, Statement};
+use crate::{
+    Allocation, Assignment, Await, Enqueue, FnDef, IfStatement, MatchBranch, MatchStatement,
+    Memory, Program, Statement,
+};
 
 #[derive(Clone, Debug)]
 pub struct AwaitDeduplicator {
@@ -49,14 +52,124 @@ impl AwaitDeduplicator {
     fn deduplicate_statements(&mut self, statements: Vec<Statement>) -> Vec<Statement> {
         statements
             .into_iter()
-            .filter_map(|statement| match statement {
-                Statement::Await(await_) => self.deduplicate_await(await_).map(Statement::from),
-                Statement::IfStatement(if_) => Some(self.deduplicate_if(if_).into()),
-                Statement::MatchStatement(match_) => Some(self.deduplicate_match(match_).into()),
-                statement => Some(statement),
+            .flat_map(|statement| match statement {
+                Statement::Await(await_) => self
+                    .deduplicate_await(await_)
+                    .map(Statement::from)
+                    .into_iter()
+                    .collect_vec(),
+                Statement::IfStatement(if_) => {
+                    let hoisted_await = self.hoist_awaits(vec![&if_.branches.0, &if_.branches.1]);
+                    hoisted_await
+                        .map(Statement::from)
+                        .into_iter()
+                        .chain(iter::once(self.deduplicate_if(if_).into()))
+                        .collect_vec()
+                }
+                Statement::MatchStatement(match_) => {
+                    let hoisted_await = self.hoist_awaits(
+                        match_
+                            .branches
+                            .iter()
+                            .map(|branch| &branch.statements)
+                            .collect_vec(),
+                    );
+                    hoisted_await
+                        .map(Statement::from)
+                        .into_iter()
+                        .chain(iter::once(self.deduplicate_match(match_).into()))
+                        .collect_vec()
+                }
+                statement => vec![statement],
             })
             .collect_vec()
     }
+    /// Await values before branching if every branch awaits them and they are computed before the branches.
+    fn hoist_awaits(&mut self, branches: Vec<&Vec<Statement>>) -> Option<Await> {
+        let assigned_ids = branches
+            .iter()
+            .flat_map(|statements| Self::assigned_ids(statements))
+            .collect::<HashSet<_>>();
+        let awaited_ids = branches
+            .into_iter()
+            .map(|statements| Self::awaited_ids(statements))
+            .reduce(|x, y| x.intersection(&y).cloned().collect())?;
+        let hoisted_ids = awaited_ids
+            .into_iter()
+            .filter(|id| !assigned_ids.contains(id))
+            .sorted()
+            .collect_vec();
+        self.deduplicate_await(Await(hoisted_ids))
+    }
+    /// Find the values that are awaited on every path through the statements.
+    fn awaited_ids(statements: &[Statement]) -> HashSet<Memory> {
+        let common_ids = |branches: Vec<&Vec<Statement>>| {
+            branches
+                .into_iter()
+                .map(|statements| Self::awaited_ids(statements))
+                .reduce(|x, y| x.intersection(&y).cloned().collect())
+                .unwrap_or_default()
+        };
+        statements
+            .iter()
+            .flat_map(|statement| match statement {
+                Statement::Await(Await(ids)) => ids.iter().cloned().collect(),
+                Statement::IfStatement(IfStatement {
+                    condition: _,
+                    branches,
+                }) => common_ids(vec![&branches.0, &branches.1]),
+                Statement::MatchStatement(MatchStatement {
+                    expression: _,
+                    branches,
+                    auxiliary_memory: _,
+                }) => common_ids(
+                    branches
+                        .iter()
+                        .map(|branch| &branch.statements)
+                        .collect_vec(),
+                ),
+                _ => HashSet::new(),
+            })
+            .collect()
+    }
+    /// Find the values that are computed or enqueued on any path through the statements.
+    fn assigned_ids(statements: &[Statement]) -> HashSet<Memory> {
+        statements
+            .iter()
+            .flat_map(|statement| match statement {
+                Statement::Assignment(Assignment { target, value: _ })
+                | Statement::Allocation(Allocation {
+                    name: _,
+                    fns: _,
+                    target,
+                })
+                | Statement::Enqueue(Enqueue(target)) => HashSet::from([target.clone()]),
+                Statement::IfStatement(IfStatement {
+                    condition: _,
+                    branches,
+                }) => [&branches.0, &branches.1]
+                    .into_iter()
+                    .flat_map(|statements| Self::assigned_ids(statements))
+                    .collect(),
+                Statement::MatchStatement(MatchStatement {
+                    expression: _,
+                    branches,
+                    auxiliary_memory,
+                }) => branches
+                    .iter()
+                    .flat_map(|MatchBranch { target, statements }| {
+                        Self::assigned_ids(statements)
+                            .into_iter()
+                            .chain(target.clone())
+                    })
+                    .chain(iter::once(auxiliary_memory.clone()))
+                    .collect(),
+                Statement::Await(_) | Statement::Declaration(_) | Statement::TailCall(_) => {
+                    HashSet::new()
+                }
+            })
+            .collect()
+    }
     fn deduplicate_await(&mut self, Await(ids): Await) -> Option<Await> {
         {
             let fresh_ids = ids
@@ -376,11 +489,11 @@ mod tests {
                 memory: Memory(Id::from("m0")),
                 type_: AtomicTypeEnum::BOOL.into()
             }.into(),
+            Await(vec![Memory(Id::from("m1"))]).into(),
             IfStatement{
                 condition: Value::from(Boolean{value: false}).into(),
                 branches: (
                     vec![
-                        Await(vec![Memory(Id::from("m1"))]).into(),
                         Assignment{
                             target: Memory(Id::from("m0")),
                             value: FnCall {
@@ -396,7 +509,6 @@ mod tests {
                         }.into(),
                     ],
                     vec![
-                        Await(vec![Memory(Id::from("m1"))]).into(),
                         Assignment{
                             target: Memory(Id::from("m0")),
                             value: FnCall {
@@ -716,6 +828,7 @@ mod tests {
                 memory: Memory(Id::from("shared")),
                 type_: AtomicTypeEnum::BOOL.into()
             }.into(),
+            Await(vec![Memory(Id::from("f"))]).into(),
             MatchStatement{
                 expression: (
                     Memory(Id::from("subject")).into(),
@@ -726,7 +839,6 @@ mod tests {
                     MatchBranch {
                         target: None,
                         statements: vec![
-                            Await(vec![Memory(Id::from("f"))]).into(),
                             Assignment{
                                 target: Memory(Id::from("shared")),
                                 value: FnCall {
@@ -852,6 +964,7 @@ mod tests {
                 memory: Memory(Id::from("shared")),
                 type_: AtomicTypeEnum::BOOL.into()
             }.into(),
+            Await(vec![Memory(Id::from("f"))]).into(),
             MatchStatement{
                 expression: (
                     Memory(Id::from("subject")).into(),
@@ -862,7 +975,6 @@ mod tests {
                     MatchBranch {
                         target: Some(Memory(Id::from("a"))),
                         statements: vec![
-                            Await(vec![Memory(Id::from("f"))]).into(),
                             Assignment{
                                 target: Memory(Id::from("shared")),
                                 value: FnCall {
@@ -881,7 +993,6 @@ mod tests {
                     MatchBranch {
                         target: Some(Memory(Id::from("b"))),
                         statements: vec![
-                            Await(vec![Memory(Id::from("f"))]).into(),
                             Assignment{
                                 target: Memory(Id::from("shared")),
                                 value: FnCall {
@@ -900,7 +1011,6 @@ mod tests {
                     MatchBranch {
                         target: Some(Memory(Id::from("c"))),
                         statements: vec![
-                            Await(vec![Memory(Id::from("f"))]).into(),
                             Assignment{
                                 target: Memory(Id::from("shared")),
                                 value: FnCall {
@@ -1089,6 +1199,95 @@ mod tests {
         ];
         "await in match statement multiple branches"
     )]
+    #[test_case(
+        vec![
+            Declaration{
+                memory: Memory(Id::from("m1")),
+                type_: AtomicTypeEnum::BOOL.into()
+            }.into(),
+            IfStatement{
+                condition: Value::from(Boolean{value: true}),
+                branches: (
+                    vec![
+                        Assignment{
+                            target: Memory(Id::from("m1")),
+                            value: FnCall {
+                                fn_: Memory(Id::from("f")).into(),
+                                fn_type: FnType(
+                                    vec![AtomicTypeEnum::INT.into()],
+                                    Box::new(AtomicTypeEnum::BOOL.into())
+                                ),
+                                args: vec![
+                                    Memory(Id::from("x")).into(),
+                                ]
+                            }.into(),
+                        }.into(),
+                        Await(vec![Memory(Id::from("m1"))]).into(),
+                    ],
+                    vec![
+                        Assignment{
+                            target: Memory(Id::from("m1")),
+                            value: FnCall {
+                                fn_: Memory(Id::from("f")).into(),
+                                fn_type: FnType(
+                                    vec![AtomicTypeEnum::INT.into()],
+                                    Box::new(AtomicTypeEnum::BOOL.into())
+                                ),
+                                args: vec![
+                                    Memory(Id::from("y")).into(),
+                                ]
+                            }.into(),
+                        }.into(),
+                        Await(vec![Memory(Id::from("m1"))]).into(),
+                    ],
+                )
+            }.into(),
+        ],
+        vec![
+            Declaration{
+                memory: Memory(Id::from("m1")),
+                type_: AtomicTypeEnum::BOOL.into()
+            }.into(),
+            IfStatement{
+                condition: Value::from(Boolean{value: true}),
+                branches: (
+                    vec![
+                        Assignment{
+                            target: Memory(Id::from("m1")),
+                            value: FnCall {
+                                fn_: Memory(Id::from("f")).into(),
+                                fn_type: FnType(
+                                    vec![AtomicTypeEnum::INT.into()],
+                                    Box::new(AtomicTypeEnum::BOOL.into())
+                                ),
+                                args: vec![
+                                    Memory(Id::from("x")).into(),
+                                ]
+                            }.into(),
+                        }.into(),
+                        Await(vec![Memory(Id::from("m1"))]).into(),
+                    ],
+                    vec![
+                        Assignment{
+                            target: Memory(Id::from("m1")),
+                            value: FnCall {
+                                fn_: Memory(Id::from("f")).into(),
+                                fn_type: FnType(
+                                    vec![AtomicTypeEnum::INT.into()],
+                                    Box::new(AtomicTypeEnum::BOOL.into())
+                                ),
+                                args: vec![
+                                    Memory(Id::from("y")).into(),
+                                ]
+                            }.into(),
+                        }.into(),
+                        Await(vec![Memory(Id::from("m1"))]).into(),
+                    ],
+                )
+            }.into(),
+        ];
+        "awaits of values computed in both if statement branches"
+    )]
     fn test_deduplicate_statements(
         duplicated_statements: Vec<Statement>,
         expected_statements: Vec<Statement>,
@@ -1098,6 +1297,70 @@ mod tests {
         assert_eq!(expected_statements, deduplicated_statements)
     }
 
+    fn count_awaits(statements: &[Statement]) -> usize {
+        statements
+            .iter()
+            .map(|statement| match statement {
+                Statement::Await(_) => 1,
+                Statement::IfStatement(IfStatement {
+                    condition: _,
+                    branches,
+                }) => count_awaits(&branches.0) + count_awaits(&branches.1),
+                Statement::MatchStatement(MatchStatement {
+                    expression: _,
+                    branches,
+                    auxiliary_memory: _,
+                }) => branches
+                    .iter()
+                    .map(|branch| count_awaits(&branch.statements))
+                    .sum(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_hoisted_await_count() {
+        let branch = |arg: &str| {
+            vec![
+                Await(vec![Memory(Id::from("f"))]).into(),
+                Assignment {
+                    target: Memory(Id::from("y")),
+                    value: FnCall {
+                        fn_: Memory(Id::from("f")).into(),
+                        fn_type: FnType(
+                            vec![AtomicTypeEnum::INT.into()],
+                            Box::new(AtomicTypeEnum::BOOL.into()),
+                        ),
+                        args: vec![Memory(Id::from(arg)).into()],
+                    }
+                    .into(),
+                }
+                .into(),
+            ]
+        };
+        let statements: Vec<Statement> = vec![
+            Declaration {
+                memory: Memory(Id::from("y")),
+                type_: AtomicTypeEnum::BOOL.into(),
+            }
+            .into(),
+            IfStatement {
+                condition: Memory(Id::from("c")).into(),
+                branches: (branch("x0"), branch("x1")),
+            }
+            .into(),
+        ];
+        let deduplicated_statements =
+            AwaitDeduplicator::new().deduplicate_statements(statements.clone());
+        assert_eq!(count_awaits(&statements), 2);
+        assert_eq!(count_awaits(&deduplicated_statements), 1);
+        assert_eq!(
+            deduplicated_statements[1],
+            Await(vec![Memory(Id::from("f"))]).into()
+        );
+    }
+
     #[test_case(
         Program{
             type_defs: vec![
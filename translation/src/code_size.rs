@@ -9,7 +9,8 @@ use itertools::Itertools;
 use lowering::{
     BuiltInFn, Id, IntermediateAssignment, IntermediateBlock, IntermediateBuiltIn,
     IntermediateExpression, IntermediateFnCall, IntermediateIf, IntermediateLambda,
-    IntermediateMatch, IntermediateMatchBranch, IntermediateStatement, IntermediateValue,
+    IntermediateMatch, IntermediateMatchBranch, IntermediateProgram, IntermediateStatement,
+    IntermediateValue, Register,
 };
 use once_cell::sync::Lazy;
 use std::fs;
@@ -21,6 +22,7 @@ define_named_vector!(
     CodeVector,
     builtin_bool,
     builtin_int,
+    builtin_str,
     builtin_fn,
     memory_access,
     tuple_expression,
@@ -38,6 +40,7 @@ define_named_vector!(
 pub const CODE_SIZE_CONSTANTS: Lazy<CodeVector> = Lazy::new(|| CodeVector {
     builtin_bool: 0,
     builtin_int: 0,
+    builtin_str: 0,
     builtin_fn: 0,
     memory_access: 0,
     tuple_expression: 7,
@@ -58,6 +61,7 @@ pub const CODE_SIZE_CONSTANTS: Lazy<CodeVector> = Lazy::new(|| CodeVector {
             ("+", 9),
             ("-", 9),
             (">>", 9),
+            (">>>", 9),
             ("<<", 9),
             ("<=>", 9),
             ("&", 9),
@@ -65,13 +69,38 @@ pub const CODE_SIZE_CONSTANTS: Lazy<CodeVector> = Lazy::new(|| CodeVector {
             ("|", 9),
             ("++", 8),
             ("--", 8),
+            ("~", 8),
+            ("min", 9),
+            ("max", 9),
+            ("abs", 8),
+            ("fma", 10),
+            ("+sat", 10),
+            ("-sat", 10),
+            ("*sat", 10),
+            ("+wrap", 9),
+            ("-wrap", 9),
+            ("*wrap", 9),
             ("<", 9),
             ("<=", 9),
             (">", 9),
             (">=", 9),
             ("==", 9),
             ("!=", 9),
+            ("&&", 9),
+            ("||", 9),
             ("!", 8),
+            ("assert", 8),
+            ("concat", 9),
+            ("length", 8),
+            ("ord", 8),
+            ("chr", 8),
+            ("empty", 8),
+            ("push", 12),
+            ("get", 9),
+            ("len", 8),
+            ("error", 8),
+            ("pmap", 12),
+            ("preduce", 12),
         ]
         .map(|(id, size)| (Id::from(id), size as usize)),
     ),
@@ -83,8 +112,11 @@ pub struct CodeSizeEstimator {}
 impl CodeSizeEstimator {
     fn builtin_size(built_in: &IntermediateBuiltIn) -> usize {
         match built_in {
-            IntermediateBuiltIn::Integer(_) => CODE_SIZE_CONSTANTS.builtin_int,
+            IntermediateBuiltIn::Integer(_) | IntermediateBuiltIn::Char(_) => {
+                CODE_SIZE_CONSTANTS.builtin_int
+            }
             IntermediateBuiltIn::Boolean(_) => CODE_SIZE_CONSTANTS.builtin_bool,
+            IntermediateBuiltIn::Str(_) => CODE_SIZE_CONSTANTS.builtin_str,
             IntermediateBuiltIn::BuiltInFn(_) => CODE_SIZE_CONSTANTS.builtin_fn,
         }
     }
@@ -119,7 +151,15 @@ impl CodeSizeEstimator {
                         _,
                     ))),
                 args,
-            }) => Interval::singleton(CODE_SIZE_CONSTANTS.operators[id] + Self::values_size(args)),
+            }) => Interval::singleton(
+                // Estimate extra built-ins as fn calls.
+                CODE_SIZE_CONSTANTS
+                    .operators
+                    .get(id)
+                    .copied()
+                    .unwrap_or(CODE_SIZE_CONSTANTS.fn_call)
+                    + Self::values_size(args),
+            ),
             IntermediateExpression::IntermediateFnCall(_) => {
                 Interval::singleton(CODE_SIZE_CONSTANTS.fn_call + values_size)
             }
@@ -176,6 +216,47 @@ impl CodeSizeEstimator {
         let size_interval = Self::block_size(&lambda.block);
         (size_interval.lower(), size_interval.upper())
     }
+    /// Estimate the upper bound on the size of each fn in the program (excluding main), indexed by the register that it is assigned to.
+    pub fn estimate_detailed(program: &IntermediateProgram) -> HashMap<Register, usize> {
+        let mut sizes = HashMap::new();
+        Self::estimate_fns_in_block(&program.main.block, &mut sizes);
+        sizes
+    }
+    fn estimate_fns_in_block(block: &IntermediateBlock, sizes: &mut HashMap<Register, usize>) {
+        for statement in &block.statements {
+            let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                register,
+                expression,
+            }) = statement;
+            match expression {
+                IntermediateExpression::IntermediateLambda(lambda) => {
+                    sizes.insert(register.clone(), Self::estimate_size(lambda).1);
+                    // Nested fns are attributed to their own registers.
+                    Self::estimate_fns_in_block(&lambda.block, sizes);
+                }
+                IntermediateExpression::IntermediateIf(IntermediateIf {
+                    condition: _,
+                    branches,
+                }) => {
+                    Self::estimate_fns_in_block(&branches.0, sizes);
+                    Self::estimate_fns_in_block(&branches.1, sizes);
+                }
+                IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                    subject: _,
+                    branches,
+                }) => {
+                    for IntermediateMatchBranch { target: _, block } in branches {
+                        Self::estimate_fns_in_block(block, sizes);
+                    }
+                }
+                IntermediateExpression::IntermediateValue(_)
+                | IntermediateExpression::IntermediateElementAccess(_)
+                | IntermediateExpression::IntermediateTupleExpression(_)
+                | IntermediateExpression::IntermediateFnCall(_)
+                | IntermediateExpression::IntermediateCtorCall(_) => {}
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -190,8 +271,8 @@ mod tests {
         AtomicTypeEnum, Boolean, BuiltInFn, Id, Integer, IntermediateArg, IntermediateAssignment,
         IntermediateCtorCall, IntermediateElementAccess, IntermediateFnCall, IntermediateFnType,
         IntermediateLambda, IntermediateMatchBranch, IntermediateMemory, IntermediateStatement,
-        IntermediateTupleExpression, IntermediateTupleType, IntermediateUnionType, Register,
-        DEFAULT_CONTEXT,
+        IntermediateTupleExpression, IntermediateTupleType, IntermediateType,
+        IntermediateUnionType, Register, DEFAULT_CONTEXT,
     };
     use test_case::test_case;
 
@@ -214,7 +295,12 @@ mod tests {
     fn exhaustive_operator_test() {
         assert_eq!(
             CSC.operators.keys().cloned().collect::<HashSet<_>>(),
-            DEFAULT_CONTEXT.with(|context| context.keys().cloned().collect::<HashSet<_>>())
+            // Structural equality and iteration are lowered into lambdas rather than operators.
+            DEFAULT_CONTEXT.with(|context| context
+                .iter()
+                .filter(|(id, _)| !["eq", "iterate"].contains(&id.as_str()))
+                .map(|(id, _)| id.clone())
+                .collect::<HashSet<_>>())
         )
     }
 
@@ -628,4 +714,84 @@ mod tests {
         let size = CodeSizeEstimator::estimate_size(&lambda);
         assert_eq!(size, expected_size)
     }
+
+    #[test]
+    fn test_estimate_detailed() {
+        let x = IntermediateArg::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let identity = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        )));
+        let increment = IntermediateMemory::from(IntermediateType::from(IntermediateFnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        )));
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: identity.register.clone(),
+                            expression: IntermediateLambda {
+                                args: vec![x.clone()],
+                                block: IntermediateBlock {
+                                    statements: Vec::new(),
+                                    ret: x.clone().into(),
+                                },
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: increment.register.clone(),
+                            expression: IntermediateLambda {
+                                args: vec![x.clone()],
+                                block: IntermediateBlock {
+                                    statements: vec![IntermediateAssignment {
+                                        register: y.register.clone(),
+                                        expression: IntermediateFnCall {
+                                            fn_: BuiltInFn(
+                                                Id::from("+"),
+                                                IntermediateFnType(
+                                                    vec![
+                                                        AtomicTypeEnum::INT.into(),
+                                                        AtomicTypeEnum::INT.into(),
+                                                    ],
+                                                    Box::new(AtomicTypeEnum::INT.into()),
+                                                ),
+                                            )
+                                            .into(),
+                                            args: vec![
+                                                x.clone().into(),
+                                                Integer { value: 1 }.into(),
+                                            ],
+                                        }
+                                        .into(),
+                                    }
+                                    .into()],
+                                    ret: y.clone().into(),
+                                },
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: identity.clone().into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        assert_eq!(
+            CodeSizeEstimator::estimate_detailed(&program),
+            HashMap::from([
+                (identity.register, *MAS),
+                (
+                    increment.register,
+                    CSC.operators[&Id::from("+")] + *MAS + *BIS + *AS + *MAS
+                ),
+            ])
+        );
+    }
 }
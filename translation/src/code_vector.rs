@@ -15,8 +15,11 @@ pub struct CodeVectorCalculator {}
 impl CodeVectorCalculator {
     fn builtin_vector(built_in: &IntermediateBuiltIn) -> CodeVector {
         match built_in {
-            IntermediateBuiltIn::Integer(_) => CodeVector::builtin_int(),
+            IntermediateBuiltIn::Integer(_) | IntermediateBuiltIn::Char(_) => {
+                CodeVector::builtin_int()
+            }
             IntermediateBuiltIn::Boolean(_) => CodeVector::builtin_bool(),
+            IntermediateBuiltIn::Str(_) => CodeVector::builtin_str(),
             IntermediateBuiltIn::BuiltInFn(_) => CodeVector::builtin_fn(),
         }
     }
@@ -700,7 +703,12 @@ mod tests {
                 .cloned()
                 .sorted()
                 .collect_vec(),
-            DEFAULT_CONTEXT.with(|context| context.keys().cloned().sorted().collect_vec())
+            DEFAULT_CONTEXT.with(|context| context
+                .iter()
+                .filter(|(id, _)| !["eq", "iterate"].contains(&id.as_str()))
+                .map(|(id, _)| id.clone())
+                .sorted()
+                .collect_vec())
         )
     }
 }
@@ -0,0 +1,84 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use lowering::{
+    Integer, IntermediateBlock, IntermediateBuiltIn, IntermediateLambda, IntermediateProgram,
+};
+use serde::Deserialize;
+
+use crate::{Program, TranslationArgs};
+
+/// Version of the cache entries, to be bumped whenever the layout of a translated `Program` changes.
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// On-disk cache of translated programs, keyed by a hash of the program and the translation args.
+pub struct CompilationCache {
+    path: PathBuf,
+}
+
+impl CompilationCache {
+    /// Locate the cache entry for translating a program with these args.
+    pub fn new(dir: &Path, program: &IntermediateProgram, args: &TranslationArgs) -> Self {
+        CompilationCache {
+            path: dir.join(format!("{:016x}.json", Self::key(program, args))),
+        }
+    }
+    /// Hash a program so that the key is the same across runs and for programs that only differ in their registers.
+    /// The key is salted with the crate and cache format versions so that entries from other releases are not reused.
+    pub fn key(program: &IntermediateProgram, args: &TranslationArgs) -> u64 {
+        let mut program = program.clone();
+        program.canonicalize();
+        let serialize = |program: IntermediateProgram| {
+            serde_json::to_string(&program).expect("Failed to serialize program")
+        };
+        // Types are listed in an arbitrary order, so they are serialized separately and sorted.
+        let types = program
+            .types
+            .iter()
+            .map(|type_| {
+                serialize(IntermediateProgram {
+                    main: IntermediateLambda {
+                        args: Vec::new(),
+                        block: IntermediateBlock {
+                            statements: Vec::new(),
+                            ret: IntermediateBuiltIn::from(Integer { value: 0 }).into(),
+                        },
+                    },
+                    types: vec![type_.clone()],
+                })
+            })
+            .sorted()
+            .join("\n");
+        let main = serialize(IntermediateProgram {
+            main: program.main,
+            types: Vec::new(),
+        });
+        // Args that only affect where the output goes are skipped when serializing.
+        let options = serde_json::to_string(args).expect("Failed to serialize args");
+        let version = format!("{}+{CACHE_FORMAT_VERSION}", env!("CARGO_PKG_VERSION"));
+        Self::fnv1a([version, main, types, options].join("\n").as_bytes())
+    }
+    /// 64-bit FNV-1a hash, which (unlike the standard library's hashers) is fixed across releases.
+    fn fnv1a(bytes: &[u8]) -> u64 {
+        bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+    /// Read the cached translation, treating unreadable entries as missing.
+    pub fn load(&self) -> Option<Program> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        let mut deserializer = serde_json::Deserializer::from_str(&contents);
+        // Translated programs are nested more deeply than the default limit.
+        deserializer.disable_recursion_limit();
+        Program::deserialize(&mut deserializer).ok()
+    }
+    pub fn store(&self, program: &Program) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string(program)?)
+    }
+}
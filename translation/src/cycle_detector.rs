@@ -0,0 +1,393 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    Assignment, ClosureInstantiation, Expression, IfStatement, MachineType, MatchStatement, Memory,
+    Name, Program, Statement, TupleExpression, Value,
+};
+
+/// Closure environment entry identified by the fn name and index.
+type EnvEntry = (Name, usize);
+type Graph = HashMap<Memory, Vec<(Memory, Option<EnvEntry>)>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Cycle of strong references that cannot be freed by reference counting.
+pub struct StrongCycle {
+    pub memory: Vec<Memory>,
+    /// Environment entries that should hold weak references to break the cycle.
+    pub unweakened: Vec<EnvEntry>,
+}
+
+impl fmt::Display for StrongCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "strong reference cycle between {}",
+            self.memory.iter().map(|Memory(id)| id).join(", ")
+        )?;
+        if !self.unweakened.is_empty() {
+            write!(
+                f,
+                " (weaken {})",
+                self.unweakened
+                    .iter()
+                    .map(|(name, idx)| format!("{name}[{idx}]"))
+                    .join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct CycleDetector {
+    envs: HashMap<Name, Vec<MachineType>>,
+}
+
+impl CycleDetector {
+    /// Find all strong reference cycles between values in a program.
+    pub fn detect(program: &Program) -> Vec<StrongCycle> {
+        let detector = CycleDetector {
+            envs: program
+                .fn_defs
+                .iter()
+                .map(|fn_def| (fn_def.name.clone(), fn_def.env.clone()))
+                .collect(),
+        };
+        program
+            .fn_defs
+            .iter()
+            .flat_map(|fn_def| detector.detect_cycles(&fn_def.statements))
+            .collect_vec()
+    }
+    fn detect_cycles(&self, statements: &Vec<Statement>) -> Vec<StrongCycle> {
+        let mut closure_envs = HashMap::new();
+        Self::collect_closure_envs(statements, &mut closure_envs);
+        let mut graph = Graph::new();
+        self.construct_graph(statements, &closure_envs, &mut graph);
+
+        // Find strongly connected components by sorting and searching the transposed graph.
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+        for node in graph.keys().sorted() {
+            if !visited.contains(node) {
+                Self::topsort(&graph, node, &mut visited, &mut order);
+            }
+        }
+        let transpose = Self::transpose(&graph);
+        visited = HashSet::new();
+        let mut cycles = Vec::new();
+        for node in order.into_iter().rev() {
+            if visited.contains(&node) {
+                continue;
+            }
+            let mut component = Vec::new();
+            Self::topsort(&transpose, &node, &mut visited, &mut component);
+            let component: HashSet<_> = component.into_iter().collect();
+            let edges = component
+                .iter()
+                .flat_map(|node| graph.get(node).cloned().unwrap_or_default())
+                .filter(|(neighbor, _)| component.contains(neighbor))
+                .collect_vec();
+            if component.len() > 1 || !edges.is_empty() {
+                cycles.push(StrongCycle {
+                    memory: component.into_iter().sorted().collect(),
+                    unweakened: edges
+                        .into_iter()
+                        .filter_map(|(_, entry)| entry)
+                        .sorted()
+                        .dedup()
+                        .collect(),
+                });
+            }
+        }
+        cycles
+    }
+    /// Record which fn each environment tuple belongs to.
+    fn collect_closure_envs(statements: &Vec<Statement>, closure_envs: &mut HashMap<Memory, Name>) {
+        for statement in statements {
+            match statement {
+                Statement::Assignment(Assignment {
+                    target: _,
+                    value:
+                        Expression::ClosureInstantiation(ClosureInstantiation {
+                            name,
+                            env: Some(Value::Memory(memory)),
+                        }),
+                }) => {
+                    closure_envs.insert(memory.clone(), name.clone());
+                }
+                Statement::IfStatement(IfStatement {
+                    condition: _,
+                    branches,
+                }) => {
+                    Self::collect_closure_envs(&branches.0, closure_envs);
+                    Self::collect_closure_envs(&branches.1, closure_envs);
+                }
+                Statement::MatchStatement(MatchStatement {
+                    expression: _,
+                    branches,
+                    auxiliary_memory: _,
+                }) => {
+                    for branch in branches {
+                        Self::collect_closure_envs(&branch.statements, closure_envs);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    /// Construct a graph of strong references, labelling references from closure environments.
+    fn construct_graph(
+        &self,
+        statements: &Vec<Statement>,
+        closure_envs: &HashMap<Memory, Name>,
+        graph: &mut Graph,
+    ) {
+        for statement in statements {
+            match statement {
+                Statement::Assignment(Assignment { target, value }) => {
+                    let references = match value {
+                        Expression::Value(Value::Memory(memory)) => vec![(memory.clone(), None)],
+                        Expression::TupleExpression(TupleExpression(values)) => values
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, value)| {
+                                let memory = value.filter_memory()?;
+                                match closure_envs.get(target) {
+                                    Some(name) => {
+                                        if let Some(MachineType::WeakFnType(_)) =
+                                            self.envs.get(name).and_then(|env| env.get(i))
+                                        {
+                                            None
+                                        } else {
+                                            Some((memory, Some((name.clone(), i))))
+                                        }
+                                    }
+                                    None => Some((memory, None)),
+                                }
+                            })
+                            .collect_vec(),
+                        Expression::ClosureInstantiation(ClosureInstantiation {
+                            name: _,
+                            env: Some(Value::Memory(memory)),
+                        }) => vec![(memory.clone(), None)],
+                        // Other expressions do not store references to their inputs.
+                        _ => Vec::new(),
+                    };
+                    graph.entry(target.clone()).or_default().extend(references);
+                }
+                Statement::IfStatement(IfStatement {
+                    condition: _,
+                    branches,
+                }) => {
+                    self.construct_graph(&branches.0, closure_envs, graph);
+                    self.construct_graph(&branches.1, closure_envs, graph);
+                }
+                Statement::MatchStatement(MatchStatement {
+                    expression: _,
+                    branches,
+                    auxiliary_memory: _,
+                }) => {
+                    for branch in branches {
+                        self.construct_graph(&branch.statements, closure_envs, graph);
+                    }
+                }
+                Statement::Await(_)
+                | Statement::Declaration(_)
+                | Statement::Allocation(_)
+                | Statement::Enqueue(_)
+                | Statement::TailCall(_) => {}
+            }
+        }
+    }
+    fn transpose(graph: &Graph) -> Graph {
+        let mut transpose = Graph::new();
+        for (node, neighbors) in graph {
+            for (neighbor, entry) in neighbors {
+                transpose
+                    .entry(neighbor.clone())
+                    .or_default()
+                    .push((node.clone(), entry.clone()));
+            }
+        }
+        transpose
+    }
+    /// Topologically sort nodes, storing the result into `order`.
+    fn topsort(
+        graph: &Graph,
+        node: &Memory,
+        visited: &mut HashSet<Memory>,
+        order: &mut Vec<Memory>,
+    ) {
+        visited.insert(node.clone());
+        for (neighbor, _) in graph.get(node).cloned().unwrap_or_default() {
+            if !visited.contains(&neighbor) {
+                Self::topsort(graph, &neighbor, visited, order);
+            }
+        }
+        order.push(node.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{weakener::Weakener, Declaration, FnDef, FnType, Id};
+
+    use super::*;
+
+    use lowering::{AtomicTypeEnum, Integer};
+    use test_case::test_case;
+
+    fn closure_def(name: &str, env: Vec<MachineType>) -> FnDef {
+        FnDef {
+            name: Name::from(name),
+            arguments: Vec::new(),
+            statements: Vec::new(),
+            ret: (Integer { value: 0 }.into(), AtomicTypeEnum::INT.into()),
+            env,
+            is_recursive: true,
+            size_bounds: (0, 0),
+        }
+    }
+
+    fn main_def(statements: Vec<Statement>) -> FnDef {
+        FnDef {
+            name: Name::from("Main"),
+            arguments: Vec::new(),
+            statements,
+            ret: (Integer { value: 0 }.into(), AtomicTypeEnum::INT.into()),
+            env: Vec::new(),
+            is_recursive: false,
+            size_bounds: (0, 0),
+        }
+    }
+
+    fn fn_type() -> MachineType {
+        FnType(Vec::new(), Box::new(AtomicTypeEnum::INT.into())).into()
+    }
+
+    #[test_case(
+        Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![
+                main_def(vec![
+                    Assignment {
+                        target: Memory(Id::from("m0")),
+                        value: TupleExpression(vec![
+                            Integer { value: 1 }.into(),
+                            Integer { value: 2 }.into(),
+                        ]).into(),
+                    }.into(),
+                    Assignment {
+                        target: Memory(Id::from("m1")),
+                        value: TupleExpression(vec![
+                            Memory(Id::from("m0")).into(),
+                            Memory(Id::from("m0")).into(),
+                        ]).into(),
+                    }.into(),
+                ])
+            ]
+        },
+        Vec::new();
+        "acyclic tuples"
+    )]
+    #[test_case(
+        Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![
+                closure_def("F0", vec![fn_type()]),
+                main_def(vec![
+                    Declaration {
+                        type_: fn_type(),
+                        memory: Memory(Id::from("m0")),
+                    }.into(),
+                    Assignment {
+                        target: Memory(Id::from("m1")),
+                        value: TupleExpression(vec![Memory(Id::from("m0")).into()]).into(),
+                    }.into(),
+                    Assignment {
+                        target: Memory(Id::from("m0")),
+                        value: ClosureInstantiation {
+                            name: Name::from("F0"),
+                            env: Some(Memory(Id::from("m1")).into()),
+                        }.into(),
+                    }.into(),
+                ])
+            ]
+        },
+        vec![
+            StrongCycle {
+                memory: vec![Memory(Id::from("m0")), Memory(Id::from("m1"))],
+                unweakened: vec![(Name::from("F0"), 0)],
+            }
+        ];
+        "self-recursive closure"
+    )]
+    #[test_case(
+        Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![
+                closure_def("F0", vec![fn_type()]),
+                closure_def("F1", vec![AtomicTypeEnum::INT.into(), fn_type()]),
+                main_def(vec![
+                    Declaration {
+                        type_: fn_type(),
+                        memory: Memory(Id::from("m0")),
+                    }.into(),
+                    Declaration {
+                        type_: fn_type(),
+                        memory: Memory(Id::from("m2")),
+                    }.into(),
+                    Assignment {
+                        target: Memory(Id::from("m1")),
+                        value: TupleExpression(vec![Memory(Id::from("m2")).into()]).into(),
+                    }.into(),
+                    Assignment {
+                        target: Memory(Id::from("m0")),
+                        value: ClosureInstantiation {
+                            name: Name::from("F0"),
+                            env: Some(Memory(Id::from("m1")).into()),
+                        }.into(),
+                    }.into(),
+                    Assignment {
+                        target: Memory(Id::from("m3")),
+                        value: TupleExpression(vec![
+                            Integer { value: 5 }.into(),
+                            Memory(Id::from("m0")).into(),
+                        ]).into(),
+                    }.into(),
+                    Assignment {
+                        target: Memory(Id::from("m2")),
+                        value: ClosureInstantiation {
+                            name: Name::from("F1"),
+                            env: Some(Memory(Id::from("m3")).into()),
+                        }.into(),
+                    }.into(),
+                ])
+            ]
+        },
+        vec![
+            StrongCycle {
+                memory: vec![
+                    Memory(Id::from("m0")),
+                    Memory(Id::from("m1")),
+                    Memory(Id::from("m2")),
+                    Memory(Id::from("m3")),
+                ],
+                unweakened: vec![(Name::from("F0"), 0), (Name::from("F1"), 1)],
+            }
+        ];
+        "mutually recursive closures"
+    )]
+    fn test_detect_cycles(program: Program, expected_cycles: Vec<StrongCycle>) {
+        assert_eq!(CycleDetector::detect(&program), expected_cycles);
+        assert_eq!(
+            CycleDetector::detect(&Weakener::new().weaken_program(program)),
+            Vec::new()
+        );
+    }
+}
@@ -1,17 +1,146 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 
 use crate::{
-    Assignment, Await, Enqueue, Expression, FnDef, IfStatement, MatchBranch, MatchStatement,
-    Memory, Program, Statement, Value,
+    translator::BUILT_IN_SIZES, Assignment, Await, BuiltIn, ClosureInstantiation, Enqueue,
+    Expression, FnCall, FnDef, IfStatement, MatchBranch, MatchStatement, Memory, Name, Program,
+    Statement, Value,
 };
 
-pub struct Enqueuer {}
+/// Calls that are run together as a single task.
+struct Batch {
+    size: usize,
+    // Memory read by calls in the batch, which must not be computed by another call in the batch.
+    dependencies: HashSet<Memory>,
+}
+
+pub struct Enqueuer {
+    min_task_size: usize,
+    batch_size: usize,
+    fn_sizes: HashMap<Name, usize>,
+    closures: HashMap<Memory, Name>,
+}
 
 impl Enqueuer {
     fn new() -> Self {
-        Self {}
+        Self {
+            min_task_size: 0,
+            batch_size: 0,
+            fn_sizes: HashMap::new(),
+            closures: HashMap::new(),
+        }
+    }
+    fn from_program(program: &Program, min_task_size: usize, batch_size: usize) -> Self {
+        let mut enqueuer = Self::new();
+        enqueuer.min_task_size = min_task_size;
+        enqueuer.batch_size = batch_size;
+        for fn_def in &program.fn_defs {
+            enqueuer
+                .fn_sizes
+                .insert(fn_def.name.clone(), fn_def.size_bounds.1);
+            enqueuer.collect_closures(&fn_def.statements);
+        }
+        enqueuer
+    }
+    /// Record which fn each closure instantiates.
+    fn collect_closures(&mut self, statements: &Vec<Statement>) {
+        for statement in statements {
+            match statement {
+                Statement::Assignment(Assignment {
+                    target,
+                    value: Expression::ClosureInstantiation(ClosureInstantiation { name, env: _ }),
+                }) => {
+                    self.closures.insert(target.clone(), name.clone());
+                }
+                Statement::IfStatement(IfStatement {
+                    condition: _,
+                    branches,
+                }) => {
+                    self.collect_closures(&branches.0);
+                    self.collect_closures(&branches.1);
+                }
+                Statement::MatchStatement(MatchStatement {
+                    expression: _,
+                    branches,
+                    auxiliary_memory: _,
+                }) => {
+                    for branch in branches {
+                        self.collect_closures(&branch.statements);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    /// Estimate the size of a call, if it is known.
+    fn call_size(&self, expression: &Expression) -> Option<usize> {
+        match expression {
+            Expression::FnCall(FnCall {
+                fn_: Value::Memory(memory),
+                fn_type: _,
+                args: _,
+            }) => self
+                .closures
+                .get(memory)
+                .and_then(|name| self.fn_sizes.get(name))
+                .copied(),
+            Expression::FnCall(FnCall {
+                fn_: Value::BuiltIn(BuiltIn::BuiltInFn(name) | BuiltIn::GenericBuiltInFn(name, _)),
+                fn_type: _,
+                args: _,
+            }) => BUILT_IN_SIZES.get(name).copied(),
+            _ => None,
+        }
+    }
+    /// Determine whether a call is estimated to be too cheap to run as a separate task.
+    fn is_small(&self, expression: &Expression) -> bool {
+        matches!(
+            expression,
+            Expression::FnCall(FnCall {
+                fn_: Value::Memory(_),
+                fn_type: _,
+                args: _,
+            })
+        ) && self
+            .call_size(expression)
+            .is_some_and(|size| size < self.min_task_size)
+    }
+    /// Add a call to the current batch (which follows it), returning whether it was added.
+    /// Otherwise, the call starts a new batch if it fits in one.
+    fn extend_batch(
+        &self,
+        batch: &mut Option<Batch>,
+        target: &Memory,
+        expression: &Expression,
+    ) -> bool {
+        if self.batch_size == 0 {
+            return false;
+        }
+        let Some(size) = self.call_size(expression) else {
+            *batch = None;
+            return false;
+        };
+        let dependencies = expression
+            .values()
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::Memory(memory) => Some(memory),
+                Value::BuiltIn(_) => None,
+            })
+            .collect_vec();
+        if let Some(batch) = batch {
+            if batch.size + size <= self.batch_size && !batch.dependencies.contains(target) {
+                batch.size += size;
+                batch.dependencies.extend(dependencies);
+                return true;
+            }
+        }
+        *batch = (size <= self.batch_size).then(|| Batch {
+            size,
+            dependencies: HashSet::from_iter(dependencies),
+        });
+        false
     }
     /// Add extra requirements to statements and order correctly.
     fn fix_statements(
@@ -29,6 +158,8 @@ impl Enqueuer {
         statements: Vec<Statement>,
         mut required: HashSet<Memory>,
     ) -> (Vec<Statement>, HashSet<Memory>) {
+        // Only the last call in each batch is enqueued; the others are run when they are awaited.
+        let mut batch = None;
         let statements = statements
             .into_iter()
             .rev()
@@ -41,6 +172,7 @@ impl Enqueuer {
                     ref target,
                     value: Expression::Value(ref value),
                 }) => {
+                    batch = None;
                     if required.remove(target) {
                         if let Value::Memory(memory) = value {
                             required.insert(memory.clone());
@@ -50,9 +182,13 @@ impl Enqueuer {
                 }
                 Statement::Assignment(Assignment {
                     ref target,
-                    value: _,
+                    ref value,
                 }) => {
-                    if required.remove(target) {
+                    // Small calls are run inline when they are awaited.
+                    if required.remove(target)
+                        && !self.is_small(value)
+                        && !self.extend_batch(&mut batch, target, value)
+                    {
                         vec![Enqueue(target.clone()).into(), statement]
                     } else {
                         vec![statement]
@@ -62,6 +198,7 @@ impl Enqueuer {
                     condition,
                     branches: (true_branch, false_branch),
                 }) => {
+                    batch = None;
                     let (true_branch, true_required) =
                         self.enqueue_statements(true_branch, required.clone());
                     let (false_branch, false_required) =
@@ -86,6 +223,7 @@ impl Enqueuer {
                     branches,
                     auxiliary_memory,
                 }) => {
+                    batch = None;
                     let ((statements, all_required), targets): ((Vec<_>, Vec<_>), Vec<_>) =
                         branches
                             .into_iter()
@@ -124,7 +262,16 @@ impl Enqueuer {
                     }
                     .into()]
                 }
-                statement => vec![statement],
+                Statement::TailCall(_) => {
+                    // Nothing after a tail call is executed.
+                    required.clear();
+                    batch = None;
+                    vec![statement]
+                }
+                statement => {
+                    batch = None;
+                    vec![statement]
+                }
             })
             .collect_vec();
         (statements, required)
@@ -157,9 +304,9 @@ impl Enqueuer {
         }
     }
     /// Update program with enqueue statements.
-    pub fn enqueue(program: Program) -> Program {
+    pub fn enqueue(program: Program, min_task_size: usize, batch_size: usize) -> Program {
+        let enqueuer = Enqueuer::from_program(&program, min_task_size, batch_size);
         let Program { type_defs, fn_defs } = program;
-        let enqueuer = Enqueuer::new();
         let fn_defs = fn_defs
             .into_iter()
             .map(|fn_def| enqueuer.enqueue_fn_def(fn_def))
@@ -172,7 +319,7 @@ impl Enqueuer {
 mod tests {
     use crate::{
         BuiltIn, Declaration, FnCall, FnDef, FnType, Id, IfStatement, MatchBranch, MatchStatement,
-        Name, TypeDef, UnionType, Value,
+        Name, TupleExpression, TypeDef, UnionType, Value,
     };
 
     use super::*;
@@ -798,7 +945,153 @@ mod tests {
         "simple program"
     )]
     fn test_enqueue_program(program: Program, expected_program: Program) {
-        let program = Enqueuer::enqueue(program);
+        let program = Enqueuer::enqueue(program, 0, 0);
         assert_eq!(expected_program, program);
     }
+
+    #[test_case(0, vec!["small_call", "large_call"]; "no threshold")]
+    #[test_case(100, vec!["large_call"]; "threshold between sizes")]
+    #[test_case(1000, Vec::new(); "threshold above sizes")]
+    fn test_enqueue_min_task_size(min_task_size: usize, expected_enqueued: Vec<&str>) {
+        let fn_type = FnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        );
+        let fn_def = |name: &str, size: usize| FnDef {
+            name: Name::from(name),
+            arguments: vec![(Memory(Id::from("x")), AtomicTypeEnum::INT.into())],
+            statements: Vec::new(),
+            ret: (Memory(Id::from("x")).into(), AtomicTypeEnum::INT.into()),
+            env: Vec::new(),
+            is_recursive: false,
+            size_bounds: (size, size),
+        };
+        let call = |target: &str, fn_: &str| {
+            Statement::from(Assignment {
+                target: Memory(Id::from(target)),
+                value: FnCall {
+                    fn_: Memory(Id::from(fn_)).into(),
+                    fn_type: fn_type.clone(),
+                    args: vec![Integer { value: 0 }.into()],
+                }
+                .into(),
+            })
+        };
+        let program = Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![
+                fn_def("Small", 5),
+                fn_def("Large", 500),
+                FnDef {
+                    name: Name::from("Main"),
+                    arguments: Vec::new(),
+                    statements: vec![
+                        Assignment {
+                            target: Memory(Id::from("small")),
+                            value: ClosureInstantiation {
+                                name: Name::from("Small"),
+                                env: None,
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        Assignment {
+                            target: Memory(Id::from("large")),
+                            value: ClosureInstantiation {
+                                name: Name::from("Large"),
+                                env: None,
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        call("small_call", "small"),
+                        call("large_call", "large"),
+                        Await(vec![
+                            Memory(Id::from("small_call")),
+                            Memory(Id::from("large_call")),
+                        ])
+                        .into(),
+                    ],
+                    ret: (Integer { value: 0 }.into(), AtomicTypeEnum::INT.into()),
+                    env: Vec::new(),
+                    is_recursive: false,
+                    size_bounds: (300, 300),
+                },
+            ],
+        };
+        let program = Enqueuer::enqueue(program, min_task_size, 0);
+        let enqueued = program
+            .fn_defs
+            .last()
+            .unwrap()
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Enqueue(Enqueue(memory)) => Some(memory.clone()),
+                _ => None,
+            })
+            .collect_vec();
+        assert_eq!(
+            enqueued,
+            expected_enqueued
+                .into_iter()
+                .map(|id| Memory(Id::from(id)))
+                .collect_vec()
+        );
+    }
+
+    #[test_case(0, false, 4; "no batching")]
+    #[test_case(18, false, 2; "batches of two")]
+    #[test_case(1000, false, 1; "single batch")]
+    #[test_case(1000, true, 4; "dependent calls")]
+    fn test_enqueue_batch_size(batch_size: usize, dependent: bool, expected_enqueues: usize) {
+        let plus = |target: &str, x: &str, y: &str| {
+            vec![
+                Await(vec![Memory(Id::from(x)), Memory(Id::from(y))]).into(),
+                Statement::from(Assignment {
+                    target: Memory(Id::from(target)),
+                    value: FnCall {
+                        fn_: BuiltIn::BuiltInFn(Name::from("Plus__BuiltIn")).into(),
+                        fn_type: FnType(
+                            vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                            Box::new(AtomicTypeEnum::INT.into()),
+                        ),
+                        args: vec![Memory(Id::from(x)).into(), Memory(Id::from(y)).into()],
+                    }
+                    .into(),
+                }),
+            ]
+        };
+        // `(a + b, a + a, b + b, b + a)`, or `(a + b, y0 + a, y1 + b, y2 + a)` when dependent.
+        let operands = if dependent {
+            [("a", "b"), ("y0", "a"), ("y1", "b"), ("y2", "a")]
+        } else {
+            [("a", "b"), ("a", "a"), ("b", "b"), ("b", "a")]
+        };
+        let targets = ["y0", "y1", "y2", "y3"];
+        let mut statements = targets
+            .iter()
+            .zip_eq(operands)
+            .flat_map(|(target, (x, y))| plus(target, x, y))
+            .collect_vec();
+        let memory = targets.map(|target| Memory(Id::from(target)));
+        statements.extend([
+            Await(memory.to_vec()).into(),
+            Assignment {
+                target: Memory(Id::from("t")),
+                value: TupleExpression(memory.map(Value::from).to_vec()).into(),
+            }
+            .into(),
+        ]);
+        let enqueuer = Enqueuer {
+            batch_size,
+            ..Enqueuer::new()
+        };
+        let (statements, _) = enqueuer.enqueue_statements(statements, HashSet::new());
+        let enqueues = statements
+            .iter()
+            .filter(|statement| matches!(statement, Statement::Enqueue(_)))
+            .count();
+        assert_eq!(enqueues, expected_enqueues);
+    }
 }
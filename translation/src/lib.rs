@@ -2,6 +2,8 @@ mod args;
 mod await_deduplicator;
 mod code_size;
 mod code_vector;
+mod compilation_cache;
+mod cycle_detector;
 mod enqueuer;
 mod machine_nodes;
 mod named_vector;
@@ -11,6 +13,9 @@ mod weakener;
 
 pub use args::TranslationArgs;
 pub use code_size::CodeSizeEstimator;
-pub use lowering::{AtomicTypeEnum, Boolean, Integer};
+pub use compilation_cache::CompilationCache;
+pub use cycle_detector::{CycleDetector, StrongCycle};
+pub use lowering::{AtomicTypeEnum, Boolean, Char, Integer, Str};
 pub use machine_nodes::*;
 pub use translator::Translator;
+pub use weakener::WeakenedEdge;
@@ -1,17 +1,19 @@
 use from_variants::FromVariants;
 use itertools::Itertools;
-use lowering::{AtomicTypeEnum, Boolean, Integer};
+use lowering::{AtomicTypeEnum, Boolean, Char, Integer, Str};
+use serde::{Deserialize, Serialize};
 
 pub type Name = String;
 pub type Id = String;
 
-#[derive(Clone, Debug, FromVariants, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, FromVariants, Hash, PartialEq, Eq)]
 pub enum MachineType {
     AtomicType(AtomicType),
     TupleType(TupleType),
     FnType(FnType),
     WeakFnType(FnType),
     UnionType(UnionType),
+    ArrayType(ArrayType),
     NamedType(Name),
 }
 
@@ -21,17 +23,19 @@ impl From<AtomicTypeEnum> for MachineType {
     }
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct AtomicType(pub AtomicTypeEnum);
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct TupleType(pub Vec<MachineType>);
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct FnType(pub Vec<MachineType>, pub Box<MachineType>);
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct UnionType(pub Vec<Name>);
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ArrayType(pub Box<MachineType>);
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct TypeDef {
     pub name: Name,
     pub constructors: Vec<(Name, Option<MachineType>)>,
@@ -59,6 +63,7 @@ impl TypeDef {
                 types
             }
             MachineType::UnionType(UnionType(names)) => names.clone(),
+            MachineType::ArrayType(ArrayType(type_)) => self.used_types(type_),
             MachineType::NamedType(name) => vec![name.clone()],
         }
     }
@@ -70,7 +75,7 @@ impl TypeDef {
     }
 }
 
-#[derive(Clone, Debug, FromVariants, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, FromVariants, PartialEq, Eq)]
 pub enum Value {
     BuiltIn(BuiltIn),
     Memory(Memory),
@@ -97,17 +102,33 @@ impl From<Boolean> for Value {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+impl From<Str> for Value {
+    fn from(value: Str) -> Self {
+        BuiltIn::from(value).into()
+    }
+}
+
+impl From<Char> for Value {
+    fn from(value: Char) -> Self {
+        BuiltIn::from(value).into()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Memory(pub Id);
 
-#[derive(Clone, Debug, FromVariants, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, FromVariants, PartialEq, Eq)]
 pub enum BuiltIn {
     Integer(Integer),
     Boolean(Boolean),
+    Str(Str),
+    Char(Char),
     BuiltInFn(Name),
+    /// Built-in fn that is instantiated with type parameters.
+    GenericBuiltInFn(Name, Vec<MachineType>),
 }
 
-#[derive(Clone, Debug, FromVariants, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, FromVariants, PartialEq, Eq)]
 pub enum Expression {
     Value(Value),
     ElementAccess(ElementAccess),
@@ -154,36 +175,36 @@ impl From<Memory> for Expression {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ElementAccess {
     pub value: Value,
     pub idx: usize,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct TupleExpression(pub Vec<Value>);
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct FnCall {
     pub fn_: Value,
     pub fn_type: FnType,
     pub args: Vec<Value>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ConstructorCall {
     pub type_: Name,
     pub idx: usize,
     pub data: Option<(Name, Value)>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ClosureInstantiation {
     pub name: Name,
     pub env: Option<Value>,
 }
 
-#[derive(Clone, Debug, FromVariants, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, FromVariants, PartialEq)]
 pub enum Statement {
     Await(Await),
     Declaration(Declaration),
@@ -192,53 +213,60 @@ pub enum Statement {
     IfStatement(IfStatement),
     MatchStatement(MatchStatement),
     Enqueue(Enqueue),
+    TailCall(TailCall),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Await(pub Vec<Memory>);
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Enqueue(pub Memory);
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Reassign the arguments of the current fn and restart its body.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TailCall {
+    pub arguments: Vec<(Memory, Value)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Declaration {
     pub type_: MachineType,
     pub memory: Memory,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Allocation {
     pub name: Name,
     pub fns: Vec<(Memory, Name)>,
     pub target: Memory,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Assignment {
     pub target: Memory,
     pub value: Expression,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct IfStatement {
     pub condition: Value,
     pub branches: (Vec<Statement>, Vec<Statement>),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct MatchStatement {
     pub expression: (Value, UnionType),
     pub branches: Vec<MatchBranch>,
     pub auxiliary_memory: Memory,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct MatchBranch {
     pub target: Option<Memory>,
     pub statements: Vec<Statement>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FnDef {
     pub name: Name,
     pub arguments: Vec<(Memory, MachineType)>,
@@ -261,7 +289,7 @@ impl PartialEq for FnDef {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Program {
     pub type_defs: Vec<TypeDef>,
     pub fn_defs: Vec<FnDef>,
@@ -5,8 +5,8 @@ use itertools::Either::{self, Left, Right};
 use itertools::Itertools;
 
 use crate::{
-    Assignment, Await, Expression, FnCall, IfStatement, MatchBranch, MatchStatement, Memory,
-    Program, Statement, Value,
+    translator::EFFECTFUL_BUILT_INS, Assignment, Await, BuiltIn, Expression, FnCall, IfStatement,
+    MatchBranch, MatchStatement, Memory, Program, Statement, Value,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -73,12 +73,15 @@ type Graph = HashMap<Memory, Node>;
 #[derive(Debug, Clone)]
 pub struct StatementReorderer {
     fn_calls: HashSet<Memory>,
+    /// Keep assignments after the values that they await, which orders impure arguments.
+    strict_arg_order: bool,
 }
 
 impl StatementReorderer {
     fn new() -> Self {
         Self {
             fn_calls: HashSet::new(),
+            strict_arg_order: false,
         }
     }
 
@@ -111,6 +114,18 @@ impl StatementReorderer {
         }
     }
 
+    /// Determine whether an expression calls a built-in fn with side-effects.
+    fn is_effectful(expression: &Expression) -> bool {
+        match expression {
+            Expression::FnCall(FnCall {
+                fn_: Value::BuiltIn(BuiltIn::BuiltInFn(name) | BuiltIn::GenericBuiltInFn(name, _)),
+                fn_type: _,
+                args: _,
+            }) => EFFECTFUL_BUILT_INS.contains(name),
+            _ => false,
+        }
+    }
+
     /// Batch statements into blocks that can be independently reordered.
     fn batch_statements(
         &self,
@@ -173,26 +188,36 @@ impl StatementReorderer {
     fn construct_graph(&self, statements: Vec<Statement>) -> Graph {
         let mut graph = Graph::new();
         let mut last_await = None;
+        let mut last_effect = None;
         for statement in statements {
             match statement {
                 Statement::Await(Await(memory)) => last_await = Some(memory),
                 Statement::Assignment(Assignment { target, value }) => {
+                    let awaits = last_await.take().unwrap_or_default();
+                    // Effectful calls depend on the previous one so that their effects stay in source order.
+                    let previous_effect = if Self::is_effectful(&value) {
+                        last_effect.replace(target.clone())
+                    } else {
+                        None
+                    };
+                    // With a strict argument order, awaited values must also be computed before the assignment.
+                    let ordered_awaits = if self.strict_arg_order {
+                        awaits.clone()
+                    } else {
+                        Vec::new()
+                    };
+                    let dependencies = value
+                        .values()
+                        .iter()
+                        .filter_map(Value::filter_memory)
+                        .chain(ordered_awaits)
+                        .chain(previous_effect)
+                        .collect();
                     let node = Node {
-                        dependencies: value
-                            .values()
-                            .iter()
-                            .filter_map(Value::filter_memory)
-                            .collect(),
+                        dependencies,
                         dependents: Vec::new(),
                         is_fn: self.fn_calls.contains(&target),
-                        awaits: {
-                            if let Some(awaits) = last_await {
-                                last_await = None;
-                                awaits
-                            } else {
-                                Vec::new()
-                            }
-                        },
+                        awaits,
                         memory: target.clone(),
                         expression: value,
                         fn_dependents: None,
@@ -341,11 +366,12 @@ impl StatementReorderer {
             .collect_vec();
         statements
     }
-    /// Reorder statements in a program.
-    pub fn reorder(mut program: Program) -> Program {
+    /// Reorder statements in a program, keeping awaited values before the assignments that await them if `strict_arg_order` is set.
+    pub fn reorder(mut program: Program, strict_arg_order: bool) -> Program {
         for fn_def in program.fn_defs.iter_mut() {
-            fn_def.statements =
-                StatementReorderer::new().reorder_statements(fn_def.statements.clone());
+            let mut reorderer = StatementReorderer::new();
+            reorderer.strict_arg_order = strict_arg_order;
+            fn_def.statements = reorderer.reorder_statements(fn_def.statements.clone());
         }
         program
     }
@@ -355,8 +381,8 @@ impl StatementReorderer {
 mod tests {
     use crate::{
         Assignment, Await, BuiltIn, ClosureInstantiation, Declaration, FnCall, FnDef, FnType, Id,
-        IfStatement, MatchBranch, MatchStatement, Name, Statement, TupleExpression, TypeDef,
-        UnionType,
+        IfStatement, MatchBranch, MatchStatement, Name, Statement, TupleExpression, TupleType,
+        TypeDef, UnionType,
     };
 
     use super::*;
@@ -2007,7 +2033,63 @@ mod tests {
         "program with match"
     )]
     fn test_reorder_program(program: Program, expected_program: Program) {
-        let reordered_program = StatementReorderer::reorder(program);
+        let reordered_program = StatementReorderer::reorder(program, false);
         assert_eq!(expected_program, reordered_program)
     }
+
+    #[test]
+    fn test_effectful_calls_keep_order() {
+        let assert = |target: &str, arg: Value| -> Statement {
+            Assignment {
+                target: Memory(Id::from(target)),
+                value: FnCall {
+                    fn_: BuiltIn::BuiltInFn(Name::from("Assert__BuiltIn")).into(),
+                    fn_type: FnType(
+                        vec![AtomicTypeEnum::BOOL.into()],
+                        Box::new(TupleType(Vec::new()).into()),
+                    ),
+                    args: vec![arg],
+                }
+                .into(),
+            }
+            .into()
+        };
+        // `(assert(f(5)), assert(true))`, where the heuristics prefer the second assertion.
+        let statements = vec![
+            Await(vec![Memory(Id::from("f"))]).into(),
+            Assignment {
+                target: Memory(Id::from("y")),
+                value: FnCall {
+                    fn_: Memory(Id::from("f")).into(),
+                    fn_type: FnType(
+                        vec![AtomicTypeEnum::INT.into()],
+                        Box::new(AtomicTypeEnum::BOOL.into()),
+                    ),
+                    args: vec![Integer { value: 5 }.into()],
+                }
+                .into(),
+            }
+            .into(),
+            assert("e1", Memory(Id::from("y")).into()),
+            assert("e2", Boolean { value: true }.into()),
+            Assignment {
+                target: Memory(Id::from("t")),
+                value: TupleExpression(vec![
+                    Memory(Id::from("e1")).into(),
+                    Memory(Id::from("e2")).into(),
+                ])
+                .into(),
+            }
+            .into(),
+        ];
+        let reordered = StatementReorderer::new().reorder_statements(statements);
+        let targets = reordered
+            .into_iter()
+            .filter_map(|statement| match statement {
+                Statement::Assignment(Assignment { target, value: _ }) => Some(target.0),
+                _ => None,
+            })
+            .collect_vec();
+        assert_eq!(targets, vec!["y", "e1", "e2", "t"]);
+    }
 }
@@ -1,12 +1,22 @@
-use std::{cell::RefCell, collections::HashMap, path::Path, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::Path,
+    rc::Rc,
+};
 
 use crate::{
-    await_deduplicator::AwaitDeduplicator, code_vector::CodeVectorCalculator, enqueuer::Enqueuer,
-    statement_reorderer::StatementReorderer, weakener::Weakener, Assignment, Await, BuiltIn,
-    ClosureInstantiation, CodeSizeEstimator, ConstructorCall, Declaration, ElementAccess,
-    Expression, FnCall, FnDef, FnType, Id, IfStatement, MachineType, MatchBranch, MatchStatement,
-    Memory, Name, Program, Statement, TranslationArgs, TupleExpression, TupleType, TypeDef,
-    UnionType, Value,
+    await_deduplicator::AwaitDeduplicator,
+    code_size::CODE_SIZE_CONSTANTS,
+    code_vector::CodeVectorCalculator,
+    compilation_cache::CompilationCache,
+    enqueuer::Enqueuer,
+    statement_reorderer::StatementReorderer,
+    weakener::{WeakenedEdge, Weakener},
+    ArrayType, Assignment, Await, BuiltIn, ClosureInstantiation, CodeSizeEstimator,
+    ConstructorCall, CycleDetector, Declaration, ElementAccess, Expression, FnCall, FnDef, FnType,
+    Id, IfStatement, MachineType, MatchBranch, MatchStatement, Memory, Name, Program, Statement,
+    StrongCycle, TailCall, TranslationArgs, TupleExpression, TupleType, TypeDef, UnionType, Value,
 };
 use itertools::Itertools;
 use lowering::*;
@@ -24,29 +34,146 @@ const OPERATOR_NAMES: Lazy<HashMap<Id, Id>> = Lazy::new(|| {
             ("%", "Modulo__BuiltIn"),
             ("<<", "Left_Shift__BuiltIn"),
             (">>", "Right_Shift__BuiltIn"),
+            (">>>", "Logical_Right_Shift__BuiltIn"),
             ("<=>", "Spaceship__BuiltIn"),
             ("&", "Bitwise_And__BuiltIn"),
             ("|", "Bitwise_Or__BuiltIn"),
             ("^", "Bitwise_Xor__BuiltIn"),
             ("++", "Increment__BuiltIn"),
             ("--", "Decrement__BuiltIn"),
+            ("~", "Bitwise_Not__BuiltIn"),
+            ("min", "Minimum__BuiltIn"),
+            ("max", "Maximum__BuiltIn"),
+            ("abs", "Absolute__BuiltIn"),
+            ("fma", "Fused_Multiply_Add__BuiltIn"),
+            ("+sat", "Saturating_Plus__BuiltIn"),
+            ("-sat", "Saturating_Minus__BuiltIn"),
+            ("*sat", "Saturating_Multiply__BuiltIn"),
+            ("+wrap", "Wrapping_Plus__BuiltIn"),
+            ("-wrap", "Wrapping_Minus__BuiltIn"),
+            ("*wrap", "Wrapping_Multiply__BuiltIn"),
             ("<", "Comparison_LT__BuiltIn"),
             ("<=", "Comparison_LE__BuiltIn"),
             (">", "Comparison_GT__BuiltIn"),
             (">=", "Comparison_GE__BuiltIn"),
             ("==", "Comparison_EQ__BuiltIn"),
             ("!=", "Comparison_NE__BuiltIn"),
+            ("&&", "Conjunction__BuiltIn"),
+            ("||", "Disjunction__BuiltIn"),
             ("!", "Negation__BuiltIn"),
+            ("assert", "Assert__BuiltIn"),
+            ("concat", "Concat__BuiltIn"),
+            ("length", "Length__BuiltIn"),
+            ("ord", "Ord__BuiltIn"),
+            ("chr", "Chr__BuiltIn"),
+        ]
+        .into_iter()
+        .map(|(op, name)| (Id::from(op), Id::from(name))),
+    )
+});
+
+static ARRAY_OPERATOR_NAMES: Lazy<HashMap<Id, Id>> = Lazy::new(|| {
+    // Names for the built-in fns that operate on arrays.
+    HashMap::from_iter(
+        [
+            ("empty", "Array_Empty__BuiltIn"),
+            ("push", "Array_Push__BuiltIn"),
+            ("get", "Array_Get__BuiltIn"),
+            ("len", "Array_Length__BuiltIn"),
         ]
         .into_iter()
         .map(|(op, name)| (Id::from(op), Id::from(name))),
     )
 });
 
+static PARALLEL_OPERATOR_NAMES: Lazy<HashMap<Id, Id>> = Lazy::new(|| {
+    // Names for the built-in fns that fan out over arrays.
+    HashMap::from_iter(
+        [
+            ("pmap", "Array_Parallel_Map__BuiltIn"),
+            ("preduce", "Array_Parallel_Reduce__BuiltIn"),
+        ]
+        .into_iter()
+        .map(|(op, name)| (Id::from(op), Id::from(name))),
+    )
+});
+
+static STRING_OPERATOR_NAMES: Lazy<HashMap<Id, Id>> = Lazy::new(|| {
+    // Names for the built-in operators overloaded for strings.
+    HashMap::from_iter(
+        [
+            ("==", "String_Comparison_EQ__BuiltIn"),
+            ("!=", "String_Comparison_NE__BuiltIn"),
+        ]
+        .into_iter()
+        .map(|(op, name)| (Id::from(op), Id::from(name))),
+    )
+});
+
+static CHAR_OPERATOR_NAMES: Lazy<HashMap<Id, Id>> = Lazy::new(|| {
+    // Names for the built-in operators overloaded for characters.
+    HashMap::from_iter(
+        [
+            ("==", "Char_Comparison_EQ__BuiltIn"),
+            ("!=", "Char_Comparison_NE__BuiltIn"),
+        ]
+        .into_iter()
+        .map(|(op, name)| (Id::from(op), Id::from(name))),
+    )
+});
+
+static UNARY_OPERATOR_NAMES: Lazy<HashMap<Id, Id>> = Lazy::new(|| {
+    // Names for the built-in operators overloaded for a single argument.
+    HashMap::from_iter(
+        [("-", "Negative__BuiltIn")]
+            .into_iter()
+            .map(|(op, name)| (Id::from(op), Id::from(name))),
+    )
+});
+
+pub(crate) static BUILT_IN_SIZES: Lazy<HashMap<Name, usize>> = Lazy::new(|| {
+    // Estimated sizes of the built-in fns, looked up by their translated names.
+    [
+        &*OPERATOR_NAMES,
+        &*ARRAY_OPERATOR_NAMES,
+        &*PARALLEL_OPERATOR_NAMES,
+        &*STRING_OPERATOR_NAMES,
+        &*CHAR_OPERATOR_NAMES,
+        &*UNARY_OPERATOR_NAMES,
+    ]
+    .into_iter()
+    .flat_map(|names| {
+        names
+            .iter()
+            .filter_map(|(op, name)| Some((name.clone(), *CODE_SIZE_CONSTANTS.operators.get(op)?)))
+            .collect_vec()
+    })
+    .collect()
+});
+
+pub(crate) static EFFECTFUL_BUILT_INS: Lazy<HashSet<Name>> = Lazy::new(|| {
+    // Translated names of the built-in fns with side-effects, whose calls must stay in order.
+    IMPURE_BUILT_INS
+        .iter()
+        .filter_map(|id| match *id {
+            "error" => Some(Name::from("Error__BuiltIn")),
+            id => OPERATOR_NAMES.get(id).cloned(),
+        })
+        .collect()
+});
+
 type ReferenceNames = HashMap<*mut IntermediateType, MachineType>;
 type MemoryIds = HashMap<Register, Memory>;
 type TypeLookup = HashMap<IntermediateUnionType, (Name, UnionType)>;
 type FnDefs = Vec<FnDef>;
+type ArgumentOrderings = HashMap<Register, Vec<Register>>;
+
+/// The tail-recursive fn whose body is being translated.
+#[derive(Clone)]
+struct LoopedFn {
+    fn_: Memory,
+    arguments: Vec<Memory>,
+}
 
 pub struct Translator {
     reference_names: ReferenceNames,
@@ -54,6 +181,16 @@ pub struct Translator {
     type_lookup: TypeLookup,
     fn_defs: FnDefs,
     recursive_fns: RecursiveFns,
+    argument_orderings: ArgumentOrderings,
+    tail_call_loops: bool,
+    looped_fn: Option<LoopedFn>,
+    tail_position: bool,
+    tail_calls: HashSet<Memory>,
+    min_task_size: usize,
+    batch_size: usize,
+    strict_arg_order: bool,
+    weakened_edges: Vec<WeakenedEdge>,
+    strong_cycles: Vec<StrongCycle>,
 }
 
 impl Translator {
@@ -64,6 +201,16 @@ impl Translator {
             type_lookup: TypeLookup::new(),
             fn_defs: FnDefs::new(),
             recursive_fns: RecursiveFns::new(),
+            argument_orderings: ArgumentOrderings::new(),
+            tail_call_loops: true,
+            looped_fn: None,
+            tail_position: false,
+            tail_calls: HashSet::new(),
+            min_task_size: 0,
+            batch_size: 0,
+            strict_arg_order: false,
+            weakened_edges: Vec::new(),
+            strong_cycles: Vec::new(),
         }
     }
 
@@ -86,6 +233,9 @@ impl Translator {
             IntermediateType::IntermediateUnionType(union_type) => {
                 self.type_lookup[union_type].1.clone().into()
             }
+            IntermediateType::IntermediateArrayType(IntermediateArrayType(type_)) => {
+                ArrayType(Box::new(self.translate_type(type_))).into()
+            }
             IntermediateType::Reference(reference) => {
                 match self.reference_names.get(&reference.as_ptr()) {
                     Some(type_) => type_.clone(),
@@ -193,8 +343,72 @@ impl Translator {
             IntermediateValue::IntermediateBuiltIn(built_in) => Value::from(match built_in {
                 IntermediateBuiltIn::Boolean(boolean) => BuiltIn::from(boolean.clone()),
                 IntermediateBuiltIn::Integer(integer) => BuiltIn::from(integer.clone()),
-                IntermediateBuiltIn::BuiltInFn(BuiltInFn(name, _)) => {
-                    BuiltIn::BuiltInFn(OPERATOR_NAMES[name].clone()).into()
+                IntermediateBuiltIn::Str(string) => BuiltIn::from(string.clone()),
+                IntermediateBuiltIn::Char(character) => BuiltIn::from(*character),
+                IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                    name,
+                    IntermediateFnType(_, ret_type),
+                )) if name == "error" => {
+                    // Errors are instantiated with the type that they stand in for.
+                    BuiltIn::GenericBuiltInFn(
+                        Name::from("Error__BuiltIn"),
+                        vec![self.translate_type(ret_type)],
+                    )
+                }
+                IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                    name,
+                    IntermediateFnType(arg_types, ret_type),
+                )) if ARRAY_OPERATOR_NAMES.contains_key(name) => {
+                    // Array built-ins are instantiated with the type of their elements.
+                    let array_type = arg_types.first().unwrap_or(ret_type);
+                    let MachineType::ArrayType(ArrayType(element_type)) =
+                        self.translate_type(array_type)
+                    else {
+                        panic!("Array built-in does not operate on an array.")
+                    };
+                    BuiltIn::GenericBuiltInFn(
+                        ARRAY_OPERATOR_NAMES[name].clone(),
+                        vec![*element_type],
+                    )
+                }
+                IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                    name,
+                    IntermediateFnType(arg_types, ret_type),
+                )) if PARALLEL_OPERATOR_NAMES.contains_key(name) => {
+                    // Parallel built-ins are instantiated with the types of the elements
+                    // that they consume and produce.
+                    let element_types = [&arg_types[1], ret_type]
+                        .into_iter()
+                        .filter_map(|type_| match self.translate_type(type_) {
+                            MachineType::ArrayType(ArrayType(element_type)) => Some(*element_type),
+                            _ => None,
+                        })
+                        .collect_vec();
+                    BuiltIn::GenericBuiltInFn(PARALLEL_OPERATOR_NAMES[name].clone(), element_types)
+                }
+                IntermediateBuiltIn::BuiltInFn(BuiltInFn(
+                    name,
+                    IntermediateFnType(arg_types, _),
+                )) => {
+                    let operator_names =
+                        if arg_types.first() == Some(&AtomicTypeEnum::STRING.into()) {
+                            &STRING_OPERATOR_NAMES
+                        } else if arg_types.first() == Some(&AtomicTypeEnum::CHAR.into())
+                            && CHAR_OPERATOR_NAMES.contains_key(name)
+                        {
+                            &CHAR_OPERATOR_NAMES
+                        } else if arg_types.len() == 1 && UNARY_OPERATOR_NAMES.contains_key(name) {
+                            &UNARY_OPERATOR_NAMES
+                        } else {
+                            &OPERATOR_NAMES
+                        };
+                    // Extra built-ins keep their own names.
+                    BuiltIn::BuiltInFn(
+                        operator_names
+                            .get(name)
+                            .cloned()
+                            .unwrap_or_else(|| name.clone()),
+                    )
                 }
             }),
         }
@@ -282,7 +496,7 @@ impl Translator {
                 (Vec::new(), value.into())
             }
             IntermediateExpression::IntermediateLambda(lambda) => {
-                let (statements, closure_inst) = self.translate_lambda(lambda);
+                let (statements, closure_inst) = self.translate_lambda(lambda, None);
                 (statements, closure_inst.into())
             }
             IntermediateExpression::IntermediateIf(if_) => {
@@ -300,6 +514,7 @@ impl Translator {
             condition,
             branches: (true_block, false_block),
         } = if_;
+        let tail = std::mem::take(&mut self.tail_position);
         let target = IntermediateMemory::from(true_block.type_());
         let memory = self.translate_memory(&target);
         // Add declaration for shared result.
@@ -312,22 +527,28 @@ impl Translator {
         if let Value::Memory(mem) = &condition {
             statements.push(Await(vec![mem.clone()]).into())
         };
+        self.tail_position = tail;
         let (mut true_statements, true_value) = self.translate_block(true_block);
-        true_statements.push(
-            Assignment {
-                target: memory.clone(),
-                value: true_value.into(),
-            }
-            .into(),
-        );
+        if !self.is_tail_call(&true_value) {
+            true_statements.push(
+                Assignment {
+                    target: memory.clone(),
+                    value: true_value.into(),
+                }
+                .into(),
+            );
+        }
+        self.tail_position = tail;
         let (mut false_statements, false_value) = self.translate_block(false_block);
-        false_statements.push(
-            Assignment {
-                target: memory.clone(),
-                value: false_value.into(),
-            }
-            .into(),
-        );
+        if !self.is_tail_call(&false_value) {
+            false_statements.push(
+                Assignment {
+                    target: memory.clone(),
+                    value: false_value.into(),
+                }
+                .into(),
+            );
+        }
         statements.push(
             IfStatement {
                 condition,
@@ -339,7 +560,12 @@ impl Translator {
     }
     fn translate_match(&mut self, match_: IntermediateMatch) -> (Vec<Statement>, Value) {
         let IntermediateMatch { subject, branches } = match_;
-        let type_ = subject.type_();
+        let tail = std::mem::take(&mut self.tail_position);
+        // Values of referenced types are matched as their underlying union.
+        let type_ = match subject.type_() {
+            IntermediateType::Reference(reference) => reference.borrow().clone(),
+            type_ => type_,
+        };
         let MachineType::UnionType(union_type) = self.translate_type(&type_) else {
             panic!("Match expression subject has non-union type.")
         };
@@ -358,14 +584,17 @@ impl Translator {
         let branches = branches
             .into_iter()
             .map(|IntermediateMatchBranch { target, block }| {
+                self.tail_position = tail;
                 let (mut statements, value) = self.translate_block(block);
-                statements.push(
-                    Assignment {
-                        target: memory.clone(),
-                        value: value.into(),
-                    }
-                    .into(),
-                );
+                if !self.is_tail_call(&value) {
+                    statements.push(
+                        Assignment {
+                            target: memory.clone(),
+                            value: value.into(),
+                        }
+                        .into(),
+                    );
+                }
                 MatchBranch {
                     target: target.map(|arg| self.translate_arg(&arg)),
                     statements,
@@ -396,8 +625,31 @@ impl Translator {
             register,
         } = assignment;
         let type_ = self.translate_type(&expression.type_());
-        let (mut statements, value) = self.translate_expression(expression);
+        let (mut statements, value) = match expression {
+            IntermediateExpression::IntermediateLambda(lambda) => {
+                let (statements, closure_inst) = self.translate_lambda(lambda, Some(&register));
+                (statements, closure_inst.into())
+            }
+            expression => self.translate_expression(expression),
+        };
+        if let Some(registers) = self.argument_orderings.get(&register).cloned() {
+            // Wait for earlier impure arguments before evaluating this one.
+            let memory = registers
+                .iter()
+                .map(|register| self.translate_register(register))
+                .collect_vec();
+            match statements.first_mut() {
+                Some(Statement::Await(Await(awaits))) => awaits.extend(memory),
+                _ => statements.insert(0, Await(memory).into()),
+            }
+        }
         let memory = self.translate_register(&register);
+        if let Some(tail_call) = self.translate_tail_call(&value) {
+            // Loop back to the start of the fn instead of calling it.
+            statements.push(tail_call.into());
+            self.tail_calls.insert(memory);
+            return statements;
+        }
         let assignment = Assignment {
             target: memory.clone(),
             value: value.clone(),
@@ -424,15 +676,61 @@ impl Translator {
             .flat_map(|statement| self.translate_statement(statement))
             .collect()
     }
-    fn translate_block(&mut self, block: IntermediateBlock) -> (Vec<Statement>, Value) {
-        let statements = block
-            .statements
+    /// Translate statements, marking the one that computes the return value if the block is in tail position.
+    fn translate_tail_statements(
+        &mut self,
+        statements: Vec<IntermediateStatement>,
+        ret: &IntermediateValue,
+        tail: bool,
+    ) -> Vec<Statement> {
+        if !tail {
+            return self.translate_statements(statements);
+        }
+        let ret = ret.register();
+        let last = statements.len().saturating_sub(1);
+        statements
             .into_iter()
-            .flat_map(|statement| self.translate_statement(statement))
-            .collect();
+            .enumerate()
+            .flat_map(|(i, statement)| {
+                let IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                    expression: _,
+                    register,
+                }) = &statement;
+                self.tail_position = i == last && ret.as_ref() == Some(register);
+                let statements = self.translate_statement(statement);
+                self.tail_position = false;
+                statements
+            })
+            .collect()
+    }
+    fn translate_block(&mut self, block: IntermediateBlock) -> (Vec<Statement>, Value) {
+        let tail = std::mem::take(&mut self.tail_position);
+        let statements = self.translate_tail_statements(block.statements, &block.ret, tail);
         let value = self.translate_value(block.ret);
         (statements, value)
     }
+    /// Convert a call to the looped fn in tail position into a jump back to its start.
+    fn translate_tail_call(&self, value: &Expression) -> Option<TailCall> {
+        let LoopedFn { fn_, arguments } = self.looped_fn.as_ref()?;
+        match value {
+            Expression::FnCall(FnCall {
+                fn_: Value::Memory(memory),
+                fn_type: _,
+                args,
+            }) if self.tail_position && memory == fn_ => Some(TailCall {
+                arguments: arguments
+                    .iter()
+                    .cloned()
+                    .zip_eq(args.iter().cloned())
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+    /// Determine whether a value is the result of a call that was replaced with a loop.
+    fn is_tail_call(&self, value: &Value) -> bool {
+        matches!(value, Value::Memory(memory) if self.tail_calls.contains(memory))
+    }
     /// Substitute open variables in lambdas with new variables.
     fn replace_open_vars(
         &mut self,
@@ -477,10 +775,21 @@ impl Translator {
     fn translate_lambda(
         &mut self,
         mut lambda: IntermediateLambda,
+        register: Option<&Register>,
     ) -> (Vec<Statement>, ClosureInstantiation) {
         let is_recursive = self.recursive_fns.get(&lambda).cloned().unwrap_or(false);
+        let is_tail_recursive = self.tail_call_loops
+            && register
+                .is_some_and(|register| self.recursive_fns.tail_recursive.contains(register));
         // Replace open variables to determine environment.
         let env_values = self.replace_open_vars(&mut lambda);
+        // Find the register the fn uses to refer to itself.
+        let self_register = register.filter(|_| is_tail_recursive).and_then(|register| {
+            env_values
+                .iter()
+                .find(|(value, _)| value.register().as_ref() == Some(register))
+                .map(|(_, register)| register.clone())
+        });
         // Determine types of open variables.
         let env_types = env_values
             .iter()
@@ -505,7 +814,23 @@ impl Translator {
             .map(|arg| (self.translate_arg(&arg), self.translate_type(&arg.type_())))
             .collect_vec();
         let mut prefix = self.closure_prefix(&env_registers);
-        let mut statements = self.translate_statements(statements);
+        let looped_fn = self_register.map(|register| LoopedFn {
+            fn_: self.translate_register(&register),
+            arguments: args.iter().map(|(memory, _)| memory.clone()).collect(),
+        });
+        // A fn that immediately calls itself has no base case, so only calls in branches become loops.
+        let tail = looped_fn.is_some()
+            && matches!(
+                statements.last(),
+                Some(IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                    expression: IntermediateExpression::IntermediateIf(_)
+                        | IntermediateExpression::IntermediateMatch(_),
+                    register
+                })) if return_value.register().as_ref() == Some(register)
+            );
+        let enclosing_fn = std::mem::replace(&mut self.looped_fn, looped_fn);
+        let mut statements = self.translate_tail_statements(statements, &return_value, tail);
+        self.looped_fn = enclosing_fn;
         prefix.extend(statements);
         statements = prefix;
         let ret_type = self.translate_type(&return_value.type_());
@@ -545,11 +870,79 @@ impl Translator {
             (Vec::new(), ClosureInstantiation { name, env: None })
         }
     }
+    /// Record which impure arguments must be evaluated before others to maintain left-to-right order.
+    fn order_arguments(&mut self, program: &IntermediateProgram) {
+        let mut analyzer = PurityAnalyzer::new(program);
+        self.order_arguments_in_block(&program.main.block, &mut analyzer);
+    }
+    fn order_arguments_in_block(
+        &mut self,
+        block: &IntermediateBlock,
+        analyzer: &mut PurityAnalyzer,
+    ) {
+        let expressions: HashMap<_, _> = block
+            .statements
+            .iter()
+            .map(
+                |IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+                     expression,
+                     register,
+                 })| (register.clone(), expression),
+            )
+            .collect();
+        for IntermediateStatement::IntermediateAssignment(IntermediateAssignment {
+            expression,
+            register: _,
+        }) in &block.statements
+        {
+            match expression {
+                IntermediateExpression::IntermediateFnCall(IntermediateFnCall { fn_: _, args }) => {
+                    // Only arguments computed in this block can be sequenced.
+                    let impure_args = args
+                        .iter()
+                        .filter_map(IntermediateValue::register)
+                        .filter(|register| {
+                            expressions
+                                .get(register)
+                                .is_some_and(|expression| !analyzer.is_pure(expression))
+                        })
+                        .unique()
+                        .collect_vec();
+                    for (previous, next) in impure_args.into_iter().tuple_windows() {
+                        self.argument_orderings
+                            .entry(next)
+                            .or_default()
+                            .push(previous);
+                    }
+                }
+                IntermediateExpression::IntermediateLambda(IntermediateLambda {
+                    args: _,
+                    block,
+                }) => self.order_arguments_in_block(block, analyzer),
+                IntermediateExpression::IntermediateIf(IntermediateIf {
+                    condition: _,
+                    branches,
+                }) => {
+                    self.order_arguments_in_block(&branches.0, analyzer);
+                    self.order_arguments_in_block(&branches.1, analyzer);
+                }
+                IntermediateExpression::IntermediateMatch(IntermediateMatch {
+                    subject: _,
+                    branches,
+                }) => {
+                    for branch in branches {
+                        self.order_arguments_in_block(&branch.block, analyzer);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
     fn translate_program(&mut self, program: IntermediateProgram) -> Program {
         self.recursive_fns = RecursiveFnFinder::recursive_fns(&program);
         let IntermediateProgram { main, types } = program;
         let type_defs = self.translate_type_defs(types);
-        let (statements, _) = self.translate_lambda(main);
+        let (statements, _) = self.translate_lambda(main, None);
         // Check that main has no open variables.
         assert_eq!(statements.len(), 0);
         // Main is the last translated program.
@@ -559,18 +952,79 @@ impl Translator {
             fn_defs: self.fn_defs.clone(),
             type_defs,
         };
-        let program = Weakener::weaken(program);
-        let program = StatementReorderer::reorder(program);
+        let mut weakener = Weakener::new();
+        let program = weakener.weaken_program(program);
+        self.weakened_edges = weakener.weakened_edges().clone();
+        self.strong_cycles = CycleDetector::detect(&program);
+        let program = StatementReorderer::reorder(program, self.strict_arg_order);
         let program = AwaitDeduplicator::deduplicate(program);
-        let program = Enqueuer::enqueue(program);
+        let program = Enqueuer::enqueue(program, self.min_task_size, self.batch_size);
         program
     }
+    /// Translate a program, using the cache in `args.cache_dir` if it is set.
+    /// The cache is skipped when exporting code vectors, which needs the translation to run.
     pub fn translate(program: IntermediateProgram, args: TranslationArgs) -> Program {
+        let cache = match &args.cache_dir {
+            Some(dir) if args.export_vector_file.is_none() => {
+                Some(CompilationCache::new(dir, &program, &args))
+            }
+            _ => None,
+        };
+        if let Some(program) = cache.as_ref().and_then(CompilationCache::load) {
+            return program;
+        }
+        let program = Self::translate_with_weakened_edges(program, args).0;
+        if let Some(cache) = cache {
+            // The translation is still usable when the cache cannot be written.
+            if let Err(error) = cache.store(&program) {
+                eprintln!("Warning: failed to write to the compilation cache: {error}.");
+            }
+        }
+        program
+    }
+    /// Translate a program, also returning the edges between closures that were weakened to break cycles.
+    pub fn translate_with_weakened_edges(
+        program: IntermediateProgram,
+        args: TranslationArgs,
+    ) -> (Program, Vec<WeakenedEdge>) {
+        let (program, translator) = Self::run(program, args);
+        (program, translator.weakened_edges)
+    }
+    /// Translate a program, also returning any strong reference cycles that weakening did not break.
+    pub fn translate_with_cycles(
+        program: IntermediateProgram,
+        args: TranslationArgs,
+    ) -> (Program, Vec<StrongCycle>) {
+        let (program, translator) = Self::run(program, args);
+        (program, translator.strong_cycles)
+    }
+    /// Translate a program, also returning the register that each memory address was translated from.
+    pub fn translate_with_registers(
+        program: IntermediateProgram,
+        args: TranslationArgs,
+    ) -> (Program, HashMap<Memory, Register>) {
+        let (program, translator) = Self::run(program, args);
+        let registers = translator
+            .memory_ids
+            .into_iter()
+            .map(|(register, memory)| (memory, register))
+            .collect();
+        (program, registers)
+    }
+    fn run(program: IntermediateProgram, args: TranslationArgs) -> (Program, Self) {
         let mut translator = Translator::new();
         if let Some(filename) = args.export_vector_file {
             Self::export_vector(&program, filename).expect("Failed to save program")
         };
-        translator.translate_program(program)
+        if args.strict_arg_order {
+            translator.order_arguments(&program);
+        }
+        translator.strict_arg_order = args.strict_arg_order;
+        translator.tail_call_loops = !args.no_tail_call_loops;
+        translator.min_task_size = args.min_task_size;
+        translator.batch_size = args.batch_size;
+        let program = translator.translate_program(program);
+        (program, translator)
     }
     /// Export code vectors to a file.
     fn export_vector(program: &IntermediateProgram, filename: String) -> Result<(), String> {
@@ -731,6 +1185,205 @@ mod tests {
         ).into();
         "built-in fn"
     )]
+    #[test_case(
+        IntermediateBuiltIn::from(Str{value: String::from("")}).into(),
+        BuiltIn::from(Str{value: String::from("")}).into();
+        "empty string"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("=="),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::STRING.into(),AtomicTypeEnum::STRING.into()],
+                Box::new(AtomicTypeEnum::BOOL.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("String_Comparison_EQ__BuiltIn"),
+        ).into();
+        "string built-in fn"
+    )]
+    #[test_case(
+        IntermediateBuiltIn::from(Char{value: 97}).into(),
+        BuiltIn::from(Char{value: 97}).into();
+        "char"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("!="),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::CHAR.into(),AtomicTypeEnum::CHAR.into()],
+                Box::new(AtomicTypeEnum::BOOL.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("Char_Comparison_NE__BuiltIn"),
+        ).into();
+        "char built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("ord"),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::CHAR.into()],
+                Box::new(AtomicTypeEnum::INT.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("Ord__BuiltIn"),
+        ).into();
+        "char conversion built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("abs"),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::INT.into()],
+                Box::new(AtomicTypeEnum::INT.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("Absolute__BuiltIn"),
+        ).into();
+        "abs built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("+wrap"),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                Box::new(AtomicTypeEnum::INT.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("Wrapping_Plus__BuiltIn"),
+        ).into();
+        "wrapping built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("double"),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::INT.into()],
+                Box::new(AtomicTypeEnum::INT.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("double"),
+        ).into();
+        "extra built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("push"),
+            IntermediateFnType(
+                vec![
+                    IntermediateArrayType(Box::new(AtomicTypeEnum::INT.into())).into(),
+                    AtomicTypeEnum::INT.into()
+                ],
+                Box::new(IntermediateArrayType(Box::new(AtomicTypeEnum::INT.into())).into())
+            )
+        ).into(),
+        BuiltIn::GenericBuiltInFn(
+            Name::from("Array_Push__BuiltIn"),
+            vec![AtomicTypeEnum::INT.into()]
+        ).into();
+        "array built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("empty"),
+            IntermediateFnType(
+                Vec::new(),
+                Box::new(IntermediateArrayType(Box::new(AtomicTypeEnum::BOOL.into())).into())
+            )
+        ).into(),
+        BuiltIn::GenericBuiltInFn(
+            Name::from("Array_Empty__BuiltIn"),
+            vec![AtomicTypeEnum::BOOL.into()]
+        ).into();
+        "empty array built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("pmap"),
+            IntermediateFnType(
+                vec![
+                    IntermediateFnType(
+                        vec![AtomicTypeEnum::INT.into()],
+                        Box::new(AtomicTypeEnum::BOOL.into())
+                    ).into(),
+                    IntermediateArrayType(Box::new(AtomicTypeEnum::INT.into())).into()
+                ],
+                Box::new(IntermediateArrayType(Box::new(AtomicTypeEnum::BOOL.into())).into())
+            )
+        ).into(),
+        BuiltIn::GenericBuiltInFn(
+            Name::from("Array_Parallel_Map__BuiltIn"),
+            vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::BOOL.into()]
+        ).into();
+        "parallel map built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("preduce"),
+            IntermediateFnType(
+                vec![
+                    IntermediateFnType(
+                        vec![AtomicTypeEnum::INT.into(), AtomicTypeEnum::INT.into()],
+                        Box::new(AtomicTypeEnum::INT.into())
+                    ).into(),
+                    IntermediateArrayType(Box::new(AtomicTypeEnum::INT.into())).into()
+                ],
+                Box::new(AtomicTypeEnum::INT.into())
+            )
+        ).into(),
+        BuiltIn::GenericBuiltInFn(
+            Name::from("Array_Parallel_Reduce__BuiltIn"),
+            vec![AtomicTypeEnum::INT.into()]
+        ).into();
+        "parallel reduce built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("error"),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::STRING.into()],
+                Box::new(AtomicTypeEnum::BOOL.into())
+            )
+        ).into(),
+        BuiltIn::GenericBuiltInFn(
+            Name::from("Error__BuiltIn"),
+            vec![AtomicTypeEnum::BOOL.into()]
+        ).into();
+        "error built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("-"),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::INT.into()],
+                Box::new(AtomicTypeEnum::INT.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("Negative__BuiltIn"),
+        ).into();
+        "unary built-in fn"
+    )]
+    #[test_case(
+        BuiltInFn(
+            Name::from("-"),
+            IntermediateFnType(
+                vec![AtomicTypeEnum::INT.into(),AtomicTypeEnum::INT.into()],
+                Box::new(AtomicTypeEnum::INT.into())
+            )
+        ).into(),
+        BuiltIn::BuiltInFn(
+            Name::from("Minus__BuiltIn"),
+        ).into();
+        "binary built-in fn"
+    )]
     #[test_case(
         IntermediateValue::IntermediateMemory(
             IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT))
@@ -1857,7 +2510,7 @@ mod tests {
 
         let mut translator = Translator::new();
         let size = CodeSizeEstimator::estimate_size(&fn_def);
-        let translated = translator.translate_lambda(fn_def);
+        let translated = translator.translate_lambda(fn_def, None);
         assert_eq!(translated, (expected_statements, expected_value));
         let translated_fn_def = &translator.fn_defs[0];
         assert_eq!(translated_fn_def, &expected_fn_def);
@@ -2642,6 +3295,7 @@ mod tests {
         let main_size = CodeSizeEstimator::estimate_size(&program.main);
         let translated_program = translator.translate_program(program);
         assert_eq!(expected_program, translated_program);
+        assert_eq!(translator.strong_cycles, Vec::new());
         let main = translated_program.fn_defs.last().unwrap();
         assert_eq!(main.size_bounds, main_size);
     }
@@ -2698,9 +3352,330 @@ mod tests {
             program,
             TranslationArgs {
                 export_vector_file: Some(temporary_filename.to_str().unwrap().into()),
+                strict_arg_order: false,
+                no_tail_call_loops: false,
+                min_task_size: 0,
+                batch_size: 0,
+                cache_dir: None,
             },
         );
         let contents = fs::read_to_string(temporary_filename).expect("Failed to read file.");
         assert_eq!(contents, identity_vector.to_string())
     }
+
+    #[test_case(true; "strict order")]
+    #[test_case(false; "relaxed order")]
+    fn test_translate_program_argument_order(strict_arg_order: bool) {
+        let fn_type = IntermediateFnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        );
+        let f: IntermediateArg = IntermediateType::from(fn_type.clone()).into();
+        let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let z = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: vec![f.clone()],
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: x.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: f.clone().into(),
+                                args: vec![IntermediateBuiltIn::from(Integer { value: 1 }).into()],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: y.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: f.clone().into(),
+                                args: vec![IntermediateBuiltIn::from(Integer { value: 2 }).into()],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: z.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: IntermediateBuiltIn::from(BuiltInFn(
+                                    Id::from("-"),
+                                    IntermediateFnType(
+                                        vec![
+                                            AtomicTypeEnum::INT.into(),
+                                            AtomicTypeEnum::INT.into(),
+                                        ],
+                                        Box::new(AtomicTypeEnum::INT.into()),
+                                    ),
+                                ))
+                                .into(),
+                                args: vec![x.clone().into(), y.clone().into()],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: z.clone().into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        let mut translator = Translator::new();
+        if strict_arg_order {
+            translator.order_arguments(&program);
+        }
+        translator.strict_arg_order = strict_arg_order;
+        let translated_program = translator.translate_program(program);
+        let x = translator.translate_register(&x.register);
+        let y = translator.translate_register(&y.register);
+        let statements = &translated_program.fn_defs.last().unwrap().statements;
+        let position = |memory: &Memory| {
+            statements
+                .iter()
+                .position(|statement| {
+                    matches!(statement, Statement::Assignment(Assignment { target, value: _ }) if target == memory)
+                })
+                .unwrap()
+        };
+        let awaits_x_before_y = statements[..position(&y)].iter().any(
+            |statement| matches!(statement, Statement::Await(Await(memory)) if memory.contains(&x)),
+        );
+        assert_eq!(awaits_x_before_y, strict_arg_order);
+        if strict_arg_order {
+            assert!(position(&x) < position(&y));
+        }
+    }
+
+    /// Count the fn calls that create new tasks.
+    fn count_tasks(statements: &[Statement]) -> usize {
+        statements
+            .iter()
+            .map(|statement| match statement {
+                Statement::Assignment(Assignment {
+                    target: _,
+                    value:
+                        Expression::FnCall(FnCall {
+                            fn_: Value::Memory(_),
+                            fn_type: _,
+                            args: _,
+                        }),
+                }) => 1,
+                Statement::IfStatement(IfStatement {
+                    condition: _,
+                    branches,
+                }) => count_tasks(&branches.0) + count_tasks(&branches.1),
+                Statement::MatchStatement(MatchStatement {
+                    expression: _,
+                    branches,
+                    auxiliary_memory: _,
+                }) => branches
+                    .iter()
+                    .map(|branch| count_tasks(&branch.statements))
+                    .sum(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test_case(false, 1; "tail call loops")]
+    #[test_case(true, 2; "no tail call loops")]
+    fn test_translate_tail_recursive_fn(no_tail_call_loops: bool, expected_tasks: usize) {
+        let fn_type = IntermediateFnType(
+            vec![AtomicTypeEnum::BOOL.into(), AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        );
+        let f = IntermediateMemory::from(IntermediateType::from(fn_type.clone()));
+        let b: IntermediateArg = IntermediateType::from(AtomicTypeEnum::BOOL).into();
+        let x: IntermediateArg = IntermediateType::from(AtomicTypeEnum::INT).into();
+        let r = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let c = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let y = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        // `f = (b, x) -> if b { f(false, x) } else { x }; f(true, 3)`
+        let lambda = IntermediateLambda {
+            args: vec![b.clone(), x.clone()],
+            block: IntermediateBlock {
+                statements: vec![IntermediateAssignment {
+                    register: c.register.clone(),
+                    expression: IntermediateIf {
+                        condition: b.clone().into(),
+                        branches: (
+                            (
+                                vec![IntermediateAssignment {
+                                    register: r.register.clone(),
+                                    expression: IntermediateFnCall {
+                                        fn_: f.clone().into(),
+                                        args: vec![
+                                            IntermediateBuiltIn::from(Boolean { value: false })
+                                                .into(),
+                                            x.clone().into(),
+                                        ],
+                                    }
+                                    .into(),
+                                }
+                                .into()],
+                                r.clone().into(),
+                            )
+                                .into(),
+                            IntermediateValue::from(x.clone()).into(),
+                        ),
+                    }
+                    .into(),
+                }
+                .into()],
+                ret: c.clone().into(),
+            },
+        };
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![
+                        IntermediateAssignment {
+                            register: f.register.clone(),
+                            expression: lambda.into(),
+                        }
+                        .into(),
+                        IntermediateAssignment {
+                            register: y.register.clone(),
+                            expression: IntermediateFnCall {
+                                fn_: f.clone().into(),
+                                args: vec![
+                                    IntermediateBuiltIn::from(Boolean { value: true }).into(),
+                                    IntermediateBuiltIn::from(Integer { value: 3 }).into(),
+                                ],
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    ret: y.clone().into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        let translated_program = Translator::translate(
+            program,
+            TranslationArgs {
+                export_vector_file: None,
+                strict_arg_order: false,
+                no_tail_call_loops,
+                min_task_size: 0,
+                batch_size: 0,
+                cache_dir: None,
+            },
+        );
+        let tasks: usize = translated_program
+            .fn_defs
+            .iter()
+            .map(|fn_def| count_tasks(&fn_def.statements))
+            .sum();
+        assert_eq!(tasks, expected_tasks);
+    }
+
+    #[test]
+    fn test_translate_with_cache() {
+        // Programs are built twice so that they have different registers.
+        let program = || {
+            let arg: IntermediateArg = IntermediateType::from(AtomicTypeEnum::INT).into();
+            IntermediateProgram {
+                main: IntermediateLambda {
+                    args: vec![arg.clone()],
+                    block: IntermediateBlock {
+                        statements: Vec::new(),
+                        ret: arg.into(),
+                    },
+                },
+                types: Vec::new(),
+            }
+        };
+        let tmp_dir = TempDir::new().expect("Could not create temp dir.");
+        let args = || TranslationArgs {
+            export_vector_file: None,
+            strict_arg_order: false,
+            no_tail_call_loops: false,
+            min_task_size: 0,
+            batch_size: 0,
+            cache_dir: Some(tmp_dir.path().to_path_buf()),
+        };
+        let translated_program = Translator::translate(program(), args());
+        let entries = fs::read_dir(tmp_dir.path())
+            .expect("Failed to read cache.")
+            .map(|entry| entry.unwrap().path())
+            .collect_vec();
+        assert_eq!(entries.len(), 1);
+
+        // Replace the cached translation to detect whether it is used.
+        let cached_program = Program {
+            type_defs: Vec::new(),
+            fn_defs: Vec::new(),
+        };
+        fs::write(&entries[0], serde_json::to_string(&cached_program).unwrap()).unwrap();
+        assert_eq!(Translator::translate(program(), args()), cached_program);
+        assert_eq!(fs::read_dir(tmp_dir.path()).unwrap().count(), 1);
+
+        // Changing the args misses the cache.
+        let strict_args = TranslationArgs {
+            strict_arg_order: true,
+            ..args()
+        };
+        assert_eq!(
+            Translator::translate(program(), strict_args),
+            translated_program
+        );
+        assert_eq!(fs::read_dir(tmp_dir.path()).unwrap().count(), 2);
+
+        // A cache that cannot be written is skipped.
+        let unwritable_args = TranslationArgs {
+            cache_dir: Some(entries[0].clone()),
+            ..args()
+        };
+        assert_eq!(
+            Translator::translate(program(), unwritable_args),
+            translated_program
+        );
+    }
+
+    #[test]
+    fn test_translate_with_registers() {
+        let x = IntermediateMemory::from(IntermediateType::from(AtomicTypeEnum::INT));
+        let program = IntermediateProgram {
+            main: IntermediateLambda {
+                args: Vec::new(),
+                block: IntermediateBlock {
+                    statements: vec![IntermediateAssignment {
+                        register: x.register.clone(),
+                        expression: IntermediateValue::from(IntermediateBuiltIn::from(Integer {
+                            value: 5,
+                        }))
+                        .into(),
+                    }
+                    .into()],
+                    ret: x.clone().into(),
+                },
+            },
+            types: Vec::new(),
+        };
+        let (translated_program, registers) = Translator::translate_with_registers(
+            program,
+            TranslationArgs {
+                export_vector_file: None,
+                strict_arg_order: false,
+                no_tail_call_loops: false,
+                min_task_size: 0,
+                batch_size: 0,
+                cache_dir: None,
+            },
+        );
+        let memory = registers
+            .iter()
+            .find_map(|(memory, register)| (*register == x.register).then_some(memory))
+            .expect("Register was not translated.");
+        assert!(translated_program.fn_defs.iter().any(|fn_def| {
+            fn_def.statements.iter().any(|statement| {
+                matches!(statement, Statement::Assignment(Assignment { target, value: _ }) if target == memory)
+            })
+        }));
+    }
 }
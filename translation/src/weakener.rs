@@ -38,11 +38,25 @@ impl ClosureCycles {
     }
 }
 
-pub struct Weakener {}
+/// Edge from a closure to a fn in its env that was replaced with a weak reference.
+pub type WeakenedEdge = (Memory, Memory);
+
+pub struct Weakener {
+    weakened_edges: Vec<WeakenedEdge>,
+}
 
 impl Weakener {
-    /// Add weak fn types and allocators to a program with recursive closures.
-    pub fn weaken(program: Program) -> Program {
+    pub fn new() -> Self {
+        Weakener {
+            weakened_edges: Vec::new(),
+        }
+    }
+    /// Edges that have been weakened by this weakener.
+    pub fn weakened_edges(&self) -> &Vec<WeakenedEdge> {
+        &self.weakened_edges
+    }
+    /// Add weak fn types and allocators to a program with recursive closures, recording the edges that are weakened.
+    pub fn weaken_program(&mut self, program: Program) -> Program {
         let Program { type_defs, fn_defs } = program;
         // Find all cyclic closures in the program.
         let mut closure_cycles = ClosureCycles::new();
@@ -62,7 +76,7 @@ impl Weakener {
                      size_bounds,
                      is_recursive,
                  }| {
-                    let (statements, weak_fns) = Self::add_allocations(statements, &closure_cycles);
+                    let (statements, weak_fns) = self.add_allocations(statements, &closure_cycles);
                     (
                         FnDef {
                             name,
@@ -96,7 +110,8 @@ impl Weakener {
                 Statement::Allocation(_)
                 | Statement::Await(_)
                 | Statement::Declaration(_)
-                | Statement::Enqueue(_) => {}
+                | Statement::Enqueue(_)
+                | Statement::TailCall(_) => {}
                 Statement::Assignment(Assignment {
                     target,
                     value: expression,
@@ -230,6 +245,7 @@ impl Weakener {
 
     /// Insert allocators for cyclic references into the statements, returning any fns that need weak ptrs.
     fn add_allocations(
+        &mut self,
         statements: Vec<Statement>,
         closure_cycles: &ClosureCycles,
     ) -> (Vec<Statement>, HashSet<(Name, usize)>) {
@@ -246,6 +262,7 @@ impl Weakener {
                 Statement::Await(await_) => vec![await_.into()],
                 Statement::Allocation(allocation) => vec![allocation.into()],
                 Statement::Enqueue(enqueue) => vec![enqueue.into()],
+                Statement::TailCall(tail_call) => vec![tail_call.into()],
                 Statement::Assignment(assignment) => {
                     if let Assignment {
                         target,
@@ -260,6 +277,8 @@ impl Weakener {
                                     if let Value::Memory(memory) = value {
                                         if closed_values.contains(memory) {
                                             weak_fns.insert((fn_name.clone(), i));
+                                            self.weakened_edges
+                                                .push((closure.clone(), memory.clone()));
                                         }
                                     }
                                 }
@@ -304,7 +323,7 @@ impl Weakener {
                 }) => {
                     let (branches, extra_fns) = [branches.0, branches.1]
                         .into_iter()
-                        .map(|branch| Self::add_allocations(branch, &closure_cycles))
+                        .map(|branch| self.add_allocations(branch, &closure_cycles))
                         .collect::<(Vec<_>, Vec<_>)>();
                     for fns in extra_fns {
                         weak_fns.extend(fns.into_iter());
@@ -325,7 +344,7 @@ impl Weakener {
                         .into_iter()
                         .map(|MatchBranch { target, statements }| {
                             let (statements, weak_fns) =
-                                Self::add_allocations(statements, &closure_cycles);
+                                self.add_allocations(statements, &closure_cycles);
                             (MatchBranch { target, statements }, weak_fns)
                         })
                         .collect::<(Vec<_>, Vec<_>)>();
@@ -2308,7 +2327,7 @@ mod tests {
         expected_weak_fns: HashSet<(Name, usize)>,
     ) {
         let cycles = Weakener::detect_closure_cycles(&statements);
-        let (statements, weak_fns) = Weakener::add_allocations(statements, &cycles);
+        let (statements, weak_fns) = Weakener::new().add_allocations(statements, &cycles);
         assert_eq!(statements, expected_statements);
         assert_eq!(weak_fns, expected_weak_fns);
     }
@@ -3028,8 +3047,77 @@ mod tests {
         "extra self cycle"
     )]
     fn test_weaken_program(program: Program, expected_fn_defs: Vec<FnDef>) {
-        let weak_program = Weakener::weaken(program.clone());
+        let weak_program = Weakener::new().weaken_program(program.clone());
         assert_eq!(weak_program.fn_defs, expected_fn_defs);
         assert_eq!(weak_program.type_defs, program.type_defs);
     }
+
+    #[test]
+    fn test_weakened_edges() {
+        let fn_type = FnType(
+            vec![AtomicTypeEnum::INT.into()],
+            Box::new(AtomicTypeEnum::INT.into()),
+        );
+        // A closure that contains itself in its env.
+        let program = Program {
+            type_defs: Vec::new(),
+            fn_defs: vec![
+                FnDef {
+                    name: Name::from("f"),
+                    arguments: vec![(Memory(Id::from("a")), AtomicTypeEnum::INT.into())],
+                    ret: (
+                        BuiltIn::from(Integer { value: 0 }).into(),
+                        AtomicTypeEnum::INT.into(),
+                    ),
+                    env: vec![fn_type.clone().into()],
+                    statements: Vec::new(),
+                    size_bounds: (10, 100),
+                    is_recursive: true,
+                },
+                FnDef {
+                    name: Name::from("Main"),
+                    arguments: Vec::new(),
+                    ret: (
+                        BuiltIn::from(Integer { value: 0 }).into(),
+                        AtomicTypeEnum::INT.into(),
+                    ),
+                    env: Vec::new(),
+                    statements: vec![
+                        Declaration {
+                            memory: Memory(Id::from("closure")),
+                            type_: fn_type.clone().into(),
+                        }
+                        .into(),
+                        Declaration {
+                            memory: Memory(Id::from("env")),
+                            type_: TupleType(vec![fn_type.into()]).into(),
+                        }
+                        .into(),
+                        Assignment {
+                            target: Memory(Id::from("env")),
+                            value: TupleExpression(vec![Memory(Id::from("closure")).into()]).into(),
+                        }
+                        .into(),
+                        Assignment {
+                            target: Memory(Id::from("closure")),
+                            value: ClosureInstantiation {
+                                name: Name::from("f"),
+                                env: Some(Memory(Id::from("env")).into()),
+                            }
+                            .into(),
+                        }
+                        .into(),
+                    ],
+                    size_bounds: (10, 100),
+                    is_recursive: false,
+                },
+            ],
+        };
+        let mut weakener = Weakener::new();
+        weakener.weaken_program(program);
+        assert_eq!(
+            weakener.weakened_edges(),
+            &vec![(Memory(Id::from("closure")), Memory(Id::from("closure")))]
+        );
+    }
 }
@@ -1,5 +1,5 @@
-use serde::{Deserialize, Serialize};
-use std::{convert::From, fmt};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{collections::BTreeMap, convert::From, fmt};
 use strum_macros::EnumIter;
 
 pub type Id = String;
@@ -9,6 +9,8 @@ use from_variants::FromVariants;
 pub enum AtomicTypeEnum {
     INT,
     BOOL,
+    STRING,
+    CHAR,
 }
 
 impl fmt::Display for AtomicTypeEnum {
@@ -30,6 +32,14 @@ pub const ATOMIC_TYPE_INT: AtomicType = AtomicType {
 pub const ATOMIC_TYPE_BOOL: AtomicType = AtomicType {
     type_: AtomicTypeEnum::BOOL,
 };
+#[allow(dead_code)]
+pub const ATOMIC_TYPE_STRING: AtomicType = AtomicType {
+    type_: AtomicTypeEnum::STRING,
+};
+#[allow(dead_code)]
+pub const ATOMIC_TYPE_CHAR: AtomicType = AtomicType {
+    type_: AtomicTypeEnum::CHAR,
+};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct GenericType {
@@ -50,6 +60,18 @@ pub struct TupleType {
     pub types: Vec<TypeInstance>,
 }
 
+/// Record type with named fields, whose order does not matter.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct RecordType {
+    pub fields: Vec<RecordTypeField>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct RecordTypeField {
+    pub id: Id,
+    pub type_: TypeInstance,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct FunctionType {
     pub argument_types: Vec<TypeInstance>,
@@ -62,12 +84,31 @@ pub enum TypeInstance {
     AtomicType(AtomicType),
     TupleType(TupleType),
     GenericType(GenericType),
+    RecordType(RecordType),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct TypeItem {
     pub id: Id,
-    pub type_: Option<TypeInstance>,
+    /// Fields of the variant, which are stored as a tuple when there is more than one.
+    #[serde(alias = "type_", deserialize_with = "deserialize_fields")]
+    pub types: Vec<TypeInstance>,
+}
+
+/// Accept a list of fields or a single optional field.
+fn deserialize_fields<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<TypeInstance>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Fields {
+        Many(Vec<TypeInstance>),
+        Single(Option<TypeInstance>),
+    }
+    Ok(match Fields::deserialize(deserializer)? {
+        Fields::Many(types) => types,
+        Fields::Single(type_) => type_.into_iter().collect(),
+    })
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -80,23 +121,44 @@ pub struct GenericTypeVariable {
 pub struct UnionTypeDefinition {
     pub variable: GenericTypeVariable,
     pub items: Vec<TypeItem>,
+    /// Documentation attached to the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct OpaqueTypeDefinition {
     pub variable: GenericTypeVariable,
     pub type_: TypeInstance,
+    /// Documentation attached to the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct EmptyTypeDefinition {
     pub id: Id,
+    /// Documentation attached to the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+/// Type without any values, which is distinct from an `EmptyTypeDefinition` with a single value.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct VoidTypeDefinition {
+    pub id: Id,
+    /// Documentation attached to the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct TransparentTypeDefinition {
     pub variable: GenericTypeVariable,
     pub type_: TypeInstance,
+    /// Documentation attached to the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, FromVariants, Clone)]
@@ -105,6 +167,7 @@ pub enum Definition {
     OpaqueTypeDefinition(OpaqueTypeDefinition),
     TransparentTypeDefinition(TransparentTypeDefinition),
     EmptyTypeDefinition(EmptyTypeDefinition),
+    VoidTypeDefinition(VoidTypeDefinition),
     Assignment(Assignment),
 }
 
@@ -118,8 +181,10 @@ impl Definition {
                         generic_variables: _,
                     },
                 items: _,
+                doc: _,
             })
-            | Self::EmptyTypeDefinition(EmptyTypeDefinition { id })
+            | Self::EmptyTypeDefinition(EmptyTypeDefinition { id, doc: _ })
+            | Self::VoidTypeDefinition(VoidTypeDefinition { id, doc: _ })
             | Self::TransparentTypeDefinition(TransparentTypeDefinition {
                 variable:
                     GenericTypeVariable {
@@ -127,6 +192,7 @@ impl Definition {
                         generic_variables: _,
                     },
                 type_: _,
+                doc: _,
             })
             | Self::OpaqueTypeDefinition(OpaqueTypeDefinition {
                 variable:
@@ -135,14 +201,17 @@ impl Definition {
                         generic_variables: _,
                     },
                 type_: _,
+                doc: _,
             })
             | Self::Assignment(Assignment {
                 assignee:
                     ParametricAssignee {
                         assignee: Assignee { id },
                         generic_variables: _,
+                        bounds: _,
                     },
                 expression: _,
+                doc: _,
             }) => id,
         }
     }
@@ -155,6 +224,7 @@ impl Definition {
                         generic_variables,
                     },
                 items: _,
+                doc: _,
             })
             | Self::TransparentTypeDefinition(TransparentTypeDefinition {
                 variable:
@@ -163,6 +233,7 @@ impl Definition {
                         generic_variables,
                     },
                 type_: _,
+                doc: _,
             })
             | Self::OpaqueTypeDefinition(OpaqueTypeDefinition {
                 variable:
@@ -171,30 +242,124 @@ impl Definition {
                         generic_variables,
                     },
                 type_: _,
+                doc: _,
             })
             | Self::Assignment(Assignment {
                 assignee:
                     ParametricAssignee {
                         assignee: _,
                         generic_variables,
+                        bounds: _,
                     },
                 expression: _,
+                doc: _,
             }) => generic_variables.clone(),
-            Self::EmptyTypeDefinition(EmptyTypeDefinition { id: _ }) => Vec::new(),
+            Self::EmptyTypeDefinition(EmptyTypeDefinition { id: _, doc: _ })
+            | Self::VoidTypeDefinition(VoidTypeDefinition { id: _, doc: _ }) => Vec::new(),
+        }
+    }
+    pub fn get_doc(&self) -> Option<&String> {
+        match self {
+            Self::UnionTypeDefinition(UnionTypeDefinition { doc, .. })
+            | Self::OpaqueTypeDefinition(OpaqueTypeDefinition { doc, .. })
+            | Self::TransparentTypeDefinition(TransparentTypeDefinition { doc, .. })
+            | Self::EmptyTypeDefinition(EmptyTypeDefinition { doc, .. })
+            | Self::VoidTypeDefinition(VoidTypeDefinition { doc, .. })
+            | Self::Assignment(Assignment { doc, .. }) => doc.as_ref(),
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Hash, Eq)]
 pub struct Integer {
+    #[serde(deserialize_with = "deserialize_integer")]
     pub value: i64,
 }
 
+/// Accept an integer as a number or a string with an optional sign and radix prefix (`0x`, `0b` or `0o`).
+fn deserialize_integer<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Literal {
+        Number(i64),
+        String(String),
+    }
+    let string = match Literal::deserialize(deserializer)? {
+        Literal::Number(value) => return Ok(value),
+        Literal::String(string) => string,
+    };
+    let invalid = || de::Error::custom(format!("{string:?} is not a valid integer literal"));
+    let (negative, unsigned) = match string.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, string.strip_prefix('+').unwrap_or(&string)),
+    };
+    let (radix, digits) = [
+        ("0x", 16),
+        ("0X", 16),
+        ("0b", 2),
+        ("0B", 2),
+        ("0o", 8),
+        ("0O", 8),
+    ]
+    .into_iter()
+    .find_map(|(prefix, radix)| unsigned.strip_prefix(prefix).map(|digits| (radix, digits)))
+    .unwrap_or((10, unsigned));
+    // Signs are only allowed before the prefix.
+    if digits.starts_with(['+', '-']) {
+        return Err(invalid());
+    }
+    let magnitude = u64::from_str_radix(digits, radix).map_err(|_| invalid())?;
+    if negative {
+        0i64.checked_sub_unsigned(magnitude)
+    } else {
+        i64::try_from(magnitude).ok()
+    }
+    .ok_or_else(|| de::Error::custom(format!("{string:?} does not fit into 64 bits")))
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Hash, Eq)]
 pub struct Boolean {
     pub value: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Hash, Eq)]
+pub struct Str {
+    pub value: String,
+}
+
+/// A single character, stored as its codepoint.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Hash, Eq)]
+pub struct Char {
+    #[serde(deserialize_with = "deserialize_codepoint")]
+    pub value: u32,
+}
+
+/// Accept a codepoint as a number or a single-character string.
+fn deserialize_codepoint<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Codepoint {
+        Number(u32),
+        Character(String),
+    }
+    let character = match Codepoint::deserialize(deserializer)? {
+        Codepoint::Number(codepoint) => char::from_u32(codepoint)
+            .ok_or_else(|| de::Error::custom(format!("{codepoint} is not a valid codepoint")))?,
+        Codepoint::Character(string) => {
+            let mut characters = string.chars();
+            match (characters.next(), characters.next()) {
+                (Some(character), None) => character,
+                _ => {
+                    return Err(de::Error::custom(format!(
+                        "{string:?} is not a single character"
+                    )))
+                }
+            }
+        }
+    };
+    Ok(character as u32)
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct TupleExpression {
     pub expressions: Vec<Expression>,
@@ -220,17 +385,39 @@ pub struct ElementAccess {
     pub index: usize,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct RecordExpression {
+    pub fields: Vec<RecordField>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct RecordField {
+    pub id: Id,
+    pub expression: Expression,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct FieldAccess {
+    pub expression: Box<Expression>,
+    pub field: Id,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct IfExpression {
     pub condition: Box<Expression>,
     pub true_block: Block,
-    pub false_block: Block,
+    /// Missing when there is no else branch, which is only valid for unit-typed ifs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub false_block: Option<Block>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct MatchItem {
     pub type_name: Id,
     pub assignee: Option<Assignee>,
+    /// Nested pattern that the payload must also match.
+    #[serde(default)]
+    pub pattern: Option<Box<MatchItem>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -270,6 +457,13 @@ pub struct ConstructorCall {
     pub arguments: Vec<Expression>,
 }
 
+/// An expression with an explicit type, written `expression : type_`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Annotated {
+    pub expression: Box<Expression>,
+    pub type_: TypeInstance,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct GenericConstructor {
     pub id: Id,
@@ -288,14 +482,20 @@ pub fn Constructor(id: &str) -> GenericConstructor {
 pub enum Expression {
     Integer(Integer),
     Boolean(Boolean),
+    #[serde(rename = "String")]
+    Str(Str),
+    Char(Char),
     TupleExpression(TupleExpression),
     GenericVariable(GenericVariable),
     ElementAccess(ElementAccess),
+    RecordExpression(RecordExpression),
+    FieldAccess(FieldAccess),
     IfExpression(IfExpression),
     MatchExpression(MatchExpression),
     FunctionDefinition(FunctionDefinition),
     FunctionCall(FunctionCall),
     ConstructorCall(ConstructorCall),
+    Annotated(Annotated),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -309,10 +509,20 @@ impl From<Id> for Assignee {
     }
 }
 
+/// Class of types that a generic variable can be restricted to.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TypeClass {
+    /// Types that can be compared with `eq`.
+    Comparable,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ParametricAssignee {
     pub assignee: Assignee,
     pub generic_variables: Vec<Id>,
+    /// Type classes that instantiations of each generic variable must belong to, such as `T: comparable`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bounds: BTreeMap<Id, Vec<TypeClass>>,
 }
 
 impl ParametricAssignee {
@@ -326,6 +536,7 @@ pub fn VariableAssignee(id: &str) -> ParametricAssignee {
     ParametricAssignee {
         assignee: Assignee::from(Id::from(id)),
         generic_variables: Vec::new(),
+        bounds: BTreeMap::new(),
     }
 }
 
@@ -333,6 +544,9 @@ pub fn VariableAssignee(id: &str) -> ParametricAssignee {
 pub struct Assignment {
     pub assignee: ParametricAssignee,
     pub expression: Box<Expression>,
+    /// Documentation attached to the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -470,16 +684,38 @@ mod tests {
             items: vec![
                 TypeItem {
                     id: Id::from("Some"),
-                    type_: Some(Typename("T").into()),
+                    types: vec![Typename("T").into()],
                 },
                 TypeItem {
                     id: Id::from("None"),
-                    type_: None
+                    types: Vec::new()
                 }
-            ]
+            ],
+            doc: None,
         };
         "union type definition"
     )]
+    #[test_case(
+        r#"{"variable":{"id":"List","generic_variables":[]},"items":[{"id":"Cons","types":[{"AtomicType":{"type_":"INT"}},{"GenericType":{"id":"List","type_variables":[]}}]},{"id":"Nil","types":[]}]}"#,
+        UnionTypeDefinition {
+            variable: GenericTypeVariable{
+                id: Id::from("List"),
+                generic_variables: Vec::new()
+            },
+            items: vec![
+                TypeItem {
+                    id: Id::from("Cons"),
+                    types: vec![ATOMIC_TYPE_INT.into(), Typename("List").into()],
+                },
+                TypeItem {
+                    id: Id::from("Nil"),
+                    types: Vec::new()
+                }
+            ],
+            doc: None,
+        };
+        "multi-field union type definition"
+    )]
     #[test_case(
         r#"{"variable":{"id":"Pair","generic_variables":["T","U"]},"type_":{"TupleType":{"types":[{"GenericType":{"id":"T","type_variables":[]}},{"GenericType":{"id":"U","type_variables":[]}}]}}}"#,
         OpaqueTypeDefinition{
@@ -489,7 +725,8 @@ mod tests {
             },
             type_: TupleType{
                 types: vec![Typename("T").into(), Typename("U").into()]
-            }.into()
+            }.into(),
+            doc: None,
         };
         "opaque type definition"
     )]
@@ -512,17 +749,27 @@ mod tests {
                 return_type: Box::new(
                     ATOMIC_TYPE_INT.into()
                 )
-            }.into()
+            }.into(),
+            doc: None,
         };
         "opaque function type definition"
     )]
     #[test_case(
         r#"{"id":"None"}"#,
         EmptyTypeDefinition{
-            id: Id::from("None")
+            id: Id::from("None"),
+            doc: None,
         };
         "empty type definition"
     )]
+    #[test_case(
+        r#"{"id":"Never"}"#,
+        VoidTypeDefinition{
+            id: Id::from("Never"),
+            doc: None,
+        };
+        "void type definition"
+    )]
     #[test_case(
         r#"{"variable":{"id":"ii","generic_variables":[]},"type_":{"TupleType":{"types":[{"AtomicType":{"type_":"INT"}},{"AtomicType":{"type_":"INT"}}]}}}"#,
         TransparentTypeDefinition{
@@ -535,7 +782,8 @@ mod tests {
                     ATOMIC_TYPE_INT.into(),
                     ATOMIC_TYPE_INT.into(),
                 ]
-            }.into()
+            }.into(),
+            doc: None,
         };
         "transparent type definition"
     )]
@@ -560,6 +808,55 @@ mod tests {
         };
         "boolean"
     )]
+    #[test_case(
+        r#"{"value":""}"#,
+        Str{
+            value: String::new()
+        };
+        "empty string"
+    )]
+    #[test_case(
+        r#"{"value":"a\"b\\c\n\u00e9"}"#,
+        Str{
+            value: String::from("a\"b\\c\né")
+        };
+        "string with escapes"
+    )]
+    #[test_case(
+        r#"{"String":{"value":"hello"}}"#,
+        Expression::Str(Str{
+            value: String::from("hello")
+        });
+        "string expression"
+    )]
+    #[test_case(
+        r#"{"value":"a"}"#,
+        Char{
+            value: 97
+        };
+        "character"
+    )]
+    #[test_case(
+        r#"{"value":"\u00e9"}"#,
+        Char{
+            value: 0xe9
+        };
+        "escaped character"
+    )]
+    #[test_case(
+        r#"{"value":128512}"#,
+        Char{
+            value: 0x1F600
+        };
+        "codepoint"
+    )]
+    #[test_case(
+        r#"{"Char":{"value":"z"}}"#,
+        Expression::Char(Char{
+            value: 122
+        });
+        "character expression"
+    )]
     #[test_case(
         r#"{"expressions":[]}"#,
         TupleExpression{
@@ -646,7 +943,8 @@ mod tests {
         r#"{"assignee":{"id":"a"},"generic_variables":[]}"#,
         ParametricAssignee {
             assignee: Id::from("a").into(),
-            generic_variables: Vec::new()
+            generic_variables: Vec::new(),
+            bounds: BTreeMap::new(),
         };
         "basic assignee"
     )]
@@ -657,7 +955,8 @@ mod tests {
             generic_variables: vec![
                 Id::from("T"),
                 Id::from("U")
-            ]
+            ],
+            bounds: BTreeMap::new(),
         };
         "generic assignee"
     )]
@@ -665,7 +964,8 @@ mod tests {
         r#"{"assignee":{"assignee":{"id":"a"},"generic_variables":[]},"expression":{"GenericVariable":{"id":"b","type_instances":[]}}}"#,
         Assignment {
             assignee: VariableAssignee("a"),
-            expression: Box::new(Var("b").into())
+            expression: Box::new(Var("b").into()),
+            doc: None,
         };
         "variable assignment"
     )]
@@ -676,14 +976,16 @@ mod tests {
                 assignee: Id::from("a").into(),
                 generic_variables: vec![
                     Id::from("T")
-                ]
+                ],
+                bounds: BTreeMap::new(),
             },
             expression: Box::new(GenericVariable{
                 id: Id::from("b"),
                 type_instances: vec![
                     Typename("T").into()
                 ]
-            }.into())
+            }.into()),
+            doc: None,
         };
         "generic variable assignment"
     )]
@@ -699,11 +1001,13 @@ mod tests {
             assignments: vec![
                 Assignment {
                     assignee: VariableAssignee("a"),
-                    expression: Box::new(Var("x").into())
+                    expression: Box::new(Var("x").into()),
+                    doc: None,
                 },
                 Assignment {
                     assignee: VariableAssignee("b"),
-                    expression: Box::new(Integer{value:3}.into())
+                    expression: Box::new(Integer{value:3}.into()),
+                    doc: None,
                 },
             ],
             expression: Box::new(Integer{
@@ -719,10 +1023,21 @@ mod tests {
                 Boolean{ value: true }.into()
             ),
             true_block: ExpressionBlock(Integer{ value: 1 }.into()),
-            false_block: ExpressionBlock(Integer{ value: -1 }.into()),
+            false_block: Some(ExpressionBlock(Integer{ value: -1 }.into())),
         };
         "flat if expression"
     )]
+    #[test_case(
+        r#"{"condition":{"Boolean":{"value":true}},"true_block":{"assignments":[],"expression":{"TupleExpression":{"expressions":[]}}}}"#,
+        IfExpression {
+            condition: Box::new(
+                Boolean{ value: true }.into()
+            ),
+            true_block: ExpressionBlock(TupleExpression{ expressions: Vec::new() }.into()),
+            false_block: None,
+        };
+        "one-armed if expression"
+    )]
     #[test_case(
         r#"{"condition":{"IfExpression":{"condition":{"Boolean":{"value":true}},"true_block":{"assignments":[],"expression":{"Boolean":{"value":true}}},"false_block":{"assignments":[],"expression":{"Boolean":{"value":false}}}}},"true_block":{"assignments":[],"expression":{"IfExpression":{"condition":{"Boolean":{"value":false}},"true_block":{"assignments":[],"expression":{"Integer":{"value":1}}},"false_block":{"assignments":[],"expression":{"Integer":{"value":0}}}}}},"false_block":{"assignments":[],"expression":{"Integer":{"value":-1}}}}"#,
         IfExpression {
@@ -735,9 +1050,9 @@ mod tests {
                         Boolean{ value: true }.into()
                     )
                     ,
-                    false_block: ExpressionBlock(
+                    false_block: Some(ExpressionBlock(
                         Boolean{ value: false }.into()
-                    )
+                    ))
 
                 }.into()
             ),
@@ -748,11 +1063,11 @@ mod tests {
                         true_block: ExpressionBlock(
                             Integer{ value: 1 }.into()
                         ),
-                        false_block: ExpressionBlock(
+                        false_block: Some(ExpressionBlock(
                             Integer{ value: 0 }.into()
-                        )
+                        ))
                     }.into()),
-            false_block: ExpressionBlock(Integer{ value: -1 }.into()),
+            false_block: Some(ExpressionBlock(Integer{ value: -1 }.into())),
         };
         "nested if expression"
     )]
@@ -761,6 +1076,7 @@ mod tests {
         MatchItem {
             type_name: Id::from("Some"),
             assignee: Some(Id::from("x").into()),
+            pattern: None,
         };
         "present match item"
     )]
@@ -769,9 +1085,23 @@ mod tests {
         MatchItem {
             type_name: Id::from("None"),
             assignee: None,
+            pattern: None,
         };
         "absent match item"
     )]
+    #[test_case(
+        r#"{"type_name":"Some","assignee":null,"pattern":{"type_name":"Cons","assignee":{"id":"x"}}}"#,
+        MatchItem {
+            type_name: Id::from("Some"),
+            assignee: None,
+            pattern: Some(Box::new(MatchItem {
+                type_name: Id::from("Cons"),
+                assignee: Some(Id::from("x").into()),
+                pattern: None,
+            })),
+        };
+        "nested match item"
+    )]
     #[test_case(
         r#"{"matches":[{"type_name":"None","assignee":null},{"type_name":"Some","assignee":{"id":"x"}}],"block":{"assignments":[],"expression":{"Boolean":{"value":true}}}}"#,
         MatchBlock {
@@ -779,10 +1109,12 @@ mod tests {
                 MatchItem {
                     type_name: Id::from("None"),
                     assignee: None,
+                    pattern: None,
                 },
                 MatchItem {
                     type_name: Id::from("Some"),
                     assignee: Some(Id::from("x").into()),
+                    pattern: None,
                 }
             ],
             block: Block{
@@ -804,6 +1136,7 @@ mod tests {
                         MatchItem {
                             type_name: Id::from("Some"),
                             assignee: Some(Id::from("x").into()),
+                            pattern: None,
                         }
                     ],
                     block: Block{
@@ -818,6 +1151,7 @@ mod tests {
                         MatchItem {
                             type_name: Id::from("None"),
                             assignee: None,
+                            pattern: None,
                         }
                     ],
                     block: Block{
@@ -841,6 +1175,7 @@ mod tests {
                         MatchItem {
                             type_name: Id::from("Some"),
                             assignee: Some(Id::from("x").into()),
+                            pattern: None,
                         }
                     ],
                     block: Block{
@@ -853,7 +1188,8 @@ mod tests {
                                         matches: vec![
                                             MatchItem{
                                                 type_name: Id::from("Positive"),
-                                                assignee: None
+                                                assignee: None,
+                                                pattern: None
                                             }
                                         ],
                                         block: Block{
@@ -865,7 +1201,8 @@ mod tests {
                                         matches: vec![
                                             MatchItem{
                                                 type_name: Id::from("Negative"),
-                                                assignee: None
+                                                assignee: None,
+                                                pattern: None
                                             }
                                         ],
                                         block: Block{
@@ -883,6 +1220,7 @@ mod tests {
                         MatchItem {
                             type_name: Id::from("None"),
                             assignee: None,
+                            pattern: None,
                         }
                     ],
                     block: Block{
@@ -962,7 +1300,8 @@ mod tests {
                         return_type: Box::new(
                             ATOMIC_TYPE_INT.into()
                         )
-                    }.into()
+                    }.into(),
+                    doc: None,
                 }.into()
             ]
         };
@@ -980,13 +1319,14 @@ mod tests {
                     items: vec![
                         TypeItem {
                             id: Id::from("Some"),
-                            type_: Some(Typename("T").into()),
+                            types: vec![Typename("T").into()],
                         },
                         TypeItem {
                             id: Id::from("None"),
-                            type_: None
+                            types: Vec::new()
                         }
-                    ]
+                    ],
+                    doc: None,
                 }.into(),
                 TransparentTypeDefinition{
                     variable: GenericTypeVariable{
@@ -995,15 +1335,18 @@ mod tests {
                     },
                     type_: TupleType{
                         types: vec![Typename("T").into(), Typename("U").into()]
-                    }.into()
+                    }.into(),
+                    doc: None,
                 }.into(),
                 Assignment {
                     assignee: VariableAssignee("a"),
-                    expression: Box::new(Var("x").into())
+                    expression: Box::new(Var("x").into()),
+                    doc: None,
                 }.into(),
                 Assignment {
                     assignee: VariableAssignee("b"),
-                    expression: Box::new(Integer{value:3}.into())
+                    expression: Box::new(Integer{value:3}.into()),
+                    doc: None,
                 }.into(),
             ]
         };
@@ -1023,6 +1366,67 @@ mod tests {
         let _ = result.inspect(|ast| assert_eq!(ast, &node));
     }
 
+    #[test_case(r#"{"value":""}"#; "empty string")]
+    #[test_case(r#"{"value":"ab"}"#; "multiple characters")]
+    #[test_case(r#"{"value":55296}"#; "surrogate codepoint")]
+    #[test_case(r#"{"value":1114112}"#; "codepoint out of range")]
+    fn test_deserialize_invalid_char(json: &str) {
+        assert!(serde_json::from_str::<Char>(json).is_err());
+    }
+
+    #[test_case(r#"{"value":"0xFF"}"#, 255; "hexadecimal")]
+    #[test_case(r#"{"value":"0b1010"}"#, 10; "binary")]
+    #[test_case(r#"{"value":"-0o17"}"#, -15; "negative octal")]
+    #[test_case(r#"{"value":"+42"}"#, 42; "explicit sign decimal")]
+    #[test_case(r#"{"value":-7}"#, -7; "negative number")]
+    #[test_case(r#"{"value":"-0x8000000000000000"}"#, i64::MIN; "minimum")]
+    fn test_deserialize_integer_literal(json: &str, value: i64) {
+        assert_eq!(
+            serde_json::from_str::<Integer>(json).unwrap(),
+            Integer { value }
+        );
+    }
+
+    #[test_case(r#"{"value":""}"#; "empty string")]
+    #[test_case(r#"{"value":"0x"}"#; "missing digits")]
+    #[test_case(r#"{"value":"0b102"}"#; "invalid binary digit")]
+    #[test_case(r#"{"value":"0x-1"}"#; "sign after prefix")]
+    #[test_case(r#"{"value":"--1"}"#; "double sign")]
+    #[test_case(r#"{"value":"0x8000000000000000"}"#; "overflow")]
+    fn test_deserialize_invalid_integer(json: &str) {
+        assert!(serde_json::from_str::<Integer>(json).is_err());
+    }
+
+    #[test_case(|value| format!("{value}"); "decimal")]
+    #[test_case(|value| format!("0x{value:X}"); "hexadecimal")]
+    #[test_case(|value| format!("0b{value:b}"); "binary")]
+    #[test_case(|value| format!("0o{value:o}"); "octal")]
+    fn test_integer_radix_round_trip(format: fn(u64) -> String) {
+        for value in [0, 1, -15, 255, i64::MAX, i64::MIN] {
+            let sign = if value < 0 { "-" } else { "" };
+            let literal = format!("{sign}{}", format(value.unsigned_abs()));
+            let json = serde_json::json!({ "value": literal }).to_string();
+            let integer = serde_json::from_str::<Integer>(&json).unwrap();
+            assert_eq!(integer, Integer { value });
+            // Integers are serialized as plain numbers, which can also be read back.
+            let json = serde_json::to_string(&integer).unwrap();
+            assert_eq!(serde_json::from_str::<Integer>(&json).unwrap(), integer);
+        }
+    }
+
+    #[test_case(
+        r#"{"definitions":[{"EmptyTypeDefinition":{"id":"Unit","doc":"A type with one value."}},{"Assignment":{"assignee":{"assignee":{"id":"x"},"generic_variables":[]},"expression":{"Integer":{"value":3}},"doc":"Three.\nOn two lines."}}]}"#;
+        "documented"
+    )]
+    #[test_case(
+        r#"{"definitions":[{"EmptyTypeDefinition":{"id":"Unit"}},{"Assignment":{"assignee":{"assignee":{"id":"x"},"generic_variables":[]},"expression":{"Integer":{"value":3}}}}]}"#;
+        "undocumented"
+    )]
+    fn test_doc_round_trip(json: &str) {
+        let program = serde_json::from_str::<Program>(json).unwrap();
+        assert_eq!(serde_json::to_string(&program).unwrap(), json);
+    }
+
     #[test]
     fn test() {
         let string = "{\"definitions\": []}\n";
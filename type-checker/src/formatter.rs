@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::{
+    operators::{is_operator, operator_precedence, Assoc},
+    Annotated, AtomicType, AtomicTypeEnum, Block, ConstructorCall, ElementAccess, Expression,
+    FieldAccess, FunctionCall, FunctionDefinition, FunctionType, GenericConstructor, GenericType,
+    GenericVariable, Id, IfExpression, MatchBlock, MatchExpression, MatchItem, RecordExpression,
+    RecordField, RecordType, RecordTypeField, TupleExpression, TupleType, TypeInstance,
+};
+
+/// Convert AST nodes back into source code.
+pub struct Formatter {
+    precedence: HashMap<Id, (u8, Assoc)>,
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter {
+    pub fn new() -> Self {
+        Formatter {
+            precedence: operator_precedence(),
+        }
+    }
+    /// Format an expression, only including parentheses where they are needed.
+    pub fn format_expression(&self, expression: &Expression) -> String {
+        if let Some((operator, left, right)) = self.infix_call(expression) {
+            let (precedence, assoc) = self.precedence[operator];
+            return format!(
+                "{} {operator} {}",
+                self.format_operand(left, precedence, assoc, Assoc::Left),
+                self.format_operand(right, precedence, assoc, Assoc::Right)
+            );
+        }
+        match expression {
+            Expression::Integer(integer) => integer.value.to_string(),
+            Expression::Boolean(boolean) => boolean.value.to_string(),
+            Expression::Str(string) => format!("{:?}", string.value),
+            Expression::Char(character) => match char::from_u32(character.value) {
+                Some(character) => format!("{character:?}"),
+                None => format!("chr({})", character.value),
+            },
+            Expression::TupleExpression(TupleExpression { expressions }) => {
+                match expressions.as_slice() {
+                    [expression] => format!("({},)", self.format_expression(expression)),
+                    expressions => format!("({})", self.format_expressions(expressions)),
+                }
+            }
+            Expression::GenericVariable(GenericVariable { id, type_instances }) => {
+                let id = if is_operator(id) {
+                    format!("__{id}__")
+                } else {
+                    id.clone()
+                };
+                format!("{id}{}", Self::format_type_instances(type_instances))
+            }
+            Expression::ElementAccess(ElementAccess { expression, index }) => {
+                format!("{}.{index}", self.format_head(expression))
+            }
+            Expression::RecordExpression(RecordExpression { fields }) => format!(
+                "{{{}}}",
+                fields
+                    .iter()
+                    .map(|RecordField { id, expression }| format!(
+                        "{id}: {}",
+                        self.format_expression(expression)
+                    ))
+                    .join(", ")
+            ),
+            Expression::FieldAccess(FieldAccess { expression, field }) => {
+                format!("{}.{field}", self.format_head(expression))
+            }
+            Expression::IfExpression(IfExpression {
+                condition,
+                true_block,
+                false_block,
+            }) => format!(
+                "if ({}) {}{}",
+                self.format_expression(condition),
+                self.format_block(true_block),
+                false_block
+                    .as_ref()
+                    .map(|block| format!(" else {}", self.format_block(block)))
+                    .unwrap_or_default()
+            ),
+            Expression::MatchExpression(MatchExpression { subject, blocks }) => format!(
+                "match ({}) {{{}}}",
+                self.format_expression(subject),
+                blocks
+                    .iter()
+                    .map(|MatchBlock { matches, block }| format!(
+                        "{}: {}",
+                        matches.iter().map(Self::format_match_item).join(" | "),
+                        self.format_block(block)
+                    ))
+                    .join(", ")
+            ),
+            Expression::FunctionDefinition(FunctionDefinition {
+                parameters,
+                return_type,
+                body,
+            }) => format!(
+                "({}) -> {} {}",
+                parameters
+                    .iter()
+                    .map(|parameter| format!(
+                        "{}: {}",
+                        parameter.assignee.id,
+                        Self::format_type(&parameter.type_)
+                    ))
+                    .join(", "),
+                Self::format_type(return_type),
+                self.format_block(body)
+            ),
+            Expression::FunctionCall(FunctionCall {
+                function,
+                arguments,
+            }) => match (function.as_ref(), arguments.as_slice()) {
+                (
+                    Expression::GenericVariable(GenericVariable { id, type_instances }),
+                    [argument],
+                ) if is_operator(id) && type_instances.is_empty() => {
+                    format!(
+                        "{id}{}",
+                        self.format_operand(argument, 0, Assoc::None, Assoc::Right)
+                    )
+                }
+                _ => format!(
+                    "{}({})",
+                    self.format_head(function),
+                    self.format_expressions(arguments)
+                ),
+            },
+            Expression::ConstructorCall(ConstructorCall {
+                constructor: GenericConstructor { id, type_instances },
+                arguments,
+            }) => format!(
+                "{id}{}{{{}}}",
+                Self::format_type_instances(type_instances),
+                self.format_expressions(arguments)
+            ),
+            Expression::Annotated(Annotated { expression, type_ }) => format!(
+                "({} : {})",
+                self.format_expression(expression),
+                Self::format_type(type_)
+            ),
+        }
+    }
+    fn format_match_item(
+        MatchItem {
+            type_name,
+            assignee,
+            pattern,
+        }: &MatchItem,
+    ) -> String {
+        let mut item = type_name.clone();
+        if let Some(assignee) = assignee {
+            item = format!("{item} {}", assignee.id);
+        }
+        if let Some(pattern) = pattern {
+            item = format!("{item} ({})", Self::format_match_item(pattern));
+        }
+        item
+    }
+    fn format_expressions(&self, expressions: &[Expression]) -> String {
+        expressions
+            .iter()
+            .map(|expression| self.format_expression(expression))
+            .join(", ")
+    }
+    fn format_block(
+        &self,
+        Block {
+            assignments,
+            expression,
+        }: &Block,
+    ) -> String {
+        format!(
+            "{{{}{}}}",
+            assignments
+                .iter()
+                .map(|assignment| {
+                    let generics = &assignment.assignee.generic_variables;
+                    format!(
+                        "{}{} = {}; ",
+                        assignment.assignee.id(),
+                        if generics.is_empty() {
+                            String::new()
+                        } else {
+                            format!("<{}>", generics.join(", "))
+                        },
+                        self.format_expression(&assignment.expression)
+                    )
+                })
+                .join(""),
+            self.format_expression(expression)
+        )
+    }
+    /// Split an expression into an operator and its operands if it is an infix call.
+    fn infix_call<'a>(
+        &self,
+        expression: &'a Expression,
+    ) -> Option<(&'a Id, &'a Expression, &'a Expression)> {
+        match expression {
+            Expression::FunctionCall(FunctionCall {
+                function,
+                arguments,
+            }) => match (function.as_ref(), arguments.as_slice()) {
+                (
+                    Expression::GenericVariable(GenericVariable { id, type_instances }),
+                    [left, right],
+                ) if type_instances.is_empty() && self.precedence.contains_key(id) => {
+                    Some((id, left, right))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// Format the operand of an operator, adding parentheses if it would not otherwise bind.
+    fn format_operand(
+        &self,
+        operand: &Expression,
+        precedence: u8,
+        assoc: Assoc,
+        side: Assoc,
+    ) -> String {
+        let formatted = self.format_expression(operand);
+        let needs_parentheses = match self.infix_call(operand) {
+            Some((operator, _, _)) => {
+                let (operand_precedence, _) = self.precedence[operator];
+                operand_precedence > precedence
+                    || (operand_precedence == precedence && assoc != side)
+            }
+            None => self.is_prefix_call(operand),
+        };
+        if needs_parentheses {
+            format!("({formatted})")
+        } else {
+            formatted
+        }
+    }
+    /// Format an expression that is called or accessed.
+    fn format_head(&self, expression: &Expression) -> String {
+        let formatted = self.format_expression(expression);
+        if self.infix_call(expression).is_some() || self.is_prefix_call(expression) {
+            format!("({formatted})")
+        } else {
+            formatted
+        }
+    }
+    fn is_prefix_call(&self, expression: &Expression) -> bool {
+        matches!(
+            expression,
+            Expression::FunctionCall(FunctionCall { function, arguments })
+                if arguments.len() == 1 && matches!(
+                    function.as_ref(),
+                    Expression::GenericVariable(GenericVariable { id, type_instances })
+                        if is_operator(id) && type_instances.is_empty()
+                )
+        )
+    }
+    fn format_type_instances(type_instances: &[TypeInstance]) -> String {
+        if type_instances.is_empty() {
+            String::new()
+        } else {
+            format!(
+                ".<{}>",
+                type_instances.iter().map(Self::format_type).join(", ")
+            )
+        }
+    }
+    fn format_type(type_instance: &TypeInstance) -> String {
+        match type_instance {
+            TypeInstance::AtomicType(AtomicType {
+                type_: AtomicTypeEnum::INT,
+            }) => String::from("int"),
+            TypeInstance::AtomicType(AtomicType {
+                type_: AtomicTypeEnum::BOOL,
+            }) => String::from("bool"),
+            TypeInstance::AtomicType(AtomicType {
+                type_: AtomicTypeEnum::STRING,
+            }) => String::from("string"),
+            TypeInstance::AtomicType(AtomicType {
+                type_: AtomicTypeEnum::CHAR,
+            }) => String::from("char"),
+            TypeInstance::TupleType(TupleType { types }) => match types.as_slice() {
+                [type_] => format!("({},)", Self::format_type(type_)),
+                types => format!("({})", types.iter().map(Self::format_type).join(", ")),
+            },
+            TypeInstance::FunctionType(FunctionType {
+                argument_types,
+                return_type,
+            }) => format!(
+                "({}) -> {}",
+                argument_types.iter().map(Self::format_type).join(", "),
+                Self::format_type(return_type)
+            ),
+            TypeInstance::GenericType(GenericType { id, type_variables }) => {
+                format!("{id}{}", Self::format_type_instances(type_variables))
+            }
+            TypeInstance::RecordType(RecordType { fields }) => format!(
+                "{{{}}}",
+                fields
+                    .iter()
+                    .map(|RecordTypeField { id, type_ }| format!(
+                        "{id}: {}",
+                        Self::format_type(type_)
+                    ))
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Integer, Var, ATOMIC_TYPE_INT};
+    use test_case::test_case;
+
+    fn infix(operator: &str, left: Expression, right: Expression) -> Expression {
+        FunctionCall {
+            function: Box::new(Var(operator).into()),
+            arguments: vec![left, right],
+        }
+        .into()
+    }
+
+    #[test_case(
+        infix("+", Var("a").into(), infix("*", Var("b").into(), Var("c").into())),
+        "a + b * c";
+        "higher precedence right operand"
+    )]
+    #[test_case(
+        infix("*", infix("+", Var("a").into(), Var("b").into()), Var("c").into()),
+        "(a + b) * c";
+        "lower precedence left operand"
+    )]
+    #[test_case(
+        infix("-", infix("-", Var("a").into(), Var("b").into()), Var("c").into()),
+        "(a - b) - c";
+        "right associative operator"
+    )]
+    #[test_case(
+        infix("**", infix("**", Var("a").into(), Var("b").into()), Var("c").into()),
+        "a ** b ** c";
+        "left associative operator"
+    )]
+    #[test_case(
+        infix("==", infix("<", Var("a").into(), Var("b").into()), Var("c").into()),
+        "(a < b) == c";
+        "non-associative operator"
+    )]
+    #[test_case(
+        infix(
+            "&&",
+            FunctionCall {
+                function: Box::new(Var("!").into()),
+                arguments: vec![Var("a").into()]
+            }.into(),
+            Var("b").into()
+        ),
+        "(!a) && b";
+        "prefix operand"
+    )]
+    #[test_case(
+        ElementAccess {
+            expression: Box::new(infix("+", Var("a").into(), Integer{value: 1}.into())),
+            index: 0
+        }.into(),
+        "(a + 1).0";
+        "access of infix call"
+    )]
+    #[test_case(
+        FunctionCall {
+            function: Box::new(Var("f").into()),
+            arguments: vec![
+                infix("|", Var("a").into(), Var("b").into()),
+                Var("+").into()
+            ]
+        }.into(),
+        "f(a | b, __+__)";
+        "function call arguments"
+    )]
+    #[test_case(
+        Annotated {
+            expression: Box::new(infix("+", Var("a").into(), Integer{value: 1}.into())),
+            type_: ATOMIC_TYPE_INT.into()
+        }.into(),
+        "(a + 1 : int)";
+        "annotation"
+    )]
+    fn test_format_expression(expression: Expression, expected: &str) {
+        assert_eq!(Formatter::new().format_expression(&expression), expected);
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let precedence = operator_precedence();
+        assert!(precedence[&Id::from("*")].0 < precedence[&Id::from("+")].0);
+        assert!(precedence[&Id::from("+")].0 < precedence[&Id::from("==")].0);
+        assert!(precedence[&Id::from("&&")].0 < precedence[&Id::from("||")].0);
+    }
+}
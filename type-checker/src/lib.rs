@@ -1,10 +1,21 @@
 mod ast_nodes;
-mod prefix;
+mod formatter;
+mod operators;
+mod parse_error;
+mod serialization;
 mod type_check;
 mod type_check_nodes;
 mod utils;
 
 use ast_nodes::*;
-pub use ast_nodes::{AtomicTypeEnum, Boolean, Id, Integer, Program};
-pub use type_check::{TypeChecker, DEFAULT_CONTEXT};
+pub use ast_nodes::{
+    AtomicTypeEnum, Boolean, Char, Definition, Expression, Id, Integer, Program, Str,
+};
+pub use formatter::Formatter;
+pub use operators::{operator_precedence, Assoc};
+pub use parse_error::ParseError;
+pub use type_check::{
+    BuiltInShadowing, IntWidth, Shadowing, TypeCheckOptions, TypeChecker, CHAR_COMPARISONS,
+    DEFAULT_CONTEXT, STRING_COMPARISONS, UNARY_OPERATORS,
+};
 pub use type_check_nodes::*;
@@ -0,0 +1,51 @@
+use std::{
+    io::{self, Read},
+    process,
+};
+
+use clap::{Parser, ValueEnum};
+use type_checker::{Program, TypeCheckError, TypeChecker};
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Emit {
+    /// Rust debug formatting.
+    Debug,
+    /// JSON with shared types stored in tables.
+    Json,
+}
+
+#[derive(Parser)]
+struct Cli {
+    /// Format of the type-checked program.
+    #[arg(long, value_enum, default_value_t = Emit::Debug)]
+    emit: Emit,
+}
+
+fn main() {
+    let args = Cli::parse();
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .expect("Failed to read from stdin");
+    // Deserialize the JSON from the stdin.
+    let program = Program::from_json(&input).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        process::exit(1)
+    });
+    match TypeChecker::type_check(program) {
+        Ok(type_checked_program) => match args.emit {
+            Emit::Debug => println!("{:?}", type_checked_program),
+            Emit::Json => println!(
+                "{}",
+                serde_json::to_string(&type_checked_program).expect("Failed to serialize program")
+            ),
+        },
+        Err(TypeCheckError::MissingMain) => {
+            eprintln!("Error: no `main` function defined.");
+            process::exit(1)
+        }
+        Err(e) => {
+            panic!("{:?}", e)
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use crate::Id;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Assoc {
+    Left,
+    Right,
+    None,
+}
+
+/// Precedence and associativity of the built-in infix operators (lower values bind more tightly).
+pub fn operator_precedence() -> HashMap<Id, (u8, Assoc)> {
+    // Matches the table used by the parser.
+    [
+        ("**", 3, Assoc::Left),
+        ("*", 4, Assoc::Right),
+        ("/", 5, Assoc::Right),
+        ("%", 6, Assoc::Right),
+        ("+", 7, Assoc::Right),
+        ("-", 7, Assoc::Right),
+        (">>", 8, Assoc::Right),
+        (">>>", 8, Assoc::Right),
+        ("<<", 8, Assoc::Right),
+        ("<=>", 10, Assoc::None),
+        ("<", 11, Assoc::None),
+        ("<=", 11, Assoc::None),
+        (">", 11, Assoc::None),
+        (">=", 11, Assoc::None),
+        ("==", 11, Assoc::None),
+        ("!=", 11, Assoc::None),
+        ("&", 12, Assoc::Right),
+        ("^", 13, Assoc::Right),
+        ("|", 14, Assoc::Right),
+        ("&&", 15, Assoc::Right),
+        ("||", 16, Assoc::Right),
+    ]
+    .into_iter()
+    .map(|(operator, precedence, assoc)| (Id::from(operator), (precedence, assoc)))
+    .collect()
+}
+
+/// Determine whether an id is made up of operator symbols.
+pub fn is_operator(id: &Id) -> bool {
+    !id.is_empty() && id.chars().all(|c| "&|!+-^$<>@:*%/=.~".contains(c))
+}
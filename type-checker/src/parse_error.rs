@@ -0,0 +1,163 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::Program;
+
+/// Error from reading a program from JSON, with the location of the problem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Description of the problem from the deserializer, without its position.
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    /// Byte offset into the input of the token where the problem was detected.
+    pub offset: usize,
+    /// Keys of the objects and arrays that are open at the offset, outermost first.
+    pub path: Vec<String>,
+}
+
+impl ParseError {
+    fn new(input: &str, error: serde_json::Error) -> Self {
+        let (line, column) = (error.line(), error.column());
+        let message = error.to_string();
+        let message = message
+            .strip_suffix(&format!(" at line {line} column {column}"))
+            .unwrap_or(&message)
+            .to_string();
+        let offset = Self::offset(input, line, column);
+        ParseError {
+            message,
+            line,
+            column,
+            offset,
+            path: Self::open_keys(input, offset),
+        }
+    }
+    /// Convert a one-based line and column into a byte offset.
+    fn offset(input: &str, line: usize, column: usize) -> usize {
+        let start = input
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>();
+        (start + column.saturating_sub(1)).min(input.len())
+    }
+    /// Find the keys of the objects and arrays that are open before the offset, outermost first.
+    fn open_keys(input: &str, offset: usize) -> Vec<String> {
+        let mut open = Vec::new();
+        let mut key = None;
+        let mut last_string = None;
+        let mut string_start = None;
+        let mut escaped = false;
+        for (i, c) in input[..offset].char_indices() {
+            if let Some(start) = string_start {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    last_string = Some(&input[start..i]);
+                    string_start = None;
+                }
+                continue;
+            }
+            match c {
+                '"' => string_start = Some(i + 1),
+                ':' => key = last_string.take(),
+                '{' | '[' => open.push(key.take()),
+                '}' | ']' => {
+                    open.pop();
+                    key = None;
+                }
+                ',' => key = None,
+                _ => {}
+            }
+        }
+        open.into_iter().flatten().map(String::from).collect()
+    }
+    /// Innermost node (such as `FunctionCall`) that was being read when the problem was detected.
+    pub fn node(&self) -> Option<&str> {
+        self.path
+            .iter()
+            .rev()
+            .find(|key| key.starts_with(|c: char| c.is_ascii_uppercase()))
+            .map(String::as_str)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid program at line {}, column {} (byte {}): {}",
+            self.line, self.column, self.offset, self.message
+        )?;
+        if let Some(node) = self.node() {
+            write!(f, " (while reading `{node}` at `{}`)", self.path.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Program {
+    /// Deserialize a program from the JSON output of the parser.
+    pub fn from_json(input: &str) -> Result<Program, ParseError> {
+        let mut deserializer = serde_json::Deserializer::from_str(input);
+        // Programs may be nested more deeply than the default limit.
+        deserializer.disable_recursion_limit();
+        Program::deserialize(&mut deserializer)
+            .and_then(|program| deserializer.end().map(|()| program))
+            .map_err(|error| ParseError::new(input, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_case::test_case;
+
+    #[test_case(
+        r#"{"definitions":[{"Assignment":{"assignee":{"assignee":{"id":"x"},"generic_variables":[]},"expression":{"Integer":{"value":3}}}}]}"#,
+        Ok(1);
+        "valid"
+    )]
+    #[test_case(
+        r#"{"definitions":[{"Assignment":{"assignee":{"assignee":{"id":"x"},"generic_variables":[]},"expression":{"Integr":{"value":3}}}}]}"#,
+        Err(Some("Assignment"));
+        "unknown variant"
+    )]
+    #[test_case(
+        r#"{"definitions":[{"Assignment":{"assignee":{"assignee":{"id":"x"},"generic_variables":[]},"expression":{"FunctionCall":{"function":{"Integer":{"value":3}}}}}}]}"#,
+        Err(Some("FunctionCall"));
+        "missing field"
+    )]
+    #[test_case(r#"{"definitions":[]"#, Err(None); "unclosed object")]
+    #[test_case(r#"{"definitions":[]} []"#, Err(None); "trailing characters")]
+    fn test_from_json(input: &str, expected: Result<usize, Option<&str>>) {
+        match (Program::from_json(input), expected) {
+            (Ok(program), Ok(definitions)) => assert_eq!(program.definitions.len(), definitions),
+            (Err(error), Err(node)) => {
+                assert_eq!(error.node(), node);
+                assert!(error.offset <= input.len());
+                assert!(error.to_string().contains(&error.message));
+            }
+            (result, _) => panic!("Unexpected result {result:?}."),
+        }
+    }
+
+    #[test]
+    fn test_from_json_location() {
+        let input = "{\"definitions\": [\n  {\"Assignment\": 3}\n]}";
+        let error = Program::from_json(input).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            &input[..error.offset],
+            "{\"definitions\": [\n  {\"Assignment\": "
+        );
+        assert_eq!(error.path, vec!["definitions"]);
+    }
+}
@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::ser::{Error, SerializeStruct};
+use serde::{Serialize, Serializer};
+
+use crate::{Id, ParametricType, Type, TypeDefinitions, TypedProgram};
+
+/// Tables of shared types that are referenced by id during serialization.
+#[derive(Default)]
+struct Interner {
+    types: HashMap<*const RefCell<ParametricType>, usize>,
+    pending_types: Vec<Rc<RefCell<ParametricType>>>,
+    type_variables: HashMap<*const RefCell<Option<Type>>, usize>,
+    pending_type_variables: Vec<Rc<RefCell<Option<Type>>>>,
+}
+
+thread_local! {
+    static INTERNER: RefCell<Option<Interner>> = const { RefCell::new(None) };
+}
+
+impl Interner {
+    fn with<T>(f: impl FnOnce(&mut Interner) -> T) -> Result<T, String> {
+        INTERNER.with(|interner| match interner.borrow_mut().as_mut() {
+            Some(interner) => Ok(f(interner)),
+            None => Err(String::from(
+                "Shared types can only be serialized as part of a program.",
+            )),
+        })
+    }
+    fn type_id(reference: &Rc<RefCell<ParametricType>>) -> Result<usize, String> {
+        Self::with(|interner| {
+            let idx = interner.types.len();
+            *interner
+                .types
+                .entry(Rc::as_ptr(reference))
+                .or_insert_with(|| {
+                    // Keep the type alive so that its address is not reused.
+                    interner.pending_types.push(reference.clone());
+                    idx
+                })
+        })
+    }
+    fn type_variable_id(variable: &Rc<RefCell<Option<Type>>>) -> Result<usize, String> {
+        Self::with(|interner| {
+            let idx = interner.type_variables.len();
+            *interner
+                .type_variables
+                .entry(Rc::as_ptr(variable))
+                .or_insert_with(|| {
+                    interner.pending_type_variables.push(variable.clone());
+                    idx
+                })
+        })
+    }
+    /// Serialize any newly referenced values, returning whether there were any.
+    fn drain<T: Serialize>(
+        values: &mut Vec<serde_json::Value>,
+        pending: impl Fn(&Interner, usize) -> Option<Rc<RefCell<T>>>,
+    ) -> Result<bool, String> {
+        let start = values.len();
+        while let Some(value) = Self::with(|interner| pending(interner, values.len()))? {
+            values.push(serde_json::to_value(&*value.borrow()).map_err(|e| e.to_string())?);
+        }
+        Ok(values.len() > start)
+    }
+}
+
+pub fn serialize_type_reference<S: Serializer>(
+    reference: &Rc<RefCell<ParametricType>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(Interner::type_id(reference).map_err(S::Error::custom)? as u64)
+}
+
+pub fn serialize_type_variable<S: Serializer>(
+    variable: &Rc<RefCell<Option<Type>>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(Interner::type_variable_id(variable).map_err(S::Error::custom)? as u64)
+}
+
+pub fn serialize_type_variables<S: Serializer>(
+    variables: &[Rc<RefCell<Option<Type>>>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        variables
+            .iter()
+            .map(Interner::type_variable_id)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(S::Error::custom)?,
+    )
+}
+
+pub fn serialize_named_type_variables<S: Serializer>(
+    variables: &[(Id, Rc<RefCell<Option<Type>>>)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let variables = variables
+        .iter()
+        .map(|(id, variable)| Ok((id, Interner::type_variable_id(variable)?)))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(S::Error::custom)?;
+    serializer.collect_seq(variables)
+}
+
+impl Serialize for TypeDefinitions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut definitions = self.0.iter().collect::<Vec<_>>();
+        definitions.sort_by_key(|(id, _)| *id);
+        let definitions = definitions
+            .into_iter()
+            .map(|(id, reference)| Ok((id, Interner::type_id(reference)?)))
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(S::Error::custom)?;
+        serializer.collect_map(definitions)
+    }
+}
+
+/// Serialize a program with each shared type emitted once in a table and referenced by its index.
+impl Serialize for TypedProgram {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let previous = INTERNER.with(|interner| interner.replace(Some(Interner::default())));
+        let tables = (|| {
+            let type_definitions =
+                serde_json::to_value(&self.type_definitions).map_err(|e| e.to_string())?;
+            let main = serde_json::to_value(&self.main).map_err(|e| e.to_string())?;
+            let mut types = Vec::new();
+            let mut type_variables = Vec::new();
+            // Types and type variables can reference each other so repeat until neither changes.
+            while Interner::drain(&mut types, |interner, idx| {
+                interner.pending_types.get(idx).cloned()
+            })? | Interner::drain(&mut type_variables, |interner, idx| {
+                interner.pending_type_variables.get(idx).cloned()
+            })? {}
+            Ok((type_definitions, main, types, type_variables))
+        })();
+        INTERNER.with(|interner| interner.replace(previous));
+        let (type_definitions, main, types, type_variables) =
+            tables.map_err(|e: String| S::Error::custom(e))?;
+        let mut program = serializer.serialize_struct("TypedProgram", 5)?;
+        program.serialize_field("type_definitions", &type_definitions)?;
+        program.serialize_field("main", &main)?;
+        program.serialize_field("types", &types)?;
+        program.serialize_field("type_variables", &type_variables)?;
+        if self.docs.is_empty() {
+            program.skip_field("docs")?;
+        } else {
+            program.serialize_field("docs", &self.docs)?;
+        }
+        program.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        Assignment, ExpressionBlock, FunctionDefinition, GenericTypeVariable, Integer, Program,
+        TupleType, TypeChecker, TypeInstantiation, TypeItem, Typename, UnionTypeDefinition,
+        VariableAssignee, ATOMIC_TYPE_INT, TYPE_INT,
+    };
+
+    #[test]
+    fn test_recursive_type_serialization() {
+        // `type List = Cons (int, List) | Nil; main = () -> int { 0 }`
+        let program = TypeChecker::type_check(Program {
+            definitions: vec![
+                UnionTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from("List"),
+                        generic_variables: Vec::new(),
+                    },
+                    items: vec![
+                        TypeItem {
+                            id: Id::from("Cons"),
+                            types: vec![TupleType {
+                                types: vec![ATOMIC_TYPE_INT.into(), Typename("List").into()],
+                            }
+                            .into()],
+                        },
+                        TypeItem {
+                            id: Id::from("Nil"),
+                            types: Vec::new(),
+                        },
+                    ],
+                    doc: None,
+                }
+                .into(),
+                Assignment {
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: Vec::new(),
+                            return_type: ATOMIC_TYPE_INT.into(),
+                            body: ExpressionBlock(Integer { value: 0 }.into()),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+            ],
+        })
+        .unwrap();
+        let json = serde_json::to_value(&program).unwrap();
+        let idx = &json["type_definitions"]["List"];
+        let list = &json["types"][idx.as_u64().unwrap() as usize]["type_"]["TypeUnion"];
+        assert_eq!(list["id"], "List");
+        assert_eq!(
+            list["variants"][0]["TypeTuple"][1]["TypeInstantiation"]["reference"],
+            *idx
+        );
+        assert_eq!(list["variants"][1], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_shared_type_outside_program() {
+        let type_ = Type::from(TypeInstantiation {
+            reference: Rc::new(RefCell::new(TYPE_INT.into())),
+            instances: Vec::new(),
+        });
+        assert!(serde_json::to_value(&type_).is_err());
+    }
+}
@@ -1,30 +1,35 @@
-use crate::prefix::prefix;
+use crate::operators::is_operator;
 use crate::type_check_nodes::{
-    ConstructorType, GenericVariables, ParametricType, Type, TypeCheckError, TypeContext,
-    TypeDefinitions, TypedAccess, TypedAssignment, TypedBlock, TypedConstructorCall,
-    TypedElementAccess, TypedExpression, TypedFunctionCall, TypedIf, TypedLambdaDef, TypedMatch,
-    TypedMatchBlock, TypedMatchItem, TypedProgram, TypedTuple, TypedVariable, TYPE_BOOL, TYPE_INT,
+    ConstructorType, GenericParameterSite, GenericVariables, ParametricType, Type, TypeCheckError,
+    TypeCheckWarning, TypeContext, TypeContextExt, TypeDefinitions, TypedAccess, TypedAssignment,
+    TypedBlock, TypedConstructorCall, TypedElementAccess, TypedExpression, TypedFieldAccess,
+    TypedFunctionCall, TypedIf, TypedLambdaDef, TypedMatch, TypedMatchBlock, TypedMatchItem,
+    TypedProgram, TypedRecord, TypedTuple, TypedVariable, TYPE_BOOL, TYPE_CHAR, TYPE_INT,
+    TYPE_STRING, TYPE_UNIT,
 };
 use crate::utils::UniqueError;
 use crate::{
-    utils, AtomicType, AtomicTypeEnum, Block, ConstructorCall, Definition, ElementAccess,
-    EmptyTypeDefinition, Expression, FunctionCall, FunctionDefinition, FunctionType, GenericType,
-    GenericTypeVariable, GenericVariable, Id, IfExpression, MatchExpression, OpaqueTypeDefinition,
-    ParametricExpression, Program, TransparentTypeDefinition, TupleExpression, TupleType,
-    TypeAtomic, TypeFn, TypeInstance, TypeInstantiation, TypeTuple, TypeUnion, TypeVariable,
-    TypedFnDef, TypedStatement, UnionTypeDefinition, Variable,
+    utils, Annotated, Assignee, Assignment, AtomicType, AtomicTypeEnum, Block, ConstructorCall,
+    Definition, ElementAccess, EmptyTypeDefinition, Expression, FieldAccess, FunctionCall,
+    FunctionDefinition, FunctionType, GenericConstructor, GenericType, GenericTypeVariable,
+    GenericVariable, Id, IfExpression, Integer, MatchBlock, MatchExpression, MatchItem,
+    OpaqueTypeDefinition, ParametricExpression, Program, RecordExpression, RecordField, RecordType,
+    RecordTypeField, Str, TransparentTypeDefinition, TupleExpression, TupleType, TypeArray,
+    TypeAtomic, TypeClass, TypeFn, TypeInstance, TypeInstantiation, TypeRecord, TypeTuple,
+    TypeUnion, TypeVariable, TypedAssignee, TypedFnDef, TypedStatement, UnionTypeDefinition,
+    Variable, VoidTypeDefinition,
 };
 use itertools::Either::*;
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use std::cell::RefCell;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 use strum::IntoEnumIterator;
 
 thread_local! {pub static DEFAULT_CONTEXT: Lazy<TypeContext> = Lazy::new(|| {
     let integer_binary_operators = [
-        "**", "*", "/", "%", "+", "-", ">>", "<<", "<=>", "&", "^", "|",
+        "**", "*", "/", "%", "+", "-", ">>", ">>>", "<<", "<=>", "&", "^", "|",
     ]
     .into_iter()
     .map(|operator| {
@@ -33,18 +38,58 @@ thread_local! {pub static DEFAULT_CONTEXT: Lazy<TypeContext> = Lazy::new(|| {
             Type::from(TypeFn(vec![TYPE_INT, TYPE_INT], Box::new(TYPE_INT))),
         )
     });
-    let integer_unary_operators = ["++", "--"].into_iter().map(|operator| {
+    let integer_unary_operators = ["++", "--", "~"].into_iter().map(|operator| {
         (
             Id::from(operator),
             Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_INT))),
         )
     });
+    let integer_functions = [
+        (
+            Id::from("min"),
+            Type::from(TypeFn(vec![TYPE_INT, TYPE_INT], Box::new(TYPE_INT))),
+        ),
+        (
+            Id::from("max"),
+            Type::from(TypeFn(vec![TYPE_INT, TYPE_INT], Box::new(TYPE_INT))),
+        ),
+        (
+            Id::from("abs"),
+            Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_INT))),
+        ),
+        (
+            // `fma(a, b, c)` computes `a * b + c`.
+            Id::from("fma"),
+            Type::from(TypeFn(vec![TYPE_INT, TYPE_INT, TYPE_INT], Box::new(TYPE_INT))),
+        ),
+    ];
+    // Arithmetic with explicit behavior on overflow.
+    let integer_overflow_operators = ["+sat", "-sat", "*sat", "+wrap", "-wrap", "*wrap"]
+        .into_iter()
+        .map(|operator| {
+            (
+                Id::from(operator),
+                Type::from(TypeFn(vec![TYPE_INT, TYPE_INT], Box::new(TYPE_INT))),
+            )
+        });
+    // Calls to `&&` and `||` short-circuit when they are lowered.
+    let boolean_binary_operators = ["&&", "||"].into_iter().map(|operator| {
+        (
+            Id::from(operator),
+            Type::from(TypeFn(vec![TYPE_BOOL, TYPE_BOOL], Box::new(TYPE_BOOL))),
+        )
+    });
     let boolean_unary_operators = ["!"].into_iter().map(|operator| {
         (
             Id::from(operator),
             Type::from(TypeFn(vec![TYPE_BOOL], Box::new(TYPE_BOOL))),
         )
     });
+    let boolean_functions = [(
+        // `assert` aborts the program when its argument is false.
+        Id::from("assert"),
+        Type::from(TypeFn(vec![TYPE_BOOL], Box::new(TYPE_UNIT))),
+    )];
     let integer_comparisons = ["<", "<=", ">", ">=", "==", "!="]
         .into_iter()
         .map(|operator| {
@@ -53,28 +98,307 @@ thread_local! {pub static DEFAULT_CONTEXT: Lazy<TypeContext> = Lazy::new(|| {
                 Type::from(TypeFn(vec![TYPE_INT, TYPE_INT], Box::new(TYPE_BOOL))),
             )
         });
+    let string_functions = [
+        (
+            Id::from("concat"),
+            Type::from(TypeFn(vec![TYPE_STRING, TYPE_STRING], Box::new(TYPE_STRING))),
+        ),
+        (
+            Id::from("length"),
+            Type::from(TypeFn(vec![TYPE_STRING], Box::new(TYPE_INT))),
+        ),
+    ];
+    let char_functions = [
+        (
+            Id::from("ord"),
+            Type::from(TypeFn(vec![TYPE_CHAR], Box::new(TYPE_INT))),
+        ),
+        (
+            Id::from("chr"),
+            Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_CHAR))),
+        ),
+    ];
+    let parameter = Rc::new(RefCell::new(None));
+    let error_parameter = Rc::new(RefCell::new(None));
+    let iterate_parameter = Rc::new(RefCell::new(None));
+    let iterated = Type::from(TypeVariable(iterate_parameter.clone()));
+    let generic_functions = [
+        (
+            Id::from("eq"),
+            ParametricType {
+                type_: TypeFn(
+                    vec![
+                        TypeVariable(parameter.clone()).into(),
+                        TypeVariable(parameter.clone()).into(),
+                    ],
+                    Box::new(TYPE_BOOL),
+                )
+                .into(),
+                parameters: vec![parameter],
+            },
+        ),
+        (
+            // `error` aborts the program so its result can have any type.
+            Id::from("error"),
+            ParametricType {
+                type_: TypeFn(
+                    vec![TYPE_STRING],
+                    Box::new(TypeVariable(error_parameter.clone()).into()),
+                )
+                .into(),
+                parameters: vec![error_parameter],
+            },
+        ),
+        (
+            // `iterate(n, f, x)` applies `f` to `x` `n` times.
+            Id::from("iterate"),
+            ParametricType {
+                type_: TypeFn(
+                    vec![
+                        TYPE_INT,
+                        TypeFn(vec![iterated.clone()], Box::new(iterated.clone())).into(),
+                        iterated.clone(),
+                    ],
+                    Box::new(iterated),
+                )
+                .into(),
+                parameters: vec![iterate_parameter],
+            },
+        ),
+    ];
+    // Array built-ins are generic over the type of their elements.
+    fn array(element: Type) -> Type {
+        TypeArray(Box::new(element)).into()
+    }
+    let array_function = |id: &str, arguments: fn(Type) -> Vec<Type>, return_type: fn(Type) -> Type| {
+        let parameter = Rc::new(RefCell::new(None));
+        let element = Type::from(TypeVariable(parameter.clone()));
+        (
+            Id::from(id),
+            ParametricType {
+                type_: TypeFn(arguments(element.clone()), Box::new(return_type(element))).into(),
+                parameters: vec![parameter],
+            },
+        )
+    };
+    let array_functions = [
+        array_function("empty", |_| Vec::new(), array),
+        array_function("push", |element| vec![array(element.clone()), element], array),
+        array_function("get", |element| vec![array(element), TYPE_INT], |element| element),
+        array_function("len", |element| vec![array(element)], |_| TYPE_INT),
+        array_function(
+            "preduce",
+            |element| {
+                vec![
+                    TypeFn(vec![element.clone(), element.clone()], Box::new(element.clone())).into(),
+                    array(element),
+                ]
+            },
+            |element| element,
+        ),
+    ];
+    // `pmap` is also generic over the type of the results.
+    let element_parameter = Rc::new(RefCell::new(None));
+    let result_parameter = Rc::new(RefCell::new(None));
+    let element = Type::from(TypeVariable(element_parameter.clone()));
+    let result = Type::from(TypeVariable(result_parameter.clone()));
+    let parallel_functions = [(
+        Id::from("pmap"),
+        ParametricType {
+            type_: TypeFn(
+                vec![
+                    TypeFn(vec![element.clone()], Box::new(result.clone())).into(),
+                    array(element),
+                ],
+                Box::new(array(result)),
+            )
+            .into(),
+            parameters: vec![element_parameter, result_parameter],
+        },
+    )];
     TypeContext::from_iter(
         integer_binary_operators
             .chain(integer_comparisons)
             .chain(integer_unary_operators)
+            .chain(integer_functions)
+            .chain(integer_overflow_operators)
+            .chain(boolean_binary_operators)
             .chain(boolean_unary_operators)
-            .map(|(id, type_)| (id, type_.into())),
+            .chain(boolean_functions)
+            .chain(string_functions)
+            .chain(char_functions)
+            .map(|(id, type_)| (id, type_.into()))
+            .chain(generic_functions.map(|(id, type_)| (id, type_.into())))
+            .chain(array_functions.map(|(id, type_)| (id, type_.into())))
+            .chain(parallel_functions.map(|(id, type_)| (id, type_.into()))),
     )
 });
+/// Overloads of built-in operators that apply to strings.
+pub static STRING_COMPARISONS: Lazy<TypeContext> = Lazy::new(|| {
+    TypeContext::from_iter(["==", "!="].into_iter().map(|operator| {
+        (
+            Id::from(operator),
+            Type::from(TypeFn(vec![TYPE_STRING, TYPE_STRING], Box::new(TYPE_BOOL))).into(),
+        )
+    }))
+});
+/// Overloads of built-in operators that apply to characters.
+pub static CHAR_COMPARISONS: Lazy<TypeContext> = Lazy::new(|| {
+    TypeContext::from_iter(["==", "!="].into_iter().map(|operator| {
+        (
+            Id::from(operator),
+            Type::from(TypeFn(vec![TYPE_CHAR, TYPE_CHAR], Box::new(TYPE_BOOL))).into(),
+        )
+    }))
+});
+/// Overloads of built-in operators that apply to a single integer.
+pub static UNARY_OPERATORS: Lazy<TypeContext> = Lazy::new(|| {
+    TypeContext::from_iter(["-"].into_iter().map(|operator| {
+        (
+            Id::from(operator),
+            Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_INT))).into(),
+        )
+    }))
+});
 }
 
+/// Name of the built-in parametric array type.
+pub const ARRAY_TYPE_NAME: &str = "array";
+
+/// Separator between a module and a name defined in it, as in `list::Cons`.
+pub const MODULE_SEPARATOR: &str = "::";
+
+/// Match item name that covers all remaining variants.
+const WILDCARD: &str = "_";
+
 #[derive(Debug)]
 pub struct TypeChecker {
     type_definitions: TypeDefinitions,
     constructors: HashMap<Id, ConstructorType>,
+    deny_unused_bindings: bool,
+    shadowing: Shadowing,
+    built_in_shadowing: BuiltInShadowing,
+    int_width: IntWidth,
+    /// Type variables for type parameters that are inferred from their context.
+    inferred_types: RefCell<Vec<Rc<RefCell<Option<Type>>>>>,
+    /// Names of generic variables, indexed by the type variables that represent them.
+    generic_names: RefCell<HashMap<*mut Option<Type>, Id>>,
+    /// Type classes that generic variables are bounded by, indexed by the type variables that represent them.
+    generic_bounds: RefCell<HashMap<*mut Option<Type>, Vec<TypeClass>>>,
+    /// Errors in independent subexpressions after the first, if all errors are being reported.
+    collected_errors: Option<Rc<RefCell<Vec<TypeCheckError>>>>,
+    /// Warnings found so far, which are returned with the typed program.
+    warnings: RefCell<Vec<TypeCheckWarning>>,
+}
+
+/// How to handle a variable that is assigned more than once in the same block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Shadowing {
+    /// The last assignment is used.
+    #[default]
+    LastWins,
+    /// Reassignments are reported as duplicated names.
+    Deny,
+}
+
+/// How to handle a binding that has the same name as a built-in operator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BuiltInShadowing {
+    /// The binding is used and reported as a `TypeCheckWarning`.
+    #[default]
+    Warn,
+    /// The binding is used silently.
+    Allow,
+    /// The binding is reported as a `BuiltInShadow`.
+    Deny,
+}
+
+/// The number of bits in the runtime representation of integers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntWidth(pub u32);
+
+impl Default for IntWidth {
+    fn default() -> Self {
+        IntWidth(64)
+    }
+}
+
+impl IntWidth {
+    /// Determine whether a value can be represented as a signed integer of this width.
+    pub fn contains(&self, value: i64) -> bool {
+        let IntWidth(bits) = *self;
+        if bits >= i64::BITS {
+            return true;
+        }
+        let bound = 1i64 << (bits - 1);
+        -bound <= value && value < bound
+    }
+}
+
+/// Options for checking a whole program.
+#[derive(Clone, Debug, Default)]
+pub struct TypeCheckOptions {
+    /// Call `main` with these integer arguments instead of passing them through.
+    pub main_args: Option<Vec<i64>>,
+    /// Report variables and functions in blocks that are never used.
+    pub deny_unused_bindings: bool,
+    /// Whether variables can be reassigned in the same block.
+    pub shadowing: Shadowing,
+    /// Whether bindings can have the same name as built-in operators.
+    pub built_in_shadowing: BuiltInShadowing,
+    /// Width that all integer literals must fit into.
+    pub int_width: IntWidth,
 }
 
 impl TypeChecker {
+    /// Create a type checker for these type definitions with the default options.
+    fn new(type_definitions: TypeDefinitions, constructors: HashMap<Id, ConstructorType>) -> Self {
+        TypeChecker {
+            type_definitions,
+            constructors,
+            deny_unused_bindings: false,
+            shadowing: Shadowing::default(),
+            built_in_shadowing: BuiltInShadowing::default(),
+            int_width: IntWidth::default(),
+            inferred_types: RefCell::new(Vec::new()),
+            generic_names: RefCell::new(HashMap::new()),
+            generic_bounds: RefCell::new(HashMap::new()),
+            collected_errors: None,
+            warnings: RefCell::new(Vec::new()),
+        }
+    }
+    /// Module that a name is qualified with, if any.
+    fn module_of(id: &Id) -> Option<&str> {
+        id.rsplit_once(MODULE_SEPARATOR).map(|(module, _)| module)
+    }
+    /// Prefix a name with a module unless it is already qualified.
+    fn qualify(id: Id, module: Option<&str>) -> Id {
+        match module {
+            Some(module) if !id.contains(MODULE_SEPARATOR) => {
+                format!("{module}{MODULE_SEPARATOR}{id}")
+            }
+            _ => id,
+        }
+    }
     /// Translate AST nodes that represent types into type checking nodes.
     fn convert_ast_type(
         type_instance: TypeInstance,
         type_definitions: &TypeDefinitions,
         generic_variables: &GenericVariables,
+    ) -> Result<Type, TypeCheckError> {
+        TypeChecker::convert_ast_type_in_module(
+            type_instance,
+            type_definitions,
+            generic_variables,
+            None,
+        )
+    }
+    /// Translate a type that is written in a module, where unqualified names refer to the module's types before top-level ones.
+    fn convert_ast_type_in_module(
+        type_instance: TypeInstance,
+        type_definitions: &TypeDefinitions,
+        generic_variables: &GenericVariables,
+        module: Option<&str>,
     ) -> Result<Type, TypeCheckError> {
         Ok(match type_instance {
             TypeInstance::AtomicType(AtomicType {
@@ -90,7 +414,10 @@ impl TypeChecker {
                             type_instances: type_variables.clone(),
                         });
                     }
-                } else if let Some(reference) = type_definitions.get(&id) {
+                } else if let Some(reference) = type_definitions
+                    .get(&TypeChecker::qualify(id.clone(), module))
+                    .or_else(|| type_definitions.get(&id))
+                {
                     if type_variables.len() != reference.borrow().parameters.len() {
                         return Err(TypeCheckError::WrongNumberOfTypeParameters {
                             type_: reference.borrow().clone(),
@@ -103,9 +430,32 @@ impl TypeChecker {
                             type_variables,
                             type_definitions,
                             generic_variables,
+                            module,
                         )?,
                     }
                     .into()
+                } else if id == ARRAY_TYPE_NAME {
+                    let [type_variable] = <[TypeInstance; 1]>::try_from(type_variables.clone())
+                        .map_err(|_| {
+                            let parameter = Rc::new(RefCell::new(None));
+                            TypeCheckError::WrongNumberOfTypeParameters {
+                                type_: ParametricType {
+                                    type_: TypeArray(Box::new(
+                                        TypeVariable(parameter.clone()).into(),
+                                    ))
+                                    .into(),
+                                    parameters: vec![parameter],
+                                },
+                                type_instances: type_variables,
+                            }
+                        })?;
+                    TypeArray(Box::new(TypeChecker::convert_ast_type_in_module(
+                        type_variable,
+                        type_definitions,
+                        generic_variables,
+                        module,
+                    )?))
+                    .into()
                 } else {
                     return Err(TypeCheckError::UnknownError {
                         id,
@@ -119,7 +469,7 @@ impl TypeChecker {
                 }
             }
             TypeInstance::TupleType(TupleType { types }) => TypeTuple(
-                TypeChecker::convert_ast_types(types, type_definitions, generic_variables)?,
+                TypeChecker::convert_ast_types(types, type_definitions, generic_variables, module)?,
             )
             .into(),
             TypeInstance::FunctionType(FunctionType {
@@ -130,25 +480,59 @@ impl TypeChecker {
                     argument_types,
                     type_definitions,
                     generic_variables,
+                    module,
                 )?,
-                Box::new(TypeChecker::convert_ast_type(
+                Box::new(TypeChecker::convert_ast_type_in_module(
                     *return_type,
                     type_definitions,
                     generic_variables,
+                    module,
                 )?),
             )
             .into(),
+            TypeInstance::RecordType(RecordType { fields }) => {
+                if let Err(UniqueError { duplicate }) =
+                    utils::check_unique(fields.iter().map(|field| &field.id))
+                {
+                    return Err(TypeCheckError::DuplicateField {
+                        field: duplicate.clone(),
+                    });
+                }
+                TypeRecord(
+                    fields
+                        .into_iter()
+                        .map(|RecordTypeField { id, type_ }| {
+                            Ok((
+                                id,
+                                TypeChecker::convert_ast_type_in_module(
+                                    type_,
+                                    type_definitions,
+                                    generic_variables,
+                                    module,
+                                )?,
+                            ))
+                        })
+                        .collect::<Result<_, _>>()?,
+                )
+                .into()
+            }
         })
     }
     fn convert_ast_types(
         type_instances: Vec<TypeInstance>,
         type_definitions: &TypeDefinitions,
         generic_variables: &GenericVariables,
+        module: Option<&str>,
     ) -> Result<Vec<Type>, TypeCheckError> {
         type_instances
             .into_iter()
             .map(|type_instance| {
-                TypeChecker::convert_ast_type(type_instance, type_definitions, generic_variables)
+                TypeChecker::convert_ast_type_in_module(
+                    type_instance,
+                    type_definitions,
+                    generic_variables,
+                    module,
+                )
             })
             .collect::<Result<_, _>>()
     }
@@ -159,6 +543,7 @@ impl TypeChecker {
         let all_type_parameters = definitions.iter().map(Definition::get_parameters);
         let predefined_type_names = AtomicTypeEnum::iter()
             .map(|a| AtomicTypeEnum::to_string(&a).to_lowercase())
+            .chain(std::iter::once(Id::from(ARRAY_TYPE_NAME)))
             .collect_vec();
         if let Err(UniqueError { duplicate }) =
             utils::check_unique(type_names.clone().chain(predefined_type_names.iter()))
@@ -220,6 +605,7 @@ impl TypeChecker {
         let mut constructors = HashMap::new();
         for definition in definitions.clone() {
             let type_name = definition.get_id().clone();
+            let module = TypeChecker::module_of(&type_name);
             let type_reference = &type_definitions[&type_name];
             let type_ = match definition {
                 Definition::OpaqueTypeDefinition(OpaqueTypeDefinition {
@@ -229,6 +615,7 @@ impl TypeChecker {
                             generic_variables,
                         },
                     type_,
+                    doc: _,
                 }) => {
                     if let Some(_) = constructors.insert(
                         id.clone(),
@@ -244,10 +631,11 @@ impl TypeChecker {
                     }
                     TypeUnion {
                         id: id.clone(),
-                        variants: vec![Some(TypeChecker::convert_ast_type(
+                        variants: vec![Some(TypeChecker::convert_ast_type_in_module(
                             type_,
                             &type_definitions,
                             &GenericVariables::from((&generic_variables, &type_definitions[&id])),
+                            module,
                         )?)],
                     }
                     .into()
@@ -259,6 +647,7 @@ impl TypeChecker {
                             generic_variables,
                         },
                     items,
+                    doc: _,
                 }) => {
                     let variant_names = items.iter().map(|item| &item.id);
                     match utils::check_unique(variant_names.clone()) {
@@ -271,30 +660,41 @@ impl TypeChecker {
                         }
                     }
                     let variants = items.into_iter().enumerate().map(|(index, item)| {
+                        // Constructors of types in a module are qualified with the module too.
+                        let constructor = TypeChecker::qualify(item.id, module);
                         if let Some(_) = constructors.insert(
-                            item.id.clone(),
+                            constructor.clone(),
                             ConstructorType {
                                 type_: type_reference.clone(),
                                 index,
                             },
                         ) {
                             return Err(TypeCheckError::DuplicatedName {
-                                duplicate: item.id,
+                                duplicate: constructor,
                                 reason: String::from("constructor name"),
                             });
                         }
-                        item.type_
+                        let mut types = item
+                            .types
+                            .into_iter()
                             .map(|type_instance| {
-                                TypeChecker::convert_ast_type(
+                                TypeChecker::convert_ast_type_in_module(
                                     type_instance,
                                     &type_definitions,
                                     &GenericVariables::from((
                                         &generic_variables,
                                         &type_definitions[&id],
                                     )),
+                                    module,
                                 )
                             })
-                            .transpose()
+                            .collect::<Result<Vec<_>, _>>()?;
+                        // Variants with several fields store them as a tuple.
+                        Ok(match types.len() {
+                            0 => None,
+                            1 => types.pop(),
+                            _ => Some(TypeTuple(types).into()),
+                        })
                     });
                     TypeUnion {
                         id: id.clone(),
@@ -309,12 +709,14 @@ impl TypeChecker {
                             generic_variables,
                         },
                     type_,
-                }) => TypeChecker::convert_ast_type(
+                    doc: _,
+                }) => TypeChecker::convert_ast_type_in_module(
                     type_,
                     &type_definitions,
                     &GenericVariables::from((&generic_variables, &type_definitions[&id])),
+                    module,
                 )?,
-                Definition::EmptyTypeDefinition(EmptyTypeDefinition { id }) => {
+                Definition::EmptyTypeDefinition(EmptyTypeDefinition { id, doc: _ }) => {
                     if let Some(_) = constructors.insert(
                         id.clone(),
                         ConstructorType {
@@ -332,6 +734,13 @@ impl TypeChecker {
                         variants: vec![None],
                     })
                 }
+                // Void types have no constructors.
+                Definition::VoidTypeDefinition(VoidTypeDefinition { id, doc: _ }) => {
+                    Type::from(TypeUnion {
+                        id,
+                        variants: Vec::new(),
+                    })
+                }
                 Definition::Assignment(_) => continue,
             };
             if let Some(type_reference) = type_definitions.get_mut(&type_name) {
@@ -352,6 +761,7 @@ impl TypeChecker {
                             generic_variables: _,
                         },
                     type_: _,
+                    doc: _,
                 }) = definition
                 {
                     Some(id)
@@ -362,79 +772,90 @@ impl TypeChecker {
             .collect_vec();
         transparent_definitions.into_iter().try_for_each(|id| {
             id.map_or(Ok(()), |id| {
-                if TypeChecker::is_self_recursive(&id, &type_definitions).is_err() {
-                    Err(TypeCheckError::RecursiveTypeAlias { type_alias: id })
-                } else {
-                    Ok(())
-                }
+                TypeChecker::is_self_recursive(&id, &type_definitions).map_err(|cycle| {
+                    TypeCheckError::RecursiveTypeAlias {
+                        type_alias: id,
+                        cycle,
+                    }
+                })
             })
         })?;
 
-        return Ok(TypeChecker {
-            type_definitions,
-            constructors,
-        });
+        Ok(TypeChecker::new(type_definitions, constructors))
     }
-    /// Check whether a defined id is recursive.
-    fn is_self_recursive(id: &Id, definitions: &TypeDefinitions) -> Result<(), ()> {
+    /// Check whether a defined id is recursive, returning the names in the cycle if it is.
+    fn is_self_recursive(id: &Id, definitions: &TypeDefinitions) -> Result<(), Vec<Id>> {
         let start = definitions.get(id).unwrap();
         let mut queue = VecDeque::from([start.clone()]);
         let mut visited: HashMap<*mut ParametricType, bool> =
             HashMap::from_iter(definitions.values().map(|p| (p.as_ptr(), false)));
+        let names: HashMap<*mut ParametricType, &Id> =
+            HashMap::from_iter(definitions.0.iter().map(|(id, p)| (p.as_ptr(), id)));
+        // Record the definition that each definition was first reached from.
+        let mut parents: HashMap<*mut ParametricType, *mut ParametricType> = HashMap::new();
         // Perform BFS on definitions.
-        fn update_queue(
-            type_: &Type,
-            start: &Rc<RefCell<ParametricType>>,
-            queue: &mut VecDeque<Rc<RefCell<ParametricType>>>,
-            visited: &mut HashMap<*mut ParametricType, bool>,
-        ) -> Result<(), ()> {
-            match type_ {
-                Type::TypeUnion(TypeUnion {
-                    id: _,
-                    variants: items,
-                }) => {
-                    for type_ in items {
-                        if let Some(type_) = type_ {
-                            update_queue(type_, start, queue, visited)?;
+        while let Some(current) = queue.pop_front() {
+            let definition = current.borrow();
+            // Traverse each type with an explicit stack to handle deeply nested types.
+            let mut stack = vec![&definition.type_];
+            while let Some(type_) = stack.pop() {
+                match type_ {
+                    Type::TypeUnion(TypeUnion {
+                        id: _,
+                        variants: items,
+                    }) => stack.extend(items.iter().flatten()),
+                    Type::TypeInstantiation(TypeInstantiation {
+                        reference: rc,
+                        instances: ts,
+                    }) => {
+                        if rc.as_ptr() == start.as_ptr() {
+                            // Follow the parents back to the start to find the cycle.
+                            let mut cycle = vec![names[&current.as_ptr()].clone()];
+                            let mut ptr = current.as_ptr();
+                            while ptr != start.as_ptr() {
+                                ptr = parents[&ptr];
+                                cycle.push(names[&ptr].clone());
+                            }
+                            cycle.reverse();
+                            return Err(cycle);
                         }
+                        if !visited.get(&rc.as_ptr()).unwrap() {
+                            visited.insert(rc.as_ptr(), true);
+                            parents.insert(rc.as_ptr(), current.as_ptr());
+                            queue.push_back(rc.clone());
+                        }
+                        stack.extend(ts);
                     }
-                }
-                Type::TypeInstantiation(TypeInstantiation {
-                    reference: rc,
-                    instances: ts,
-                }) => {
-                    if rc.as_ptr() == start.as_ptr() {
-                        return Err(());
-                    }
-                    if !visited.get(&rc.as_ptr()).unwrap() {
-                        visited.insert(rc.as_ptr(), true);
-                        queue.push_back(rc.clone());
-                    }
-                    update_queue(&TypeTuple(ts.clone()).into(), start, queue, visited)?
-                }
-                Type::TypeTuple(TypeTuple(types)) => {
-                    for type_ in types {
-                        update_queue(type_, start, queue, visited)?;
+                    Type::TypeTuple(TypeTuple(types)) => stack.extend(types),
+                    Type::TypeFn(TypeFn(argument_types, return_type)) => {
+                        stack.extend(argument_types);
+                        stack.push(&**return_type);
                     }
+                    Type::TypeRecord(TypeRecord(fields)) => stack.extend(fields.values()),
+                    Type::TypeArray(TypeArray(type_)) => stack.push(&**type_),
+                    _ => (),
                 }
-                Type::TypeFn(TypeFn(argument_types, return_type)) => {
-                    update_queue(
-                        &TypeTuple(argument_types.clone()).into(),
-                        start,
-                        queue,
-                        visited,
-                    )?;
-                    update_queue(&*return_type, start, queue, visited)?;
-                }
-                _ => (),
             }
-            Ok(())
-        }
-        while let Some(rc) = queue.pop_front() {
-            update_queue(&rc.borrow().type_, start, &mut queue, &mut visited)?;
         }
         Ok(())
     }
+    /// Build a `TypeChecker` from type definitions so that it can be reused across many checks.
+    pub fn with_definitions(definitions: Vec<Definition>) -> Result<Self, TypeCheckError> {
+        TypeChecker::check_type_definitions(
+            definitions
+                .into_iter()
+                .filter(|definition| !matches!(definition, Definition::Assignment(_)))
+                .collect(),
+        )
+    }
+    /// Type check a single expression in a given context.
+    pub fn check_standalone(
+        &self,
+        expression: Expression,
+        context: &TypeContext,
+    ) -> Result<TypedExpression, TypeCheckError> {
+        self.check_expression(expression, context, &GenericVariables::new())
+    }
     /// Type check an expression.
     fn check_expression(
         &self,
@@ -443,8 +864,18 @@ impl TypeChecker {
         generic_variables: &GenericVariables,
     ) -> Result<TypedExpression, TypeCheckError> {
         Ok(match expression {
-            Expression::Integer(i) => i.into(),
+            Expression::Integer(i) => {
+                if !self.int_width.contains(i.value) {
+                    return Err(TypeCheckError::IntegerOutOfRange {
+                        value: i.value,
+                        width: self.int_width,
+                    });
+                }
+                i.into()
+            }
             Expression::Boolean(b) => b.into(),
+            Expression::Str(s) => s.into(),
+            Expression::Char(c) => c.into(),
             Expression::TupleExpression(TupleExpression { expressions }) => TypedTuple {
                 expressions: self.check_expressions(expressions, context, generic_variables)?,
             }
@@ -455,50 +886,104 @@ impl TypeChecker {
             Expression::ElementAccess(element_access) => self
                 .check_element_access(element_access, context, generic_variables)?
                 .into(),
+            Expression::RecordExpression(record) => self
+                .check_record_expression(record, context, generic_variables)?
+                .into(),
+            Expression::FieldAccess(field_access) => self
+                .check_field_access(field_access, context, generic_variables)?
+                .into(),
             Expression::IfExpression(if_expression) => self
                 .check_if_expression(if_expression, context, generic_variables)?
                 .into(),
             Expression::FunctionDefinition(fn_def) => self
                 .check_fn_def(fn_def, context, generic_variables)?
                 .into(),
-            Expression::FunctionCall(fn_call) => self
-                .check_fn_call(fn_call, context, generic_variables)?
-                .into(),
+            Expression::FunctionCall(fn_call) => {
+                self.check_fn_call(fn_call, context, generic_variables)?
+            }
             Expression::ConstructorCall(constructor_call) => self
                 .check_constructor_call(constructor_call, context, generic_variables)?
                 .into(),
+            Expression::MatchExpression(MatchExpression { subject, blocks })
+                if blocks.is_empty() =>
+            {
+                self.check_empty_match(*subject, context, generic_variables)?
+            }
             Expression::MatchExpression(match_expression) => self
                 .check_match_expression(match_expression, context, generic_variables)?
                 .into(),
+            Expression::Annotated(annotated) => {
+                self.check_annotated(annotated, context, generic_variables)?
+            }
         })
     }
+    /// Type check an expression and ensure that it has the annotated type, using it to infer omitted type parameters.
+    fn check_annotated(
+        &self,
+        Annotated { expression, type_ }: Annotated,
+        context: &TypeContext,
+        generic_variables: &GenericVariables,
+    ) -> Result<TypedExpression, TypeCheckError> {
+        let type_ =
+            TypeChecker::convert_ast_type(type_, &self.type_definitions, generic_variables)?;
+        let expression = self.check_expression(*expression, context, generic_variables)?;
+        self.unify(&expression.type_(), &type_);
+        if !Type::equality(&expression.type_(), &type_) {
+            return Err(TypeCheckError::AnnotationMismatch {
+                expression: Box::new(expression),
+                type_,
+            });
+        }
+        // The annotation has no effect on the typed program.
+        Ok(expression)
+    }
     /// Type check a variable.
     fn check_generic_variable(
+        &self,
+        generic_variable: GenericVariable,
+        context: &TypeContext,
+        generic_variables: &GenericVariables,
+    ) -> Result<TypedAccess, TypeCheckError> {
+        let access =
+            self.check_inferred_variable(generic_variable, context, generic_variables, false)?;
+        self.check_comparable(&access)?;
+        Ok(access)
+    }
+    /// Type check a variable, inferring omitted type parameters if requested.
+    fn check_inferred_variable(
         &self,
         GenericVariable { id, type_instances }: GenericVariable,
         context: &TypeContext,
         generic_variables: &GenericVariables,
+        infer: bool,
     ) -> Result<TypedAccess, TypeCheckError> {
         let variable = context.get(&id);
         match variable {
             Some(typed_variable) => {
                 let type_ = &typed_variable.type_;
-                if type_instances.len() != type_.parameters.len() {
-                    return Err(TypeCheckError::WrongNumberOfTypeParameters {
-                        type_: type_.clone(),
-                        type_instances,
-                    });
-                }
-                let types = type_instances
-                    .into_iter()
-                    .map(|type_instance| {
-                        TypeChecker::convert_ast_type(
-                            type_instance,
-                            &self.type_definitions,
-                            &generic_variables,
-                        )
-                    })
-                    .collect::<Result<_, _>>()?;
+                let types = if type_instances.is_empty()
+                    && (infer || Self::is_built_in("error", &typed_variable.variable))
+                {
+                    // The result of `error` takes whichever type its context requires.
+                    self.infer_type_parameters(type_)
+                } else {
+                    if type_instances.len() != type_.parameters.len() {
+                        return Err(TypeCheckError::WrongNumberOfTypeParameters {
+                            type_: type_.clone(),
+                            type_instances,
+                        });
+                    }
+                    type_instances
+                        .into_iter()
+                        .map(|type_instance| {
+                            TypeChecker::convert_ast_type(
+                                type_instance,
+                                &self.type_definitions,
+                                &generic_variables,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?
+                };
                 Ok(TypedAccess {
                     variable: typed_variable.clone(),
                     parameters: types,
@@ -513,6 +998,244 @@ impl TypeChecker {
             }
         }
     }
+    fn is_built_in(id: &str, variable: &Variable) -> bool {
+        DEFAULT_CONTEXT.with(|context| {
+            context
+                .get(&Id::from(id))
+                .is_some_and(|built_in| &built_in.variable == variable)
+        })
+    }
+    /// Determine whether a generic variable is bounded by a type class.
+    fn is_bounded(&self, variable: &Rc<RefCell<Option<Type>>>, class: TypeClass) -> bool {
+        self.generic_bounds
+            .borrow()
+            .get(&variable.as_ptr())
+            .is_some_and(|classes| classes.contains(&class))
+    }
+    /// Check that `eq` and generic variables bounded by `comparable` are only instantiated with comparable types.
+    fn check_comparable(
+        &self,
+        TypedAccess {
+            variable,
+            parameters,
+        }: &TypedAccess,
+    ) -> Result<(), TypeCheckError> {
+        let is_eq = Self::is_built_in("eq", &variable.variable);
+        let non_comparable = parameters
+            .iter()
+            .zip(&variable.type_.parameters)
+            .filter(|(_, parameter)| is_eq || self.is_bounded(parameter, TypeClass::Comparable))
+            .map(|(type_, _)| type_)
+            .find(|type_| {
+                !type_.is_comparable_assuming(&|variable| {
+                    self.is_bounded(variable, TypeClass::Comparable)
+                })
+            });
+        if let Some(type_) = non_comparable {
+            return Err(TypeCheckError::NonComparableType {
+                type_: type_.clone(),
+            });
+        }
+        Ok(())
+    }
+    /// Create type variables for type parameters that are inferred from their context.
+    fn infer_type_parameters(&self, type_: &ParametricType) -> Vec<Type> {
+        type_
+            .parameters
+            .iter()
+            .map(|_| {
+                let type_variable = Rc::new(RefCell::new(None));
+                self.inferred_types.borrow_mut().push(type_variable.clone());
+                TypeVariable(type_variable).into()
+            })
+            .collect()
+    }
+    /// Report bindings with the same name as a built-in operator.
+    fn check_built_in_shadowing<'a>(
+        &self,
+        names: impl Iterator<Item = &'a Id>,
+    ) -> Result<(), TypeCheckError> {
+        if self.built_in_shadowing == BuiltInShadowing::Allow {
+            return Ok(());
+        }
+        for name in names.unique().sorted() {
+            if !is_operator(name) || !DEFAULT_CONTEXT.with(|context| context.contains_key(name)) {
+                continue;
+            }
+            if self.built_in_shadowing == BuiltInShadowing::Deny {
+                return Err(TypeCheckError::BuiltInShadow { name: name.clone() });
+            }
+            self.warnings
+                .borrow_mut()
+                .push(TypeCheckWarning::BuiltInShadow { name: name.clone() });
+        }
+        Ok(())
+    }
+    /// Create type variables for generic variables, recording their names for error messages and their bounds.
+    fn declare_generic_variables(
+        &self,
+        ids: &Vec<Id>,
+        bounds: &BTreeMap<Id, Vec<TypeClass>>,
+    ) -> Result<GenericVariables, TypeCheckError> {
+        if let Some(id) = bounds.keys().find(|id| !ids.contains(id)) {
+            return Err(TypeCheckError::UnknownError {
+                place: String::from("generic variable"),
+                id: id.clone(),
+                options: ids.clone(),
+            });
+        }
+        let generic_variables = GenericVariables::from(ids);
+        self.generic_names.borrow_mut().extend(
+            ids.iter()
+                .map(|id| (generic_variables[id].as_ptr(), id.clone())),
+        );
+        self.generic_bounds.borrow_mut().extend(
+            bounds
+                .iter()
+                .map(|(id, classes)| (generic_variables[id].as_ptr(), classes.clone())),
+        );
+        Ok(generic_variables)
+    }
+    /// Suggest structural equality when a built-in comparison is applied to a tuple or union.
+    fn comparison_note(function: &TypedExpression, found: &Type) -> Option<String> {
+        let TypedExpression::TypedAccess(TypedAccess {
+            variable,
+            parameters: _,
+        }) = function
+        else {
+            return None;
+        };
+        let operator = ["==", "!=", "<", "<=", ">", ">="]
+            .into_iter()
+            .find(|operator| Self::is_built_in(operator, &variable.variable))?;
+        let is_structured = match found {
+            Type::TypeTuple(_) | Type::TypeUnion(_) => true,
+            Type::TypeInstantiation(TypeInstantiation {
+                reference,
+                instances,
+            }) => matches!(
+                reference.borrow().instantiate(instances),
+                Type::TypeUnion(_)
+            ),
+            _ => false,
+        };
+        is_structured.then(|| {
+            format!("`{operator}` only compares integers; use `eq` to compare tuples and unions structurally.")
+        })
+    }
+    /// Find the generic variable that the type of an argument to a generic fn was declared as.
+    fn generic_parameter_site(
+        &self,
+        function: &TypedExpression,
+        arg_index: usize,
+        id: Option<Id>,
+    ) -> Option<GenericParameterSite> {
+        let TypedExpression::TypedAccess(TypedAccess {
+            variable,
+            parameters: _,
+        }) = function
+        else {
+            return None;
+        };
+        let Type::TypeFn(TypeFn(argument_types, _)) = &variable.type_.type_ else {
+            return None;
+        };
+        let Type::TypeVariable(TypeVariable(parameter)) = &argument_types[arg_index] else {
+            return None;
+        };
+        let variable = self
+            .generic_names
+            .borrow()
+            .get(&parameter.as_ptr())?
+            .clone();
+        Some(GenericParameterSite {
+            variable,
+            function: id?,
+        })
+    }
+    fn is_inferred(&self, variable: &Rc<RefCell<Option<Type>>>) -> bool {
+        self.inferred_types
+            .borrow()
+            .iter()
+            .any(|inferred| Rc::ptr_eq(inferred, variable))
+    }
+    /// Bind inferred type variables so that two types match, where possible.
+    fn unify(&self, t1: &Type, t2: &Type) {
+        for (variable, type_) in [(t1, t2), (t2, t1)] {
+            let Type::TypeVariable(TypeVariable(variable)) = variable else {
+                continue;
+            };
+            if !self.is_inferred(variable) {
+                continue;
+            }
+            let bound = variable.borrow().clone();
+            match bound {
+                Some(bound) => return self.unify(&bound, type_),
+                // Only bind to resolved types so that instantiation does not have to follow chains.
+                None if !type_.has_unbound_variable(&|variable| self.is_inferred(variable)) => {
+                    *variable.borrow_mut() = Some(type_.instantiate());
+                    return;
+                }
+                None => {}
+            }
+        }
+        let unify_all = |types1: Vec<&Type>, types2: Vec<&Type>| {
+            for (t1, t2) in types1.into_iter().zip(types2) {
+                self.unify(t1, t2)
+            }
+        };
+        match (t1, t2) {
+            (
+                Type::TypeUnion(TypeUnion {
+                    id: id1,
+                    variants: variants1,
+                }),
+                Type::TypeUnion(TypeUnion {
+                    id: id2,
+                    variants: variants2,
+                }),
+            ) if id1 == id2 && variants1.len() == variants2.len() => unify_all(
+                variants1.iter().flatten().collect(),
+                variants2.iter().flatten().collect(),
+            ),
+            (
+                Type::TypeInstantiation(TypeInstantiation {
+                    reference: r1,
+                    instances: types1,
+                }),
+                Type::TypeInstantiation(TypeInstantiation {
+                    reference: r2,
+                    instances: types2,
+                }),
+            ) if Rc::ptr_eq(r1, r2) && types1.len() == types2.len() => {
+                unify_all(types1.iter().collect(), types2.iter().collect())
+            }
+            (Type::TypeTuple(TypeTuple(types1)), Type::TypeTuple(TypeTuple(types2)))
+                if types1.len() == types2.len() =>
+            {
+                unify_all(types1.iter().collect(), types2.iter().collect())
+            }
+            (Type::TypeRecord(TypeRecord(fields1)), Type::TypeRecord(TypeRecord(fields2)))
+                if fields1.keys().eq(fields2.keys()) =>
+            {
+                unify_all(fields1.values().collect(), fields2.values().collect())
+            }
+            (Type::TypeArray(TypeArray(type1)), Type::TypeArray(TypeArray(type2))) => {
+                self.unify(type1, type2)
+            }
+            (
+                Type::TypeFn(TypeFn(argument_types1, return_type1)),
+                Type::TypeFn(TypeFn(argument_types2, return_type2)),
+            ) if argument_types1.len() == argument_types2.len() => {
+                unify_all(
+                    argument_types1.iter().collect(),
+                    argument_types2.iter().collect(),
+                );
+                self.unify(return_type1, return_type2)
+            }
+            _ => {}
+        }
+    }
     fn check_element_access(
         &self,
         ElementAccess { expression, index }: ElementAccess,
@@ -537,10 +1260,54 @@ impl TypeChecker {
             index,
         })
     }
-    fn check_if_expression(
+    fn check_record_expression(
         &self,
-        IfExpression {
-            condition,
+        RecordExpression { fields }: RecordExpression,
+        context: &TypeContext,
+        generic_variables: &GenericVariables,
+    ) -> Result<TypedRecord, TypeCheckError> {
+        if let Err(UniqueError { duplicate }) =
+            utils::check_unique(fields.iter().map(|field| &field.id))
+        {
+            return Err(TypeCheckError::DuplicateField {
+                field: duplicate.clone(),
+            });
+        }
+        let fields = fields
+            .into_iter()
+            .map(|RecordField { id, expression }| {
+                Ok((
+                    id,
+                    self.check_expression(expression, context, generic_variables)?,
+                ))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(TypedRecord { fields })
+    }
+    fn check_field_access(
+        &self,
+        FieldAccess { expression, field }: FieldAccess,
+        context: &TypeContext,
+        generic_variables: &GenericVariables,
+    ) -> Result<TypedFieldAccess, TypeCheckError> {
+        let typed_expression = self.check_expression(*expression, context, generic_variables)?;
+        match typed_expression.type_() {
+            Type::TypeRecord(TypeRecord(fields)) if fields.contains_key(&field) => {
+                Ok(TypedFieldAccess {
+                    expression: Box::new(typed_expression),
+                    field,
+                })
+            }
+            _ => Err(TypeCheckError::InvalidFieldAccess {
+                expression: typed_expression,
+                field,
+            }),
+        }
+    }
+    fn check_if_expression(
+        &self,
+        IfExpression {
+            condition,
             true_block,
             false_block,
         }: IfExpression,
@@ -553,8 +1320,19 @@ impl TypeChecker {
         }
         let typed_true_block =
             self.check_block(true_block, context.clone(), generic_variables.clone())?;
+        // A missing else branch evaluates to unit, so the true branch must also be unit.
+        let false_block = false_block.unwrap_or_else(|| Block {
+            assignments: Vec::new(),
+            expression: Box::new(
+                TupleExpression {
+                    expressions: Vec::new(),
+                }
+                .into(),
+            ),
+        });
         let typed_false_block =
             self.check_block(false_block, context.clone(), generic_variables.clone())?;
+        self.unify(&typed_true_block.type_(), &typed_false_block.type_());
         if typed_true_block.type_() != typed_false_block.type_() {
             return Err(TypeCheckError::NonMatchingIfBlocks {
                 true_block: typed_true_block,
@@ -619,10 +1397,25 @@ impl TypeChecker {
             new_context.insert(id.clone(), variable.clone().into());
         }
         let body = self.check_block(body, new_context, generic_variables.clone())?;
+        self.unify(&return_type, &body.type_());
         if return_type != body.type_() {
+            let types = [&return_type, &body.type_()];
+            let generic_variables = generic_variables
+                .keys()
+                .filter(|id| {
+                    types.iter().any(|type_| {
+                        type_.has_unbound_variable(&|variable| {
+                            Rc::ptr_eq(variable, &generic_variables[id])
+                        })
+                    })
+                })
+                .cloned()
+                .sorted()
+                .collect_vec();
             return Err(TypeCheckError::FunctionReturnTypeMismatch {
                 return_type: return_type.clone(),
                 body,
+                generic_variables,
             });
         }
         Ok(TypedLambdaDef {
@@ -642,36 +1435,228 @@ impl TypeChecker {
         }: FunctionCall,
         context: &TypeContext,
         generic_variables: &GenericVariables,
-    ) -> Result<TypedFunctionCall, TypeCheckError> {
-        let function = self.check_expression(*function, context, generic_variables)?;
+    ) -> Result<TypedExpression, TypeCheckError> {
+        let fn_id = match function.as_ref() {
+            Expression::GenericVariable(GenericVariable {
+                id,
+                type_instances: _,
+            }) => Some(id.clone()),
+            _ => None,
+        };
+        let overload_id = match function.as_ref() {
+            Expression::GenericVariable(GenericVariable { id, type_instances })
+                if type_instances.is_empty() =>
+            {
+                Some(id.clone())
+            }
+            _ => None,
+        };
+        // Type parameters that are omitted from a called variable are inferred from the arguments.
+        let inferred_id = overload_id.clone().filter(|id| {
+            context.get(id).is_some_and(|variable| {
+                !variable.type_.parameters.is_empty()
+                    && !Self::is_built_in("error", &variable.variable)
+            })
+        });
+        let function = match (*function, &inferred_id) {
+            (Expression::GenericVariable(generic_variable), Some(_)) => self
+                .check_inferred_variable(generic_variable, context, generic_variables, true)?
+                .into(),
+            (function, _) => self.check_expression(function, context, generic_variables)?,
+        };
         let arguments = self.check_expressions(arguments, context, generic_variables)?;
         let types = TypedExpression::types(&arguments);
+        let function = match overload_id {
+            Some(id) => Self::resolve_overload(&id, function, &types),
+            None => function,
+        };
         let Type::TypeFn(TypeFn(argument_types, _)) = function.type_() else {
-            return Err(TypeCheckError::InvalidFunctionCall {
-                expression: function,
+            return Err(TypeCheckError::NonFunctionCall {
+                expression: Box::new(function),
                 arguments,
             });
         };
-        if argument_types != types {
+        // Calls with some but not all of the arguments are partial applications.
+        let is_partial = !types.is_empty() && types.len() < argument_types.len();
+        if argument_types.len() != types.len() && !is_partial {
+            return Err(TypeCheckError::ArityMismatch {
+                expected: argument_types.len(),
+                found: types.len(),
+            });
+        }
+        for (argument_type, type_) in argument_types.iter().zip(&types) {
+            self.unify(argument_type, type_);
+        }
+        let types = TypedExpression::types(&arguments);
+        let (function, argument_types) = match inferred_id {
+            Some(id) => {
+                let function = self.solve_type_parameters(id, function)?;
+                let Type::TypeFn(TypeFn(argument_types, _)) = function.type_() else {
+                    panic!("Inferring type parameters changed the type of a fn.")
+                };
+                (function, argument_types)
+            }
+            None => (function, argument_types),
+        };
+        if let Some(arg_index) = (0..types.len()).find(|&i| argument_types[i] != types[i]) {
+            let note = Self::comparison_note(&function, &types[arg_index]);
+            let generic_parameter = self.generic_parameter_site(&function, arg_index, fn_id);
             return Err(TypeCheckError::InvalidFunctionCall {
-                expression: function,
+                expression: Box::new(function),
                 arguments,
+                arg_index,
+                expected: Box::new(argument_types[arg_index].clone()),
+                found: Box::new(types[arg_index].clone()),
+                generic_parameter: generic_parameter.map(Box::new),
+                note,
             });
         }
+        if is_partial {
+            return Ok(Self::partially_apply(function, arguments));
+        }
         Ok(TypedFunctionCall {
             function: Box::new(function),
             arguments,
-        })
+        }
+        .into())
     }
-    fn check_constructor_call(
-        &self,
-        ConstructorCall {
-            constructor,
+    /// Convert a call with too few arguments into a fn that takes the remaining arguments.
+    /// `f(a)` with `f: (A, B) -> C` becomes `((a': A) -> (B) -> C { (b: B) -> C { f(a', b) } })(a)` so that `a` is only evaluated once.
+    fn partially_apply(
+        function: TypedExpression,
+        arguments: Vec<TypedExpression>,
+    ) -> TypedExpression {
+        let Type::TypeFn(TypeFn(argument_types, return_type)) = function.type_() else {
+            panic!("Partially applied a non-fn.")
+        };
+        let variable = |type_: &Type| TypedVariable {
+            variable: Variable::new(),
+            type_: type_.clone().into(),
+        };
+        let access = |variable: &TypedVariable| -> TypedExpression {
+            TypedAccess {
+                variable: variable.clone(),
+                parameters: Vec::new(),
+            }
+            .into()
+        };
+        let (captured, remaining) = argument_types.split_at(arguments.len());
+        let captured = captured.iter().map(variable).collect_vec();
+        let remaining = remaining.iter().map(variable).collect_vec();
+        let call_arguments = captured.iter().chain(&remaining).map(access).collect_vec();
+        // Accesses are cheap to repeat but other fns are evaluated once, along with the arguments.
+        let (function, captured, arguments) = match function {
+            TypedExpression::TypedAccess(_) => (function, captured, arguments),
+            function => {
+                let fn_variable = variable(&function.type_());
+                (
+                    access(&fn_variable),
+                    [fn_variable].into_iter().chain(captured).collect_vec(),
+                    [function].into_iter().chain(arguments).collect_vec(),
+                )
+            }
+        };
+        let partial: TypedExpression = TypedLambdaDef {
+            parameters: remaining,
+            return_type,
+            body: TypedBlock {
+                statements: Vec::new(),
+                expression: Box::new(
+                    TypedFunctionCall {
+                        function: Box::new(function),
+                        arguments: call_arguments,
+                    }
+                    .into(),
+                ),
+            },
+        }
+        .into();
+        TypedFunctionCall {
+            function: Box::new(
+                TypedLambdaDef {
+                    parameters: captured,
+                    return_type: Box::new(partial.type_()),
+                    body: TypedBlock {
+                        statements: Vec::new(),
+                        expression: Box::new(partial),
+                    },
+                }
+                .into(),
+            ),
             arguments,
-        }: ConstructorCall,
-        context: &TypeContext,
+        }
+        .into()
+    }
+    /// Replace inferred type parameters with their solutions, failing if the arguments do not determine them.
+    fn solve_type_parameters(
+        &self,
+        id: Id,
+        function: TypedExpression,
+    ) -> Result<TypedExpression, TypeCheckError> {
+        let TypedExpression::TypedAccess(TypedAccess {
+            variable,
+            parameters,
+        }) = function
+        else {
+            panic!("Inferred fn is no longer a variable.")
+        };
+        if parameters
+            .iter()
+            .any(|parameter| parameter.has_unbound_variable(&|variable| self.is_inferred(variable)))
+        {
+            return Err(TypeCheckError::AmbiguousTypeParameters {
+                id,
+                type_: variable.type_,
+            });
+        }
+        let access = TypedAccess {
+            variable,
+            parameters: Type::instantiate_types(&parameters),
+        };
+        self.check_comparable(&access)?;
+        Ok(access.into())
+    }
+    /// Replace a built-in operator with its overload when the arguments match the overload instead.
+    fn resolve_overload(id: &Id, function: TypedExpression, types: &Vec<Type>) -> TypedExpression {
+        let TypedExpression::TypedAccess(TypedAccess {
+            variable,
+            parameters: _,
+        }) = &function
+        else {
+            return function;
+        };
+        let is_built_in = DEFAULT_CONTEXT.with(|context| {
+            context
+                .get(id)
+                .is_some_and(|built_in| built_in.variable == variable.variable)
+        });
+        if !is_built_in {
+            return function;
+        }
+        let overload = [&STRING_COMPARISONS, &CHAR_COMPARISONS, &UNARY_OPERATORS]
+            .into_iter()
+            .filter_map(|overloads| overloads.with(|overloads| overloads.get(id).cloned()))
+            .find(|overload| {
+                matches!(
+                    &overload.type_.type_,
+                    Type::TypeFn(TypeFn(argument_types, _)) if argument_types == types
+                )
+            });
+        match overload {
+            Some(overload) => TypedAccess {
+                variable: overload,
+                parameters: Vec::new(),
+            }
+            .into(),
+            None => function,
+        }
+    }
+    /// Find the type that a constructor produces without checking its arguments.
+    fn constructor_output_type(
+        &self,
+        constructor: &GenericConstructor,
         generic_variables: &GenericVariables,
-    ) -> Result<TypedConstructorCall, TypeCheckError> {
+    ) -> Result<(&ConstructorType, Type), TypeCheckError> {
         let Some(constructor_type) = self.constructors.get(&constructor.id) else {
             return Err(TypeCheckError::UnknownError {
                 id: constructor.id.clone(),
@@ -682,17 +1667,31 @@ impl TypeChecker {
         if constructor.type_instances.len() != constructor_type.type_.borrow().parameters.len() {
             return Err(TypeCheckError::WrongNumberOfTypeParameters {
                 type_: constructor_type.type_.borrow().clone(),
-                type_instances: constructor.type_instances,
+                type_instances: constructor.type_instances.clone(),
             });
         }
-        let arguments = self.check_expressions(arguments, context, generic_variables)?;
-        let types = TypedExpression::types(&arguments);
         let type_variables = TypeChecker::convert_ast_types(
-            constructor.type_instances,
+            constructor.type_instances.clone(),
             &self.type_definitions,
             &generic_variables,
+            None,
         )?;
         let output_type = constructor_type.type_.borrow().instantiate(&type_variables);
+        Ok((constructor_type, output_type))
+    }
+    fn check_constructor_call(
+        &self,
+        ConstructorCall {
+            constructor,
+            arguments,
+        }: ConstructorCall,
+        context: &TypeContext,
+        generic_variables: &GenericVariables,
+    ) -> Result<TypedConstructorCall, TypeCheckError> {
+        let (constructor_type, output_type) =
+            self.constructor_output_type(&constructor, generic_variables)?;
+        let arguments = self.check_expressions(arguments, context, generic_variables)?;
+        let types = TypedExpression::types(&arguments);
         let Type::TypeUnion(TypeUnion {
             id: _,
             variants: variant_types,
@@ -701,23 +1700,32 @@ impl TypeChecker {
             panic!("Constructor call for non-union type.")
         };
         let input_type = variant_types[constructor_type.index].clone();
-        match input_type {
+        let invalid_arguments = |arguments| {
+            let Type::TypeUnion(TypeUnion { id: _, variants }) =
+                &constructor_type.type_.borrow().type_
+            else {
+                panic!("Constructor call for non-union type.")
+            };
+            Err(TypeCheckError::InvalidConstructorArguments {
+                id: constructor.id.clone(),
+                input_type: input_type.clone(),
+                generic_input_type: variants[constructor_type.index].clone().map(Box::new),
+                type_instances: constructor.type_instances.clone(),
+                arguments,
+            })
+        };
+        match &input_type {
+            // Multiple arguments fill the fields of a tuple variant.
+            Some(Type::TypeTuple(TypeTuple(field_types)))
+                if arguments.len() > 1 && *field_types == types => {}
             Some(type_) => {
                 if vec![type_.clone()] != types {
-                    return Err(TypeCheckError::InvalidConstructorArguments {
-                        id: constructor.id.clone(),
-                        input_type: Some(type_),
-                        arguments,
-                    });
+                    return invalid_arguments(arguments);
                 }
             }
             None => {
                 if !types.is_empty() {
-                    return Err(TypeCheckError::InvalidConstructorArguments {
-                        id: constructor.id.clone(),
-                        input_type,
-                        arguments,
-                    });
+                    return invalid_arguments(arguments);
                 }
             }
         }
@@ -727,6 +1735,112 @@ impl TypeChecker {
             output_type,
         })
     }
+    /// Names of the constructors of a union type in order of their indices.
+    fn constructor_names(&self, id: &Id) -> Vec<Id> {
+        self.constructors
+            .iter()
+            .filter(|(_, constructor)| {
+                matches!(
+                    &constructor.type_.borrow().type_,
+                    Type::TypeUnion(TypeUnion { id: name, .. }) if name == id
+                )
+            })
+            .sorted_by_key(|(_, constructor)| constructor.index)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+    /// Replace items with nested patterns by a single arm per variant that binds the payload and matches on it.
+    fn expand_nested_patterns(blocks: Vec<MatchBlock>) -> Vec<MatchBlock> {
+        let mut expanded = Vec::new();
+        let mut nested: Vec<(MatchItem, Vec<MatchBlock>)> = Vec::new();
+        for MatchBlock { matches, block } in blocks {
+            let (plain, patterns): (Vec<_>, Vec<_>) = matches
+                .into_iter()
+                .partition(|item| item.pattern.is_none() || item.type_name == WILDCARD);
+            for MatchItem {
+                type_name,
+                assignee,
+                pattern,
+            } in patterns
+            {
+                // Names with spaces cannot clash with variables from the source.
+                let assignee =
+                    assignee.unwrap_or_else(|| Assignee::from(format!("{type_name} payload")));
+                let item = MatchItem {
+                    type_name,
+                    assignee: Some(assignee),
+                    pattern: None,
+                };
+                let inner_block = MatchBlock {
+                    matches: pattern.into_iter().map(|pattern| *pattern).collect(),
+                    block: block.clone(),
+                };
+                match nested.iter_mut().find(|(existing, _)| *existing == item) {
+                    Some((_, inner_blocks)) => inner_blocks.push(inner_block),
+                    None => nested.push((item, vec![inner_block])),
+                }
+            }
+            if !plain.is_empty() {
+                expanded.push(MatchBlock {
+                    matches: plain,
+                    block,
+                });
+            }
+        }
+        expanded.extend(nested.into_iter().map(|(item, blocks)| {
+            let subject = GenericVariable {
+                id: item.assignee.as_ref().unwrap().id.clone(),
+                type_instances: Vec::new(),
+            };
+            MatchBlock {
+                matches: vec![item],
+                block: Block {
+                    assignments: Vec::new(),
+                    expression: Box::new(
+                        MatchExpression {
+                            subject: Box::new(subject.into()),
+                            blocks,
+                        }
+                        .into(),
+                    ),
+                },
+            }
+        }));
+        expanded
+    }
+    /// Check a match without arms, which is only exhaustive when the subject has a void type.
+    fn check_empty_match(
+        &self,
+        subject: Expression,
+        context: &TypeContext,
+        generic_variables: &GenericVariables,
+    ) -> Result<TypedExpression, TypeCheckError> {
+        let subject = self.check_expression(subject, context, generic_variables)?;
+        let Type::TypeUnion(TypeUnion { id, variants }) = subject.type_() else {
+            return Err(TypeCheckError::NonUnionTypeMatchSubject(subject));
+        };
+        if !variants.is_empty() {
+            return Err(TypeCheckError::NonExhaustiveMatch {
+                missing: self.constructor_names(&id),
+                type_name: id,
+            });
+        }
+        // Void values are never created, so the match is unreachable and can have any type.
+        let unreachable = FunctionCall {
+            function: Box::new(
+                GenericVariable {
+                    id: Id::from("error"),
+                    type_instances: Vec::new(),
+                }
+                .into(),
+            ),
+            arguments: vec![Str {
+                value: format!("Unreachable match on void type {id}."),
+            }
+            .into()],
+        };
+        DEFAULT_CONTEXT.with(|context| self.check_fn_call(unreachable, context, generic_variables))
+    }
     fn check_match_expression(
         &self,
         MatchExpression { subject, blocks }: MatchExpression,
@@ -734,6 +1848,8 @@ impl TypeChecker {
         generic_variables: &GenericVariables,
     ) -> Result<TypedMatch, TypeCheckError> {
         let subject = self.check_expression(*subject, context, generic_variables)?;
+        // Inner matches are checked recursively, so each level must be exhaustive.
+        let blocks = Self::expand_nested_patterns(blocks);
         let Type::TypeUnion(TypeUnion { id, variants }) = subject.type_() else {
             return Err(TypeCheckError::NonUnionTypeMatchSubject(subject));
         };
@@ -748,13 +1864,14 @@ impl TypeChecker {
             })
             .concat();
         if let Err(UniqueError { duplicate }) = utils::check_unique(variant_names.iter()) {
-            return Err(TypeCheckError::DuplicatedName {
-                duplicate: duplicate.clone(),
-                reason: String::from("match block variant name"),
+            return Err(TypeCheckError::RedundantMatchArm {
+                type_name: id,
+                variant: duplicate.clone(),
             });
         }
         let Some(variant_lookup) = variant_names
             .iter()
+            .filter(|variant_name| *variant_name != WILDCARD)
             .map(|variant_name| {
                 self.constructors
                     .get(variant_name)
@@ -773,14 +1890,31 @@ impl TypeChecker {
         }) {
             return Err(TypeCheckError::IncorrectVariants { blocks });
         }
-        let constructor_indices = variant_lookup
-            .values()
-            .map(|constructor| constructor.index)
-            .sorted()
+        let missing = self
+            .constructor_names(&id)
+            .into_iter()
+            .filter(|name| !variant_lookup.contains_key(name))
             .collect_vec();
-        if constructor_indices != (0..variants.len() as usize).collect_vec() {
-            return Err(TypeCheckError::IncorrectVariants { blocks });
-        }
+        // A wildcard covers all the variants without explicit arms.
+        let wildcard_indices = if variant_names.contains(&Id::from(WILDCARD)) {
+            if missing.is_empty() {
+                return Err(TypeCheckError::RedundantMatchArm {
+                    type_name: id,
+                    variant: Id::from(WILDCARD),
+                });
+            }
+            missing
+                .iter()
+                .map(|name| self.constructors[name].index)
+                .collect_vec()
+        } else if !missing.is_empty() {
+            return Err(TypeCheckError::NonExhaustiveMatch {
+                type_name: id,
+                missing,
+            });
+        } else {
+            Vec::new()
+        };
         let blocks = blocks
             .into_iter()
             .map(|block| {
@@ -788,6 +1922,16 @@ impl TypeChecker {
                     .matches
                     .iter()
                     .map(|item| {
+                        if item.type_name == WILDCARD {
+                            return match (&item.assignee, &item.pattern) {
+                                (None, None) => Ok(None),
+                                (assignee, _) => Err(TypeCheckError::MismatchedVariant {
+                                    type_: subject.type_(),
+                                    variant_id: item.type_name.clone(),
+                                    assignee: assignee.clone(),
+                                }),
+                            };
+                        }
                         match (
                             &item.assignee,
                             &variants[variant_lookup[&item.type_name].index],
@@ -802,6 +1946,25 @@ impl TypeChecker {
                         }
                     })
                     .collect::<Result<Vec<_>, _>>()?;
+                let binds_one_name = assignments
+                    .iter()
+                    .map(|assignment| assignment.as_ref().map(|(id, _)| id))
+                    .all_equal_value()
+                    .is_ok_and(|id| id.is_some());
+                if binds_one_name && !assignments.iter().all_equal() {
+                    return Err(TypeCheckError::IncompatibleMultiMatchBinding {
+                        variants: block
+                            .matches
+                            .iter()
+                            .map(|item| item.type_name.clone())
+                            .collect(),
+                        types: assignments
+                            .into_iter()
+                            .flatten()
+                            .map(|(_, type_)| type_.clone())
+                            .collect(),
+                    });
+                }
                 let assignee = if assignments.iter().all_equal() {
                     assignments.first().unwrap().clone()
                 } else {
@@ -816,9 +1979,21 @@ impl TypeChecker {
                 let match_items = block
                     .matches
                     .into_iter()
-                    .map(|item| TypedMatchItem {
-                        type_idx: variant_lookup[&item.type_name].index,
-                        assignee: variable.clone(),
+                    .flat_map(|item| {
+                        if item.type_name == WILDCARD {
+                            wildcard_indices
+                                .iter()
+                                .map(|index| TypedMatchItem {
+                                    type_idx: *index,
+                                    assignee: None,
+                                })
+                                .collect_vec()
+                        } else {
+                            vec![TypedMatchItem {
+                                type_idx: variant_lookup[&item.type_name].index,
+                                assignee: variable.clone(),
+                            }]
+                        }
                     })
                     .collect_vec();
                 let block = self.check_block(block.block, context, generic_variables.clone())?;
@@ -828,6 +2003,11 @@ impl TypeChecker {
                 })
             })
             .collect::<Result<Vec<_>, _>>()?;
+        if let Some((first, rest)) = blocks.split_first() {
+            for block in rest {
+                self.unify(&first.block.type_(), &block.block.type_());
+            }
+        }
         if let Err(block_types) = blocks
             .iter()
             .map(|block| block.block.type_())
@@ -863,10 +2043,24 @@ impl TypeChecker {
         context: &TypeContext,
         generic_variables: &GenericVariables,
     ) -> Result<Vec<TypedExpression>, TypeCheckError> {
-        expressions
+        let Some(collected_errors) = &self.collected_errors else {
+            return expressions
+                .into_iter()
+                .map(|expression| self.check_expression(expression, context, generic_variables))
+                .collect::<Result<_, _>>();
+        };
+        // Check every expression, returning the first error and collecting the rest.
+        let (typed_expressions, mut errors): (Vec<_>, VecDeque<_>) = expressions
             .into_iter()
             .map(|expression| self.check_expression(expression, context, generic_variables))
-            .collect::<Result<_, _>>()
+            .partition_result();
+        match errors.pop_front() {
+            None => Ok(typed_expressions),
+            Some(error) => {
+                collected_errors.borrow_mut().extend(errors);
+                Err(error)
+            }
+        }
     }
     /// Determine the type of a function definition.
     fn fn_signature(
@@ -926,6 +2120,17 @@ impl TypeChecker {
                 })
             }
         }
+        if self.shadowing == Shadowing::Deny {
+            match utils::check_unique(var_names.iter()) {
+                Ok(()) => {}
+                Err(UniqueError { duplicate }) => {
+                    return Err(TypeCheckError::DuplicatedName {
+                        duplicate: duplicate.clone(),
+                        reason: String::from("variable names"),
+                    })
+                }
+            }
+        }
         match utils::check_unique(fn_names.iter().chain(var_names.iter().unique())) {
             Ok(()) => {}
             Err(UniqueError { duplicate }) => {
@@ -935,38 +2140,61 @@ impl TypeChecker {
                 })
             }
         }
+        self.check_built_in_shadowing(fn_names.iter().chain(var_names.iter()))?;
 
         let mut new_context = context.clone();
         let mut fn_context = context;
-        for assignment in &assignments {
-            if let (assignee, Right(fn_def)) = assignment {
-                let mut generic_variables = generic_variables.clone();
-                generic_variables
-                    .extend(GenericVariables::from(&assignee.generic_variables).into_iter());
-                let type_ = self.fn_signature(&fn_def, &generic_variables)?;
-                fn_context.insert(
-                    assignee.id(),
-                    TypedVariable {
-                        variable: Variable::new(),
-                        type_: ParametricType {
-                            parameters: assignee
-                                .generic_variables
-                                .iter()
-                                .map(|id| generic_variables[id].clone())
-                                .collect(),
-                            type_: type_.into(),
-                        },
-                    },
-                );
+        // Values that may be referenced before they are defined (under a lambda).
+        let mut recursive_values = TypeContext::new();
+        for (assignee, expression) in &assignments {
+            let mut generic_variables = generic_variables.clone();
+            generic_variables.extend(
+                self.declare_generic_variables(&assignee.generic_variables, &assignee.bounds)?
+                    .into_iter(),
+            );
+            let type_ = match expression {
+                Right(fn_def) => self.fn_signature(&fn_def, &generic_variables)?.into(),
+                // Constructor calls have a known type before their arguments are checked.
+                Left(Expression::ConstructorCall(ConstructorCall {
+                    constructor,
+                    arguments: _,
+                })) if assignee.generic_variables.is_empty()
+                    && var_names.iter().filter(|id| **id == assignee.id()).count() == 1 =>
+                {
+                    let (_, type_) =
+                        self.constructor_output_type(constructor, &generic_variables)?;
+                    type_
+                }
+                Left(_) => continue,
+            };
+            let variable = TypedVariable {
+                variable: Variable::new(),
+                type_: ParametricType {
+                    parameters: assignee
+                        .generic_variables
+                        .iter()
+                        .map(|id| generic_variables[id].clone())
+                        .collect(),
+                    type_,
+                },
+            };
+            if let Left(_) = expression {
+                recursive_values.insert(assignee.id(), variable.clone());
             }
+            fn_context.insert(assignee.id(), variable);
         }
 
         let mut statements = Vec::new();
+        let mut names = Vec::new();
+        let mut recursive_indices = HashMap::new();
         for (assignee, expression) in assignments {
             let mut generic_variables = generic_variables.clone();
-            generic_variables
-                .extend(GenericVariables::from(&assignee.generic_variables).into_iter());
+            generic_variables.extend(
+                self.declare_generic_variables(&assignee.generic_variables, &assignee.bounds)?
+                    .into_iter(),
+            );
             let id = assignee.id();
+            names.push(id.clone());
             let parameters = assignee
                 .generic_variables
                 .into_iter()
@@ -974,19 +2202,58 @@ impl TypeChecker {
                 .collect_vec();
             let statement = match expression {
                 Left(expression) => {
-                    let typed_expression =
-                        self.check_expression(expression, &new_context, &generic_variables)?;
+                    let (typed_expression, variable) = match recursive_values.remove(&id) {
+                        Some(variable) => {
+                            // Make this value and later ones visible for references under lambdas.
+                            let mut recursive_context = new_context.clone();
+                            recursive_context.insert(id.clone(), variable.clone());
+                            recursive_context.extend(recursive_values.clone());
+                            let typed_expression = self.check_expression(
+                                expression,
+                                &recursive_context,
+                                &generic_variables,
+                            )?;
+                            let mut strict_references = HashSet::new();
+                            Self::collect_used_variables(
+                                &typed_expression,
+                                &mut strict_references,
+                                false,
+                            );
+                            let undefined = [(&id, &variable)]
+                                .into_iter()
+                                .chain(recursive_values.iter().sorted_by_key(|(id, _)| *id));
+                            for (dependency, undefined_variable) in undefined {
+                                if strict_references.contains(&undefined_variable.variable) {
+                                    return Err(TypeCheckError::StrictCycle {
+                                        variable: id.clone(),
+                                        dependency: dependency.clone(),
+                                    });
+                                }
+                            }
+                            recursive_indices.insert(variable.variable.clone(), statements.len());
+                            (typed_expression, variable)
+                        }
+                        None => {
+                            let typed_expression = self.check_expression(
+                                expression,
+                                &new_context,
+                                &generic_variables,
+                            )?;
+                            let variable = TypedVariable {
+                                variable: Variable::new(),
+                                type_: ParametricType {
+                                    parameters: parameters
+                                        .iter()
+                                        .map(|(_, parameter)| parameter.clone())
+                                        .collect(),
+                                    type_: typed_expression.type_(),
+                                },
+                            };
+                            (typed_expression, variable)
+                        }
+                    };
                     let assignment = TypedAssignment {
-                        variable: TypedVariable {
-                            variable: Variable::new(),
-                            type_: ParametricType {
-                                parameters: parameters
-                                    .iter()
-                                    .map(|(_, parameter)| parameter.clone())
-                                    .collect(),
-                                type_: typed_expression.type_(),
-                            },
-                        },
+                        variable,
                         expression: ParametricExpression {
                             expression: typed_expression,
                             parameters,
@@ -1003,6 +2270,7 @@ impl TypeChecker {
                         panic!("Function def changed form");
                     };
                     let variable = &fn_context[&id];
+                    Self::check_polymorphic_recursion(&id, variable, &parameters, &lambda_def)?;
                     new_context.insert(id, variable.clone());
                     TypedFnDef {
                         variable: variable.clone(),
@@ -1021,35 +2289,799 @@ impl TypeChecker {
             statements,
             expression: Box::new(typed_expression),
         };
-        Ok(block)
+        if self.deny_unused_bindings {
+            Self::check_bindings_used(&block, names)?;
+        }
+        Ok(Self::introduce_thunks(block, recursive_indices))
     }
-    fn check_program(
-        program: Program,
-        context: &TypeContext,
-    ) -> Result<TypedProgram, TypeCheckError> {
-        let definitions = program.definitions;
-        let (assignments, type_definitions): (Vec<_>, Vec<_>) = definitions
+    /// Replace recursive values that are referenced before their definition with calls to thunks.
+    fn introduce_thunks(
+        block: TypedBlock,
+        recursive_indices: HashMap<Variable, usize>,
+    ) -> TypedBlock {
+        if recursive_indices.is_empty() {
+            return block;
+        }
+        let TypedBlock {
+            statements,
+            expression,
+        } = block;
+        // Recursive values referenced by each statement that are not yet defined.
+        let premature_references = statements
+            .iter()
+            .enumerate()
+            .map(|(i, statement)| {
+                let mut used = HashSet::new();
+                Self::collect_used_variables_in_statement(statement, &mut used, true);
+                used.retain(|variable| {
+                    recursive_indices
+                        .get(variable)
+                        .is_some_and(|&index| index >= i)
+                });
+                used
+            })
+            .collect_vec();
+        let thunks = premature_references
+            .iter()
+            .flatten()
+            .unique()
+            .map(|variable| {
+                let statement = &statements[recursive_indices[variable]];
+                let type_ = statement.variable().type_.type_;
+                (
+                    variable.clone(),
+                    TypedVariable::from(Type::from(TypeFn(Vec::new(), Box::new(type_)))),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let statements = statements
             .into_iter()
-            .partition_map(|definition| match definition {
-                Definition::Assignment(assignment) => Left(assignment),
-                def => Right(def),
-            });
-
-        let type_checker = TypeChecker::check_type_definitions(type_definitions)?;
-        // Add a `return main` statement at the end of the program.
-        let program_block = Block {
-            assignments,
-            expression: Box::new(
-                GenericVariable {
-                    id: Id::from("main"),
-                    type_instances: Vec::new(),
-                }
-                .into(),
-            ),
-        };
-        // Check that `main` is a function with correct argument and return types.
+            .zip(premature_references)
+            .flat_map(|(statement, references)| {
+                let premature_thunks = thunks
+                    .iter()
+                    .filter(|(variable, _)| references.contains(variable))
+                    .map(|(variable, thunk)| (variable.clone(), thunk.clone()))
+                    .collect::<HashMap<_, _>>();
+                match statement {
+                    TypedStatement::TypedAssignment(TypedAssignment {
+                        variable,
+                        expression:
+                            ParametricExpression {
+                                expression,
+                                parameters,
+                            },
+                    }) => {
+                        let expression = Self::call_thunks(expression, &premature_thunks);
+                        match thunks.get(&variable.variable) {
+                            Some(thunk) => vec![
+                                TypedFnDef {
+                                    variable: thunk.clone(),
+                                    parameters: Vec::new(),
+                                    fn_: TypedLambdaDef {
+                                        parameters: Vec::new(),
+                                        return_type: Box::new(variable.type_.type_.clone()),
+                                        body: TypedBlock {
+                                            statements: Vec::new(),
+                                            expression: Box::new(expression),
+                                        },
+                                    },
+                                }
+                                .into(),
+                                TypedAssignment {
+                                    variable,
+                                    expression: ParametricExpression {
+                                        expression: Self::call_thunk(thunk.clone()),
+                                        parameters,
+                                    },
+                                }
+                                .into(),
+                            ],
+                            None => vec![TypedAssignment {
+                                variable,
+                                expression: ParametricExpression {
+                                    expression,
+                                    parameters,
+                                },
+                            }
+                            .into()],
+                        }
+                    }
+                    TypedStatement::TypedFnDef(TypedFnDef {
+                        variable,
+                        parameters,
+                        fn_,
+                    }) => vec![TypedFnDef {
+                        variable,
+                        parameters,
+                        fn_: TypedLambdaDef {
+                            body: Self::call_thunks_in_block(fn_.body, &premature_thunks),
+                            ..fn_
+                        },
+                    }
+                    .into()],
+                }
+            })
+            .collect_vec();
+        TypedBlock {
+            statements,
+            expression,
+        }
+    }
+    fn call_thunk(thunk: TypedVariable) -> TypedExpression {
+        TypedFunctionCall {
+            function: Box::new(
+                TypedAccess {
+                    variable: thunk,
+                    parameters: Vec::new(),
+                }
+                .into(),
+            ),
+            arguments: Vec::new(),
+        }
+        .into()
+    }
+    fn call_thunks_in_block(
+        block: TypedBlock,
+        thunks: &HashMap<Variable, TypedVariable>,
+    ) -> TypedBlock {
+        TypedBlock {
+            statements: block
+                .statements
+                .into_iter()
+                .map(|statement| match statement {
+                    TypedStatement::TypedAssignment(TypedAssignment {
+                        variable,
+                        expression:
+                            ParametricExpression {
+                                expression,
+                                parameters,
+                            },
+                    }) => TypedAssignment {
+                        variable,
+                        expression: ParametricExpression {
+                            expression: Self::call_thunks(expression, thunks),
+                            parameters,
+                        },
+                    }
+                    .into(),
+                    TypedStatement::TypedFnDef(TypedFnDef {
+                        variable,
+                        parameters,
+                        fn_,
+                    }) => TypedFnDef {
+                        variable,
+                        parameters,
+                        fn_: TypedLambdaDef {
+                            parameters: fn_.parameters,
+                            return_type: fn_.return_type,
+                            body: Self::call_thunks_in_block(fn_.body, thunks),
+                        },
+                    }
+                    .into(),
+                })
+                .collect(),
+            expression: Box::new(Self::call_thunks(*block.expression, thunks)),
+        }
+    }
+    /// Replace accesses to variables with calls to their thunks.
+    fn call_thunks(
+        expression: TypedExpression,
+        thunks: &HashMap<Variable, TypedVariable>,
+    ) -> TypedExpression {
+        let call_thunks = |expressions: Vec<TypedExpression>| {
+            expressions
+                .into_iter()
+                .map(|expression| Self::call_thunks(expression, thunks))
+                .collect_vec()
+        };
+        match expression {
+            TypedExpression::Integer(_)
+            | TypedExpression::Boolean(_)
+            | TypedExpression::Str(_)
+            | TypedExpression::Char(_) => expression,
+            TypedExpression::TypedAccess(TypedAccess {
+                variable,
+                parameters,
+            }) => match thunks.get(&variable.variable) {
+                Some(thunk) => Self::call_thunk(thunk.clone()),
+                None => TypedAccess {
+                    variable,
+                    parameters,
+                }
+                .into(),
+            },
+            TypedExpression::TypedTuple(TypedTuple { expressions }) => TypedTuple {
+                expressions: call_thunks(expressions),
+            }
+            .into(),
+            TypedExpression::TypedConstructorCall(TypedConstructorCall {
+                idx,
+                output_type,
+                arguments,
+            }) => TypedConstructorCall {
+                idx,
+                output_type,
+                arguments: call_thunks(arguments),
+            }
+            .into(),
+            TypedExpression::TypedElementAccess(TypedElementAccess { expression, index }) => {
+                TypedElementAccess {
+                    expression: Box::new(Self::call_thunks(*expression, thunks)),
+                    index,
+                }
+                .into()
+            }
+            TypedExpression::TypedRecord(TypedRecord { fields }) => TypedRecord {
+                fields: fields
+                    .into_iter()
+                    .map(|(id, expression)| (id, Self::call_thunks(expression, thunks)))
+                    .collect(),
+            }
+            .into(),
+            TypedExpression::TypedFieldAccess(TypedFieldAccess { expression, field }) => {
+                TypedFieldAccess {
+                    expression: Box::new(Self::call_thunks(*expression, thunks)),
+                    field,
+                }
+                .into()
+            }
+            TypedExpression::TypedIf(TypedIf {
+                condition,
+                true_block,
+                false_block,
+            }) => TypedIf {
+                condition: Box::new(Self::call_thunks(*condition, thunks)),
+                true_block: Self::call_thunks_in_block(true_block, thunks),
+                false_block: Self::call_thunks_in_block(false_block, thunks),
+            }
+            .into(),
+            TypedExpression::TypedMatch(TypedMatch { subject, blocks }) => TypedMatch {
+                subject: Box::new(Self::call_thunks(*subject, thunks)),
+                blocks: blocks
+                    .into_iter()
+                    .map(|TypedMatchBlock { matches, block }| TypedMatchBlock {
+                        matches,
+                        block: Self::call_thunks_in_block(block, thunks),
+                    })
+                    .collect(),
+            }
+            .into(),
+            TypedExpression::TypedLambdaDef(TypedLambdaDef {
+                parameters,
+                return_type,
+                body,
+            }) => match (
+                parameters.as_slice(),
+                body.statements.as_slice(),
+                &*body.expression,
+            ) {
+                // Use the thunk directly instead of wrapping a call to it.
+                (
+                    [],
+                    [],
+                    TypedExpression::TypedAccess(TypedAccess {
+                        variable,
+                        parameters: _,
+                    }),
+                ) if thunks.contains_key(&variable.variable) => TypedAccess {
+                    variable: thunks[&variable.variable].clone(),
+                    parameters: Vec::new(),
+                }
+                .into(),
+                _ => TypedLambdaDef {
+                    parameters,
+                    return_type,
+                    body: Self::call_thunks_in_block(body, thunks),
+                }
+                .into(),
+            },
+            TypedExpression::TypedFunctionCall(TypedFunctionCall {
+                function,
+                arguments,
+            }) => TypedFunctionCall {
+                function: Box::new(Self::call_thunks(*function, thunks)),
+                arguments: call_thunks(arguments),
+            }
+            .into(),
+        }
+    }
+    /// Check that every statement in a block is read by another statement or the block expression.
+    fn check_bindings_used(block: &TypedBlock, names: Vec<Id>) -> Result<(), TypeCheckError> {
+        let uses = block
+            .statements
+            .iter()
+            .map(|statement| {
+                let mut used = HashSet::new();
+                Self::collect_used_variables_in_statement(statement, &mut used, true);
+                used
+            })
+            .collect_vec();
+        let mut expression_uses = HashSet::new();
+        Self::collect_used_variables(&block.expression, &mut expression_uses, true);
+        for (i, (statement, name)) in block.statements.iter().zip(names).enumerate() {
+            // Operators and `main` are used implicitly and names starting with `_` are intentionally unused.
+            if name == "main" || is_operator(&name) || name.starts_with('_') {
+                continue;
+            }
+            let variable = statement.variable().variable;
+            let used = expression_uses.contains(&variable)
+                || uses
+                    .iter()
+                    .enumerate()
+                    .any(|(j, used)| i != j && used.contains(&variable));
+            if !used {
+                return Err(TypeCheckError::UnusedBinding { name });
+            }
+        }
+        Ok(())
+    }
+    /// Reject generic fns that call themselves at a type built from their own parameters (such as `List[T]`),
+    /// which would need infinitely many instances when lowering.
+    fn check_polymorphic_recursion(
+        id: &Id,
+        variable: &TypedVariable,
+        parameters: &[(Id, Rc<RefCell<Option<Type>>>)],
+        fn_: &TypedLambdaDef,
+    ) -> Result<(), TypeCheckError> {
+        if parameters.is_empty() {
+            return Ok(());
+        }
+        let mut instantiations = Vec::new();
+        Self::collect_instantiations_in_block(&fn_.body, &variable.variable, &mut instantiations);
+        let is_parameter = |type_variable: &Rc<RefCell<Option<Type>>>| {
+            parameters
+                .iter()
+                .any(|(_, parameter)| Rc::ptr_eq(parameter, type_variable))
+        };
+        for type_instances in instantiations {
+            if type_instances.iter().any(|type_| {
+                !Self::is_type_variable(type_) && type_.has_unbound_variable(&is_parameter)
+            }) {
+                return Err(TypeCheckError::PolymorphicRecursion {
+                    name: id.clone(),
+                    type_instances,
+                });
+            }
+        }
+        Ok(())
+    }
+    /// Determine whether a type is an unbound type variable, possibly through other type variables.
+    fn is_type_variable(type_: &Type) -> bool {
+        match type_ {
+            Type::TypeVariable(TypeVariable(variable)) => match &*variable.borrow() {
+                Some(type_) => Self::is_type_variable(type_),
+                None => true,
+            },
+            _ => false,
+        }
+    }
+    fn collect_instantiations_in_block(
+        block: &TypedBlock,
+        variable: &Variable,
+        instantiations: &mut Vec<Vec<Type>>,
+    ) {
+        for statement in &block.statements {
+            let expression = match statement {
+                TypedStatement::TypedAssignment(assignment) => &assignment.expression.expression,
+                TypedStatement::TypedFnDef(fn_def) => {
+                    Self::collect_instantiations_in_block(
+                        &fn_def.fn_.body,
+                        variable,
+                        instantiations,
+                    );
+                    continue;
+                }
+            };
+            Self::collect_instantiations(expression, variable, instantiations);
+        }
+        Self::collect_instantiations(&block.expression, variable, instantiations);
+    }
+    /// Collect the type parameters of every access to a variable, including those inside lambdas.
+    fn collect_instantiations(
+        expression: &TypedExpression,
+        variable: &Variable,
+        instantiations: &mut Vec<Vec<Type>>,
+    ) {
+        match expression {
+            TypedExpression::Integer(_)
+            | TypedExpression::Boolean(_)
+            | TypedExpression::Str(_)
+            | TypedExpression::Char(_) => {}
+            TypedExpression::TypedAccess(TypedAccess {
+                variable: accessed,
+                parameters,
+            }) => {
+                if &accessed.variable == variable {
+                    instantiations.push(parameters.clone());
+                }
+            }
+            TypedExpression::TypedTuple(TypedTuple { expressions })
+            | TypedExpression::TypedConstructorCall(TypedConstructorCall {
+                idx: _,
+                output_type: _,
+                arguments: expressions,
+            }) => {
+                for expression in expressions {
+                    Self::collect_instantiations(expression, variable, instantiations);
+                }
+            }
+            TypedExpression::TypedElementAccess(TypedElementAccess {
+                expression,
+                index: _,
+            })
+            | TypedExpression::TypedFieldAccess(TypedFieldAccess {
+                expression,
+                field: _,
+            }) => Self::collect_instantiations(expression, variable, instantiations),
+            TypedExpression::TypedRecord(TypedRecord { fields }) => {
+                for (_, expression) in fields {
+                    Self::collect_instantiations(expression, variable, instantiations);
+                }
+            }
+            TypedExpression::TypedIf(TypedIf {
+                condition,
+                true_block,
+                false_block,
+            }) => {
+                Self::collect_instantiations(condition, variable, instantiations);
+                Self::collect_instantiations_in_block(true_block, variable, instantiations);
+                Self::collect_instantiations_in_block(false_block, variable, instantiations);
+            }
+            TypedExpression::TypedMatch(TypedMatch { subject, blocks }) => {
+                Self::collect_instantiations(subject, variable, instantiations);
+                for block in blocks {
+                    Self::collect_instantiations_in_block(&block.block, variable, instantiations);
+                }
+            }
+            TypedExpression::TypedLambdaDef(TypedLambdaDef {
+                parameters: _,
+                return_type: _,
+                body,
+            }) => Self::collect_instantiations_in_block(body, variable, instantiations),
+            TypedExpression::TypedFunctionCall(TypedFunctionCall {
+                function,
+                arguments,
+            }) => {
+                Self::collect_instantiations(function, variable, instantiations);
+                for argument in arguments {
+                    Self::collect_instantiations(argument, variable, instantiations);
+                }
+            }
+        }
+    }
+    fn collect_used_variables_in_statement(
+        statement: &TypedStatement,
+        used: &mut HashSet<Variable>,
+        include_lambdas: bool,
+    ) {
+        match statement {
+            TypedStatement::TypedAssignment(assignment) => Self::collect_used_variables(
+                &assignment.expression.expression,
+                used,
+                include_lambdas,
+            ),
+            TypedStatement::TypedFnDef(fn_def) => {
+                if include_lambdas {
+                    Self::collect_used_variables_in_block(&fn_def.fn_.body, used, include_lambdas)
+                }
+            }
+        }
+    }
+    fn collect_used_variables_in_block(
+        block: &TypedBlock,
+        used: &mut HashSet<Variable>,
+        include_lambdas: bool,
+    ) {
+        for statement in &block.statements {
+            Self::collect_used_variables_in_statement(statement, used, include_lambdas);
+        }
+        Self::collect_used_variables(&block.expression, used, include_lambdas);
+    }
+    /// Collect variables that an expression references, optionally ignoring those only referenced inside lambdas.
+    fn collect_used_variables(
+        expression: &TypedExpression,
+        used: &mut HashSet<Variable>,
+        include_lambdas: bool,
+    ) {
+        match expression {
+            TypedExpression::Integer(_)
+            | TypedExpression::Boolean(_)
+            | TypedExpression::Str(_)
+            | TypedExpression::Char(_) => {}
+            TypedExpression::TypedAccess(TypedAccess {
+                variable,
+                parameters: _,
+            }) => {
+                used.insert(variable.variable.clone());
+            }
+            TypedExpression::TypedTuple(TypedTuple { expressions })
+            | TypedExpression::TypedConstructorCall(TypedConstructorCall {
+                idx: _,
+                output_type: _,
+                arguments: expressions,
+            }) => {
+                for expression in expressions {
+                    Self::collect_used_variables(expression, used, include_lambdas);
+                }
+            }
+            TypedExpression::TypedElementAccess(TypedElementAccess {
+                expression,
+                index: _,
+            })
+            | TypedExpression::TypedFieldAccess(TypedFieldAccess {
+                expression,
+                field: _,
+            }) => Self::collect_used_variables(expression, used, include_lambdas),
+            TypedExpression::TypedRecord(TypedRecord { fields }) => {
+                for (_, expression) in fields {
+                    Self::collect_used_variables(expression, used, include_lambdas);
+                }
+            }
+            TypedExpression::TypedIf(TypedIf {
+                condition,
+                true_block,
+                false_block,
+            }) => {
+                Self::collect_used_variables(condition, used, include_lambdas);
+                Self::collect_used_variables_in_block(true_block, used, include_lambdas);
+                Self::collect_used_variables_in_block(false_block, used, include_lambdas);
+            }
+            TypedExpression::TypedMatch(TypedMatch { subject, blocks }) => {
+                Self::collect_used_variables(subject, used, include_lambdas);
+                for block in blocks {
+                    Self::collect_used_variables_in_block(&block.block, used, include_lambdas);
+                }
+            }
+            TypedExpression::TypedLambdaDef(TypedLambdaDef {
+                parameters: _,
+                return_type: _,
+                body,
+            }) => {
+                if include_lambdas {
+                    Self::collect_used_variables_in_block(body, used, include_lambdas)
+                }
+            }
+            TypedExpression::TypedFunctionCall(TypedFunctionCall {
+                function,
+                arguments,
+            }) => {
+                Self::collect_used_variables(function, used, include_lambdas);
+                for argument in arguments {
+                    Self::collect_used_variables(argument, used, include_lambdas);
+                }
+            }
+        }
+    }
+    /// Collect the ids that an expression references without binding them itself.
+    fn collect_free_ids(expression: &Expression, bound: &HashSet<Id>, free: &mut HashSet<Id>) {
+        match expression {
+            Expression::Integer(_)
+            | Expression::Boolean(_)
+            | Expression::Str(_)
+            | Expression::Char(_) => {}
+            Expression::TupleExpression(TupleExpression { expressions }) => {
+                for expression in expressions {
+                    Self::collect_free_ids(expression, bound, free);
+                }
+            }
+            Expression::GenericVariable(GenericVariable {
+                id,
+                type_instances: _,
+            }) => {
+                if !bound.contains(id) {
+                    free.insert(id.clone());
+                }
+            }
+            Expression::ElementAccess(ElementAccess {
+                expression,
+                index: _,
+            }) => Self::collect_free_ids(expression, bound, free),
+            Expression::RecordExpression(RecordExpression { fields }) => {
+                for RecordField { id: _, expression } in fields {
+                    Self::collect_free_ids(expression, bound, free);
+                }
+            }
+            Expression::FieldAccess(FieldAccess {
+                expression,
+                field: _,
+            })
+            | Expression::Annotated(Annotated {
+                expression,
+                type_: _,
+            }) => Self::collect_free_ids(expression, bound, free),
+            Expression::IfExpression(IfExpression {
+                condition,
+                true_block,
+                false_block,
+            }) => {
+                Self::collect_free_ids(condition, bound, free);
+                Self::collect_free_ids_in_block(true_block, bound, free);
+                if let Some(false_block) = false_block {
+                    Self::collect_free_ids_in_block(false_block, bound, free);
+                }
+            }
+            Expression::MatchExpression(MatchExpression { subject, blocks }) => {
+                Self::collect_free_ids(subject, bound, free);
+                for MatchBlock { matches, block } in blocks {
+                    let mut bound = bound.clone();
+                    bound.extend(
+                        matches
+                            .iter()
+                            .filter_map(|item| item.assignee.as_ref())
+                            .map(|assignee| assignee.id.clone()),
+                    );
+                    Self::collect_free_ids_in_block(block, &bound, free);
+                }
+            }
+            Expression::FunctionDefinition(FunctionDefinition {
+                parameters,
+                return_type: _,
+                body,
+            }) => {
+                let mut bound = bound.clone();
+                bound.extend(
+                    parameters
+                        .iter()
+                        .map(|TypedAssignee { assignee, type_: _ }| assignee.id.clone()),
+                );
+                Self::collect_free_ids_in_block(body, &bound, free);
+            }
+            Expression::FunctionCall(FunctionCall {
+                function,
+                arguments,
+            }) => {
+                Self::collect_free_ids(function, bound, free);
+                for argument in arguments {
+                    Self::collect_free_ids(argument, bound, free);
+                }
+            }
+            Expression::ConstructorCall(ConstructorCall {
+                constructor: _,
+                arguments,
+            }) => {
+                for argument in arguments {
+                    Self::collect_free_ids(argument, bound, free);
+                }
+            }
+        }
+    }
+    fn collect_free_ids_in_block(block: &Block, bound: &HashSet<Id>, free: &mut HashSet<Id>) {
+        let mut bound = bound.clone();
+        bound.extend(
+            block
+                .assignments
+                .iter()
+                .map(|assignment| assignment.assignee.id()),
+        );
+        for assignment in &block.assignments {
+            Self::collect_free_ids(&assignment.expression, &bound, free);
+        }
+        Self::collect_free_ids(&block.expression, &bound, free);
+    }
+    /// Move top-level assignments after any later assignments they reference.
+    /// Cycles are left in textual order so that they are still reported.
+    fn order_top_level_assignments(assignments: Vec<Assignment>) -> Vec<Assignment> {
+        let positions = assignments
+            .iter()
+            .enumerate()
+            .map(|(i, assignment)| (assignment.assignee.id(), i))
+            .collect::<HashMap<_, _>>();
+        // Redefinitions depend on textual order.
+        if positions.len() != assignments.len() {
+            return assignments;
+        }
+        let dependencies = assignments
+            .iter()
+            .enumerate()
+            .map(|(i, assignment)| {
+                // Constructor calls can already refer forwards as recursive values.
+                if matches!(*assignment.expression, Expression::ConstructorCall(_)) {
+                    return Vec::new();
+                }
+                let mut free = HashSet::new();
+                Self::collect_free_ids(&assignment.expression, &HashSet::new(), &mut free);
+                free.iter()
+                    .filter_map(|id| positions.get(id).copied())
+                    .filter(|&j| j > i)
+                    .sorted()
+                    .collect_vec()
+            })
+            .collect_vec();
+        fn visit(
+            i: usize,
+            dependencies: &[Vec<usize>],
+            visited: &mut [bool],
+            order: &mut Vec<usize>,
+        ) {
+            if visited[i] {
+                return;
+            }
+            visited[i] = true;
+            for &j in &dependencies[i] {
+                visit(j, dependencies, visited, order);
+            }
+            order.push(i);
+        }
+        let mut visited = vec![false; assignments.len()];
+        let mut order = Vec::new();
+        for i in 0..assignments.len() {
+            visit(i, &dependencies, &mut visited, &mut order);
+        }
+        let mut assignments = assignments.into_iter().map(Some).collect_vec();
+        order
+            .into_iter()
+            .map(|i| assignments[i].take().unwrap())
+            .collect()
+    }
+    fn check_program(
+        program: Program,
+        context: &TypeContext,
+        options: TypeCheckOptions,
+    ) -> Result<(TypedProgram, Vec<TypeCheckWarning>), TypeCheckError> {
+        Self::check_program_collecting_errors(program, context, options, None)
+    }
+    fn check_program_collecting_errors(
+        program: Program,
+        context: &TypeContext,
+        options: TypeCheckOptions,
+        collected_errors: Option<Rc<RefCell<Vec<TypeCheckError>>>>,
+    ) -> Result<(TypedProgram, Vec<TypeCheckWarning>), TypeCheckError> {
+        let TypeCheckOptions {
+            main_args,
+            deny_unused_bindings,
+            shadowing,
+            built_in_shadowing,
+            int_width,
+        } = options;
+        let definitions = program.definitions;
+        let docs = definitions
+            .iter()
+            .filter_map(|definition| {
+                Some((definition.get_id().clone(), definition.get_doc()?.clone()))
+            })
+            .collect();
+        let (assignments, type_definitions): (Vec<_>, Vec<_>) = definitions
+            .into_iter()
+            .partition_map(|definition| match definition {
+                Definition::Assignment(assignment) => Left(assignment),
+                def => Right(def),
+            });
+
+        let mut type_checker = TypeChecker::check_type_definitions(type_definitions)?;
+        type_checker.deny_unused_bindings = deny_unused_bindings;
+        type_checker.shadowing = shadowing;
+        type_checker.built_in_shadowing = built_in_shadowing;
+        type_checker.int_width = int_width;
+        type_checker.collected_errors = collected_errors;
+        if !assignments
+            .iter()
+            .any(|assignment| assignment.assignee.assignee.id == "main")
+        {
+            return Err(TypeCheckError::MissingMain);
+        }
+        // Add a `return main` statement at the end of the program.
+        let program_block = Block {
+            assignments: Self::order_top_level_assignments(assignments),
+            expression: Box::new(
+                GenericVariable {
+                    id: Id::from("main"),
+                    type_instances: Vec::new(),
+                }
+                .into(),
+            ),
+        };
+        // Check that `main` is a function with correct argument and return types.
         let typed_block =
             type_checker.check_block(program_block, context.clone(), GenericVariables::new())?;
+        // Inferred types that are never constrained (e.g. an unused `error`) default to unit.
+        for type_variable in type_checker.inferred_types.borrow().iter() {
+            if type_variable.borrow().is_none() {
+                *type_variable.borrow_mut() = Some(TYPE_UNIT);
+            }
+        }
+        // Replace inferred types with their solutions.
+        let typed_block = typed_block.instantiate();
         let Type::TypeFn(TypeFn(args, ret)) = typed_block.type_() else {
             return Err(TypeCheckError::IncorrectMainType {
                 type_: typed_block.type_(),
@@ -1087,39 +3119,74 @@ impl TypeChecker {
             }) = statement
             {
                 if *variable == main {
-                    let args = parameters
-                        .iter()
-                        .map(|TypedVariable { variable: _, type_ }| TypedVariable {
-                            variable: Variable::new(),
-                            type_: type_.clone(),
-                        })
-                        .collect_vec();
-                    return Ok(TypedProgram {
-                        type_definitions: type_checker.type_definitions,
-                        main: TypedLambdaDef {
-                            parameters: args.clone(),
-                            return_type: return_type.clone(),
-                            body: TypedBlock {
-                                statements: typed_block.statements,
-                                expression: Box::new(
-                                    TypedFunctionCall {
-                                        function: typed_block.expression,
-                                        arguments: args
-                                            .into_iter()
-                                            .map(|arg| {
-                                                TypedAccess {
-                                                    variable: arg,
-                                                    parameters: Vec::new(),
-                                                }
-                                                .into()
-                                            })
-                                            .collect_vec(),
+                    let (args, arguments) = match main_args {
+                        // Supply the arguments as constants.
+                        Some(main_args) => {
+                            if main_args.len() != parameters.len()
+                                || parameters
+                                    .iter()
+                                    .any(|parameter| parameter.type_.type_ != TYPE_INT)
+                            {
+                                return Err(TypeCheckError::MainArgumentMismatch {
+                                    parameters: parameters
+                                        .iter()
+                                        .map(|parameter| parameter.type_.type_.clone())
+                                        .collect(),
+                                    arguments: main_args,
+                                });
+                            }
+                            (
+                                Vec::new(),
+                                main_args
+                                    .into_iter()
+                                    .map(|value| Integer { value }.into())
+                                    .collect_vec(),
+                            )
+                        }
+                        // Pass the arguments through from the program's inputs.
+                        None => {
+                            let args = parameters
+                                .iter()
+                                .map(|TypedVariable { variable: _, type_ }| TypedVariable {
+                                    variable: Variable::new(),
+                                    type_: type_.clone(),
+                                })
+                                .collect_vec();
+                            let arguments = args
+                                .iter()
+                                .map(|arg| {
+                                    TypedAccess {
+                                        variable: arg.clone(),
+                                        parameters: Vec::new(),
                                     }
-                                    .into(),
-                                ),
+                                    .into()
+                                })
+                                .collect_vec();
+                            (args, arguments)
+                        }
+                    };
+                    let warnings = type_checker.warnings.take();
+                    return Ok((
+                        TypedProgram {
+                            type_definitions: type_checker.type_definitions,
+                            docs,
+                            main: TypedLambdaDef {
+                                parameters: args,
+                                return_type: return_type.clone(),
+                                body: TypedBlock {
+                                    statements: typed_block.statements,
+                                    expression: Box::new(
+                                        TypedFunctionCall {
+                                            function: typed_block.expression,
+                                            arguments,
+                                        }
+                                        .into(),
+                                    ),
+                                },
                             },
                         },
-                    });
+                        warnings,
+                    ));
                 }
             }
         }
@@ -1128,27 +3195,84 @@ impl TypeChecker {
             block: typed_block,
         });
     }
-    pub fn type_check(mut program: Program) -> Result<TypedProgram, TypeCheckError> {
-        program.definitions = vec![prefix(), program.definitions].concat();
+    pub fn type_check(program: Program) -> Result<TypedProgram, TypeCheckError> {
+        Self::type_check_with_options(program, TypeCheckOptions::default())
+    }
+    /// Type check a program, calling `main` with the given integer arguments.
+    pub fn type_check_with_main_args(
+        program: Program,
+        main_args: Vec<i64>,
+    ) -> Result<TypedProgram, TypeCheckError> {
+        Self::type_check_with_options(
+            program,
+            TypeCheckOptions {
+                main_args: Some(main_args),
+                ..Default::default()
+            },
+        )
+    }
+    /// Type check a program with custom options.
+    pub fn type_check_with_options(
+        program: Program,
+        options: TypeCheckOptions,
+    ) -> Result<TypedProgram, TypeCheckError> {
+        Self::type_check_with_warnings(program, options).map(|(program, _)| program)
+    }
+    /// Type check a program with custom options, also returning the warnings that were found.
+    pub fn type_check_with_warnings(
+        program: Program,
+        options: TypeCheckOptions,
+    ) -> Result<(TypedProgram, Vec<TypeCheckWarning>), TypeCheckError> {
         // Add the default context (operators) when checking programs.
-        DEFAULT_CONTEXT.with(|context| Self::check_program(program, context))
+        DEFAULT_CONTEXT.with(|context| Self::check_program(program, context, options))
     }
-}
-
-#[cfg(test)]
-mod tests {
-
-    use crate::{
+    /// Type check a program, reporting the errors in all independent subexpressions (such as tuple elements and fn arguments) instead of only the first.
+    pub fn type_check_all(program: Program) -> Result<TypedProgram, Vec<TypeCheckError>> {
+        let collected_errors = Rc::new(RefCell::new(Vec::new()));
+        DEFAULT_CONTEXT
+            .with(|context| {
+                Self::check_program_collecting_errors(
+                    program,
+                    context,
+                    TypeCheckOptions::default(),
+                    Some(collected_errors.clone()),
+                )
+            })
+            .map(|(program, _)| program)
+            .map_err(|error| {
+                let mut errors = vec![error];
+                errors.extend(collected_errors.take());
+                errors
+            })
+    }
+    /// Type check a program with extra built-in fns in addition to the default context.
+    /// The same context must be passed to `Lowerer::lower_with_context` to lower the result.
+    pub fn type_check_with_context(
+        program: Program,
+        extra: TypeContext,
+    ) -> Result<TypedProgram, TypeCheckError> {
+        let mut context = DEFAULT_CONTEXT.with(|context| (**context).clone());
+        context.merge(extra)?;
+        Self::check_program(program, &context, TypeCheckOptions::default())
+            .map(|(program, _)| program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
         type_check_nodes::{ConstructorType, TYPE_UNIT},
-        Assignee, Assignment, Block, Boolean, Constructor, ConstructorCall, ElementAccess,
+        Assignee, Assignment, Block, Boolean, Char, Constructor, ConstructorCall, ElementAccess,
         ExpressionBlock, FunctionCall, FunctionDefinition, GenericConstructor, GenericTypeVariable,
-        IfExpression, Integer, MatchBlock, MatchExpression, MatchItem, ParametricAssignee,
-        TypeItem, TypeVariable, TypedAssignee, Typename, Var, VariableAssignee, ATOMIC_TYPE_BOOL,
-        ATOMIC_TYPE_INT,
+        IfExpression, Integer, MatchBlock, MatchExpression, MatchItem, ParametricAssignee, Str,
+        TupleType, TypeItem, TypeVariable, TypedAssignee, Typename, Var, VariableAssignee,
+        ATOMIC_TYPE_BOOL, ATOMIC_TYPE_INT, ATOMIC_TYPE_STRING,
     };
 
     use super::*;
 
+    use std::collections::BTreeMap;
     use test_case::test_case;
 
     #[test_case(
@@ -1163,7 +3287,8 @@ mod tests {
                     id: Id::from("i"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_INT.into()
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from([
@@ -1182,14 +3307,16 @@ mod tests {
                     id: Id::from("i"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_INT.into()
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition {
                 variable: GenericTypeVariable{
                     id: Id::from("i"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_BOOL.into()
+                type_: ATOMIC_TYPE_BOOL.into(),
+                doc: None,
             }.into()
         ],
         None;
@@ -1202,14 +3329,16 @@ mod tests {
                     id: Id::from("i"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_INT.into()
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition {
                 variable: GenericTypeVariable{
                     id: Id::from("i"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_INT.into()
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
             }.into()
         ],
         None;
@@ -1225,13 +3354,14 @@ mod tests {
                 items: vec![
                     TypeItem {
                         id: Id::from("Int"),
-                        type_: Some(ATOMIC_TYPE_INT.into())
+                        types: vec![ATOMIC_TYPE_INT.into()]
                     },
                     TypeItem {
                         id: Id::from("Bool"),
-                        type_: Some(ATOMIC_TYPE_BOOL.into())
+                        types: vec![ATOMIC_TYPE_BOOL.into()]
                     },
-                ]
+                ],
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from([
@@ -1248,6 +3378,40 @@ mod tests {
         ]));
         "basic union type definition"
     )]
+    #[test_case(
+        vec![
+            UnionTypeDefinition {
+                variable: GenericTypeVariable{
+                    id: Id::from("pair_or_unit"),
+                    generic_variables: Vec::new()
+                },
+                items: vec![
+                    TypeItem {
+                        id: Id::from("Pair"),
+                        types: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
+                    },
+                    TypeItem {
+                        id: Id::from("Unit"),
+                        types: Vec::new()
+                    },
+                ],
+                doc: None,
+            }.into()
+        ],
+        Some(TypeDefinitions::from([
+            (
+                Id::from("pair_or_unit"),
+                Type::from(TypeUnion{
+                    id: Id::from("pair_or_unit"),
+                    variants: vec![
+                        Some(TypeTuple(vec![TYPE_INT, TYPE_BOOL]).into()),
+                        None
+                    ]
+                })
+            )
+        ]));
+        "multi-field union type definition"
+    )]
     #[test_case(
         vec![
             UnionTypeDefinition {
@@ -1258,13 +3422,14 @@ mod tests {
                 items: vec![
                     TypeItem{
                         id: Id::from("Cons"),
-                        type_: Some(Typename("int_list").into())
+                        types: vec![Typename("int_list").into()]
                     },
                     TypeItem{
                         id: Id::from("Nil"),
-                        type_: None
+                        types: Vec::new()
                     },
-                ]
+                ],
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from([
@@ -1290,14 +3455,16 @@ mod tests {
                     id: Id::from("Int"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_INT.into()
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition {
                 variable: GenericTypeVariable{
                     id: Id::from("Bool"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_BOOL.into()
+                type_: ATOMIC_TYPE_BOOL.into(),
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from([
@@ -1323,7 +3490,8 @@ mod tests {
                     id: Id::from("int"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_INT.into()
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -1337,9 +3505,10 @@ mod tests {
                     generic_variables: Vec::new()
                 },
                 items: vec![
-                    TypeItem { id: Id::from("two"), type_: None},
-                    TypeItem { id: Id::from("four"), type_: None},
-                ]
+                    TypeItem { id: Id::from("two"), types: Vec::new()},
+                    TypeItem { id: Id::from("four"), types: Vec::new()},
+                ],
+                doc: None,
             }.into()
         ],
         None;
@@ -1354,7 +3523,8 @@ mod tests {
                 },
                 type_: TupleType{
                     types: vec![ATOMIC_TYPE_INT.into(),ATOMIC_TYPE_INT.into()]
-                }.into()
+                }.into(),
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from([
@@ -1377,7 +3547,8 @@ mod tests {
                 type_: FunctionType{
                     argument_types: vec![ATOMIC_TYPE_INT.into()],
                     return_type: Box::new(ATOMIC_TYPE_BOOL.into()),
-                }.into()
+                }.into(),
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from([
@@ -1400,7 +3571,8 @@ mod tests {
                 type_: FunctionType{
                     argument_types: Vec::new(),
                     return_type: Box::new(TupleType{types: Vec::new()}.into()),
-                }.into()
+                }.into(),
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from([
@@ -1413,7 +3585,60 @@ mod tests {
     )]
     #[test_case(
         vec![
-            EmptyTypeDefinition{id: Id::from("None")}.into()
+            TransparentTypeDefinition {
+                variable: GenericTypeVariable{
+                    id: Id::from("ints"),
+                    generic_variables: Vec::new()
+                },
+                type_: GenericType{
+                    id: Id::from("array"),
+                    type_variables: vec![ATOMIC_TYPE_INT.into()]
+                }.into(),
+                doc: None,
+            }.into()
+        ],
+        Some(TypeDefinitions::from([
+            (
+                Id::from("ints"),
+                Type::from(TypeArray(Box::new(TYPE_INT)))
+            ),
+        ]));
+        "array type definition"
+    )]
+    #[test_case(
+        vec![
+            TransparentTypeDefinition {
+                variable: GenericTypeVariable{
+                    id: Id::from("pairs"),
+                    generic_variables: Vec::new()
+                },
+                type_: GenericType{
+                    id: Id::from("array"),
+                    type_variables: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
+                }.into(),
+                doc: None,
+            }.into()
+        ],
+        None;
+        "array with wrong number of parameters"
+    )]
+    #[test_case(
+        vec![
+            OpaqueTypeDefinition {
+                variable: GenericTypeVariable{
+                    id: Id::from("array"),
+                    generic_variables: Vec::new()
+                },
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
+            }.into()
+        ],
+        None;
+        "array type override"
+    )]
+    #[test_case(
+        vec![
+            EmptyTypeDefinition{id: Id::from("None"), doc: None}.into()
         ],
         Some(
             TypeDefinitions::from([
@@ -1427,6 +3652,20 @@ mod tests {
         );
         "empty type definition"
     )]
+    #[test_case(
+        vec![
+            VoidTypeDefinition{id: Id::from("Never"), doc: None}.into()
+        ],
+        Some(
+            TypeDefinitions::from([
+                (
+                    Id::from("Never"),
+                    Type::from(TypeUnion{id: Id::from("Never"),variants: Vec::new()})
+                )
+            ])
+        );
+        "void type definition"
+    )]
     #[test_case(
         vec![
             OpaqueTypeDefinition{
@@ -1434,7 +3673,8 @@ mod tests {
                     id: Id::from("iint"),
                     generic_variables: Vec::new()
                 },
-                type_: ATOMIC_TYPE_INT.into()
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition{
                 variable: GenericTypeVariable{
@@ -1442,6 +3682,7 @@ mod tests {
                     generic_variables: Vec::new()
                 },
                 type_: Typename("iint").into(),
+                doc: None,
             }.into(),
         ],
         Some(
@@ -1469,20 +3710,21 @@ mod tests {
                 items: vec![
                     TypeItem{
                         id: Id::from("Right"),
-                        type_: Some(
+                        types: vec![
                             TupleType{
                                 types: vec![
                                     Typename("right").into(),
                                     ATOMIC_TYPE_BOOL.into()
                                 ]
                             }.into()
-                        )
+                        ]
                     },
                     TypeItem{
                         id: Id::from("Incorrect"),
-                        type_: None
+                        types: Vec::new()
                     }
-                ]
+                ],
+                doc: None,
             }.into(),
             UnionTypeDefinition{
                 variable: GenericTypeVariable{
@@ -1492,13 +3734,14 @@ mod tests {
                 items: vec![
                     TypeItem{
                         id: Id::from("Left"),
-                        type_: Some(Typename("left").into())
+                        types: vec![Typename("left").into()]
                     },
                     TypeItem{
                         id: Id::from("Correct"),
-                        type_: None
+                        types: Vec::new()
                     }
-                ]
+                ],
+                doc: None,
             }.into(),
         ],
         Some(
@@ -1536,13 +3779,14 @@ mod tests {
                 items: vec![
                     TypeItem{
                         id: Id::from("left"),
-                        type_: Some(ATOMIC_TYPE_BOOL.into())
+                        types: vec![ATOMIC_TYPE_BOOL.into()]
                     },
                     TypeItem{
                         id: Id::from("left"),
-                        type_: Some(ATOMIC_TYPE_BOOL.into())
+                        types: vec![ATOMIC_TYPE_BOOL.into()]
                     }
-                ]
+                ],
+                doc: None,
             }.into(),
         ],
         None;
@@ -1558,13 +3802,14 @@ mod tests {
                 items: vec![
                     TypeItem{
                         id: Id::from("left"),
-                        type_: Some(ATOMIC_TYPE_BOOL.into())
+                        types: vec![ATOMIC_TYPE_BOOL.into()]
                     },
                     TypeItem{
                         id: Id::from("left"),
-                        type_: None
+                        types: Vec::new()
                     }
-                ]
+                ],
+                doc: None,
             }.into(),
         ],
         None;
@@ -1577,7 +3822,8 @@ mod tests {
                     id: Id::from("wrapper"),
                     generic_variables: vec![String::from("T")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into()
         ],
         Some(
@@ -1605,7 +3851,8 @@ mod tests {
                     id: Id::from("transparent"),
                     generic_variables: vec![String::from("T")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into()
         ],
         Some(
@@ -1634,17 +3881,18 @@ mod tests {
                 items: vec![
                     TypeItem {
                         id: String::from("Left"),
-                        type_: Some(
+                        types: vec![
                             Typename("T").into()
-                        )
+                        ]
                     },
                     TypeItem {
                         id: String::from("Right"),
-                        type_: Some(
+                        types: vec![
                             Typename("U").into()
-                        )
+                        ]
                     }
-                ]
+                ],
+                doc: None,
             }.into()
         ],
         Some(
@@ -1675,6 +3923,7 @@ mod tests {
                     generic_variables: Vec::new()
                 },
                 type_: Typename("Unknown").into(),
+                doc: None,
             }.into()
         ],
         None;
@@ -1687,14 +3936,16 @@ mod tests {
                     id: Id::from("One"),
                     generic_variables: vec![String::from("T")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition{
                 variable: GenericTypeVariable{
                     id: Id::from("Zero"),
                     generic_variables: vec![String::from("U")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into()
         ],
         None;
@@ -1707,7 +3958,8 @@ mod tests {
                     id: Id::from("One"),
                     generic_variables: vec![String::from("T"), String::from("U"), String::from("T")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -1720,7 +3972,8 @@ mod tests {
                     id: Id::from("One"),
                     generic_variables: vec![String::from("int")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -1733,7 +3986,8 @@ mod tests {
                     id: Id::from("One"),
                     generic_variables: vec![String::from("One")]
                 },
-                type_: Typename("One").into()
+                type_: Typename("One").into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -1746,14 +4000,16 @@ mod tests {
                     id: Id::from("U"),
                     generic_variables: vec![String::from("T")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition{
                 variable: GenericTypeVariable{
                     id: Id::from("V"),
                     generic_variables: vec![String::from("U")]
                 },
-                type_: Typename("U").into()
+                type_: Typename("U").into(),
+                doc: None,
             }.into()
         ],
         Some(
@@ -1798,14 +4054,16 @@ mod tests {
                 type_: GenericType{
                     id: Id::from("wrapper"),
                     type_variables: vec![ATOMIC_TYPE_INT.into()]
-                }.into()
+                }.into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition{
                 variable: GenericTypeVariable{
                     id: Id::from("wrapper"),
                     generic_variables: vec![String::from("T")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into()
         ],
         Some(
@@ -1836,14 +4094,16 @@ mod tests {
                 type_: GenericType{
                     id: Id::from("wrapper"),
                     type_variables: vec![]
-                }.into()
+                }.into(),
+                doc: None,
             }.into(),
             OpaqueTypeDefinition{
                 variable: GenericTypeVariable{
                     id: Id::from("wrapper"),
                     generic_variables: vec![String::from("T")]
                 },
-                type_: Typename("T").into()
+                type_: Typename("T").into(),
+                doc: None,
             }.into()
         ],
         None;
@@ -1859,7 +4119,8 @@ mod tests {
                 type_: GenericType{
                     id: Id::from("T"),
                     type_variables: vec![Typename("U").into()]
-                }.into()
+                }.into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -1874,7 +4135,8 @@ mod tests {
                 },
                 type_: TupleType{
                     types: vec![Typename("T").into(), Typename("U").into()]
-                }.into()
+                }.into(),
+                doc: None,
             }.into(),
         ],
         Some(
@@ -1904,7 +4166,8 @@ mod tests {
                 type_: FunctionType{
                     argument_types: vec![Typename("T").into()],
                     return_type: Box::new(Typename("U").into())
-                }.into()
+                }.into(),
+                doc: None,
             }.into(),
         ],
         Some(
@@ -1935,7 +4198,7 @@ mod tests {
                 items: vec![
                     TypeItem {
                         id: Id::from("Node"),
-                        type_: Some(TupleType {
+                        types: vec![TupleType {
                             types: vec![
                                 Typename("T").into(),
                                 GenericType{
@@ -1944,13 +4207,14 @@ mod tests {
                                 }.into(),
                                 Typename("T").into()
                             ]
-                        }.into())
+                        }.into()]
                     },
                     TypeItem {
                         id: Id::from("Leaf"),
-                        type_: None
+                        types: Vec::new()
                     }
-                ]
+                ],
+                doc: None,
             }.into(),
         ],
         Some(
@@ -1987,6 +4251,7 @@ mod tests {
                     generic_variables: Vec::new()
                 },
                 type_: Typename("recursive").into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -2003,6 +4268,7 @@ mod tests {
                     id: Id::from("Recursive"),
                     type_variables: vec![Typename("T").into()]
                 }.into(),
+                doc: None,
             }.into(),
             TransparentTypeDefinition{
                 variable: GenericTypeVariable{
@@ -2013,6 +4279,7 @@ mod tests {
                     id: Id::from("Recursive"),
                     type_variables: vec![Typename("recursive_alias").into()]
                 }.into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -2026,6 +4293,7 @@ mod tests {
                     generic_variables: Vec::new()
                 },
                 type_: Typename("recursive2").into(),
+                doc: None,
             }.into(),
             TransparentTypeDefinition{
                 variable: GenericTypeVariable{
@@ -2033,6 +4301,7 @@ mod tests {
                     generic_variables: Vec::new()
                 },
                 type_: Typename("recursive1").into(),
+                doc: None,
             }.into(),
         ],
         None;
@@ -2048,20 +4317,22 @@ mod tests {
                 items: vec![
                     TypeItem{
                         id: Id::from("Cons"),
-                        type_: Some(Typename("int_list").into())
+                        types: vec![Typename("int_list").into()]
                     },
                     TypeItem{
                         id: Id::from("Nil"),
-                        type_: None
+                        types: Vec::new()
                     },
-                ]
+                ],
+                doc: None,
             }.into(),
             TransparentTypeDefinition {
                 variable: GenericTypeVariable{
                     id: Id::from("int_list2"),
                     generic_variables: Vec::new()
                 },
-                type_: Typename("int_list").into()
+                type_: Typename("int_list").into(),
+                doc: None,
             }.into()
         ],
         Some(TypeDefinitions::from(
@@ -2102,6 +4373,209 @@ mod tests {
         }
     }
 
+    /// `typedef <id> { Leaf | Branch <payload> }`
+    fn node_definition(id: &str, payload: TypeInstance) -> Definition {
+        UnionTypeDefinition {
+            variable: GenericTypeVariable {
+                id: Id::from(id),
+                generic_variables: Vec::new(),
+            },
+            items: vec![
+                TypeItem {
+                    id: Id::from("Leaf"),
+                    types: Vec::new(),
+                },
+                TypeItem {
+                    id: Id::from("Branch"),
+                    types: vec![payload],
+                },
+            ],
+            doc: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_module_type_definitions() {
+        let type_checker = TypeChecker::check_type_definitions(vec![
+            node_definition("a::Node", Typename("Node").into()),
+            node_definition("b::Node", ATOMIC_TYPE_INT.into()),
+            node_definition("Node", Typename("a::Node").into()),
+        ])
+        .unwrap();
+        assert_eq!(
+            type_checker.constructors.keys().sorted().collect_vec(),
+            vec![
+                "Branch",
+                "Leaf",
+                "a::Branch",
+                "a::Leaf",
+                "b::Branch",
+                "b::Leaf"
+            ]
+        );
+        let payload = |id: &str| {
+            let Type::TypeUnion(TypeUnion { id: _, variants }) = type_checker.type_definitions
+                [&Id::from(id)]
+                .borrow()
+                .type_
+                .clone()
+            else {
+                panic!("{id} is not a union.")
+            };
+            variants[1].clone().unwrap()
+        };
+        let references = |payload: Type, id: &str| {
+            matches!(
+                payload,
+                Type::TypeInstantiation(TypeInstantiation { reference, instances: _ })
+                    if Rc::ptr_eq(&reference, &type_checker.type_definitions[&Id::from(id)])
+            )
+        };
+        // Unqualified names refer to the module's own types.
+        assert!(references(payload("a::Node"), "a::Node"));
+        assert_eq!(payload("b::Node"), TYPE_INT);
+        assert!(references(payload("Node"), "a::Node"));
+    }
+
+    #[test_case(
+        vec![
+            node_definition("a::Node", ATOMIC_TYPE_INT.into()),
+            node_definition("a::Node", ATOMIC_TYPE_BOOL.into()),
+        ],
+        "a::Node",
+        "type name";
+        "type in the same module"
+    )]
+    #[test_case(
+        vec![
+            node_definition("a::Node", ATOMIC_TYPE_INT.into()),
+            EmptyTypeDefinition {
+                id: Id::from("a::Leaf"),
+                doc: None,
+            }
+            .into(),
+        ],
+        "a::Leaf",
+        "constructor name";
+        "constructor in the same module"
+    )]
+    fn test_duplicate_module_names(definitions: Vec<Definition>, duplicate: &str, reason: &str) {
+        assert_eq!(
+            TypeChecker::check_type_definitions(definitions).map(|_| ()),
+            Err(TypeCheckError::DuplicatedName {
+                duplicate: Id::from(duplicate),
+                reason: String::from(reason),
+            })
+        );
+    }
+
+    #[test_case(
+        vec![("recursive", "recursive")],
+        vec!["recursive"];
+        "recursive typealias"
+    )]
+    #[test_case(
+        vec![("recursive1", "recursive2"), ("recursive2", "recursive1")],
+        vec!["recursive1", "recursive2"];
+        "mutually recursive typealias"
+    )]
+    #[test_case(
+        vec![("a", "b"), ("b", "c"), ("c", "b")],
+        vec!["b", "c"];
+        "recursive typealias reached indirectly"
+    )]
+    fn test_recursive_type_alias_cycle(aliases: Vec<(&str, &str)>, cycle: Vec<&str>) {
+        let definitions = aliases
+            .into_iter()
+            .map(|(id, type_)| {
+                TransparentTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from(id),
+                        generic_variables: Vec::new(),
+                    },
+                    type_: Typename(type_).into(),
+                    doc: None,
+                }
+                .into()
+            })
+            .collect();
+        let Err(TypeCheckError::RecursiveTypeAlias {
+            type_alias,
+            cycle: result,
+        }) = TypeChecker::check_type_definitions(definitions)
+        else {
+            panic!("Recursive type alias was not detected.")
+        };
+        assert_eq!(type_alias, cycle[0]);
+        assert_eq!(result, cycle.into_iter().map(Id::from).collect_vec());
+    }
+
+    #[test_case("None", Err(TypeCheckError::NonExhaustiveMatch {
+        type_name: Id::from("None"),
+        missing: vec![Id::from("None")]
+    }); "empty type")]
+    #[test_case("Never", Ok(()); "void type")]
+    fn test_match_without_arms(type_name: &str, expected: Result<(), TypeCheckError>) {
+        let type_checker = TypeChecker::check_type_definitions(vec![
+            EmptyTypeDefinition {
+                id: Id::from("None"),
+                doc: None,
+            }
+            .into(),
+            VoidTypeDefinition {
+                id: Id::from("Never"),
+                doc: None,
+            }
+            .into(),
+        ])
+        .unwrap();
+        assert!(type_checker.constructors.contains_key("None"));
+        assert!(!type_checker.constructors.contains_key("Never"));
+        let context = TypeContext::from([(
+            Id::from("x"),
+            TypedVariable::from(Type::from(TypeInstantiation {
+                reference: type_checker.type_definitions[&Id::from(type_name)].clone(),
+                instances: Vec::new(),
+            })),
+        )]);
+        let result = type_checker.check_expression(
+            MatchExpression {
+                subject: Box::new(Var("x").into()),
+                blocks: Vec::new(),
+            }
+            .into(),
+            &context,
+            &GenericVariables::new(),
+        );
+        match (result, expected) {
+            (Ok(typed_expression), Ok(())) => {
+                assert!(matches!(
+                    typed_expression,
+                    TypedExpression::TypedFunctionCall(_)
+                ))
+            }
+            (result, expected) => assert_eq!(result.map(|_| ()), expected),
+        }
+    }
+
+    #[test]
+    fn test_deeply_nested_type_alias() {
+        let type_ = (0..100).fold(TypeInstance::from(ATOMIC_TYPE_INT), |type_, _| {
+            TupleType { types: vec![type_] }.into()
+        });
+        let definitions = vec![TransparentTypeDefinition {
+            variable: GenericTypeVariable {
+                id: Id::from("deep"),
+                generic_variables: Vec::new(),
+            },
+            type_,
+            doc: None,
+        }
+        .into()];
+        assert!(TypeChecker::check_type_definitions(definitions).is_ok());
+    }
+
     const ALPHA_TYPE: Lazy<Rc<RefCell<ParametricType>>> = Lazy::new(|| {
         let parameter = Rc::new(RefCell::new(None));
         Rc::new(RefCell::new(ParametricType {
@@ -2323,6 +4797,12 @@ mod tests {
         TypeContext::new();
         "type check boolean"
     )]
+    #[test_case(
+        Str{value: String::from("hello")}.into(),
+        Some(TYPE_STRING),
+        TypeContext::new();
+        "type check string"
+    )]
     #[test_case(
         TupleExpression{
             expressions: Vec::new()
@@ -2514,53 +4994,172 @@ mod tests {
         "nested element access"
     )]
     #[test_case(
-        Var("empty").into(),
-        Some(Type::TypeUnion(
-            TypeUnion{id: Id::from("Empty"),variants: vec![None].into()}
-        )),
-        TypeContext::from([(
-            Id::from("empty"),
-            Type::from(TypeUnion{id: Id::from("Empty"),variants: [(
-                None
-            )].into()}).into()
-        )]);
-        "variable with empty type"
+        RecordExpression{
+            fields: vec![
+                RecordField{id: Id::from("name"), expression: Integer{value: 5}.into()},
+                RecordField{id: Id::from("age"), expression: Boolean{value: true}.into()},
+            ]
+        }.into(),
+        Some(Type::from(TypeRecord(BTreeMap::from([
+            (Id::from("age"), TYPE_BOOL),
+            (Id::from("name"), TYPE_INT),
+        ])))),
+        TypeContext::new();
+        "record expression"
     )]
     #[test_case(
-        IfExpression {
-            condition: Box::new(Integer{value: 0}.into()),
-            true_block: ExpressionBlock(Boolean{value: true}.into()),
-            false_block: ExpressionBlock(Boolean{value: false}.into())
+        RecordExpression{
+            fields: vec![
+                RecordField{id: Id::from("name"), expression: Integer{value: 5}.into()},
+                RecordField{id: Id::from("name"), expression: Boolean{value: true}.into()},
+            ]
         }.into(),
         None,
         TypeContext::new();
-        "if expression invalid condition"
+        "record expression duplicate field"
     )]
     #[test_case(
-        IfExpression {
-            condition: Box::new(Boolean{value: false}.into()),
-            true_block: ExpressionBlock(Boolean{value: true}.into()),
-            false_block: ExpressionBlock(Boolean{value: false}.into())
+        FieldAccess{
+            expression: Box::new(Var("a").into()),
+            field: Id::from("name")
         }.into(),
-        Some(TYPE_BOOL.into()),
-        TypeContext::new();
-        "if expression no assignments condition"
+        Some(TYPE_INT),
+        TypeContext::from([(
+            Id::from("a"),
+            Type::from(TypeRecord(BTreeMap::from([
+                (Id::from("name"), TYPE_INT),
+                (Id::from("age"), TYPE_BOOL),
+            ]))).into()
+        )]);
+        "field access"
     )]
     #[test_case(
-        IfExpression {
-            condition: Box::new(Boolean{value: false}.into()),
-            true_block: ExpressionBlock(Integer{value: 8}.into()),
-            false_block: ExpressionBlock(Boolean{value: false}.into())
+        FieldAccess{
+            expression: Box::new(Var("a").into()),
+            field: Id::from("height")
         }.into(),
         None,
-        TypeContext::new();
-        "if expression different blocks"
+        TypeContext::from([(
+            Id::from("a"),
+            Type::from(TypeRecord(BTreeMap::from([
+                (Id::from("name"), TYPE_INT),
+            ]))).into()
+        )]);
+        "field access missing field"
     )]
     #[test_case(
-        IfExpression {
-            condition: Box::new(Boolean{value: false}.into()),
-            true_block: ExpressionBlock(Var("x").into()),
-            false_block: ExpressionBlock(Boolean{value: false}.into())
+        FieldAccess{
+            expression: Box::new(Var("a").into()),
+            field: Id::from("name")
+        }.into(),
+        None,
+        TypeContext::from([(
+            Id::from("a"),
+            Type::from(TypeTuple(vec![TYPE_INT])).into()
+        )]);
+        "field access on tuple"
+    )]
+    #[test_case(
+        FunctionCall{
+            function: Box::new(FunctionDefinition{
+                parameters: vec![
+                    TypedAssignee{
+                        assignee: Id::from("x").into(),
+                        type_: RecordType{
+                            fields: vec![
+                                RecordTypeField{id: Id::from("age"), type_: ATOMIC_TYPE_BOOL.into()},
+                                RecordTypeField{id: Id::from("name"), type_: ATOMIC_TYPE_INT.into()},
+                            ]
+                        }.into()
+                    }
+                ],
+                return_type: ATOMIC_TYPE_INT.into(),
+                body: ExpressionBlock(FieldAccess{
+                    expression: Box::new(Var("x").into()),
+                    field: Id::from("name")
+                }.into())
+            }.into()),
+            arguments: vec![
+                RecordExpression{
+                    fields: vec![
+                        RecordField{id: Id::from("name"), expression: Integer{value: 5}.into()},
+                        RecordField{id: Id::from("age"), expression: Boolean{value: true}.into()},
+                    ]
+                }.into()
+            ]
+        }.into(),
+        Some(TYPE_INT),
+        TypeContext::new();
+        "record argument with reordered fields"
+    )]
+    #[test_case(
+        Var("empty").into(),
+        Some(Type::TypeUnion(
+            TypeUnion{id: Id::from("Empty"),variants: vec![None].into()}
+        )),
+        TypeContext::from([(
+            Id::from("empty"),
+            Type::from(TypeUnion{id: Id::from("Empty"),variants: [(
+                None
+            )].into()}).into()
+        )]);
+        "variable with empty type"
+    )]
+    #[test_case(
+        IfExpression {
+            condition: Box::new(Integer{value: 0}.into()),
+            true_block: ExpressionBlock(Boolean{value: true}.into()),
+            false_block: Some(ExpressionBlock(Boolean{value: false}.into()))
+        }.into(),
+        None,
+        TypeContext::new();
+        "if expression invalid condition"
+    )]
+    #[test_case(
+        IfExpression {
+            condition: Box::new(Boolean{value: false}.into()),
+            true_block: ExpressionBlock(Boolean{value: true}.into()),
+            false_block: Some(ExpressionBlock(Boolean{value: false}.into()))
+        }.into(),
+        Some(TYPE_BOOL.into()),
+        TypeContext::new();
+        "if expression no assignments condition"
+    )]
+    #[test_case(
+        IfExpression {
+            condition: Box::new(Boolean{value: false}.into()),
+            true_block: ExpressionBlock(Integer{value: 8}.into()),
+            false_block: Some(ExpressionBlock(Boolean{value: false}.into()))
+        }.into(),
+        None,
+        TypeContext::new();
+        "if expression different blocks"
+    )]
+    #[test_case(
+        IfExpression {
+            condition: Box::new(Boolean{value: false}.into()),
+            true_block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into()),
+            false_block: None
+        }.into(),
+        Some(TYPE_UNIT),
+        TypeContext::new();
+        "one-armed if expression with unit type"
+    )]
+    #[test_case(
+        IfExpression {
+            condition: Box::new(Boolean{value: false}.into()),
+            true_block: ExpressionBlock(Integer{value: 8}.into()),
+            false_block: None
+        }.into(),
+        None,
+        TypeContext::new();
+        "one-armed if expression with non-unit type"
+    )]
+    #[test_case(
+        IfExpression {
+            condition: Box::new(Boolean{value: false}.into()),
+            true_block: ExpressionBlock(Var("x").into()),
+            false_block: Some(ExpressionBlock(Boolean{value: false}.into()))
         }.into(),
         None,
         TypeContext::new();
@@ -2573,12 +5172,13 @@ mod tests {
                 assignments: vec![
                     Assignment {
                         assignee: VariableAssignee("x"),
-                        expression: Box::new(Integer{value: -5}.into())
+                        expression: Box::new(Integer{value: -5}.into()),
+                        doc: None,
                     }
                 ],
                 expression: Box::new(Var("x").into())
             },
-            false_block: ExpressionBlock(Integer{value: 5}.into())
+            false_block: Some(ExpressionBlock(Integer{value: 5}.into()))
         }.into(),
         Some(TYPE_INT.into()),
         TypeContext::new();
@@ -2591,12 +5191,13 @@ mod tests {
                 assignments: vec![
                     Assignment {
                         assignee: VariableAssignee("x"),
-                        expression: Box::new(Integer{value: -5}.into())
+                        expression: Box::new(Integer{value: -5}.into()),
+                        doc: None,
                     }
                 ],
                 expression: Box::new(Var("x").into())
             },
-            false_block: ExpressionBlock(Integer{value: 5}.into())
+            false_block: Some(ExpressionBlock(Integer{value: 5}.into()))
         }.into(),
         Some(TYPE_INT.into()),
         TypeContext::from([(
@@ -2612,12 +5213,13 @@ mod tests {
                 assignments: vec![
                     Assignment {
                         assignee: VariableAssignee("x"),
-                        expression: Box::new(Integer{value: -5}.into())
+                        expression: Box::new(Integer{value: -5}.into()),
+                        doc: None,
                     }
                 ],
                 expression: Box::new(Var("x").into())
             },
-            false_block: ExpressionBlock(Var("x").into())
+            false_block: Some(ExpressionBlock(Var("x").into()))
         }.into(),
         None,
         TypeContext::from([(
@@ -2633,17 +5235,18 @@ mod tests {
                 assignments: vec![
                     Assignment {
                         assignee: VariableAssignee("x"),
-                        expression: Box::new(Integer{value: -5}.into())
+                        expression: Box::new(Integer{value: -5}.into()),
+                        doc: None,
                     }
                 ],
                 expression: Box::new(Var("x").into())
             },
-            false_block: ExpressionBlock(
+            false_block: Some(ExpressionBlock(
                 ElementAccess {
                     expression: Box::new(Var("x").into()),
                     index: 1
                 }.into()
-            ),
+            )),
         }.into(),
         Some(TYPE_INT),
         TypeContext::from([(
@@ -2825,6 +5428,64 @@ mod tests {
         TypeContext::new();
         "constructor call generic"
     )]
+    #[test_case(
+        ConstructorCall {
+            constructor: GenericConstructor{
+                id: Id::from("Cons"),
+                type_instances: vec![ATOMIC_TYPE_INT.into()]
+            },
+            arguments: vec![
+            Integer{value: 3}.into(),
+            ConstructorCall {
+                constructor: GenericConstructor{
+                    id: Id::from("Nil"),
+                    type_instances: vec![ATOMIC_TYPE_INT.into()]
+                },
+                arguments: Vec::new(),
+            }.into(),
+            ],
+        }.into(),
+        Some(TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("List")].borrow().instantiate(&vec![TYPE_INT]))),
+        TypeContext::new();
+        "constructor call multiple arguments"
+    )]
+    #[test_case(
+        ConstructorCall {
+            constructor: GenericConstructor{
+                id: Id::from("Cons"),
+                type_instances: vec![ATOMIC_TYPE_INT.into()]
+            },
+            arguments: vec![
+            Integer{value: 3}.into(),
+            Boolean{value: true}.into(),
+            ],
+        }.into(),
+        None,
+        TypeContext::new();
+        "constructor call multiple wrong arguments"
+    )]
+    #[test_case(
+        ConstructorCall {
+            constructor: GenericConstructor{
+                id: Id::from("Cons"),
+                type_instances: vec![ATOMIC_TYPE_INT.into()]
+            },
+            arguments: vec![
+            Integer{value: 3}.into(),
+            Integer{value: 4}.into(),
+            ConstructorCall {
+                constructor: GenericConstructor{
+                    id: Id::from("Nil"),
+                    type_instances: vec![ATOMIC_TYPE_INT.into()]
+                },
+                arguments: Vec::new(),
+            }.into(),
+            ],
+        }.into(),
+        None,
+        TypeContext::new();
+        "constructor call too many arguments"
+    )]
     #[test_case(
         ConstructorCall {
             constructor: GenericConstructor{
@@ -2845,11 +5506,13 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("twoo"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                         MatchItem {
                             type_name: Id::from("faws"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         }
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -2871,7 +5534,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("twoo"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -2880,7 +5544,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("faws"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         }
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -2902,11 +5567,13 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("two"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                         MatchItem {
                             type_name: Id::from("faw"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         }
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -2928,7 +5595,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("twoo"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(Boolean{ value: true }.into())
@@ -2937,7 +5605,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("faws"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         }
                     ],
                     block: ExpressionBlock(Integer{ value: 4 }.into())
@@ -2959,7 +5628,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("True"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -2978,7 +5648,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("twoo"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -3002,7 +5673,8 @@ mod tests {
                             type_name: Id::from("faws"),
                             assignee: Some(Assignee {
                                 id: Id::from("x")
-                            })
+                            }),
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -3011,7 +5683,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("twoo"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
@@ -3035,7 +5708,8 @@ mod tests {
                             type_name: Id::from("Some"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(GenericVariable{id:Id::from("y"), type_instances: Vec::new()}.into())
@@ -3044,7 +5718,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("None"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(Integer{value: 3}.into())
@@ -3066,7 +5741,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("Some"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(Integer{value: -3}.into())
@@ -3075,7 +5751,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("None"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(Integer{value: 3}.into())
@@ -3099,7 +5776,8 @@ mod tests {
                             type_name: Id::from("Some"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(GenericVariable{id:Id::from("y"), type_instances: Vec::new()}.into())
@@ -3108,7 +5786,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("None"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(GenericVariable{id:Id::from("y"), type_instances: Vec::new()}.into())
@@ -3132,11 +5811,13 @@ mod tests {
                             type_name: Id::from("Some"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                         MatchItem {
                             type_name: Id::from("None"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(GenericVariable{id:Id::from("y"), type_instances: Vec::new()}.into())
@@ -3160,13 +5841,15 @@ mod tests {
                             type_name: Id::from("Left"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                         MatchItem {
                             type_name: Id::from("Right"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(GenericVariable{id:Id::from("y"), type_instances: Vec::new()}.into())
@@ -3190,13 +5873,15 @@ mod tests {
                             type_name: Id::from("Left"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                         MatchItem {
                             type_name: Id::from("Right"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(GenericVariable{id:Id::from("y"), type_instances: Vec::new()}.into())
@@ -3220,13 +5905,15 @@ mod tests {
                             type_name: Id::from("Left"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                         MatchItem {
                             type_name: Id::from("Right"),
                             assignee: Some(Assignee {
                                 id: Id::from("z")
-                            })
+                            }),
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(GenericVariable{id:Id::from("y"), type_instances: Vec::new()}.into())
@@ -3250,7 +5937,8 @@ mod tests {
                             type_name: Id::from("Some"),
                             assignee: Some(Assignee {
                                 id: Id::from("y")
-                            })
+                            }),
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(
@@ -3263,7 +5951,8 @@ mod tests {
                                             type_name: Id::from("Left"),
                                             assignee: Some(Assignee {
                                                 id: Id::from("y")
-                                            })
+                                            }),
+                                            pattern: None
                                         },
                                     ],
                                     block: ExpressionBlock(Var("y").into())
@@ -3274,7 +5963,8 @@ mod tests {
                                             type_name: Id::from("Right"),
                                             assignee: Some(Assignee {
                                                 id: Id::from("r")
-                                            })
+                                            }),
+                                            pattern: None
                                         },
                                     ],
                                     block: ExpressionBlock(
@@ -3292,7 +5982,8 @@ mod tests {
                     matches: vec![
                         MatchItem {
                             type_name: Id::from("None"),
-                            assignee: None
+                            assignee: None,
+                            pattern: None
                         },
                     ],
                     block: ExpressionBlock(Integer{value: 0}.into())
@@ -3312,1275 +6003,3644 @@ mod tests {
         ]);
         "nested match"
     )]
-    fn test_check_expressions(
-        expression: Expression,
-        expected_type: Option<Type>,
-        context: TypeContext,
-    ) {
-        let type_checker = TypeChecker {
-            type_definitions: TYPE_DEFINITIONS
-                .with(|definitions| TypeDefinitions::from(definitions.0.clone())),
-            constructors: TYPE_CONSTRUCTORS.clone(),
-        };
-        let type_check_result =
-            type_checker.check_expression(expression, &context, &GenericVariables::new());
-        match expected_type {
-            Some(type_) => match &type_check_result {
-                Ok(typed_expression) => {
-                    assert!(Type::equality(&typed_expression.type_(), &type_))
-                }
-                Err(msg) => {
-                    dbg!(msg);
-                    assert!(&type_check_result.is_ok());
-                }
-            },
-            None => {
-                if type_check_result.is_ok() {
-                    dbg!(&type_check_result);
-                }
-                assert!(&type_check_result.is_err());
-            }
-        }
-    }
-
     #[test_case(
-        ExpressionBlock(Boolean{value: true}.into()),
+        FunctionCall {
+            function: Box::new(GenericVariable{id: Id::from("get"), type_instances: vec![ATOMIC_TYPE_BOOL.into()]}.into()),
+            arguments: vec![
+                FunctionCall {
+                    function: Box::new(GenericVariable{id: Id::from("push"), type_instances: vec![ATOMIC_TYPE_BOOL.into()]}.into()),
+                    arguments: vec![
+                        FunctionCall {
+                            function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_BOOL.into()]}.into()),
+                            arguments: Vec::new(),
+                        }.into(),
+                        Boolean{value: true}.into(),
+                    ],
+                }.into(),
+                Integer{value: 0}.into(),
+            ],
+        }.into(),
         Some(TYPE_BOOL),
-        TypeContext::new();
-        "block no assignments"
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "array get"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Boolean{value: true}.into())
-                }
+        FunctionCall {
+            function: Box::new(GenericVariable{id: Id::from("len"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+            arguments: vec![
+                FunctionCall {
+                    function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+                    arguments: Vec::new(),
+                }.into(),
             ],
-            expression: Box::new(Boolean{value: true}.into())
-        },
-        Some(TYPE_BOOL),
-        TypeContext::new();
-        "block unused assignment"
+        }.into(),
+        Some(TYPE_INT),
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "array length"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Boolean{value: true}.into())
-                }
+        FunctionCall {
+            function: Box::new(GenericVariable{id: Id::from("push"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+            arguments: vec![
+                FunctionCall {
+                    function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+                    arguments: Vec::new(),
+                }.into(),
+                Boolean{value: true}.into(),
             ],
-            expression: Box::new(Var("x").into())
-        },
-        Some(TYPE_BOOL),
-        TypeContext::new();
-        "block used assignment"
+        }.into(),
+        None,
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "array push wrong element"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Integer{value: 3}.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("y"),
-                    expression: Box::new(Var("x").into())
-                },
-            ],
-            expression: Box::new(Var("y").into())
-        },
+        IfExpression {
+            condition: Box::new(Boolean{value: false}.into()),
+            true_block: ExpressionBlock(FunctionCall {
+                function: Box::new(Var("error").into()),
+                arguments: vec![Str{value: String::from("unreachable")}.into()],
+            }.into()),
+            false_block: Some(ExpressionBlock(Integer{value: 1}.into()))
+        }.into(),
         Some(TYPE_INT),
-        TypeContext::new();
-        "block multiple assignments"
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "error in if arm"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Integer{value: 3}.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Boolean{value: true}.into())
-                },
+        FunctionCall {
+            function: Box::new(Var("++").into()),
+            arguments: vec![
+                FunctionCall {
+                    function: Box::new(Var("error").into()),
+                    arguments: vec![Str{value: String::from("unreachable")}.into()],
+                }.into(),
             ],
-            expression: Box::new(Var("x").into())
-        },
-        Some(TYPE_BOOL),
-        TypeContext::new();
-        "block duplicate assignments"
+        }.into(),
+        Some(TYPE_INT),
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "error as argument"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(Integer{value: 3}.into())
-                    }.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(Integer{value: -3}.into())
-                    }.into())
-                },
-            ],
-            expression: Box::new(Var("f").into())
-        },
-        None,
-        TypeContext::new();
-        "block duplicate fn assignments"
+        FunctionCall {
+            function: Box::new(GenericVariable{id: Id::from("error"), type_instances: vec![ATOMIC_TYPE_BOOL.into()]}.into()),
+            arguments: vec![Str{value: String::from("unreachable")}.into()],
+        }.into(),
+        Some(TYPE_BOOL),
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "error with type instance"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(Integer{value: 3}.into())
-                    }.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(Integer{value: -3}.into())
-                },
-            ],
-            expression: Box::new(Var("f").into())
-        },
+        FunctionCall {
+            function: Box::new(Var("error").into()),
+            arguments: vec![Integer{value: 1}.into()],
+        }.into(),
         None,
-        TypeContext::new();
-        "block variable and fn assignments"
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "error with non-string message"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Integer{value: 3}.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
-                },
+        FunctionCall {
+            function: Box::new(Var("id").into()),
+            arguments: vec![Integer{value: 5}.into()],
+        }.into(),
+        Some(TYPE_INT),
+        TypeContext::from([
+            (
+                Id::from("id"),
+                {
+                    let parameter = Rc::new(RefCell::new(None));
+                    ParametricType{
+                        type_: TypeFn(
+                            vec![TypeVariable(parameter.clone()).into()],
+                            Box::new(TypeVariable(parameter.clone()).into()),
+                        ).into(),
+                        parameters: vec![parameter]
+                    }.into()
+                }
+            )
+        ]);
+        "inferred type parameter"
+    )]
+    #[test_case(
+        FunctionCall {
+            function: Box::new(Var("len").into()),
+            arguments: vec![
+                FunctionCall {
+                    function: Box::new(Var("push").into()),
+                    arguments: vec![
+                        FunctionCall {
+                            function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_BOOL.into()]}.into()),
+                            arguments: Vec::new(),
+                        }.into(),
+                        Boolean{value: true}.into(),
+                    ],
+                }.into(),
             ],
-            expression: Box::new(Var("f").into())
-        },
-        Some(TypeFn(
-            Vec::new(),
-            Box::new(TYPE_INT.into())
-        ).into()),
-        TypeContext::new();
-        "fn use closure"
+        }.into(),
+        Some(TYPE_INT),
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "inferred array element"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Integer{value: 3}.into())
-                },
+        FunctionCall {
+            function: Box::new(Var("push").into()),
+            arguments: vec![
+                FunctionCall {
+                    function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+                    arguments: Vec::new(),
+                }.into(),
+                Boolean{value: true}.into(),
             ],
-            expression: Box::new(Var("f").into())
-        },
+        }.into(),
         None,
-        TypeContext::new();
-        "fn invalid closure"
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "inferred conflicting arguments"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Integer{value: 3}.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: vec![
-                            TypedAssignee{
-                                assignee: Id::from("x").into(),
-                                type_: ATOMIC_TYPE_BOOL.into()
-                            },
-                        ],
-                        return_type: ATOMIC_TYPE_BOOL.into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
-                },
+        FunctionCall {
+            function: Box::new(Var("eq").into()),
+            arguments: vec![Integer{value: 1}.into(), Integer{value: 2}.into()],
+        }.into(),
+        Some(TYPE_BOOL),
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "inferred eq"
+    )]
+    #[test_case(
+        FunctionCall {
+            function: Box::new(Var("eq").into()),
+            arguments: vec![Var("f").into(), Var("f").into()],
+        }.into(),
+        None,
+        {
+            let mut context = DEFAULT_CONTEXT.with(|context| (**context).clone());
+            context.insert(
+                Id::from("f"),
+                Type::from(TypeFn(Vec::new(), Box::new(TYPE_INT))).into(),
+            );
+            context
+        };
+        "inferred eq of fns"
+    )]
+    #[test_case(
+        FunctionCall {
+            function: Box::new(Var("pmap").into()),
+            arguments: vec![
+                Var("f").into(),
+                FunctionCall {
+                    function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+                    arguments: Vec::new(),
+                }.into(),
             ],
-            expression: Box::new(Var("f").into())
-        },
-        Some(TypeFn(
-            vec![TYPE_BOOL.into()],
-            Box::new(TYPE_BOOL.into())
-        ).into()),
-        TypeContext::new();
-        "fn shadowed closure"
+        }.into(),
+        Some(TypeArray(Box::new(TYPE_BOOL)).into()),
+        {
+            let mut context = DEFAULT_CONTEXT.with(|context| (**context).clone());
+            context.insert(
+                Id::from("f"),
+                Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_BOOL))).into(),
+            );
+            context
+        };
+        "parallel map"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("foo"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_BOOL.into(),
-                        body: ExpressionBlock(FunctionCall{
-                            function: Box::new(Var("bar").into()),
-                            arguments: Vec::new()
-                        }.into())
-                    }.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("bar"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_BOOL.into(),
-                        body: ExpressionBlock(FunctionCall{
-                            function: Box::new(Var("foo").into()),
-                            arguments: Vec::new()
-                        }.into())
-                    }.into())
-                },
+        FunctionCall {
+            function: Box::new(Var("preduce").into()),
+            arguments: vec![
+                Var("+").into(),
+                FunctionCall {
+                    function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+                    arguments: Vec::new(),
+                }.into(),
             ],
-            expression: Box::new(Var("foo").into())
-        },
-        Some(TypeFn(
-            Vec::new(),
-            Box::new(TYPE_BOOL.into())
-        ).into()),
-        TypeContext::new();
-        "mutually recursive fns"
+        }.into(),
+        Some(TYPE_INT),
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "parallel reduce"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("foo"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_BOOL.into(),
-                        body: ExpressionBlock(Boolean{value:true}.into())
-                    }.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(FunctionCall{
-                        function: Box::new(Var("foo").into()),
-                        arguments: Vec::new()
-                    }.into())
-                },
+        FunctionCall {
+            function: Box::new(Var("preduce").into()),
+            arguments: vec![
+                Var("f").into(),
+                FunctionCall {
+                    function: Box::new(GenericVariable{id: Id::from("empty"), type_instances: vec![ATOMIC_TYPE_INT.into()]}.into()),
+                    arguments: Vec::new(),
+                }.into(),
             ],
-            expression: Box::new(Var("x").into())
-        },
-        Some(TYPE_BOOL.into()),
+        }.into(),
+        None,
+        {
+            let mut context = DEFAULT_CONTEXT.with(|context| (**context).clone());
+            context.insert(
+                Id::from("f"),
+                Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_BOOL))).into(),
+            );
+            context
+        };
+        "parallel reduce with unary fn"
+    )]
+    #[test_case(
+        Annotated {
+            expression: Box::new(Integer{value: 3}.into()),
+            type_: ATOMIC_TYPE_INT.into()
+        }.into(),
+        Some(TYPE_INT),
         TypeContext::new();
-        "user defined fn call"
+        "correct annotation"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(FunctionCall{
-                        function: Box::new(Var("foo").into()),
-                        arguments: Vec::new()
-                    }.into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("foo"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_BOOL.into(),
-                        body: ExpressionBlock(Boolean{value:true}.into())
-                    }.into())
-                },
-            ],
-            expression: Box::new(Var("x").into())
-        },
+        Annotated {
+            expression: Box::new(Integer{value: 3}.into()),
+            type_: ATOMIC_TYPE_BOOL.into()
+        }.into(),
         None,
         TypeContext::new();
-        "call before definition"
+        "wrong annotation"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("f"),
-                    expression: Box::new(FunctionDefinition {
-                        parameters: vec![
-                            TypedAssignee{
-                                assignee: Id::from("x").into(),
-                                type_: ATOMIC_TYPE_BOOL.into()
-                            },
-                        ],
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: Block{
-                            assignments: vec![
-                                Assignment{
-                                    assignee: VariableAssignee("x"),
-                                    expression: Box::new(Integer{value: 3}.into())
-                                },
-                            ],
-                            expression: Box::new(Var("x").into())
-                        }
-                    }.into())
-                },
-            ],
-            expression: Box::new(Var("f").into())
-        },
-        Some(TypeFn(
-            vec![TYPE_BOOL.into()],
-            Box::new(TYPE_INT.into())
-        ).into()),
-        TypeContext::new();
-        "fn shadowed arg"
-    )]
-    #[test_case(
-        Block {
-            assignments: vec![
-                Assignment{
-                    assignee: VariableAssignee("y"),
-                    expression: Box::new(Var("x").into())
-                },
-                Assignment{
-                    assignee: VariableAssignee("x"),
-                    expression: Box::new(Integer{value: 3}.into())
-                },
-            ],
-            expression: Box::new(Var("y").into())
-        },
-        None,
-        TypeContext::new();
-        "block flipped assignments"
+        Annotated {
+            expression: Box::new(FunctionCall {
+                function: Box::new(Var("error").into()),
+                arguments: vec![Str{value: String::from("unreachable")}.into()],
+            }.into()),
+            type_: ATOMIC_TYPE_BOOL.into()
+        }.into(),
+        Some(TYPE_BOOL),
+        DEFAULT_CONTEXT.with(|context| (**context).clone());
+        "annotation infers type parameter"
     )]
+    fn test_check_expressions(
+        expression: Expression,
+        expected_type: Option<Type>,
+        context: TypeContext,
+    ) {
+        let type_checker = TypeChecker::new(
+            TYPE_DEFINITIONS.with(|definitions| TypeDefinitions::from(definitions.0.clone())),
+            TYPE_CONSTRUCTORS.clone(),
+        );
+        let type_check_result =
+            type_checker.check_expression(expression, &context, &GenericVariables::new());
+        match expected_type {
+            Some(type_) => match &type_check_result {
+                Ok(typed_expression) => {
+                    assert!(Type::equality(&typed_expression.type_(), &type_))
+                }
+                Err(msg) => {
+                    dbg!(msg);
+                    assert!(&type_check_result.is_ok());
+                }
+            },
+            None => {
+                if type_check_result.is_ok() {
+                    dbg!(&type_check_result);
+                }
+                assert!(&type_check_result.is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_annotation_mismatch() {
+        let type_checker = TypeChecker::with_definitions(Vec::new()).unwrap();
+        let result = type_checker.check_standalone(
+            Annotated {
+                expression: Box::new(Integer { value: 3 }.into()),
+                type_: ATOMIC_TYPE_BOOL.into(),
+            }
+            .into(),
+            &TypeContext::new(),
+        );
+        let Err(TypeCheckError::AnnotationMismatch { expression, type_ }) = result else {
+            panic!("Expected annotation mismatch, got {:?}.", result)
+        };
+        assert_eq!(*expression, TypedExpression::from(Integer { value: 3 }));
+        assert_eq!(type_, TYPE_BOOL);
+    }
+
+    fn check_call_to_int_bool_fn(
+        arguments: Vec<Expression>,
+    ) -> Result<TypedExpression, TypeCheckError> {
+        let type_checker = TypeChecker::new(TypeDefinitions::new(), HashMap::new());
+        let context = TypeContext::from([(
+            Id::from("f"),
+            Type::from(TypeFn(vec![TYPE_INT, TYPE_BOOL], Box::new(TYPE_INT))).into(),
+        )]);
+        type_checker.check_fn_call(
+            FunctionCall {
+                function: Box::new(Var("f").into()),
+                arguments,
+            },
+            &context,
+            &GenericVariables::new(),
+        )
+    }
+
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: ATOMIC_TYPE_INT.into()
-                },
-                TypedAssignee{
-                    assignee: Id::from("y").into(),
-                    type_: ATOMIC_TYPE_BOOL.into()
-                },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(Var("z").into())
-        }.into()),
-        None,
-        TypeContext::new();
-        "function invalid block"
+        vec![Boolean { value: true }.into(), Boolean { value: true }.into()],
+        (0, TYPE_INT, TYPE_BOOL);
+        "first argument"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: ATOMIC_TYPE_INT.into()
-                },
-                TypedAssignee{
-                    assignee: Id::from("y").into(),
-                    type_: ATOMIC_TYPE_BOOL.into()
-                },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(Var("y").into())
-        }.into()),
-        None,
-        TypeContext::new();
-        "function incorrect return type"
+        vec![Integer { value: 3 }.into(), Integer { value: 3 }.into()],
+        (1, TYPE_BOOL, TYPE_INT);
+        "second argument"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: ATOMIC_TYPE_INT.into()
-                },
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: ATOMIC_TYPE_BOOL.into()
-                },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(Integer{value: 5}.into())
-        }.into()),
-        None,
-        TypeContext::new();
-        "function duplicate parameter"
+        vec![Boolean { value: true }.into(), Integer { value: 3 }.into()],
+        (0, TYPE_INT, TYPE_BOOL);
+        "both arguments"
     )]
-    #[test_case(
-        Block {
-            assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee{
-                        assignee: Assignee{
-                            id: Id::from("g")
+    fn test_invalid_function_call(arguments: Vec<Expression>, mismatch: (usize, Type, Type)) {
+        let result = check_call_to_int_bool_fn(arguments);
+        let Err(TypeCheckError::InvalidFunctionCall {
+            expression: _,
+            arguments: _,
+            arg_index,
+            expected,
+            found,
+            generic_parameter: None,
+            note: None,
+        }) = result
+        else {
+            panic!("Expected invalid function call, got {:?}.", result)
+        };
+        assert_eq!((arg_index, *expected, *found), mismatch);
+    }
+
+    /// `pick<T> = (x: T, y: T) -> T { x }; pick<instances>(arguments)`
+    fn check_call_to_generic_fn(
+        type_instances: Vec<TypeInstance>,
+        arguments: Vec<Expression>,
+    ) -> Result<TypedBlock, TypeCheckError> {
+        let type_checker = TypeChecker::check_type_definitions(Vec::new()).unwrap();
+        let generic = || {
+            TypeInstance::from(GenericType {
+                id: Id::from("T"),
+                type_variables: Vec::new(),
+            })
+        };
+        type_checker.check_block(
+            Block {
+                assignments: vec![Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Assignee {
+                            id: Id::from("pick"),
                         },
-                        generic_variables: vec![Id::from("T"), Id::from("U")]
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
                     },
                     expression: Box::new(
                         FunctionDefinition {
                             parameters: vec![
-                                TypedAssignee{
-                                    assignee: Id::from("f").into(),
-                                    type_: FunctionType{
-                                        argument_types: vec![
-                                            GenericType{
-                                                id: Id::from("T"),
-                                                type_variables: Vec::new()
-                                            }.into()
-                                        ],
-                                        return_type: Box::new(GenericType{
-                                            id: Id::from("U"),
-                                            type_variables: Vec::new()
-                                        }.into())
-                                    }.into(),
-                                },
-                                TypedAssignee{
+                                TypedAssignee {
                                     assignee: Id::from("x").into(),
-                                    type_: GenericType{
-                                        id: Id::from("T"),
-                                        type_variables: Vec::new()
-                                    }.into()
+                                    type_: generic(),
+                                },
+                                TypedAssignee {
+                                    assignee: Id::from("y").into(),
+                                    type_: generic(),
                                 },
                             ],
-                            return_type: GenericType{
-                                id: Id::from("U"),
-                                type_variables: Vec::new()
-                            }.into(),
-                            body: ExpressionBlock(FunctionCall{
-                                function: Box::new(Var("f").into()),
-                                arguments: vec![Var("x").into()]
-                            }.into())
-                        }.into()
-                    )
-                }
-            ],
-            expression: Box::new(GenericVariable{
-                id: Id::from("g"),
-                type_instances: vec![
-                    ATOMIC_TYPE_INT.into(),
-                    ATOMIC_TYPE_BOOL.into()
-                ]
-            }.into())
-        },
-        Some(Type::from(TypeFn(
-            vec![
-                Type::from(TypeFn(vec![TYPE_INT],Box::new(TYPE_BOOL))),
-                TYPE_INT
-            ],
-            Box::new(TYPE_BOOL),
-        ))),
-        TypeContext::new();
-        "function matched generics"
+                            return_type: generic(),
+                            body: ExpressionBlock(Var("x").into()),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }],
+                expression: Box::new(
+                    FunctionCall {
+                        function: Box::new(
+                            GenericVariable {
+                                id: Id::from("pick"),
+                                type_instances,
+                            }
+                            .into(),
+                        ),
+                        arguments,
+                    }
+                    .into(),
+                ),
+            },
+            TypeContext::new(),
+            GenericVariables::new(),
+        )
+    }
+
+    #[test_case(
+        Vec::new(),
+        vec![Integer { value: 3 }.into(), Boolean { value: true }.into()],
+        (1, TYPE_INT, TYPE_BOOL);
+        "inferred parameter"
     )]
     #[test_case(
-        Block {
-            assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee{
-                        assignee: Assignee{
-                            id: Id::from("g")
-                        },
-                        generic_variables: vec![Id::from("T"), Id::from("U")]
-                    },
-                    expression: Box::new(
-                        FunctionDefinition {
-                            parameters: vec![
-                                TypedAssignee{
-                                    assignee: Id::from("f").into(),
-                                    type_: FunctionType{
-                                        argument_types: vec![
-                                            GenericType{
-                                                id: Id::from("T"),
-                                                type_variables: Vec::new()
-                                            }.into()
-                                        ],
-                                        return_type: Box::new(GenericType{
-                                            id: Id::from("U"),
-                                            type_variables: Vec::new()
-                                        }.into())
-                                    }.into(),
-                                },
-                                TypedAssignee{
-                                    assignee: Id::from("x").into(),
-                                    type_: GenericType{
-                                        id: Id::from("T"),
-                                        type_variables: Vec::new()
-                                    }.into()
-                                },
-                            ],
-                            return_type: GenericType{
-                                id: Id::from("U"),
-                                type_variables: Vec::new()
-                            }.into(),
-                            body: ExpressionBlock(Var("x").into())
-                        }.into()
-                    )
-                }
-            ],
-            expression: Box::new(GenericVariable{
-                id: Id::from("g"),
-                type_instances: vec![
-                    ATOMIC_TYPE_INT.into(),
-                    ATOMIC_TYPE_BOOL.into()
-                ]
-            }.into())
-        },
-        None,
-        TypeContext::new();
-        "function mismatched generics"
+        vec![ATOMIC_TYPE_INT.into()],
+        vec![Boolean { value: true }.into(), Integer { value: 3 }.into()],
+        (0, TYPE_INT, TYPE_BOOL);
+        "explicit parameter"
     )]
+    fn test_invalid_generic_function_call(
+        type_instances: Vec<TypeInstance>,
+        arguments: Vec<Expression>,
+        mismatch: (usize, Type, Type),
+    ) {
+        let result = check_call_to_generic_fn(type_instances, arguments);
+        let Err(TypeCheckError::InvalidFunctionCall {
+            expression: _,
+            arguments: _,
+            arg_index,
+            expected,
+            found,
+            generic_parameter,
+            note: None,
+        }) = result
+        else {
+            panic!("Expected invalid function call, got {:?}.", result)
+        };
+        assert_eq!((arg_index, *expected, *found), mismatch);
+        assert_eq!(
+            generic_parameter,
+            Some(Box::new(GenericParameterSite {
+                variable: Id::from("T"),
+                function: Id::from("pick"),
+            }))
+        );
+    }
+
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: Typename("opaque_int").into()
-                },
-            ],
-            return_type: Typename("opaque_int").into(),
-            body: ExpressionBlock(Var("x").into()),
-        }.into()),
-        Some(Type::from(TypeFn(
-            vec![Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("opaque_int")].clone()), instances: Vec::new()})],
-            Box::new(Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("opaque_int")].clone()), instances: Vec::new()}))
-        ))),
-        TypeContext::new();
-        "opaque type reference"
+        "==",
+        vec![
+            TupleExpression { expressions: vec![Integer { value: 1 }.into()] }.into(),
+            TupleExpression { expressions: vec![Integer { value: 2 }.into()] }.into(),
+        ],
+        true;
+        "tuple equality"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: Typename("transparent_int").into()
-                },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(Var("x").into()),
-        }.into()),
-        Some(Type::from(TypeFn(
-            vec![Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("transparent_int")].clone()), instances: Vec::new()})],
-            Box::new(TYPE_INT)
-        ))),
-        TypeContext::new();
-        "transparent type reference"
+        "<",
+        vec![
+            TupleExpression { expressions: Vec::new() }.into(),
+            TupleExpression { expressions: Vec::new() }.into(),
+        ],
+        true;
+        "tuple comparison"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: Typename("transparent_int").into()
+        "==",
+        vec![Integer { value: 1 }.into(), Boolean { value: true }.into()],
+        false;
+        "boolean equality"
+    )]
+    fn test_comparison_note(operator: &str, arguments: Vec<Expression>, has_note: bool) {
+        let type_checker = TypeChecker::check_type_definitions(Vec::new()).unwrap();
+        let result = DEFAULT_CONTEXT.with(|context| {
+            type_checker.check_fn_call(
+                FunctionCall {
+                    function: Box::new(Var(operator).into()),
+                    arguments,
                 },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(Var("x").into()),
-        }.into()),
-        Some(Type::from(TypeFn(
-            vec![Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("transparent_int")].clone()), instances: Vec::new()})],
-            Box::new(Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("transparent_int_2")].clone()), instances: Vec::new()}))
-        ))),
-        TypeContext::new();
-        "double transparent type reference"
+                context,
+                &GenericVariables::new(),
+            )
+        });
+        let Err(TypeCheckError::InvalidFunctionCall { note, .. }) = result else {
+            panic!("Expected invalid function call, got {:?}.", result)
+        };
+        assert_eq!(note.is_some(), has_note);
+        if let Some(note) = note {
+            assert!(note.contains("`eq`"));
+        }
+    }
+
+    #[test_case(Vec::new(), 0; "no arguments")]
+    #[test_case(
+        vec![
+            Integer { value: 3 }.into(),
+            Boolean { value: true }.into(),
+            Boolean { value: true }.into()
+        ],
+        3;
+        "too many arguments"
     )]
+    fn test_function_call_arity_mismatch(arguments: Vec<Expression>, found: usize) {
+        assert_eq!(
+            check_call_to_int_bool_fn(arguments),
+            Err(TypeCheckError::ArityMismatch { expected: 2, found })
+        );
+    }
+
+    #[test]
+    fn test_partial_function_call() {
+        let partial = check_call_to_int_bool_fn(vec![Integer { value: 3 }.into()]);
+        assert_eq!(
+            partial.as_ref().map(TypedExpression::type_),
+            Ok(Type::from(TypeFn(vec![TYPE_BOOL], Box::new(TYPE_INT))))
+        );
+        let Ok(TypedExpression::TypedFunctionCall(TypedFunctionCall {
+            function,
+            arguments,
+        })) = partial
+        else {
+            panic!("Expected partial application to bind its arguments.")
+        };
+        assert_eq!(arguments, vec![Integer { value: 3 }.into()]);
+        let TypedExpression::TypedLambdaDef(TypedLambdaDef {
+            parameters,
+            return_type: _,
+            body,
+        }) = *function
+        else {
+            panic!("Expected arguments to be bound by a fn.")
+        };
+        assert_eq!(parameters.len(), 1);
+        assert!(matches!(
+            *body.expression,
+            TypedExpression::TypedLambdaDef(TypedLambdaDef { ref parameters, .. })
+                if parameters.len() == 1
+        ));
+    }
+
+    #[test]
+    fn test_invalid_generic_constructor_arguments() {
+        let type_checker = TypeChecker::new(
+            TYPE_DEFINITIONS.with(|definitions| TypeDefinitions::from(definitions.0.clone())),
+            TYPE_CONSTRUCTORS.clone(),
+        );
+        // `Cons[bool](1, Nil[bool])`
+        let result = type_checker.check_expression(
+            ConstructorCall {
+                constructor: GenericConstructor {
+                    id: Id::from("Cons"),
+                    type_instances: vec![ATOMIC_TYPE_BOOL.into()],
+                },
+                arguments: vec![
+                    Integer { value: 1 }.into(),
+                    ConstructorCall {
+                        constructor: GenericConstructor {
+                            id: Id::from("Nil"),
+                            type_instances: vec![ATOMIC_TYPE_BOOL.into()],
+                        },
+                        arguments: Vec::new(),
+                    }
+                    .into(),
+                ],
+            }
+            .into(),
+            &TypeContext::new(),
+            &GenericVariables::new(),
+        );
+        let Err(TypeCheckError::InvalidConstructorArguments {
+            id,
+            input_type: Some(Type::TypeTuple(TypeTuple(input_types))),
+            generic_input_type: Some(generic_input_type),
+            type_instances,
+            arguments: _,
+        }) = result
+        else {
+            panic!("Expected invalid constructor arguments, got {:?}.", result)
+        };
+        let Type::TypeTuple(TypeTuple(generic_input_types)) = *generic_input_type else {
+            panic!(
+                "Expected a generic tuple type, got {:?}.",
+                generic_input_type
+            )
+        };
+        assert_eq!(id, Id::from("Cons"));
+        assert_eq!(type_instances, vec![TypeInstance::from(ATOMIC_TYPE_BOOL)]);
+        assert_eq!(input_types[0], TYPE_BOOL);
+        assert!(matches!(
+            &generic_input_types[0],
+            Type::TypeVariable(TypeVariable(parameter)) if parameter.borrow().is_none()
+        ));
+    }
+
+    #[test_case(1 << 31, IntWidth(32), false; "overflow at 2^31")]
+    #[test_case((1 << 31) - 1, IntWidth(32), true; "largest 32-bit integer")]
+    #[test_case(-(1 << 31), IntWidth(32), true; "smallest 32-bit integer")]
+    #[test_case(-(1 << 31) - 1, IntWidth(32), false; "underflow below -2^31")]
+    #[test_case(1 << 31, IntWidth(64), true; "2^31 in 64-bit")]
+    #[test_case(i64::MAX, IntWidth::default(), true; "largest 64-bit integer")]
+    #[test_case(i64::MIN, IntWidth::default(), true; "smallest 64-bit integer")]
+    fn test_integer_range(value: i64, int_width: IntWidth, fits: bool) {
+        let type_checker = TypeChecker {
+            int_width,
+            ..TypeChecker::new(TypeDefinitions::new(), HashMap::new())
+        };
+        let result = type_checker.check_expression(
+            Integer { value }.into(),
+            &TypeContext::new(),
+            &GenericVariables::new(),
+        );
+        if fits {
+            assert_eq!(result, Ok(Integer { value }.into()));
+        } else {
+            assert_eq!(
+                result,
+                Err(TypeCheckError::IntegerOutOfRange {
+                    value,
+                    width: int_width
+                })
+            );
+        }
+    }
+
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: Typename("ii").into()
+        MatchExpression {
+            subject: Box::new(Var("random_bull").into()),
+            blocks: vec![
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("twoo"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
                 },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(ElementAccess{
-                expression: Box::new(Var("x").into()),
-                index: 0
-            }.into()),
-        }.into()),
-        Some(Type::from(TypeFn(
-            vec![Type::from(TypeTuple(vec![TYPE_INT, TYPE_INT]))],
-            Box::new(TYPE_INT)
-        ))),
-        TypeContext::new();
-        "transparent type usage"
+            ]
+        },
+        Err(TypeCheckError::NonExhaustiveMatch {
+            type_name: Id::from("Bull"),
+            missing: vec![Id::from("faws")]
+        });
+        "missing variant"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition {
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: Typename("recursive").into()
+        MatchExpression {
+            subject: Box::new(Var("random_bull").into()),
+            blocks: vec![
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("faws"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
                 },
-            ],
-            return_type: Typename("recursive").into(),
-            body: ExpressionBlock(ElementAccess{
-                expression: Box::new(Var("x").into()),
-                index: 0
-            }.into()),
-        }.into()),
-        None,
-        TypeContext::new();
-        "invalid recursive type usage"
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("twoo"),
+                            assignee: None,
+                            pattern: None
+                        },
+                        MatchItem {
+                            type_name: Id::from("faws"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
+                },
+            ]
+        },
+        Err(TypeCheckError::RedundantMatchArm {
+            type_name: Id::from("Bull"),
+            variant: Id::from("faws")
+        });
+        "repeated variant"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition{
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: ATOMIC_TYPE_INT.into()
+        MatchExpression {
+            subject: Box::new(Var("random_bull").into()),
+            blocks: vec![
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("faws"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
                 },
-                TypedAssignee{
-                    assignee: Id::from("y").into(),
-                    type_: ATOMIC_TYPE_INT.into()
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("_"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
                 },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(FunctionCall {
-                function: Box::new(Var("+").into()),
-                arguments: vec![
-                    Var("x").into(),
-                    Var("y").into(),
-                ],
-            }.into())
-        }.into()),
-        Some(Type::from(TypeFn(
-            vec![TYPE_INT, TYPE_INT],
-            Box::new(TYPE_INT)
-        ))),
-        TypeContext::from([(
-            Id::from("+"),
-            Type::from(TypeFn(
-                vec![TYPE_INT, TYPE_INT],
-                Box::new(TYPE_INT)
-            )).into()
-        )]);
-        "add function definition"
+            ]
+        },
+        Ok(vec![vec![1], vec![0]]);
+        "wildcard remaining variant"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition{
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: ATOMIC_TYPE_INT.into()
+        MatchExpression {
+            subject: Box::new(Var("random_bull").into()),
+            blocks: vec![
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("_"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
                 },
-                TypedAssignee{
-                    assignee: Id::from("y").into(),
-                    type_: ATOMIC_TYPE_BOOL.into()
+            ]
+        },
+        Ok(vec![vec![0, 1]]);
+        "wildcard all variants"
+    )]
+    #[test_case(
+        MatchExpression {
+            subject: Box::new(Var("random_bull").into()),
+            blocks: vec![
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("twoo"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
                 },
-            ],
-            return_type: ATOMIC_TYPE_INT.into(),
-            body: ExpressionBlock(FunctionCall {
-                function: Box::new(Var("+").into()),
-                arguments: vec![
-                    Var("x").into(),
-                    Var("y").into(),
-                ],
-            }.into())
-        }.into()),
-        None,
-        TypeContext::from([(
-            Id::from("+"),
-            Type::from(TypeFn(
-                vec![TYPE_INT, TYPE_INT],
-                Box::new(TYPE_INT)
-            )).into()
-        )]);
-        "add invalid function definition"
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("faws"),
+                            assignee: None,
+                            pattern: None
+                        },
+                        MatchItem {
+                            type_name: Id::from("_"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
+                },
+            ]
+        },
+        Err(TypeCheckError::RedundantMatchArm {
+            type_name: Id::from("Bull"),
+            variant: Id::from("_")
+        });
+        "wildcard without remaining variants"
     )]
     #[test_case(
-        ExpressionBlock(FunctionDefinition{
-            parameters: vec![
-                TypedAssignee{
-                    assignee: Id::from("x").into(),
-                    type_: Typename("opaque_int").into()
+        MatchExpression {
+            subject: Box::new(Var("random_bull").into()),
+            blocks: vec![
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("_"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
                 },
-            ],
-            return_type: Typename("opaque_int_2").into(),
-            body: ExpressionBlock(Var("x").into())
-        }.into()),
-        None,
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("_"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
+                },
+            ]
+        },
+        Err(TypeCheckError::RedundantMatchArm {
+            type_name: Id::from("Bull"),
+            variant: Id::from("_")
+        });
+        "repeated wildcard"
+    )]
+    #[test_case(
+        MatchExpression {
+            subject: Box::new(Var("random_bull").into()),
+            blocks: vec![
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("twoo"),
+                            assignee: None,
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
+                },
+                MatchBlock {
+                    matches: vec![
+                        MatchItem {
+                            type_name: Id::from("_"),
+                            assignee: Some(Assignee{id: Id::from("x")}),
+                            pattern: None
+                        },
+                    ],
+                    block: ExpressionBlock(TupleExpression{expressions: Vec::new()}.into())
+                },
+            ]
+        },
+        Err(TypeCheckError::MismatchedVariant {
+            type_: TYPE_DEFINITIONS.with(|definitions| definitions[&String::from("Bull")].borrow().type_.clone()),
+            variant_id: Id::from("_"),
+            assignee: Some(Assignee{id: Id::from("x")})
+        });
+        "wildcard assignee"
+    )]
+    fn test_match_arms(
+        match_expression: MatchExpression,
+        expected: Result<Vec<Vec<usize>>, TypeCheckError>,
+    ) {
+        let type_checker = TypeChecker::new(
+            TYPE_DEFINITIONS.with(|definitions| TypeDefinitions::from(definitions.0.clone())),
+            TYPE_CONSTRUCTORS.clone(),
+        );
+        let context = TypeContext::from([(
+            Id::from("random_bull"),
+            TYPE_DEFINITIONS
+                .with(|definitions| definitions[&String::from("Bull")].clone())
+                .into(),
+        )]);
+        let result = type_checker.check_match_expression(
+            match_expression,
+            &context,
+            &GenericVariables::new(),
+        );
+        let indices = result.map(|TypedMatch { subject: _, blocks }| {
+            blocks
+                .into_iter()
+                .map(|block| {
+                    block
+                        .matches
+                        .into_iter()
+                        .map(|item| item.type_idx)
+                        .collect_vec()
+                })
+                .collect_vec()
+        });
+        assert_eq!(indices, expected);
+    }
+
+    #[test]
+    fn test_incompatible_multi_match_binding() {
+        let type_checker = TypeChecker::new(
+            TYPE_DEFINITIONS.with(|definitions| TypeDefinitions::from(definitions.0.clone())),
+            TYPE_CONSTRUCTORS.clone(),
+        );
+        let opaque_int = Type::from(TypeInstantiation {
+            reference: TYPE_DEFINITIONS
+                .with(|definitions| definitions[&String::from("opaque_int")].clone()),
+            instances: Vec::new(),
+        });
+        let context = TypeContext::from([(
+            Id::from("x"),
+            Type::from(TypeInstantiation {
+                reference: TYPE_DEFINITIONS
+                    .with(|definitions| definitions[&String::from("Either")].clone()),
+                instances: vec![TYPE_INT, opaque_int.clone()],
+            })
+            .into(),
+        )]);
+        let match_expression = MatchExpression {
+            subject: Box::new(Var("x").into()),
+            blocks: vec![MatchBlock {
+                matches: vec![
+                    match_item("Left", Some("y"), None),
+                    match_item("Right", Some("y"), None),
+                ],
+                block: ExpressionBlock(
+                    GenericVariable {
+                        id: Id::from("y"),
+                        type_instances: Vec::new(),
+                    }
+                    .into(),
+                ),
+            }],
+        };
+        let result = type_checker.check_match_expression(
+            match_expression,
+            &context,
+            &GenericVariables::new(),
+        );
+        let Err(TypeCheckError::IncompatibleMultiMatchBinding { variants, types }) = result else {
+            panic!("Unexpected result {result:?}.")
+        };
+        assert_eq!(variants, vec![Id::from("Left"), Id::from("Right")]);
+        assert_eq!(types.len(), 2);
+        assert!(Type::equality(&types[0], &TYPE_INT));
+        assert!(Type::equality(&types[1], &opaque_int));
+    }
+
+    fn match_item(
+        type_name: &str,
+        assignee: Option<&str>,
+        pattern: Option<MatchItem>,
+    ) -> MatchItem {
+        MatchItem {
+            type_name: Id::from(type_name),
+            assignee: assignee.map(|id| Assignee { id: Id::from(id) }),
+            pattern: pattern.map(Box::new),
+        }
+    }
+
+    #[test_case(
+        vec![
+            (
+                match_item("Some", None, Some(match_item("Cons", Some("pair"), None))),
+                ElementAccess{expression: Box::new(Var("pair").into()), index: 0}.into()
+            ),
+            (match_item("Some", None, Some(match_item("Nil", None, None))), Integer{value: 0}.into()),
+            (match_item("None", None, None), Integer{value: -1}.into()),
+        ],
+        Ok(vec![(1, Vec::new()), (0, vec![0, 1])]);
+        "two level pattern"
+    )]
+    #[test_case(
+        vec![
+            (
+                match_item("Some", Some("xs"), Some(match_item("Cons", Some("pair"), None))),
+                ElementAccess{expression: Box::new(Var("pair").into()), index: 0}.into()
+            ),
+            (match_item("Some", Some("xs"), Some(match_item("_", None, None))), Integer{value: 0}.into()),
+            (match_item("None", None, None), Integer{value: -1}.into()),
+        ],
+        Ok(vec![(1, Vec::new()), (0, vec![0, 1])]);
+        "two level pattern with inner wildcard"
+    )]
+    #[test_case(
+        vec![
+            (
+                match_item("Some", None, Some(match_item("Cons", Some("pair"), None))),
+                ElementAccess{expression: Box::new(Var("pair").into()), index: 0}.into()
+            ),
+            (match_item("None", None, None), Integer{value: -1}.into()),
+        ],
+        Err(TypeCheckError::NonExhaustiveMatch {
+            type_name: Id::from("List"),
+            missing: vec![Id::from("Nil")]
+        });
+        "non exhaustive inner pattern"
+    )]
+    #[test_case(
+        vec![
+            (
+                match_item("Some", None, Some(match_item("Cons", Some("pair"), None))),
+                ElementAccess{expression: Box::new(Var("pair").into()), index: 0}.into()
+            ),
+            (match_item("Some", None, None), Integer{value: 0}.into()),
+            (match_item("None", None, None), Integer{value: -1}.into()),
+        ],
+        Err(TypeCheckError::RedundantMatchArm {
+            type_name: Id::from("Option"),
+            variant: Id::from("Some")
+        });
+        "nested and plain arm"
+    )]
+    #[test_case(
+        vec![
+            (
+                match_item("Some", None, Some(match_item("Cons", Some("pair"), None))),
+                ElementAccess{expression: Box::new(Var("pair").into()), index: 0}.into()
+            ),
+            (match_item("Some", None, Some(match_item("Cons", Some("pair"), None))), Integer{value: 0}.into()),
+            (match_item("Some", None, Some(match_item("Nil", None, None))), Integer{value: 0}.into()),
+            (match_item("None", None, None), Integer{value: -1}.into()),
+        ],
+        Err(TypeCheckError::RedundantMatchArm {
+            type_name: Id::from("List"),
+            variant: Id::from("Cons")
+        });
+        "repeated inner pattern"
+    )]
+    fn test_nested_match_patterns(
+        arms: Vec<(MatchItem, Expression)>,
+        expected: Result<Vec<(usize, Vec<usize>)>, TypeCheckError>,
+    ) {
+        let type_checker = TypeChecker::new(
+            TYPE_DEFINITIONS.with(|definitions| TypeDefinitions::from(definitions.0.clone())),
+            TYPE_CONSTRUCTORS.clone(),
+        );
+        // `x: Option<List<int>>`
+        let context = TypeContext::from([(
+            Id::from("x"),
+            Type::from(TypeInstantiation {
+                reference: TYPE_DEFINITIONS
+                    .with(|definitions| definitions[&String::from("Option")].clone()),
+                instances: vec![TypeInstantiation {
+                    reference: TYPE_DEFINITIONS
+                        .with(|definitions| definitions[&String::from("List")].clone()),
+                    instances: vec![TYPE_INT],
+                }
+                .into()],
+            })
+            .into(),
+        )]);
+        let match_expression = MatchExpression {
+            subject: Box::new(Var("x").into()),
+            blocks: arms
+                .into_iter()
+                .map(|(item, expression)| MatchBlock {
+                    matches: vec![item],
+                    block: ExpressionBlock(expression),
+                })
+                .collect(),
+        };
+        let result = type_checker.check_match_expression(
+            match_expression,
+            &context,
+            &GenericVariables::new(),
+        );
+        let indices = result.map(|TypedMatch { subject: _, blocks }| {
+            blocks
+                .into_iter()
+                .map(|block| {
+                    let inner = match *block.block.expression {
+                        TypedExpression::TypedMatch(TypedMatch { blocks, .. }) => blocks
+                            .iter()
+                            .flat_map(|block| block.matches.iter().map(|item| item.type_idx))
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    (block.matches[0].type_idx, inner)
+                })
+                .collect_vec()
+        });
+        assert_eq!(indices, expected);
+    }
+
+    #[test_case(
+        ExpressionBlock(Boolean{value: true}.into()),
+        Some(TYPE_BOOL),
         TypeContext::new();
-        "mixed opaque types"
+        "block no assignments"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("x").into(),
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(Integer {value: -12}.into())
-                },
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: true}.into()),
+                    doc: None,
+                }
             ],
-            expression: Box::new(
-                GenericVariable {
-                    id: Id::from("x"),
-                    type_instances: vec![
-                        ATOMIC_TYPE_INT.into()
-                    ]
-                }.into()
-            )
+            expression: Box::new(Boolean{value: true}.into())
         },
-        Some(TYPE_INT),
+        Some(TYPE_BOOL),
         TypeContext::new();
-        "generic instantiation"
+        "block unused assignment"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("x").into(),
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(Integer {value: -12}.into())
-                },
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: true}.into()),
+                    doc: None,
+                }
             ],
-            expression: Box::new(
-                GenericVariable {
-                    id: Id::from("x"),
-                    type_instances: vec![
-                        ATOMIC_TYPE_INT.into(),
-                        ATOMIC_TYPE_BOOL.into()
-                    ]
-                }.into()
-            )
+            expression: Box::new(Var("x").into())
         },
-        None,
+        Some(TYPE_BOOL),
         TypeContext::new();
-        "extra variable generic instantiation"
+        "block used assignment"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("id").into(),
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Id::from("x").into(),
-                                type_: Typename("T").into(),
-                            }
-                        ],
-                        return_type: Typename("T").into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("y"),
+                    expression: Box::new(Var("x").into()),
+                    doc: None,
                 },
             ],
-            expression: Box::new(
-                FunctionCall {
-                    function: Box::new(GenericVariable{
-                        id: Id::from("id"),
-                        type_instances: vec![ATOMIC_TYPE_INT.into()]
-                    }.into()),
-                    arguments: vec![Integer{value: 5}.into()]
-                }.into()
-            )
+            expression: Box::new(Var("y").into())
         },
         Some(TYPE_INT),
         TypeContext::new();
-        "generic function instantiation"
+        "block multiple assignments"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("id").into(),
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Id::from("x").into(),
-                                type_: Typename("T").into(),
-                            }
-                        ],
-                        return_type: Typename("T").into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
                 },
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("id_").into(),
-                        generic_variables: vec![Id::from("U")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Id::from("x").into(),
-                                type_: Typename("U").into(),
-                            }
-                        ],
-                        return_type: Typename("U").into(),
-                        body: ExpressionBlock(FunctionCall {
-                            function: Box::new(GenericVariable{
-                                id: Id::from("id"),
-                                type_instances: vec![Typename("U").into()]
-                            }.into()),
-                            arguments: vec![Var("x").into()]
-                        }.into())
-                    }.into())
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: true}.into()),
+                    doc: None,
                 },
             ],
-            expression: Box::new(
-                FunctionCall {
-                    function: Box::new(GenericVariable{
-                        id: Id::from("id_"),
-                        type_instances: vec![ATOMIC_TYPE_INT.into()]
-                    }.into()),
-                    arguments: vec![Integer{value: 5}.into()]
-                }.into()
-            )
+            expression: Box::new(Var("x").into())
         },
-        Some(TYPE_INT),
+        Some(TYPE_BOOL),
         TypeContext::new();
-        "used generic function"
+        "block duplicate assignments"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("id").into(),
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Id::from("x").into(),
-                                type_: Typename("T").into(),
-                            }
-                        ],
-                        return_type: Typename("T").into(),
-                        body: Block{
-                            assignments: vec![
-                                Assignment {
-                                    assignee: ParametricAssignee {
-                                        assignee: Id::from("hold").into(),
-                                        generic_variables: vec![Id::from("U")]
-                                    },
-                                    expression: Box::new(FunctionDefinition{
-                                        parameters: vec![
-                                            TypedAssignee {
-                                                assignee: Id::from("y").into(),
-                                                type_: Typename("U").into(),
-                                            }
-                                        ],
-                                        return_type: Typename("T").into(),
-                                        body: ExpressionBlock(Var("x").into())
-                                    }.into())
-                                },
-                            ],
-                            expression: Box::new(FunctionCall {
-                                function: Box::new(GenericVariable{
-                                    id: Id::from("hold"),
-                                    type_instances: vec![ATOMIC_TYPE_BOOL.into()]
-                                }.into()),
-                                arguments: vec![
-                                    Boolean{value: false}.into()
-                                ]
-                            }.into())
-                        }
-                    }.into())
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: 3}.into())
+                    }.into()),
+                    doc: None,
                 },
-            ],
-            expression: Box::new(
-                FunctionCall {
-                    function: Box::new(GenericVariable{
-                        id: Id::from("id"),
-                        type_instances: vec![ATOMIC_TYPE_INT.into()]
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: -3}.into())
                     }.into()),
-                    arguments: vec![Integer{value: 5}.into()]
-                }.into()
-            )
+                    doc: None,
+                },
+            ],
+            expression: Box::new(Var("f").into())
         },
-        Some(TYPE_INT),
+        None,
         TypeContext::new();
-        "nested generic function instantiation"
+        "block duplicate fn assignments"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("id").into(),
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Id::from("x").into(),
-                                type_: Typename("T").into(),
-                            }
-                        ],
-                        return_type: Typename("T").into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: 3}.into())
+                    }.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(Integer{value: -3}.into()),
+                    doc: None,
                 },
             ],
-            expression: Box::new(
-                FunctionCall {
-                    function: Box::new(Var("&").into()),
-                    arguments: vec![
-                        FunctionCall {
-                            function: Box::new(GenericVariable{
-                                id: Id::from("id"),
-                                type_instances: vec![ATOMIC_TYPE_INT.into()]
-                            }.into()),
-                            arguments: vec![Integer{value: 5}.into()]
-                        }.into(),
-                        FunctionCall {
-                            function: Box::new(GenericVariable{
-                                id: Id::from("id"),
-                                type_instances: vec![ATOMIC_TYPE_BOOL.into()]
-                            }.into()),
-                            arguments: vec![Boolean{value: false}.into()]
-                        }.into()
-                    ]
-                }.into()
-            )
+            expression: Box::new(Var("f").into())
         },
-        Some(TYPE_INT),
-        TypeContext::from([(
-            Id::from("&"),
-            Type::from(TypeFn(vec![TYPE_INT, TYPE_BOOL], Box::new(TYPE_INT))).into()
-        )]);
-        "reused generic function"
+        None,
+        TypeContext::new();
+        "block variable and fn assignments"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("apply").into(),
-                        generic_variables: vec![Id::from("T"), Id::from("U")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Id::from("f").into(),
-                                type_: FunctionType{
-                                    argument_types: vec![Typename("T").into()],
-                                    return_type: Box::new(Typename("U").into())
-                                }.into(),
-                            },
-                            TypedAssignee {
-                                assignee: Id::from("x").into(),
-                                type_: Typename("T").into(),
-                            }
-                        ],
-                        return_type: Typename("U").into(),
-                        body: ExpressionBlock(FunctionCall {
-                            function: Box::new(Var("f").into()),
-                            arguments: vec![Var("x").into()]
-                        }.into())
-                    }.into())
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
                 },
             ],
-            expression: Box::new(
-                GenericVariable{
-                    id: Id::from("apply"),
-                    type_instances: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
-                }.into()
-            )
+            expression: Box::new(Var("f").into())
         },
-        Some(Type::from(TypeFn(
-            vec![
-                Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_BOOL))),
-                TYPE_INT
-            ],
-            Box::new(TYPE_BOOL)
-        ))),
+        Some(TypeFn(
+            Vec::new(),
+            Box::new(TYPE_INT.into())
+        ).into()),
         TypeContext::new();
-        "compound generic function"
+        "fn use closure"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("extra").into(),
-                        generic_variables: vec![Id::from("T"), Id::from("U")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Id::from("x").into(),
-                                type_: Typename("T").into(),
-                            }
-                        ],
-                        return_type: Typename("T").into(),
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
                         body: ExpressionBlock(Var("x").into())
-                    }.into())
+                    }.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
                 },
             ],
-            expression: Box::new(
-                GenericVariable{
-                    id: Id::from("extra"),
-                    type_instances: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
-                }.into()
-            )
+            expression: Box::new(Var("f").into())
         },
-        Some(Type::from(TypeFn(
-            vec![
-                TYPE_INT
-            ],
-            Box::new(TYPE_INT)
-        ))),
+        None,
         TypeContext::new();
-        "dual generic function"
+        "fn invalid closure"
     )]
     #[test_case(
         Block {
             assignments: vec![
-                Assignment {
-                    assignee: ParametricAssignee {
-                        assignee: Id::from("first").into(),
-                        generic_variables: vec![Id::from("T"), Id::from("U")]
-                    },
-                    expression: Box::new(FunctionDefinition{
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition {
                         parameters: vec![
-                            TypedAssignee {
+                            TypedAssignee{
                                 assignee: Id::from("x").into(),
-                                type_: TupleType{
-                                    types: vec![Typename("T").into(), Typename("U").into()],
-                                }.into()
+                                type_: ATOMIC_TYPE_BOOL.into()
                             },
                         ],
-                        return_type: Typename("T").into(),
-                        body: ExpressionBlock(ElementAccess {
-                            expression: Box::new(Var("x").into()),
-                            index: 0
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(Var("f").into())
+        },
+        Some(TypeFn(
+            vec![TYPE_BOOL.into()],
+            Box::new(TYPE_BOOL.into())
+        ).into()),
+        TypeContext::new();
+        "fn shadowed closure"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("foo"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("bar").into()),
+                            arguments: Vec::new()
+                        }.into())
+                    }.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("bar"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("foo").into()),
+                            arguments: Vec::new()
                         }.into())
-                    }.into())
+                    }.into()),
+                    doc: None,
                 },
             ],
-            expression: Box::new(
-                GenericVariable{
-                    id: Id::from("first"),
-                    type_instances: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
-                }.into()
-            )
+            expression: Box::new(Var("foo").into())
         },
-        Some(Type::from(TypeFn(
-            vec![
-                Type::from(TypeTuple(vec![TYPE_INT, TYPE_BOOL])),
+        Some(TypeFn(
+            Vec::new(),
+            Box::new(TYPE_BOOL.into())
+        ).into()),
+        TypeContext::new();
+        "mutually recursive fns"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("foo"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(Boolean{value:true}.into())
+                    }.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(FunctionCall{
+                        function: Box::new(Var("foo").into()),
+                        arguments: Vec::new()
+                    }.into()),
+                    doc: None,
+                },
             ],
-            Box::new(TYPE_INT)
-        ))),
+            expression: Box::new(Var("x").into())
+        },
+        Some(TYPE_BOOL.into()),
         TypeContext::new();
-        "tuple generic function"
+        "user defined fn call"
     )]
-    fn test_check_blocks(block: Block, expected_type: Option<Type>, context: TypeContext) {
-        let type_checker = TypeChecker {
-            type_definitions: TYPE_DEFINITIONS
-                .with(|definitions| TypeDefinitions::from(definitions.0.clone())),
-            constructors: TYPE_CONSTRUCTORS.clone(),
-        };
-        let type_check_result = type_checker.check_block(block, context, GenericVariables::new());
-        match expected_type {
-            Some(type_) => match &type_check_result {
-                Ok(typed_block) => {
-                    dbg!(&typed_block);
-                    dbg!(typed_block.type_());
-                    dbg!(&type_);
-                    assert!(Type::equality(&typed_block.type_(), &type_))
-                }
-                Err(msg) => {
-                    dbg!(msg);
-                    assert!(&type_check_result.is_ok());
-                }
-            },
-            None => {
-                if type_check_result.is_ok() {
-                    dbg!(&type_check_result);
-                }
-                assert!(&type_check_result.is_err());
-            }
-        }
-    }
-
-    #[test]
-    fn test_valid_constructor_list() {
-        let type_definitions = vec![
-            UnionTypeDefinition {
-                variable: GenericTypeVariable {
-                    id: Id::from("Tree"),
-                    generic_variables: vec![Id::from("T")],
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(FunctionCall{
+                        function: Box::new(Var("foo").into()),
+                        arguments: Vec::new()
+                    }.into()),
+                    doc: None,
                 },
-                items: vec![
-                    TypeItem {
-                        id: Id::from("Node"),
-                        type_: Some(
-                            TupleType {
-                                types: vec![
-                                    Typename("T").into(),
-                                    GenericType {
-                                        id: Id::from("Tree"),
-                                        type_variables: vec![Typename("T").into()],
-                                    }
-                                    .into(),
-                                    Typename("T").into(),
-                                ],
-                            }
-                            .into(),
-                        ),
-                    },
-                    TypeItem {
-                        id: Id::from("Leaf"),
-                        type_: None,
-                    },
-                ],
-            }
-            .into(),
-            UnionTypeDefinition {
-                variable: GenericTypeVariable {
-                    id: Id::from("Empty"),
-                    generic_variables: Vec::new(),
+                Assignment{
+                    assignee: VariableAssignee("foo"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(Boolean{value:true}.into())
+                    }.into()),
+                    doc: None,
                 },
-                items: Vec::new(),
-            }
-            .into(),
-            OpaqueTypeDefinition {
-                variable: GenericTypeVariable {
-                    id: Id::from("opaque_int"),
-                    generic_variables: Vec::new(),
+            ],
+            expression: Box::new(Var("x").into())
+        },
+        None,
+        TypeContext::new();
+        "call before definition"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition {
+                        parameters: vec![
+                            TypedAssignee{
+                                assignee: Id::from("x").into(),
+                                type_: ATOMIC_TYPE_BOOL.into()
+                            },
+                        ],
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: Block{
+                            assignments: vec![
+                                Assignment{
+                                    assignee: VariableAssignee("x"),
+                                    expression: Box::new(Integer{value: 3}.into()),
+                                    doc: None,
+                                },
+                            ],
+                            expression: Box::new(Var("x").into())
+                        }
+                    }.into()),
+                    doc: None,
                 },
-                type_: ATOMIC_TYPE_INT.into(),
-            }
-            .into(),
-            OpaqueTypeDefinition {
-                variable: GenericTypeVariable {
-                    id: Id::from("opaque_opaque_int"),
-                    generic_variables: Vec::new(),
+            ],
+            expression: Box::new(Var("f").into())
+        },
+        Some(TypeFn(
+            vec![TYPE_BOOL.into()],
+            Box::new(TYPE_INT.into())
+        ).into()),
+        TypeContext::new();
+        "fn shadowed arg"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("y"),
+                    expression: Box::new(Var("x").into()),
+                    doc: None,
                 },
-                type_: Typename("opaque_int").into(),
-            }
-            .into(),
-            OpaqueTypeDefinition {
-                variable: GenericTypeVariable {
-                    id: Id::from("int_tree"),
-                    generic_variables: Vec::new(),
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
                 },
-                type_: GenericType {
-                    id: Id::from("Tree"),
-                    type_variables: vec![ATOMIC_TYPE_INT.into()],
-                }
-                .into(),
-            }
-            .into(),
+            ],
+            expression: Box::new(Var("y").into())
+        },
+        None,
+        TypeContext::new();
+        "block flipped assignments"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: ATOMIC_TYPE_INT.into()
+                },
+                TypedAssignee{
+                    assignee: Id::from("y").into(),
+                    type_: ATOMIC_TYPE_BOOL.into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(Var("z").into())
+        }.into()),
+        None,
+        TypeContext::new();
+        "function invalid block"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: ATOMIC_TYPE_INT.into()
+                },
+                TypedAssignee{
+                    assignee: Id::from("y").into(),
+                    type_: ATOMIC_TYPE_BOOL.into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(Var("y").into())
+        }.into()),
+        None,
+        TypeContext::new();
+        "function incorrect return type"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: ATOMIC_TYPE_INT.into()
+                },
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: ATOMIC_TYPE_BOOL.into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(Integer{value: 5}.into())
+        }.into()),
+        None,
+        TypeContext::new();
+        "function duplicate parameter"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee{
+                        assignee: Assignee{
+                            id: Id::from("g")
+                        },
+                        generic_variables: vec![Id::from("T"), Id::from("U")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: vec![
+                                TypedAssignee{
+                                    assignee: Id::from("f").into(),
+                                    type_: FunctionType{
+                                        argument_types: vec![
+                                            GenericType{
+                                                id: Id::from("T"),
+                                                type_variables: Vec::new()
+                                            }.into()
+                                        ],
+                                        return_type: Box::new(GenericType{
+                                            id: Id::from("U"),
+                                            type_variables: Vec::new()
+                                        }.into())
+                                    }.into(),
+                                },
+                                TypedAssignee{
+                                    assignee: Id::from("x").into(),
+                                    type_: GenericType{
+                                        id: Id::from("T"),
+                                        type_variables: Vec::new()
+                                    }.into()
+                                },
+                            ],
+                            return_type: GenericType{
+                                id: Id::from("U"),
+                                type_variables: Vec::new()
+                            }.into(),
+                            body: ExpressionBlock(FunctionCall{
+                                function: Box::new(Var("f").into()),
+                                arguments: vec![Var("x").into()]
+                            }.into())
+                        }.into()
+                    ),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(GenericVariable{
+                id: Id::from("g"),
+                type_instances: vec![
+                    ATOMIC_TYPE_INT.into(),
+                    ATOMIC_TYPE_BOOL.into()
+                ]
+            }.into())
+        },
+        Some(Type::from(TypeFn(
+            vec![
+                Type::from(TypeFn(vec![TYPE_INT],Box::new(TYPE_BOOL))),
+                TYPE_INT
+            ],
+            Box::new(TYPE_BOOL),
+        ))),
+        TypeContext::new();
+        "function matched generics"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee{
+                        assignee: Assignee{
+                            id: Id::from("g")
+                        },
+                        generic_variables: vec![Id::from("T"), Id::from("U")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: vec![
+                                TypedAssignee{
+                                    assignee: Id::from("f").into(),
+                                    type_: FunctionType{
+                                        argument_types: vec![
+                                            GenericType{
+                                                id: Id::from("T"),
+                                                type_variables: Vec::new()
+                                            }.into()
+                                        ],
+                                        return_type: Box::new(GenericType{
+                                            id: Id::from("U"),
+                                            type_variables: Vec::new()
+                                        }.into())
+                                    }.into(),
+                                },
+                                TypedAssignee{
+                                    assignee: Id::from("x").into(),
+                                    type_: GenericType{
+                                        id: Id::from("T"),
+                                        type_variables: Vec::new()
+                                    }.into()
+                                },
+                            ],
+                            return_type: GenericType{
+                                id: Id::from("U"),
+                                type_variables: Vec::new()
+                            }.into(),
+                            body: ExpressionBlock(Var("x").into())
+                        }.into()
+                    ),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(GenericVariable{
+                id: Id::from("g"),
+                type_instances: vec![
+                    ATOMIC_TYPE_INT.into(),
+                    ATOMIC_TYPE_BOOL.into()
+                ]
+            }.into())
+        },
+        None,
+        TypeContext::new();
+        "function mismatched generics"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: Typename("opaque_int").into()
+                },
+            ],
+            return_type: Typename("opaque_int").into(),
+            body: ExpressionBlock(Var("x").into()),
+        }.into()),
+        Some(Type::from(TypeFn(
+            vec![Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("opaque_int")].clone()), instances: Vec::new()})],
+            Box::new(Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("opaque_int")].clone()), instances: Vec::new()}))
+        ))),
+        TypeContext::new();
+        "opaque type reference"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: Typename("transparent_int").into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(Var("x").into()),
+        }.into()),
+        Some(Type::from(TypeFn(
+            vec![Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("transparent_int")].clone()), instances: Vec::new()})],
+            Box::new(TYPE_INT)
+        ))),
+        TypeContext::new();
+        "transparent type reference"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: Typename("transparent_int").into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(Var("x").into()),
+        }.into()),
+        Some(Type::from(TypeFn(
+            vec![Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("transparent_int")].clone()), instances: Vec::new()})],
+            Box::new(Type::from(TypeInstantiation{reference: TYPE_DEFINITIONS.with(|definitions| definitions[&Id::from("transparent_int_2")].clone()), instances: Vec::new()}))
+        ))),
+        TypeContext::new();
+        "double transparent type reference"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: Typename("ii").into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(ElementAccess{
+                expression: Box::new(Var("x").into()),
+                index: 0
+            }.into()),
+        }.into()),
+        Some(Type::from(TypeFn(
+            vec![Type::from(TypeTuple(vec![TYPE_INT, TYPE_INT]))],
+            Box::new(TYPE_INT)
+        ))),
+        TypeContext::new();
+        "transparent type usage"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition {
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: Typename("recursive").into()
+                },
+            ],
+            return_type: Typename("recursive").into(),
+            body: ExpressionBlock(ElementAccess{
+                expression: Box::new(Var("x").into()),
+                index: 0
+            }.into()),
+        }.into()),
+        None,
+        TypeContext::new();
+        "invalid recursive type usage"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition{
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: ATOMIC_TYPE_INT.into()
+                },
+                TypedAssignee{
+                    assignee: Id::from("y").into(),
+                    type_: ATOMIC_TYPE_INT.into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(FunctionCall {
+                function: Box::new(Var("+").into()),
+                arguments: vec![
+                    Var("x").into(),
+                    Var("y").into(),
+                ],
+            }.into())
+        }.into()),
+        Some(Type::from(TypeFn(
+            vec![TYPE_INT, TYPE_INT],
+            Box::new(TYPE_INT)
+        ))),
+        TypeContext::from([(
+            Id::from("+"),
+            Type::from(TypeFn(
+                vec![TYPE_INT, TYPE_INT],
+                Box::new(TYPE_INT)
+            )).into()
+        )]);
+        "add function definition"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition{
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: ATOMIC_TYPE_INT.into()
+                },
+                TypedAssignee{
+                    assignee: Id::from("y").into(),
+                    type_: ATOMIC_TYPE_BOOL.into()
+                },
+            ],
+            return_type: ATOMIC_TYPE_INT.into(),
+            body: ExpressionBlock(FunctionCall {
+                function: Box::new(Var("+").into()),
+                arguments: vec![
+                    Var("x").into(),
+                    Var("y").into(),
+                ],
+            }.into())
+        }.into()),
+        None,
+        TypeContext::from([(
+            Id::from("+"),
+            Type::from(TypeFn(
+                vec![TYPE_INT, TYPE_INT],
+                Box::new(TYPE_INT)
+            )).into()
+        )]);
+        "add invalid function definition"
+    )]
+    #[test_case(
+        ExpressionBlock(FunctionDefinition{
+            parameters: vec![
+                TypedAssignee{
+                    assignee: Id::from("x").into(),
+                    type_: Typename("opaque_int").into()
+                },
+            ],
+            return_type: Typename("opaque_int_2").into(),
+            body: ExpressionBlock(Var("x").into())
+        }.into()),
+        None,
+        TypeContext::new();
+        "mixed opaque types"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("x").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(Integer {value: -12}.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                GenericVariable {
+                    id: Id::from("x"),
+                    type_instances: vec![
+                        ATOMIC_TYPE_INT.into()
+                    ]
+                }.into()
+            )
+        },
+        Some(TYPE_INT),
+        TypeContext::new();
+        "generic instantiation"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("x").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(Integer {value: -12}.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                GenericVariable {
+                    id: Id::from("x"),
+                    type_instances: vec![
+                        ATOMIC_TYPE_INT.into(),
+                        ATOMIC_TYPE_BOOL.into()
+                    ]
+                }.into()
+            )
+        },
+        None,
+        TypeContext::new();
+        "extra variable generic instantiation"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("id").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: Typename("T").into(),
+                            }
+                        ],
+                        return_type: Typename("T").into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                FunctionCall {
+                    function: Box::new(GenericVariable{
+                        id: Id::from("id"),
+                        type_instances: vec![ATOMIC_TYPE_INT.into()]
+                    }.into()),
+                    arguments: vec![Integer{value: 5}.into()]
+                }.into()
+            )
+        },
+        Some(TYPE_INT),
+        TypeContext::new();
+        "generic function instantiation"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("id").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: Typename("T").into(),
+                            }
+                        ],
+                        return_type: Typename("T").into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                },
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("id_").into(),
+                        generic_variables: vec![Id::from("U")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: Typename("U").into(),
+                            }
+                        ],
+                        return_type: Typename("U").into(),
+                        body: ExpressionBlock(FunctionCall {
+                            function: Box::new(GenericVariable{
+                                id: Id::from("id"),
+                                type_instances: vec![Typename("U").into()]
+                            }.into()),
+                            arguments: vec![Var("x").into()]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                FunctionCall {
+                    function: Box::new(GenericVariable{
+                        id: Id::from("id_"),
+                        type_instances: vec![ATOMIC_TYPE_INT.into()]
+                    }.into()),
+                    arguments: vec![Integer{value: 5}.into()]
+                }.into()
+            )
+        },
+        Some(TYPE_INT),
+        TypeContext::new();
+        "used generic function"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("id").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: Typename("T").into(),
+                            }
+                        ],
+                        return_type: Typename("T").into(),
+                        body: Block{
+                            assignments: vec![
+                                Assignment {
+                                    assignee: ParametricAssignee {
+                                        assignee: Id::from("hold").into(),
+                                        generic_variables: vec![Id::from("U")],
+                                        bounds: BTreeMap::new(),
+                                    },
+                                    expression: Box::new(FunctionDefinition{
+                                        parameters: vec![
+                                            TypedAssignee {
+                                                assignee: Id::from("y").into(),
+                                                type_: Typename("U").into(),
+                                            }
+                                        ],
+                                        return_type: Typename("T").into(),
+                                        body: ExpressionBlock(Var("x").into())
+                                    }.into()),
+                                    doc: None,
+                                },
+                            ],
+                            expression: Box::new(FunctionCall {
+                                function: Box::new(GenericVariable{
+                                    id: Id::from("hold"),
+                                    type_instances: vec![ATOMIC_TYPE_BOOL.into()]
+                                }.into()),
+                                arguments: vec![
+                                    Boolean{value: false}.into()
+                                ]
+                            }.into())
+                        }
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                FunctionCall {
+                    function: Box::new(GenericVariable{
+                        id: Id::from("id"),
+                        type_instances: vec![ATOMIC_TYPE_INT.into()]
+                    }.into()),
+                    arguments: vec![Integer{value: 5}.into()]
+                }.into()
+            )
+        },
+        Some(TYPE_INT),
+        TypeContext::new();
+        "nested generic function instantiation"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("id").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: Typename("T").into(),
+                            }
+                        ],
+                        return_type: Typename("T").into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                FunctionCall {
+                    function: Box::new(Var("&").into()),
+                    arguments: vec![
+                        FunctionCall {
+                            function: Box::new(GenericVariable{
+                                id: Id::from("id"),
+                                type_instances: vec![ATOMIC_TYPE_INT.into()]
+                            }.into()),
+                            arguments: vec![Integer{value: 5}.into()]
+                        }.into(),
+                        FunctionCall {
+                            function: Box::new(GenericVariable{
+                                id: Id::from("id"),
+                                type_instances: vec![ATOMIC_TYPE_BOOL.into()]
+                            }.into()),
+                            arguments: vec![Boolean{value: false}.into()]
+                        }.into()
+                    ]
+                }.into()
+            )
+        },
+        Some(TYPE_INT),
+        TypeContext::from([(
+            Id::from("&"),
+            Type::from(TypeFn(vec![TYPE_INT, TYPE_BOOL], Box::new(TYPE_INT))).into()
+        )]);
+        "reused generic function"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("apply").into(),
+                        generic_variables: vec![Id::from("T"), Id::from("U")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("f").into(),
+                                type_: FunctionType{
+                                    argument_types: vec![Typename("T").into()],
+                                    return_type: Box::new(Typename("U").into())
+                                }.into(),
+                            },
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: Typename("T").into(),
+                            }
+                        ],
+                        return_type: Typename("U").into(),
+                        body: ExpressionBlock(FunctionCall {
+                            function: Box::new(Var("f").into()),
+                            arguments: vec![Var("x").into()]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                GenericVariable{
+                    id: Id::from("apply"),
+                    type_instances: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
+                }.into()
+            )
+        },
+        Some(Type::from(TypeFn(
+            vec![
+                Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_BOOL))),
+                TYPE_INT
+            ],
+            Box::new(TYPE_BOOL)
+        ))),
+        TypeContext::new();
+        "compound generic function"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("extra").into(),
+                        generic_variables: vec![Id::from("T"), Id::from("U")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: Typename("T").into(),
+                            }
+                        ],
+                        return_type: Typename("T").into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                GenericVariable{
+                    id: Id::from("extra"),
+                    type_instances: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
+                }.into()
+            )
+        },
+        Some(Type::from(TypeFn(
+            vec![
+                TYPE_INT
+            ],
+            Box::new(TYPE_INT)
+        ))),
+        TypeContext::new();
+        "dual generic function"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("first").into(),
+                        generic_variables: vec![Id::from("T"), Id::from("U")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: TupleType{
+                                    types: vec![Typename("T").into(), Typename("U").into()],
+                                }.into()
+                            },
+                        ],
+                        return_type: Typename("T").into(),
+                        body: ExpressionBlock(ElementAccess {
+                            expression: Box::new(Var("x").into()),
+                            index: 0
+                        }.into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(
+                GenericVariable{
+                    id: Id::from("first"),
+                    type_instances: vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_BOOL.into()]
+                }.into()
+            )
+        },
+        Some(Type::from(TypeFn(
+            vec![
+                Type::from(TypeTuple(vec![TYPE_INT, TYPE_BOOL])),
+            ],
+            Box::new(TYPE_INT)
+        ))),
+        TypeContext::new();
+        "tuple generic function"
+    )]
+    fn test_check_blocks(block: Block, expected_type: Option<Type>, context: TypeContext) {
+        let type_checker = TypeChecker::new(
+            TYPE_DEFINITIONS.with(|definitions| TypeDefinitions::from(definitions.0.clone())),
+            TYPE_CONSTRUCTORS.clone(),
+        );
+        let type_check_result = type_checker.check_block(block, context, GenericVariables::new());
+        match expected_type {
+            Some(type_) => match &type_check_result {
+                Ok(typed_block) => {
+                    dbg!(&typed_block);
+                    dbg!(typed_block.type_());
+                    dbg!(&type_);
+                    assert!(Type::equality(&typed_block.type_(), &type_))
+                }
+                Err(msg) => {
+                    dbg!(msg);
+                    assert!(&type_check_result.is_ok());
+                }
+            },
+            None => {
+                if type_check_result.is_ok() {
+                    dbg!(&type_check_result);
+                }
+                assert!(&type_check_result.is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_function_mismatched_generics() {
+        let type_checker = TypeChecker::check_type_definitions(Vec::new()).unwrap();
+        let generic = |id: &str| {
+            TypeInstance::from(GenericType {
+                id: Id::from(id),
+                type_variables: Vec::new(),
+            })
+        };
+        // g<T, U> = (f: T -> U, x: T) -> U { x }
+        let block = Block {
+            assignments: vec![Assignment {
+                assignee: ParametricAssignee {
+                    assignee: Assignee { id: Id::from("g") },
+                    generic_variables: vec![Id::from("T"), Id::from("U")],
+                    bounds: BTreeMap::new(),
+                },
+                expression: Box::new(
+                    FunctionDefinition {
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Id::from("f").into(),
+                                type_: FunctionType {
+                                    argument_types: vec![generic("T")],
+                                    return_type: Box::new(generic("U")),
+                                }
+                                .into(),
+                            },
+                            TypedAssignee {
+                                assignee: Id::from("x").into(),
+                                type_: generic("T"),
+                            },
+                        ],
+                        return_type: generic("U"),
+                        body: ExpressionBlock(Var("x").into()),
+                    }
+                    .into(),
+                ),
+                doc: None,
+            }],
+            expression: Box::new(Var("g").into()),
+        };
+        let result = type_checker.check_block(block, TypeContext::new(), GenericVariables::new());
+        let Err(TypeCheckError::FunctionReturnTypeMismatch {
+            return_type: _,
+            body: _,
+            generic_variables,
+        }) = result
+        else {
+            panic!("Expected return type mismatch, got {:?}.", result)
+        };
+        assert_eq!(generic_variables, vec![Id::from("T"), Id::from("U")]);
+    }
+
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: true}.into()),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(Boolean{value: true}.into())
+        },
+        Err(TypeCheckError::UnusedBinding { name: Id::from("x") });
+        "unused assignment"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("_x"),
+                    expression: Box::new(FunctionCall{
+                        function: Box::new(FunctionDefinition{
+                            parameters: Vec::new(),
+                            return_type: ATOMIC_TYPE_INT.into(),
+                            body: ExpressionBlock(Integer{value: 0}.into())
+                        }.into()),
+                        arguments: Vec::new()
+                    }.into()),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(Boolean{value: true}.into())
+        },
+        Ok(());
+        "underscore assignment"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: true}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("y"),
+                    expression: Box::new(Var("x").into()),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(Var("y").into())
+        },
+        Ok(());
+        "assignments used"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: true}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: false}.into()),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(Var("x").into())
+        },
+        Err(TypeCheckError::UnusedBinding { name: Id::from("x") });
+        "shadowed assignment"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("f").into()),
+                            arguments: Vec::new()
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(Integer{value: 0}.into())
+        },
+        Err(TypeCheckError::UnusedBinding { name: Id::from("f") });
+        "function only used recursively"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 0}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                }
+            ],
+            expression: Box::new(Var("f").into())
+        },
+        Ok(());
+        "assignment used in function body"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 0}.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(FunctionDefinition{
+                parameters: Vec::new(),
+                return_type: ATOMIC_TYPE_INT.into(),
+                body: Block {
+                    assignments: vec![
+                        Assignment{
+                            assignee: VariableAssignee("y"),
+                            expression: Box::new(Integer{value: 1}.into()),
+                            doc: None,
+                        },
+                    ],
+                    expression: Box::new(Var("x").into())
+                }
+            }.into())
+        },
+        Err(TypeCheckError::UnusedBinding { name: Id::from("y") });
+        "unused in nested block"
+    )]
+    fn test_unused_bindings(block: Block, expected: Result<(), TypeCheckError>) {
+        let type_checker = TypeChecker {
+            deny_unused_bindings: true,
+            ..TypeChecker::new(TypeDefinitions::new(), HashMap::new())
+        };
+        let result =
+            type_checker.check_block(block.clone(), TypeContext::new(), GenericVariables::new());
+        assert_eq!(result.map(|_| ()), expected);
+        // Unused bindings are allowed by default.
+        let type_checker = TypeChecker {
+            deny_unused_bindings: false,
+            ..type_checker
+        };
+        let result = type_checker.check_block(block, TypeContext::new(), GenericVariables::new());
+        assert!(result.is_ok());
+    }
+
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Boolean{value: true}.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(Var("x").into())
+        },
+        Ok(()),
+        Err(TypeCheckError::DuplicatedName {
+            duplicate: Id::from("x"),
+            reason: String::from("variable names")
+        });
+        "duplicate assignments"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Integer{value: 3}.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("y"),
+                    expression: Box::new(Var("x").into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(Var("y").into())
+        },
+        Ok(()),
+        Ok(());
+        "distinct assignments"
+    )]
+    #[test_case(
+        Block {
+            assignments: vec![
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: 0}.into())
+                    }.into()),
+                    doc: None,
+                },
+                Assignment{
+                    assignee: VariableAssignee("f"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: 1}.into())
+                    }.into()),
+                    doc: None,
+                },
+            ],
+            expression: Box::new(Integer{value: 0}.into())
+        },
+        Err(TypeCheckError::DuplicatedName {
+            duplicate: Id::from("f"),
+            reason: String::from("function names")
+        }),
+        Err(TypeCheckError::DuplicatedName {
+            duplicate: Id::from("f"),
+            reason: String::from("function names")
+        });
+        "duplicate functions"
+    )]
+    fn test_shadowing(
+        block: Block,
+        last_wins: Result<(), TypeCheckError>,
+        deny: Result<(), TypeCheckError>,
+    ) {
+        for (shadowing, expected) in [(Shadowing::LastWins, last_wins), (Shadowing::Deny, deny)] {
+            let type_checker = TypeChecker {
+                shadowing,
+                ..TypeChecker::new(TypeDefinitions::new(), HashMap::new())
+            };
+            let result = type_checker.check_block(
+                block.clone(),
+                TypeContext::new(),
+                GenericVariables::new(),
+            );
+            assert_eq!(result.map(|_| ()), expected);
+        }
+    }
+
+    #[test_case("+", BuiltInShadowing::Deny, Err(TypeCheckError::BuiltInShadow { name: Id::from("+") }), false; "denied operator")]
+    #[test_case("+", BuiltInShadowing::Warn, Ok(()), true; "warned operator")]
+    #[test_case("+", BuiltInShadowing::Allow, Ok(()), false; "allowed operator")]
+    #[test_case("max", BuiltInShadowing::Deny, Ok(()), false; "built-in fn")]
+    #[test_case("<$>", BuiltInShadowing::Warn, Ok(()), false; "user operator")]
+    fn test_built_in_shadowing(
+        name: &str,
+        built_in_shadowing: BuiltInShadowing,
+        expected: Result<(), TypeCheckError>,
+        warned: bool,
+    ) {
+        let type_checker = TypeChecker {
+            built_in_shadowing,
+            ..TypeChecker::new(TypeDefinitions::new(), HashMap::new())
+        };
+        let block = Block {
+            assignments: vec![Assignment {
+                assignee: VariableAssignee(name),
+                expression: Box::new(Integer { value: 3 }.into()),
+                doc: None,
+            }],
+            expression: Box::new(Integer { value: 0 }.into()),
+        };
+        let result = DEFAULT_CONTEXT.with(|context| {
+            type_checker.check_block(block, (**context).clone(), GenericVariables::new())
+        });
+        assert_eq!(result.map(|_| ()), expected);
+        let expected_warnings = if warned {
+            vec![TypeCheckWarning::BuiltInShadow {
+                name: Id::from(name),
+            }]
+        } else {
+            Vec::new()
+        };
+        assert_eq!(type_checker.warnings.take(), expected_warnings);
+    }
+
+    #[test]
+    fn test_valid_constructor_list() {
+        let type_definitions = vec![
+            UnionTypeDefinition {
+                variable: GenericTypeVariable {
+                    id: Id::from("Tree"),
+                    generic_variables: vec![Id::from("T")],
+                },
+                items: vec![
+                    TypeItem {
+                        id: Id::from("Node"),
+                        types: vec![TupleType {
+                            types: vec![
+                                Typename("T").into(),
+                                GenericType {
+                                    id: Id::from("Tree"),
+                                    type_variables: vec![Typename("T").into()],
+                                }
+                                .into(),
+                                Typename("T").into(),
+                            ],
+                        }
+                        .into()],
+                    },
+                    TypeItem {
+                        id: Id::from("Leaf"),
+                        types: Vec::new(),
+                    },
+                ],
+                doc: None,
+            }
+            .into(),
+            UnionTypeDefinition {
+                variable: GenericTypeVariable {
+                    id: Id::from("Empty"),
+                    generic_variables: Vec::new(),
+                },
+                items: Vec::new(),
+                doc: None,
+            }
+            .into(),
+            OpaqueTypeDefinition {
+                variable: GenericTypeVariable {
+                    id: Id::from("opaque_int"),
+                    generic_variables: Vec::new(),
+                },
+                type_: ATOMIC_TYPE_INT.into(),
+                doc: None,
+            }
+            .into(),
+            OpaqueTypeDefinition {
+                variable: GenericTypeVariable {
+                    id: Id::from("opaque_opaque_int"),
+                    generic_variables: Vec::new(),
+                },
+                type_: Typename("opaque_int").into(),
+                doc: None,
+            }
+            .into(),
+            OpaqueTypeDefinition {
+                variable: GenericTypeVariable {
+                    id: Id::from("int_tree"),
+                    generic_variables: Vec::new(),
+                },
+                type_: GenericType {
+                    id: Id::from("Tree"),
+                    type_variables: vec![ATOMIC_TYPE_INT.into()],
+                }
+                .into(),
+                doc: None,
+            }
+            .into(),
+        ];
+        let expected_constructors = HashMap::from([
+            ("Tree", vec!["Node", "Leaf"]),
+            ("Empty", vec![]),
+            ("opaque_int", vec!["opaque_int"]),
+            ("opaque_opaque_int", vec!["opaque_opaque_int"]),
+            ("int_tree", vec!["int_tree"]),
+        ]);
+        let Ok(type_checker) = TypeChecker::check_type_definitions(type_definitions) else {
+            panic!("Invalid type checker definition");
+        };
+        assert_eq!(
+            type_checker.constructors,
+            expected_constructors
+                .into_iter()
+                .map(|(type_name, constructors)| constructors
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, id)| (
+                        Id::from(id),
+                        ConstructorType {
+                            type_: type_checker.type_definitions[&Id::from(type_name)].clone(),
+                            index: i
+                        }
+                    ))
+                    .collect_vec())
+                .concat()
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        )
+    }
+
+    #[test]
+    fn test_invalid_constructor_list() {
+        let type_definitions = vec![
+            UnionTypeDefinition {
+                variable: GenericTypeVariable {
+                    id: Id::from("Tree"),
+                    generic_variables: vec![Id::from("T")],
+                },
+                items: vec![
+                    TypeItem {
+                        id: Id::from("Node"),
+                        types: vec![TupleType {
+                            types: vec![
+                                Typename("T").into(),
+                                GenericType {
+                                    id: Id::from("Tree"),
+                                    type_variables: vec![Typename("T").into()],
+                                }
+                                .into(),
+                                Typename("T").into(),
+                            ],
+                        }
+                        .into()],
+                    },
+                    TypeItem {
+                        id: Id::from("Leaf"),
+                        types: Vec::new(),
+                    },
+                ],
+                doc: None,
+            }
+            .into(),
+            EmptyTypeDefinition {
+                id: Id::from("Leaf"),
+                doc: None,
+            }
+            .into(),
         ];
-        let expected_constructors = HashMap::from([
-            ("Tree", vec!["Node", "Leaf"]),
-            ("Empty", vec![]),
-            ("opaque_int", vec!["opaque_int"]),
-            ("opaque_opaque_int", vec!["opaque_opaque_int"]),
-            ("int_tree", vec!["int_tree"]),
-        ]);
-        let Ok(type_checker) = TypeChecker::check_type_definitions(type_definitions) else {
-            panic!("Invalid type checker definition");
-        };
+        let result = TypeChecker::check_type_definitions(type_definitions);
+        assert!(result.is_err())
+    }
+
+    #[test_case(
+        Program{
+            definitions: Vec::new()
+        },
+        Err(()),
+        TypeContext::new();
+        "empty program"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: 0}.into())
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Ok(()),
+        TypeContext::new();
+        "basic program"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("not_main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: 0}.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(Var("not_main").into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Err(()),
+        TypeContext::new();
+        "main reassignment"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(
+                            FunctionCall {
+                                arguments: vec![
+                                    Integer{value: 1}.into(),
+                                    Integer{value: -1}.into()
+                                ],
+                                function: Box::new(Var("+").into())
+                            }.into()
+                        )
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Ok(()),
+        TypeContext::from([(
+            Id::from("+"),
+            Type::from(TypeFn(
+                vec![TYPE_INT, TYPE_INT],
+                Box::new(TYPE_INT)
+            )).into()
+        )]);
+        "basic using context"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(
+                            FunctionCall {
+                                arguments: vec![
+                                    Integer{value: 1}.into(),
+                                    Integer{value: -1}.into()
+                                ],
+                                function: Box::new(Var("+").into())
+                            }.into()
+                        )
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Err(()),
+        TypeContext::new();
+        "basic outside of context"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                OpaqueTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from("opaque_int"),
+                        generic_variables: Vec::new()
+                    },
+                    type_: ATOMIC_TYPE_INT.into(),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(
+                            ConstructorCall {
+                                arguments: vec![
+                                    Integer{value: -1}.into(),
+                                ],
+                                constructor: GenericConstructor {
+                                    id: Id::from("opaque_int"),
+                                    type_instances: Vec::new()
+                                }
+                            }.into()
+                        )
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Err(()),
+        TypeContext::new();
+        "opaque type definition usage"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                OpaqueTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from("opaque_int"),
+                        generic_variables: Vec::new()
+                    },
+                    type_: ATOMIC_TYPE_INT.into(),
+                    doc: None,
+                }.into(),
+                TransparentTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from("transparent_int"),
+                        generic_variables: Vec::new()
+                    },
+                    type_: ATOMIC_TYPE_INT.into(),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: Typename("transparent_int").into(),
+                        body: Block{
+                            assignments: vec![
+                                Assignment {
+                                    assignee: VariableAssignee("x"),
+                                    expression: Box::new(ConstructorCall {
+                                        arguments: vec![
+                                            Integer{value: -1}.into(),
+                                        ],
+                                        constructor: GenericConstructor {
+                                            id: Id::from("opaque_int"),
+                                            type_instances: Vec::new()
+                                        }
+                                    }.into()),
+                                    doc: None,
+                                },
+                            ],
+                            expression: Box::new(MatchExpression {
+                                subject: Box::new(Var("x").into()),
+                                blocks: vec![
+                                    MatchBlock {
+                                        matches: vec![
+                                            MatchItem {
+                                                type_name: Id::from("opaque_int"),
+                                                assignee: Some(Assignee{
+                                                    id: Id::from("x")
+                                                }),
+                                                pattern: None
+                                            },
+                                        ],
+                                        block: ExpressionBlock(Var("x").into())
+                                    }
+                                ]
+                            }.into()),
+                        }
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Ok(()),
+        TypeContext::new();
+        "type definition match expression"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                UnionTypeDefinition {
+                    variable: GenericTypeVariable{
+                        id: Id::from("Maybe"),
+                        generic_variables: vec![Id::from("T"), Id::from("U")]
+                    },
+                    items: vec![
+                        TypeItem {
+                            id: Id::from("Left"),
+                            types: vec![Typename("T").into()]
+                        },
+                        TypeItem {
+                            id: Id::from("Right"),
+                            types: vec![Typename("U").into()]
+                        }
+                    ],
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: Block{
+                            assignments: vec![
+                                Assignment {
+                                    assignee: VariableAssignee("x"),
+                                    expression: Box::new(ConstructorCall {
+                                        arguments: vec![
+                                            Integer{value: 0}.into(),
+                                        ],
+                                        constructor: GenericConstructor {
+                                            id: Id::from("Left"),
+                                            type_instances: vec![
+                                                ATOMIC_TYPE_INT.into(),
+                                                ATOMIC_TYPE_BOOL.into()
+                                            ]
+                                        }
+                                    }.into()),
+                                    doc: None,
+                                },
+                            ],
+                            expression: Box::new(MatchExpression {
+                                subject: Box::new(Var("x").into()),
+                                blocks: vec![
+                                    MatchBlock {
+                                        matches: vec![
+                                            MatchItem {
+                                                type_name: Id::from("Left"),
+                                                assignee: Some(Assignee{
+                                                    id: Id::from("x")
+                                                }),
+                                                pattern: None
+                                            },
+                                        ],
+                                        block: ExpressionBlock(Var("x").into())
+                                    },
+                                    MatchBlock {
+                                        matches: vec![
+                                            MatchItem {
+                                                type_name: Id::from("Right"),
+                                                assignee: Some(Assignee{
+                                                    id: Id::from("x")
+                                                }),
+                                                pattern: None
+                                            },
+                                        ],
+                                        block: ExpressionBlock(IfExpression{
+                                            condition: Box::new(Var("x").into()),
+                                            true_block: ExpressionBlock(Integer{value: 1}.into()),
+                                            false_block: Some(ExpressionBlock(Integer{value: -1}.into())),
+                                        }.into())
+                                    }
+                                ]
+                            }.into()),
+                        }
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Ok(()),
+        TypeContext::new();
+        "union type instantiation"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Assignee { id: Id::from("x") },
+                                type_: TupleType { types: Vec::new() }.into()
+                            }
+                        ],
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(
+                            Integer{value: 1}.into(),
+                        )
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Err(()),
+        TypeContext::new();
+        "wrong argument type"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Assignee { id: Id::from("x") },
+                                type_: ATOMIC_TYPE_INT.into()
+                            },
+                            TypedAssignee {
+                                assignee: Assignee { id: Id::from("y") },
+                                type_: ATOMIC_TYPE_BOOL.into()
+                            },
+                        ],
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(
+                            Var("x").into(),
+                        )
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Ok(()),
+        TypeContext::new();
+        "main with arguments"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: ParametricAssignee {
+                        assignee: Assignee {
+                            id: Id::from("main")
+                        },
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![],
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(
+                            Integer{value: 1}.into(),
+                        )
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Err(()),
+        TypeContext::new();
+        "parametric main"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(GenericVariable{
+                                id: Id::from("identity"),
+                                type_instances: vec![ATOMIC_TYPE_INT.into()]
+                            }.into()),
+                            arguments: vec![
+                                Integer{ value: 11 }.into()
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: ParametricAssignee{
+                        assignee: Assignee { id: Id::from("identity") },
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Assignee {
+                                    id: Id::from("x")
+                                },
+                                type_: Typename("T").into()
+                            }
+                        ],
+                        return_type: Typename("T").into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Ok(()),
+        TypeContext::new();
+        "function type instantiation"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: FunctionType{
+                            argument_types: vec![ATOMIC_TYPE_INT.into()],
+                            return_type: Box::new(ATOMIC_TYPE_INT.into()),
+                        }.into(),
+                        body: ExpressionBlock(GenericVariable{
+                            id: Id::from("identity"),
+                            type_instances: vec![ATOMIC_TYPE_INT.into()]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: ParametricAssignee{
+                        assignee: Assignee { id: Id::from("identity") },
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(FunctionDefinition{
+                        parameters: vec![
+                            TypedAssignee {
+                                assignee: Assignee {
+                                    id: Id::from("x")
+                                },
+                                type_: Typename("T").into()
+                            }
+                        ],
+                        return_type: Typename("T").into(),
+                        body: ExpressionBlock(Var("x").into())
+                    }.into()),
+                    doc: None,
+                }.into()
+            ]
+        },
+        Err(()),
+        TypeContext::new();
+        "returning function from main"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Var("offset").into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("offset"),
+                    expression: Box::new(Integer{value: 5}.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(()),
+        TypeContext::new();
+        "value defined after main"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Var("offset").into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("offset"),
+                    expression: Box::new(FunctionCall{
+            function: Box::new(Var("helper").into()),
+            arguments: Vec::new()
+        }.into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("helper"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer{value: 5}.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(()),
+        TypeContext::new();
+        "value defined after main using later function"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Var("y").into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("x"),
+                    expression: Box::new(Var("y").into()),
+                    doc: None,
+                }.into(),
+                Assignment{
+                    assignee: VariableAssignee("y"),
+                    expression: Box::new(Var("x").into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Err(()),
+        TypeContext::new();
+        "cyclic values defined after main"
+    )]
+    fn test_program(program: Program, result: Result<(), ()>, context: TypeContext) {
+        let type_check_result =
+            TypeChecker::check_program(program, &context, TypeCheckOptions::default());
+        match (type_check_result.clone(), result) {
+            (Ok(program), Err(())) => {
+                dbg!(program);
+                assert!(type_check_result.is_err())
+            }
+            (Err(msg), Ok(())) => {
+                dbg!(msg);
+                assert!(type_check_result.is_ok())
+            }
+            _ => (),
+        }
+    }
+
+    #[test_case(Vec::new(); "empty program")]
+    #[test_case(
+        vec![Assignment {
+            assignee: VariableAssignee("mian"),
+            expression: Box::new(Integer { value: 0 }.into()),
+            doc: None,
+        }.into()];
+        "misspelled main"
+    )]
+    fn test_missing_main(definitions: Vec<Definition>) {
         assert_eq!(
-            type_checker.constructors,
-            expected_constructors
-                .into_iter()
-                .map(|(type_name, constructors)| constructors
-                    .into_iter()
-                    .enumerate()
-                    .map(|(i, id)| (
-                        Id::from(id),
-                        ConstructorType {
-                            type_: type_checker.type_definitions[&Id::from(type_name)].clone(),
-                            index: i
+            TypeChecker::type_check(Program { definitions }).unwrap_err(),
+            TypeCheckError::MissingMain
+        );
+    }
+
+    #[test_case(
+        GenericType{
+            id: Id::from("List"),
+            type_variables: vec![Typename("T").into()]
+        }.into(),
+        true;
+        "list of parameter"
+    )]
+    #[test_case(Typename("T").into(), false; "same parameter")]
+    #[test_case(ATOMIC_TYPE_INT.into(), false; "concrete type")]
+    fn test_polymorphic_recursion(type_instance: TypeInstance, is_polymorphic: bool) {
+        // `type List[T] = Cons (T, List[T]) | Nil; f[T] = () -> int { f[<type_instance>]() }; main = () -> int { f[int]() }`
+        let call = |type_instance: TypeInstance| -> Expression {
+            FunctionCall {
+                function: Box::new(
+                    GenericVariable {
+                        id: Id::from("f"),
+                        type_instances: vec![type_instance],
+                    }
+                    .into(),
+                ),
+                arguments: Vec::new(),
+            }
+            .into()
+        };
+        let program = Program {
+            definitions: vec![
+                UnionTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from("List"),
+                        generic_variables: vec![Id::from("T")],
+                    },
+                    items: vec![
+                        TypeItem {
+                            id: Id::from("Cons"),
+                            types: vec![
+                                Typename("T").into(),
+                                GenericType {
+                                    id: Id::from("List"),
+                                    type_variables: vec![Typename("T").into()],
+                                }
+                                .into(),
+                            ],
+                        },
+                        TypeItem {
+                            id: Id::from("Nil"),
+                            types: Vec::new(),
+                        },
+                    ],
+                    doc: None,
+                }
+                .into(),
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("f").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds: BTreeMap::new(),
+                    },
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: Vec::new(),
+                            return_type: ATOMIC_TYPE_INT.into(),
+                            body: ExpressionBlock(call(type_instance)),
                         }
-                    ))
-                    .collect_vec())
-                .concat()
-                .into_iter()
-                .collect::<HashMap<_, _>>()
-        )
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+                Assignment {
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: Vec::new(),
+                            return_type: ATOMIC_TYPE_INT.into(),
+                            body: ExpressionBlock(call(ATOMIC_TYPE_INT.into())),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+            ],
+        };
+        let result = TypeChecker::type_check(program);
+        if is_polymorphic {
+            let Err(TypeCheckError::PolymorphicRecursion {
+                name,
+                type_instances,
+            }) = result
+            else {
+                panic!("Expected polymorphic recursion, got {:?}.", result)
+            };
+            assert_eq!(name, Id::from("f"));
+            assert_eq!(type_instances.len(), 1);
+        } else {
+            assert!(result.is_ok());
+        }
     }
 
-    #[test]
-    fn test_invalid_constructor_list() {
-        let type_definitions = vec![
-            UnionTypeDefinition {
-                variable: GenericTypeVariable {
-                    id: Id::from("Tree"),
-                    generic_variables: vec![Id::from("T")],
-                },
-                items: vec![
-                    TypeItem {
-                        id: Id::from("Node"),
-                        type_: Some(
-                            TupleType {
-                                types: vec![
-                                    Typename("T").into(),
-                                    GenericType {
-                                        id: Id::from("Tree"),
-                                        type_variables: vec![Typename("T").into()],
-                                    }
-                                    .into(),
-                                    Typename("T").into(),
-                                ],
+    #[test_case("double", Ok(()); "extra built-in")]
+    #[test_case("max", Err(Id::from("max")); "overridden built-in")]
+    fn test_type_check_with_context(name: &str, result: Result<(), Id>) {
+        let program = Program {
+            definitions: vec![Assignment {
+                assignee: VariableAssignee("main"),
+                expression: Box::new(
+                    FunctionDefinition {
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(
+                            FunctionCall {
+                                function: Box::new(Var(name).into()),
+                                arguments: vec![Integer { value: 3 }.into()],
                             }
                             .into(),
                         ),
-                    },
-                    TypeItem {
-                        id: Id::from("Leaf"),
-                        type_: None,
-                    },
-                ],
+                    }
+                    .into(),
+                ),
+                doc: None,
             }
-            .into(),
-            EmptyTypeDefinition {
-                id: Id::from("Leaf"),
+            .into()],
+        };
+        let extra = TypeContext::from([(
+            Id::from(name),
+            Type::from(TypeFn(vec![TYPE_INT], Box::new(TYPE_INT))).into(),
+        )]);
+        match (TypeChecker::type_check_with_context(program, extra), result) {
+            (Ok(_), Ok(())) => {}
+            (Err(TypeCheckError::BuiltInOverride { name, reason: _ }), Err(expected)) => {
+                assert_eq!(name, expected)
             }
-            .into(),
-        ];
-        let result = TypeChecker::check_type_definitions(type_definitions);
-        assert!(result.is_err())
+            (type_check_result, _) => panic!("{type_check_result:?}"),
+        }
     }
 
     #[test_case(
         Program{
-            definitions: Vec::new()
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(GenericVariable{
+                                id: Id::from("+"),
+                                type_instances: Vec::new()
+                            }.into()),
+                            arguments: vec![
+                                Integer{ value: -1 }.into(),
+                                Integer{ value: 2 }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(());
+        "builtin-in operator usage"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(GenericVariable{
+                                id: Id::from("&&"),
+                                type_instances: Vec::new()
+                            }.into()),
+                            arguments: vec![
+                                Boolean{ value: false }.into(),
+                                Boolean{ value: true }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(());
+        "pre-defined operator usage"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_STRING.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("concat").into()),
+                            arguments: vec![
+                                Str{ value: String::from("") }.into(),
+                                Str{ value: String::from("world") }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(());
+        "string concatenation"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("length").into()),
+                            arguments: vec![
+                                Str{ value: String::from("hello") }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(());
+        "string length"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("==").into()),
+                            arguments: vec![
+                                Str{ value: String::from("a") }.into(),
+                                Str{ value: String::from("b") }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(());
+        "string equality"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("!=").into()),
+                            arguments: vec![
+                                Str{ value: String::from("a") }.into(),
+                                Str{ value: String::from("b") }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Ok(());
+        "string inequality"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("==").into()),
+                            arguments: vec![
+                                Str{ value: String::from("a") }.into(),
+                                Integer{ value: 1 }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Err(());
+        "string integer equality"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("<").into()),
+                            arguments: vec![
+                                Str{ value: String::from("a") }.into(),
+                                Str{ value: String::from("b") }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
+        },
+        Err(());
+        "string comparison"
+    )]
+    #[test_case(
+        Program{
+            definitions: vec![
+                Assignment{
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(FunctionDefinition{
+                        parameters: Vec::new(),
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("==").into()),
+                            arguments: vec![
+                                Char{ value: 97 }.into(),
+                                Char{ value: 98 }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
+            ]
         },
-        Err(()),
-        TypeContext::new();
-        "empty program"
+        Ok(());
+        "char equality"
     )]
     #[test_case(
         Program{
@@ -4589,36 +9649,46 @@ mod tests {
                     assignee: VariableAssignee("main"),
                     expression: Box::new(FunctionDefinition{
                         parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(Integer{value: 0}.into())
-                    }.into())
-                }.into()
+                        return_type: ATOMIC_TYPE_BOOL.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("!=").into()),
+                            arguments: vec![
+                                Char{ value: 97 }.into(),
+                                Integer{ value: 97 }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Ok(()),
-        TypeContext::new();
-        "basic program"
+        Err(());
+        "char integer inequality"
     )]
     #[test_case(
         Program{
             definitions: vec![
                 Assignment{
-                    assignee: VariableAssignee("not_main"),
+                    assignee: VariableAssignee("main"),
                     expression: Box::new(FunctionDefinition{
                         parameters: Vec::new(),
                         return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(Integer{value: 0}.into())
-                    }.into())
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("ord").into()),
+                            arguments: vec![
+                                FunctionCall{
+                                    function: Box::new(Var("chr").into()),
+                                    arguments: vec![Integer{ value: 97 }.into()]
+                                }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
                 }.into(),
-                Assignment{
-                    assignee: VariableAssignee("main"),
-                    expression: Box::new(Var("not_main").into())
-                }.into()
             ]
         },
-        Err(()),
-        TypeContext::new();
-        "main reassignment"
+        Ok(());
+        "char conversion"
     )]
     #[test_case(
         Program{
@@ -4628,28 +9698,19 @@ mod tests {
                     expression: Box::new(FunctionDefinition{
                         parameters: Vec::new(),
                         return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(
-                            FunctionCall {
-                                arguments: vec![
-                                    Integer{value: 1}.into(),
-                                    Integer{value: -1}.into()
-                                ],
-                                function: Box::new(Var("+").into())
-                            }.into()
-                        )
-                    }.into())
-                }.into()
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("ord").into()),
+                            arguments: vec![
+                                Integer{ value: 97 }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Ok(()),
-        TypeContext::from([(
-            Id::from("+"),
-            Type::from(TypeFn(
-                vec![TYPE_INT, TYPE_INT],
-                Box::new(TYPE_INT)
-            )).into()
-        )]);
-        "basic using context"
+        Err(());
+        "integer ord"
     )]
     #[test_case(
         Program{
@@ -4659,200 +9720,97 @@ mod tests {
                     expression: Box::new(FunctionDefinition{
                         parameters: Vec::new(),
                         return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(
-                            FunctionCall {
-                                arguments: vec![
-                                    Integer{value: 1}.into(),
-                                    Integer{value: -1}.into()
-                                ],
-                                function: Box::new(Var("+").into())
-                            }.into()
-                        )
-                    }.into())
-                }.into()
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("min").into()),
+                            arguments: vec![
+                                FunctionCall{
+                                    function: Box::new(Var("abs").into()),
+                                    arguments: vec![Integer{ value: -5 }.into()]
+                                }.into(),
+                                FunctionCall{
+                                    function: Box::new(Var("max").into()),
+                                    arguments: vec![
+                                        Integer{ value: 3 }.into(),
+                                        Integer{ value: 4 }.into(),
+                                    ]
+                                }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Err(()),
-        TypeContext::new();
-        "basic outside of context"
+        Ok(());
+        "min max abs"
     )]
     #[test_case(
         Program{
             definitions: vec![
-                OpaqueTypeDefinition {
-                    variable: GenericTypeVariable {
-                        id: Id::from("opaque_int"),
-                        generic_variables: Vec::new()
-                    },
-                    type_: ATOMIC_TYPE_INT.into()
-                }.into(),
                 Assignment{
                     assignee: VariableAssignee("main"),
                     expression: Box::new(FunctionDefinition{
                         parameters: Vec::new(),
                         return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(
-                            ConstructorCall {
-                                arguments: vec![
-                                    Integer{value: -1}.into(),
-                                ],
-                                constructor: GenericConstructor {
-                                    id: Id::from("opaque_int"),
-                                    type_instances: Vec::new()
-                                }
-                            }.into()
-                        )
-                    }.into())
-                }.into()
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("abs").into()),
+                            arguments: vec![
+                                Integer{ value: -5 }.into(),
+                                Integer{ value: 5 }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Err(()),
-        TypeContext::new();
-        "opaque type definition usage"
+        Err(());
+        "abs two arguments"
     )]
     #[test_case(
         Program{
             definitions: vec![
-                OpaqueTypeDefinition {
-                    variable: GenericTypeVariable {
-                        id: Id::from("opaque_int"),
-                        generic_variables: Vec::new()
-                    },
-                    type_: ATOMIC_TYPE_INT.into()
-                }.into(),
-                TransparentTypeDefinition {
-                    variable: GenericTypeVariable {
-                        id: Id::from("transparent_int"),
-                        generic_variables: Vec::new()
-                    },
-                    type_: ATOMIC_TYPE_INT.into()
-                }.into(),
                 Assignment{
                     assignee: VariableAssignee("main"),
                     expression: Box::new(FunctionDefinition{
                         parameters: Vec::new(),
-                        return_type: Typename("transparent_int").into(),
-                        body: Block{
-                            assignments: vec![
-                                Assignment {
-                                    assignee: VariableAssignee("x"),
-                                    expression: Box::new(ConstructorCall {
-                                        arguments: vec![
-                                            Integer{value: -1}.into(),
-                                        ],
-                                        constructor: GenericConstructor {
-                                            id: Id::from("opaque_int"),
-                                            type_instances: Vec::new()
-                                        }
-                                    }.into())
-                                },
-                            ],
-                            expression: Box::new(MatchExpression {
-                                subject: Box::new(Var("x").into()),
-                                blocks: vec![
-                                    MatchBlock {
-                                        matches: vec![
-                                            MatchItem {
-                                                type_name: Id::from("opaque_int"),
-                                                assignee: Some(Assignee{
-                                                    id: Id::from("x")
-                                                })
-                                            },
-                                        ],
-                                        block: ExpressionBlock(Var("x").into())
-                                    }
-                                ]
-                            }.into()),
-                        }
-                    }.into())
-                }.into()
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("-").into()),
+                            arguments: vec![
+                                FunctionCall{
+                                    function: Box::new(Var("-").into()),
+                                    arguments: vec![Integer{ value: 5 }.into()]
+                                }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Ok(()),
-        TypeContext::new();
-        "type definition match expression"
+        Ok(());
+        "double negation"
     )]
     #[test_case(
         Program{
             definitions: vec![
-                UnionTypeDefinition {
-                    variable: GenericTypeVariable{
-                        id: Id::from("Maybe"),
-                        generic_variables: vec![Id::from("T"), Id::from("U")]
-                    },
-                    items: vec![
-                        TypeItem {
-                            id: Id::from("Left"),
-                            type_: Some(Typename("T").into())
-                        },
-                        TypeItem {
-                            id: Id::from("Right"),
-                            type_: Some(Typename("U").into())
-                        }
-                    ]
-                }.into(),
                 Assignment{
                     assignee: VariableAssignee("main"),
                     expression: Box::new(FunctionDefinition{
                         parameters: Vec::new(),
                         return_type: ATOMIC_TYPE_INT.into(),
-                        body: Block{
-                            assignments: vec![
-                                Assignment {
-                                    assignee: VariableAssignee("x"),
-                                    expression: Box::new(ConstructorCall {
-                                        arguments: vec![
-                                            Integer{value: 0}.into(),
-                                        ],
-                                        constructor: GenericConstructor {
-                                            id: Id::from("Left"),
-                                            type_instances: vec![
-                                                ATOMIC_TYPE_INT.into(),
-                                                ATOMIC_TYPE_BOOL.into()
-                                            ]
-                                        }
-                                    }.into())
-                                },
-                            ],
-                            expression: Box::new(MatchExpression {
-                                subject: Box::new(Var("x").into()),
-                                blocks: vec![
-                                    MatchBlock {
-                                        matches: vec![
-                                            MatchItem {
-                                                type_name: Id::from("Left"),
-                                                assignee: Some(Assignee{
-                                                    id: Id::from("x")
-                                                })
-                                            },
-                                        ],
-                                        block: ExpressionBlock(Var("x").into())
-                                    },
-                                    MatchBlock {
-                                        matches: vec![
-                                            MatchItem {
-                                                type_name: Id::from("Right"),
-                                                assignee: Some(Assignee{
-                                                    id: Id::from("x")
-                                                })
-                                            },
-                                        ],
-                                        block: ExpressionBlock(IfExpression{
-                                            condition: Box::new(Var("x").into()),
-                                            true_block: ExpressionBlock(Integer{value: 1}.into()),
-                                            false_block: ExpressionBlock(Integer{value: -1}.into()),
-                                        }.into())
-                                    }
-                                ]
-                            }.into()),
-                        }
-                    }.into())
-                }.into()
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("~").into()),
+                            arguments: vec![Integer{ value: 0 }.into()]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Ok(()),
-        TypeContext::new();
-        "union type instantiation"
+        Ok(());
+        "bitwise not"
     )]
     #[test_case(
         Program{
@@ -4860,23 +9818,25 @@ mod tests {
                 Assignment{
                     assignee: VariableAssignee("main"),
                     expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Assignee { id: Id::from("x") },
-                                type_: TupleType { types: Vec::new() }.into()
-                            }
-                        ],
+                        parameters: Vec::new(),
                         return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(
-                            Integer{value: 1}.into(),
-                        )
-                    }.into())
-                }.into()
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("-").into()),
+                            arguments: vec![
+                                Integer{ value: 5 }.into(),
+                                FunctionCall{
+                                    function: Box::new(Var("-").into()),
+                                    arguments: vec![Integer{ value: 3 }.into()]
+                                }.into(),
+                            ]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Err(()),
-        TypeContext::new();
-        "wrong argument type"
+        Ok(());
+        "binary and unary minus"
     )]
     #[test_case(
         Program{
@@ -4884,213 +9844,791 @@ mod tests {
                 Assignment{
                     assignee: VariableAssignee("main"),
                     expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Assignee { id: Id::from("x") },
-                                type_: ATOMIC_TYPE_INT.into()
-                            },
-                            TypedAssignee {
-                                assignee: Assignee { id: Id::from("y") },
-                                type_: ATOMIC_TYPE_BOOL.into()
-                            },
-                        ],
+                        parameters: Vec::new(),
                         return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(
-                            Var("x").into(),
-                        )
-                    }.into())
-                }.into()
+                        body: ExpressionBlock(FunctionCall{
+                            function: Box::new(Var("-").into()),
+                            arguments: vec![Boolean{ value: true }.into()]
+                        }.into())
+                    }.into()),
+                    doc: None,
+                }.into(),
             ]
         },
-        Ok(()),
-        TypeContext::new();
-        "main with arguments"
+        Err(());
+        "boolean negative"
+    )]
+    fn test_default_program(program: Program, result: Result<(), ()>) {
+        let type_check_result = TypeChecker::type_check(program);
+        match (type_check_result.clone(), result) {
+            (Ok(program), Err(())) => {
+                dbg!(program);
+                assert!(type_check_result.is_err())
+            }
+            (Err(msg), Ok(())) => {
+                dbg!(msg);
+                assert!(type_check_result.is_ok())
+            }
+            _ => (),
+        }
+    }
+
+    fn stream_program(assignments: Vec<Assignment>) -> Program {
+        // `type Stream = Cons (int, () -> Stream); main = () -> int { f = (s: Stream) -> int { 0 }; ...; 0 }`
+        Program {
+            definitions: vec![
+                UnionTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from("Stream"),
+                        generic_variables: Vec::new(),
+                    },
+                    items: vec![TypeItem {
+                        id: Id::from("Cons"),
+                        types: vec![TupleType {
+                            types: vec![
+                                ATOMIC_TYPE_INT.into(),
+                                FunctionType {
+                                    argument_types: Vec::new(),
+                                    return_type: Box::new(Typename("Stream").into()),
+                                }
+                                .into(),
+                            ],
+                        }
+                        .into()],
+                    }],
+                    doc: None,
+                }
+                .into(),
+                Assignment {
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: Vec::new(),
+                            return_type: ATOMIC_TYPE_INT.into(),
+                            body: Block {
+                                assignments: vec![Assignment {
+                                    assignee: VariableAssignee("f"),
+                                    expression: Box::new(
+                                        FunctionDefinition {
+                                            parameters: vec![TypedAssignee {
+                                                assignee: Assignee { id: Id::from("s") },
+                                                type_: Typename("Stream").into(),
+                                            }],
+                                            return_type: ATOMIC_TYPE_INT.into(),
+                                            body: ExpressionBlock(Integer { value: 0 }.into()),
+                                        }
+                                        .into(),
+                                    ),
+                                    doc: None,
+                                }]
+                                .into_iter()
+                                .chain(assignments)
+                                .collect(),
+                                expression: Box::new(Integer { value: 0 }.into()),
+                            },
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+            ],
+        }
+    }
+
+    fn cons(head: Expression, tail: &str) -> Box<Expression> {
+        Box::new(
+            ConstructorCall {
+                constructor: GenericConstructor {
+                    id: Id::from("Cons"),
+                    type_instances: Vec::new(),
+                },
+                arguments: vec![TupleExpression {
+                    expressions: vec![
+                        head,
+                        FunctionDefinition {
+                            parameters: Vec::new(),
+                            return_type: Typename("Stream").into(),
+                            body: ExpressionBlock(Var(tail).into()),
+                        }
+                        .into(),
+                    ],
+                }
+                .into()],
+            }
+            .into(),
+        )
+    }
+
+    fn strict_head(id: &str) -> Expression {
+        FunctionCall {
+            function: Box::new(Var("f").into()),
+            arguments: vec![Var(id).into()],
+        }
+        .into()
+    }
+
+    #[test_case(
+        vec![
+            Assignment {
+                assignee: VariableAssignee("ones"),
+                expression: cons(Integer{value: 1}.into(), "ones"),
+                doc: None,
+            }
+        ],
+        Ok(());
+        "lazy self reference"
+    )]
+    #[test_case(
+        vec![
+            Assignment {
+                assignee: VariableAssignee("a"),
+                expression: cons(Integer{value: 1}.into(), "b"),
+                doc: None,
+            },
+            Assignment {
+                assignee: VariableAssignee("b"),
+                expression: cons(Integer{value: 2}.into(), "a"),
+                doc: None,
+            }
+        ],
+        Ok(());
+        "lazy mutual reference"
+    )]
+    #[test_case(
+        vec![
+            Assignment {
+                assignee: VariableAssignee("ones"),
+                expression: cons(strict_head("ones"), "ones"),
+                doc: None,
+            }
+        ],
+        Err(TypeCheckError::StrictCycle {
+            variable: Id::from("ones"),
+            dependency: Id::from("ones")
+        });
+        "strict self reference"
+    )]
+    #[test_case(
+        vec![
+            Assignment {
+                assignee: VariableAssignee("a"),
+                expression: cons(strict_head("b"), "b"),
+                doc: None,
+            },
+            Assignment {
+                assignee: VariableAssignee("b"),
+                expression: cons(Integer{value: 2}.into(), "a"),
+                doc: None,
+            }
+        ],
+        Err(TypeCheckError::StrictCycle {
+            variable: Id::from("a"),
+            dependency: Id::from("b")
+        });
+        "strict forward reference"
+    )]
+    #[test_case(
+        vec![
+            Assignment {
+                assignee: VariableAssignee("a"),
+                expression: cons(Integer{value: 1}.into(), "a"),
+                doc: None,
+            },
+            Assignment {
+                assignee: VariableAssignee("b"),
+                expression: cons(strict_head("a"), "a"),
+                doc: None,
+            }
+        ],
+        Ok(());
+        "strict backward reference"
+    )]
+    fn test_recursive_values(assignments: Vec<Assignment>, expected: Result<(), TypeCheckError>) {
+        let result = TypeChecker::type_check(stream_program(assignments)).map(|_| ());
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_recursive_value_thunks() {
+        let program = TypeChecker::type_check(stream_program(vec![
+            Assignment {
+                assignee: VariableAssignee("a"),
+                expression: cons(Integer { value: 1 }.into(), "b"),
+                doc: None,
+            },
+            Assignment {
+                assignee: VariableAssignee("b"),
+                expression: cons(Integer { value: 2 }.into(), "a"),
+                doc: None,
+            },
+        ]))
+        .unwrap();
+        let Some(TypedStatement::TypedFnDef(TypedFnDef {
+            variable: _,
+            parameters: _,
+            fn_: main,
+        })) = program.main.body.statements.last()
+        else {
+            panic!("Main is not the last definition.")
+        };
+        let statements = &main.body.statements;
+        // Only `b` is referenced before its definition so only `b` is defined with a thunk.
+        assert!(matches!(
+            statements.as_slice(),
+            [
+                TypedStatement::TypedFnDef(_),
+                TypedStatement::TypedAssignment(_),
+                TypedStatement::TypedFnDef(_),
+                TypedStatement::TypedAssignment(_),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_check_standalone() {
+        let type_checker = TypeChecker::with_definitions(vec![UnionTypeDefinition {
+            variable: GenericTypeVariable {
+                id: Id::from("Bool"),
+                generic_variables: Vec::new(),
+            },
+            items: vec![
+                TypeItem {
+                    id: Id::from("True"),
+                    types: Vec::new(),
+                },
+                TypeItem {
+                    id: Id::from("False"),
+                    types: Vec::new(),
+                },
+            ],
+            doc: None,
+        }
+        .into()])
+        .unwrap();
+        let bool_type = type_checker.type_definitions[&Id::from("Bool")]
+            .borrow()
+            .type_
+            .clone();
+        let x = TypedVariable::from(TYPE_INT);
+        let context = TypeContext::from([(Id::from("x"), x.clone())]);
+
+        let constructor_call = ConstructorCall {
+            constructor: GenericConstructor {
+                id: Id::from("True"),
+                type_instances: Vec::new(),
+            },
+            arguments: Vec::new(),
+        };
+        let typed_expression = type_checker
+            .check_standalone(constructor_call.clone().into(), &context)
+            .unwrap();
+        assert_eq!(typed_expression.type_(), bool_type);
+        // Reuse the same type checker with a different expression.
+        let tuple = TupleExpression {
+            expressions: vec![Var("x").into(), constructor_call.into()],
+        };
+        let typed_expression = type_checker
+            .check_standalone(tuple.clone().into(), &context)
+            .unwrap();
+        assert_eq!(
+            typed_expression.type_(),
+            TypeTuple(vec![TYPE_INT, bool_type]).into()
+        );
+        assert!(type_checker
+            .check_standalone(tuple.into(), &TypeContext::new())
+            .is_err());
+    }
+
+    fn main_program(parameters: Vec<TypeInstance>) -> Program {
+        Program {
+            definitions: vec![Assignment {
+                assignee: VariableAssignee("main"),
+                expression: Box::new(
+                    FunctionDefinition {
+                        parameters: parameters
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, type_)| TypedAssignee {
+                                assignee: Assignee {
+                                    id: format!("x{i}"),
+                                },
+                                type_,
+                            })
+                            .collect(),
+                        return_type: ATOMIC_TYPE_INT.into(),
+                        body: ExpressionBlock(Integer { value: 0 }.into()),
+                    }
+                    .into(),
+                ),
+                doc: None,
+            }
+            .into()],
+        }
+    }
+
+    #[test]
+    fn test_docs_carried_through() {
+        let mut program = main_program(Vec::new());
+        let Definition::Assignment(main) = &mut program.definitions[0] else {
+            panic!("Main program changed form.")
+        };
+        main.doc = Some(String::from("Entry point."));
+        program.definitions.push(
+            EmptyTypeDefinition {
+                id: Id::from("Unit"),
+                doc: Some(String::from("Only one value.")),
+            }
+            .into(),
+        );
+        program.definitions.push(
+            VoidTypeDefinition {
+                id: Id::from("Never"),
+                doc: None,
+            }
+            .into(),
+        );
+        let typed_program = TypeChecker::type_check(program).unwrap();
+        assert_eq!(
+            typed_program.docs,
+            BTreeMap::from([
+                (Id::from("main"), String::from("Entry point.")),
+                (Id::from("Unit"), String::from("Only one value.")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_union_constructors() {
+        let mut program = main_program(Vec::new());
+        program.definitions.push(
+            UnionTypeDefinition {
+                variable: GenericTypeVariable {
+                    id: Id::from("List"),
+                    generic_variables: vec![Id::from("T")],
+                },
+                items: vec![
+                    TypeItem {
+                        id: Id::from("Cons"),
+                        types: vec![TupleType {
+                            types: vec![
+                                Typename("T").into(),
+                                GenericType {
+                                    id: Id::from("List"),
+                                    type_variables: vec![Typename("T").into()],
+                                }
+                                .into(),
+                            ],
+                        }
+                        .into()],
+                    },
+                    TypeItem {
+                        id: Id::from("Nil"),
+                        types: Vec::new(),
+                    },
+                ],
+                doc: None,
+            }
+            .into(),
+        );
+        let typed_program = TypeChecker::type_check(program).unwrap();
+        let list = typed_program
+            .type_definitions
+            .get(&Id::from("List"))
+            .unwrap();
+        let parameter = Type::from(TypeVariable(list.borrow().parameters[0].clone()));
+        assert_eq!(
+            typed_program.union_constructors(&Id::from("List")),
+            Some(vec![
+                (
+                    0,
+                    Some(Type::from(TypeTuple(vec![
+                        parameter.clone(),
+                        TypeInstantiation {
+                            reference: list.clone(),
+                            instances: vec![parameter],
+                        }
+                        .into()
+                    ])))
+                ),
+                (1, None)
+            ])
+        );
+        assert_eq!(typed_program.union_constructors(&Id::from("main")), None);
+    }
+
+    #[test]
+    fn test_type_check_all_errors() {
+        let mut program = main_program(Vec::new());
+        let Definition::Assignment(main) = &mut program.definitions[0] else {
+            panic!("Main program changed form.")
+        };
+        let Expression::FunctionDefinition(fn_def) = main.expression.as_mut() else {
+            panic!("Main program changed form.")
+        };
+        fn_def.body = ExpressionBlock(
+            TupleExpression {
+                expressions: vec![Var("a").into(), Var("b").into()],
+            }
+            .into(),
+        );
+        let unknown_ids = |errors: Vec<TypeCheckError>| {
+            errors
+                .into_iter()
+                .map(|error| match error {
+                    TypeCheckError::UnknownError {
+                        place: _,
+                        id,
+                        options: _,
+                    } => id,
+                    error => panic!("Expected unknown variable, got {:?}.", error),
+                })
+                .collect_vec()
+        };
+        let error = TypeChecker::type_check(program.clone()).unwrap_err();
+        assert_eq!(unknown_ids(vec![error]), vec![Id::from("a")]);
+        let errors = TypeChecker::type_check_all(program).unwrap_err();
+        assert_eq!(unknown_ids(errors), vec![Id::from("a"), Id::from("b")]);
+    }
+
+    #[test]
+    fn test_ambiguous_type_parameters() {
+        let type_checker = TypeChecker::check_type_definitions(Vec::new()).unwrap();
+        let result = DEFAULT_CONTEXT.with(|context| {
+            type_checker.check_expression(
+                FunctionCall {
+                    function: Box::new(Var("empty").into()),
+                    arguments: Vec::new(),
+                }
+                .into(),
+                context,
+                &GenericVariables::new(),
+            )
+        });
+        assert!(matches!(
+            result,
+            Err(TypeCheckError::AmbiguousTypeParameters { id, type_: _ }) if id == "empty"
+        ));
+    }
+
+    #[test]
+    fn test_deny_unused_bindings_program() {
+        let options = TypeCheckOptions {
+            main_args: None,
+            deny_unused_bindings: true,
+            shadowing: Shadowing::default(),
+            built_in_shadowing: BuiltInShadowing::default(),
+            int_width: IntWidth::default(),
+        };
+        assert!(TypeChecker::type_check_with_options(main_program(Vec::new()), options).is_ok());
+    }
+
+    #[test_case(
+        Vec::new(),
+        Vec::new(),
+        Ok(());
+        "no arguments"
+    )]
+    #[test_case(
+        vec![ATOMIC_TYPE_INT.into(), ATOMIC_TYPE_INT.into()],
+        vec![3, -4],
+        Ok(());
+        "integer arguments"
+    )]
+    #[test_case(
+        vec![ATOMIC_TYPE_INT.into()],
+        Vec::new(),
+        Err(TypeCheckError::MainArgumentMismatch {
+            parameters: vec![TYPE_INT],
+            arguments: Vec::new()
+        });
+        "too few arguments"
     )]
     #[test_case(
-        Program{
-            definitions: vec![
-                Assignment{
-                    assignee: ParametricAssignee {
-                        assignee: Assignee {
-                            id: Id::from("main")
-                        },
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![],
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(
-                            Integer{value: 1}.into(),
-                        )
-                    }.into())
-                }.into()
-            ]
-        },
-        Err(()),
-        TypeContext::new();
-        "parametric main"
+        Vec::new(),
+        vec![1],
+        Err(TypeCheckError::MainArgumentMismatch {
+            parameters: Vec::new(),
+            arguments: vec![1]
+        });
+        "too many arguments"
     )]
     #[test_case(
-        Program{
-            definitions: vec![
-                Assignment{
-                    assignee: VariableAssignee("main"),
-                    expression: Box::new(FunctionDefinition{
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(FunctionCall{
-                            function: Box::new(GenericVariable{
-                                id: Id::from("identity"),
-                                type_instances: vec![ATOMIC_TYPE_INT.into()]
-                            }.into()),
-                            arguments: vec![
-                                Integer{ value: 11 }.into()
-                            ]
-                        }.into())
-                    }.into())
-                }.into(),
-                Assignment{
-                    assignee: ParametricAssignee{
-                        assignee: Assignee { id: Id::from("identity") },
-                        generic_variables: vec![Id::from("T")]
-                    },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Assignee {
-                                    id: Id::from("x")
-                                },
-                                type_: Typename("T").into()
-                            }
-                        ],
-                        return_type: Typename("T").into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
-                }.into()
-            ]
-        },
-        Ok(()),
-        TypeContext::new();
-        "function type instantiation"
+        vec![ATOMIC_TYPE_BOOL.into()],
+        vec![1],
+        Err(TypeCheckError::MainArgumentMismatch {
+            parameters: vec![TYPE_BOOL],
+            arguments: vec![1]
+        });
+        "boolean parameter"
+    )]
+    fn test_main_args(
+        parameters: Vec<TypeInstance>,
+        main_args: Vec<i64>,
+        expected: Result<(), TypeCheckError>,
+    ) {
+        let result =
+            TypeChecker::type_check_with_main_args(main_program(parameters), main_args.clone());
+        match (result, expected) {
+            (Ok(program), Ok(())) => {
+                assert!(program.main.parameters.is_empty());
+                let TypedExpression::TypedFunctionCall(TypedFunctionCall {
+                    function: _,
+                    arguments,
+                }) = *program.main.body.expression
+                else {
+                    panic!("Main body is not a function call.")
+                };
+                assert_eq!(
+                    arguments,
+                    main_args
+                        .into_iter()
+                        .map(|value| Integer { value }.into())
+                        .collect_vec()
+                );
+            }
+            (result, expected) => assert_eq!(result.map(|_| ()), expected),
+        }
+    }
+
+    #[test_case(ATOMIC_TYPE_INT.into(), true; "integer")]
+    #[test_case(ATOMIC_TYPE_STRING.into(), true; "string")]
+    #[test_case(
+        TupleType{types: vec![ATOMIC_TYPE_BOOL.into(), Typename("int_list").into()]}.into(),
+        true;
+        "tuple with recursive type"
     )]
     #[test_case(
-        Program{
+        FunctionType{
+            argument_types: vec![ATOMIC_TYPE_INT.into()],
+            return_type: Box::new(ATOMIC_TYPE_INT.into())
+        }.into(),
+        false;
+        "function"
+    )]
+    #[test_case(
+        TupleType{types: vec![
+            ATOMIC_TYPE_INT.into(),
+            FunctionType{
+                argument_types: Vec::new(),
+                return_type: Box::new(ATOMIC_TYPE_INT.into())
+            }.into()
+        ]}.into(),
+        false;
+        "tuple with function"
+    )]
+    #[test_case(
+        GenericType{id: Id::from("array"), type_variables: vec![ATOMIC_TYPE_INT.into()]}.into(),
+        false;
+        "array"
+    )]
+    fn test_eq_comparability(type_: TypeInstance, comparable: bool) {
+        let program = Program {
             definitions: vec![
-                Assignment{
-                    assignee: VariableAssignee("main"),
-                    expression: Box::new(FunctionDefinition{
-                        parameters: Vec::new(),
-                        return_type: FunctionType{
-                            argument_types: vec![ATOMIC_TYPE_INT.into()],
-                            return_type: Box::new(ATOMIC_TYPE_INT.into()),
-                        }.into(),
-                        body: ExpressionBlock(GenericVariable{
-                            id: Id::from("identity"),
-                            type_instances: vec![ATOMIC_TYPE_INT.into()]
-                        }.into())
-                    }.into())
-                }.into(),
-                Assignment{
-                    assignee: ParametricAssignee{
-                        assignee: Assignee { id: Id::from("identity") },
-                        generic_variables: vec![Id::from("T")]
+                UnionTypeDefinition {
+                    variable: GenericTypeVariable {
+                        id: Id::from("int_list"),
+                        generic_variables: Vec::new(),
                     },
-                    expression: Box::new(FunctionDefinition{
-                        parameters: vec![
-                            TypedAssignee {
-                                assignee: Assignee {
-                                    id: Id::from("x")
+                    items: vec![
+                        TypeItem {
+                            id: Id::from("Cons"),
+                            types: vec![ATOMIC_TYPE_INT.into(), Typename("int_list").into()],
+                        },
+                        TypeItem {
+                            id: Id::from("Nil"),
+                            types: Vec::new(),
+                        },
+                    ],
+                    doc: None,
+                }
+                .into(),
+                Assignment {
+                    assignee: VariableAssignee("equal"),
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: vec![
+                                TypedAssignee {
+                                    assignee: Assignee { id: Id::from("x") },
+                                    type_: type_.clone(),
                                 },
-                                type_: Typename("T").into()
-                            }
-                        ],
-                        return_type: Typename("T").into(),
-                        body: ExpressionBlock(Var("x").into())
-                    }.into())
-                }.into()
-            ]
-        },
-        Err(()),
-        TypeContext::new();
-        "returning function from main"
-    )]
-    fn test_program(program: Program, result: Result<(), ()>, context: TypeContext) {
-        let type_check_result = TypeChecker::check_program(program, &context);
-        match (type_check_result.clone(), result) {
-            (Ok(program), Err(())) => {
-                dbg!(program);
-                assert!(type_check_result.is_err())
-            }
-            (Err(msg), Ok(())) => {
-                dbg!(msg);
-                assert!(type_check_result.is_ok())
-            }
-            _ => (),
+                                TypedAssignee {
+                                    assignee: Assignee { id: Id::from("y") },
+                                    type_: type_.clone(),
+                                },
+                            ],
+                            return_type: ATOMIC_TYPE_BOOL.into(),
+                            body: ExpressionBlock(
+                                FunctionCall {
+                                    function: Box::new(
+                                        GenericVariable {
+                                            id: Id::from("eq"),
+                                            type_instances: vec![type_],
+                                        }
+                                        .into(),
+                                    ),
+                                    arguments: vec![Var("x").into(), Var("y").into()],
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+                Assignment {
+                    assignee: VariableAssignee("main"),
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: Vec::new(),
+                            return_type: ATOMIC_TYPE_INT.into(),
+                            body: ExpressionBlock(Integer { value: 0 }.into()),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+            ],
+        };
+        let result = TypeChecker::type_check(program);
+        if comparable {
+            assert!(result.is_ok(), "{:?}", result);
+        } else {
+            assert!(matches!(
+                result,
+                Err(TypeCheckError::NonComparableType { type_: _ })
+            ));
         }
     }
 
     #[test_case(
-        Program{
-            definitions: vec![
-                Assignment{
-                    assignee: VariableAssignee("main"),
-                    expression: Box::new(FunctionDefinition{
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_INT.into(),
-                        body: ExpressionBlock(FunctionCall{
-                            function: Box::new(GenericVariable{
-                                id: Id::from("+"),
-                                type_instances: Vec::new()
-                            }.into()),
-                            arguments: vec![
-                                Integer{ value: -1 }.into(),
-                                Integer{ value: 2 }.into(),
-                            ]
-                        }.into())
-                    }.into())
-                }.into(),
-            ]
-        },
-        Ok(());
-        "builtin-in operator usage"
+        BTreeMap::from([(Id::from("T"), vec![TypeClass::Comparable])]),
+        ATOMIC_TYPE_INT.into(),
+        |result| result.is_ok();
+        "comparable instantiation"
     )]
     #[test_case(
-        Program{
+        BTreeMap::from([(Id::from("T"), vec![TypeClass::Comparable])]),
+        FunctionType{
+            argument_types: vec![ATOMIC_TYPE_INT.into()],
+            return_type: Box::new(ATOMIC_TYPE_INT.into())
+        }.into(),
+        |result| matches!(
+            result,
+            Err(TypeCheckError::NonComparableType { type_: Type::TypeFn(_) })
+        );
+        "function instantiation"
+    )]
+    #[test_case(
+        BTreeMap::new(),
+        ATOMIC_TYPE_INT.into(),
+        |result| matches!(
+            result,
+            Err(TypeCheckError::NonComparableType { type_: Type::TypeVariable(_) })
+        );
+        "unbounded definition"
+    )]
+    #[test_case(
+        BTreeMap::from([(Id::from("U"), vec![TypeClass::Comparable])]),
+        ATOMIC_TYPE_INT.into(),
+        |result| matches!(
+            result,
+            Err(TypeCheckError::UnknownError { place: _, id, options: _ }) if id == "U"
+        );
+        "unknown generic variable"
+    )]
+    fn test_type_parameter_bounds(
+        bounds: BTreeMap<Id, Vec<TypeClass>>,
+        instance: TypeInstance,
+        check: fn(&Result<TypedProgram, TypeCheckError>) -> bool,
+    ) {
+        // `same<T> = (x: T, y: T) -> bool { eq<T>(x, y) }` and `check = (x: instance) -> bool { same<instance>(x, x) }`
+        let program = Program {
             definitions: vec![
-                Assignment{
+                Assignment {
+                    assignee: ParametricAssignee {
+                        assignee: Id::from("same").into(),
+                        generic_variables: vec![Id::from("T")],
+                        bounds,
+                    },
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: vec![
+                                TypedAssignee {
+                                    assignee: Assignee { id: Id::from("x") },
+                                    type_: Typename("T").into(),
+                                },
+                                TypedAssignee {
+                                    assignee: Assignee { id: Id::from("y") },
+                                    type_: Typename("T").into(),
+                                },
+                            ],
+                            return_type: ATOMIC_TYPE_BOOL.into(),
+                            body: ExpressionBlock(
+                                FunctionCall {
+                                    function: Box::new(
+                                        GenericVariable {
+                                            id: Id::from("eq"),
+                                            type_instances: vec![Typename("T").into()],
+                                        }
+                                        .into(),
+                                    ),
+                                    arguments: vec![Var("x").into(), Var("y").into()],
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+                Assignment {
+                    assignee: VariableAssignee("check"),
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: vec![TypedAssignee {
+                                assignee: Assignee { id: Id::from("x") },
+                                type_: instance.clone(),
+                            }],
+                            return_type: ATOMIC_TYPE_BOOL.into(),
+                            body: ExpressionBlock(
+                                FunctionCall {
+                                    function: Box::new(
+                                        GenericVariable {
+                                            id: Id::from("same"),
+                                            type_instances: vec![instance],
+                                        }
+                                        .into(),
+                                    ),
+                                    arguments: vec![Var("x").into(), Var("x").into()],
+                                }
+                                .into(),
+                            ),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+                Assignment {
                     assignee: VariableAssignee("main"),
-                    expression: Box::new(FunctionDefinition{
-                        parameters: Vec::new(),
-                        return_type: ATOMIC_TYPE_BOOL.into(),
-                        body: ExpressionBlock(FunctionCall{
-                            function: Box::new(GenericVariable{
-                                id: Id::from("&&"),
-                                type_instances: Vec::new()
-                            }.into()),
-                            arguments: vec![
-                                Boolean{ value: false }.into(),
-                                Boolean{ value: true }.into(),
-                            ]
-                        }.into())
-                    }.into())
-                }.into(),
-            ]
-        },
-        Ok(());
-        "pre-defined operator usage"
-    )]
-    fn test_default_program(program: Program, result: Result<(), ()>) {
-        let type_check_result = TypeChecker::type_check(program);
-        match (type_check_result.clone(), result) {
-            (Ok(program), Err(())) => {
-                dbg!(program);
-                assert!(type_check_result.is_err())
-            }
-            (Err(msg), Ok(())) => {
-                dbg!(msg);
-                assert!(type_check_result.is_ok())
-            }
-            _ => (),
-        }
+                    expression: Box::new(
+                        FunctionDefinition {
+                            parameters: Vec::new(),
+                            return_type: ATOMIC_TYPE_INT.into(),
+                            body: ExpressionBlock(Integer { value: 0 }.into()),
+                        }
+                        .into(),
+                    ),
+                    doc: None,
+                }
+                .into(),
+            ],
+        };
+        let result = TypeChecker::type_check(program);
+        assert!(check(&result), "{:?}", result);
     }
 }
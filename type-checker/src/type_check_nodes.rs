@@ -1,18 +1,26 @@
-use crate::{Assignee, AtomicTypeEnum, Boolean, Id, Integer, MatchBlock, TypeInstance};
+use crate::serialization::{
+    serialize_named_type_variables, serialize_type_reference, serialize_type_variable,
+    serialize_type_variables,
+};
+use crate::{
+    Assignee, AtomicTypeEnum, Boolean, Char, Id, IntWidth, Integer, MatchBlock, Str, TypeInstance,
+};
 use from_variants::FromVariants;
 use itertools::Itertools;
-use std::cell::RefCell;
+use serde::Serialize;
+use std::cell::{Ref, RefCell};
 use std::collections::hash_map::{IntoIter, Keys, Values};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{self, Debug};
 use std::hash::Hash;
 use std::ops::Index;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[derive(PartialEq, Clone, Debug, Eq)]
+#[derive(PartialEq, Clone, Debug, Eq, Serialize)]
 pub struct ParametricType {
     pub type_: Type,
+    #[serde(serialize_with = "serialize_type_variables")]
     pub parameters: Vec<Rc<RefCell<Option<Type>>>>,
 }
 
@@ -33,8 +41,10 @@ impl ParametricType {
         }
     }
     pub fn instantiate(&self, type_variables: &Vec<Type>) -> Type {
+        // Resolve inferred types before the parameters are bound.
+        let type_variables = Type::instantiate_types(type_variables);
         for (parameter, variable) in self.parameters.iter().zip_eq(type_variables) {
-            *parameter.borrow_mut() = Some(variable.clone());
+            *parameter.borrow_mut() = Some(variable);
         }
         let type_ = self.type_.instantiate();
         for parameter in &self.parameters {
@@ -42,11 +52,41 @@ impl ParametricType {
         }
         type_
     }
+    /// Copy this type and every type that it references into a new graph, preserving cycles.
+    pub fn deep_clone(&self) -> Rc<RefCell<ParametricType>> {
+        self.deep_clone_with(&mut Copies::default())
+    }
+    fn deep_clone_with(&self, copies: &mut Copies) -> Rc<RefCell<ParametricType>> {
+        // References point at the value inside the `RefCell`, which is `self`.
+        let original = self as *const ParametricType as *mut ParametricType;
+        if let Some(copy) = copies.types.get(&original) {
+            return copy.clone();
+        }
+        // Register the copy before filling it in so that self-references find it.
+        let copy = Rc::new(RefCell::new(ParametricType::new()));
+        copies.types.insert(original, copy.clone());
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|parameter| Type::deep_clone_variable(parameter, copies))
+            .collect();
+        let type_ = self.type_.deep_clone_with(copies);
+        *copy.borrow_mut() = ParametricType { type_, parameters };
+        copy
+    }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// Copies made during a deep clone, keyed by the original pointers.
+#[derive(Default)]
+struct Copies {
+    types: HashMap<*mut ParametricType, Rc<RefCell<ParametricType>>>,
+    variables: HashMap<*mut Option<Type>, Rc<RefCell<Option<Type>>>>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedParametricVariable {
     pub variable: Variable,
+    #[serde(serialize_with = "serialize_type_reference")]
     pub type_: Rc<RefCell<ParametricType>>,
 }
 
@@ -80,7 +120,7 @@ impl From<TypedVariable> for TypedParametricVariable {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedVariable {
     pub variable: Variable,
     pub type_: ParametricType,
@@ -132,13 +172,15 @@ macro_rules! strict_partial_eq {
     };
 }
 
-#[derive(Clone, Eq, Hash, FromVariants)]
+#[derive(Clone, Eq, Hash, FromVariants, Serialize)]
 // Types are prefixed with "Type" to differentiate them from types in other modules.
 pub enum Type {
     TypeAtomic(TypeAtomic),
     TypeUnion(TypeUnion),
     TypeInstantiation(TypeInstantiation),
     TypeTuple(TypeTuple),
+    TypeRecord(TypeRecord),
+    TypeArray(TypeArray),
     TypeFn(TypeFn),
     TypeVariable(TypeVariable),
 }
@@ -164,6 +206,14 @@ impl Type {
         match self {
             Self::TypeAtomic(_) => self.clone(),
             Self::TypeTuple(TypeTuple(types)) => TypeTuple(Type::instantiate_types(types)).into(),
+            Self::TypeRecord(TypeRecord(fields)) => TypeRecord(
+                fields
+                    .iter()
+                    .map(|(id, type_)| (id.clone(), type_.instantiate()))
+                    .collect(),
+            )
+            .into(),
+            Self::TypeArray(TypeArray(type_)) => TypeArray(Box::new(type_.instantiate())).into(),
             Self::TypeUnion(TypeUnion {
                 id,
                 variants: types,
@@ -191,6 +241,231 @@ impl Type {
             Self::TypeVariable(TypeVariable(v)) => v.borrow().clone().unwrap_or(self.clone()),
         }
     }
+    fn deep_clone_variable(
+        variable: &Rc<RefCell<Option<Type>>>,
+        copies: &mut Copies,
+    ) -> Rc<RefCell<Option<Type>>> {
+        if let Some(copy) = copies.variables.get(&variable.as_ptr()) {
+            return copy.clone();
+        }
+        let copy = Rc::new(RefCell::new(None));
+        copies.variables.insert(variable.as_ptr(), copy.clone());
+        let type_ = variable
+            .borrow()
+            .as_ref()
+            .map(|type_| type_.deep_clone_with(copies));
+        *copy.borrow_mut() = type_;
+        copy
+    }
+    fn deep_clone_with(&self, copies: &mut Copies) -> Type {
+        let clone_all = |types: &Vec<Type>, copies: &mut Copies| {
+            types
+                .iter()
+                .map(|type_| type_.deep_clone_with(copies))
+                .collect_vec()
+        };
+        match self {
+            Self::TypeAtomic(_) => self.clone(),
+            Self::TypeUnion(TypeUnion { id, variants }) => TypeUnion {
+                id: id.clone(),
+                variants: variants
+                    .iter()
+                    .map(|type_| type_.as_ref().map(|type_| type_.deep_clone_with(copies)))
+                    .collect(),
+            }
+            .into(),
+            Self::TypeInstantiation(TypeInstantiation {
+                reference,
+                instances,
+            }) => TypeInstantiation {
+                reference: reference.borrow().deep_clone_with(copies),
+                instances: clone_all(instances, copies),
+            }
+            .into(),
+            Self::TypeTuple(TypeTuple(types)) => TypeTuple(clone_all(types, copies)).into(),
+            Self::TypeRecord(TypeRecord(fields)) => TypeRecord(
+                fields
+                    .iter()
+                    .map(|(id, type_)| (id.clone(), type_.deep_clone_with(copies)))
+                    .collect(),
+            )
+            .into(),
+            Self::TypeArray(TypeArray(type_)) => {
+                TypeArray(Box::new(type_.deep_clone_with(copies))).into()
+            }
+            Self::TypeFn(TypeFn(argument_types, return_type)) => TypeFn(
+                clone_all(argument_types, copies),
+                Box::new(return_type.deep_clone_with(copies)),
+            )
+            .into(),
+            Self::TypeVariable(TypeVariable(variable)) => {
+                TypeVariable(Self::deep_clone_variable(variable, copies)).into()
+            }
+        }
+    }
+    /// Expand transparent type aliases until only opaque, union, atomic and structural types remain.
+    pub fn resolve_aliases(&self) -> Type {
+        self.resolve_aliases_with(&mut Vec::new())
+    }
+    fn resolve_aliases_with(&self, aliases: &mut Vec<*mut ParametricType>) -> Type {
+        let resolve_all = |types: &Vec<Type>, aliases: &mut Vec<*mut ParametricType>| {
+            types
+                .iter()
+                .map(|type_| type_.resolve_aliases_with(aliases))
+                .collect_vec()
+        };
+        match self {
+            Self::TypeAtomic(_) => self.clone(),
+            Self::TypeUnion(TypeUnion { id, variants }) => TypeUnion {
+                id: id.clone(),
+                variants: variants
+                    .iter()
+                    .map(|type_| {
+                        type_
+                            .as_ref()
+                            .map(|type_| type_.resolve_aliases_with(aliases))
+                    })
+                    .collect(),
+            }
+            .into(),
+            Self::TypeInstantiation(TypeInstantiation {
+                reference,
+                instances,
+            }) => {
+                let instances = resolve_all(instances, aliases);
+                let parametric_type = reference.borrow().clone();
+                let is_alias = !matches!(parametric_type.type_, Type::TypeUnion(_));
+                // Stop at recursive aliases instead of expanding them forever.
+                if !is_alias
+                    || aliases.contains(&reference.as_ptr())
+                    || parametric_type.parameters.len() != instances.len()
+                {
+                    return TypeInstantiation {
+                        reference: reference.clone(),
+                        instances,
+                    }
+                    .into();
+                }
+                // Restore the parameters afterwards in case they are being instantiated elsewhere.
+                let previous = parametric_type
+                    .parameters
+                    .iter()
+                    .map(|parameter| parameter.borrow().clone())
+                    .collect_vec();
+                let type_ = parametric_type.instantiate(&instances);
+                for (parameter, value) in parametric_type.parameters.iter().zip_eq(previous) {
+                    *parameter.borrow_mut() = value;
+                }
+                aliases.push(reference.as_ptr());
+                let type_ = type_.resolve_aliases_with(aliases);
+                aliases.pop();
+                type_
+            }
+            Self::TypeTuple(TypeTuple(types)) => TypeTuple(resolve_all(types, aliases)).into(),
+            Self::TypeRecord(TypeRecord(fields)) => TypeRecord(
+                fields
+                    .iter()
+                    .map(|(id, type_)| (id.clone(), type_.resolve_aliases_with(aliases)))
+                    .collect(),
+            )
+            .into(),
+            Self::TypeArray(TypeArray(type_)) => {
+                TypeArray(Box::new(type_.resolve_aliases_with(aliases))).into()
+            }
+            Self::TypeFn(TypeFn(argument_types, return_type)) => TypeFn(
+                resolve_all(argument_types, aliases),
+                Box::new(return_type.resolve_aliases_with(aliases)),
+            )
+            .into(),
+            Self::TypeVariable(TypeVariable(variable)) => match &*variable.borrow() {
+                Some(type_) => type_.resolve_aliases_with(aliases),
+                None => self.clone(),
+            },
+        }
+    }
+    /// Determine whether values of this type can be compared structurally.
+    pub fn is_comparable(&self) -> bool {
+        self.is_comparable_assuming(&|_| false)
+    }
+    /// Determine whether values of this type can be compared structurally, given which unbound type variables are known to be comparable.
+    pub fn is_comparable_assuming(
+        &self,
+        comparable: &impl Fn(&Rc<RefCell<Option<Type>>>) -> bool,
+    ) -> bool {
+        self.is_comparable_with(comparable, &mut Vec::new())
+    }
+    fn is_comparable_with(
+        &self,
+        comparable: &impl Fn(&Rc<RefCell<Option<Type>>>) -> bool,
+        visited: &mut Vec<*mut ParametricType>,
+    ) -> bool {
+        match self {
+            Self::TypeAtomic(_) => true,
+            Self::TypeUnion(TypeUnion { id: _, variants }) => variants
+                .iter()
+                .flatten()
+                .all(|type_| type_.is_comparable_with(comparable, visited)),
+            Self::TypeInstantiation(TypeInstantiation {
+                reference,
+                instances,
+            }) => {
+                // Recursive types are comparable if the rest of the type is.
+                if visited.contains(&reference.as_ptr()) {
+                    return true;
+                }
+                let type_ = reference.borrow().instantiate(instances);
+                visited.push(reference.as_ptr());
+                let is_comparable = type_.is_comparable_with(comparable, visited);
+                visited.pop();
+                is_comparable
+            }
+            Self::TypeTuple(TypeTuple(types)) => types
+                .iter()
+                .all(|type_| type_.is_comparable_with(comparable, visited)),
+            Self::TypeRecord(TypeRecord(fields)) => fields
+                .values()
+                .all(|type_| type_.is_comparable_with(comparable, visited)),
+            // Arrays are compared by the program rather than structurally.
+            Self::TypeArray(_) | Self::TypeFn(_) => false,
+            // Unknown types may be instantiated with fns unless they are bounded.
+            Self::TypeVariable(TypeVariable(variable)) => match &*variable.borrow() {
+                Some(type_) => type_.is_comparable_with(comparable, visited),
+                None => comparable(variable),
+            },
+        }
+    }
+    /// Determine whether a type contains an unbound type variable that satisfies a predicate.
+    pub fn has_unbound_variable(
+        &self,
+        predicate: &impl Fn(&Rc<RefCell<Option<Type>>>) -> bool,
+    ) -> bool {
+        let any = |types: Vec<&Type>| {
+            types
+                .into_iter()
+                .any(|type_| type_.has_unbound_variable(predicate))
+        };
+        match self {
+            Self::TypeAtomic(_) => false,
+            Self::TypeUnion(TypeUnion { id: _, variants }) => {
+                any(variants.iter().flatten().collect())
+            }
+            Self::TypeInstantiation(TypeInstantiation {
+                reference: _,
+                instances: types,
+            })
+            | Self::TypeTuple(TypeTuple(types)) => any(types.iter().collect()),
+            Self::TypeRecord(TypeRecord(fields)) => any(fields.values().collect()),
+            Self::TypeArray(TypeArray(type_)) => type_.has_unbound_variable(predicate),
+            Self::TypeFn(TypeFn(argument_types, return_type)) => any(argument_types
+                .iter()
+                .chain(std::iter::once(&**return_type))
+                .collect()),
+            Self::TypeVariable(TypeVariable(variable)) => match &*variable.borrow() {
+                Some(type_) => type_.has_unbound_variable(predicate),
+                None => predicate(variable),
+            },
+        }
+    }
     pub fn types_equality(
         t1: &Vec<Self>,
         t2: &Vec<Self>,
@@ -288,6 +563,17 @@ impl Type {
             (Self::TypeTuple(TypeTuple(t1)), Self::TypeTuple(TypeTuple(t2))) => {
                 Type::types_equality(t1, t2, equal_references)
             }
+            // Fields are stored by name, so their order in the source does not matter.
+            (Self::TypeRecord(TypeRecord(f1)), Self::TypeRecord(TypeRecord(f2))) => {
+                f1.keys().eq(f2.keys())
+                    && f1
+                        .values()
+                        .zip_eq(f2.values())
+                        .all(|(t1, t2)| Type::type_equality(t1, t2, equal_references))
+            }
+            (Self::TypeArray(TypeArray(t1)), Self::TypeArray(TypeArray(t2))) => {
+                Type::type_equality(t1, t2, equal_references)
+            }
             (Self::TypeFn(TypeFn(a1, r1)), Self::TypeFn(TypeFn(a2, r2))) => {
                 Type::types_equality(a1, a2, equal_references)
                     && Type::type_equality(r1, r2, equal_references)
@@ -310,6 +596,12 @@ impl Type {
             (Type::TypeTuple(t1), Type::TypeTuple(t2)) => {
                 TypeTuple::strict_equality(t1, t2, visited)
             }
+            (Type::TypeRecord(r1), Type::TypeRecord(r2)) => {
+                TypeRecord::strict_equality(r1, r2, visited)
+            }
+            (Type::TypeArray(a1), Type::TypeArray(a2)) => {
+                TypeArray::strict_equality(a1, a2, visited)
+            }
             (Type::TypeFn(f1), Type::TypeFn(f2)) => TypeFn::strict_equality(f1, f2, visited),
             (Type::TypeVariable(v1), Type::TypeVariable(v2)) => {
                 TypeVariable::strict_equality(v1, v2, visited)
@@ -390,7 +682,7 @@ impl Type {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
 pub struct TypeAtomic(pub AtomicTypeEnum);
 
 impl TypeAtomic {
@@ -399,7 +691,7 @@ impl TypeAtomic {
     }
 }
 
-#[derive(Clone, Eq, Hash)]
+#[derive(Clone, Eq, Hash, Serialize)]
 pub struct TypeUnion {
     pub id: Id,
     pub variants: Vec<Option<Type>>,
@@ -408,14 +700,19 @@ pub struct TypeUnion {
 strict_partial_eq!(TypeUnion);
 
 impl TypeUnion {
+    /// Index and payload type of each constructor, in the order they are defined.
+    pub fn constructors(&self) -> Vec<(usize, Option<Type>)> {
+        self.variants.iter().cloned().enumerate().collect()
+    }
     fn strict_equality(&self, other: &Self, visited: Visited) -> bool {
         self.id == other.id
             && Type::strict_equalities_option(&self.variants, &other.variants, visited)
     }
 }
 
-#[derive(Clone, Eq)]
+#[derive(Clone, Eq, Serialize)]
 pub struct TypeInstantiation {
+    #[serde(serialize_with = "serialize_type_reference")]
     pub reference: Rc<RefCell<ParametricType>>,
     pub instances: Vec<Type>,
 }
@@ -435,7 +732,7 @@ impl Hash for TypeInstantiation {
     }
 }
 
-#[derive(Clone, Eq, Hash)]
+#[derive(Clone, Eq, Hash, Serialize)]
 pub struct TypeTuple(pub Vec<Type>);
 
 strict_partial_eq!(TypeTuple);
@@ -446,7 +743,40 @@ impl TypeTuple {
     }
 }
 
-#[derive(Clone, Eq, Hash)]
+/// Record type with fields ordered by name.
+#[derive(Clone, Eq, Hash, Serialize)]
+pub struct TypeRecord(pub BTreeMap<Id, Type>);
+
+strict_partial_eq!(TypeRecord);
+
+impl TypeRecord {
+    fn strict_equality(&self, other: &Self, visited: Visited) -> bool {
+        self.0.keys().eq(other.0.keys())
+            && Type::strict_equalities(
+                &self.0.values().cloned().collect(),
+                &other.0.values().cloned().collect(),
+                visited,
+            )
+    }
+    /// Position of a field when the record is stored as a tuple.
+    pub fn index(&self, field: &Id) -> Option<usize> {
+        self.0.keys().position(|id| id == field)
+    }
+}
+
+/// Built-in `array[T]` type.
+#[derive(Clone, Eq, Hash, Serialize)]
+pub struct TypeArray(pub Box<Type>);
+
+strict_partial_eq!(TypeArray);
+
+impl TypeArray {
+    fn strict_equality(&self, other: &Self, visited: Visited) -> bool {
+        Type::strict_equality(&self.0, &other.0, visited)
+    }
+}
+
+#[derive(Clone, Eq, Hash, Serialize)]
 pub struct TypeFn(pub Vec<Type>, pub Box<Type>);
 
 strict_partial_eq!(TypeFn);
@@ -458,8 +788,10 @@ impl TypeFn {
     }
 }
 
-#[derive(Clone, Eq)]
-pub struct TypeVariable(pub Rc<RefCell<Option<Type>>>);
+#[derive(Clone, Eq, Serialize)]
+pub struct TypeVariable(
+    #[serde(serialize_with = "serialize_type_variable")] pub Rc<RefCell<Option<Type>>>,
+);
 
 strict_partial_eq!(TypeVariable);
 
@@ -477,37 +809,73 @@ impl Hash for TypeVariable {
 
 pub const TYPE_INT: Type = Type::TypeAtomic(TypeAtomic(AtomicTypeEnum::INT));
 pub const TYPE_BOOL: Type = Type::TypeAtomic(TypeAtomic(AtomicTypeEnum::BOOL));
+pub const TYPE_STRING: Type = Type::TypeAtomic(TypeAtomic(AtomicTypeEnum::STRING));
+pub const TYPE_CHAR: Type = Type::TypeAtomic(TypeAtomic(AtomicTypeEnum::CHAR));
 pub const TYPE_UNIT: Type = Type::TypeTuple(TypeTuple(Vec::new()));
 
 impl fmt::Debug for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
+        // Show the expanded form of aliases so that errors are readable.
+        write!(f, "{:?}", ResolvedType(&self.resolve_aliases()))
+    }
+}
+
+/// Formatter for types that have already had their aliases resolved.
+struct ResolvedType<'a>(&'a Type);
+impl ResolvedType<'_> {
+    fn all(types: &[Type]) -> Vec<ResolvedType<'_>> {
+        types.iter().map(ResolvedType).collect_vec()
+    }
+}
+impl fmt::Debug for ResolvedType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
             Type::TypeAtomic(TypeAtomic(atomic_type)) => write!(f, "TypeAtomic({:?})", atomic_type),
-            Type::TypeUnion(TypeUnion { id, variants }) => {
-                write!(f, "TypeUnion({:?},{:?})", id, variants.iter().collect_vec())
-            }
+            Type::TypeUnion(TypeUnion { id, variants }) => write!(
+                f,
+                "TypeUnion({:?},{:?})",
+                id,
+                variants
+                    .iter()
+                    .map(|type_| type_.as_ref().map(ResolvedType))
+                    .collect_vec()
+            ),
             Type::TypeInstantiation(TypeInstantiation {
                 reference,
                 instances,
-            }) => {
-                write!(
-                    f,
-                    "TypeInstantiation({:p},{:?})",
-                    Rc::as_ptr(reference),
-                    instances
-                )
+            }) => write!(
+                f,
+                "TypeInstantiation({:p},{:?})",
+                Rc::as_ptr(reference),
+                ResolvedType::all(instances)
+            ),
+            Type::TypeTuple(TypeTuple(types)) => {
+                write!(f, "TypeTuple({:?})", ResolvedType::all(types))
             }
-            Type::TypeTuple(TypeTuple(types)) => write!(f, "TypeTuple({:?})", types),
-            Type::TypeFn(TypeFn(argument_type, return_type)) => {
-                write!(f, "TypeFn({:?},{:?})", argument_type, return_type)
+            Type::TypeRecord(TypeRecord(fields)) => write!(
+                f,
+                "TypeRecord({:?})",
+                fields
+                    .iter()
+                    .map(|(id, type_)| (id, ResolvedType(type_)))
+                    .collect_vec()
+            ),
+            Type::TypeArray(TypeArray(type_)) => {
+                write!(f, "TypeArray({:?})", ResolvedType(type_))
             }
+            Type::TypeFn(TypeFn(argument_type, return_type)) => write!(
+                f,
+                "TypeFn({:?},{:?})",
+                ResolvedType::all(argument_type),
+                ResolvedType(return_type)
+            ),
             Type::TypeVariable(TypeVariable(idx)) => write!(f, "TypeVariable({:?})", idx.as_ptr()),
         }
     }
 }
 
 static VARIABLE_ID: AtomicUsize = AtomicUsize::new(0);
-#[derive(Eq, Clone, PartialEq, Debug, Hash)]
+#[derive(Eq, Clone, PartialEq, Debug, Hash, Serialize)]
 pub struct Variable(usize);
 
 impl Variable {
@@ -516,31 +884,43 @@ impl Variable {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedTuple {
     pub expressions: Vec<TypedExpression>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedAccess {
     pub variable: TypedVariable,
     pub parameters: Vec<Type>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedElementAccess {
     pub expression: Box<TypedExpression>,
     pub index: usize,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// Record literal with fields in the order they are evaluated.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct TypedRecord {
+    pub fields: Vec<(Id, TypedExpression)>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct TypedFieldAccess {
+    pub expression: Box<TypedExpression>,
+    pub field: Id,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedIf {
     pub condition: Box<TypedExpression>,
     pub true_block: TypedBlock,
     pub false_block: TypedBlock,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedMatchItem {
     pub type_idx: usize,
     pub assignee: Option<TypedVariable>,
@@ -558,7 +938,7 @@ impl TypedMatchItem {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedMatchBlock {
     pub matches: Vec<TypedMatchItem>,
     pub block: TypedBlock,
@@ -577,39 +957,43 @@ impl TypedMatchBlock {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedMatch {
     pub subject: Box<TypedExpression>,
     pub blocks: Vec<TypedMatchBlock>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedLambdaDef {
     pub parameters: Vec<TypedVariable>,
     pub return_type: Box<Type>,
     pub body: TypedBlock,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedFunctionCall {
     pub function: Box<TypedExpression>,
     pub arguments: Vec<TypedExpression>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedConstructorCall {
     pub idx: usize,
     pub output_type: Type,
     pub arguments: Vec<TypedExpression>,
 }
 
-#[derive(Debug, PartialEq, Clone, FromVariants)]
+#[derive(Debug, PartialEq, Clone, FromVariants, Serialize)]
 pub enum TypedExpression {
     Integer(Integer),
     Boolean(Boolean),
+    Str(Str),
+    Char(Char),
     TypedTuple(TypedTuple),
     TypedAccess(TypedAccess),
     TypedElementAccess(TypedElementAccess),
+    TypedRecord(TypedRecord),
+    TypedFieldAccess(TypedFieldAccess),
     TypedIf(TypedIf),
     TypedMatch(TypedMatch),
     TypedLambdaDef(TypedLambdaDef),
@@ -622,6 +1006,8 @@ impl TypedExpression {
         let type_ = match self {
             Self::Integer(_) => TYPE_INT,
             Self::Boolean(_) => TYPE_BOOL,
+            Self::Str(_) => TYPE_STRING,
+            Self::Char(_) => TYPE_CHAR,
             Self::TypedTuple(TypedTuple { expressions }) => {
                 TypeTuple(Self::types(expressions)).into()
             }
@@ -636,6 +1022,20 @@ impl TypedExpression {
                     panic!("Type of an element access is no longer a tuple!")
                 }
             }
+            Self::TypedRecord(TypedRecord { fields }) => TypeRecord(
+                fields
+                    .iter()
+                    .map(|(id, expression)| (id.clone(), expression.type_()))
+                    .collect(),
+            )
+            .into(),
+            Self::TypedFieldAccess(TypedFieldAccess { expression, field }) => {
+                if let Type::TypeRecord(TypeRecord(fields)) = expression.type_() {
+                    fields[field].clone()
+                } else {
+                    panic!("Type of a field access is no longer a record!")
+                }
+            }
             Self::TypedIf(TypedIf {
                 condition: _,
                 true_block,
@@ -690,7 +1090,7 @@ impl TypedExpression {
     }
     fn instantiate(&self) -> TypedExpression {
         match &self {
-            Self::Boolean(_) | Self::Integer(_) => self.clone(),
+            Self::Boolean(_) | Self::Integer(_) | Self::Str(_) | Self::Char(_) => self.clone(),
             Self::TypedTuple(TypedTuple { expressions }) => TypedTuple {
                 expressions: (Self::instantiate_expressions(expressions)),
             }
@@ -710,6 +1110,18 @@ impl TypedExpression {
                 }
                 .into()
             }
+            Self::TypedRecord(TypedRecord { fields }) => TypedRecord {
+                fields: fields
+                    .iter()
+                    .map(|(id, expression)| (id.clone(), expression.instantiate()))
+                    .collect(),
+            }
+            .into(),
+            Self::TypedFieldAccess(TypedFieldAccess { expression, field }) => TypedFieldAccess {
+                expression: Box::new(expression.instantiate()),
+                field: field.clone(),
+            }
+            .into(),
             Self::TypedIf(TypedIf {
                 condition,
                 true_block,
@@ -770,6 +1182,8 @@ impl TypedExpression {
         match (e1, e2) {
             (TypedExpression::Integer(i1), TypedExpression::Integer(i2)) => i1 == i2,
             (TypedExpression::Boolean(b1), TypedExpression::Boolean(b2)) => b1 == b2,
+            (TypedExpression::Str(s1), TypedExpression::Str(s2)) => s1 == s2,
+            (TypedExpression::Char(c1), TypedExpression::Char(c2)) => c1 == c2,
             (
                 TypedExpression::TypedTuple(TypedTuple { expressions: e1 }),
                 TypedExpression::TypedTuple(TypedTuple { expressions: e2 }),
@@ -794,6 +1208,26 @@ impl TypedExpression {
                     index: i2,
                 }),
             ) => i1 == i2 && Self::equal(&*e1, &*e2),
+            (
+                TypedExpression::TypedRecord(TypedRecord { fields: f1 }),
+                TypedExpression::TypedRecord(TypedRecord { fields: f2 }),
+            ) => {
+                f1.len() == f2.len()
+                    && f1
+                        .iter()
+                        .zip_eq(f2.iter())
+                        .all(|((i1, e1), (i2, e2))| i1 == i2 && Self::equal(e1, e2))
+            }
+            (
+                TypedExpression::TypedFieldAccess(TypedFieldAccess {
+                    expression: e1,
+                    field: f1,
+                }),
+                TypedExpression::TypedFieldAccess(TypedFieldAccess {
+                    expression: e2,
+                    field: f2,
+                }),
+            ) => f1 == f2 && Self::equal(e1, e2),
             (
                 TypedExpression::TypedIf(TypedIf {
                     condition: c1,
@@ -909,9 +1343,10 @@ impl TypedExpression {
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize)]
 pub struct ParametricExpression {
     pub expression: TypedExpression,
+    #[serde(serialize_with = "serialize_named_type_variables")]
     pub parameters: Vec<(Id, Rc<RefCell<Option<Type>>>)>,
 }
 
@@ -937,7 +1372,7 @@ impl From<TypedExpression> for ParametricExpression {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, FromVariants)]
+#[derive(Debug, PartialEq, Clone, FromVariants, Serialize)]
 pub enum TypedStatement {
     TypedAssignment(TypedAssignment),
     TypedFnDef(TypedFnDef),
@@ -986,7 +1421,7 @@ impl TypedStatement {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedAssignment {
     pub variable: TypedVariable,
     pub expression: ParametricExpression,
@@ -1010,9 +1445,10 @@ impl TypedAssignment {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedFnDef {
     pub variable: TypedVariable,
+    #[serde(serialize_with = "serialize_named_type_variables")]
     pub parameters: Vec<(Id, Rc<RefCell<Option<Type>>>)>,
     pub fn_: TypedLambdaDef,
 }
@@ -1040,7 +1476,7 @@ impl TypedFnDef {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TypedBlock {
     pub statements: Vec<TypedStatement>,
     pub expression: Box<TypedExpression>,
@@ -1062,6 +1498,32 @@ impl TypedBlock {
 pub struct TypedProgram {
     pub type_definitions: TypeDefinitions,
     pub main: TypedLambdaDef,
+    /// Documentation attached to top-level definitions, keyed by name.
+    pub docs: BTreeMap<Id, String>,
+}
+
+impl TypedProgram {
+    /// Borrow the definition of a named type.
+    pub fn type_definition(&self, id: &Id) -> Option<Ref<'_, ParametricType>> {
+        self.type_definitions
+            .get(id)
+            .map(|definition| definition.borrow())
+    }
+    /// Constructors of a named union type, or `None` if there is no such union.
+    pub fn union_constructors(&self, id: &Id) -> Option<Vec<(usize, Option<Type>)>> {
+        match &self.type_definition(id)?.type_ {
+            Type::TypeUnion(type_union) => Some(type_union.constructors()),
+            _ => None,
+        }
+    }
+}
+
+/// Generic variable that a fn parameter was declared as, such as `T` in a call to `apply`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericParameterSite {
+    pub variable: Id,
+    /// Name of the called fn.
+    pub function: Id,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -1084,6 +1546,8 @@ pub enum TypeCheckError {
     FunctionReturnTypeMismatch {
         return_type: Type,
         body: TypedBlock,
+        /// Generic variables in scope that either type refers to.
+        generic_variables: Vec<Id>,
     },
     UnknownError {
         id: Id,
@@ -1094,15 +1558,40 @@ pub enum TypeCheckError {
         name: Id,
         reason: String,
     },
+    /// A binding has the same name as a built-in operator.
+    BuiltInShadow {
+        name: Id,
+    },
     TypeAsParameter {
         type_name: Id,
     },
     RecursiveTypeAlias {
         type_alias: Id,
+        /// Names of the definitions in the cycle, starting from the alias.
+        cycle: Vec<Id>,
     },
     InvalidFunctionCall {
-        expression: TypedExpression,
+        expression: Box<TypedExpression>,
         arguments: Vec<TypedExpression>,
+        arg_index: usize,
+        expected: Box<Type>,
+        found: Box<Type>,
+        /// Generic variable of the called fn that the argument was declared as, if any.
+        generic_parameter: Option<Box<GenericParameterSite>>,
+        /// Suggestion for a likely fix, such as using `eq` to compare tuples.
+        note: Option<String>,
+    },
+    NonFunctionCall {
+        expression: Box<TypedExpression>,
+        arguments: Vec<TypedExpression>,
+    },
+    ArityMismatch {
+        expected: usize,
+        found: usize,
+    },
+    IntegerOutOfRange {
+        value: i64,
+        width: IntWidth,
     },
     InstantiationOfTypeVariable {
         variable: Id,
@@ -1112,9 +1601,17 @@ pub enum TypeCheckError {
         type_: ParametricType,
         type_instances: Vec<TypeInstance>,
     },
+    /// The arguments to a call do not determine its omitted type parameters.
+    AmbiguousTypeParameters {
+        id: Id,
+        type_: ParametricType,
+    },
     InvalidConstructorArguments {
         id: Id,
         input_type: Option<Type>,
+        /// Variant type before the type parameters are substituted.
+        generic_input_type: Option<Box<Type>>,
+        type_instances: Vec<TypeInstance>,
         arguments: Vec<TypedExpression>,
     },
     DifferingMatchBlockTypes(TypedMatchBlock, TypedMatchBlock),
@@ -1122,11 +1619,24 @@ pub enum TypeCheckError {
     IncorrectVariants {
         blocks: Vec<MatchBlock>,
     },
+    NonExhaustiveMatch {
+        type_name: Id,
+        missing: Vec<Id>,
+    },
+    RedundantMatchArm {
+        type_name: Id,
+        variant: Id,
+    },
     MismatchedVariant {
         type_: Type,
         variant_id: Id,
         assignee: Option<Assignee>,
     },
+    /// Variants matched in the same arm bind one name to payloads of different types.
+    IncompatibleMultiMatchBinding {
+        variants: Vec<Id>,
+        types: Vec<Type>,
+    },
     MainAsAssignment {
         block: TypedBlock,
         variable: TypedVariable,
@@ -1134,6 +1644,45 @@ pub enum TypeCheckError {
     IncorrectMainType {
         type_: Type,
     },
+    MissingMain,
+    /// An expression does not have the type that it is annotated with.
+    AnnotationMismatch {
+        expression: Box<TypedExpression>,
+        type_: Type,
+    },
+    MainArgumentMismatch {
+        parameters: Vec<Type>,
+        arguments: Vec<i64>,
+    },
+    UnusedBinding {
+        name: Id,
+    },
+    NonComparableType {
+        type_: Type,
+    },
+    DuplicateField {
+        field: Id,
+    },
+    InvalidFieldAccess {
+        expression: TypedExpression,
+        field: Id,
+    },
+    StrictCycle {
+        variable: Id,
+        dependency: Id,
+    },
+    /// A generic fn calls itself with type arguments built from its own parameters, so it has infinitely many instances.
+    PolymorphicRecursion {
+        name: Id,
+        type_instances: Vec<Type>,
+    },
+}
+
+/// Problem that does not stop a program from type checking.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeCheckWarning {
+    /// A binding has the same name as a built-in operator.
+    BuiltInShadow { name: Id },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -1314,6 +1863,16 @@ impl TypeDefinitions {
                         )
                     })
             }
+            (Type::TypeArray(TypeArray(t1)), Type::TypeArray(TypeArray(t2))) => {
+                TypeDefinitions::type_equality(
+                    self_references_index,
+                    other_references_index,
+                    self_generics_index,
+                    other_generics_index,
+                    t1,
+                    t2,
+                )
+            }
             (Type::TypeFn(TypeFn(a1, r1)), Type::TypeFn(TypeFn(a2, r2))) => {
                 TypeDefinitions::type_equality(
                     self_references_index,
@@ -1461,6 +2020,13 @@ impl fmt::Debug for DebugTypeWrapper {
                         .collect_vec()
                 )
             }
+            Type::TypeArray(TypeArray(type_)) => {
+                write!(
+                    f,
+                    "Array({:?})",
+                    DebugTypeWrapper(*type_, references_index.clone())
+                )
+            }
             Type::TypeFn(TypeFn(argument_types, return_type)) => {
                 write!(
                     f,
@@ -1516,6 +2082,31 @@ impl PartialEq for TypeDefinitions {
 
 pub type TypeContext = HashMap<Id, TypedVariable>;
 
+/// Build type contexts out of other contexts, rejecting names that are already defined.
+pub trait TypeContextExt: Sized {
+    /// Add every variable from `other`, leaving `self` unchanged if any name collides.
+    fn merge(&mut self, other: TypeContext) -> Result<(), TypeCheckError>;
+    /// Add a single variable to the context.
+    fn with(self, id: Id, type_: ParametricType) -> Result<Self, TypeCheckError>;
+}
+
+impl TypeContextExt for TypeContext {
+    fn merge(&mut self, other: TypeContext) -> Result<(), TypeCheckError> {
+        if let Some(name) = other.keys().filter(|id| self.contains_key(*id)).min() {
+            return Err(TypeCheckError::BuiltInOverride {
+                name: name.clone(),
+                reason: String::from("built-in fn"),
+            });
+        }
+        self.extend(other);
+        Ok(())
+    }
+    fn with(mut self, id: Id, type_: ParametricType) -> Result<Self, TypeCheckError> {
+        self.merge(TypeContext::from([(id, type_.into())]))?;
+        Ok(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1918,4 +2509,190 @@ mod tests {
             &expected
         ));
     }
+
+    #[test_case(
+        {
+            let int = Rc::new(RefCell::new(ParametricType::from(TYPE_INT)));
+            let alias = Rc::new(RefCell::new(ParametricType::from(Type::from(TypeInstantiation {
+                reference: int,
+                instances: Vec::new(),
+            }))));
+            (
+                Type::from(TypeInstantiation {
+                    reference: alias,
+                    instances: Vec::new(),
+                }),
+                TYPE_INT,
+            )
+        };
+        "double transparent reference"
+    )]
+    #[test_case(
+        {
+            let parameter = Rc::new(RefCell::new(None));
+            let pair = Rc::new(RefCell::new(ParametricType {
+                type_: TypeTuple(vec![
+                    TypeVariable(parameter.clone()).into(),
+                    TypeVariable(parameter.clone()).into(),
+                ])
+                .into(),
+                parameters: vec![parameter],
+            }));
+            (
+                Type::from(TypeFn(
+                    vec![TypeInstantiation {
+                        reference: pair,
+                        instances: vec![TYPE_BOOL],
+                    }
+                    .into()],
+                    Box::new(TYPE_INT),
+                )),
+                Type::from(TypeFn(
+                    vec![TypeTuple(vec![TYPE_BOOL, TYPE_BOOL]).into()],
+                    Box::new(TYPE_INT),
+                )),
+            )
+        };
+        "generic alias"
+    )]
+    #[test_case(
+        {
+            let union = Type::from(TypeInstantiation {
+                reference: Rc::new(RefCell::new(ParametricType::from(Type::from(TypeUnion {
+                    id: Id::from("Opaque"),
+                    variants: vec![Some(TYPE_INT)],
+                })))),
+                instances: Vec::new(),
+            });
+            (union.clone(), union)
+        };
+        "opaque type"
+    )]
+    #[test_case(
+        {
+            let alias = Rc::new(RefCell::new(ParametricType::new()));
+            let recursive = Type::from(TypeInstantiation {
+                reference: alias.clone(),
+                instances: Vec::new(),
+            });
+            alias.borrow_mut().type_ = TypeTuple(vec![TYPE_INT, recursive.clone()]).into();
+            (recursive.clone(), TypeTuple(vec![TYPE_INT, recursive]).into())
+        };
+        "recursive alias"
+    )]
+    fn test_resolve_aliases(type_expected: (Type, Type)) {
+        let (type_, expected) = type_expected;
+        let resolved = type_.resolve_aliases();
+        assert_eq!(resolved, expected);
+        // Formatting does not follow the recursion indefinitely.
+        let _ = format!("{:?}", resolved);
+    }
+
+    #[test_case(
+        vec![("triple", TYPE_INT)],
+        Ok(vec!["double", "negate", "triple"]);
+        "disjoint"
+    )]
+    #[test_case(
+        vec![("triple", TYPE_INT), ("negate", TYPE_BOOL)],
+        Err(Id::from("negate"));
+        "collision"
+    )]
+    #[test_case(
+        vec![("negate", TYPE_INT), ("double", TYPE_BOOL), ("abs", TYPE_INT)],
+        Err(Id::from("double"));
+        "first collision"
+    )]
+    fn test_merge_type_contexts(extra: Vec<(&str, Type)>, expected: Result<Vec<&str>, Id>) {
+        let mut context = TypeContext::new()
+            .with(Id::from("negate"), TYPE_INT.into())
+            .unwrap();
+        context
+            .merge(TypeContext::from([(Id::from("double"), TYPE_INT.into())]))
+            .unwrap();
+        let original = context.clone();
+        let extra = TypeContext::from_iter(
+            extra
+                .into_iter()
+                .map(|(id, type_)| (Id::from(id), type_.into())),
+        );
+        match (context.merge(extra), expected) {
+            (Ok(()), Ok(names)) => {
+                assert_eq!(
+                    context.into_keys().sorted().collect_vec(),
+                    names.into_iter().map(Id::from).collect_vec()
+                )
+            }
+            (Err(TypeCheckError::BuiltInOverride { name, reason: _ }), Err(expected)) => {
+                assert_eq!(name, expected);
+                assert_eq!(context, original);
+            }
+            (result, _) => panic!("{result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_deep_clone_recursive_type() {
+        // `type List<T> = Cons (T, List<T>) | Nil`
+        let parameter = Rc::new(RefCell::new(None));
+        let list = Rc::new(RefCell::new(ParametricType {
+            parameters: vec![parameter.clone()],
+            type_: Type::new(),
+        }));
+        list.borrow_mut().type_ = TypeUnion {
+            id: Id::from("List"),
+            variants: vec![
+                Some(
+                    TypeTuple(vec![
+                        TypeVariable(parameter.clone()).into(),
+                        TypeInstantiation {
+                            reference: list.clone(),
+                            instances: vec![TypeVariable(parameter.clone()).into()],
+                        }
+                        .into(),
+                    ])
+                    .into(),
+                ),
+                None,
+            ],
+        }
+        .into();
+
+        let clone = list.borrow().deep_clone();
+        assert!(!Rc::ptr_eq(&clone, &list));
+        assert!(Type::equality(
+            &TypeInstantiation {
+                reference: clone.clone(),
+                instances: vec![TYPE_INT],
+            }
+            .into(),
+            &TypeInstantiation {
+                reference: list.clone(),
+                instances: vec![TYPE_INT],
+            }
+            .into(),
+        ));
+        let clone_parameter = clone.borrow().parameters[0].clone();
+        assert!(!Rc::ptr_eq(&clone_parameter, &parameter));
+        let Type::TypeUnion(TypeUnion { id: _, variants }) = &clone.borrow().type_ else {
+            panic!("Cloned type is not a union.")
+        };
+        let Some(Type::TypeTuple(TypeTuple(types))) = &variants[0] else {
+            panic!("Cloned variant is not a tuple.")
+        };
+        assert!(
+            matches!(&types[0], Type::TypeVariable(TypeVariable(variable)) if Rc::ptr_eq(variable, &clone_parameter))
+        );
+        let Type::TypeInstantiation(TypeInstantiation {
+            reference,
+            instances,
+        }) = &types[1]
+        else {
+            panic!("Cloned tail is not an instantiation.")
+        };
+        assert!(Rc::ptr_eq(reference, &clone));
+        assert!(
+            matches!(&instances[0], Type::TypeVariable(TypeVariable(variable)) if Rc::ptr_eq(variable, &clone_parameter))
+        );
+    }
 }